@@ -0,0 +1,84 @@
+use winterfell::{math::FieldElement, Assertion};
+
+use crate::utils::WinterCircomError;
+
+/// Returns assertions in the canonical order, without duplicates.
+///
+/// The canonical order is the one in which Winterfell draws the coefficients
+/// of the boundary constraints, and in which the `Verify` circuit sorts the
+/// assertions of the AIR template: by stride, then first step, then column.
+/// Assertions asserting the same values at the same steps of the same column
+/// are kept once, so that each of them counts once in the
+/// [number of assertions](crate::WinterCircomProofOptions::num_assertions) of
+/// the circuit:
+///
+/// ```ignore
+/// fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+///     canonical_assertions(vec![
+///         Assertion::single(1, last_step, self.result),
+///         Assertion::single(0, 0, self.start),
+///         Assertion::single(1, 0, self.start),
+///         Assertion::single(0, 0, self.start),
+///     ])
+/// }
+/// ```
+///
+/// returns 3 assertions, on column 0 then 1 of the first step, then on column
+/// 1 of the last step. Different assertions of the same column and steps are
+/// kept, and rejected by Winterfell.
+pub fn canonical_assertions<E: FieldElement>(
+    mut assertions: Vec<Assertion<E>>,
+) -> Vec<Assertion<E>> {
+    // equal assertions are made adjacent by ordering them by their values
+    // last, so that they are all removed
+    assertions.sort_by(|a, b| {
+        (a.stride(), a.first_step(), a.column(), a.values().len())
+            .cmp(&(b.stride(), b.first_step(), b.column(), b.values().len()))
+            .then_with(|| E::elements_as_bytes(a.values()).cmp(E::elements_as_bytes(b.values())))
+    });
+    assertions.dedup_by(|a, b| same_steps(a, b) && a.values() == b.values());
+    assertions
+}
+
+/// Check that no two assertions assert a common step of the same column.
+///
+/// Winterfell panics on such assertions while proving, this returns an
+/// [InvalidAssertions](WinterCircomError::InvalidAssertions) error instead.
+pub(crate) fn check_assertions<E: FieldElement>(
+    assertions: &[Assertion<E>],
+) -> Result<(), WinterCircomError> {
+    for (i, assertion) in assertions.iter().enumerate() {
+        if let Some(other) = assertions[i + 1..]
+            .iter()
+            .find(|other| assertion.overlaps_with(other))
+        {
+            let comment = if !same_steps(assertion, other) {
+                "assertions overlap"
+            } else if other.values() == assertion.values() {
+                "assertion is repeated, deduplicate them with canonical_assertions"
+            } else {
+                "assertions differ"
+            };
+            return Err(WinterCircomError::InvalidAssertions {
+                comment: format!(
+                    "column {} is asserted twice from step {} with stride {}, the {}",
+                    assertion.column(),
+                    assertion.first_step(),
+                    assertion.stride(),
+                    comment
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn same_steps<E: FieldElement>(a: &Assertion<E>, b: &Assertion<E>) -> bool {
+    a.column() == b.column()
+        && a.first_step() == b.first_step()
+        && a.stride() == b.stride()
+        && a.values().len() == b.values().len()
+}
@@ -0,0 +1,167 @@
+use std::future::Future;
+
+use serde_json::json;
+
+use crate::{
+    audit::AuditedStep,
+    pipeline::{Pipeline, Stage},
+    utils::{command_execution_async, LoggingLevel, StageTimer, WinterCircomError},
+    workspace::{with_workspace, CircomWorkspace},
+};
+
+impl Stage {
+    /// Run the stage for the given circuit asynchronously, see [run](Stage::run).
+    ///
+    /// The external commands are spawned with `tokio::process`, so that the
+    /// runtime is not blocked while they run, and the returned future can be
+    /// moved to another task, e.g. with `tokio::spawn`. The stage runs in the
+    /// [CircomWorkspace] current when this function is called.
+    ///
+    /// Dropping the future, e.g. by aborting its task, cancels the stage: the
+    /// running command is killed, and the artifacts of the stage are marked
+    /// incomplete until it is run again, as for an interrupted stage.
+    pub fn run_async(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> impl Future<Output = Result<(), WinterCircomError>> + Send + 'static {
+        let stage = *self;
+        let circuit_name = String::from(circuit_name);
+        let workspace = CircomWorkspace::current();
+
+        async move {
+            let audit = in_workspace(&workspace, || {
+                let (action, artifacts) = stage.audit(&circuit_name);
+                AuditedStep::begin(&circuit_name, action, json!({}), &artifacts)
+            })?;
+            let result = run_plan(stage, &circuit_name, &workspace, &logging_level).await;
+            in_workspace(&workspace, || audit.end(result))
+        }
+    }
+}
+
+impl Pipeline {
+    /// Run the stages of the pipeline asynchronously, see [run](Pipeline::run)
+    /// and [Stage::run_async].
+    ///
+    /// The pipelines of different circuits are independent, and can run
+    /// concurrently:
+    ///
+    /// ```ignore
+    /// let (sum, merkle) = tokio::join!(
+    ///     Pipeline::new("sum").witness().prove().run_async(LoggingLevel::Default),
+    ///     Pipeline::new("merkle").witness().prove().run_async(LoggingLevel::Default),
+    /// );
+    /// ```
+    pub fn run_async(
+        &self,
+        logging_level: LoggingLevel,
+    ) -> impl Future<Output = Result<Vec<Stage>, WinterCircomError>> + Send + 'static {
+        let pipeline = self.clone();
+        let workspace = CircomWorkspace::current();
+
+        async move {
+            let mut run = Vec::new();
+            for stage in pipeline.stages() {
+                if in_workspace(&workspace, || pipeline.skips(&stage, &run, &logging_level)) {
+                    continue;
+                }
+                in_workspace(&workspace, || {
+                    stage.run_async(pipeline.circuit_name(), logging_level)
+                })
+                .await?;
+                run.push(stage);
+            }
+            Ok(run)
+        }
+    }
+}
+
+/// Compile the Circom code of a circuit asynchronously, see
+/// [circom_compile](crate::circom_compile).
+pub fn circom_compile_async(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> impl Future<Output = Result<(), WinterCircomError>> + Send + 'static {
+    Stage::Compile.run_async(circuit_name, logging_level)
+}
+
+/// Generate the circuit-specific keys of a circuit asynchronously, see
+/// [groth16_setup](crate::groth16_setup).
+pub fn groth16_setup_async(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> impl Future<Output = Result<(), WinterCircomError>> + Send + 'static {
+    Stage::Setup.run_async(circuit_name, logging_level)
+}
+
+/// Compute the execution witness of a circuit asynchronously, see
+/// [compute_witness](crate::compute_witness).
+pub fn compute_witness_async(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> impl Future<Output = Result<(), WinterCircomError>> + Send + 'static {
+    Stage::Witness.run_async(circuit_name, logging_level)
+}
+
+/// Generate the Groth16 proof of a circuit asynchronously, see
+/// [groth16_prove](crate::groth16_prove).
+pub fn groth16_prove_async(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> impl Future<Output = Result<(), WinterCircomError>> + Send + 'static {
+    Stage::Prove.run_async(circuit_name, logging_level)
+}
+
+/// Verify the Groth16 proof of a circuit asynchronously, see
+/// [groth16_verify](crate::groth16_verify).
+pub fn groth16_verify_async(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> impl Future<Output = Result<(), WinterCircomError>> + Send + 'static {
+    Stage::Verify.run_async(circuit_name, logging_level)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Run a closure in a workspace.
+///
+/// The workspace is thread-local, and the task of a future may be resumed on
+/// another thread after each await point: it is set again around every
+/// synchronous part of the async stages.
+fn in_workspace<T, F: FnOnce() -> T>(workspace: &CircomWorkspace, f: F) -> T {
+    with_workspace(workspace.clone(), f)
+}
+
+/// Run the commands of a stage asynchronously, stopping at the first error,
+/// see [run_stage](crate::pipeline::run_stage).
+async fn run_plan(
+    stage: Stage,
+    circuit_name: &str,
+    workspace: &CircomWorkspace,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let plan = in_workspace(workspace, || stage.plan(circuit_name))?;
+    let timer = StageTimer::start(plan.message, logging_level);
+    let mut result = Ok(());
+    for (executable, args) in plan.commands {
+        result = command_execution_async(
+            executable,
+            args,
+            plan.stdin.clone(),
+            plan.dir.clone(),
+            *logging_level,
+        )
+        .await;
+        if result.is_err() {
+            break;
+        }
+    }
+    in_workspace(workspace, || {
+        stage.complete(circuit_name, result, logging_level)
+    })?;
+    timer.finish(&plan.outputs);
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, create_dir_all, File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f256::BaseElement,
+};
+
+use crate::{
+    interrupt::{begin_step, end_step},
+    telemetry::{Telemetry, TelemetryReport},
+    tenant::validate_circuit_name,
+    utils::{strip_colors, WinterCircomError},
+    workspace::circuit_dir,
+};
+
+/// Pipeline step recorded in the audit log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Compile,
+    Setup,
+    Prove,
+    Witness,
+    Groth16Prove,
+    Verify,
+    RotateKeys,
+    FinalizeKeys,
+}
+
+/// Entry of the audit log of a circuit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Start of the invocation, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub action: AuditAction,
+
+    /// Parameters of the invocation (e.g. proof options).
+    pub parameters: Value,
+
+    /// BLAKE3 hashes of the artifacts read or produced by the invocation, keyed
+    /// by file name. Missing artifacts are not recorded.
+    pub artifacts: BTreeMap<String, String>,
+
+    pub duration_ms: u64,
+
+    /// Error message if the invocation failed.
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    /// Returns whether the invocation succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Returns the path of the audit log of the given circuit.
+fn audit_log_path(circuit_name: &str) -> PathBuf {
+    circuit_dir(circuit_name).join("audit.log")
+}
+
+/// Returns the entries of the audit log of the given circuit, oldest first.
+///
+/// Every invocation of [circom_create](crate::circom_create),
+/// [circom_prove](crate::circom_prove), [circom_verify](crate::circom_verify)
+/// and [rotate_keys](crate::rotate_keys) appends an entry to the
+/// `target/circom/<circuit_name>/audit.log` file, one JSON object per line.
+/// The log is never rewritten by this crate.
+///
+/// Lines that cannot be parsed, e.g. the last line of a process killed while
+/// appending it, are skipped: they record no completed step.
+pub fn history(circuit_name: &str) -> Result<Vec<AuditEntry>, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let data = match fs::read_to_string(audit_log_path(circuit_name)) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("reading audit.log")),
+            })
+        }
+    };
+
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Run a pipeline step and append its outcome to the audit log of the circuit.
+///
+/// The artifacts are hashed once the step is over. If the step succeeded but
+/// the entry could not be written, the I/O error is returned.
+///
+/// The outcome is also reported to the [Telemetry] endpoint, if configured.
+///
+/// The step is interruptible: the artifacts of an interrupted step are marked
+/// incomplete and rejected by the following steps (see
+/// [begin_step](crate::interrupt::begin_step)).
+pub(crate) fn audited<T, F>(
+    circuit_name: &str,
+    action: AuditAction,
+    parameters: Value,
+    artifacts: &[PathBuf],
+    step: F,
+) -> Result<T, WinterCircomError>
+where
+    F: FnOnce() -> Result<T, WinterCircomError>,
+{
+    let audit = AuditedStep::begin(circuit_name, action, parameters, artifacts)?;
+    audit.end(step())
+}
+
+/// Pipeline step started by [begin](AuditedStep::begin), whose outcome is
+/// appended to the audit log by [end](AuditedStep::end).
+///
+/// This is [audited] for steps which cannot run in a closure, e.g. futures. A
+/// step which is never ended keeps its artifacts marked incomplete, as an
+/// interrupted one.
+pub(crate) struct AuditedStep {
+    circuit_name: String,
+    action: AuditAction,
+    parameters: Value,
+    artifacts: Vec<PathBuf>,
+    timestamp: u64,
+    start: Instant,
+}
+
+impl AuditedStep {
+    pub(crate) fn begin(
+        circuit_name: &str,
+        action: AuditAction,
+        parameters: Value,
+        artifacts: &[PathBuf],
+    ) -> Result<Self, WinterCircomError> {
+        // invalid names are rejected before anything is written, including the log
+        validate_circuit_name(circuit_name)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let start = Instant::now();
+
+        begin_step(circuit_name, action, artifacts)?;
+        Ok(Self {
+            circuit_name: String::from(circuit_name),
+            action,
+            parameters,
+            artifacts: artifacts.to_vec(),
+            timestamp,
+            start,
+        })
+    }
+
+    pub(crate) fn end<T>(
+        self,
+        result: Result<T, WinterCircomError>,
+    ) -> Result<T, WinterCircomError> {
+        let circuit_name = &self.circuit_name;
+        let ended = end_step(circuit_name, self.action, &result);
+        let result = result.and_then(|output| ended.map(|_| output));
+
+        let entry = AuditEntry {
+            timestamp: self.timestamp,
+            action: self.action,
+            parameters: self.parameters,
+            artifacts: self
+                .artifacts
+                .iter()
+                .filter_map(|path| {
+                    let hash = hash_file(path).ok()?;
+                    let name = path.file_name()?.to_string_lossy().into_owned();
+                    Some((name, hash))
+                })
+                .collect(),
+            duration_ms: self.start.elapsed().as_millis() as u64,
+            error: result.as_ref().err().map(|e| strip_colors(&e.to_string())),
+        };
+
+        // failed reports are ignored, telemetry must not fail the pipeline
+        if let Some(telemetry) = Telemetry::from_env() {
+            let _ = telemetry.send(&TelemetryReport::of(&entry, &result));
+        }
+
+        let appended = append_entry(circuit_name, &entry);
+        result.and_then(|output| appended.map(|_| output))
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn append_entry(circuit_name: &str, entry: &AuditEntry) -> Result<(), WinterCircomError> {
+    let io_error = |comment: &str| {
+        let comment = Some(String::from(comment));
+        move |e| WinterCircomError::IoError {
+            io_error: e,
+            comment,
+        }
+    };
+
+    create_dir_all(circuit_dir(circuit_name))
+        .map_err(io_error("creating Circom output directory"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(audit_log_path(circuit_name))
+        .map_err(io_error("opening audit.log"))?;
+
+    // a torn last line is terminated, so that only it is skipped by `history`
+    let mut last = [b'\n'];
+    if file
+        .metadata()
+        .map_err(io_error("reading audit.log"))?
+        .len()
+        > 0
+    {
+        file.seek(SeekFrom::End(-1))
+            .and_then(|_| file.read_exact(&mut last))
+            .map_err(io_error("reading audit.log"))?;
+    }
+    let line = serde_json::to_string(entry).expect("audit entry should be serializable");
+    let separator = if last[0] == b'\n' { "" } else { "\n" };
+    writeln!(file, "{}{}", separator, line).map_err(io_error("writing audit.log"))
+}
+
+/// Returns the hexadecimal BLAKE3 hash of an artifact.
+pub(crate) fn hash_artifact(bytes: &[u8]) -> String {
+    hex(&Blake3_256::<BaseElement>::hash(bytes).as_bytes())
+}
+
+/// Returns the hexadecimal BLAKE3 hash of an artifact file, i.e. the
+/// [hash_artifact] of its contents.
+///
+/// The file is streamed through the hasher, so that multi-gigabyte witnesses
+/// and zkeys are hashed without being loaded in memory.
+pub(crate) fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(
+        &mut BufReader::with_capacity(1 << 20, File::open(path)?),
+        &mut hasher,
+    )?;
+    Ok(hex(hasher.finalize().as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
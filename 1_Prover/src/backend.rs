@@ -0,0 +1,113 @@
+use std::env;
+
+use crate::{
+    utils::{Executable, WinterCircomError},
+    SnarkScheme,
+};
+
+/// Prover generating the SNARK proofs of the [Prove](crate::Stage::Prove)
+/// stage, from the witness and the proving key of a circuit.
+///
+/// snarkjs proves the STARK-verifier circuits of large traces in tens of
+/// minutes. [Rapidsnark](ProverBackend::Rapidsnark) reads the same
+/// `verifier.zkey` and `witness.wtns` files and writes the same `proof.json`
+/// and `public.json` files, several times faster, but only generates Groth16
+/// proofs. The other stages always run snarkjs.
+///
+/// ## Configuration
+///
+/// The backend of the current host is read from the
+/// `WINTER_CIRCOM_PROVER_BACKEND` environment variable (`snarkjs` or
+/// `rapidsnark`), and defaults to [SnarkJS](ProverBackend::SnarkJS). The
+/// rapidsnark prover is run from `iden3/rapidsnark/package/bin/prover`, next to
+/// the Circom and snarkjs installations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverBackend {
+    /// `snarkjs groth16 prove`, or its PLONK and FFLONK counterparts.
+    SnarkJS,
+
+    /// The native Groth16 prover of rapidsnark.
+    Rapidsnark,
+}
+
+impl ProverBackend {
+    pub const BACKEND_VARIABLE: &'static str = "WINTER_CIRCOM_PROVER_BACKEND";
+
+    /// Returns the backend configured by the environment variable.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_PROVER_BACKEND` is not `snarkjs` or
+    /// `rapidsnark`.
+    pub fn from_env() -> Result<Self, WinterCircomError> {
+        match env::var(ProverBackend::BACKEND_VARIABLE).as_deref() {
+            Ok("snarkjs") | Err(_) => Ok(ProverBackend::SnarkJS),
+            Ok("rapidsnark") => Ok(ProverBackend::Rapidsnark),
+            Ok(value) => Err(WinterCircomError::InvalidEnvVariable {
+                variable: String::from(ProverBackend::BACKEND_VARIABLE),
+                comment: format!("expected snarkjs or rapidsnark, got {}", value),
+            }),
+        }
+    }
+
+    /// Returns the name of the backend, as set in the environment variable.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProverBackend::SnarkJS => "snarkjs",
+            ProverBackend::Rapidsnark => "rapidsnark",
+        }
+    }
+
+    /// Returns the message and the command of the prove stage of a circuit of
+    /// the given scheme, reading the witness at the given path.
+    ///
+    /// The command is run in the directory of the circuit. Rapidsnark only
+    /// generates Groth16 proofs.
+    pub(crate) fn prove_command(
+        &self,
+        scheme: SnarkScheme,
+        witness: &str,
+    ) -> Result<(&'static str, (Executable, Vec<String>)), WinterCircomError> {
+        let (message, executable, mut args) = match (*self, scheme) {
+            (ProverBackend::SnarkJS, SnarkScheme::Groth16) => (
+                "Generating Groth16 proof...",
+                Executable::SnarkJS,
+                vec![scheme.prove_command()],
+            ),
+            (ProverBackend::SnarkJS, SnarkScheme::Plonk) => (
+                "Generating PLONK proof...",
+                Executable::SnarkJS,
+                vec![scheme.prove_command()],
+            ),
+            (ProverBackend::SnarkJS, SnarkScheme::Fflonk) => (
+                "Generating FFLONK proof...",
+                Executable::SnarkJS,
+                vec![scheme.prove_command()],
+            ),
+            (ProverBackend::Rapidsnark, SnarkScheme::Groth16) => (
+                "Generating Groth16 proof with rapidsnark...",
+                Executable::Rapidsnark,
+                vec![],
+            ),
+            (ProverBackend::Rapidsnark, _) => {
+                return Err(WinterCircomError::UnsupportedScheme {
+                    scheme: String::from(scheme.name()),
+                    comment: format!(
+                        "rapidsnark only generates Groth16 proofs, unset {}",
+                        ProverBackend::BACKEND_VARIABLE
+                    ),
+                })
+            }
+        };
+        args.extend(["verifier.zkey", witness, "proof.json", "public.json"]);
+        Ok((
+            message,
+            (executable, args.into_iter().map(String::from).collect()),
+        ))
+    }
+}
+
+impl Default for ProverBackend {
+    fn default() -> Self {
+        ProverBackend::SnarkJS
+    }
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::utils::{command_output, Executable, WinterCircomError};
+
+/// Public source of randomness for the last contribution to the
+/// circuit-specific keys of a circuit (see
+/// [finalize_keys](crate::finalize_keys)).
+///
+/// The beacon value must be unpredictable when the previous contributions are
+/// made, and publicly verifiable afterwards: a drand round or an Ethereum block
+/// announced in advance, e.g. a few minutes after the keys are generated.
+/// Each beacon returns the provenance of its value along with it, so that it
+/// is recorded in the [SetupAttestation](crate::SetupAttestation) of the keys.
+pub trait Beacon {
+    /// Fetch the beacon value.
+    fn fetch(&self) -> Result<BeaconValue, WinterCircomError>;
+}
+
+/// Value of a [Beacon], with its provenance.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconValue {
+    /// Lowercase hexadecimal value, without `0x` prefix, given to
+    /// `snarkjs zkey beacon`.
+    pub hex: String,
+
+    pub source: BeaconSource,
+}
+
+/// Provenance of a [BeaconValue], from which anyone can fetch it again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BeaconSource {
+    /// Randomness of a drand round, with its BLS signature.
+    Drand {
+        url: String,
+        round: u64,
+        signature: String,
+    },
+
+    /// Hash of an Ethereum block.
+    EthereumBlock { rpc_url: String, number: u64 },
+
+    /// Value given by hand, with a free-form description of its origin.
+    Manual { description: String },
+}
+
+/// Randomness of a [drand](https://drand.love) round, fetched from the HTTP
+/// API of a drand network with `curl`, which must be installed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrandBeacon {
+    /// URL of the chain, e.g. `https://api.drand.sh` for the default chain of
+    /// the League of Entropy, or `https://api.drand.sh/<chain hash>` for
+    /// another one.
+    pub url: String,
+
+    /// Round to fetch, or `None` for the latest one.
+    ///
+    /// The latest round is known to everyone, and should only be used for
+    /// tests; announce a future round instead, and fetch it once emitted.
+    pub round: Option<u64>,
+}
+
+impl DrandBeacon {
+    /// Returns the beacon of the given round of the default chain of the
+    /// League of Entropy.
+    pub fn new(round: u64) -> Self {
+        DrandBeacon {
+            url: String::from("https://api.drand.sh"),
+            round: Some(round),
+        }
+    }
+}
+
+impl Beacon for DrandBeacon {
+    fn fetch(&self) -> Result<BeaconValue, WinterCircomError> {
+        let url = format!(
+            "{}/public/{}",
+            self.url.trim_end_matches('/'),
+            self.round
+                .map_or_else(|| String::from("latest"), |round| round.to_string())
+        );
+        let response = response_json(&command_output(Executable::Curl, &["-sfS", &url])?)?;
+
+        let round = response["round"]
+            .as_u64()
+            .ok_or_else(|| invalid("the drand response has no round"))?;
+        if let Some(expected) = self.round {
+            if round != expected {
+                return Err(invalid(&format!(
+                    "drand returned round {} instead of {}",
+                    round, expected
+                )));
+            }
+        }
+        let randomness = response["randomness"]
+            .as_str()
+            .ok_or_else(|| invalid("the drand response has no randomness"))?;
+        let signature = response["signature"]
+            .as_str()
+            .ok_or_else(|| invalid("the drand response has no signature"))?;
+
+        Ok(BeaconValue {
+            hex: parse_hex(randomness)?,
+            source: BeaconSource::Drand {
+                url: self.url.clone(),
+                round,
+                signature: String::from(signature),
+            },
+        })
+    }
+}
+
+/// Hash of an Ethereum block, fetched from a JSON-RPC endpoint with `curl`,
+/// which must be installed.
+///
+/// Block hashes can be biased by the proposer of the block, who can withhold
+/// it, at the cost of its block reward. Use a [DrandBeacon] when this matters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockhashBeacon {
+    pub rpc_url: String,
+
+    /// Number of the block, to be announced before it is proposed.
+    pub number: u64,
+}
+
+impl Beacon for BlockhashBeacon {
+    fn fetch(&self) -> Result<BeaconValue, WinterCircomError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getBlockByNumber",
+            "params": [format!("{:#x}", self.number), false],
+        })
+        .to_string();
+        let response = response_json(&command_output(
+            Executable::Curl,
+            &[
+                "-sfS",
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "--data",
+                &request,
+                &self.rpc_url,
+            ],
+        )?)?;
+
+        // the result is null until the block is proposed
+        let hash = response["result"]["hash"].as_str().ok_or_else(|| {
+            invalid(&format!(
+                "block {} is not available from {}",
+                self.number, self.rpc_url
+            ))
+        })?;
+
+        Ok(BeaconValue {
+            hex: parse_hex(hash)?,
+            source: BeaconSource::EthereumBlock {
+                rpc_url: self.rpc_url.clone(),
+                number: self.number,
+            },
+        })
+    }
+}
+
+/// Beacon value given by hand, e.g. the hash of a Bitcoin block or the
+/// output of a public ceremony, with a description of its origin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManualBeacon {
+    pub hex: String,
+    pub description: String,
+}
+
+impl ManualBeacon {
+    pub fn new(hex: &str, description: &str) -> Self {
+        ManualBeacon {
+            hex: String::from(hex),
+            description: String::from(description),
+        }
+    }
+}
+
+impl Beacon for ManualBeacon {
+    fn fetch(&self) -> Result<BeaconValue, WinterCircomError> {
+        Ok(BeaconValue {
+            hex: parse_hex(&self.hex)?,
+            source: BeaconSource::Manual {
+                description: self.description.clone(),
+            },
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the lowercase hexadecimal string of a beacon value, without its
+/// optional `0x` prefix.
+///
+/// snarkjs reads the value as a byte array, so it must have an even number of
+/// digits.
+fn parse_hex(value: &str) -> Result<String, WinterCircomError> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    if hex.is_empty() || hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid(&format!(
+            "{:?} is not an even-length hexadecimal string",
+            value
+        )));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+fn response_json(response: &str) -> Result<Value, WinterCircomError> {
+    serde_json::from_str(response).map_err(|e| invalid(&format!("invalid response ({})", e)))
+}
+
+fn invalid(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidBeacon {
+        comment: String::from(comment),
+    }
+}
@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(winter_circom_prover::cli::main());
+}
@@ -0,0 +1,76 @@
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::utils::WinterCircomError;
+
+thread_local! {
+    static TOKEN: RefCell<Option<CancellationToken>> = RefCell::new(None);
+}
+
+/// Token cancelling the jobs of a service between the long pure-Rust phases of
+/// the functions of this crate.
+///
+/// The external commands of an async [Stage](crate::Stage) are cancelled by
+/// dropping their future, but the Rust code of a job, e.g. the conversion of a
+/// large Winterfell proof to `input.json` by
+/// [circom_prove](crate::circom_prove), cannot be interrupted that way. Run
+/// with [with_cancellation], this code polls the token between the queries and
+/// the FRI layers of the proof, and fails with a
+/// [Cancelled](WinterCircomError::Cancelled) error once the token is
+/// cancelled, so that the worker running the job is released promptly.
+///
+/// Tokens are cheap to clone, and all clones are cancelled together, so that a
+/// job can be cancelled from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the jobs running with this token, or any of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Run `f` with the given cancellation token on the current thread, restoring
+/// the previous one afterwards.
+pub fn with_cancellation<T, F: FnOnce() -> T>(token: &CancellationToken, f: F) -> T {
+    struct Restore(Option<CancellationToken>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            TOKEN.with(|token| *token.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(TOKEN.with(|current| current.borrow_mut().replace(token.clone())));
+    f()
+}
+
+/// Returns a [Cancelled](WinterCircomError::Cancelled) error if the
+/// cancellation token of the current thread was cancelled during `step`.
+pub(crate) fn checkpoint(step: &str) -> Result<(), WinterCircomError> {
+    let cancelled = TOKEN.with(|token| token.borrow().as_ref().map_or(false, |t| t.is_cancelled()));
+    if cancelled {
+        return Err(WinterCircomError::Cancelled {
+            step: String::from(step),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use winterfell::{Air, ProofOptions, Prover, ProverError, StarkProof, Trace};
+
+use crate::utils::{strip_colors, WinterCircomError};
+
+/// Wrapper of a [Prover] checking that the public inputs are consistent with
+/// the trace before proving.
+///
+/// A common mistake is to compute public inputs in
+/// [get_pub_inputs](Prover::get_pub_inputs) that do not match the boundary
+/// values of the trace (e.g. reading the wrong step or column). Winterfell then
+/// produces a proof that fails to verify, and the witness generation of the
+/// Circom circuit fails long after the proof was generated. This wrapper
+/// evaluates the assertions of the AIR, instantiated with the public inputs,
+/// against the trace first:
+///
+/// ```ignore
+/// circom_prove(CheckedProver::new(prover), trace, "sum", LoggingLevel::Default)?;
+/// ```
+///
+/// [circom_prove](crate::circom_prove) runs the same check with any prover and
+/// returns the first mismatch as an error. When the wrapper is used to prove
+/// directly, [prove](Prover::prove) panics with the first mismatch instead. The
+/// check can also be run separately with [check](CheckedProver::check).
+pub struct CheckedProver<P> {
+    prover: P,
+}
+
+impl<P: Prover> CheckedProver<P> {
+    pub fn new(prover: P) -> Self {
+        Self { prover }
+    }
+
+    /// Returns the wrapped prover.
+    pub fn into_inner(self) -> P {
+        self.prover
+    }
+
+    /// Check that the assertions of the AIR, instantiated with the public
+    /// inputs of the trace, hold on the main segment of the trace.
+    ///
+    /// Returns an [InvalidTrace](WinterCircomError::InvalidTrace) error for the
+    /// first assertion that does not hold.
+    pub fn check(&self, trace: &P::Trace) -> Result<(), WinterCircomError> {
+        let air = P::Air::new(
+            trace.get_info(),
+            self.prover.get_pub_inputs(trace),
+            self.prover.options().clone(),
+        );
+        check_trace_assertions(&air, trace)
+    }
+}
+
+impl<P: Prover> Prover for CheckedProver<P> {
+    type BaseField = P::BaseField;
+    type Air = P::Air;
+    type Trace = P::Trace;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> <<Self as Prover>::Air as Air>::PublicInputs {
+        self.prover.get_pub_inputs(trace)
+    }
+
+    fn options(&self) -> &ProofOptions {
+        self.prover.options()
+    }
+
+    fn prove(&self, trace: Self::Trace) -> Result<StarkProof, ProverError> {
+        if let Err(e) = self.check(&trace) {
+            panic!(
+                "public inputs are inconsistent with the trace: {}",
+                strip_colors(&e.to_string())
+            );
+        }
+        self.prover.prove(trace)
+    }
+}
+
+/// Check that the assertions of an AIR hold on the main segment of a trace,
+/// see [CheckedProver::check].
+pub(crate) fn check_trace_assertions<AIR, T>(air: &AIR, trace: &T) -> Result<(), WinterCircomError>
+where
+    AIR: Air,
+    T: Trace<BaseField = AIR::BaseField>,
+{
+    let mut mismatch = None;
+    for assertion in air.get_assertions() {
+        assertion.apply(trace.length(), |step, expected| {
+            let value = trace.main_segment().get(assertion.column(), step);
+            if mismatch.is_none() && value != expected {
+                mismatch = Some(WinterCircomError::InvalidTrace {
+                    row: Some(step),
+                    comment: format!(
+                        "column {} is {} but the public inputs assert {}",
+                        assertion.column(),
+                        value,
+                        expected
+                    ),
+                });
+            }
+        });
+        if let Some(mismatch) = mismatch {
+            return Err(mismatch);
+        }
+    }
+
+    Ok(())
+}
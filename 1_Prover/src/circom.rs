@@ -1,24 +1,35 @@
 use std::{
-    collections::HashMap,
-    fs::{create_dir_all, File},
-    io::Write,
+    fs::{self, create_dir_all},
+    path::PathBuf,
+    time::Duration,
 };
 
-use colored::Colorize;
-use rug::{ops::Pow, Float};
+use serde_json::{json, Value};
 use winterfell::{
     crypto::hashers::Poseidon,
-    math::{fields::f256::BaseElement, log2, StarkField},
-    Air, AirContext, HashFunction, Prover, TraceInfo,
+    math::{fields::f256::BaseElement, FieldElement},
+    Air, HashFunction, ProofOptions, Prover, StarkProof, Trace, TraceInfo,
 };
 
 use crate::{
+    assertions::check_assertions,
+    audit::{audited, AuditAction},
+    checked::check_trace_assertions,
+    concurrency::Concurrency,
+    constraints::check_transition_degrees,
+    in_memory::CircuitInputs,
     json::proof_to_json,
-    utils::{
-        canonicalize, check_file, command_execution, delete_directory, delete_file, Executable,
-        LoggingLevel, WinterCircomError,
-    },
-    WinterCircomProofOptions, WinterPublicInputs,
+    manifest::{fri_tree_depths, CircuitManifest, CircuitParameters},
+    memory::MemoryLimit,
+    packing::{public_input_elements, PublicInputLayout},
+    pipeline::{run_stage, Stage},
+    scratch::{scratch_dir, ScratchSpace},
+    signals::Signal,
+    stats::ProofStats,
+    tenant::Tenant,
+    utils::{LoggingLevel, StageTimer, WinterCircomError},
+    workspace::circuit_dir,
+    WinterPublicInputs,
 };
 
 /// Verify the Groth16 proof of the verification of the Winterfell proof.
@@ -31,33 +42,69 @@ use crate::{
 ///
 /// This function requires the `verification_key.json`, `proof.json` and
 /// `public.json` files to be present in the directory
-/// `target/circom/<circuit_name>`. These files can be generated by the
-/// [circom_compile] and [circom_prove] functions.
+/// `target/circom/<circuit_name>`. These files are generated by the
+/// [groth16_setup](crate::groth16_setup) and [groth16_prove](crate::groth16_prove)
+/// functions.
 ///
 /// [Verbose](LoggingLevel::Verbose) logging level is *highly* recommended.
 pub fn circom_verify(
     circuit_name: &str,
     logging_level: LoggingLevel,
 ) -> Result<(), WinterCircomError> {
-    check_file(
-        format!("target/circom/{}/verification_key.json", circuit_name),
-        Some("needed for verification"),
-    )?;
-    check_file(
-        format!("target/circom/{}/public.json", circuit_name),
-        Some("needed for verification"),
-    )?;
-    check_file(
-        format!("target/circom/{}/proof.json", circuit_name),
-        Some("needed for verification"),
-    )?;
+    run_stage(Stage::Verify, circuit_name, &logging_level)
+}
 
-    command_execution(
-        Executable::SnarkJS,
-        &["g16v", "verification_key.json", "public.json", "proof.json"],
-        Some(&format!("target/circom/{}", circuit_name)),
-        &logging_level,
-    )
+/// Outputs of [circom_prove], returned so that callers can post-process, log
+/// or transmit them without reading the files back.
+#[derive(Clone, Debug)]
+pub struct ProveArtifacts {
+    /// Serialized Winterfell proof, see
+    /// [StarkProof::from_bytes](winterfell::StarkProof::from_bytes).
+    pub stark_proof: Vec<u8>,
+
+    /// Public inputs of the Winterfell proof, as field elements.
+    pub public_inputs: Vec<BaseElement>,
+
+    /// Path of `input.json`, in the [ScratchSpace](crate::ScratchSpace).
+    pub input_path: PathBuf,
+
+    /// Input signals of the circuit, the contents of `input.json`.
+    pub input: Value,
+
+    /// Path of `fri_layers.json`.
+    pub fri_layers_path: PathBuf,
+
+    /// Depths of the Merkle trees committing to the FRI layers of the proof.
+    pub fri_tree_depths: Vec<usize>,
+
+    /// Whether the Winterfell proof was verified before being converted, see
+    /// [IntermediateVerification](crate::IntermediateVerification).
+    pub inner_proof_verified: bool,
+
+    pub timings: ProveTimings,
+}
+
+/// Durations of the steps of [circom_prove].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProveTimings {
+    /// Building the Winterfell proof.
+    pub proving: Duration,
+
+    /// Verifying the Winterfell proof, `None` if it was not verified.
+    pub verification: Option<Duration>,
+
+    /// Converting the proof to the input signals of the circuit.
+    pub conversion: Duration,
+
+    /// Writing `input.json` and `fri_layers.json`.
+    pub writing: Duration,
+}
+
+impl ProveTimings {
+    /// Returns the total duration of the steps.
+    pub fn total(&self) -> Duration {
+        self.proving + self.verification.unwrap_or_default() + self.conversion + self.writing
+    }
 }
 
 /// Generate a Groth16 proof that the Winterfell proof is correct.
@@ -65,17 +112,25 @@ pub fn circom_verify(
 /// Only verifying the Groth16 proof attests of the validity of the Winterfell
 /// proof. This makes this function the core of this crate.
 ///
-/// This function only works if the Circom code has previously generated and
-/// compiled and if the circuit-specific keys have been generated. This is
-/// performed by the [circom_compile] function.
+/// This function only works if the Circom code has previously been generated by
+/// [circom_create](crate::circom_create). The witness and the Groth16 proof are
+/// then computed from the written inputs by the
+/// [compute_witness](crate::compute_witness) and
+/// [groth16_prove](crate::groth16_prove) stages of a [Pipeline](crate::Pipeline),
+/// once the code is compiled and the circuit-specific keys are generated.
+/// The proof is generated by snarkjs, or by rapidsnark on hosts configured with
+/// the [Rapidsnark](crate::ProverBackend::Rapidsnark) backend.
 ///
 /// ## Steps
 ///
 /// - Generate the Groth16 proof
 /// - (Not in release mode) Verify the proof
-/// - Parse the proof into a Circom-compatible JSON file
-/// - Compute execution witness
-/// - Generate proof
+/// - Parse the proof into a Circom-compatible JSON file, written to the
+/// [ScratchSpace](crate::ScratchSpace)
+/// - Write the per-layer FRI query data to `fri_layers.json` (see [FriLayer](crate::FriLayer))
+///
+/// The Winterfell proof, the circuit inputs and the durations of the steps are
+/// returned as [ProveArtifacts].
 ///
 /// ## Soundness
 ///
@@ -92,340 +147,487 @@ pub fn circom_prove<P>(
     trace: <P as Prover>::Trace,
     circuit_name: &str,
     logging_level: LoggingLevel,
-) -> Result<(), WinterCircomError>
+) -> Result<ProveArtifacts, WinterCircomError>
 where
     P: Prover<BaseField = BaseElement>,
     <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
 {
-    // CHECK FOR FILES
-    // ===========================================================================
-
-    // check_file(
-    //     format!("target/circom/{}/verifier.r1cs", circuit_name),
-    //     Some("did you run compile?"),
-    // )?;
-    // check_file(
-    //     format!("target/circom/{}/verifier.zkey", circuit_name),
-    //     Some("did you run compile?"),
-    // )?;
+    let options = prover.options();
+    let parameters = json!({
+        "trace_length": trace.length(),
+        "trace_width": trace.main_trace_width(),
+        "num_queries": options.num_queries(),
+        "blowup_factor": options.blowup_factor(),
+        "grinding_factor": options.grinding_factor(),
+    });
+    let artifacts = [
+        scratch_dir(circuit_name).join("input.json"),
+        circuit_dir(circuit_name).join("fri_layers.json"),
+    ];
+
+    audited(
+        circuit_name,
+        AuditAction::Prove,
+        parameters,
+        &artifacts,
+        || {
+            prove_circuit(circuit_name, &logging_level, |manifest| {
+                circuit_inputs(prover, trace, None, manifest, circuit_name, &logging_level)
+            })
+        },
+    )
+}
 
-    // BUILD PROOF
-    // ===========================================================================
+/// Generate a Groth16 proof that the Winterfell proof is correct, binding the
+/// given external data to it.
+///
+/// The circuit must have been generated with the
+/// [bind_data_commitment](crate::WinterCircomProofOptions::bind_data_commitment)
+/// option, and the bound public input of the Winterfell proof must be the
+/// [commit](crate::commitment::commit)ment to `data`. Otherwise, no witness
+/// can be computed for the Circom circuit.
+///
+/// See [circom_prove] for the other steps of the proof generation.
+pub fn circom_prove_with_data<P>(
+    prover: P,
+    trace: <P as Prover>::Trace,
+    data: &[u8],
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<ProveArtifacts, WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    let parameters = json!({
+        "trace_length": trace.length(),
+        "trace_width": trace.main_trace_width(),
+        "data_bytes": data.len(),
+    });
+    let artifacts = [
+        scratch_dir(circuit_name).join("input.json"),
+        circuit_dir(circuit_name).join("fri_layers.json"),
+    ];
+
+    audited(
+        circuit_name,
+        AuditAction::Prove,
+        parameters,
+        &artifacts,
+        || {
+            prove_circuit(circuit_name, &logging_level, |manifest| {
+                circuit_inputs(
+                    prover,
+                    trace,
+                    Some(data),
+                    manifest,
+                    circuit_name,
+                    &logging_level,
+                )
+            })
+        },
+    )
+}
 
-    if logging_level.print_big_steps() {
-        println!("{}", "Building STARK proof...".green());
+/// Convert a Winterfell proof generated beforehand, possibly on another host,
+/// into the inputs of the Circom circuit.
+///
+/// This is [circom_prove] without building the Winterfell proof: the proof,
+/// serialized with [StarkProof::to_bytes], is checked against the manifest of
+/// the circuit and written to `input.json` for the
+/// [compute_witness](crate::compute_witness) and
+/// [groth16_prove](crate::groth16_prove) stages:
+///
+/// ```ignore
+/// circom_prove_from_proof::<WorkAir>(&stark_proof, pub_inputs, None, "sum", LoggingLevel::Default)?;
+/// Pipeline::new("sum").witness().prove().run(LoggingLevel::Default)?;
+/// ```
+///
+/// `data` must be given if and only if the circuit binds external data (see
+/// [circom_prove_with_data]). The proof is verified before being converted as
+/// configured by the [IntermediateVerification](crate::IntermediateVerification)
+/// mode of the circuit, and the returned [proving](ProveTimings::proving) time
+/// is zero.
+pub fn circom_prove_from_proof<AIR>(
+    stark_proof: &[u8],
+    pub_inputs: AIR::PublicInputs,
+    data: Option<&[u8]>,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<ProveArtifacts, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let proof =
+        StarkProof::from_bytes(stark_proof).map_err(|e| WinterCircomError::InvalidStarkProof {
+            comment: e.to_string(),
+        })?;
+    if proof.options().hash_fn() != HashFunction::Poseidon {
+        return Err(WinterCircomError::InvalidStarkProof {
+            comment: String::from("the proof must be generated with the Poseidon hash function"),
+        });
     }
 
-    assert_eq!(prover.options().hash_fn(), HashFunction::Poseidon);
-
-    let pub_inputs = prover.get_pub_inputs(&trace);
-    let proof = prover
-        .prove(trace)
-        .map_err(|e| WinterCircomError::ProverError(e))?;
+    let trace_info = proof.get_trace_info();
+    let parameters = json!({
+        "trace_length": trace_info.length(),
+        "trace_width": trace_info.layout().main_trace_width(),
+        "num_queries": proof.options().num_queries(),
+        "blowup_factor": proof.options().blowup_factor(),
+        "grinding_factor": proof.options().grinding_factor(),
+    });
+    let artifacts = [
+        scratch_dir(circuit_name).join("input.json"),
+        circuit_dir(circuit_name).join("fri_layers.json"),
+    ];
+
+    audited(
+        circuit_name,
+        AuditAction::Prove,
+        parameters,
+        &artifacts,
+        || {
+            prove_circuit(circuit_name, &logging_level, |manifest| {
+                if let Some(compiled) = manifest.and_then(|m| m.circuit_parameters) {
+                    compiled.check(&proof_parameters::<AIR>(
+                        proof.get_trace_info(),
+                        pub_inputs.clone(),
+                        proof.options().clone(),
+                    ))?;
+                }
+                proof_inputs::<AIR>(
+                    proof,
+                    pub_inputs,
+                    data,
+                    manifest,
+                    circuit_name,
+                    &logging_level,
+                    ProveTimings::default(),
+                )
+            })
+        },
+    )
+}
 
-    // VERIFY PROOF
+/// Build the Circom inputs of a circuit with `build_inputs`, given the manifest
+/// of the circuit if there is one, and write them.
+fn prove_circuit<F>(
+    circuit_name: &str,
+    logging_level: &LoggingLevel,
+    build_inputs: F,
+) -> Result<ProveArtifacts, WinterCircomError>
+where
+    F: FnOnce(Option<&CircuitManifest>) -> Result<CircuitInputs, WinterCircomError>,
+{
+    // CHECK FOR FILES
     // ===========================================================================
 
-    #[cfg(debug_assertions)]
-    {
-        if logging_level.print_big_steps() {
-            println!("{}", "Verifying STARK proof...".green());
-        }
+    if let Some(tenant) = Tenant::of_circuit(circuit_name)? {
+        tenant.check_quota()?;
+    }
 
-        winterfell::verify::<P::Air>(proof.clone(), pub_inputs.clone())
-            .map_err(|err| WinterCircomError::InvalidProof(Some(err)))?;
+    let mut manifest = CircuitManifest::read(circuit_name)?;
+    if matches!(&manifest, Some(manifest) if manifest.sources_changed()) {
+        return Err(WinterCircomError::OutdatedCircuit {
+            circuit_name: String::from(circuit_name),
+        });
+    }
+    if let Some(manifest) = manifest.as_ref() {
+        check_transition_degrees(manifest, logging_level)?;
     }
 
-    // BUILD JSON OUTPUTS
-    // ===========================================================================
+    let inputs = build_inputs(manifest.as_ref())?;
 
-    if logging_level.print_big_steps() {
-        println!("{}", "Parsing proof to JSON...".green());
+    // record whether the proof was checked on this host
+    if let Some(manifest) = manifest.as_mut() {
+        manifest.inner_proof_verified = Some(inputs.inner_proof_verified);
+        manifest.write()?;
     }
 
-    // retrieve air and proof options
-    let air = P::Air::new(
-        proof.get_trace_info(),
-        pub_inputs.clone(),
-        proof.options().clone(),
-    );
+    // WRITE JSON OUTPUTS
+    // ===========================================================================
 
-    // convert proof to json object
-    let mut fri_tree_depths = Vec::new();
-    let json = proof_to_json::<P::Air, Poseidon<BaseElement>>(
-        proof,
-        &air,
-        pub_inputs.clone(),
-        &mut fri_tree_depths,
-    );
+    let timer = StageTimer::start("Writing JSON outputs...", logging_level);
 
-    // print json to file
-    let json_string = format!("{}", json);
-    create_dir_all(format!("target/circom/{}", circuit_name)).map_err(|e| {
-        WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("creating Circom output directory")),
-        }
+    // print json to the scratch space
+    let json_string = format!("{}", inputs.input);
+    let input_file =
+        ScratchSpace::from_env()?.write(circuit_name, "input.json", json_string.as_bytes())?;
+    let circuit_dir = circuit_dir(circuit_name);
+    create_dir_all(&circuit_dir).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("creating Circom output directory")),
+    })?;
+
+    // print the per-layer FRI query data to a separate file
+    let fri_layers_file = circuit_dir.join("fri_layers.json");
+    let fri_layers_string = serde_json::to_string(&inputs.fri_layers).unwrap();
+    fs::write(&fri_layers_file, fri_layers_string).map_err(|err| WinterCircomError::IoError {
+        io_error: err,
+        comment: Some(String::from("writing fri_layers.json")),
     })?;
-    let mut file =
-        File::create(format!("target/circom/{}/input.json", circuit_name)).map_err(|e| {
-            WinterCircomError::IoError {
-                io_error: e,
-                comment: Some(String::from("creating input.json")),
-            }
-        })?;
-    file.write(&json_string.into_bytes())
-        .map_err(|err| WinterCircomError::IoError {
-            io_error: err,
-            comment: Some(String::from("writing input.json")),
-        })?;
 
+    let mut timings = inputs.timings;
+    timings.writing = timer.finish(&[&input_file, &fri_layers_file]);
+    if logging_level.print_timings() {
+        if let Ok(stats) = ProofStats::from_input(&inputs.input) {
+            println!("{}", stats);
+        }
+    }
 
-    Ok(())
+    Ok(ProveArtifacts {
+        fri_tree_depths: inputs
+            .fri_layers
+            .iter()
+            .map(|layer| layer.tree_depth)
+            .collect(),
+        stark_proof: inputs.stark_proof,
+        public_inputs: inputs.public_inputs,
+        input_path: input_file,
+        input: inputs.input,
+        fri_layers_path: fri_layers_file,
+        inner_proof_verified: inputs.inner_proof_verified,
+        timings,
+    })
 }
 
-/// Generate and compile Circom code to verify a Winterfell proof with given
-/// parameters.
-///
-/// The execution of this function, especially the generation of the circuit-specific keys, can last several minutes.
-///
-/// ## Powers of tau phase 1 transcript
+/// Build the Circom inputs of the verification of a Winterfell proof of the
+/// given trace, checking them against the manifest of the compiled circuit if
+/// there is one.
 ///
-/// This function requires a powers of tau phase 1 transcript that has been
-/// prepared for phase 2 utilization. The file must be named `final.ptau` and
-/// placed in the project root.
-///
-/// ## Transition constraints and assertions
-///
-/// This function requires that a file named `<circuit_name>.circom` be placed in
-/// the `circuits/air/` directory. This file must contain two templates:
-///
-/// - `AIRTransitions` returning the degree of all transition constraints.
-/// - `AIRAssertions` defining the assertions.
-///
-/// These definition are similar to the ones defined in the class implementing
-/// the [Air] trait that is needed by the Winterfell prover and verifier.
-///
-/// There are examples already available in the `circuits/air/` directory.
-///
-/// ## Steps
-///
-/// - Generate Circom code to verify a Winterfell proof of given parameters.
-/// - Compile the generated code.
-/// - Generate circuit-specific keys from the powers of tau phase 1 transcript.
-/// - Export a verification key
-///
-/// Generated files are placed in the `target/circom/<circuit_name>/` directory.
-pub fn circom_create<P, const N: usize>(
-    proof_options: WinterCircomProofOptions<N>,
+/// Nothing is written to disk, `circuit_name` is only used in error messages.
+pub(crate) fn circuit_inputs<P>(
+    prover: P,
+    trace: <P as Prover>::Trace,
+    data: Option<&[u8]>,
+    manifest: Option<&CircuitManifest>,
     circuit_name: &str,
-    logging_level: LoggingLevel,
-) -> Result<(), WinterCircomError>
+    logging_level: &LoggingLevel,
+) -> Result<CircuitInputs, WinterCircomError>
 where
     P: Prover<BaseField = BaseElement>,
     <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
 {
-    // CHECK FOR REQUIRED FILES
+    // the number of composition columns is at most the blowup factor, the
+    // monitor runs until the JSON outputs are built
+    let _memory_monitor = match MemoryLimit::from_env()? {
+        Some(limit) => {
+            let blowup_factor = prover.options().blowup_factor();
+            limit.check(MemoryLimit::estimate(
+                trace.length(),
+                trace.main_trace_width(),
+                blowup_factor,
+                blowup_factor,
+            ))?;
+            Some(limit.monitor())
+        }
+        None => None,
+    };
 
-    check_file(
-        String::from("final.ptau"),
-        Some("required for the generation of circuit-specific keys"),
-    )?;
-    check_file(
-        format!("circuits/air/{}.circom", circuit_name),
-        Some("required for the compilation of Circom code"),
-    )?;
+    // BUILD PROOF
+    // ===========================================================================
 
-    // CREATE OUTPUT DIRECTORY
+    let timer = StageTimer::start("Building STARK proof...", logging_level);
+    let mut timings = ProveTimings::default();
 
-    create_dir_all(format!("target/circom/{}", circuit_name)).map_err(|e| {
-        WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("creating Circom output directory")),
-        }
-    })?;
+    if prover.options().hash_fn() != HashFunction::Poseidon {
+        return Err(WinterCircomError::InvalidStarkProof {
+            comment: String::from("the proof must be generated with the Poseidon hash function"),
+        });
+    }
 
-    // GENERATE CIRCOM CODE
-    // ===========================================================================
+    let pub_inputs = prover.get_pub_inputs(&trace);
 
-    if logging_level.print_big_steps() {
-        println!("{}", "Generating Circom code...".green());
+    // Winterfell panics on assertions of the same steps of a column, which the
+    // circuit would count twice
+    let air = P::Air::new(
+        trace.get_info(),
+        pub_inputs.clone(),
+        prover.options().clone(),
+    );
+    check_assertions(&air.get_assertions())?;
+
+    // public inputs inconsistent with the trace would only be detected when
+    // computing the witness, long after proving
+    check_trace_assertions(&air, &trace)?;
+
+    // check the parameters of the proof against the compiled circuit before
+    // proving, they cannot differ between the proofs of a circuit
+    if let Some(compiled) = manifest.and_then(|m| m.circuit_parameters) {
+        compiled.check(&proof_parameters::<P::Air>(
+            trace.get_info(),
+            pub_inputs.clone(),
+            prover.options().clone(),
+        ))?;
     }
 
-    generate_circom_main::<P::BaseField, P::Air, N>(proof_options, circuit_name)?;
-    Ok(())
+    Concurrency::from_env()?.configure(logging_level);
+    let proof = prover
+        .prove(trace)
+        .map_err(|e| WinterCircomError::ProverError(e))?;
+
+    timings.proving = timer.finish::<&str>(&[]);
+
+    proof_inputs::<P::Air>(
+        proof,
+        pub_inputs,
+        data,
+        manifest,
+        circuit_name,
+        logging_level,
+        timings,
+    )
 }
 
-/// Generate a circom main file that defines the parameters for verifying a proof.
-///
-/// The main file is generated in the `target/circom/<circuit_name>/` directory,
-/// with the `verifier.circom` name.
-pub fn generate_circom_main<E, AIR, const N: usize>(
-    proof_options: WinterCircomProofOptions<N>,
+/// Build the Circom inputs of the verification of a Winterfell proof, see
+/// [circuit_inputs].
+fn proof_inputs<AIR>(
+    proof: StarkProof,
+    pub_inputs: AIR::PublicInputs,
+    data: Option<&[u8]>,
+    manifest: Option<&CircuitManifest>,
     circuit_name: &str,
-) -> Result<(), WinterCircomError>
+    logging_level: &LoggingLevel,
+    mut timings: ProveTimings,
+) -> Result<CircuitInputs, WinterCircomError>
 where
-    E: StarkField,
-    AIR: Air,
+    AIR: Air<BaseField = BaseElement>,
     AIR::PublicInputs: WinterPublicInputs,
 {
-    // FRI TREE DEPTHS
-    let mut fri_tree_depths = vec![];
-    let mut lde_domain_size = proof_options.trace_length * proof_options.lde_blowup_factor();
-    while lde_domain_size > proof_options.fri_max_remainder_size {
-        lde_domain_size /= proof_options.lde_blowup_factor();
-        fri_tree_depths.push(log2(lde_domain_size));
+    let stark_proof = proof.to_bytes();
+
+    // VERIFY PROOF
+    // ===========================================================================
+
+    let verification = manifest
+        .map(|manifest| manifest.intermediate_verification)
+        .unwrap_or_default();
+
+    if verification.is_enabled() {
+        let timer = StageTimer::start("Verifying STARK proof...", logging_level);
+
+        winterfell::verify::<AIR>(proof.clone(), pub_inputs.clone())
+            .map_err(|err| WinterCircomError::InvalidProof(Some(err)))?;
+
+        timings.verification = Some(timer.finish::<&str>(&[]));
     }
 
-    let num_fri_layers = fri_tree_depths.len();
-
-    let fri_tree_depths = if fri_tree_depths.len() == 0 {
-        String::from("[0]")
-    } else {
-        format!(
-            "[{}]",
-            fri_tree_depths
-                .iter()
-                .map(|x| format!("{}", x))
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
-    };
+    // BUILD JSON OUTPUTS
+    // ===========================================================================
 
-    // AIR CONTEXT
+    let timer = StageTimer::start("Parsing proof to JSON...", logging_level);
 
-    let air_context = AirContext::<E>::new(
-        TraceInfo::new(proof_options.trace_width, proof_options.trace_length),
-        proof_options.transition_constraint_degrees().to_vec(),
-        proof_options.num_assertions(),
-        proof_options.get_proof_options(),
+    // retrieve air and proof options
+    let air = AIR::new(
+        proof.get_trace_info(),
+        pub_inputs.clone(),
+        proof.options().clone(),
     );
 
-    // CREATE FILE
+    // open the commitment to the external data, if the circuit binds any
+    let commitment_data = match manifest {
+        Some(manifest) => manifest.commitment_data(data)?,
+        None if data.is_none() => vec![BaseElement::ZERO],
+        None => {
+            return Err(WinterCircomError::FileNotFound {
+                file: CircuitManifest::path(circuit_name),
+                comment: Some(String::from("required to bind external data")),
+            })
+        }
+    };
 
-    let mut file = File::create(format!("target/circom/{}/verifier.circom", circuit_name))
-        .map_err(|e| WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("trying to create circom main file")),
-        })?;
+    // pack the public inputs according to their layout
+    let public_inputs = public_input_elements(&pub_inputs);
+    let packed_public_inputs = PublicInputLayout::of::<AIR::PublicInputs>().pack(&public_inputs)?;
 
-    // WRITE TO FILE
-
-    let arguments = format!(
-        "{}, // addicity\n    \
-            {}, // ce_blowup_factor\n    \
-            {}, // domain_offset\n    \
-            {}, // folding_factor\n    \
-            {}, // fri_tree_depth\n    \
-            {}, // grinding_factor\n    \
-            {}, // lde_blowup_factor\n    \
-            {}, // num_assertions\n    \
-            {}, // num_draws\n    \
-            {}, // num_fri_layers\n    \
-            {}, // num_pub_coin_seed\n    \
-            {}, // num_public_inputs\n    \
-            {}, // num_queries\n    \
-            {}, // num_transition_constraints\n    \
-            {}, // trace_length\n    \
-            {}, // trace_width\n    \
-            {} // tree_depth",
-        E::TWO_ADICITY,
-        air_context.ce_domain_size() / proof_options.trace_length,
-        E::GENERATOR,
-        proof_options.fri_folding_factor(),
-        fri_tree_depths,
-        proof_options.grinding_factor(),
-        proof_options.lde_blowup_factor(),
-        proof_options.num_assertions,
-        number_of_draws(
-            proof_options.num_queries() as u128,
-            (proof_options.trace_length * proof_options.fri_folding_factor()) as u128,
-            128
-        ),
-        num_fri_layers,
-        // 2 is the size of the serialized context in f256 field elements
-        AIR::PublicInputs::NUM_PUB_INPUTS + 2,
-        AIR::PublicInputs::NUM_PUB_INPUTS,
-        proof_options.num_queries,
-        air_context.num_transition_constraints(),
-        proof_options.trace_length,
-        proof_options.trace_width,
-        log2(proof_options.trace_length * proof_options.fri_folding_factor()),
-    );
+    // commit to the OOD data, and to the public inputs if they are exposed
+    let committed_public_inputs = manifest
+        .filter(|manifest| manifest.public_signals_committed.unwrap_or(false))
+        .map(|manifest| match manifest.public_inputs_exposed {
+            Some(true) => &public_inputs[..],
+            _ => &public_inputs[..0],
+        });
 
-    let file_contents = format!(
-        "pragma circom 2.0.0;\n\
-        \n\
-        include \"../../../circuits/verify.circom\";\n\
-        include \"../../../circuits/air/{}.circom\";\n\
-        \n\
-        component main {{public [ood_frame_constraint_evaluation, ood_trace_frame]}} = Verify(\n    \
-            {}\n\
-        );\n\
-",
-        circuit_name, arguments
-    );
+    // convert proof to json object
+    let mut fri_layers = Vec::new();
+    let json = proof_to_json::<AIR, Poseidon<BaseElement>>(
+        proof,
+        &air,
+        pub_inputs.clone(),
+        commitment_data,
+        packed_public_inputs,
+        committed_public_inputs,
+        &mut fri_layers,
+    )?;
 
-    file.write(file_contents.as_bytes())
-        .map_err(|e| WinterCircomError::IoError {
-            io_error: e,
-            comment: Some(String::from("trying to write to circom main file")),
-        })?;
+    // check the FRI tree depths against the ones of the compiled circuit
+    let fri_options = air.options().to_fri_options();
+    let layer_depths = fri_layers
+        .iter()
+        .map(|layer| layer.tree_depth)
+        .collect::<Vec<_>>();
+    let expected_depths = fri_tree_depths(
+        air.lde_domain_size(),
+        fri_options.folding_factor(),
+        fri_options.max_remainder_size(),
+    );
+    if layer_depths != expected_depths {
+        return Err(WinterCircomError::InvalidStarkProof {
+            comment: format!(
+                "FRI tree depths of the proof {:?} differ from the computed ones {:?}",
+                layer_depths, expected_depths
+            ),
+        });
+    }
+    // check the number of exported composition columns against the circuit
+    let num_composition_columns = json[Signal::OodConstraintEvaluations.name()]
+        .as_array()
+        .map_or(0, |evaluations| evaluations.len());
+    if num_composition_columns != air.ce_blowup_factor() {
+        return Err(WinterCircomError::InvalidStarkProof {
+            comment: format!(
+                "{} composition columns exported, the AIR has {}",
+                num_composition_columns,
+                air.ce_blowup_factor()
+            ),
+        });
+    }
+    if let Some(manifest) = manifest {
+        manifest.check_fri_tree_depths(&layer_depths)?;
+        manifest.check_num_composition_columns(num_composition_columns)?;
+        manifest.check_column_map(air.trace_layout().main_trace_width())?;
+    }
 
-    Ok(())
-}
+    timings.conversion = timer.finish::<&str>(&[]);
 
-// HELPER FUNCTIONS
-// ===========================================================================
-
-fn number_of_draws(num_queries: u128, lde_domain_size: u128, security: i32) -> u128 {
-    let mut num_draws: u128 = 0;
-    let precision: u32 = security as u32 + 2;
-
-    while {
-        let st = step(
-            0,
-            num_draws,
-            &mut HashMap::new(),
-            num_queries,
-            lde_domain_size,
-            security,
-        );
-        num_draws += 1;
-        1 - st > Float::with_val(precision, 2_f64).pow(-security)
-    } {}
-
-    num_draws
+    Ok(CircuitInputs {
+        input: json,
+        fri_layers,
+        inner_proof_verified: verification.is_enabled(),
+        stark_proof,
+        public_inputs,
+        timings,
+    })
 }
 
-fn step(
-    x: u128,
-    n: u128,
-    memo: &mut HashMap<(u128, u128), Float>,
-    num_queries: u128,
-    lde_domain_size: u128,
-    security: i32,
-) -> Float {
-    let precision: u32 = security as u32 + 2;
-    match memo.get(&(x, n)) {
-        Some(val) => val.clone(),
-        None => {
-            let num: Float;
-            if x == num_queries {
-                num = Float::with_val(precision, 1f64);
-            } else if n == 0 {
-                num = Float::with_val(precision, 0f64);
-            } else {
-                let a = step(x + 1, n - 1, memo, num_queries, lde_domain_size, security);
-                let b = step(x, n - 1, memo, num_queries, lde_domain_size, security);
-                num = Float::with_val(precision, lde_domain_size - x)
-                    / (Float::with_val(precision, lde_domain_size))
-                    * a
-                    + Float::with_val(precision, x) / (Float::with_val(precision, lde_domain_size))
-                        * b;
-            }
-            memo.insert((x, n), num.clone());
-            num
-        }
+/// Returns the parameters of the Winterfell proofs of an AIR, to be checked
+/// against the ones of the compiled circuit.
+fn proof_parameters<AIR: Air>(
+    trace_info: TraceInfo,
+    pub_inputs: AIR::PublicInputs,
+    options: ProofOptions,
+) -> CircuitParameters {
+    let air = AIR::new(trace_info.clone(), pub_inputs, options.clone());
+    CircuitParameters {
+        trace_length: trace_info.length(),
+        trace_width: trace_info.layout().main_trace_width(),
+        num_transition_constraints: air.context().num_transition_constraints(),
+        num_assertions: air.get_assertions().len(),
+        num_queries: options.num_queries(),
+        lde_blowup_factor: options.blowup_factor(),
+        grinding_factor: options.grinding_factor(),
+        fri_folding_factor: options.to_fri_options().folding_factor(),
+        fri_max_remainder_size: options.to_fri_options().max_remainder_size(),
     }
 }
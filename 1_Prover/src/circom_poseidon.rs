@@ -0,0 +1,682 @@
+//! Poseidon hash function, as used by the generated Circom circuits.
+//!
+//! The public coin and the Merkle commitments of the Winterfell proofs verified
+//! by this crate are computed with the Poseidon hash function over the f256
+//! field. The functions of this module are the exact counterparts of the
+//! Circom templates of `circuits/poseidon/poseidon.circom` and
+//! `circuits/merkle.circom`, so that applications can pre-compute values
+//! that will match in-circuit hashing (e.g. to commit to inputs off-chain).
+//!
+//! ## Stability
+//!
+//! The Poseidon parameters (round constants and MDS matrix) are shared with
+//! `circuits/poseidon/param.circom`. Changing them on either side breaks the
+//! verification of all proofs.
+//!
+//! The parameters are published as [MDS] and [ROUND_CONSTANTS], along with
+//! known digests ([TEST_VECTORS]), so that other implementations of the hash
+//! function can be validated with [verify_test_vectors].
+
+use winterfell::{
+    crypto::{hashers::Poseidon, Digest, ElementHasher},
+    math::{
+        fields::f256::{BaseElement, U256},
+        StarkField,
+    },
+};
+
+/// Hash a sequence of field elements.
+///
+/// This matches the output of the `Poseidon(n)` Circom template, where `n` is
+/// the number of elements.
+pub fn hash_elements(elements: &[BaseElement]) -> BaseElement {
+    digest_to_element(&Poseidon::<BaseElement>::hash_elements(elements))
+}
+
+/// Hash two nodes of a Merkle tree into their parent node.
+///
+/// This matches the `Poseidon(2)` Circom template used at each level of
+/// `MerkleOpeningRoot`, with the left node as the first input.
+pub fn hash_merkle_node(left: BaseElement, right: BaseElement) -> BaseElement {
+    hash_elements(&[left, right])
+}
+
+/// Returns the root of a Merkle tree, given a leaf, its index and its
+/// authentication path, from the sibling of the leaf to the child of the root.
+///
+/// The leaf is hashed with [hash_elements] first, as in the `MerkleOpeningRoot`
+/// Circom template.
+pub fn merkle_root(leaf: &[BaseElement], index: usize, opening: &[BaseElement]) -> BaseElement {
+    let mut node = hash_elements(leaf);
+    for (i, sibling) in opening.iter().enumerate() {
+        node = if (index >> i) & 1 == 0 {
+            hash_merkle_node(node, *sibling)
+        } else {
+            hash_merkle_node(*sibling, node)
+        };
+    }
+    node
+}
+
+/// Returns the public commitment of a circuit generated with
+/// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals),
+/// given the values it commits to: the OOD constraint evaluations, the current
+/// and next rows of the OOD trace frame, and the public inputs if they are
+/// exposed, unpacked, in that order.
+///
+/// This matches the hash of the `public_commitment` signal checked by the
+/// `Verify` Circom template.
+pub fn public_commitment(committed_signals: &[BaseElement]) -> BaseElement {
+    hash_elements(committed_signals)
+}
+
+/// Parameters of the query positions drawn by the `DrawQueryPositions` Circom
+/// template (see [derive_query_positions]).
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryDrawParameters {
+    /// Number of draws, hardcoded in the generated Circom main file.
+    pub num_draws: usize,
+    pub num_queries: usize,
+
+    /// Size of the LDE domain, a power of two.
+    pub lde_domain_size: usize,
+}
+
+/// Returns the query positions drawn from the seed of the public coin after the
+/// proof of work, or `None` if the draws contain less than `num_queries`
+/// distinct positions (in which case no witness can be computed).
+///
+/// This replicates the rejection sampling of the `DrawQueryPositions` Circom
+/// template of `circuits/public_coin.circom`: the `i`-th draw is the low
+/// `log2(lde_domain_size)` bits of `hash(seed, i + 1)`, and the query
+/// positions are the first `num_queries` distinct draws, in order. A mismatch
+/// between the two silently weakens the soundness of the circuit, as the
+/// circuit would then check openings at positions the prover did not commit
+/// to answering honestly.
+#[doc(hidden)]
+pub fn derive_query_positions(
+    seed: BaseElement,
+    parameters: QueryDrawParameters,
+) -> Option<Vec<usize>> {
+    assert!(
+        parameters.lde_domain_size.is_power_of_two(),
+        "the LDE domain size must be a power of two"
+    );
+    let mask = parameters.lde_domain_size as u64 - 1;
+
+    let mut positions = Vec::with_capacity(parameters.num_queries);
+    for i in 0..parameters.num_draws {
+        if positions.len() == parameters.num_queries {
+            break;
+        }
+        let draw = hash_elements(&[seed, BaseElement::from((i + 1) as u64)]);
+        let position = (draw.as_int().low_u64() & mask) as usize;
+        if !positions.contains(&position) {
+            positions.push(position);
+        }
+    }
+
+    (positions.len() == parameters.num_queries).then(|| positions)
+}
+
+// PARAMETERS
+// ===========================================================================
+
+/// Width of the state of the Poseidon permutation.
+pub const STATE_WIDTH: usize = 4;
+
+/// Number of elements absorbed by each permutation of the sponge, the
+/// remaining element of the state being its capacity.
+pub const RATE: usize = 3;
+
+/// Number of full rounds, half of them before the partial rounds.
+pub const NUM_FULL_ROUNDS: usize = 8;
+
+/// Number of partial rounds, applying the S-box `x^5` to the first element of
+/// the state only.
+pub const NUM_PARTIAL_ROUNDS: usize = 58;
+
+/// MDS matrix of the permutation, as decimal field elements: the state `s`
+/// becomes `MDS * s` at the end of each round.
+pub const MDS: [[&str; STATE_WIDTH]; STATE_WIDTH] = [
+    [
+        "13682032250801179187570186285176733763308291836613942991479520590256716961232",
+        "19807656384719591425791552925292162125472531523842931528010588787546530670933",
+        "8480858688710537284044155618254509512044353645728868286672343037476095514727",
+        "12214991638150566385351300014811456752648014435654858529043329724953935400908",
+    ],
+    [
+        "932609354172568758621898802162548910750085747674861943621300469229428996293",
+        "11975570439724300049427624059185735298466199651049665650528367284730050080061",
+        "2496991100123035306946304087156754650943712752477134966408347869013799258614",
+        "15203151410367761253872155638116635593972103817310453963894412905622580538624",
+    ],
+    [
+        "15769083491408695821293953432311849404675481052199955565246542172728344652638",
+        "11172925168439976744262347703667110058577952721340171328243610295065712584680",
+        "823775840048352895109063666787603553255555875879064484340739496936638354115",
+        "14873589042569363018733567413594526490824580536169546361376239438852440570232",
+    ],
+    [
+        "1993254594848538629647767427467154242266341087089807051511845988684169892843",
+        "538948912143034996708681690851566753393255887231536901372242074252209202461",
+        "18572153612839353128807930582057656558611992624574973691395192701502449938484",
+        "14532782355590045100198562172324991242037056455059852801364228131654218898265",
+    ],
+];
+
+/// Round constants of the permutation, as decimal field elements: the `i`-th
+/// element of the state is incremented by `ROUND_CONSTANTS[r][i]` at the start
+/// of round `r`.
+///
+/// These are the constants of the Grain LFSR of the reference implementation
+/// of Poseidon. `param.circom` stores the equivalent constants and sparse
+/// matrices of the optimized partial rounds instead, which
+/// `generate_parameters_grain.sage.py` derives from these ones.
+pub const ROUND_CONSTANTS: [[&str; STATE_WIDTH]; NUM_FULL_ROUNDS + NUM_PARTIAL_ROUNDS] = [
+    [
+        "9349661298342399812155975888769216971032306854753642878665919446294879656360",
+        "20355483845293419182639799451281054460194762622258826357723348986791339666584",
+        "3863924254320797838188096098429099056002921664247415692790885110053932478199",
+        "1179615533324779426788185816518326392495151040698217115677996613301276682774",
+    ],
+    [
+        "17857817167154695836609045863125494616953942146896460807392682577567978661748",
+        "598171348165321622852793933505075582400337822156992624972338044918725664853",
+        "10288182281340317860498695241603468482410103834992200046713945227738753708698",
+        "14033715015428758690964178846063326121907630185247031945740719057439149565993",
+    ],
+    [
+        "4403200580683260380496436725772333420155669721780700308273860885868494218346",
+        "19596494125741654142983078963141005641888021246517341126259813204502802384067",
+        "8564650104936121756307676523083173187701906303606385520973932800410096546403",
+        "14645239752159699463621673219464989513053653395607183327172956630300632214161",
+    ],
+    [
+        "5306035170159303765381094919358101787761660740153826945252354930617409344545",
+        "18879875135569478383581020380333204197826083956201656258109092867558319472011",
+        "3327735463285264389547553322049591191687534162750551437554125229508114433905",
+        "16842705392485332591186430147391106061494560395960911123266351758884473968040",
+    ],
+    [
+        "19116030705444699721811373764669364345407858022672453360681431146172949087255",
+        "2289497963651166564821328421048398089227958159979648636214346006101392913971",
+        "15745445270188095679915550281308711330297798345940650478404136616321429798117",
+        "3011337724227793379719940665471296616651805859988581738487652645836267590171",
+    ],
+    [
+        "9811892028219580749728474137247190502469695007070797037824794912024761441114",
+        "14444529256249055788576113890085160544238844477021196470519328078051564870058",
+        "8236598392421158948985531741384601455375731009454033851408593765765955905917",
+        "14469588998931877790646555043804220374430079256592557600379312153780564446448",
+    ],
+    [
+        "2201563765785355333673906784661986554054149480342064616965781997606874020693",
+        "19408256378536411484001522826775615960821294329946603004671235609277012763510",
+        "16407840299448924952232164701536489940740729079636625360589636373848316275717",
+        "4934715837641680858973095977846665771515439959660969395996313298547448277806",
+    ],
+    [
+        "18226515615667822111847925609341650918947230026239908578214546539037502316886",
+        "41293529246768213184746126233347774754067127234460586812917287976400190572",
+        "15982808774063066655007849424378585379924670132799505811503285626682048848987",
+        "15009986292734227808988913994159809239976343120771552992345264892136084647386",
+    ],
+    [
+        "17420808720689774924811775801582547991393718310864586904905079266371877341686",
+        "10694889466199780005040145739280563097176978766392271314245268992688888142361",
+        "9629327996871535796378358552291802388219841917605916233853295665987724111280",
+        "4005638278132297543466645220208430270290941828801836247971529499707165767674",
+    ],
+    [
+        "8617602262409490806051103417618373473230580645599642785879406811512772519516",
+        "876461689077884742784882688862296241914460552813200531837350580656791699736",
+        "9114808012827482517939433907295006066309740670143010604062271414729893641441",
+        "17772265644067240999060498990905975687178337851390504360352408183799773564731",
+    ],
+    [
+        "29545021022720483342713725443507054924440608382673070359004630792128748609",
+        "3188202705052272796657204923255899258076313756484781807081137546209305058717",
+        "11573321144518655609847285667516852227405328288185630768307018845446053566403",
+        "1307392736738626694362399667287979629083433695789008880813180741370585611774",
+    ],
+    [
+        "14982430986771183905058243954769212612141813313997557269656413928262053738416",
+        "8238277770831275082502005145164859109431761953072823725964254474395823029016",
+        "6173521992969575637473981590050879005523435747330530948374803249934506049247",
+        "17621207018784731512908327724093043529334084131117014312237713022159959607688",
+    ],
+    [
+        "7632944111191257924958110586098523344123208352663546193857001937050866848145",
+        "6025997795964140904230690729695121575185608942398124856031998453031598463099",
+        "4248032595048848010348066279025405114629905995835284579006258904556915009111",
+        "3588929553388275211025539693626985999008403465498856327504332441227731233284",
+    ],
+    [
+        "15572427458408768994562276877754519361698407602059958317267558432364213941018",
+        "20392288077802951927014364644167321398030182186143987792196835964168599665928",
+        "21034017426328793790556567601593584335973844830891229626662141220427300072741",
+        "9831150334911160363869436522772304659275314059284401802260285386448906930240",
+    ],
+    [
+        "7447112317827672878931491484489637257999006861510730910599606280908005104594",
+        "12522239158559211602852805368461373430453123483739911951695836164777795672097",
+        "1779830670321736523047104765818962287079892210793844252664463776791551332564",
+        "990254061139726798790147921398467284142802431938887522065813040364848671214",
+    ],
+    [
+        "13675652083544771699290931177897918265755010792802630245955753187229107239324",
+        "3875250872220681445838805175532088359880740354888557862765278062334579561866",
+        "4218186890873971508995725751622898347275069763310514877594887703464244735203",
+        "5174180704030764646184300717690518324677430783331934939977943713847072467453",
+    ],
+    [
+        "7544482984300767251273176033472582050176297728341520609351381157371056156167",
+        "10390183693684052623253928403519052857162977187363803992584582989475945715620",
+        "74971105925293690996729143424805256065189699763672160210284055947523270633",
+        "156602143553699378748546159591771650061087917833361911632339270962819112775",
+    ],
+    [
+        "17089011734834440767718218565326807913122067135954503315413077910417490767218",
+        "5930714215894626883731938646005041202161960733085728111741632827965950804469",
+        "15746150945801378988976713169636932149844417863584305788814049866250741857963",
+        "1905563309980403567103951171796469982184840891367411966322724777389700945715",
+    ],
+    [
+        "15385044433698739297558796412535170460858274377547642308027664410002229842554",
+        "2581324215467446811925768526186562450077174359990831771751701384346493115296",
+        "13456874724004135677873237850294133608021401146211365111944319154813221410898",
+        "11287163302903088326808144349512220647262046351014244551587739997236654263600",
+    ],
+    [
+        "14680808913158071480157136151116132032683630752711124309703278182377958736214",
+        "16662040736820589481276854855578848198608262738099541868692407332444690133415",
+        "8630837633229192016294515211911572037546632164302267117969983401336107375070",
+        "15934242475550541047869023393520239809573561490843689934010786778787399335526",
+    ],
+    [
+        "7011423728559748526541687462084841442964587442287000820296737058927136670883",
+        "10394658086156708422499923567256161894327902472867778776911379233484127274811",
+        "4062688424175029710287730011486787170049234798355242517438791492270050963786",
+        "20583984966876065484197467780719844972217620964049290928448033200007602407054",
+    ],
+    [
+        "11551539316882309252544196424776567598801776459901693086641731999084461357620",
+        "7241439467248040811756745704003344586918577311716115050434833310547355688885",
+        "10085353196897124101642672706033981388019961667802444779676295461496805951550",
+        "13315384965205617856014776594521468620507294402383101227315765045659569148951",
+    ],
+    [
+        "12426584669927156344775728888683946235045398136714689142043159351531162429361",
+        "12083986941822605860463234027242605416019838237567715182235258664030138958585",
+        "21295158127791412238157668617107146833963266362808404833053715110425783586811",
+        "11077823412430212204390518082624615425479910673507896876033128377010663433617",
+    ],
+    [
+        "10524614300254432535168276527406224616396683216207890758251612520926420150857",
+        "7084130010881563174625145604366726458782610733324373155621845602415496066440",
+        "18119879694758682385310370307737973727558935723466721474552924552121041807337",
+        "13877342791026338300764288985496659039852065330473962147618589917948021854940",
+    ],
+    [
+        "8724550502015960902717695936346569842892877075666427830875384715060464303325",
+        "17824033842511228768402964371579249247711237335667796145900430105577502210066",
+        "20086758462455469760178151703798810515626275838830646431598225423135677701953",
+        "11866837360658059788340265930350553579135272090282378360491488098848880004425",
+    ],
+    [
+        "16157395891099733986906611789653591314502709277229980588352435449895917319591",
+        "10412199273879424947520666918860933576993442087699829121227617810631640172320",
+        "10426008200219750124052633183179397710073391472175717842380099346894125097866",
+        "9633723613700804753722019099509532862210512234232431597109171801863256670127",
+    ],
+    [
+        "7643018832703673000340305300380316548696289241177099914725906225067455933069",
+        "17052140315735613886124325145106448520162697740141305217693609823067045530344",
+        "15743159033208704997552679131392876413038642701990733797218990903821671614091",
+        "11029151682476576592261685044154835491282602610372485652177785948586806308388",
+    ],
+    [
+        "5038811659957654296368326745178981323534967846592364811440897295603205251860",
+        "5251651914330853945830953926510478620546410406161697088697090059990253732477",
+        "5734116749125067664540310648205591719627486074293484192708460264190054224328",
+        "8908225635350998199324727929782037840613409640236502436936075568257684842867",
+    ],
+    [
+        "20193220905849409625363676377708153644066376703272787868847764442680884573030",
+        "20510537554565051840820590576467854292333227271602375469823645847624057655767",
+        "18955132577994484396532038507893412378441854850110450301236921785753152788281",
+        "6229158017123608859832061331729671015051945587810210154760393321501529708144",
+    ],
+    [
+        "4874384672141986693710026480648751670028812527017107819948968189667783994560",
+        "7222087946550957014260542386482950578264039194708136220350288817537669950364",
+        "15334936627743100935700603322133560357525276758288664161795136977843314771823",
+        "11902696955362571898859774636692580463970312375615269043084868738792424364377",
+    ],
+    [
+        "17047499782498182906320291218673447485773153595142780591039498256737846247598",
+        "15384333322593901258421949906138919278224766936698077284020533477620488389349",
+        "13581564987260191458701559480132776348435263109545944802495045839423170630789",
+        "12279089234261586209581728146667086627272683660206540253555496547611142275568",
+    ],
+    [
+        "17627061856066352413765181091323779025592551373456368317926895350294384431233",
+        "13569942944612501581606654338164244065294568383053484162557230713095740042741",
+        "19012258642855949678187420097035679098077855412691107069970788571471901599649",
+        "20676459457149178129222685936175195233673446845563271289621201659257787514393",
+    ],
+    [
+        "16847712785852896860462256338604915800617709765202225981546678117286983022520",
+        "3013600601093039306050664345861439840022117614166525306318048005082151345349",
+        "18453975834810206824823213073866680030748474508683996453313542043220047931005",
+        "619373060702549880886769180113235220529998048644010428699814893030388339071",
+    ],
+    [
+        "21861802909826980540938737218598306360323296026305861488043653172400533909177",
+        "957787141522144251362664220364839172863980555571423451725896888006735545309",
+        "17778765407855151473595825493733703945347184245719473657342267720455937979575",
+        "8147224972182548859893111399488944784609320996055896768309080139934272332969",
+    ],
+    [
+        "3474217658011872797143658722839950618719757349531608721350400255737991199351",
+        "11459512671208342530349655663311533913751557505744762311354479232363001779380",
+        "13689422458093000125742693775676388393969158053219844436146782674990251617120",
+        "2132146399037220777743023516988156184457875457742014539733471075702322403855",
+    ],
+    [
+        "9417256040992545959471687970846154551453710343626645150724144723410376810306",
+        "2662568208229259027512433215308401932952993716042038050640608088480009539444",
+        "20247371484197454626843235352681572965441381130347697360200283925014127102640",
+        "3258131269359783840999194211937389437255612249924171306645794571197671237958",
+    ],
+    [
+        "2246769231092344426644415313614879821949172952615061436002466742961615503213",
+        "8286537983983986003694997713491938512871363766008679985777763397661777130009",
+        "16062984815188427526014208541120816156920746107262588675032755168953002751425",
+        "13189067071533931874264842763164642721817465624704883569654045209138867281047",
+    ],
+    [
+        "9598708054054421080540320651941346678968078685984829345041775097399432367577",
+        "168018832481764889935623263878769370923638481496690183397205740204525325313",
+        "12382103959242222179772905739917409487092922783419809900692312978982853008969",
+        "8342752911095173229208233594982005882446460643346183277961582380062606659170",
+    ],
+    [
+        "14169179831883393702664757361248489569960243939236468121169894167447686161207",
+        "10264996694551079821528046469285308578314749228520158403060790328526580597858",
+        "20022396207816105732548679480506403695222454995652181172472626903536994692399",
+        "256359565442309687370742689065521603165558003853600754999572759926445189608",
+    ],
+    [
+        "600149076076301828588616308064034381495366314937778150116831498115065704173",
+        "18348182380854455572987363524508971936020937736366159686890605606960600268180",
+        "11897742367449468731460080661760428726008577561043546116077944231295970408791",
+        "8050406892135037560258691191392800549042580333422630035242190746160352371343",
+    ],
+    [
+        "8632352840162828370540645626109311938857702346102287638065861279403776523192",
+        "13135236253493008116567566032337231870585607633810896435172667585841019783173",
+        "9171952233622877328897898017067083622395547011474750437579403910901082540135",
+        "9484218547344096618218689440596946492271603559662427094197831813460245789539",
+    ],
+    [
+        "2542286592130181729827897166723459729568773963328729118203975601696202234075",
+        "14863950300023394796398364187604544823745336693201489246858964426145091590774",
+        "21162657960159807806690559610424622941469862938082356113611956033191833961680",
+        "14873415454540817819101977166718635870727617899585053368667172963337334943155",
+    ],
+    [
+        "3439170749897914208750645121810986425731109417302001583316039913686232058996",
+        "11341006246924525154316780082366523098245432532559699676861817421033428617526",
+        "2134161135094687195860797188385541357307186047943343475195510873199231573998",
+        "13029301015688420656309167449742295855709011212626347331895619311338284350683",
+    ],
+    [
+        "2911584322204942726845074099982604869704960355746826463294237809949654314669",
+        "5241789623417955427907415758165064428847179147119943178729670054423584616105",
+        "19338564429629596099622158974749298903439304668998793741154935669900509023559",
+        "6084797297368156703239077397946389884086320818545092397788834978883357832314",
+    ],
+    [
+        "366760139390455565659180750169095332529594388615095597078854357892472317976",
+        "5635811309889966222797324549383757572249192022800790424268324839183763311985",
+        "11981243989884988645519046896214120965379929024679161272051941961395818386153",
+        "2805694100335748165766444278201393431703520955127555633064840786450027056046",
+    ],
+    [
+        "17547470278401204392652395493257629567430122647780052602260055099082548176109",
+        "3267307766443516854807406958780013980998738845131538525891426000570054122499",
+        "4294316039413196686148682026360688207299892815835214607345873242933090208832",
+        "21834044407556022548751335336833665629625618493651268585727621516658931549868",
+    ],
+    [
+        "796097502645345086526765971900629183411204014476096909190713188811775702591",
+        "18677517781152037729178647830056227274756399836339902366747837458339021416888",
+        "15741796869842653699470138913683764158297506146385863134265252391030035802647",
+        "8719656001826322578975192485517472970823510076435654530270439781573475313189",
+    ],
+    [
+        "11917999202345901007131649098308240314325760215202977893171446229955639820193",
+        "10770700422374958843120847561371142808447519106355626325185915123081925642770",
+        "5951492904307784855533675582534507400844558373539946922481411735365457792971",
+        "19392779394403893099417028484400998468341858046737410722985525734387055442336",
+    ],
+    [
+        "10740312562441119743917724988716079401730704699316328235998686332094325135112",
+        "6243883226538275499838740234890630913394023359786699835984883871593440653668",
+        "152765742033542016554356321498513112413575126197581360041855238461228956552",
+        "2476392386676132730656417655056400687796448160246107110920430871427622931167",
+    ],
+    [
+        "21654030497685727730524230449775259294584811331503707311847104000270740627689",
+        "18430816788387071351572676433167398433827336673975511773144614033416491628542",
+        "1340406241227150367217250088059816243589024825148604603344290797002977989281",
+        "11601970205397403572895117930500395705148290846154978310959832827983903202907",
+    ],
+    [
+        "21706287616483610478987871676661098045480245585743301760421578027901969639857",
+        "9869114771072741034204939407802932463639052054946444095277615319921808058384",
+        "16999638051153277789630004245238379784714989957663649858819390929020715859185",
+        "17656717533800296450606089401659980394574662061295046438453598440577558439896",
+    ],
+    [
+        "11923051737663172149341828716848488659469110900605472825547672376746078037292",
+        "21704041186591666064447741582335077855461316993425656545192964236826247439751",
+        "10212678162298020479210500396794025651113552660472218732790172818065621941666",
+        "14850112636684206106762438377149746688133267403167243842540783400579154138777",
+    ],
+    [
+        "13069992520199056762264083975537936216592959474434910913866549840337518634349",
+        "920362260680785929381080891717756865952022971649812582052413233059828364570",
+        "14387678128705233314531065914309050985828042737565614296905763911988420789434",
+        "5307263253680226829594863776832998788884779375346005592345947035836208618555",
+    ],
+    [
+        "18159577244486558557002126627789120557481866327437722083597393840226935873780",
+        "20389610239800874936832725233287990650799008983884198534264034304971572177199",
+        "11760494755012174217135701028792720053276823186155942161187006009536819031847",
+        "19701003571345670218796418321394011087698565716271108000913582492743146936262",
+    ],
+    [
+        "17380765918388136006808589284461790614124138868123457573492909982087721846405",
+        "16742300692089149149950079536543781881623975224229246833928278901780956817644",
+        "7686138532486732023334805561321602819241232869768209017310718979380489543434",
+        "8721012167992637609609104582054826576113301649096853696590473930355892736626",
+    ],
+    [
+        "12399951024211560025678938635062285935883644267911793345905273576205239460373",
+        "13159941972387817501735601346914497718254407150549537411065991488127472616927",
+        "7308258146202926328055453172782966561383733279590459227619767350161309055395",
+        "9882787901745119960316704283409376785999952473694510385480711094189835711743",
+    ],
+    [
+        "9963124340455790076349117992415171442550378282247788070907739651149679204064",
+        "4210488440350556584082824732279351611844962852338475097718641492109339116148",
+        "9055093442187597077523203812429372459266726809693919745068521892115597829062",
+        "10088835440998638914451630299997833323451782050413474811225079957667663355776",
+    ],
+    [
+        "11409821784240164626867606705422932846753485843041240811426651578227562940755",
+        "19985696797867963947803897065034514663665546177338229752806134086227510138989",
+        "3857386651769849995718605099506810254455059327261851071483349688355763629455",
+        "14008741561925673841266093529617255202613764802781570098255261648425862948045",
+    ],
+    [
+        "7991241863809878427618138061822208277368694472548110231705074186916568200574",
+        "101925770799669832369391666208614883060932878422330343896283855320046661663",
+        "17336829882545892760023344365854521464547929736439477488723150398692254976910",
+        "20916919259577953385292943041028627910924245972773702382519254510166530206493",
+    ],
+    [
+        "9689349962405222511346176947195031790150439110550072337435022390322205491718",
+        "3614941599415612467506294757725712398502654044221865607007828577147596399556",
+        "4138843646784675793364112220210386322903155131991405993679689621160388263998",
+        "6970572476298563705930476600409214005679720455678899240308172287398319945674",
+    ],
+    [
+        "2681059233590579692466596654344524490434934806598826843024224087532789170894",
+        "6772196220119909214796506980935170461217140255083381622082963393643632092317",
+        "11298716289836697416985154115578802005821603718267245668686807953900687577311",
+        "9477885749653366185789924817152890030706597593842529546423556525492124045871",
+    ],
+    [
+        "5419425252067255174566687724191529893470649168899333349307405878630415282106",
+        "1236100450155389110008991189514164036944271046571732357838348099853265935451",
+        "15165809505517933390014872368920078470271629444642077077916109450910186304343",
+        "16723069357425915920192837117782204858829366071882943237668473396723975026905",
+    ],
+    [
+        "3781723304823238282041311984598203813092601326394273530089132695630358346612",
+        "3877210817131422478583292089293035419531115006720513407976642521503917655828",
+        "18288778785643334665607696349644110626670483121710897972939679969152733585582",
+        "15495420713207559051343512003805682385572438645142210638759550864258402356610",
+    ],
+    [
+        "15119123750029189700711075787847466555341636481416216103240587119366240506150",
+        "11315426816296385118998923842301936481836773040737464959688429922679983454982",
+        "4554779067708368684206250019380631397702142865103060126290923342511791751189",
+        "7518797529936055312687165446662466828525723598982165204297380098190600309745",
+    ],
+    [
+        "21274000271620242444761341207873898613663993729356429002456844224117451205099",
+        "12758340679572462242513103350035983373783313600058349712999333199270358630357",
+        "18075460348028450656833105975538495928217184307195200939777466004354274817647",
+        "479287212619403249267464234107497337618767005546786937973104804424270076529",
+    ],
+    [
+        "15623099225915283184952112665460997295735843781600923635586690676724393971754",
+        "7808119454227715457905211900490542186901857058806129577898073532801689565552",
+        "17114888026887817799741290345291364699443741157849200190371808547402946059863",
+        "18234374287120548493264565642741001584228393427091467694122765660247438751703",
+    ],
+];
+
+// TEST VECTORS
+// ===========================================================================
+
+/// Input of the Poseidon hash function and its digest, as decimal field
+/// elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoseidonTestVector {
+    pub input: &'static [&'static str],
+    pub digest: &'static str,
+}
+
+impl PoseidonTestVector {
+    /// Returns the input, as field elements.
+    pub fn input_elements(&self) -> Vec<BaseElement> {
+        self.input
+            .iter()
+            .map(|value| parse_element(value))
+            .collect()
+    }
+
+    /// Returns the digest, as a field element.
+    pub fn digest_element(&self) -> BaseElement {
+        parse_element(self.digest)
+    }
+}
+
+/// Known digests of the `Poseidon(n)` Circom template, and of [hash_elements].
+///
+/// The inputs cover the padding of the sponge (empty inputs, and inputs of one
+/// to two full blocks of [RATE] elements), the two inputs of the Merkle nodes,
+/// and the largest field element. See [verify_test_vectors] to check another
+/// implementation against them.
+pub const TEST_VECTORS: [PoseidonTestVector; 8] = [
+    PoseidonTestVector {
+        input: &[],
+        digest: "1981870606672119377660264262279606533172840272327055764051742319907304045416",
+    },
+    PoseidonTestVector {
+        input: &["0"],
+        digest: "13365220021050842795708223544060405728397869254516816559113043415836722117513",
+    },
+    PoseidonTestVector {
+        input: &["1"],
+        digest: "21727638787980213492947063271318293386793603276507114868246442178525488060672",
+    },
+    PoseidonTestVector {
+        input: &["1", "2"],
+        digest: "2714474942715178258811291715128623786430734185639896604169582388982157792986",
+    },
+    PoseidonTestVector {
+        input: &["1", "2", "3"],
+        digest: "7547049270015853225776610122287733322707527749867827409892783532130281790948",
+    },
+    PoseidonTestVector {
+        input: &["1", "2", "3", "4"],
+        digest: "14849658417026999102191130177149793189251347784352388524628656660126757162382",
+    },
+    PoseidonTestVector {
+        input: &["21888242871839275222246405745257275088548364400416034343698204186575808495616"],
+        digest: "7226265129461069567432037037149696177650566231728491698005626923108680523180",
+    },
+    PoseidonTestVector {
+        input: &["1", "2", "3", "4", "5", "6", "7", "8"],
+        digest: "12319209759806962000323348614895790719496325770671526184976004148912744353497",
+    },
+];
+
+/// Check an implementation of the Poseidon hash function against the
+/// [TEST_VECTORS], and return the first vector it does not match, if any.
+///
+/// Ports of the verifier circuit and alternative exporters can run their hash
+/// function through this check to validate their compatibility with the
+/// Circom templates, before any proof is generated:
+///
+/// ```ignore
+/// assert_eq!(verify_test_vectors(hash_elements), Ok(()));
+/// ```
+pub fn verify_test_vectors<H>(hash: H) -> Result<(), PoseidonTestVector>
+where
+    H: Fn(&[BaseElement]) -> BaseElement,
+{
+    match TEST_VECTORS
+        .iter()
+        .find(|vector| hash(&vector.input_elements()) != vector.digest_element())
+    {
+        Some(vector) => Err(*vector),
+        None => Ok(()),
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Map a hash digest to the field element it encodes.
+pub(crate) fn digest_to_element<D: Digest>(digest: &D) -> BaseElement {
+    BaseElement::from_le_bytes(&digest.as_bytes())
+}
+
+/// Parse a decimal field element of the constants of this module.
+fn parse_element(value: &str) -> BaseElement {
+    let value = U256::from_str_radix(value, 10).expect("the constants should be decimal integers");
+    assert!(
+        value < BaseElement::MODULUS,
+        "the constants should be field elements"
+    );
+    BaseElement::new(value)
+}
@@ -0,0 +1,581 @@
+//! Command line interface of the `winter-circom` executable.
+//!
+//! The executable creates, proves and verifies the circuits of the
+//! [gadgets](crate::gadgets), whose parameters and inputs are read from a JSON
+//! configuration file (`winter-circom.json` by default):
+//!
+//! ```json
+//! {
+//!     "circuit_name": "merkle",
+//!     "circuit": {
+//!         "gadget": "merkle",
+//!         "leaf": 42,
+//!         "index": 5,
+//!         "siblings": [1000, 1001, 1002, 1003, 1004, 1005, 1006]
+//!     }
+//! }
+//! ```
+//!
+//! ```text
+//! winter-circom create    # generate and compile the circuit, and its keys
+//! winter-circom prove     # prove the inputs of the configuration file
+//! winter-circom verify    # verify the Groth16 proof
+//! ```
+//!
+//! The skeleton of a new circuit, with its own AIR, is generated by the
+//! `scaffold` command, without configuration file (see [scaffold]):
+//!
+//! ```text
+//! winter-circom scaffold my_circuit --trace-width 2 --assertions 3 --degrees 1,2
+//! ```
+//!
+//! Each command runs a sequence of steps, announced as they start. With
+//! `--output json` (or `--json`), the announcements are JSON lines, followed by the result of the
+//! command as printed by [print_json_result], so that the progress can be
+//! followed by orchestration systems:
+//!
+//! ```json
+//! { "event": "started", "step": "compile" }
+//! { "event": "finished", "step": "compile", "duration_ms": 81234 }
+//! { "status": "ok", "result": { "circuit_name": "merkle", "steps": ["generate", "compile", "setup"] } }
+//! ```
+//!
+//! The process exits with the [exit code](WinterCircomError::exit_code) of the
+//! error of a failed command.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
+
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use winterfell::{math::fields::f256::BaseElement, Air, TraceInfo};
+
+use crate::{
+    circom_create, circom_prove, circom_verify,
+    gadgets::{
+        merkle::{self, MerkleAir, MerklePath, MerkleProver},
+        rollup::{self, RollupAir, RollupProver, RollupState, Transfer},
+        signature::{self, SecretKey, SignatureAir, SignatureProver},
+    },
+    install_interrupt_handler,
+    pipeline::Stage,
+    scaffold,
+    utils::{print_json_result, ColorChoice, LoggingLevel, WinterCircomError},
+    with_workspace, CircomWorkspace, OodData, ScaffoldOptions, WinterPublicInputs,
+};
+
+/// Arguments of the `winter-circom` executable.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+#[clap(
+    name = "winter-circom",
+    version,
+    about = "Create, prove and verify Winterfell proofs in Groth16 circuits"
+)]
+pub struct Cli {
+    /// Configuration file of the circuit.
+    #[clap(short, long, default_value = "winter-circom.json")]
+    pub config: PathBuf,
+
+    /// Print the progress and the result as text or as JSON lines.
+    #[clap(long, value_name = "FORMAT")]
+    pub output: Option<OutputFormat>,
+
+    /// Print the progress and the result as JSON lines, as with `--output
+    /// json`.
+    #[clap(long)]
+    pub json: bool,
+
+    /// Only print errors.
+    #[clap(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print the outputs of Circom and snarkjs.
+    #[clap(short, long)]
+    pub verbose: bool,
+
+    /// Color the output: auto, always or never. Defaults to the
+    /// `WINTER_CIRCOM_COLOR` environment variable, or auto.
+    #[clap(long)]
+    pub color: Option<ColorChoice>,
+
+    #[clap(subcommand)]
+    pub command: CliCommand,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum CliCommand {
+    /// Generate and compile the Circom code of the circuit, and generate its
+    /// circuit-specific keys.
+    Create,
+
+    /// Prove the inputs of the configuration file with Winterfell, and the
+    /// Winterfell proof with Groth16.
+    Prove,
+
+    /// Verify the Groth16 proof of the circuit.
+    Verify,
+
+    /// Generate the AIR template and the crate of a new circuit.
+    Scaffold {
+        circuit_name: String,
+
+        /// Length of the execution trace.
+        #[clap(long, default_value_t = 128)]
+        trace_length: usize,
+
+        /// Number of columns of the execution trace.
+        #[clap(long)]
+        trace_width: usize,
+
+        /// Number of assertions.
+        #[clap(long)]
+        assertions: usize,
+
+        /// Degrees of the transition constraints, separated by commas.
+        #[clap(long, required = true, value_delimiter = ',')]
+        degrees: Vec<usize>,
+
+        /// Directory of the crate, `examples/example-<circuit_name>` by default.
+        #[clap(long)]
+        crate_dir: Option<PathBuf>,
+    },
+}
+
+/// Output format of the `winter-circom` executable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text, the default.
+    Text,
+
+    /// JSON lines, see the [module documentation](self).
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("the output format must be text or json, got {}", s)),
+        }
+    }
+}
+
+impl Cli {
+    /// Returns whether the progress and the result are printed as JSON lines,
+    /// with `--output json` or `--json`.
+    pub fn json_output(&self) -> bool {
+        self.json || self.output == Some(OutputFormat::Json)
+    }
+
+    /// Returns the logging level of the library functions.
+    ///
+    /// The JSON output is not interleaved with the logs of the library.
+    pub fn logging_level(&self) -> LoggingLevel {
+        match (self.json_output() || self.quiet, self.verbose) {
+            (true, _) => LoggingLevel::Quiet,
+            (false, true) => LoggingLevel::Verbose,
+            (false, false) => LoggingLevel::Default,
+        }
+    }
+}
+
+/// Configuration file of the `winter-circom` executable.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CliConfig {
+    /// Name of the circuit. The AIR template is read from
+    /// `circuits/air/<circuit_name>.circom`, so that the name is usually the
+    /// one of the gadget, possibly namespaced by a [Tenant](crate::Tenant).
+    pub circuit_name: String,
+    pub circuit: GadgetCircuit,
+
+    /// Directories of the [CircomWorkspace], relative to the current
+    /// directory. The ones which are not set are configured by the environment
+    /// variables.
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+}
+
+/// Directories of the [CircomWorkspace] of a [CliConfig].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    pub output_dir: Option<PathBuf>,
+    pub circuits_dir: Option<PathBuf>,
+    pub ptau: Option<PathBuf>,
+}
+
+/// Gadget circuit of a [CliConfig], and the inputs it is proved for.
+///
+/// The field elements are given as integers.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "gadget", rename_all = "snake_case", deny_unknown_fields)]
+pub enum GadgetCircuit {
+    /// Inclusion of a leaf in a Merkle tree, see [merkle]. The depth of the
+    /// tree is the number of siblings.
+    Merkle {
+        leaf: u64,
+        index: usize,
+        siblings: Vec<u64>,
+    },
+
+    /// Signature of a message with a hash-based one-time signature, see
+    /// [signature].
+    Signature {
+        trace_length: usize,
+        message: usize,
+        secret_key: [u64; 2],
+    },
+
+    /// Batch of transfers between the two accounts of a rollup, see
+    /// [rollup]. The balances are the ones before the batch.
+    Rollup {
+        balances: [u64; 2],
+        transfers: Vec<TransferConfig>,
+    },
+}
+
+/// Transfer of a rollup batch, e.g. `{ "a_to_b": 10 }`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferConfig {
+    AToB(u64),
+    BToA(u64),
+}
+
+impl CliConfig {
+    /// Read and validate a configuration file.
+    pub fn read(path: &Path) -> Result<Self, WinterCircomError> {
+        let file = path.to_string_lossy().into_owned();
+        let data = fs::read_to_string(path).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("reading the configuration file {}", file)),
+        })?;
+        Self::parse(&data, &file)
+    }
+
+    /// Parse and validate the contents of a configuration file.
+    pub fn parse(data: &str, file: &str) -> Result<Self, WinterCircomError> {
+        let invalid = |comment: String| WinterCircomError::InvalidConfig {
+            file: String::from(file),
+            comment,
+        };
+        let config: Self = serde_json::from_str(data).map_err(|e| invalid(e.to_string()))?;
+
+        let trace_length = config.circuit.trace_length();
+        if !trace_length.is_power_of_two() || trace_length < TraceInfo::MIN_TRACE_LENGTH {
+            return Err(invalid(format!(
+                "the trace length must be a power of two greater than or equal to {}, got {}",
+                TraceInfo::MIN_TRACE_LENGTH,
+                trace_length
+            )));
+        }
+        if let GadgetCircuit::Signature { message, .. } = config.circuit {
+            if message >= trace_length {
+                return Err(invalid(format!(
+                    "the message must be lower than the trace length, got {}",
+                    message
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns the workspace of the circuit.
+    pub fn workspace(&self) -> CircomWorkspace {
+        let default = CircomWorkspace::from_env();
+        let workspace = &self.workspace;
+        CircomWorkspace::new(
+            workspace
+                .output_dir
+                .clone()
+                .unwrap_or_else(|| default.root_dir().to_path_buf()),
+        )
+        .with_circuits_dir(
+            workspace
+                .circuits_dir
+                .clone()
+                .unwrap_or_else(|| default.circuits_dir().to_path_buf()),
+        )
+        .with_ptau(
+            workspace
+                .ptau
+                .clone()
+                .unwrap_or_else(|| default.ptau().to_path_buf()),
+        )
+    }
+}
+
+impl GadgetCircuit {
+    /// Returns the length of the execution trace of the inputs.
+    pub fn trace_length(&self) -> usize {
+        match self {
+            GadgetCircuit::Merkle { siblings, .. } => siblings.len() + 1,
+            GadgetCircuit::Signature { trace_length, .. } => *trace_length,
+            GadgetCircuit::Rollup { transfers, .. } => transfers.len() + 1,
+        }
+    }
+
+    fn create(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        let trace_length = self.trace_length();
+        match self {
+            GadgetCircuit::Merkle { .. } => circom_create::<MerkleProver, 3>(
+                merkle::proof_options(trace_length - 1),
+                circuit_name,
+                logging_level,
+            ),
+            GadgetCircuit::Signature { .. } => circom_create::<SignatureProver, 4>(
+                signature::proof_options(trace_length),
+                circuit_name,
+                logging_level,
+            ),
+            GadgetCircuit::Rollup { .. } => circom_create::<RollupProver, 3>(
+                rollup::proof_options(trace_length),
+                circuit_name,
+                logging_level,
+            ),
+        }
+    }
+
+    fn prove(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        let trace_length = self.trace_length();
+        match self {
+            GadgetCircuit::Merkle {
+                leaf,
+                index,
+                siblings,
+            } => {
+                let path = MerklePath {
+                    leaf: BaseElement::from(*leaf),
+                    index: *index,
+                    siblings: siblings.iter().copied().map(BaseElement::from).collect(),
+                };
+                let options = merkle::proof_options(trace_length - 1).get_proof_options();
+                let prover = MerkleProver::new(options);
+                let trace = prover.build_trace(&path);
+                circom_prove(prover, trace, circuit_name, logging_level)?;
+            }
+            GadgetCircuit::Signature {
+                message,
+                secret_key: [s1, s2],
+                ..
+            } => {
+                let secret_key = SecretKey {
+                    s1: BaseElement::from(*s1),
+                    s2: BaseElement::from(*s2),
+                };
+                let signature = secret_key.sign(*message, trace_length - 1);
+                let options = signature::proof_options(trace_length).get_proof_options();
+                let prover = SignatureProver::new(options);
+                let trace = prover.build_trace(*message, &signature, trace_length);
+                circom_prove(prover, trace, circuit_name, logging_level)?;
+            }
+            GadgetCircuit::Rollup {
+                balances: [balance_a, balance_b],
+                transfers,
+            } => {
+                let initial_state = RollupState {
+                    balance_a: BaseElement::from(*balance_a),
+                    balance_b: BaseElement::from(*balance_b),
+                };
+                let transfers = transfers
+                    .iter()
+                    .map(|transfer| match transfer {
+                        TransferConfig::AToB(amount) => Transfer::AToB(*amount),
+                        TransferConfig::BToA(amount) => Transfer::BToA(*amount),
+                    })
+                    .collect::<Vec<_>>();
+                let options = rollup::proof_options(trace_length).get_proof_options();
+                let prover = RollupProver::new(options);
+                let trace = prover.build_trace(initial_state, &transfers);
+                circom_prove(prover, trace, circuit_name, logging_level)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the OOD data of the proof of the circuit, as
+    /// [check_ood_frame](crate::check_ood_frame) does, returning an error
+    /// instead of panicking.
+    fn check_ood_frame(&self, circuit_name: &str) -> Result<(), WinterCircomError> {
+        let consistent = match self {
+            GadgetCircuit::Merkle { .. } => is_consistent::<MerkleAir>(circuit_name)?,
+            GadgetCircuit::Signature { .. } => is_consistent::<SignatureAir>(circuit_name)?,
+            GadgetCircuit::Rollup { .. } => is_consistent::<RollupAir>(circuit_name)?,
+        };
+        if !consistent {
+            return Err(WinterCircomError::InvalidPublicSignals {
+                comment: String::from("the OOD constraint evaluations are not correct"),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Run the `winter-circom` executable, returning its exit code.
+pub fn main() -> i32 {
+    let cli = Cli::parse();
+    let color = match cli.color {
+        Some(color) => Ok(color),
+        None => ColorChoice::from_env(),
+    };
+    // an invalid choice fails the command, whose error is printed with the
+    // automatic one
+    color
+        .as_ref()
+        .map_or(ColorChoice::Auto, |color| *color)
+        .apply();
+    let result = color
+        .and_then(|_| install_interrupt_handler())
+        .and_then(|_| run(&cli));
+    if cli.json_output() {
+        return print_json_result(result);
+    }
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            e.exit_code()
+        }
+    }
+}
+
+/// Run a command, returning the name of the circuit and the steps that ran,
+/// or the files written by the `scaffold` command.
+pub fn run(cli: &Cli) -> Result<Value, WinterCircomError> {
+    if let CliCommand::Scaffold {
+        circuit_name,
+        trace_length,
+        trace_width,
+        assertions,
+        degrees,
+        crate_dir,
+    } = &cli.command
+    {
+        let mut options =
+            ScaffoldOptions::new(*trace_length, *trace_width, *assertions, degrees.clone());
+        options.crate_dir = crate_dir.clone();
+        let files = scaffold(circuit_name, &options)?;
+        if cli.logging_level().print_big_steps() {
+            for file in files.iter() {
+                println!("{}", format!("Wrote {}.", file.display()).green());
+            }
+        }
+        return Ok(json!({ "circuit_name": circuit_name, "files": files }));
+    }
+
+    let config = CliConfig::read(&cli.config)?;
+    let circuit_name = config.circuit_name.as_str();
+    let circuit = &config.circuit;
+    let logging_level = cli.logging_level();
+    let mut progress = Progress {
+        json: cli.json_output(),
+        logging_level,
+        steps: Vec::new(),
+    };
+
+    with_workspace(config.workspace(), || {
+        let stages: &[Stage] = match &cli.command {
+            CliCommand::Create => {
+                progress.run("generate", || circuit.create(circuit_name, logging_level))?;
+                &[Stage::Compile, Stage::Setup]
+            }
+            CliCommand::Prove => {
+                progress.run("winterfell_prove", || {
+                    circuit.prove(circuit_name, logging_level)
+                })?;
+                &[Stage::Witness, Stage::Prove]
+            }
+            CliCommand::Verify => {
+                progress.run("check_ood_frame", || circuit.check_ood_frame(circuit_name))?;
+                &[Stage::Verify]
+            }
+            CliCommand::Scaffold { .. } => unreachable!("scaffolding needs no configuration"),
+        };
+        for stage in stages {
+            progress.run(stage_name(*stage), || {
+                stage.run(circuit_name, logging_level)
+            })?;
+        }
+
+        Ok(json!({ "circuit_name": circuit_name, "steps": progress.steps }))
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns whether the OOD data of the proof of the circuit are consistent
+/// with the AIR.
+fn is_consistent<AIR>(circuit_name: &str) -> Result<bool, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    Ok(OodData::of_circuit::<AIR>(circuit_name)?.is_consistent_with::<AIR>())
+}
+
+/// Announcements of the steps of a command.
+struct Progress {
+    json: bool,
+    logging_level: LoggingLevel,
+
+    /// Names of the steps that ran.
+    steps: Vec<&'static str>,
+}
+
+impl Progress {
+    fn run<F>(&mut self, step: &'static str, run: F) -> Result<(), WinterCircomError>
+    where
+        F: FnOnce() -> Result<(), WinterCircomError>,
+    {
+        if self.json {
+            println!("{}", json!({ "event": "started", "step": step }));
+        } else if self.logging_level.print_big_steps() {
+            println!("{}", format!("[{}]", step).bold());
+        }
+
+        let start = Instant::now();
+        let result = run();
+        if self.json {
+            let event = if result.is_ok() { "finished" } else { "failed" };
+            println!(
+                "{}",
+                json!({
+                    "event": event,
+                    "step": step,
+                    "duration_ms": start.elapsed().as_millis() as u64,
+                })
+            );
+        }
+        self.steps.push(step);
+        result
+    }
+}
+
+fn stage_name(stage: Stage) -> &'static str {
+    match stage {
+        Stage::Compile => "compile",
+        Stage::Setup => "setup",
+        Stage::Witness => "witness",
+        Stage::Prove => "groth16_prove",
+        Stage::Verify => "verify",
+    }
+}
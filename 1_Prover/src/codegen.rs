@@ -0,0 +1,646 @@
+use std::{
+    collections::HashMap,
+    fs::{self, create_dir_all, File},
+    io::Write,
+    path::Path,
+};
+
+use colored::Colorize;
+use rug::{ops::Pow, Float};
+use serde::Serialize;
+use serde_json::json;
+use winterfell::{
+    math::{fields::f256::BaseElement, log2, StarkField},
+    Air, AirContext, Prover, TraceInfo,
+};
+
+use crate::{
+    audit::{audited, AuditAction},
+    commitment::num_commitment_elements,
+    lint::check_air_template,
+    manifest::CircuitManifest,
+    packing::PublicInputLayout,
+    scaffold::missing_air_template,
+    signals::Signal,
+    tenant::{split_circuit_name, validate_circuit_name, Tenant},
+    utils::{check_file, LoggingLevel, StageTimer, WinterCircomError},
+    workspace::{circuit_dir, CircomWorkspace},
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Generate Circom code to verify a Winterfell proof with given parameters.
+///
+/// ## Powers of tau phase 1 transcript
+///
+/// This function requires a powers of tau phase 1 transcript that has been
+/// prepared for phase 2 utilization. The file must be named `final.ptau` and
+/// placed in the project root.
+///
+/// ## Transition constraints and assertions
+///
+/// This function requires that a file named `<circuit_name>.circom` be placed in
+/// the `circuits/air/` directory (without the tenant prefix for namespaced
+/// circuit names, see [Tenant](crate::Tenant)). This file must contain two templates:
+///
+/// - `AIRTransitions` returning the degree of all transition constraints.
+/// - `AIRAssertions` defining the assertions.
+///
+/// These definition are similar to the ones defined in the class implementing
+/// the [Air] trait that is needed by the Winterfell prover and verifier.
+///
+/// There are examples already available in the `circuits/air/` directory.
+///
+/// ## Steps
+///
+/// - Generate Circom code to verify a Winterfell proof of given parameters.
+///
+/// The generated code is then compiled by [circom_compile](crate::circom_compile),
+/// and the circuit-specific keys are generated from the powers of tau phase 1
+/// transcript by [groth16_setup](crate::groth16_setup).
+///
+/// Generated files are placed in the `target/circom/<circuit_name>/` directory.
+///
+/// The directories and the transcript can be moved with a
+/// [CircomWorkspace](crate::CircomWorkspace).
+pub fn circom_create<P, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    let parameters = json!({
+        "trace_length": proof_options.trace_length,
+        "trace_width": proof_options.trace_width,
+        "num_assertions": proof_options.num_assertions(),
+        "num_queries": proof_options.num_queries(),
+        "lde_blowup_factor": proof_options.lde_blowup_factor(),
+        "grinding_factor": proof_options.grinding_factor(),
+        "fri_folding_factor": proof_options.fri_folding_factor(),
+        "expose_public_inputs": proof_options.public_inputs_exposed(),
+        "commit_public_signals": proof_options.public_signals_committed(),
+        "data_commitment_max_bytes": proof_options.data_commitment_max_bytes(),
+        "intermediate_verification": proof_options.intermediate_verification_mode(),
+        "target_security_bits": proof_options.target_security_bits(),
+        "snark_scheme": proof_options.snark_scheme_used(),
+        "column_map": proof_options.column_map(),
+    });
+    let circuit_dir = circuit_dir(circuit_name);
+    let artifacts = [
+        circuit_dir.join("verifier.circom"),
+        circuit_dir.join("manifest.json"),
+        circuit_dir.join("parameters.md"),
+        circuit_dir.join("parameters.json"),
+    ];
+
+    audited(
+        circuit_name,
+        AuditAction::Create,
+        parameters,
+        &artifacts,
+        || create_circuit::<P, N>(proof_options, circuit_name, &logging_level),
+    )
+}
+
+fn create_circuit<P, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    // CHECK PROOF OPTIONS
+
+    let warnings = [
+        proof_options.check_lde_blowup_factor()?,
+        proof_options.check_target_security(),
+    ];
+    proof_options.check_column_map()?;
+    for warning in warnings.into_iter().flatten() {
+        if logging_level.print_big_steps() {
+            println!("{}", format!("Warning: {}.", warning).yellow());
+        }
+    }
+
+    // CHECK FOR REQUIRED FILES
+
+    let workspace = CircomWorkspace::current();
+    check_file(
+        workspace.ptau().to_string_lossy().into_owned(),
+        Some("required for the generation of circuit-specific keys"),
+    )?;
+    let template = workspace
+        .air_template(circuit_name)
+        .to_string_lossy()
+        .into_owned();
+    if !Path::new(&template).exists() {
+        return Err(missing_air_template(
+            &template,
+            &proof_options.transition_constraint_degrees,
+            proof_options.num_assertions(),
+            proof_options.scaffold_on_missing_enabled(),
+        ));
+    }
+    check_air_template(
+        &template,
+        &proof_options.transition_constraint_degrees,
+        proof_options.num_assertions(),
+        logging_level,
+    )?;
+    if let Some(tenant) = Tenant::of_circuit(circuit_name)? {
+        tenant.check_quota()?;
+    }
+
+    // CREATE OUTPUT DIRECTORY
+
+    create_dir_all(workspace.circuit_dir(circuit_name)).map_err(|e| {
+        WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("creating Circom output directory")),
+        }
+    })?;
+
+    // GENERATE CIRCOM CODE
+    // ===========================================================================
+
+    let timer = StageTimer::start("Generating Circom code...", logging_level);
+
+    let mut manifest = circuit_manifest(&proof_options, circuit_name);
+    // the hashes of the inputs of the compiled artifacts still tell whether
+    // they are up to date with the new Circom code
+    if let Ok(Some(previous)) = CircuitManifest::read(circuit_name) {
+        manifest.compiled_from = previous.compiled_from;
+        manifest.keys_generated_from = previous.keys_generated_from;
+    }
+
+    generate_circom_main::<P::BaseField, P::Air, N>(proof_options, circuit_name)?;
+    manifest.source_fingerprint = CircuitManifest::source_fingerprint(circuit_name);
+    manifest.write()?;
+
+    timer.finish(&[workspace.circuit_dir(circuit_name).join("verifier.circom")]);
+
+    Ok(())
+}
+
+/// Returns the manifest of a circuit generated with the given options, without
+/// source fingerprint nor hashes of the compiled artifacts.
+pub(crate) fn circuit_manifest<const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+    circuit_name: &str,
+) -> CircuitManifest {
+    CircuitManifest {
+        circuit_name: String::from(circuit_name),
+        trace_length: proof_options.trace_length,
+        trace_width: proof_options.trace_width,
+        fri_tree_depths: proof_options.fri_tree_depths(),
+        num_composition_columns: Some(proof_options.num_composition_columns()),
+        intermediate_verification: proof_options.intermediate_verification_mode(),
+        inner_proof_verified: None,
+        target_security_bits: Some(proof_options.target_security_bits()),
+        public_inputs_exposed: Some(proof_options.public_inputs_exposed()),
+        public_signals_committed: Some(proof_options.public_signals_committed()),
+        circuit_parameters: Some(proof_options.circuit_parameters()),
+        transition_constraint_degrees: Some(proof_options.transition_constraint_degrees.to_vec()),
+        data_commitment_max_bytes: proof_options.data_commitment_max_bytes(),
+        source_fingerprint: None,
+        snark_scheme: proof_options.snark_scheme_used(),
+        column_map: proof_options.column_map(),
+        compiled_from: None,
+        keys_generated_from: None,
+    }
+}
+
+/// Generate a circom main file that defines the parameters for verifying a proof.
+///
+/// The main file is generated in the directory of the circuit in the
+/// [CircomWorkspace] (`target/circom/<circuit_name>/` by default), with the
+/// `verifier.circom` name. The arguments of its `Verify` component are
+/// documented next to it, in `parameters.md` and `parameters.json` (see
+/// [MainArgument]).
+pub fn generate_circom_main<E, AIR, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+) -> Result<(), WinterCircomError>
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    validate_circuit_name(circuit_name)?;
+
+    let workspace = CircomWorkspace::current();
+    let file_contents = circom_main_source::<E, AIR, N>(
+        &proof_options,
+        split_circuit_name(circuit_name).1,
+        &workspace.include_dir(circuit_name),
+    );
+    let arguments = main_arguments::<E, AIR, N>(&proof_options);
+
+    let mut file = File::create(workspace.circuit_dir(circuit_name).join("verifier.circom"))
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("trying to create circom main file")),
+        })?;
+    file.write(file_contents.as_bytes())
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("trying to write to circom main file")),
+        })?;
+
+    // DOCUMENT ARGUMENTS
+
+    let arguments_json =
+        serde_json::to_string_pretty(&arguments).expect("arguments should be serializable");
+    for (file_name, contents) in [
+        (
+            "parameters.md",
+            arguments_markdown(circuit_name, &arguments),
+        ),
+        ("parameters.json", arguments_json),
+    ] {
+        fs::write(
+            workspace.circuit_dir(circuit_name).join(file_name),
+            contents,
+        )
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("trying to write {}", file_name)),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Returns the source of the Circom main file verifying proofs generated with
+/// the given options, for the AIR template `air/<air_name>.circom`.
+///
+/// The templates are included from `circuits_dir`, the path from the directory
+/// of the main file to the circuits directory (or its absolute path).
+pub(crate) fn circom_main_source<E, AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+    air_name: &str,
+    circuits_dir: &str,
+) -> String
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let layout = PublicInputLayout::of::<AIR::PublicInputs>();
+    let main_arguments = main_arguments::<E, AIR, N>(proof_options);
+    let arguments = main_arguments
+        .iter()
+        .enumerate()
+        .map(|(i, argument)| {
+            let separator = if i + 1 < main_arguments.len() {
+                ","
+            } else {
+                ""
+            };
+            format!("{}{} // {}", argument.value, separator, argument.name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    let public_list = if proof_options.public_signals_committed() {
+        format!("[{}]", Signal::PublicCommitment.name())
+    } else {
+        Signal::public_list(proof_options.public_inputs_exposed(), layout.is_packed())
+    };
+
+    format!(
+        "pragma circom 2.0.0;\n\
+        \n\
+        include \"{}/verify.circom\";\n\
+        include \"{}/air/{}.circom\";\n\
+        \n\
+        component main {{public {}}} = Verify(\n    \
+            {}\n\
+        );\n\
+",
+        circuits_dir, circuits_dir, air_name, public_list, arguments
+    )
+}
+
+/// Argument of the `Verify` template instantiated by the Circom main file.
+///
+/// The arguments are documented in the `parameters.md` and `parameters.json`
+/// files written next to `verifier.circom` by
+/// [circom_create](crate::circom_create), from the same values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MainArgument {
+    /// Name of the argument in the `Verify` template.
+    pub name: &'static str,
+
+    /// What the argument is used for by the circuit.
+    pub description: &'static str,
+
+    /// How the argument is derived from the proof options and the AIR.
+    pub formula: &'static str,
+
+    /// Value of the argument, as written in the main file.
+    pub value: String,
+}
+
+/// Returns the arguments of the `Verify` template verifying proofs generated
+/// with the given options, in the order of the template.
+pub(crate) fn main_arguments<E, AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+) -> Vec<MainArgument>
+where
+    E: StarkField,
+    AIR: Air,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    // FRI TREE DEPTHS
+    let fri_tree_depths = proof_options.fri_tree_depths();
+
+    let num_fri_layers = fri_tree_depths.len();
+
+    // remainder-only proofs have no FRI layer, but Circom does not support
+    // empty arrays: pass a single depth, which the circuit does not read when
+    // num_fri_layers is 0
+    let fri_tree_depths = if num_fri_layers == 0 {
+        String::from("[0]")
+    } else {
+        format!(
+            "[{}]",
+            fri_tree_depths
+                .iter()
+                .map(|x| format!("{}", x))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    // COLUMN MAP
+
+    let column_map = format!(
+        "[{}]",
+        proof_options
+            .template_columns()
+            .iter()
+            .map(|column| column.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    // PUBLIC INPUT LAYOUT
+
+    let layout = PublicInputLayout::of::<AIR::PublicInputs>();
+    let (public_input_bits, public_input_slots) = layout.circom_arguments();
+
+    // AIR CONTEXT
+
+    let air_context = AirContext::<E>::new(
+        TraceInfo::new(proof_options.trace_width, proof_options.trace_length),
+        proof_options.transition_constraint_degrees().to_vec(),
+        proof_options.num_assertions(),
+        proof_options.get_proof_options(),
+    );
+
+    // ARGUMENTS
+
+    let argument = |name, description, formula, value: String| MainArgument {
+        name,
+        description,
+        formula,
+        value,
+    };
+    vec![
+        argument(
+            "addicity",
+            "Two-adicity of the base field, bounding the size of the evaluation domains.",
+            "`TWO_ADICITY` of the base field",
+            E::TWO_ADICITY.to_string(),
+        ),
+        argument(
+            "column_map",
+            "Trace column read as each column of the AIR template.",
+            "`column_map` of the proof options, or `[0, ..., trace_width - 1]`",
+            column_map,
+        ),
+        argument(
+            "commitment_index",
+            "Public input the external data is bound to.",
+            "`public_input_index` of the data commitment, or 0 without bound data",
+            proof_options.data_commitment_index().to_string(),
+        ),
+        argument(
+            "domain_offset",
+            "Offset of the LDE domain.",
+            "`GENERATOR` of the base field",
+            E::GENERATOR.to_string(),
+        ),
+        argument(
+            "folding_factor",
+            "Factor by which each FRI layer folds the evaluation domain.",
+            "`fri_folding_factor` of the proof options",
+            proof_options.fri_folding_factor().to_string(),
+        ),
+        argument(
+            "fri_tree_depth",
+            "Depths of the Merkle trees committing to the FRI layers.",
+            "`fri_tree_depths(trace_length * lde_blowup_factor, folding_factor, fri_max_remainder_size)`, or `[0]` without FRI layer",
+            fri_tree_depths,
+        ),
+        argument(
+            "grinding_factor",
+            "Number of leading zeros of the proof-of-work of the query seed.",
+            "`grinding_factor` of the proof options",
+            proof_options.grinding_factor().to_string(),
+        ),
+        argument(
+            "lde_blowup_factor",
+            "Ratio of the LDE domain size to the trace length.",
+            "`lde_blowup_factor` of the proof options",
+            proof_options.lde_blowup_factor().to_string(),
+        ),
+        argument(
+            "num_assertions",
+            "Number of assertions of the `AIRAssertions` template.",
+            "`num_assertions` of the proof options",
+            proof_options.num_assertions.to_string(),
+        ),
+        argument(
+            "num_commitment_elements",
+            "Number of field elements of the bound external data.",
+            "`ceil(data_commitment_max_bytes / 31)`, or 0 without bound data",
+            proof_options
+                .data_commitment_max_bytes()
+                .map_or(0, num_commitment_elements)
+                .to_string(),
+        ),
+        argument(
+            "num_committed_signals",
+            "Number of values hashed into the public commitment.",
+            "`num_transition_constraints + 2 * trace_width`, plus `num_public_inputs` with exposed public inputs, or 0 without public commitment",
+            proof_options
+                .num_committed_signals(AIR::PublicInputs::NUM_PUB_INPUTS)
+                .to_string(),
+        ),
+        argument(
+            "num_composition_columns",
+            "Number of columns of the constraint composition polynomial.",
+            "highest `min_blowup_factor` of the transition constraint degrees",
+            proof_options.num_composition_columns().to_string(),
+        ),
+        argument(
+            "num_draws",
+            "Number of draws of the public coin needed to get `num_queries` distinct query positions.",
+            "`number_of_draws(num_queries, trace_length * folding_factor, target_security_bits)`",
+            number_of_draws(
+                proof_options.num_queries() as u128,
+                (proof_options.trace_length * proof_options.fri_folding_factor()) as u128,
+                proof_options.target_security_bits() as i32,
+            )
+            .to_string(),
+        ),
+        argument(
+            "num_fri_layers",
+            "Number of FRI layers before the remainder.",
+            "length of `fri_tree_depths`",
+            num_fri_layers.to_string(),
+        ),
+        argument(
+            "num_packed_public_inputs",
+            "Number of public signals the public inputs are packed into.",
+            "`PublicInputLayout::num_packed`",
+            layout.num_packed().to_string(),
+        ),
+        argument(
+            "num_pub_coin_seed",
+            "Number of field elements seeding the public coin.",
+            // 2 is the size of the serialized context in f256 field elements
+            "`NUM_PUB_INPUTS + 2`, the public inputs and the serialized context",
+            (AIR::PublicInputs::NUM_PUB_INPUTS + 2).to_string(),
+        ),
+        argument(
+            "num_public_inputs",
+            "Number of public inputs of the AIR.",
+            "`NUM_PUB_INPUTS` of the public inputs",
+            AIR::PublicInputs::NUM_PUB_INPUTS.to_string(),
+        ),
+        argument(
+            "num_queries",
+            "Number of queries of the LDE domain.",
+            "`num_queries` of the proof options",
+            proof_options.num_queries.to_string(),
+        ),
+        argument(
+            "num_transition_constraints",
+            "Number of transition constraints of the `AIRTransitions` template.",
+            "length of `transition_constraint_degrees`",
+            air_context.num_transition_constraints().to_string(),
+        ),
+        argument(
+            "public_input_bits",
+            "Bit size of each public input in its packed public signal.",
+            "`PublicInputLayout::of::<PublicInputs>()`",
+            public_input_bits,
+        ),
+        argument(
+            "public_input_slots",
+            "Packed public signal of each public input.",
+            "`PublicInputLayout::of::<PublicInputs>()`",
+            public_input_slots,
+        ),
+        argument(
+            "trace_length",
+            "Number of steps of the execution trace.",
+            "`trace_length` of the proof options",
+            proof_options.trace_length.to_string(),
+        ),
+        argument(
+            "trace_width",
+            "Number of columns of the execution trace.",
+            "`trace_width` of the proof options",
+            proof_options.trace_width.to_string(),
+        ),
+        argument(
+            "tree_depth",
+            "Depth of the Merkle trees committing to the trace and the constraint evaluations.",
+            "`log2(trace_length * folding_factor)`",
+            log2(proof_options.trace_length * proof_options.fri_folding_factor()).to_string(),
+        ),
+    ]
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the contents of the `parameters.md` file documenting the arguments
+/// of the main component of a circuit.
+fn arguments_markdown(circuit_name: &str, arguments: &[MainArgument]) -> String {
+    let mut markdown = format!(
+        "# Parameters of `{}`\n\n\
+        Arguments of the `Verify` component of `verifier.circom`, generated with it \
+        by `circom_create`.\n\n\
+        | Argument | Value | Formula | Description |\n\
+        | --- | --- | --- | --- |\n",
+        circuit_name
+    );
+    for argument in arguments {
+        markdown.push_str(&format!(
+            "| `{}` | `{}` | {} | {} |\n",
+            argument.name, argument.value, argument.formula, argument.description
+        ));
+    }
+    markdown
+}
+
+fn number_of_draws(num_queries: u128, lde_domain_size: u128, security: i32) -> u128 {
+    let mut num_draws: u128 = 0;
+    let precision: u32 = security as u32 + 2;
+
+    while {
+        let st = step(
+            0,
+            num_draws,
+            &mut HashMap::new(),
+            num_queries,
+            lde_domain_size,
+            security,
+        );
+        num_draws += 1;
+        1 - st > Float::with_val(precision, 2_f64).pow(-security)
+    } {}
+
+    num_draws
+}
+
+fn step(
+    x: u128,
+    n: u128,
+    memo: &mut HashMap<(u128, u128), Float>,
+    num_queries: u128,
+    lde_domain_size: u128,
+    security: i32,
+) -> Float {
+    let precision: u32 = security as u32 + 2;
+    match memo.get(&(x, n)) {
+        Some(val) => val.clone(),
+        None => {
+            let num: Float;
+            if x == num_queries {
+                num = Float::with_val(precision, 1f64);
+            } else if n == 0 {
+                num = Float::with_val(precision, 0f64);
+            } else {
+                let a = step(x + 1, n - 1, memo, num_queries, lde_domain_size, security);
+                let b = step(x, n - 1, memo, num_queries, lde_domain_size, security);
+                num = Float::with_val(precision, lde_domain_size - x)
+                    / (Float::with_val(precision, lde_domain_size))
+                    * a
+                    + Float::with_val(precision, x) / (Float::with_val(precision, lde_domain_size))
+                        * b;
+            }
+            memo.insert((x, n), num.clone());
+            num
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Commitments to external data bound to the public inputs of a proof.
+//!
+//! External data (e.g. calldata or a file) can be bound to a proof by
+//! committing to it off-chain, using the commitment as one of the public inputs
+//! of the AIR, and generating the circuit with the
+//! [bind_data_commitment](crate::WinterCircomProofOptions::bind_data_commitment)
+//! option. The generated circuit then checks that the committed public input is
+//! the hash of the data provided at proving time, so that a valid proof attests
+//! that the prover had the data available.
+//!
+//! ## Commitment scheme
+//!
+//! The data is split into chunks of [CHUNK_BYTES] bytes, each mapped to a field
+//! element (little-endian), and zero-padded to the maximum size of the data
+//! supported by the circuit. The opening of the commitment is the byte length
+//! of the data followed by the chunks, and the commitment is its
+//! [hash](crate::circom_poseidon::hash_elements), as computed by the `Verify`
+//! Circom template.
+
+use std::{fs, path::Path};
+
+use winterfell::math::{fields::f256::BaseElement, FieldElement};
+
+use crate::{circom_poseidon::hash_elements, utils::WinterCircomError};
+
+/// Number of bytes of the data mapped to each field element.
+///
+/// Chunks are one byte shorter than field elements, so that any chunk is a
+/// canonical element of the field.
+pub const CHUNK_BYTES: usize = 31;
+
+/// Returns the number of chunks of data of at most `max_bytes` bytes.
+pub const fn num_commitment_elements(max_bytes: usize) -> usize {
+    (max_bytes + CHUNK_BYTES - 1) / CHUNK_BYTES
+}
+
+/// Returns the opening of the commitment to the given data, i.e. the byte
+/// length of the data followed by its zero-padded chunks.
+///
+/// Returns an error if the data is longer than `max_bytes`.
+pub fn commitment_opening(
+    data: &[u8],
+    max_bytes: usize,
+) -> Result<Vec<BaseElement>, WinterCircomError> {
+    if data.len() > max_bytes {
+        return Err(WinterCircomError::CommitmentDataTooLarge {
+            size: data.len(),
+            max_bytes,
+        });
+    }
+
+    let mut opening = vec![BaseElement::from(data.len() as u64)];
+    opening.extend(data.chunks(CHUNK_BYTES).map(|chunk| {
+        let mut bytes = [0; 32];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        BaseElement::from_le_bytes(&bytes)
+    }));
+    opening.resize(num_commitment_elements(max_bytes) + 1, BaseElement::ZERO);
+    Ok(opening)
+}
+
+/// Commit to the given data, for a circuit binding at most `max_bytes` bytes.
+pub fn commit(data: &[u8], max_bytes: usize) -> Result<BaseElement, WinterCircomError> {
+    Ok(hash_elements(&commitment_opening(data, max_bytes)?))
+}
+
+/// Commit to the contents of a file, for a circuit binding at most `max_bytes`
+/// bytes.
+pub fn commit_file<P: AsRef<Path>>(
+    path: P,
+    max_bytes: usize,
+) -> Result<BaseElement, WinterCircomError> {
+    let data = fs::read(path.as_ref()).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", path.as_ref().display())),
+    })?;
+    commit(&data, max_bytes)
+}
@@ -0,0 +1,129 @@
+use std::{env, str::FromStr, sync::Once};
+
+use colored::Colorize;
+
+use crate::utils::{LoggingLevel, WinterCircomError};
+
+static CONFIGURE: Once = Once::new();
+
+/// Number of threads generating the Winterfell proofs of
+/// [circom_prove](crate::circom_prove).
+///
+/// With the `concurrent` feature, Winterfell extends the trace, evaluates the
+/// constraints and builds the Merkle trees on the threads of the global rayon
+/// thread pool. The proof-of-work of the query seed is searched on the same
+/// threads, each of them trying nonces until one finds a seed with
+/// `grinding_factor` leading zeros, so that high grinding factors cost the
+/// prover a fraction of their single-threaded time. The nonces are counted
+/// from 1 by Winterfell, which does not take a source of randomness for the
+/// search. The search is internal to [Prover::prove](winterfell::Prover::prove),
+/// which binds the nonce it finds into the proof: this crate only sizes its
+/// thread pool, and a grinding driver of its own or nonces drawn from a
+/// source of randomness of the prover would require changes to Winterfell.
+/// Without the feature, proofs are generated on the calling thread.
+///
+/// ## Configuration
+///
+/// The number of threads is read from the `WINTER_CIRCOM_PROVER_THREADS`
+/// environment variable, and defaults to the rayon default, i.e. the number of
+/// CPUs or `RAYON_NUM_THREADS`. Proofs are not generated if the variable is not
+/// a positive integer. The global thread pool is built once per
+/// process: the configuration of the first proof applies to the following
+/// ones, and is ignored if the application built the pool itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Concurrency {
+    num_threads: Option<usize>,
+}
+
+impl Concurrency {
+    pub const THREADS_VARIABLE: &'static str = "WINTER_CIRCOM_PROVER_THREADS";
+
+    /// Returns the configuration of a prover running on `num_threads` threads.
+    ///
+    /// Panics if `num_threads` is 0.
+    pub fn new(num_threads: usize) -> Self {
+        assert!(num_threads > 0, "the prover needs at least one thread");
+        Self {
+            num_threads: Some(num_threads),
+        }
+    }
+
+    /// Returns the configuration of the environment variable, if any.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_PROVER_THREADS` is not a positive integer.
+    pub fn from_env() -> Result<Self, WinterCircomError> {
+        match env::var(Concurrency::THREADS_VARIABLE) {
+            Ok(value) => value
+                .parse()
+                .map_err(|comment| WinterCircomError::InvalidEnvVariable {
+                    variable: String::from(Concurrency::THREADS_VARIABLE),
+                    comment,
+                }),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Returns the number of threads, or `None` for the rayon default.
+    pub fn num_threads(&self) -> Option<usize> {
+        self.num_threads
+    }
+
+    /// Build the global thread pool of the prover with the configured number
+    /// of threads, if it is not built yet.
+    ///
+    /// Warnings are printed if the pool was already built by the application,
+    /// or if threads are configured without the `concurrent` feature.
+    pub(crate) fn configure(&self, logging_level: &LoggingLevel) {
+        let num_threads = match self.num_threads {
+            Some(num_threads) => num_threads,
+            None => return,
+        };
+        CONFIGURE.call_once(|| {
+            if let Err(warning) = build_thread_pool(num_threads) {
+                if logging_level.print_big_steps() {
+                    println!("{}", format!("Warning: {}.", warning).yellow());
+                }
+            }
+        });
+    }
+}
+
+impl FromStr for Concurrency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse() {
+            Ok(num_threads) if num_threads > 0 => Ok(Self::new(num_threads)),
+            _ => Err(format!(
+                "expected a positive number of threads, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+#[cfg(feature = "concurrent")]
+fn build_thread_pool(num_threads: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build_global()
+        .map_err(|e| {
+            format!(
+                "{} is ignored, the thread pool is already built: {}",
+                Concurrency::THREADS_VARIABLE,
+                e
+            )
+        })
+}
+
+#[cfg(not(feature = "concurrent"))]
+fn build_thread_pool(_num_threads: usize) -> Result<(), String> {
+    Err(format!(
+        "{} is ignored, proofs are generated on a single thread without the concurrent feature",
+        Concurrency::THREADS_VARIABLE
+    ))
+}
@@ -0,0 +1,669 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    io::{self, ErrorKind},
+};
+
+use winterfell::TransitionConstraintDegree;
+
+use crate::{
+    tenant::validate_circuit_name,
+    utils::{Colorize, LoggingLevel, WinterCircomError},
+    workspace::circuit_dir,
+    CircuitManifest, CircuitParameters,
+};
+
+/// Part of the `Verify` template that constraints are attributed to by
+/// [constraint_breakdown].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConstraintCategory {
+    /// Merkle authentication paths of the trace and constraint queries.
+    Merkle,
+
+    /// FRI folding and the Merkle authentication paths of the FRI layers.
+    Fri,
+
+    /// Poseidon hashing of the public coin and of the external data commitment.
+    Poseidon,
+
+    /// DEEP composition of the queried evaluations and domain arithmetic.
+    Composition,
+
+    /// Evaluation of the AIR transition constraints and assertions during the
+    /// out-of-domain consistency check.
+    Air,
+
+    /// Constraints of other components (e.g. public input unpacking).
+    Other,
+}
+
+impl ConstraintCategory {
+    /// Returns the category of a component instantiated by the `Verify`
+    /// template.
+    pub fn of_component(component: &str) -> Self {
+        match component {
+            "traceCommitmentVerifier" | "constraintCommitmentVerifier" => {
+                ConstraintCategory::Merkle
+            }
+            "fri" => ConstraintCategory::Fri,
+            "pub_coin" | "commitment" => ConstraintCategory::Poseidon,
+            "main" | "addicity_pow" | "multi_sel" | "x_pow_domain_offset" | "z_m" => {
+                ConstraintCategory::Composition
+            }
+            "ood" => ConstraintCategory::Air,
+            _ => ConstraintCategory::Other,
+        }
+    }
+}
+
+/// Number of constraints of a compiled circuit, broken down by component of the
+/// `Verify` template.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintBreakdown {
+    pub total: usize,
+
+    /// Number of constraints per component instantiated by the `Verify`
+    /// template (e.g. `fri` or `traceCommitmentVerifier`), with array indices
+    /// removed. Constraints on the signals of the main component itself are
+    /// counted under `main`.
+    pub components: BTreeMap<String, usize>,
+}
+
+impl ConstraintBreakdown {
+    /// Returns the number of constraints per [ConstraintCategory].
+    pub fn categories(&self) -> BTreeMap<ConstraintCategory, usize> {
+        let mut categories = BTreeMap::new();
+        for (component, count) in self.components.iter() {
+            *categories
+                .entry(ConstraintCategory::of_component(component))
+                .or_insert(0) += count;
+        }
+        categories
+    }
+
+    /// Print the breakdown to stdout, one line per component, from the most
+    /// expensive one.
+    pub fn print(&self) {
+        let mut components = self.components.iter().collect::<Vec<_>>();
+        components.sort_by(|a, b| b.1.cmp(a.1));
+
+        println!("{}", format!("{} constraints", self.total).green());
+        for (component, count) in components {
+            println!(
+                "    {:<32} {:>12} ({:.1}%)",
+                component,
+                count,
+                100.0 * *count as f64 / self.total.max(1) as f64
+            );
+        }
+    }
+}
+
+/// Returns the number of constraints of a compiled circuit, broken down by
+/// component of the `Verify` template, to see what to optimize.
+///
+/// This reads the `verifier.r1cs` and `verifier.sym` files of the
+/// `target/circom/<circuit_name>/` directory, generated by compiling the
+/// circuit with the `--r1cs` and `--sym` options of Circom. Each constraint is
+/// attributed to the deepest component among those of its signals, as the
+/// signals of a sub-component are also referenced by the constraints of its
+/// parent (e.g. the outputs of a Poseidon hash in a Merkle opening).
+pub fn constraint_breakdown(circuit_name: &str) -> Result<ConstraintBreakdown, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    let r1cs = read_artifact(&dir.join("verifier.r1cs").to_string_lossy())?;
+    let sym = read_artifact(&dir.join("verifier.sym").to_string_lossy())?;
+
+    let wire_names = parse_sym(&String::from_utf8_lossy(&sym));
+    let r1cs = parse_r1cs(&r1cs).map_err(parse_error)?;
+
+    let mut breakdown = ConstraintBreakdown {
+        total: r1cs.constraints.len(),
+        ..Default::default()
+    };
+    for constraint in r1cs.constraints.iter() {
+        let component = constraint
+            .iter()
+            .flatten()
+            .filter_map(|(wire, _)| wire_names.get(wire))
+            .max_by_key(|name| name.matches('.').count())
+            .map_or("main", |name| top_level_component(name));
+        *breakdown
+            .components
+            .entry(String::from(component))
+            .or_insert(0) += 1;
+    }
+
+    Ok(breakdown)
+}
+
+/// Estimated number of constraints of a compiled circuit spent on a transition
+/// constraint of its AIR, see [transition_constraint_costs].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransitionConstraintCost {
+    /// Index of the constraint, in the order of the `AIRTransitions` template.
+    pub constraint: usize,
+
+    /// Declared degree of the constraint.
+    pub degree: usize,
+
+    /// Constraints of the out-of-domain consistency check which adjust the
+    /// degree of the constraint and add it to the composition.
+    pub evaluation: usize,
+
+    /// Constraints of the composition columns needed by the degree of this
+    /// constraint only, i.e. which lowering its degree to the highest degree of
+    /// the other constraints would save.
+    pub composition: usize,
+}
+
+impl TransitionConstraintCost {
+    pub fn total(&self) -> usize {
+        self.evaluation + self.composition
+    }
+}
+
+/// Returns the estimated number of constraints of a compiled circuit spent on
+/// each transition constraint of its AIR, to see which ones to simplify.
+///
+/// The transition constraints are evaluated by the Winterfell verifier, out of
+/// the circuit (see [check_ood_frame](crate::check_ood_frame)), but each of them
+/// costs constraints in two places:
+///
+/// - the out-of-domain consistency check raises `z` to the power adjusting its
+/// degree, and adds it to the composition with its coefficients. These
+/// constraints are counted exactly, from the signals of the `ood` component.
+/// - the number of composition columns is set by the highest degree of the
+/// constraints (see
+/// [num_composition_columns](crate::WinterCircomProofOptions::num_composition_columns)),
+/// and each column is hashed into the Merkle leaves of the constraint
+/// commitment and combined into the DEEP composition at every query. The
+/// constraints of the columns are counted, and attributed to the constraints
+/// of the highest degrees by the columns that only they need. This part is an
+/// estimate: it assumes that every column costs as much.
+///
+/// This reads the `verifier.r1cs` and `verifier.sym` files of the circuit (see
+/// [constraint_breakdown]), and the degrees recorded in its manifest.
+pub fn transition_constraint_costs(
+    circuit_name: &str,
+) -> Result<Vec<TransitionConstraintCost>, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let manifest = CircuitManifest::read(circuit_name)?;
+    let degrees = manifest
+        .as_ref()
+        .and_then(|manifest| manifest.transition_constraint_degrees.clone())
+        .ok_or_else(|| WinterCircomError::FileNotFound {
+            file: CircuitManifest::path(circuit_name),
+            comment: Some(String::from(
+                "the transition constraint degrees are recorded by circom_create",
+            )),
+        })?;
+    let columns = |degrees: &mut dyn Iterator<Item = usize>| {
+        degrees
+            .map(|degree| TransitionConstraintDegree::new(degree).min_blowup_factor())
+            .max()
+            .unwrap_or(1)
+    };
+    let num_columns = manifest
+        .and_then(|manifest| manifest.num_composition_columns)
+        .unwrap_or_else(|| columns(&mut degrees.iter().copied()));
+
+    let dir = circuit_dir(circuit_name);
+    let r1cs = read_artifact(&dir.join("verifier.r1cs").to_string_lossy())?;
+    let sym = read_artifact(&dir.join("verifier.sym").to_string_lossy())?;
+    let wire_names = parse_sym(&String::from_utf8_lossy(&sym));
+    let r1cs = parse_r1cs(&r1cs).map_err(parse_error)?;
+
+    let mut evaluations = vec![0; degrees.len()];
+    let mut column_constraints = 0;
+    for constraint in r1cs.constraints.iter() {
+        let names = constraint
+            .iter()
+            .flatten()
+            .filter_map(|(wire, _)| wire_names.get(wire))
+            .collect::<Vec<_>>();
+        if let Some(i) = names
+            .iter()
+            .find_map(|name| transition_constraint_of(name))
+            .filter(|i| *i < degrees.len())
+        {
+            evaluations[i] += 1;
+        } else if names.iter().any(|name| is_composition_column_signal(name)) {
+            column_constraints += 1;
+        }
+    }
+    let column_cost = column_constraints / num_columns.max(1);
+
+    Ok(degrees
+        .iter()
+        .enumerate()
+        .map(|(i, degree)| {
+            // columns needed by the other constraints, or by a constraint of
+            // degree 1 if there is none
+            let others = columns(
+                &mut degrees
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, degree)| *degree)
+                    .chain([1]),
+            );
+            TransitionConstraintCost {
+                constraint: i,
+                degree: *degree,
+                evaluation: evaluations[i],
+                composition: num_columns.saturating_sub(others) * column_cost,
+            }
+        })
+        .collect())
+}
+
+/// Returns the degrees of the transition constraints compiled in a circuit, in
+/// the order of the `AIRTransitions` template.
+///
+/// The `OodConsistencyCheck` template adjusts the degree of each transition
+/// constraint by the `transition_degree` outputs of `AIRTransitions`, which
+/// Circom compiles to constants of the `verifier.r1cs` file. The degrees are
+/// recovered from the constraints fixing these outputs or, when the optimizer
+/// removed them, the exponents of the degree adjustments, using the
+/// `verifier.sym` file to find the signals. A degree is `None` if it cannot be
+/// recovered, e.g. when all of these signals were substituted.
+///
+/// The declared degrees are only a precaution of the Winterfell prover, but a
+/// circuit compiled with wrong degrees adjusts the constraints to the wrong
+/// degree and is unsound. [circom_prove](crate::circom_prove) therefore checks
+/// the degrees of compiled circuits against those recorded in the
+/// [CircuitManifest], and fails with a
+/// [TransitionDegreeMismatch](WinterCircomError::TransitionDegreeMismatch)
+/// error if they differ.
+pub fn transition_degrees(circuit_name: &str) -> Result<Vec<Option<usize>>, WinterCircomError> {
+    let parameters = CircuitParameters::of_circuit(circuit_name)?;
+    let num_composition_columns =
+        CircuitManifest::read(circuit_name)?.and_then(|manifest| manifest.num_composition_columns);
+    compiled_degrees(circuit_name, &parameters, num_composition_columns)
+}
+
+/// Check the degrees of the transition constraints of a compiled circuit
+/// against the degrees declared in its manifest, see [transition_degrees].
+///
+/// Circuits generated by earlier versions of this crate, which did not record
+/// the degrees, and circuits which are not compiled yet are not checked.
+pub(crate) fn check_transition_degrees(
+    manifest: &CircuitManifest,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let (declared, parameters) = match (
+        &manifest.transition_constraint_degrees,
+        &manifest.circuit_parameters,
+    ) {
+        (Some(declared), Some(parameters)) => (declared, parameters),
+        _ => return Ok(()),
+    };
+    let circuit_name = &manifest.circuit_name;
+    let dir = circuit_dir(circuit_name);
+    if !dir.join("verifier.r1cs").exists() || !dir.join("verifier.sym").exists() {
+        return Ok(());
+    }
+
+    let compiled = compiled_degrees(circuit_name, parameters, manifest.num_composition_columns)?;
+    for (constraint, (declared, compiled)) in declared.iter().zip(compiled).enumerate() {
+        match compiled {
+            Some(compiled) if compiled != *declared => {
+                return Err(WinterCircomError::TransitionDegreeMismatch {
+                    circuit_name: circuit_name.clone(),
+                    constraint,
+                    declared: *declared,
+                    compiled,
+                })
+            }
+            None if logging_level.print_big_steps() => println!(
+                "{}",
+                format!(
+                    "Warning: the degree of transition constraint {} could not be recovered \
+                    from the compiled circuit {}.",
+                    constraint, circuit_name
+                )
+                .yellow()
+            ),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the degrees of the transition constraints compiled in a circuit,
+/// see [transition_degrees].
+fn compiled_degrees(
+    circuit_name: &str,
+    parameters: &CircuitParameters,
+    num_composition_columns: Option<usize>,
+) -> Result<Vec<Option<usize>>, WinterCircomError> {
+    let dir = circuit_dir(circuit_name);
+    let r1cs = read_artifact(&dir.join("verifier.r1cs").to_string_lossy())?;
+    let sym = read_artifact(&dir.join("verifier.sym").to_string_lossy())?;
+
+    let wires = parse_sym(&String::from_utf8_lossy(&sym))
+        .into_iter()
+        .map(|(wire, name)| (name, wire))
+        .collect::<HashMap<_, _>>();
+    let r1cs = parse_r1cs(&r1cs).map_err(parse_error)?;
+    let linear_constraints = r1cs.linear_constraints();
+    let fixed_signal = |name: &str| {
+        wires
+            .get(name)
+            .and_then(|wire| r1cs.fixed_value(&linear_constraints, *wire))
+    };
+
+    let trace_length = parameters.trace_length as i128;
+    let degree = |constraint: usize| {
+        let output = format!("main.ood.AIR.transition_degree[{}]", constraint);
+        let degree = fixed_signal(&output).or_else(|| {
+            // the exponent is (ce_blowup_factor + 1) * trace_length - 2
+            // - transition_degree * (trace_length - 1)
+            let ce_blowup_factor = num_composition_columns? as i128;
+            let adjustment = format!("main.ood.transition_deg_adjustment[{}]", constraint);
+            let exponent = fixed_signal(&format!("{}.exp", adjustment))
+                .or_else(|| fixed_signal(&format!("{}.n2b.in", adjustment)))
+                .or_else(|| {
+                    let prefix = format!("{}.n2b.out[", adjustment);
+                    let bits = wires
+                        .iter()
+                        .filter_map(|(name, wire)| {
+                            let bit = name.strip_prefix(&prefix)?.strip_suffix(']')?;
+                            Some((*wire, bit.parse().ok()?))
+                        })
+                        .collect();
+                    r1cs.fixed_bits_value(&linear_constraints, &bits)
+                })?;
+            divide(
+                (ce_blowup_factor + 1) * trace_length - 2 - exponent,
+                trace_length - 1,
+            )
+        })?;
+        usize::try_from(degree).ok()
+    };
+
+    Ok((0..parameters.num_transition_constraints)
+        .map(degree)
+        .collect())
+}
+
+fn read_artifact(path: &str) -> Result<Vec<u8>, WinterCircomError> {
+    fs::read(path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => WinterCircomError::FileNotFound {
+            file: String::from(path),
+            comment: Some(String::from("compile the circuit with --r1cs --sym")),
+        },
+        _ => WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("reading {}", path)),
+        },
+    })
+}
+
+/// Returns the names of the wires of a circuit, from its `.sym` file.
+///
+/// Each line of the file is of the form `label,wire,component,name`, where
+/// `wire` is -1 for signals removed by the optimizer.
+fn parse_sym(sym: &str) -> HashMap<u32, String> {
+    sym.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, ',');
+            let wire = fields.nth(1)?.parse::<i64>().ok()?;
+            let name = fields.nth(1)?;
+            (wire > 0).then(|| (wire as u32, String::from(name)))
+        })
+        .collect()
+}
+
+/// Returns the prime and the constraints of an `.r1cs` file.
+///
+/// See the [r1cs format](https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md).
+fn parse_r1cs(r1cs: &[u8]) -> io::Result<R1cs<'_>> {
+    let mut reader = Reader {
+        bytes: r1cs,
+        pos: 0,
+    };
+    if reader.take(4)? != b"r1cs" {
+        return Err(invalid_data("not an r1cs file"));
+    }
+    reader.u32()?; // version
+
+    // sections may appear in any order, the header is needed first
+    let mut sections = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let section_type = reader.u32()?;
+        let size = reader.u64()? as usize;
+        sections.insert(section_type, reader.take(size)?);
+    }
+
+    let header = sections
+        .get(&1)
+        .copied()
+        .ok_or_else(|| invalid_data("missing header section"))?;
+    let mut reader = Reader {
+        bytes: header,
+        pos: 0,
+    };
+    let field_size = reader.u32()? as usize;
+    let prime = reader.take(field_size)?;
+    reader.take(4 * 4 + 8)?; // wire, output, input and label counts
+    let num_constraints = reader.u32()?;
+
+    let section = sections
+        .get(&2)
+        .copied()
+        .ok_or_else(|| invalid_data("missing constraints section"))?;
+    let mut reader = Reader {
+        bytes: section,
+        pos: 0,
+    };
+    let mut constraints = Vec::with_capacity(num_constraints as usize);
+    for _ in 0..num_constraints {
+        // linear combinations A, B and C
+        let mut constraint: [LinearCombination; 3] = Default::default();
+        for combination in constraint.iter_mut() {
+            for _ in 0..reader.u32()? {
+                let wire = reader.u32()?;
+                combination.push((wire, reader.take(field_size)?));
+            }
+        }
+        constraints.push(constraint);
+    }
+
+    Ok(R1cs { prime, constraints })
+}
+
+/// Linear combination of an `.r1cs` constraint, as pairs of wires and
+/// little-endian coefficients.
+type LinearCombination<'a> = Vec<(u32, &'a [u8])>;
+
+/// Constraints `A * B - C = 0` of an `.r1cs` file.
+struct R1cs<'a> {
+    prime: &'a [u8],
+    constraints: Vec<[LinearCombination<'a>; 3]>,
+}
+
+impl<'a> R1cs<'a> {
+    /// Returns the combinations `C` of the linear constraints `C = 0`.
+    fn linear_constraints(&self) -> Vec<&LinearCombination<'a>> {
+        self.constraints
+            .iter()
+            .filter(|[a, b, _]| a.is_empty() && b.is_empty())
+            .map(|[_, _, c]| c)
+            .collect()
+    }
+
+    /// Returns the value of a wire fixed by one of the linear constraints, if
+    /// it is a small integer.
+    fn fixed_value(&self, linear_constraints: &[&LinearCombination], wire: u32) -> Option<i128> {
+        linear_constraints.iter().find_map(|combination| {
+            if !combination.iter().any(|(w, _)| *w == wire)
+                || combination.iter().any(|(w, _)| *w != 0 && *w != wire)
+            {
+                return None;
+            }
+            let (constant, coefficient) = self.coefficients(combination, wire)?;
+            divide(-constant, coefficient)
+        })
+    }
+
+    /// Returns the value of the integer decomposed into the given bit wires,
+    /// the output of a `Num2Bits` component, if it is fixed by one of the
+    /// linear constraints.
+    fn fixed_bits_value(
+        &self,
+        linear_constraints: &[&LinearCombination],
+        bits: &BTreeMap<u32, usize>,
+    ) -> Option<i128> {
+        linear_constraints.iter().find_map(|combination| {
+            let wires = combination
+                .iter()
+                .map(|(w, _)| *w)
+                .filter(|w| *w != 0)
+                .collect::<BTreeSet<_>>();
+            if wires.is_empty() || !wires.iter().eq(bits.keys()) {
+                return None;
+            }
+            // coefficient of the bit k is scale * 2^k
+            let mut scale = None;
+            let mut constant = 0;
+            for (wire, value) in combination.iter() {
+                let value = small_integer(value, self.prime)?;
+                match bits.get(wire) {
+                    None => constant = value,
+                    Some(k) => {
+                        let bit_scale = divide(value, 1i128.checked_shl(*k as u32)?)?;
+                        if *scale.get_or_insert(bit_scale) != bit_scale {
+                            return None;
+                        }
+                    }
+                }
+            }
+            divide(-constant, scale?)
+        })
+    }
+
+    /// Returns the constant term and the coefficient of a wire of a linear
+    /// combination, if they are small integers.
+    fn coefficients(&self, combination: &LinearCombination, wire: u32) -> Option<(i128, i128)> {
+        let mut constant = 0;
+        let mut coefficient = 0;
+        for (w, value) in combination {
+            let value = small_integer(value, self.prime)?;
+            if *w == 0 {
+                constant = value;
+            } else if *w == wire {
+                coefficient = value;
+            }
+        }
+        Some((constant, coefficient))
+    }
+}
+
+/// Returns a field element of an `.r1cs` file as a signed integer, if it or its
+/// opposite is smaller than 2^120.
+fn small_integer(value: &[u8], prime: &[u8]) -> Option<i128> {
+    let fits = |bytes: &[u8]| bytes.iter().skip(15).all(|b| *b == 0);
+    let to_i128 = |bytes: &[u8]| {
+        let mut le = [0u8; 16];
+        let len = bytes.len().min(15);
+        le[..len].copy_from_slice(&bytes[..len]);
+        i128::from_le_bytes(le)
+    };
+
+    if fits(value) {
+        return Some(to_i128(value));
+    }
+    // prime - value, in little-endian
+    let mut opposite = Vec::with_capacity(prime.len());
+    let mut borrow = 0;
+    for (i, p) in prime.iter().enumerate() {
+        let v = *value.get(i).unwrap_or(&0) as i16;
+        let mut digit = *p as i16 - v - borrow;
+        borrow = (digit < 0) as i16;
+        if digit < 0 {
+            digit += 256;
+        }
+        opposite.push(digit as u8);
+    }
+    (borrow == 0 && fits(&opposite)).then(|| -to_i128(&opposite))
+}
+
+/// Returns `a / b` if the division is exact.
+fn divide(a: i128, b: i128) -> Option<i128> {
+    (b != 0 && a % b == 0).then(|| a / b)
+}
+
+/// Returns the component instantiated by the main component that a signal
+/// belongs to, without array indices, or `main` for the signals of the main
+/// component itself.
+fn top_level_component(name: &str) -> &str {
+    let mut path = name.split('.');
+    path.next(); // main
+    match (path.next(), path.next()) {
+        (Some(component), Some(_)) => component.split('[').next().unwrap(),
+        _ => "main",
+    }
+}
+
+/// Returns the transition constraint whose degree adjustment or composition
+/// term a signal of the `OodConsistencyCheck` template belongs to.
+fn transition_constraint_of(name: &str) -> Option<usize> {
+    [
+        "main.ood.transition_deg_adjustment[",
+        "main.ood.transition_temp[",
+    ]
+    .iter()
+    .find_map(|prefix| name.strip_prefix(prefix)?.split(']').next()?.parse().ok())
+}
+
+/// Returns whether a signal of the `Verify` template is computed once per
+/// composition column: the Merkle leaves of the constraint commitment, the
+/// DEEP composition of the constraint evaluations and the powers of `z`
+/// combining their out-of-domain evaluations.
+fn is_composition_column_signal(name: &str) -> bool {
+    name.starts_with("main.constraint_div[")
+        || name.starts_with("main.constraint_evalxcoeff[")
+        || name.starts_with("main.ood.channel_ood_pow[")
+        || (name.starts_with("main.constraintCommitmentVerifier.V[") && name.contains("].P_leaf."))
+}
+
+fn parse_error(e: io::Error) -> WinterCircomError {
+    WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("parsing verifier.r1cs")),
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Little-endian reader of a binary file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| invalid_data("unexpected end of file"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
@@ -0,0 +1,234 @@
+use serde_json::Value;
+use winterfell::math::{
+    fields::f256::{BaseElement, U256},
+    StarkField,
+};
+
+use crate::utils::WinterCircomError;
+
+/// Order of the bytes of an encoded field element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Most significant byte first, as in Ethereum ABI words.
+    BigEndian,
+
+    /// Least significant byte first, as in the Winterfell serialization of
+    /// the proofs.
+    LittleEndian,
+}
+
+/// Integer an encoded field element is represented by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Representation {
+    /// The value of the element, between 0 and the modulus.
+    Canonical,
+
+    /// The Montgomery form `value * 2^256 mod modulus` of the element, as
+    /// used internally by most arithmetic libraries of the BN254 scalar field
+    /// (e.g. arkworks, gnark).
+    Montgomery,
+}
+
+/// Byte encoding of the f256 field elements of this crate.
+///
+/// The field elements of the `input.json` file written by
+/// [circom_prove](crate::circom_prove), and of the other JSON artifacts of
+/// Circom and snarkjs, are decimal strings of their canonical value, e.g.
+/// `"1"` for [BaseElement::ONE]. Downstream systems that exchange raw bytes
+/// expect a byte order and a representation, which are explicit here rather
+/// than guessed:
+///
+/// ```ignore
+/// let encoding = ElementEncoding::new(ByteOrder::BigEndian, Representation::Canonical);
+/// let bytes = encoding.encode(element);
+/// assert_eq!(encoding.decode(&bytes)?, element);
+///
+/// // the signals of input.json, as 0x-prefixed hexadecimal strings
+/// let input = encoding.encode_json(&artifacts.input)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElementEncoding {
+    pub byte_order: ByteOrder,
+    pub representation: Representation,
+}
+
+impl Default for ElementEncoding {
+    /// Canonical big-endian encoding.
+    fn default() -> Self {
+        Self::new(ByteOrder::BigEndian, Representation::Canonical)
+    }
+}
+
+impl ElementEncoding {
+    /// Number of bytes of an encoded element.
+    pub const ELEMENT_BYTES: usize = 32;
+
+    pub fn new(byte_order: ByteOrder, representation: Representation) -> Self {
+        Self {
+            byte_order,
+            representation,
+        }
+    }
+
+    /// Encode a field element into bytes.
+    pub fn encode(&self, element: BaseElement) -> [u8; 32] {
+        let value = match self.representation {
+            Representation::Canonical => element.as_int(),
+            Representation::Montgomery => to_montgomery(element.as_int()),
+        };
+
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = ((value >> (8 * i as u32)).low_u64() & 0xff) as u8;
+        }
+        if self.byte_order == ByteOrder::BigEndian {
+            bytes.reverse();
+        }
+        bytes
+    }
+
+    /// Decode a field element from bytes.
+    ///
+    /// Returns an error if there are not exactly
+    /// [ELEMENT_BYTES](Self::ELEMENT_BYTES) bytes, or if the encoded integer is
+    /// not lower than the modulus.
+    pub fn decode(&self, bytes: &[u8]) -> Result<BaseElement, WinterCircomError> {
+        if bytes.len() != Self::ELEMENT_BYTES {
+            return Err(invalid(&format!(
+                "expected {} bytes, {} given",
+                Self::ELEMENT_BYTES,
+                bytes.len()
+            )));
+        }
+
+        let mut big_endian = bytes.to_vec();
+        if self.byte_order == ByteOrder::LittleEndian {
+            big_endian.reverse();
+        }
+        let value = big_endian.iter().fold(U256::from(0u8), |value, byte| {
+            (value << 8u32) | U256::from(*byte)
+        });
+        if value >= BaseElement::MODULUS {
+            return Err(invalid("value not lower than the modulus"));
+        }
+
+        Ok(BaseElement::new(match self.representation {
+            Representation::Canonical => value,
+            Representation::Montgomery => from_montgomery(value),
+        }))
+    }
+
+    /// Encode a field element into a `0x`-prefixed hexadecimal string of its
+    /// bytes, in order.
+    pub fn encode_hex(&self, element: BaseElement) -> String {
+        let digits = self
+            .encode(element)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        format!("0x{}", digits)
+    }
+
+    /// Decode a field element from a hexadecimal string of its bytes, with or
+    /// without a `0x` prefix, see [encode_hex](Self::encode_hex).
+    pub fn decode_hex(&self, hex: &str) -> Result<BaseElement, WinterCircomError> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        if digits.len() != 2 * Self::ELEMENT_BYTES || !digits.is_ascii() {
+            return Err(invalid(&format!(
+                "expected {} hexadecimal digits",
+                2 * Self::ELEMENT_BYTES
+            )));
+        }
+        let bytes = (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid("invalid hexadecimal digit"))?;
+        self.decode(&bytes)
+    }
+
+    /// Convert the field elements of a JSON value of input signals, such as
+    /// the contents of `input.json`, from decimal strings (or integers) to
+    /// hexadecimal strings of this encoding, see [encode_hex](Self::encode_hex).
+    ///
+    /// The structure of arrays and objects is preserved.
+    pub fn encode_json(&self, value: &Value) -> Result<Value, WinterCircomError> {
+        map_elements(value, &|element| {
+            let element = match element {
+                Value::String(decimal) => U256::from_str_radix(decimal, 10)
+                    .ok()
+                    .filter(|value| *value < BaseElement::MODULUS)
+                    .map(BaseElement::new),
+                Value::Number(number) => number.as_u64().map(BaseElement::from),
+                _ => None,
+            }
+            .ok_or_else(|| invalid(&format!("{} is not a decimal field element", element)))?;
+            Ok(Value::String(self.encode_hex(element)))
+        })
+    }
+
+    /// Convert the hexadecimal strings of this encoding of a JSON value back to
+    /// the canonical decimal strings of Circom and snarkjs, see
+    /// [encode_json](Self::encode_json).
+    pub fn decode_json(&self, value: &Value) -> Result<Value, WinterCircomError> {
+        map_elements(value, &|element| match element {
+            Value::String(hex) => Ok(Value::String(self.decode_hex(hex)?.as_int().to_string())),
+            _ => Err(invalid(&format!(
+                "{} is not a hexadecimal field element",
+                element
+            ))),
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn invalid(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidFieldElement {
+        comment: String::from(comment),
+    }
+}
+
+/// Apply a conversion to the leaves of a JSON value.
+fn map_elements<F>(value: &Value, convert: &F) -> Result<Value, WinterCircomError>
+where
+    F: Fn(&Value) -> Result<Value, WinterCircomError>,
+{
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .map(|value| map_elements(value, convert))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(values) => values
+            .iter()
+            .map(|(key, value)| map_elements(value, convert).map(|value| (key.clone(), value)))
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        leaf => convert(leaf),
+    }
+}
+
+/// Returns `value * 2^256 mod modulus`, by doubling, which does not overflow as
+/// the modulus is lower than 2^255.
+fn to_montgomery(mut value: U256) -> U256 {
+    for _ in 0..256 {
+        value = value << 1u32;
+        if value >= BaseElement::MODULUS {
+            value = value - BaseElement::MODULUS;
+        }
+    }
+    value
+}
+
+/// Returns `value / 2^256 mod modulus`, by halving.
+fn from_montgomery(mut value: U256) -> U256 {
+    for _ in 0..256 {
+        if value.low_u64() & 1 == 1 {
+            value = value + BaseElement::MODULUS;
+        }
+        value = value >> 1u32;
+    }
+    value
+}
@@ -0,0 +1,240 @@
+//! Deployment of Solidity verifiers and on-chain verification, built on
+//! `ethers`.
+//!
+//! The contract exported by
+//! [export_solidity_verifier](crate::export_solidity_verifier) is compiled
+//! with `solc` and deployed to the RPC endpoint of an [EvmConfig], and the
+//! Groth16 proofs of the circuit are then checked by the deployed contract:
+//!
+//! ```ignore
+//! let config = EvmConfig::from_env().expect("WINTER_CIRCOM_EVM_RPC_URL is set");
+//! export_solidity_verifier("sum", LoggingLevel::Default)?;
+//! let verifier = deploy_verifier("sum", &config).await?;
+//!
+//! circom_prove(prover, trace, "sum", LoggingLevel::Default)?;
+//! Pipeline::new("sum").witness().prove().run(LoggingLevel::Default)?;
+//! assert!(verify_on_chain("sum", verifier, &config).await?);
+//! ```
+//!
+//! The proof is checked with an `eth_call` of the `verifyProof` view function
+//! of the verifier, which costs no gas, and the rollup contracts of the users
+//! call the same function (see the `Verify.sol` snippet of the
+//! [verifier package](crate::export_verifier_package)). The OOD data of the
+//! public signals must still be checked against the AIR, see
+//! [check_ood_frame](crate::check_ood_frame).
+//!
+//! ## Limitations
+//!
+//! Only the proofs of [Groth16](crate::SnarkScheme::Groth16) circuits are
+//! verified, see [Groth16Calldata]. Deploying a verifier requires `solc` to be
+//! installed. The `ethers` crates need a more recent Rust toolchain than the
+//! rest of the crate. This module is only available with the `evm` feature.
+
+use std::{env, path::Path, sync::Arc};
+
+use ethers::{
+    abi::{parse_abi, Abi, Function, Token},
+    contract::ContractFactory,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    solc::Solc,
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionRequest, U256},
+};
+
+use crate::{
+    tenant::validate_circuit_name, utils::WinterCircomError, workspace::circuit_dir,
+    Groth16Calldata,
+};
+
+/// Names of the contracts of the Solidity verifiers exported by the versions
+/// of snarkjs.
+const VERIFIER_CONTRACTS: [&str; 4] = [
+    "Groth16Verifier",
+    "PlonkVerifier",
+    "FflonkVerifier",
+    "Verifier",
+];
+
+/// RPC endpoint, and account deploying the verifiers.
+///
+/// ## Configuration
+///
+/// The endpoint is read from the `WINTER_CIRCOM_EVM_RPC_URL` environment
+/// variable, and the hex-encoded private key of the account from
+/// `WINTER_CIRCOM_EVM_PRIVATE_KEY`. The key is only needed to deploy the
+/// verifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvmConfig {
+    pub rpc_url: String,
+    pub private_key: Option<String>,
+}
+
+impl EvmConfig {
+    pub const RPC_URL_VARIABLE: &'static str = "WINTER_CIRCOM_EVM_RPC_URL";
+    pub const PRIVATE_KEY_VARIABLE: &'static str = "WINTER_CIRCOM_EVM_PRIVATE_KEY";
+
+    /// Returns the configuration of the environment variables, if the RPC
+    /// endpoint is set.
+    pub fn from_env() -> Option<Self> {
+        env::var(EvmConfig::RPC_URL_VARIABLE)
+            .ok()
+            .map(|rpc_url| EvmConfig {
+                rpc_url,
+                private_key: env::var(EvmConfig::PRIVATE_KEY_VARIABLE).ok(),
+            })
+    }
+
+    fn provider(&self) -> Result<Provider<Http>, WinterCircomError> {
+        Provider::<Http>::try_from(self.rpc_url.as_str())
+            .map_err(|e| evm_error(&format!("connecting to {}", self.rpc_url), e))
+    }
+}
+
+/// Compile the `verifier.sol` contract of a circuit, exported by
+/// [export_solidity_verifier](crate::export_solidity_verifier), deploy it from
+/// the account of the configuration, and return its address.
+pub async fn deploy_verifier(
+    circuit_name: &str,
+    config: &EvmConfig,
+) -> Result<Address, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let path = circuit_dir(circuit_name).join("verifier.sol");
+    if !path.exists() {
+        return Err(WinterCircomError::FileNotFound {
+            file: path.to_string_lossy().into_owned(),
+            comment: Some(String::from("did you run export_solidity_verifier?")),
+        });
+    }
+    let (abi, bytecode) = compile_verifier(&path)?;
+
+    let private_key = config.private_key.as_ref().ok_or_else(|| {
+        evm_error(
+            "deploying the verifier",
+            format!("{} is not set", EvmConfig::PRIVATE_KEY_VARIABLE),
+        )
+    })?;
+    let provider = config.provider()?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| evm_error("reading the chain id", e))?;
+    let wallet = private_key
+        .parse::<LocalWallet>()
+        .map_err(|e| evm_error("reading the private key", e))?
+        .with_chain_id(chain_id.as_u64());
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let contract = ContractFactory::new(abi, bytecode.into(), client)
+        .deploy(())
+        .map_err(|e| evm_error("deploying the verifier", e))?
+        .send()
+        .await
+        .map_err(|e| evm_error("deploying the verifier", e))?;
+    Ok(contract.address())
+}
+
+/// Verify the Groth16 proof of the last [circom_prove](crate::circom_prove) of
+/// a circuit with the verifier deployed at `verifier`, and return the result of
+/// its `verifyProof` function.
+pub async fn verify_on_chain(
+    circuit_name: &str,
+    verifier: Address,
+    config: &EvmConfig,
+) -> Result<bool, WinterCircomError> {
+    let calldata = Groth16Calldata::of_circuit(circuit_name)?;
+    let (function, data) = verify_proof_call(&calldata)?;
+    let call: TypedTransaction = TransactionRequest::new().to(verifier).data(data).into();
+    let output = config
+        .provider()?
+        .call(&call, None)
+        .await
+        .map_err(|e| evm_error("calling verifyProof", e))?;
+
+    match function
+        .decode_output(&output)
+        .map_err(|e| evm_error("decoding the result of verifyProof", e))?
+        .as_slice()
+    {
+        [Token::Bool(valid)] => Ok(*valid),
+        _ => Err(evm_error(
+            "decoding the result of verifyProof",
+            "expected a boolean",
+        )),
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the `verifyProof` function of the Groth16 verifier of the calldata,
+/// and its encoded call.
+pub(crate) fn verify_proof_call(
+    calldata: &Groth16Calldata,
+) -> Result<(Function, Vec<u8>), WinterCircomError> {
+    let abi = parse_abi(&[&format!(
+        "function verifyProof(uint256[2] a, uint256[2][2] b, uint256[2] c, uint256[{}] input) external view returns (bool)",
+        calldata.input.len()
+    )])
+    .expect("the signature of verifyProof should parse");
+    let function = abi
+        .function("verifyProof")
+        .expect("the ABI should contain verifyProof")
+        .clone();
+
+    // the public signals are a fixed-size array, not a dynamic one
+    let data = function
+        .encode_input(&[
+            words(&calldata.a)?,
+            Token::FixedArray(vec![words(&calldata.b[0])?, words(&calldata.b[1])?]),
+            words(&calldata.c)?,
+            words(&calldata.input)?,
+        ])
+        .map_err(|e| evm_error("encoding the calldata", e))?;
+    Ok((function, data))
+}
+
+/// Returns the ABI and the bytecode of the verifier contract of a Solidity
+/// file exported by snarkjs.
+fn compile_verifier(path: &Path) -> Result<(Abi, Vec<u8>), WinterCircomError> {
+    let output = Solc::default()
+        .compile_source(path)
+        .map_err(|e| evm_error("compiling verifier.sol", e))?;
+    if output.has_error() {
+        return Err(evm_error(
+            "compiling verifier.sol",
+            output
+                .errors
+                .iter()
+                .map(|error| error.message.clone())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+
+    let contract = VERIFIER_CONTRACTS
+        .iter()
+        .find_map(|name| output.find_first(name))
+        .ok_or_else(|| evm_error("compiling verifier.sol", "no verifier contract found"))?;
+    let (abi, bytecode, _) = contract.into_parts_or_default();
+    Ok((abi, bytecode.to_vec()))
+}
+
+/// Returns the fixed-size array of 256-bit words of decimal strings.
+fn words(values: &[String]) -> Result<Token, WinterCircomError> {
+    values
+        .iter()
+        .map(|value| {
+            U256::from_dec_str(value)
+                .map(Token::Uint)
+                .map_err(|e| evm_error("encoding the calldata", e))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Token::FixedArray)
+}
+
+fn evm_error<E: std::fmt::Display>(step: &str, error: E) -> WinterCircomError {
+    WinterCircomError::EvmError {
+        comment: format!("{} failed: {}", step, error),
+    }
+}
@@ -0,0 +1,162 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use crate::utils::{run_process, Executable, WinterCircomError};
+
+thread_local! {
+    static EXECUTOR: RefCell<Option<Rc<dyn Executor>>> = RefCell::new(None);
+}
+
+/// Runner of the external commands (Circom, snarkjs, ...) called by the
+/// functions of this crate.
+///
+/// Commands are run by the [ProcessExecutor] by default. Another executor, such
+/// as a [MockExecutor], can be used on the current thread with
+/// [with_executor], so that the orchestration of the pipeline can be tested
+/// without the external tools installed.
+pub trait Executor {
+    /// Run a command, returning its standard output if
+    /// [captured](Invocation::captures_output), and an empty string otherwise.
+    fn execute(&self, invocation: &Invocation) -> Result<String, WinterCircomError>;
+}
+
+/// External command to be run by an [Executor].
+#[derive(Clone, Debug)]
+pub struct Invocation {
+    /// Name of the executable, e.g. `snarkjs`.
+    pub program: String,
+    pub args: Vec<String>,
+    pub current_dir: Option<PathBuf>,
+
+    /// Data piped to the standard input of the command, if any.
+    pub stdin: Option<Vec<u8>>,
+
+    pub(crate) executable: Executable,
+    pub(crate) verbose: bool,
+    pub(crate) print_output: bool,
+    pub(crate) capture_output: bool,
+}
+
+impl Invocation {
+    /// Returns whether the standard output of the command is returned by
+    /// [execute](Executor::execute).
+    pub fn captures_output(&self) -> bool {
+        self.capture_output
+    }
+}
+
+/// [Executor] spawning the commands as child processes.
+///
+/// The commands are killed if they become inactive while a
+/// [Watchdog](crate::Watchdog) is configured, or if the process is interrupted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessExecutor;
+
+impl Executor for ProcessExecutor {
+    fn execute(&self, invocation: &Invocation) -> Result<String, WinterCircomError> {
+        run_process(invocation)
+    }
+}
+
+/// [Executor] recording the invocations without running them, and returning
+/// canned outputs.
+///
+/// Invocations succeed with an empty output unless a response is configured for
+/// their program and first arguments. The first matching response is used:
+///
+/// ```ignore
+/// let executor = Rc::new(
+///     MockExecutor::new()
+///         .with_output("snarkjs", &["g16v"], "OK!")
+///         .with_failure("snarkjs", &["zkey"], 1),
+/// );
+/// let result = with_executor(executor.clone(), || circom_verify("sum", LoggingLevel::Quiet));
+/// assert_eq!(executor.invocations()[0].args[0], "g16v");
+/// ```
+///
+/// This executor is only available with the `testkit` feature.
+#[cfg(any(test, feature = "testkit"))]
+#[derive(Debug, Default)]
+pub struct MockExecutor {
+    responses: Vec<(String, Vec<String>, Result<String, i32>)>,
+    invocations: RefCell<Vec<Invocation>>,
+}
+
+#[cfg(any(test, feature = "testkit"))]
+impl MockExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `output` for the invocations of `program` whose arguments start
+    /// with `args`.
+    pub fn with_output(mut self, program: &str, args: &[&str], output: &str) -> Self {
+        self.responses.push((
+            String::from(program),
+            args.iter().map(|arg| String::from(*arg)).collect(),
+            Ok(String::from(output)),
+        ));
+        self
+    }
+
+    /// Fail the invocations of `program` whose arguments start with `args`
+    /// with the given exit code.
+    pub fn with_failure(mut self, program: &str, args: &[&str], code: i32) -> Self {
+        self.responses.push((
+            String::from(program),
+            args.iter().map(|arg| String::from(*arg)).collect(),
+            Err(code),
+        ));
+        self
+    }
+
+    /// Returns the recorded invocations, in order.
+    pub fn invocations(&self) -> Vec<Invocation> {
+        self.invocations.borrow().clone()
+    }
+}
+
+#[cfg(any(test, feature = "testkit"))]
+impl Executor for MockExecutor {
+    fn execute(&self, invocation: &Invocation) -> Result<String, WinterCircomError> {
+        self.invocations.borrow_mut().push(invocation.clone());
+
+        let response = self.responses.iter().find(|(program, args, _)| {
+            *program == invocation.program && invocation.args.starts_with(args)
+        });
+        match response {
+            Some((_, _, Ok(output))) if invocation.capture_output => Ok(output.clone()),
+            Some((_, _, Err(code))) => Err(WinterCircomError::ExitCodeError {
+                executable: invocation.program.clone(),
+                code: *code,
+            }),
+            _ => Ok(String::new()),
+        }
+    }
+}
+
+/// Run a closure with the given [Executor] running the external commands of
+/// the current thread.
+///
+/// The previous executor is restored when the closure returns or panics.
+pub fn with_executor<T, F: FnOnce() -> T>(executor: Rc<dyn Executor>, f: F) -> T {
+    struct Restore(Option<Rc<dyn Executor>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            EXECUTOR.with(|executor| *executor.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(EXECUTOR.with(|current| current.borrow_mut().replace(executor)));
+    f()
+}
+
+/// Run a command with the executor of the current thread.
+pub(crate) fn execute(invocation: Invocation) -> Result<String, WinterCircomError> {
+    let executor = EXECUTOR.with(|executor| executor.borrow().clone());
+    match executor {
+        Some(executor) => executor.execute(&invocation),
+        None => ProcessExecutor.execute(&invocation),
+    }
+}
@@ -0,0 +1,288 @@
+//! Proof of inclusion of a leaf in a Merkle tree.
+//!
+//! The trace has [TRACE_WIDTH] columns and one row per level of the tree, plus
+//! a final row containing the root:
+//!
+//! - `node`: the node of the authentication path at the current level (the
+//! leaf in the first row, the root in the last row).
+//! - `sibling`: the sibling of `node` at the current level.
+//! - `bit`: the position of `node` relative to its sibling (0 for left, 1 for
+//! right), i.e. the bits of the leaf index, least significant bit first.
+//! - `left`: the left input of the compression function at the current level.
+//!
+//! The leaf and the root are the public inputs of the proof. The depth of the
+//! tree is therefore `trace_length - 1`.
+//!
+//! The matching Circom templates are defined in `circuits/air/merkle.circom`.
+//!
+//! ## Compression function
+//!
+//! Nodes are compressed with the low-degree function [compress], which can be
+//! expressed as a single transition constraint. The Poseidon permutation cannot
+//! be used inside the AIR yet, as its round constants require periodic columns,
+//! which the out-of-domain check of this crate does not support.
+
+use serde::{ser::SerializeTuple, Deserialize, Serialize};
+use winterfell::{
+    math::{fields::f256::BaseElement, FieldElement},
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, FieldExtension, HashFunction,
+    ProofOptions, Prover, Serializable, Trace, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+use crate::{WinterCircomProofOptions, WinterPublicInputs};
+
+/// Number of columns of the execution trace.
+pub const TRACE_WIDTH: usize = 4;
+
+/// Degrees of the transition constraints, to be hardcoded in the
+/// `AIRTransitions` Circom template.
+pub const TRANSITION_CONSTRAINT_DEGREES: [usize; 3] = [2, 2, 5];
+
+/// Number of assertions of the AIR (leaf and root).
+pub const NUM_ASSERTIONS: usize = 2;
+
+/// Returns proof options for a Merkle tree of given depth.
+///
+/// `depth + 1` must be a power of two greater than or equal to 8.
+pub const fn proof_options(depth: usize) -> WinterCircomProofOptions<3> {
+    WinterCircomProofOptions::new(
+        depth + 1,
+        TRACE_WIDTH,
+        NUM_ASSERTIONS,
+        TRANSITION_CONSTRAINT_DEGREES,
+        32,
+        8,
+        0,
+        8,
+        32,
+    )
+}
+
+// COMPRESSION FUNCTION
+// ===========================================================================
+
+/// Compress two nodes of the Merkle tree into their parent node.
+///
+/// `compress(l, r) = (l + 3r + 1)^5 + (2l + r + 2)^5`
+pub fn compress<E: FieldElement>(left: E, right: E) -> E {
+    let a = left + right * E::from(3u8) + E::ONE;
+    let b = left * E::from(2u8) + right + E::from(2u8);
+    pow5(a) + pow5(b)
+}
+
+fn pow5<E: FieldElement>(x: E) -> E {
+    let x2 = x * x;
+    x2 * x2 * x
+}
+
+/// Authentication path of a leaf in a Merkle tree.
+#[derive(Clone, Debug)]
+pub struct MerklePath {
+    pub leaf: BaseElement,
+    pub index: usize,
+    pub siblings: Vec<BaseElement>,
+}
+
+impl MerklePath {
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Returns the nodes of the path, from the leaf to the root.
+    pub fn nodes(&self) -> Vec<BaseElement> {
+        let mut nodes = vec![self.leaf];
+        let mut node = self.leaf;
+        for (i, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.index >> i) & 1 == 0 {
+                compress(node, *sibling)
+            } else {
+                compress(*sibling, node)
+            };
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> BaseElement {
+        *self.nodes().last().unwrap()
+    }
+}
+
+// PUBLIC INPUTS
+// ===========================================================================
+
+/// The public inputs are serialized as the `[leaf, root]` tuple, from which
+/// they can be decoded by [decode_public_signals](crate::decode_public_signals).
+#[derive(Clone, Default, Deserialize)]
+pub struct MerklePublicInputs {
+    pub leaf: BaseElement,
+    pub root: BaseElement,
+}
+
+impl WinterPublicInputs for MerklePublicInputs {
+    const NUM_PUB_INPUTS: usize = 2;
+}
+
+impl Serialize for MerklePublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(2)?;
+        state.serialize_element(&self.leaf)?;
+        state.serialize_element(&self.root)?;
+        state.end()
+    }
+}
+
+impl Serializable for MerklePublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.leaf);
+        target.write(self.root);
+    }
+}
+
+// AIR
+// ===========================================================================
+
+pub struct MerkleAir {
+    context: AirContext<BaseElement>,
+    leaf: BaseElement,
+    root: BaseElement,
+}
+
+impl Air for MerkleAir {
+    type BaseField = BaseElement;
+    type PublicInputs = MerklePublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: MerklePublicInputs, options: ProofOptions) -> Self {
+        let degrees = TRANSITION_CONSTRAINT_DEGREES
+            .iter()
+            .map(|d| TransitionConstraintDegree::new(*d))
+            .collect();
+
+        MerkleAir {
+            context: AirContext::new(trace_info, degrees, NUM_ASSERTIONS, options),
+            leaf: pub_inputs.leaf,
+            root: pub_inputs.root,
+        }
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        let node = current[0];
+        let sibling = current[1];
+        let bit = current[2];
+        let left = current[3];
+        let right = node + sibling - left;
+
+        // the position bit is binary
+        result[0] = bit * bit - bit;
+        // the left input is the node if bit = 0, the sibling otherwise
+        result[1] = left - (node + bit * (sibling - node));
+        // the next node is the compression of the current inputs
+        result[2] = next[0] - compress(left, right);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.leaf),
+            Assertion::single(0, last_step, self.root),
+        ]
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for MerkleAir {
+    fn default() -> Self {
+        MerkleAir::new(
+            TraceInfo::new(TRACE_WIDTH, 16),
+            MerklePublicInputs::default(),
+            ProofOptions::new(
+                32,
+                8,
+                0,
+                HashFunction::Poseidon,
+                FieldExtension::None,
+                8,
+                32,
+            ),
+        )
+    }
+}
+
+// PROVER
+// ===========================================================================
+
+pub struct MerkleProver {
+    options: ProofOptions,
+}
+
+impl MerkleProver {
+    pub fn new(options: ProofOptions) -> Self {
+        Self { options }
+    }
+
+    /// Build the execution trace proving the inclusion of the path leaf.
+    ///
+    /// Panics if `path.depth() + 1` is not a valid trace length.
+    pub fn build_trace(&self, path: &MerklePath) -> TraceTable<BaseElement> {
+        let nodes = path.nodes();
+        let mut trace = TraceTable::new(TRACE_WIDTH, path.depth() + 1);
+
+        trace.fill(
+            |state| fill_row(state, path, &nodes, 0),
+            |step, state| fill_row(state, path, &nodes, step + 1),
+        );
+
+        trace
+    }
+}
+
+fn fill_row(state: &mut [BaseElement], path: &MerklePath, nodes: &[BaseElement], level: usize) {
+    let node = nodes[level];
+    if level == path.depth() {
+        // the root row does not constrain the next one
+        state.copy_from_slice(&[node, BaseElement::ZERO, BaseElement::ZERO, node]);
+        return;
+    }
+
+    let sibling = path.siblings[level];
+    let (bit, left) = if (path.index >> level) & 1 == 0 {
+        (BaseElement::ZERO, node)
+    } else {
+        (BaseElement::ONE, sibling)
+    };
+    state.copy_from_slice(&[node, sibling, bit, left]);
+}
+
+impl Prover for MerkleProver {
+    type BaseField = BaseElement;
+    type Air = MerkleAir;
+    type Trace = TraceTable<Self::BaseField>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> MerklePublicInputs {
+        let last_step = trace.length() - 1;
+        MerklePublicInputs {
+            leaf: trace.get(0, 0),
+            root: trace.get(0, last_step),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
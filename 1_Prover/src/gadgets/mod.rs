@@ -0,0 +1,8 @@
+//! Reusable AIRs and provers for common statements.
+//!
+//! Each gadget comes with a matching Circom template in the `circuits/air/`
+//! directory, named after the gadget module.
+
+pub mod merkle;
+pub mod rollup;
+pub mod signature;
@@ -0,0 +1,317 @@
+//! State transition of a rollup of account transfers.
+//!
+//! The rollup state is made of the balances of two accounts `a` and `b`. A
+//! batch applies one transfer per step of the trace, so that a batch of
+//! `trace_length - 1` transfers moves the rollup from an initial state to a
+//! final state.
+//!
+//! The trace has [TRACE_WIDTH] columns:
+//!
+//! - `balance_a`, `balance_b`: the balances before the transfer of the current
+//! step (the final state in the last row).
+//! - `amount`: the amount of the transfer of the current step.
+//! - `direction`: 0 for a transfer from `a` to `b`, 1 for a transfer from `b` to
+//! `a`.
+//!
+//! The initial and final states are the public inputs of the proof, while the
+//! individual transfers remain private. Generating the circuit with
+//! [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs)
+//! makes both states Groth16 public signals, so that the proof can be checked
+//! against the states stored by a Solidity verifier contract.
+//!
+//! The matching Circom templates are defined in `circuits/air/rollup.circom`.
+//!
+//! ## Limitations
+//!
+//! Balances are field elements: the AIR does not range-check them, so an
+//! overdraft wraps around the field modulus instead of being rejected. Batches
+//! of different traces cannot be aggregated into a single Groth16 proof yet,
+//! each batch is proven and verified on its own.
+
+use serde::{ser::SerializeTuple, Serialize};
+use winterfell::{
+    math::{fields::f256::BaseElement, FieldElement},
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, FieldExtension, HashFunction,
+    ProofOptions, Prover, Serializable, Trace, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+use crate::{WinterCircomProofOptions, WinterPublicInputs};
+
+/// Number of columns of the execution trace.
+pub const TRACE_WIDTH: usize = 4;
+
+/// Degrees of the transition constraints, to be hardcoded in the
+/// `AIRTransitions` Circom template.
+pub const TRANSITION_CONSTRAINT_DEGREES: [usize; 3] = [2, 2, 2];
+
+/// Number of assertions of the AIR (initial and final balances).
+pub const NUM_ASSERTIONS: usize = 4;
+
+/// Returns proof options for batches of `trace_length - 1` transfers.
+///
+/// `trace_length` must be a power of two greater than or equal to 8.
+pub const fn proof_options(trace_length: usize) -> WinterCircomProofOptions<3> {
+    WinterCircomProofOptions::new(
+        trace_length,
+        TRACE_WIDTH,
+        NUM_ASSERTIONS,
+        TRANSITION_CONSTRAINT_DEGREES,
+        32,
+        8,
+        0,
+        8,
+        32,
+    )
+    .expose_public_inputs()
+}
+
+// ROLLUP STATE
+// ===========================================================================
+
+/// Balances of the accounts of the rollup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RollupState {
+    pub balance_a: BaseElement,
+    pub balance_b: BaseElement,
+}
+
+/// Transfer between the two accounts of the rollup.
+#[derive(Clone, Copy, Debug)]
+pub enum Transfer {
+    AToB(u64),
+    BToA(u64),
+}
+
+impl Transfer {
+    fn amount(&self) -> BaseElement {
+        match self {
+            Transfer::AToB(amount) | Transfer::BToA(amount) => BaseElement::from(*amount),
+        }
+    }
+
+    fn direction(&self) -> BaseElement {
+        match self {
+            Transfer::AToB(_) => BaseElement::ZERO,
+            Transfer::BToA(_) => BaseElement::ONE,
+        }
+    }
+}
+
+impl RollupState {
+    /// Returns the state after the given transfer.
+    pub fn apply(&self, transfer: &Transfer) -> RollupState {
+        let amount = transfer.amount();
+        match transfer {
+            Transfer::AToB(_) => RollupState {
+                balance_a: self.balance_a - amount,
+                balance_b: self.balance_b + amount,
+            },
+            Transfer::BToA(_) => RollupState {
+                balance_a: self.balance_a + amount,
+                balance_b: self.balance_b - amount,
+            },
+        }
+    }
+
+    /// Returns the state after the given batch of transfers.
+    pub fn apply_batch(&self, transfers: &[Transfer]) -> RollupState {
+        transfers
+            .iter()
+            .fold(*self, |state, transfer| state.apply(transfer))
+    }
+}
+
+// PUBLIC INPUTS
+// ===========================================================================
+
+#[derive(Clone, Default)]
+pub struct RollupPublicInputs {
+    pub initial_state: RollupState,
+    pub final_state: RollupState,
+}
+
+impl WinterPublicInputs for RollupPublicInputs {
+    const NUM_PUB_INPUTS: usize = 4;
+}
+
+impl Serialize for RollupPublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(4)?;
+        state.serialize_element(&self.initial_state.balance_a)?;
+        state.serialize_element(&self.initial_state.balance_b)?;
+        state.serialize_element(&self.final_state.balance_a)?;
+        state.serialize_element(&self.final_state.balance_b)?;
+        state.end()
+    }
+}
+
+impl Serializable for RollupPublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.initial_state.balance_a);
+        target.write(self.initial_state.balance_b);
+        target.write(self.final_state.balance_a);
+        target.write(self.final_state.balance_b);
+    }
+}
+
+// AIR
+// ===========================================================================
+
+pub struct RollupAir {
+    context: AirContext<BaseElement>,
+    initial_state: RollupState,
+    final_state: RollupState,
+}
+
+impl Air for RollupAir {
+    type BaseField = BaseElement;
+    type PublicInputs = RollupPublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: RollupPublicInputs, options: ProofOptions) -> Self {
+        let degrees = TRANSITION_CONSTRAINT_DEGREES
+            .iter()
+            .map(|d| TransitionConstraintDegree::new(*d))
+            .collect();
+
+        RollupAir {
+            context: AirContext::new(trace_info, degrees, NUM_ASSERTIONS, options),
+            initial_state: pub_inputs.initial_state,
+            final_state: pub_inputs.final_state,
+        }
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        let amount = current[2];
+        let direction = current[3];
+        // amount transferred from a to b, negative if the transfer is from b to a
+        let delta = amount - E::from(2u8) * direction * amount;
+
+        // the direction is binary
+        result[0] = direction * direction - direction;
+        // the amount is debited from the sender
+        result[1] = next[0] - (current[0] - delta);
+        // and credited to the receiver
+        result[2] = next[1] - (current[1] + delta);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.initial_state.balance_a),
+            Assertion::single(1, 0, self.initial_state.balance_b),
+            Assertion::single(0, last_step, self.final_state.balance_a),
+            Assertion::single(1, last_step, self.final_state.balance_b),
+        ]
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for RollupAir {
+    fn default() -> Self {
+        RollupAir::new(
+            TraceInfo::new(TRACE_WIDTH, 16),
+            RollupPublicInputs::default(),
+            ProofOptions::new(
+                32,
+                8,
+                0,
+                HashFunction::Poseidon,
+                FieldExtension::None,
+                8,
+                32,
+            ),
+        )
+    }
+}
+
+// PROVER
+// ===========================================================================
+
+pub struct RollupProver {
+    options: ProofOptions,
+}
+
+impl RollupProver {
+    pub fn new(options: ProofOptions) -> Self {
+        Self { options }
+    }
+
+    /// Build the execution trace applying a batch of transfers to the initial
+    /// state.
+    ///
+    /// Panics if `transfers.len() + 1` is not a valid trace length.
+    pub fn build_trace(
+        &self,
+        initial_state: RollupState,
+        transfers: &[Transfer],
+    ) -> TraceTable<BaseElement> {
+        let mut trace = TraceTable::new(TRACE_WIDTH, transfers.len() + 1);
+
+        trace.fill(
+            |state| fill_row(state, initial_state, transfers.first()),
+            |step, state| {
+                let current = RollupState {
+                    balance_a: state[0],
+                    balance_b: state[1],
+                };
+                let next = current.apply(&transfers[step]);
+                fill_row(state, next, transfers.get(step + 1))
+            },
+        );
+
+        trace
+    }
+}
+
+/// Fill a row with the given state and the transfer applied to it, if any.
+fn fill_row(state: &mut [BaseElement], rollup_state: RollupState, transfer: Option<&Transfer>) {
+    // the final state row does not constrain the next one
+    let (amount, direction) = match transfer {
+        Some(transfer) => (transfer.amount(), transfer.direction()),
+        None => (BaseElement::ZERO, BaseElement::ZERO),
+    };
+    state.copy_from_slice(&[
+        rollup_state.balance_a,
+        rollup_state.balance_b,
+        amount,
+        direction,
+    ]);
+}
+
+impl Prover for RollupProver {
+    type BaseField = BaseElement;
+    type Air = RollupAir;
+    type Trace = TraceTable<Self::BaseField>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> RollupPublicInputs {
+        let last_step = trace.length() - 1;
+        RollupPublicInputs {
+            initial_state: RollupState {
+                balance_a: trace.get(0, 0),
+                balance_b: trace.get(1, 0),
+            },
+            final_state: RollupState {
+                balance_a: trace.get(0, last_step),
+                balance_b: trace.get(1, last_step),
+            },
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
@@ -0,0 +1,328 @@
+//! Verification of a hash-based one-time signature.
+//!
+//! The signature scheme is a Winternitz-style one-time signature over messages
+//! in `[0, w]` where `w = trace_length - 1`, built from two hash chains of
+//! length `w`:
+//!
+//! - The secret key is a pair of field elements `(s1, s2)`.
+//! - The public key is `(h^w(s1), h^w(s2))`.
+//! - The signature of a message `m` is `(h^m(s1), h^(w - m)(s2))`.
+//!
+//! Verifying a signature amounts to hashing its first element `w - m` times and
+//! its second element `m` times, and comparing the results with the public key.
+//! The second chain prevents forging the signature of `m + 1` from the
+//! signature of `m`.
+//!
+//! The trace has [TRACE_WIDTH] columns:
+//!
+//! - `chain_1`, `chain_2`: the two hash chains, starting from the signature.
+//! - `flag`: 1 when `chain_1` is hashed at the current step, 0 when `chain_2`
+//! is.
+//! - `count`: the number of times `chain_1` has been hashed.
+//!
+//! The message and the public key are the public inputs of the proof, while the
+//! signature remains private. Generating the circuit with
+//! [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs)
+//! binds the Groth16 proof to the message and the public key, which can then be
+//! read back with [exposed_public_inputs](crate::exposed_public_inputs).
+//!
+//! The matching Circom templates are defined in `circuits/air/signature.circom`.
+
+use serde::{ser::SerializeTuple, Serialize};
+use winterfell::{
+    math::{fields::f256::BaseElement, FieldElement},
+    Air, AirContext, Assertion, ByteWriter, EvaluationFrame, FieldExtension, HashFunction,
+    ProofOptions, Prover, Serializable, Trace, TraceInfo, TraceTable, TransitionConstraintDegree,
+};
+
+use super::merkle::compress;
+use crate::{WinterCircomProofOptions, WinterPublicInputs};
+
+/// Number of columns of the execution trace.
+pub const TRACE_WIDTH: usize = 4;
+
+/// Degrees of the transition constraints, to be hardcoded in the
+/// `AIRTransitions` Circom template.
+pub const TRANSITION_CONSTRAINT_DEGREES: [usize; 4] = [2, 6, 6, 1];
+
+/// Number of assertions of the AIR.
+pub const NUM_ASSERTIONS: usize = 4;
+
+/// Returns proof options for messages in `[0, trace_length - 1]`.
+///
+/// `trace_length` must be a power of two greater than or equal to 8.
+pub const fn proof_options(trace_length: usize) -> WinterCircomProofOptions<4> {
+    WinterCircomProofOptions::new(
+        trace_length,
+        TRACE_WIDTH,
+        NUM_ASSERTIONS,
+        TRANSITION_CONSTRAINT_DEGREES,
+        32,
+        8,
+        0,
+        8,
+        32,
+    )
+    .expose_public_inputs()
+}
+
+// SIGNATURE SCHEME
+// ===========================================================================
+
+/// Hash function of the chains.
+pub fn hash<E: FieldElement>(x: E) -> E {
+    compress(x, E::ZERO)
+}
+
+fn hash_n(mut x: BaseElement, n: usize) -> BaseElement {
+    for _ in 0..n {
+        x = hash(x);
+    }
+    x
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SecretKey {
+    pub s1: BaseElement,
+    pub s2: BaseElement,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PublicKey {
+    pub pk1: BaseElement,
+    pub pk2: BaseElement,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Signature {
+    pub sigma1: BaseElement,
+    pub sigma2: BaseElement,
+}
+
+impl SecretKey {
+    /// Returns the public key for chains of length `w`.
+    pub fn public_key(&self, w: usize) -> PublicKey {
+        PublicKey {
+            pk1: hash_n(self.s1, w),
+            pk2: hash_n(self.s2, w),
+        }
+    }
+
+    /// Sign a message in `[0, w]`, for chains of length `w`.
+    pub fn sign(&self, message: usize, w: usize) -> Signature {
+        assert!(message <= w, "message must be lower than or equal to {}", w);
+        Signature {
+            sigma1: hash_n(self.s1, message),
+            sigma2: hash_n(self.s2, w - message),
+        }
+    }
+}
+
+impl PublicKey {
+    /// Verify the signature of a message, for chains of length `w`.
+    pub fn verify(&self, message: usize, signature: &Signature, w: usize) -> bool {
+        message <= w
+            && hash_n(signature.sigma1, w - message) == self.pk1
+            && hash_n(signature.sigma2, message) == self.pk2
+    }
+}
+
+// PUBLIC INPUTS
+// ===========================================================================
+
+#[derive(Clone, Default)]
+pub struct SignaturePublicInputs {
+    pub message: BaseElement,
+    pub public_key: PublicKey,
+}
+
+impl WinterPublicInputs for SignaturePublicInputs {
+    const NUM_PUB_INPUTS: usize = 3;
+}
+
+impl Serialize for SignaturePublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(3)?;
+        state.serialize_element(&self.message)?;
+        state.serialize_element(&self.public_key.pk1)?;
+        state.serialize_element(&self.public_key.pk2)?;
+        state.end()
+    }
+}
+
+impl Serializable for SignaturePublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.message);
+        target.write(self.public_key.pk1);
+        target.write(self.public_key.pk2);
+    }
+}
+
+// AIR
+// ===========================================================================
+
+pub struct SignatureAir {
+    context: AirContext<BaseElement>,
+    message: BaseElement,
+    public_key: PublicKey,
+}
+
+impl Air for SignatureAir {
+    type BaseField = BaseElement;
+    type PublicInputs = SignaturePublicInputs;
+
+    fn new(
+        trace_info: TraceInfo,
+        pub_inputs: SignaturePublicInputs,
+        options: ProofOptions,
+    ) -> Self {
+        let degrees = TRANSITION_CONSTRAINT_DEGREES
+            .iter()
+            .map(|d| TransitionConstraintDegree::new(*d))
+            .collect();
+
+        SignatureAir {
+            context: AirContext::new(trace_info, degrees, NUM_ASSERTIONS, options),
+            message: pub_inputs.message,
+            public_key: pub_inputs.public_key,
+        }
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+
+        let flag = current[2];
+        let not_flag = E::ONE - flag;
+
+        // the flag is binary
+        result[0] = flag * flag - flag;
+        // chain 1 is hashed if flag = 1, copied otherwise
+        result[1] = next[0] - (flag * hash(current[0]) + not_flag * current[0]);
+        // chain 2 is hashed if flag = 0, copied otherwise
+        result[2] = next[1] - (not_flag * hash(current[1]) + flag * current[1]);
+        // the counter counts the hashes of chain 1
+        result[3] = next[3] - (current[3] + flag);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, last_step, self.public_key.pk1),
+            Assertion::single(1, last_step, self.public_key.pk2),
+            Assertion::single(3, 0, BaseElement::ZERO),
+            Assertion::single(
+                3,
+                last_step,
+                BaseElement::from(last_step as u64) - self.message,
+            ),
+        ]
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for SignatureAir {
+    fn default() -> Self {
+        SignatureAir::new(
+            TraceInfo::new(TRACE_WIDTH, 16),
+            SignaturePublicInputs::default(),
+            ProofOptions::new(
+                32,
+                8,
+                0,
+                HashFunction::Poseidon,
+                FieldExtension::None,
+                8,
+                32,
+            ),
+        )
+    }
+}
+
+// PROVER
+// ===========================================================================
+
+pub struct SignatureProver {
+    options: ProofOptions,
+}
+
+impl SignatureProver {
+    pub fn new(options: ProofOptions) -> Self {
+        Self { options }
+    }
+
+    /// Build the execution trace verifying the signature of `message`.
+    ///
+    /// The chains have length `trace_length - 1`.
+    pub fn build_trace(
+        &self,
+        message: usize,
+        signature: &Signature,
+        trace_length: usize,
+    ) -> TraceTable<BaseElement> {
+        let w = trace_length - 1;
+        assert!(message <= w, "message must be lower than or equal to {}", w);
+        let mut trace = TraceTable::new(TRACE_WIDTH, trace_length);
+
+        // chain 1 is hashed during the first w - message steps
+        let flag = |step: usize| {
+            if step < w - message {
+                BaseElement::ONE
+            } else {
+                BaseElement::ZERO
+            }
+        };
+
+        trace.fill(
+            |state| {
+                state[0] = signature.sigma1;
+                state[1] = signature.sigma2;
+                state[2] = flag(0);
+                state[3] = BaseElement::ZERO;
+            },
+            |step, state| {
+                if state[2] == BaseElement::ONE {
+                    state[0] = hash(state[0]);
+                } else {
+                    state[1] = hash(state[1]);
+                }
+                state[3] += state[2];
+                state[2] = flag(step + 1);
+            },
+        );
+
+        trace
+    }
+}
+
+impl Prover for SignatureProver {
+    type BaseField = BaseElement;
+    type Air = SignatureAir;
+    type Trace = TraceTable<Self::BaseField>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> SignaturePublicInputs {
+        let last_step = trace.length() - 1;
+        SignaturePublicInputs {
+            message: BaseElement::from(last_step as u64) - trace.get(3, last_step),
+            public_key: PublicKey {
+                pk1: trace.get(0, last_step),
+                pk2: trace.get(1, last_step),
+            },
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
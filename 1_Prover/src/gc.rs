@@ -0,0 +1,179 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    tenant::validate_circuit_name,
+    utils::{directory_size, WinterCircomError},
+    workspace::CircomWorkspace,
+    CircuitManifest,
+};
+
+/// Action taken by [gc] on the stale circuits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GcPolicy {
+    /// Only report the stale circuits, without modifying them.
+    DryRun,
+
+    /// Delete the directories of the stale circuits.
+    Remove,
+
+    /// Move the directories of the stale circuits to the given directory, as
+    /// `<archive>/<circuit_name>/`. The archive must be outside of
+    /// `target/circom`, on the same file system.
+    Archive(PathBuf),
+}
+
+/// Reason why a circuit is considered stale by [gc].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StaleReason {
+    /// The AIR template of the circuit no longer exists.
+    MissingTemplate,
+
+    /// The sources of the circuit changed since it was generated (see
+    /// [source_fingerprint](CircuitManifest::source_fingerprint)).
+    SourcesChanged,
+}
+
+/// Circuit identified as stale by [gc].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleCircuit {
+    pub circuit_name: String,
+    pub reason: StaleReason,
+
+    /// Total size of the artifacts of the circuit, in bytes.
+    pub size_bytes: u64,
+}
+
+/// Result of a [gc] run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub stale: Vec<StaleCircuit>,
+
+    /// Number of bytes freed in `target/circom`. For a
+    /// [DryRun](GcPolicy::DryRun), the number of bytes that would be freed.
+    pub reclaimed_bytes: u64,
+}
+
+/// Remove or archive the stale circuits of the `target/circom` directory.
+///
+/// A circuit is stale if its AIR template no longer exists in `circuits/air/`,
+/// or if its manifest records a source fingerprint that does not match its
+/// current sources. Such circuits must be regenerated with
+/// [circom_create](crate::circom_create) before proving, and their artifacts
+/// (mostly the proving key) otherwise accumulate silently.
+///
+/// Circuits of all [tenants](crate::Tenant) are scanned. Circuits generated by
+/// earlier versions of this crate, which record no fingerprint, are only
+/// checked for their template.
+pub fn gc(policy: GcPolicy) -> Result<GcReport, WinterCircomError> {
+    let mut report = GcReport::default();
+
+    let workspace = CircomWorkspace::current();
+    for circuit_name in circuit_names(workspace.root_dir()) {
+        let reason = match stale_reason(&circuit_name)? {
+            Some(reason) => reason,
+            None => continue,
+        };
+
+        let dir = workspace.circuit_dir(&circuit_name);
+        let size_bytes = directory_size(&dir);
+        match &policy {
+            GcPolicy::DryRun => {}
+            GcPolicy::Remove => {
+                fs::remove_dir_all(&dir).map_err(|e| WinterCircomError::IoError {
+                    io_error: e,
+                    comment: Some(format!("removing {}", dir.display())),
+                })?
+            }
+            GcPolicy::Archive(archive) => archive_dir(&dir, &archive.join(&circuit_name))?,
+        }
+
+        report.reclaimed_bytes += size_bytes;
+        report.stale.push(StaleCircuit {
+            circuit_name,
+            reason,
+            size_bytes,
+        });
+    }
+
+    Ok(report)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the names of the circuits of a directory, including the namespaced
+/// circuits of the tenants it contains. Directories whose names are not valid
+/// circuit names are not circuits of this crate and are skipped.
+///
+/// A directory is a circuit if it holds the generated `verifier.circom` or its
+/// manifest, and a tenant directory otherwise, whether or not the tenant was
+/// saved.
+fn circuit_names(root: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    for name in subdirectories(root) {
+        let dir = root.join(&name);
+        if is_circuit_dir(&dir) {
+            names.push(name);
+        } else {
+            names.extend(
+                subdirectories(&dir)
+                    .into_iter()
+                    .filter(|circuit| is_circuit_dir(&dir.join(circuit)))
+                    .map(|circuit| format!("{}/{}", name, circuit)),
+            );
+        }
+    }
+    names.retain(|name| validate_circuit_name(name).is_ok());
+    names
+}
+
+fn is_circuit_dir(dir: &Path) -> bool {
+    dir.join("verifier.circom").exists() || dir.join("manifest.json").exists()
+}
+
+fn subdirectories(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Returns why the given circuit is stale, if it is.
+fn stale_reason(circuit_name: &str) -> Result<Option<StaleReason>, WinterCircomError> {
+    if !CircomWorkspace::current()
+        .air_template(circuit_name)
+        .exists()
+    {
+        return Ok(Some(StaleReason::MissingTemplate));
+    }
+
+    match CircuitManifest::read(circuit_name)? {
+        Some(manifest) if manifest.sources_changed() => Ok(Some(StaleReason::SourcesChanged)),
+        _ => Ok(None),
+    }
+}
+
+/// Move a circuit directory to the archive.
+fn archive_dir(dir: &Path, archived: &Path) -> Result<(), WinterCircomError> {
+    let io_error = |comment: String| {
+        move |e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(comment),
+        }
+    };
+
+    if let Some(parent) = archived.parent() {
+        fs::create_dir_all(parent).map_err(io_error(String::from("creating archive directory")))?;
+    }
+    fs::rename(dir, archived).map_err(io_error(format!("archiving {}", dir.display())))
+}
@@ -0,0 +1,219 @@
+#[cfg(feature = "prove")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "prove")]
+use colored::Colorize;
+use serde_json::Value;
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+#[cfg(feature = "prove")]
+use crate::{
+    circom::{circuit_inputs, ProveTimings},
+    json::FriLayer,
+    utils::{command_execution, Executable, LoggingLevel, StageTimer},
+    SnarkScheme,
+};
+use crate::{
+    codegen::{circom_main_source, circuit_manifest, main_arguments, MainArgument},
+    tenant::{split_circuit_name, validate_circuit_name},
+    utils::WinterCircomError,
+    CircuitManifest, WinterCircomProofOptions, WinterPublicInputs,
+};
+
+#[cfg(feature = "prove")]
+static VERIFICATION_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Circuit generated in memory by [circom_create_in_memory].
+#[derive(Clone, Debug)]
+pub struct InMemoryCircuit {
+    /// Source of the Circom main file, the contents of `verifier.circom`.
+    pub source: String,
+
+    /// Manifest of the circuit, without source fingerprint.
+    pub manifest: CircuitManifest,
+
+    /// Arguments of the main component of the source, the contents of
+    /// `parameters.json`.
+    pub arguments: Vec<MainArgument>,
+}
+
+/// Circom inputs of the verification of a Winterfell proof, built in memory by
+/// [circom_prove_in_memory].
+#[cfg(feature = "prove")]
+#[derive(Clone, Debug)]
+pub struct CircuitInputs {
+    /// Input signals of the circuit, the contents of `input.json`, from which
+    /// [compute_witness_from_input](crate::compute_witness_from_input)
+    /// computes the witness.
+    pub input: Value,
+
+    /// Per-layer FRI query data, the contents of `fri_layers.json`.
+    pub fri_layers: Vec<FriLayer>,
+
+    /// Whether the Winterfell proof was verified before being converted, see
+    /// [IntermediateVerification](crate::IntermediateVerification).
+    pub inner_proof_verified: bool,
+
+    /// Serialized Winterfell proof, see
+    /// [StarkProof::from_bytes](winterfell::StarkProof::from_bytes).
+    pub stark_proof: Vec<u8>,
+
+    /// Public inputs of the Winterfell proof, as field elements.
+    pub public_inputs: Vec<BaseElement>,
+
+    /// Durations of the steps, nothing is written to disk.
+    pub timings: ProveTimings,
+}
+
+/// Generate the Circom code verifying Winterfell proofs with given parameters,
+/// without writing it to disk.
+///
+/// This is the in-memory counterpart of the code generation of
+/// [circom_create](crate::circom_create): the AIR template of the circuit is
+/// included from `<circuits_dir>/air/<circuit_name>.circom`, where
+/// `circuits_dir` is the path to the circuits directory from the directory the
+/// source will be compiled in, e.g. `"../../../circuits"` for
+/// `target/circom/<circuit_name>/`. Compiling the source and generating the
+/// circuit-specific keys is left to the caller.
+pub fn circom_create_in_memory<P, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    circuits_dir: &str,
+) -> Result<InMemoryCircuit, WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    validate_circuit_name(circuit_name)?;
+    proof_options.check_lde_blowup_factor()?;
+
+    let air_name = split_circuit_name(circuit_name).1;
+    Ok(InMemoryCircuit {
+        source: circom_main_source::<P::BaseField, P::Air, N>(
+            &proof_options,
+            air_name,
+            circuits_dir,
+        ),
+        manifest: circuit_manifest(&proof_options, circuit_name),
+        arguments: main_arguments::<P::BaseField, P::Air, N>(&proof_options),
+    })
+}
+
+/// Build the Circom inputs of the verification of a Winterfell proof, without
+/// writing them to disk.
+///
+/// This is the in-memory counterpart of [circom_prove](crate::circom_prove) and
+/// [circom_prove_with_data](crate::circom_prove_with_data): the proof is
+/// checked against the manifest of `circuit`, and `data` must be given if and
+/// only if the circuit binds external data. The SNARK proof is then computed
+/// from [input](CircuitInputs::input) by the caller.
+#[cfg(feature = "prove")]
+pub fn circom_prove_in_memory<P>(
+    prover: P,
+    trace: <P as Prover>::Trace,
+    data: Option<&[u8]>,
+    circuit: &InMemoryCircuit,
+    logging_level: LoggingLevel,
+) -> Result<CircuitInputs, WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    circuit_inputs(
+        prover,
+        trace,
+        data,
+        Some(&circuit.manifest),
+        &circuit.manifest.circuit_name,
+        &logging_level,
+    )
+}
+
+/// Verify a proof from in-memory artifacts: the verification key, in the
+/// snarkjs JSON format, the public signals and the proof. The
+/// [SnarkScheme] is read from the `protocol` field of the key.
+///
+/// This is the in-memory counterpart of [circom_verify](crate::circom_verify).
+/// As snarkjs only reads files, the artifacts are written to a temporary
+/// directory of [std::env::temp_dir], removed once the verification is over.
+#[cfg(feature = "prove")]
+pub fn circom_verify_in_memory(
+    verification_key: &[u8],
+    public_signals: &Value,
+    proof: &Value,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let scheme = SnarkScheme::of_verification_key(verification_key);
+    let dir = verification_dir();
+    let result = write_artifacts(&dir, verification_key, public_signals, proof).and_then(|_| {
+        let message = match scheme {
+            SnarkScheme::Groth16 => "Verifying Groth16 proof...",
+            SnarkScheme::Plonk => "Verifying PLONK proof...",
+            SnarkScheme::Fflonk => "Verifying FFLONK proof...",
+        };
+        let timer = StageTimer::start(message, &logging_level);
+        command_execution(
+            Executable::SnarkJS,
+            &[
+                scheme.verify_command(),
+                "verification_key.json",
+                "public.json",
+                "proof.json",
+            ],
+            dir.to_str(),
+            &logging_level,
+        )?;
+        timer.finish::<&str>(&[]);
+        Ok(())
+    });
+
+    if fs::remove_dir_all(&dir).is_err() && logging_level.print_big_steps() {
+        println!(
+            "{}",
+            format!("Warning: could not remove {}.", dir.display()).yellow()
+        );
+    }
+    result
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns a temporary directory unique to this verification.
+#[cfg(feature = "prove")]
+fn verification_dir() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "winter-circom-verify-{}-{}",
+        process::id(),
+        VERIFICATION_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+#[cfg(feature = "prove")]
+fn write_artifacts(
+    dir: &Path,
+    verification_key: &[u8],
+    public_signals: &Value,
+    proof: &Value,
+) -> Result<(), WinterCircomError> {
+    let files = [
+        ("verification_key.json", verification_key.to_vec()),
+        ("public.json", public_signals.to_string().into_bytes()),
+        ("proof.json", proof.to_string().into_bytes()),
+    ];
+    fs::create_dir_all(dir)
+        .and_then(|_| {
+            files
+                .iter()
+                .try_for_each(|(file, contents)| fs::write(dir.join(file), contents))
+        })
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("writing the artifacts to verify")),
+        })
+}
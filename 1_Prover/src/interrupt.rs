@@ -0,0 +1,234 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    process::{Child, ExitStatus},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{audit::AuditAction, utils::WinterCircomError, workspace::circuit_dir};
+
+#[cfg(feature = "prove")]
+static HANDLER_INSTALLED: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static RUNNING_CHILDREN: AtomicUsize = AtomicUsize::new(0);
+
+/// State of the artifacts of a circuit, written to
+/// `target/circom/<circuit_name>/state.json` while a pipeline step producing
+/// artifacts is running.
+///
+/// The file is removed once the step is over, unless it was interrupted (see
+/// [install_interrupt_handler]) or the process was killed: its artifacts may
+/// then be partially written, e.g. a truncated proving key, and they are
+/// rejected with an
+/// [IncompleteArtifacts](WinterCircomError::IncompleteArtifacts) error until
+/// the interrupted step is run again or the circuit is regenerated with
+/// [circom_create](crate::circom_create).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct PipelineState {
+    /// Step that did not complete.
+    incomplete_step: AuditAction,
+
+    /// File names of the artifacts of the step.
+    artifacts: Vec<String>,
+}
+
+/// Install a SIGINT and SIGTERM handler interrupting the pipeline steps.
+///
+/// On SIGINT or SIGTERM, the running external commands are killed and fail
+/// with an [Interrupted](WinterCircomError::Interrupted) error, so that the
+/// step returns. If no command is running, e.g. while the Winterfell proof is
+/// generated, or on a second signal, the process exits with the
+/// [Interrupted](crate::utils::ErrorClass::Interrupted) exit code. In both
+/// cases the artifacts of the step are marked incomplete.
+///
+/// The handler is not installed by this crate, as it takes over the signals of
+/// the whole process: the `winter-circom` executable installs it, and
+/// applications may call this function once at startup. Without it, the
+/// signals keep their default behavior, and the artifacts of a step killed
+/// midway are marked incomplete too.
+///
+/// Further calls do nothing. Returns an
+/// [IoError](WinterCircomError::IoError) if the handler cannot be registered,
+/// e.g. because the application installed one already.
+#[cfg(feature = "prove")]
+pub fn install_interrupt_handler() -> Result<(), WinterCircomError> {
+    if HANDLER_INSTALLED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    ctrlc::set_handler(|| {
+        let repeated = INTERRUPTED.swap(true, Ordering::SeqCst);
+        if repeated || RUNNING_CHILDREN.load(Ordering::SeqCst) == 0 {
+            eprintln!("Interrupted, the artifacts of the current step are marked incomplete.");
+            std::process::exit(crate::utils::ErrorClass::Interrupted.exit_code());
+        }
+    })
+    .map_err(|e| {
+        HANDLER_INSTALLED.store(false, Ordering::SeqCst);
+        WinterCircomError::IoError {
+            io_error: io::Error::new(ErrorKind::Other, e.to_string()),
+            comment: Some(String::from("installing the interrupt handler")),
+        }
+    })
+}
+
+/// Start a pipeline step of a circuit.
+///
+/// This checks that the artifacts of the circuit are not incomplete and
+/// records the step in the state file of the circuit if it produces
+/// artifacts. The state file is kept if the step is interrupted (see
+/// [install_interrupt_handler]).
+///
+/// A corrupt state file, e.g. if the process was killed while writing it, is
+/// reported with a [CorruptArtifact](WinterCircomError::CorruptArtifact) error,
+/// as the step it records is unknown. Only [circom_create](crate::circom_create),
+/// which regenerates every artifact, replaces it.
+pub(crate) fn begin_step(
+    circuit_name: &str,
+    action: AuditAction,
+    artifacts: &[PathBuf],
+) -> Result<(), WinterCircomError> {
+    let state = match read_state(circuit_name) {
+        Err(WinterCircomError::CorruptArtifact { .. }) if action == AuditAction::Create => None,
+        state => state?,
+    };
+    if let Some(state) = state {
+        if action != AuditAction::Create && action != state.incomplete_step {
+            return Err(WinterCircomError::IncompleteArtifacts {
+                circuit_name: String::from(circuit_name),
+                step: action_name(state.incomplete_step),
+                artifacts: state.artifacts,
+            });
+        }
+    }
+    if !produces_artifacts(action) {
+        return Ok(());
+    }
+
+    let state = PipelineState {
+        incomplete_step: action,
+        artifacts: artifacts
+            .iter()
+            .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .collect(),
+    };
+    fs::create_dir_all(circuit_dir(circuit_name))
+        .and_then(|_| {
+            fs::write(
+                state_path(circuit_name),
+                serde_json::to_string_pretty(&state).expect("state should be serializable"),
+            )
+        })
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("writing state.json")),
+        })
+}
+
+/// End a pipeline step of a circuit started by [begin_step], removing the
+/// state file of the circuit unless the step was interrupted.
+pub(crate) fn end_step<T>(
+    circuit_name: &str,
+    action: AuditAction,
+    result: &Result<T, WinterCircomError>,
+) -> Result<(), WinterCircomError> {
+    if !produces_artifacts(action) || matches!(result, Err(WinterCircomError::Interrupted { .. })) {
+        return Ok(());
+    }
+
+    match fs::remove_file(state_path(circuit_name)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("removing state.json")),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Wait for a child process to exit, killing it if the process is interrupted.
+///
+/// Returns `None` if the child was killed.
+pub(crate) fn wait(child: &mut Child) -> io::Result<Option<ExitStatus>> {
+    let _running = RunningChild::new();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if is_interrupted() {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Returns whether SIGINT or SIGTERM was received.
+pub(crate) fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Guard counting the child processes waited for, which are killed on
+/// interruption instead of exiting the process.
+pub(crate) struct RunningChild;
+
+impl RunningChild {
+    /// Count a child process, clearing the interruption of the previous ones
+    /// if there is no other one: the step they ran returned, and the signal
+    /// only applies to it.
+    pub(crate) fn new() -> Self {
+        if RUNNING_CHILDREN.fetch_add(1, Ordering::SeqCst) == 0 {
+            INTERRUPTED.store(false, Ordering::SeqCst);
+        }
+        RunningChild
+    }
+}
+
+impl Drop for RunningChild {
+    fn drop(&mut self) {
+        RUNNING_CHILDREN.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn produces_artifacts(action: AuditAction) -> bool {
+    action != AuditAction::Verify
+}
+
+fn action_name(action: AuditAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn state_path(circuit_name: &str) -> PathBuf {
+    circuit_dir(circuit_name).join("state.json")
+}
+
+fn read_state(circuit_name: &str) -> Result<Option<PipelineState>, WinterCircomError> {
+    let data = match fs::read_to_string(state_path(circuit_name)) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("reading state.json")),
+            })
+        }
+    };
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| WinterCircomError::CorruptArtifact {
+            file: state_path(circuit_name).to_string_lossy().into_owned(),
+            comment: e.to_string(),
+        })
+}
@@ -0,0 +1,436 @@
+//! Content-addressed proof bundles, for distributing proofs through IPFS.
+//!
+//! A [ProofBundle] gathers everything needed to verify a Groth16 proof of a
+//! circuit: the proof, its public signals and the verification key. Bundles are
+//! serialized to deterministic [DAG-CBOR](https://ipld.io/specs/codecs/dag-cbor/spec/)
+//! (map keys sorted by length then bytewise, shortest integer encodings), so
+//! that the same bundle always has the same bytes and the same
+//! [content identifier](cid) (CIDv1, `dag-cbor` codec, BLAKE3 multihash).
+//!
+//! [IpfsNode] pins bundles to an IPFS node through its HTTP API, and fetches
+//! them back from a gateway, checking that the fetched bytes match the
+//! requested CID. The requests are made with `curl`, which must be installed.
+//! This module is only available with the `ipfs` feature.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use winterfell::{
+    crypto::{hashers::Blake3_256, Digest, Hasher},
+    math::fields::f256::BaseElement,
+};
+
+use crate::{
+    tenant::validate_circuit_name,
+    utils::{command_output, Executable, WinterCircomError},
+    workspace::{circuit_dir, CircomWorkspace},
+};
+
+/// Multicodec code of DAG-CBOR.
+const DAG_CBOR_CODEC: u8 = 0x71;
+
+/// Multihash code of BLAKE3, with its 32-byte digest length.
+const BLAKE3_MULTIHASH: [u8; 2] = [0x1e, 0x20];
+
+/// Artifacts needed to verify a Groth16 proof of a circuit, in the snarkjs
+/// JSON format.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub circuit_name: String,
+
+    /// Content of `proof.json`.
+    pub proof: Value,
+
+    /// Content of `public.json`.
+    pub public_signals: Value,
+
+    /// Content of `verification_key.json`.
+    pub verification_key: Value,
+}
+
+impl ProofBundle {
+    /// Read the bundle of the last proof of a circuit, from the
+    /// `target/circom/<circuit_name>/` directory.
+    pub fn read(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        ProofBundle::read_from(circuit_name, circuit_dir(circuit_name))
+    }
+
+    /// Read the bundle of a proof of a circuit whose `proof.json` and
+    /// `public.json` files are in the given directory, e.g. the
+    /// [proof_dir](crate::proof_dir) of a saved proof.
+    pub fn read_from<P: AsRef<Path>>(
+        circuit_name: &str,
+        proof_dir: P,
+    ) -> Result<Self, WinterCircomError> {
+        validate_circuit_name(circuit_name)?;
+        let proof_dir = proof_dir.as_ref();
+        Ok(ProofBundle {
+            circuit_name: String::from(circuit_name),
+            proof: read_json(&proof_dir.join("proof.json"))?,
+            public_signals: read_json(&proof_dir.join("public.json"))?,
+            verification_key: read_json(&circuit_dir(circuit_name).join("verification_key.json"))?,
+        })
+    }
+
+    /// Write the `proof.json`, `public.json` and `verification_key.json` files
+    /// of the bundle to the given directory, e.g. to verify it with snarkjs.
+    pub fn write_to<P: AsRef<Path>>(&self, dir: P) -> Result<(), WinterCircomError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(io_error(format!("creating {}", dir.display())))?;
+        for (file, value) in [
+            ("proof.json", &self.proof),
+            ("public.json", &self.public_signals),
+            ("verification_key.json", &self.verification_key),
+        ] {
+            let path = dir.join(file);
+            fs::write(&path, serde_json::to_string_pretty(value).unwrap())
+                .map_err(io_error(format!("writing {}", path.display())))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the canonical DAG-CBOR serialization of the bundle.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        encode_cbor(&serde_json::to_value(self).expect("bundle should be serializable"))
+    }
+
+    /// Deserialize a bundle from its canonical DAG-CBOR serialization.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, WinterCircomError> {
+        serde_json::from_value(decode_cbor(bytes)?).map_err(|e| WinterCircomError::InvalidBundle {
+            comment: e.to_string(),
+        })
+    }
+
+    /// Returns the content identifier of the bundle.
+    pub fn cid(&self) -> String {
+        cid(&self.to_cbor())
+    }
+}
+
+/// IPFS node used to pin and fetch proof bundles.
+///
+/// ## Configuration
+///
+/// The URLs of the HTTP API and of the gateway of the node default to the ones
+/// of a local Kubo node, `http://127.0.0.1:5001` and `http://127.0.0.1:8080`.
+/// They are set with the `WINTER_CIRCOM_IPFS_API` and
+/// `WINTER_CIRCOM_IPFS_GATEWAY` environment variables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpfsNode {
+    api_url: String,
+    gateway_url: String,
+}
+
+impl IpfsNode {
+    pub const API_VARIABLE: &'static str = "WINTER_CIRCOM_IPFS_API";
+    pub const GATEWAY_VARIABLE: &'static str = "WINTER_CIRCOM_IPFS_GATEWAY";
+
+    pub fn new<A: Into<String>, G: Into<String>>(api_url: A, gateway_url: G) -> Self {
+        Self {
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            gateway_url: gateway_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Returns the node configured by the environment variables.
+    pub fn from_env() -> Self {
+        Self::new(
+            env::var(IpfsNode::API_VARIABLE)
+                .unwrap_or_else(|_| String::from("http://127.0.0.1:5001")),
+            env::var(IpfsNode::GATEWAY_VARIABLE)
+                .unwrap_or_else(|_| String::from("http://127.0.0.1:8080")),
+        )
+    }
+
+    /// Store and pin a bundle on the node, returning its CID.
+    ///
+    /// The bundle is stored as a single DAG-CBOR block with
+    /// `/api/v0/dag/put`. An error is returned if the CID computed by the node
+    /// differs from the local one.
+    pub fn pin(&self, bundle: &ProofBundle) -> Result<String, WinterCircomError> {
+        let cid = bundle.cid();
+        let path = cache_path(&cid);
+        write_cache(&path, &bundle.to_cbor())?;
+
+        let url = format!(
+            "{}/api/v0/dag/put?store-codec=dag-cbor&input-codec=dag-cbor&hash=blake3&pin=true",
+            self.api_url
+        );
+        let form = format!("file=@{}", path.display());
+        let response =
+            command_output(Executable::Curl, &["-sfS", "-X", "POST", "-F", &form, &url])?;
+
+        let pinned = serde_json::from_str::<Value>(&response)
+            .ok()
+            .and_then(|response| response["Cid"]["/"].as_str().map(String::from));
+        match pinned {
+            Some(pinned) if pinned == cid => Ok(cid),
+            pinned => Err(WinterCircomError::InvalidBundle {
+                comment: format!(
+                    "the node stored the bundle {} as {}",
+                    cid,
+                    pinned.unwrap_or_else(|| String::from("an unknown CID"))
+                ),
+            }),
+        }
+    }
+
+    /// Fetch a bundle from the gateway of the node.
+    ///
+    /// The raw block is requested, and rejected if it does not match the CID,
+    /// so that the gateway does not need to be trusted. Fetched bundles are
+    /// cached in the `.ipfs/` directory of the
+    /// [workspace](crate::CircomWorkspace) root, next to the circuits.
+    pub fn fetch(&self, cid: &str) -> Result<ProofBundle, WinterCircomError> {
+        if cid.is_empty() || !cid.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(WinterCircomError::InvalidBundle {
+                comment: format!("{:?} is not a CID", cid),
+            });
+        }
+
+        let path = cache_path(cid);
+        if !path.exists() {
+            create_cache_dir()?;
+            let partial = path.with_extension("partial");
+            let url = format!("{}/ipfs/{}", self.gateway_url, cid);
+            let output = partial.to_string_lossy().into_owned();
+            command_output(
+                Executable::Curl,
+                &[
+                    "-sfS",
+                    "-H",
+                    "Accept: application/vnd.ipld.raw",
+                    "-o",
+                    &output,
+                    &url,
+                ],
+            )?;
+            fs::rename(&partial, &path).map_err(io_error(format!("moving {}", output)))?;
+        }
+
+        let bytes = fs::read(&path).map_err(io_error(format!("reading {}", path.display())))?;
+        if self::cid(&bytes) != cid {
+            let _ = fs::remove_file(&path);
+            return Err(WinterCircomError::InvalidBundle {
+                comment: format!("the fetched block does not match {}", cid),
+            });
+        }
+        ProofBundle::from_cbor(&bytes)
+    }
+}
+
+/// Returns the canonical DAG-CBOR encoding of a JSON value.
+///
+/// Integers are encoded in their shortest form, other numbers as 64-bit
+/// floats, and the keys of maps are sorted by length then bytewise.
+pub fn encode_cbor(value: &Value) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_value(&mut bytes, value);
+    bytes
+}
+
+/// Decode a canonical DAG-CBOR encoding of a JSON value.
+///
+/// Only the subset of CBOR produced by [encode_cbor] is supported, and
+/// encodings which are not canonical are rejected.
+pub fn decode_cbor(bytes: &[u8]) -> Result<Value, WinterCircomError> {
+    let mut position = 0;
+    let value =
+        read_value(bytes, &mut position).ok_or_else(|| WinterCircomError::InvalidBundle {
+            comment: String::from("unsupported or truncated CBOR encoding"),
+        })?;
+    if position != bytes.len() || encode_cbor(&value) != bytes {
+        return Err(WinterCircomError::InvalidBundle {
+            comment: String::from("the CBOR encoding is not canonical"),
+        });
+    }
+    Ok(value)
+}
+
+/// Returns the CIDv1 of a DAG-CBOR block hashed with BLAKE3, in the base32
+/// multibase encoding (`b...`).
+pub fn cid(block: &[u8]) -> String {
+    let mut bytes = vec![0x01, DAG_CBOR_CODEC];
+    bytes.extend(BLAKE3_MULTIHASH);
+    bytes.extend(Blake3_256::<BaseElement>::hash(block).as_bytes());
+    format!("b{}", base32(&bytes))
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn write_header(bytes: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => bytes.push(major | argument as u8),
+        24..=0xff => bytes.extend([major | 24, argument as u8]),
+        0x100..=0xffff => {
+            bytes.push(major | 25);
+            bytes.extend((argument as u16).to_be_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            bytes.push(major | 26);
+            bytes.extend((argument as u32).to_be_bytes());
+        }
+        _ => {
+            bytes.push(major | 27);
+            bytes.extend(argument.to_be_bytes());
+        }
+    }
+}
+
+fn write_value(bytes: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => bytes.push(0xf6),
+        Value::Bool(false) => bytes.push(0xf4),
+        Value::Bool(true) => bytes.push(0xf5),
+        Value::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(n), _) => write_header(bytes, 0, n),
+            (None, Some(n)) => write_header(bytes, 1, !n as u64),
+            (None, None) => {
+                bytes.push(0xfb);
+                bytes.extend(number.as_f64().unwrap_or(0.0).to_be_bytes());
+            }
+        },
+        Value::String(string) => {
+            write_header(bytes, 3, string.len() as u64);
+            bytes.extend(string.as_bytes());
+        }
+        Value::Array(array) => {
+            write_header(bytes, 4, array.len() as u64);
+            for value in array {
+                write_value(bytes, value);
+            }
+        }
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| (a.len(), a.as_bytes()).cmp(&(b.len(), b.as_bytes())));
+            write_header(bytes, 5, entries.len() as u64);
+            for (key, value) in entries {
+                write_header(bytes, 3, key.len() as u64);
+                bytes.extend(key.as_bytes());
+                write_value(bytes, value);
+            }
+        }
+    }
+}
+
+fn read_bytes<'a>(bytes: &'a [u8], position: &mut usize, length: usize) -> Option<&'a [u8]> {
+    let end = position.checked_add(length)?;
+    let slice = bytes.get(*position..end)?;
+    *position = end;
+    Some(slice)
+}
+
+fn read_header(bytes: &[u8], position: &mut usize) -> Option<(u8, u8, u64)> {
+    let initial = *read_bytes(bytes, position, 1)?.first()?;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    let argument = match info {
+        0..=23 => info as u64,
+        24..=27 => {
+            let length = 1 << (info - 24);
+            read_bytes(bytes, position, length)?
+                .iter()
+                .fold(0, |argument, byte| (argument << 8) | *byte as u64)
+        }
+        _ => return None,
+    };
+    Some((major, info, argument))
+}
+
+fn read_value(bytes: &[u8], position: &mut usize) -> Option<Value> {
+    let (major, info, argument) = read_header(bytes, position)?;
+    Some(match major {
+        0 => Value::from(argument),
+        1 => Value::from(-1 - i64::try_from(argument).ok()?),
+        3 => {
+            let string = read_bytes(bytes, position, usize::try_from(argument).ok()?)?;
+            Value::String(String::from_utf8(string.to_vec()).ok()?)
+        }
+        4 => Value::Array(
+            (0..argument)
+                .map(|_| read_value(bytes, position))
+                .collect::<Option<_>>()?,
+        ),
+        5 => {
+            let mut map = Map::new();
+            for _ in 0..argument {
+                match read_value(bytes, position)? {
+                    Value::String(key) => map.insert(key, read_value(bytes, position)?),
+                    _ => return None,
+                };
+            }
+            Value::Object(map)
+        }
+        7 => match (info, argument) {
+            (20, _) => Value::Bool(false),
+            (21, _) => Value::Bool(true),
+            (22, _) => Value::Null,
+            (27, bits) => Value::Number(Number::from_f64(f64::from_bits(bits))?),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+/// RFC 4648 base32, lowercase and without padding.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut encoded = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
+/// Returns the directory of the cached bundles in the current workspace.
+///
+/// Circuit names cannot start with '.', so the cache never collides with the
+/// artifacts of a circuit.
+fn cache_dir() -> PathBuf {
+    CircomWorkspace::current().root_dir().join(".ipfs")
+}
+
+fn cache_path(cid: &str) -> PathBuf {
+    cache_dir().join(format!("{}.cbor", cid))
+}
+
+fn create_cache_dir() -> Result<(), WinterCircomError> {
+    let dir = cache_dir();
+    fs::create_dir_all(&dir).map_err(io_error(format!("creating {}", dir.display())))
+}
+
+fn write_cache(path: &Path, bytes: &[u8]) -> Result<(), WinterCircomError> {
+    create_cache_dir()?;
+    fs::write(path, bytes).map_err(io_error(format!("writing {}", path.display())))
+}
+
+fn read_json(path: &Path) -> Result<Value, WinterCircomError> {
+    let data = fs::read_to_string(path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", path.display())),
+    })?;
+    serde_json::from_str(&data).map_err(|e| WinterCircomError::InvalidBundle {
+        comment: format!("{} is not valid JSON: {}", path.display(), e),
+    })
+}
+
+fn io_error(comment: String) -> impl FnOnce(std::io::Error) -> WinterCircomError {
+    move |e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(comment),
+    }
+}
@@ -1,11 +1,18 @@
 use serde::Serialize;
-use serde_json::{json, Value};
+use serde_json::Value;
 use winterfell::{
-    crypto::{Digest, ElementHasher, RandomCoin},
+    crypto::{ElementHasher, RandomCoin},
     math::{fields::f256::BaseElement, log2, FieldElement, StarkField},
     Air, Serializable, StarkProof,
 };
 
+use crate::{
+    cancellation::checkpoint,
+    circom_poseidon::{digest_to_element, public_commitment},
+    signals::{Signal, SignalValues},
+    utils::WinterCircomError,
+};
+
 /// Parse a [StarkProof] into a Circom-usable JSON object.
 ///
 /// ## Padding
@@ -13,31 +20,73 @@ use winterfell::{
 /// To ensure constant size arrays and therefore Circom compatibility, elements
 /// of `fri_layer_proofs` and `fri_layer_queries` arrays are padded with zeroes.
 ///
-/// The `fri_num_queries` and `fri_tree_depths` arguments are populated so that:
+/// The `fri_layers` argument is populated with the unpadded query data of each
+/// FRI layer, ordered by [FriLayer::index], so that:
 ///
 /// ```text
-/// fri_layer_proofs[i].len() = fri_num_queries[i]
-/// fri_layer_proofs[i][j].len() = fri_tree_depths[i]
-/// fri_layer_queries[i].len() = fri_num_queries[i] * folding_factor
+/// fri_layer_proofs[i][..fri_layers[i].num_queries] = fri_layers[i].proofs
+/// fri_layer_proofs[i][j][..fri_layers[i].tree_depth] = fri_layers[i].proofs[j]
+/// fri_layer_queries[i][..fri_layers[i].num_queries * folding_factor] = fri_layers[i].queries
 /// ```
 ///
+/// ## External data commitment
+///
+/// The `commitment_data` argument is the opening of the external data bound to
+/// the proof (see [commitment_opening](crate::commitment::commitment_opening)),
+/// or a single zero element if no data is bound.
+///
+/// ## Packed public inputs
+///
+/// The `packed_public_inputs` argument is the packing of the public inputs
+/// according to their [PublicInputLayout](crate::PublicInputLayout).
+///
+/// ## Public commitment
+///
+/// The `committed_public_inputs` argument is `None` if the public signals are
+/// not committed to, in which case `public_commitment` is zero. Otherwise,
+/// `public_commitment` is the [public_commitment] of the OOD data followed by
+/// these public inputs, empty if they are not exposed (see
+/// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)).
+///
+/// ## Cancellation
+///
+/// The [CancellationToken](crate::CancellationToken) of the current thread is
+/// polled between the FRI layers and the queries of the proof, and the
+/// conversion fails with a [Cancelled](WinterCircomError::Cancelled) error
+/// once it is cancelled.
+///
+/// ## Remainder-only proofs
+///
+/// Proofs without FRI layer (see
+/// [num_fri_layers](crate::WinterCircomProofOptions::num_fri_layers)) get a
+/// single layer of zeroes in `fri_layer_proofs` and `fri_layer_queries`, and
+/// `fri_layers` is left empty.
+///
 /// ## JSON structure
 ///
+/// The keys of the JSON object are the names of the input signals of the
+/// `Verify` template. Field elements are decimal strings of their canonical
+/// value, see [ElementEncoding](crate::ElementEncoding) to convert them to
+/// other encodings.
+///
 /// ```json
 /// {
-///     "addicity": _,
+///     "addicity_root": _,
+///     "commitment_data": [_; num_commitment_elements + 1],
 ///     "constraint_commitment": _,
-///     "constraint_evaluations": [[_; trace_width]; num_queries],
+///     "constraint_evaluations": [[_; num_composition_columns]; num_queries],
 ///     "constraint_query_proofs": [[_; tree_depth]; num_queries],
 ///     "fri_commitments": [num_fri_layers + 1],
-///     "fri_layer_proofs": [[[_; tree_depth]; num_queries]; num_fri_layers],
-///     "fri_layer_queries": [[_; num_queries * folding_factor]; num_fri_layers],
+///     "fri_layer_proofs": [[[_; tree_depth]; num_queries]; max(num_fri_layers, 1)],
+///     "fri_layer_queries": [[_; num_queries * folding_factor]; max(num_fri_layers, 1)],
 ///     "fri_remainder": [_; lde_domain_size / (folding_factor ** num_fri_layers)],
-///     "ood_constraint_evaluations": [_; ce_blowup_factor],
+///     "ood_constraint_evaluations": [_; num_composition_columns],
 ///     "ood_frame_constraint_evaluation": [_; num_transition_constraints],
 ///     "ood_trace_frame": [[_; trace_width]; 2],
+///     "packed_public_inputs": [_; num_packed_public_inputs],
 ///     "pow_nonce": _,
 ///     "pub_coin_seed": [_; num_pub_coin_seed],
+///     "public_commitment": _,
 ///     "public_inputs": [_; num_public_inputs],
 ///     "trace_commitment": _,
 ///     "trace_evaluations": [[_; trace_width]; num_queries],
@@ -45,13 +94,20 @@ use winterfell::{
 /// }
 /// ```
 ///
-// TODO: Return errors instead of panicking (`.map_err()` and `?` instead of `.unwrap()`)
+/// ## Errors
+///
+/// Returns an [InvalidStarkProof](WinterCircomError::InvalidStarkProof) error
+/// if the proof cannot be parsed according to the parameters of the AIR, or
+/// if it has more than one trace segment or FRI partition.
 pub fn proof_to_json<AIR, H>(
     proof: StarkProof,
     air: &AIR,
     pub_inputs: AIR::PublicInputs,
-    fri_tree_depths: &mut Vec<usize>,
-) -> Value
+    commitment_data: Vec<BaseElement>,
+    packed_public_inputs: Vec<BaseElement>,
+    committed_public_inputs: Option<&[BaseElement]>,
+    fri_layers: &mut Vec<FriLayer>,
+) -> Result<Value, WinterCircomError>
 where
     AIR: Air<BaseField = BaseElement>,
     <AIR as Air>::PublicInputs: Serialize,
@@ -60,7 +116,7 @@ where
     let StarkProof {
         context,
         commitments,
-        mut trace_queries,
+        trace_queries,
         constraint_queries,
         ood_frame,
         fri_proof,
@@ -74,29 +130,21 @@ where
     let fri_options = air.options().to_fri_options();
     let num_queries = air.options().num_queries();
     let folding_factor = fri_options.folding_factor();
+    let invalid = |comment: &str| WinterCircomError::InvalidStarkProof {
+        comment: String::from(comment),
+    };
 
     // enforce only one trace segment to ensure compatibility with the Circom code
-    assert_eq!(num_trace_segments, 1);
+    if num_trace_segments != 1 {
+        return Err(invalid("expected a single trace segment"));
+    }
 
     // PUBLIC COIN SEED
     // ===========================================================================
 
-    // serialize public inputs and context
-    let mut pub_coin_seed = Vec::new();
-    pub_inputs.write_into(&mut pub_coin_seed);
-    context.write_into(&mut pub_coin_seed);
-
-    let mut public_coin = RandomCoin::<BaseElement, H>::new(&pub_coin_seed);
-
-    // turn into f256 field elements
-    while pub_coin_seed.len() % BaseElement::ELEMENT_BYTES != 0 {
-        pub_coin_seed.push(0);
-    }
-    let pub_coin_seed = pub_coin_seed
-        .as_slice()
-        .chunks(BaseElement::ELEMENT_BYTES)
-        .map(|bytes| BaseElement::from_le_bytes(bytes))
-        .collect::<Vec<_>>();
+    let pub_coin_seed_bytes = public_coin_seed_bytes(&pub_inputs, &context);
+    let mut public_coin = RandomCoin::<BaseElement, H>::new(&pub_coin_seed_bytes);
+    let pub_coin_seed = bytes_to_elements(pub_coin_seed_bytes);
 
     // COMMITMENTS
     // ===========================================================================
@@ -107,7 +155,7 @@ where
             num_trace_segments,
             fri_options.num_fri_layers(lde_domain_size),
         )
-        .unwrap();
+        .map_err(|_| invalid("could not parse the commitments"))?;
 
     public_coin.reseed(trace_commitments[0]);
     public_coin.reseed(constraint_commitment);
@@ -115,11 +163,10 @@ where
     // map commitments to BaseElements
     let trace_commitment = trace_commitments
         .iter()
-        .map(|c| BaseElement::from_le_bytes(&c.as_bytes()))
+        .map(|c| digest_to_element(&c))
         .collect::<Vec<_>>()
         .remove(0);
-    let constraint_commitment: BaseElement =
-        BaseElement::from_le_bytes(&constraint_commitment.as_bytes());
+    let constraint_commitment: BaseElement = digest_to_element(&constraint_commitment);
 
     // OOD FRAME
     // ===========================================================================
@@ -127,7 +174,7 @@ where
     // parse ood_frame, ignoring the ood_aux_trace_frame
     let (ood_trace_frame, _, ood_constraint_evaluations) = ood_frame
         .parse::<BaseElement>(main_trace_width, aux_trace_width, air.ce_blowup_factor())
-        .unwrap();
+        .map_err(|_| invalid("could not parse the OOD frame"))?;
 
     public_coin.reseed(H::hash_elements(ood_trace_frame.current()));
     public_coin.reseed(H::hash_elements(ood_trace_frame.next()));
@@ -135,20 +182,37 @@ where
 
     // OOD FRAME CONSTRAINT EVALUATIONS
     // FIXME: fix periodic values
-    let mut ood_frame_constraint_evaluation = BaseElement::zeroed_vector(air.trace_info().width());
+    let mut ood_frame_constraint_evaluation =
+        BaseElement::zeroed_vector(air.context().num_transition_constraints());
     air.evaluate_transition::<BaseElement>(
         &ood_trace_frame,
         &[],
         &mut ood_frame_constraint_evaluation,
     );
 
+    // PUBLIC COMMITMENT
+    let public_commitment = match committed_public_inputs {
+        Some(public_inputs) => public_commitment(
+            &[
+                &ood_frame_constraint_evaluation[..],
+                ood_trace_frame.current(),
+                ood_trace_frame.next(),
+                public_inputs,
+            ]
+            .concat(),
+        ),
+        None => BaseElement::ZERO,
+    };
+
     let ood_trace_frame = (ood_trace_frame.current(), ood_trace_frame.next());
 
     // FRI PROOF PART 1
     // ===========================================================================
 
     // only accept a fri proof with a single partition
-    assert_eq!(fri_proof.num_partitions(), 1);
+    if fri_proof.num_partitions() != 1 {
+        return Err(invalid("expected a single FRI partition"));
+    }
 
     for root in fri_commitments.iter() {
         public_coin.reseed(*root);
@@ -158,7 +222,7 @@ where
     // of the commitment for the remainder
     let fri_commitments = fri_commitments
         .iter()
-        .map(|c| BaseElement::from_le_bytes(&c.as_bytes()))
+        .map(|c| digest_to_element(&c))
         .collect::<Vec<_>>();
 
     // QUERY POSITIONS
@@ -168,65 +232,60 @@ where
 
     let query_positions = public_coin
         .draw_integers(num_queries, lde_domain_size)
-        .unwrap();
+        .map_err(|_| invalid("could not draw the query positions"))?;
 
     // FRI PROOF PART 2
     // ===========================================================================
 
     // parse fri proof into Merkle proofs and queries for each layer
-    let fri_remainder = fri_proof.parse_remainder::<BaseElement>().unwrap();
-    let (mut fri_layer_queries, fri_layer_proofs) = fri_proof
+    let fri_remainder = fri_proof
+        .parse_remainder::<BaseElement>()
+        .map_err(|_| invalid("could not parse the FRI remainder"))?;
+    let (fri_layer_queries, fri_layer_proofs) = fri_proof
         .parse_layers::<H, BaseElement>(lde_domain_size, folding_factor)
-        .unwrap();
+        .map_err(|_| invalid("could not parse the FRI layers"))?;
 
-    // convert batch merkle proofs into authentication paths
-    // and map digests to BaseElements
+    // convert batch merkle proofs into authentication paths, map digests to
+    // BaseElements and group them with the queries of their layer
     let mut indexes = query_positions.clone();
     let mut domain_size = lde_domain_size;
-    let mut fri_layer_proofs = fri_layer_proofs
+    *fri_layers = fri_layer_proofs
         .iter()
-        .map(|merkle_proof| {
+        .zip(fri_layer_queries)
+        .enumerate()
+        .map(|(index, (merkle_proof, queries))| {
+            checkpoint("converting the FRI layers")?;
             indexes = fold_positions(&indexes, domain_size, folding_factor);
             domain_size /= folding_factor;
 
-            merkle_proof
+            let proofs = merkle_proof
                 .to_paths(&indexes)
-                .unwrap()
+                .map_err(|_| invalid("could not convert the FRI layer proofs"))?
                 .iter()
                 .map(|path| {
                     path.iter()
-                        .map(|digest| BaseElement::from_le_bytes(&digest.as_bytes()))
+                        .map(|digest| digest_to_element(&digest))
                         .collect::<Vec<_>>()
                 })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    // pad fri_query_proofs with zeroes to ensure constant size arrays
-    let tree_depth = log2(lde_domain_size) as usize;
-    let fri_layer_proofs = fri_layer_proofs
-        .iter_mut()
-        .map(|paths| {
-            fri_tree_depths.push(paths[0].len());
-
-            for path in paths.iter_mut() {
-                while path.len() < tree_depth {
-                    path.push(BaseElement::ZERO);
-                }
-            }
-            while paths.len() < num_queries {
-                paths.push(vec![BaseElement::ZERO; tree_depth]);
-            }
-            paths
+                .collect::<Vec<_>>();
+
+            Ok(FriLayer {
+                index,
+                num_queries: indexes.len(),
+                tree_depth: proofs.first().map_or(0, Vec::len),
+                proofs,
+                queries,
+            })
         })
-        .collect::<Vec<_>>();
-
-    // pad fri layer queries with zeroes to ensure constant size arrays
-    for queries in fri_layer_queries.iter_mut() {
-        while queries.len() < num_queries * folding_factor {
-            queries.push(BaseElement::ZERO);
-        }
-    }
+        .collect::<Result<_, WinterCircomError>>()?;
+
+    // pad fri layer proofs and queries with zeroes to ensure constant size arrays
+    let (fri_layer_proofs, fri_layer_queries) = fri_layer_inputs(
+        fri_layers,
+        num_queries,
+        folding_factor,
+        log2(lde_domain_size) as usize,
+    );
 
     // TRACE QUERIES
     // ===========================================================================
@@ -234,22 +293,26 @@ where
     // pick out trace queries first element (the one that corresponds to the
     // main trace segment) and parse it into a Merkle proof and trace states
     let (trace_query_proofs, trace_evaluations) = trace_queries
-        .remove(0)
+        .into_iter()
+        .next()
+        .ok_or_else(|| invalid("missing trace queries"))?
         .parse::<H, BaseElement>(lde_domain_size, num_queries, main_trace_width)
-        .unwrap();
+        .map_err(|_| invalid("could not parse the trace queries"))?;
 
     // convert the batch Merkle proof into authentication paths
     // and map hash digests to BaseElements
     let trace_query_proofs = trace_query_proofs
         .to_paths(&query_positions)
-        .unwrap()
+        .map_err(|_| invalid("could not convert the trace query proofs"))?
         .iter()
         .map(|path| {
-            path.iter()
-                .map(|digest| BaseElement::from_le_bytes(&digest.as_bytes()))
-                .collect::<Vec<_>>()
+            checkpoint("converting the trace queries")?;
+            Ok(path
+                .iter()
+                .map(|digest| digest_to_element(&digest))
+                .collect::<Vec<_>>())
         })
-        .collect::<Vec<Vec<_>>>();
+        .collect::<Result<Vec<Vec<_>>, WinterCircomError>>()?;
 
     // map constraint states table into a matrix of BaseElements
     let trace_evaluations = trace_evaluations.rows().fold(vec![], |mut e, row| {
@@ -263,20 +326,22 @@ where
     // parse constraint queries back into a Merkle proof and a vector of states
     let (constraint_query_proofs, constraint_evaluations) = constraint_queries
         .parse::<H, BaseElement>(lde_domain_size, num_queries, air.ce_blowup_factor())
-        .unwrap();
+        .map_err(|_| invalid("could not parse the constraint queries"))?;
 
     // convert the batch Merkle proof into authentication paths
     // and map hash digests to BaseElements
     let constraint_query_proofs = constraint_query_proofs
         .to_paths(&query_positions)
-        .unwrap()
+        .map_err(|_| invalid("could not convert the constraint query proofs"))?
         .iter()
         .map(|path| {
-            path.iter()
-                .map(|digest| BaseElement::from_le_bytes(&digest.as_bytes()))
-                .collect::<Vec<_>>()
+            checkpoint("converting the constraint queries")?;
+            Ok(path
+                .iter()
+                .map(|digest| digest_to_element(&digest))
+                .collect::<Vec<_>>())
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, WinterCircomError>>()?;
 
     // map constraint states table into a matrix of BaseElements
     let constraint_evaluations = constraint_evaluations.rows().fold(vec![], |mut e, row| {
@@ -287,30 +352,174 @@ where
     // BUILD JSON OBJECT
     // ===========================================================================
 
-    json!({
-        "addicity_root": BaseElement::TWO_ADIC_ROOT_OF_UNITY,
-        "constraint_commitment": constraint_commitment,
-        "constraint_evaluations": constraint_evaluations,
-        "constraint_query_proofs": constraint_query_proofs,
-        "fri_commitments": fri_commitments,
-        "fri_layer_proofs": fri_layer_proofs,
-        "fri_layer_queries": fri_layer_queries,
-        "fri_remainder": fri_remainder,
-        "ood_constraint_evaluations": ood_constraint_evaluations,
-        "ood_frame_constraint_evaluation" : ood_frame_constraint_evaluation,
-        "ood_trace_frame": ood_trace_frame,
-        "pow_nonce": pow_nonce,
-        "pub_coin_seed": pub_coin_seed,
-        "public_inputs": pub_inputs,
-        "trace_commitment": trace_commitment,
-        "trace_evaluations": trace_evaluations,
-        "trace_query_proofs": trace_query_proofs,
-    })
+    let mut signals = SignalValues::new();
+    signals.insert(Signal::AddicityRoot, BaseElement::TWO_ADIC_ROOT_OF_UNITY);
+    signals.insert(Signal::CommitmentData, commitment_data);
+    signals.insert(Signal::ConstraintCommitment, constraint_commitment);
+    signals.insert(Signal::ConstraintEvaluations, constraint_evaluations);
+    signals.insert(Signal::ConstraintQueryProofs, constraint_query_proofs);
+    signals.insert(Signal::FriCommitments, fri_commitments);
+    signals.insert(Signal::FriLayerProofs, fri_layer_proofs);
+    signals.insert(Signal::FriLayerQueries, fri_layer_queries);
+    signals.insert(Signal::FriRemainder, fri_remainder);
+    signals.insert(Signal::OodConstraintEvaluations, ood_constraint_evaluations);
+    signals.insert(
+        Signal::OodFrameConstraintEvaluation,
+        ood_frame_constraint_evaluation,
+    );
+    signals.insert(Signal::OodTraceFrame, ood_trace_frame);
+    signals.insert(Signal::PackedPublicInputs, packed_public_inputs);
+    signals.insert(Signal::PowNonce, pow_nonce);
+    signals.insert(Signal::PubCoinSeed, pub_coin_seed);
+    signals.insert(Signal::PublicCommitment, public_commitment);
+    signals.insert(Signal::PublicInputs, pub_inputs);
+    signals.insert(Signal::TraceCommitment, trace_commitment);
+    signals.insert(Signal::TraceEvaluations, trace_evaluations);
+    signals.insert(Signal::TraceQueryProofs, trace_query_proofs);
+    Ok(signals.into_json())
+}
+
+// FRI LAYERS
+// ===========================================================================
+
+/// Query data of a single FRI layer of a [StarkProof].
+///
+/// Unlike the `fri_layer_proofs` and `fri_layer_queries` input signals, which
+/// flatten all layers into zero-padded arrays of constant size, this structure
+/// holds the data of one layer only, without padding. The number of queries
+/// therefore varies from one layer to the other, as duplicate query positions
+/// are removed after each folding.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FriLayer {
+    /// Index of the layer, starting from 0 for the first folding.
+    pub index: usize,
+
+    /// Number of distinct query positions in the layer.
+    pub num_queries: usize,
+
+    /// Depth of the Merkle tree committing to the layer.
+    pub tree_depth: usize,
+
+    /// Authentication paths of the query positions, `[[_; tree_depth]; num_queries]`.
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<Vec<String>>"))]
+    pub proofs: Vec<Vec<BaseElement>>,
+
+    /// Folded evaluations at the query positions, `[_; num_queries * folding_factor]`.
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<String>"))]
+    pub queries: Vec<BaseElement>,
+}
+
+impl FriLayer {
+    /// Returns the authentication paths, padded with zeroes to `num_queries`
+    /// paths of `tree_depth` elements.
+    pub(crate) fn padded_proofs(
+        &self,
+        num_queries: usize,
+        tree_depth: usize,
+    ) -> Vec<Vec<BaseElement>> {
+        let mut proofs = self.proofs.clone();
+        for path in proofs.iter_mut() {
+            path.resize(tree_depth, BaseElement::ZERO);
+        }
+        proofs.resize(num_queries, vec![BaseElement::ZERO; tree_depth]);
+        proofs
+    }
+
+    /// Returns the folded evaluations, padded with zeroes to `len` elements.
+    pub(crate) fn padded_queries(&self, len: usize) -> Vec<BaseElement> {
+        let mut queries = self.queries.clone();
+        queries.resize(len, BaseElement::ZERO);
+        queries
+    }
+}
+
+/// Returns the `fri_layer_proofs` and `fri_layer_queries` input signals of the
+/// FRI layers of a proof, padded with zeroes to constant size arrays.
+///
+/// Proofs of traces whose LDE domain is no larger than the maximum remainder
+/// size have no FRI layer: the remainder is then the evaluation of the DEEP
+/// composition polynomial over the whole LDE domain. As Circom does not
+/// support empty arrays, these remainder-only proofs get a single layer of
+/// zeroes, which the `FriVerifier` template checks is zero.
+pub(crate) fn fri_layer_inputs(
+    fri_layers: &[FriLayer],
+    num_queries: usize,
+    folding_factor: usize,
+    tree_depth: usize,
+) -> (Vec<Vec<Vec<BaseElement>>>, Vec<Vec<BaseElement>>) {
+    let padding = [FriLayer {
+        index: 0,
+        num_queries: 0,
+        tree_depth: 0,
+        proofs: vec![],
+        queries: vec![],
+    }];
+    let fri_layers = if fri_layers.is_empty() {
+        &padding[..]
+    } else {
+        fri_layers
+    };
+
+    fri_layers
+        .iter()
+        .map(|layer| {
+            (
+                layer.padded_proofs(num_queries, tree_depth),
+                layer.padded_queries(num_queries * folding_factor),
+            )
+        })
+        .unzip()
+}
+
+// PUBLIC COIN SEED
+// ===========================================================================
+
+/// Returns the seed of the public coin, as fed to the `pub_coin_seed` input
+/// signal of the Circom circuit.
+///
+/// The seed is the serialization of the public inputs followed by the
+/// serialization of the proof context (`StarkProof::context`), zero-padded and
+/// split into f256 field elements (little-endian).
+///
+/// This allows external systems to pre-compute values derived from the public
+/// coin, and to check that the Rust and Circom transcripts agree.
+pub fn public_coin_seed<I, C>(pub_inputs: &I, context: &C) -> Vec<BaseElement>
+where
+    I: Serializable,
+    C: Serializable,
+{
+    bytes_to_elements(public_coin_seed_bytes(pub_inputs, context))
 }
 
 // HELPER FUNCTIONS
 // ===========================================================================
 
+/// Serialize public inputs and context into the bytes used to seed the public
+/// coin.
+pub(crate) fn public_coin_seed_bytes<I, C>(pub_inputs: &I, context: &C) -> Vec<u8>
+where
+    I: Serializable,
+    C: Serializable,
+{
+    let mut bytes = Vec::new();
+    pub_inputs.write_into(&mut bytes);
+    context.write_into(&mut bytes);
+    bytes
+}
+
+/// Turn bytes into f256 field elements, padding the last element with zeroes.
+fn bytes_to_elements(mut bytes: Vec<u8>) -> Vec<BaseElement> {
+    while bytes.len() % BaseElement::ELEMENT_BYTES != 0 {
+        bytes.push(0);
+    }
+    bytes
+        .as_slice()
+        .chunks(BaseElement::ELEMENT_BYTES)
+        .map(|bytes| BaseElement::from_le_bytes(bytes))
+        .collect::<Vec<_>>()
+}
+
 fn fold_positions(
     positions: &[usize],
     source_domain_size: usize,
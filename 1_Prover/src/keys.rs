@@ -0,0 +1,522 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    audit::{audited, hash_file, AuditAction},
+    beacon::{Beacon, BeaconValue},
+    tenant::validate_circuit_name,
+    utils::{check_file, command_execution, Executable, LoggingLevel, WinterCircomError},
+    workspace::circuit_dir,
+    HostRole, SnarkScheme,
+};
+
+/// Validity window of the circuit-specific keys of a circuit.
+///
+/// Epoch 0 is the key pair generated with the circuit. Each call to
+/// [rotate_keys] closes the current epoch and opens a new one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEpoch {
+    pub epoch: usize,
+
+    /// Start of the validity window, in seconds since the Unix epoch (0 if
+    /// unknown).
+    pub valid_from: u64,
+
+    /// End of the validity window, in seconds since the Unix epoch, or `None`
+    /// for the current keys.
+    pub valid_until: Option<u64>,
+}
+
+/// History of the keys of a circuit, stored in
+/// `target/circom/<circuit_name>/keys/history.json`.
+///
+/// The current keys (`verifier.zkey` and `verification_key.json`) remain in
+/// the circuit directory, while the keys of closed epochs are archived in the
+/// `keys/<epoch>/` directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyHistory {
+    pub epochs: Vec<KeyEpoch>,
+}
+
+impl KeyHistory {
+    /// Read the key history of the given circuit.
+    ///
+    /// A circuit whose keys have never been rotated has a single epoch. Returns
+    /// a [CorruptArtifact](WinterCircomError::CorruptArtifact) error if
+    /// `history.json` cannot be parsed or has no epoch.
+    pub fn read(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        let path = history_path(circuit_name);
+        let corrupt = |comment: String| WinterCircomError::CorruptArtifact {
+            file: path.to_string_lossy().into_owned(),
+            comment,
+        };
+        match fs::read_to_string(&path) {
+            Ok(data) => match serde_json::from_str::<KeyHistory>(&data) {
+                Ok(history) if history.epochs.is_empty() => {
+                    Err(corrupt(String::from("expected at least one epoch")))
+                }
+                Ok(history) => Ok(history),
+                Err(e) => Err(corrupt(e.to_string())),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(KeyHistory {
+                epochs: vec![KeyEpoch {
+                    epoch: 0,
+                    valid_from: 0,
+                    valid_until: None,
+                }],
+            }),
+            Err(e) => Err(WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("reading history.json")),
+            }),
+        }
+    }
+
+    /// Returns the current epoch.
+    pub fn current(&self) -> KeyEpoch {
+        *self.epochs.last().expect("key history should not be empty")
+    }
+
+    /// Returns the path of the verification key of the given epoch, or `None`
+    /// if the epoch does not exist.
+    pub fn verification_key(&self, circuit_name: &str, epoch: usize) -> Option<PathBuf> {
+        if epoch == self.current().epoch {
+            Some(circuit_dir(circuit_name).join("verification_key.json"))
+        } else if self.epochs.iter().any(|e| e.epoch == epoch) {
+            Some(archive_dir(circuit_name, epoch).join("verification_key.json"))
+        } else {
+            None
+        }
+    }
+
+    fn write(&self, circuit_name: &str) -> Result<(), WinterCircomError> {
+        let json = serde_json::to_string_pretty(self).expect("history should be serializable");
+        fs::write(history_path(circuit_name), json).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("writing history.json")),
+        })
+    }
+}
+
+/// Record of the last contribution to the circuit-specific keys of a circuit,
+/// stored in `target/circom/<circuit_name>/keys/attestation.json` by
+/// [finalize_keys].
+///
+/// Anyone can fetch the beacon value again from its
+/// [source](crate::BeaconSource), and check that it is the beacon of
+/// `snarkjs zkey verify`, and that the hashes match the distributed keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetupAttestation {
+    /// Epoch of the finalized keys (see [KeyHistory]).
+    pub epoch: usize,
+
+    pub beacon: BeaconValue,
+
+    /// The beacon is hashed `2^iterations_exp` times by snarkjs.
+    pub iterations_exp: u32,
+
+    /// Time of the finalization, in seconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// BLAKE3 hashes of the finalized `verifier.zkey` and
+    /// `verification_key.json` files, the latter being `None` on a
+    /// [Prover](HostRole::Prover) host.
+    pub zkey_hash: String,
+    pub verification_key_hash: Option<String>,
+}
+
+impl SetupAttestation {
+    /// Read the attestation of the keys of the given circuit, or `None` if
+    /// they were never finalized.
+    ///
+    /// The attestation is that of the last finalization, and does not cover
+    /// the keys of later epochs if the keys were rotated since. Returns a
+    /// [CorruptArtifact](WinterCircomError::CorruptArtifact) error if
+    /// `attestation.json` cannot be parsed.
+    pub fn read(circuit_name: &str) -> Result<Option<Self>, WinterCircomError> {
+        let path = attestation_path(circuit_name);
+        match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).map(Some).map_err(|e| {
+                WinterCircomError::CorruptArtifact {
+                    file: path.to_string_lossy().into_owned(),
+                    comment: e.to_string(),
+                }
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("reading attestation.json")),
+            }),
+        }
+    }
+
+    fn write(&self, circuit_name: &str) -> Result<(), WinterCircomError> {
+        let path = attestation_path(circuit_name);
+        let json = serde_json::to_string_pretty(self).expect("attestation should be serializable");
+        fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| fs::write(path, json))
+            .map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("writing attestation.json")),
+            })
+    }
+}
+
+/// Number of iterations of the beacon hash of [finalize_keys], as a power of
+/// two, the value recommended by snarkjs.
+const BEACON_ITERATIONS_EXP: u32 = 10;
+
+/// Finalize the circuit-specific keys of a circuit with a random beacon.
+///
+/// The value of the beacon is applied to the current `verifier.zkey` file with
+/// `snarkjs zkey beacon`, the matching `verification_key.json` file is
+/// exported, and a [SetupAttestation] records the provenance of the value. As
+/// the beacon is unpredictable, none of the previous contributors could have
+/// chosen the final keys, even if all of them colluded.
+///
+/// The keys should be finalized once per epoch, after the last contribution
+/// and before they are distributed. As with [rotate_keys], the verification
+/// key is not exported on a [Prover](HostRole::Prover) host, and the keys of
+/// [PLONK](SnarkScheme::Plonk) and [FFLONK](SnarkScheme::Fflonk) circuits
+/// cannot be finalized.
+pub fn finalize_keys(
+    circuit_name: &str,
+    beacon: &dyn Beacon,
+    logging_level: LoggingLevel,
+) -> Result<SetupAttestation, WinterCircomError> {
+    let mut attestation = None;
+    audited(
+        circuit_name,
+        AuditAction::FinalizeKeys,
+        json!({}),
+        &[
+            circuit_dir(circuit_name).join("verifier.zkey"),
+            circuit_dir(circuit_name).join("verification_key.json"),
+            attestation_path(circuit_name),
+        ],
+        || {
+            attestation = Some(finalize(circuit_name, beacon, &logging_level)?);
+            Ok(())
+        },
+    )?;
+    Ok(attestation.expect("finalization should have succeeded"))
+}
+
+/// Rotate the circuit-specific keys of a circuit.
+///
+/// A new contribution, with random entropy, is added to the current
+/// `verifier.zkey` file, and the matching `verification_key.json` file is
+/// exported. The previous keys are archived in the `keys/<epoch>/` directory
+/// and their validity window is closed in the [KeyHistory].
+///
+/// Proofs generated with archived keys can still be accepted with
+/// [circom_verify_with_history].
+///
+/// On a [Prover](HostRole::Prover) host, the verification key is neither
+/// archived nor exported. The keys of [PLONK](SnarkScheme::Plonk) and
+/// [FFLONK](SnarkScheme::Fflonk) circuits cannot be rotated.
+pub fn rotate_keys(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<KeyEpoch, WinterCircomError> {
+    let mut rotated = None;
+    audited(
+        circuit_name,
+        AuditAction::RotateKeys,
+        json!({}),
+        &[
+            circuit_dir(circuit_name).join("verifier.zkey"),
+            circuit_dir(circuit_name).join("verification_key.json"),
+        ],
+        || {
+            rotated = Some(rotate(circuit_name, &logging_level)?);
+            Ok(())
+        },
+    )?;
+    Ok(rotated.expect("rotation should have succeeded"))
+}
+
+/// Verify the proof of a circuit against the verification keys of the given
+/// epochs, in order.
+///
+/// Returns the epoch of the first key accepting the proof, or the error of the
+/// last attempt if none does. Unknown epochs are skipped. As with
+/// [circom_verify](crate::circom_verify), this function must be used alongside
+/// [check_ood_frame](crate::check_ood_frame).
+pub fn circom_verify_with_history(
+    circuit_name: &str,
+    accepted_epochs: &[usize],
+    logging_level: LoggingLevel,
+) -> Result<usize, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let history = KeyHistory::read(circuit_name)?;
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    check_file(
+        dir.join("public.json").to_string_lossy().into_owned(),
+        Some("needed for verification"),
+    )?;
+    check_file(
+        dir.join("proof.json").to_string_lossy().into_owned(),
+        Some("needed for verification"),
+    )?;
+
+    let mut result = Err(WinterCircomError::FileNotFound {
+        file: String::from("verification_key.json"),
+        comment: Some(String::from("no verification key for the accepted epochs")),
+    });
+    for epoch in accepted_epochs {
+        let vkey = match history.verification_key(circuit_name, *epoch) {
+            Some(vkey) if vkey.exists() => vkey,
+            _ => continue,
+        };
+
+        if logging_level.print_big_steps() {
+            println!(
+                "{}",
+                format!("Verifying against keys of epoch {}...", epoch).green()
+            );
+        }
+
+        result = command_execution(
+            Executable::SnarkJS,
+            &[
+                scheme.verify_command(),
+                vkey.to_str().expect("non UTF-8 key path"),
+                "public.json",
+                "proof.json",
+            ],
+            dir.to_str(),
+            &logging_level,
+        )
+        .map(|_| *epoch);
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    result
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn rotate(circuit_name: &str, logging_level: &LoggingLevel) -> Result<KeyEpoch, WinterCircomError> {
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    if !scheme.has_circuit_specific_setup() {
+        return Err(WinterCircomError::UnsupportedScheme {
+            scheme: String::from(scheme.name()),
+            comment: String::from("the keys have no circuit-specific contribution to rotate"),
+        });
+    }
+
+    let dir = circuit_dir(circuit_name);
+    let role = HostRole::from_env()?;
+    let keys = ["verifier.zkey", "verification_key.json"]
+        .into_iter()
+        .filter(|file| role.needs(file))
+        .collect::<Vec<_>>();
+    for file in keys.iter() {
+        check_file(
+            dir.join(file).to_string_lossy().into_owned(),
+            Some("did you generate the circuit-specific keys?"),
+        )?;
+    }
+
+    let mut history = KeyHistory::read(circuit_name)?;
+    let current = history.current();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // ARCHIVE CURRENT KEYS
+
+    if logging_level.print_big_steps() {
+        println!(
+            "{}",
+            format!("Archiving keys of epoch {}...", current.epoch).green()
+        );
+    }
+
+    let archive = archive_dir(circuit_name, current.epoch);
+    fs::create_dir_all(&archive).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("creating key archive directory")),
+    })?;
+    for file in keys.iter() {
+        fs::copy(dir.join(file), archive.join(file)).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("archiving {}", file)),
+        })?;
+    }
+
+    // CONTRIBUTE AND EXPORT NEW KEYS
+
+    if logging_level.print_big_steps() {
+        println!("{}", "Generating new circuit-specific keys...".green());
+    }
+
+    let next = KeyEpoch {
+        epoch: current.epoch + 1,
+        valid_from: now,
+        valid_until: None,
+    };
+    command_execution(
+        Executable::SnarkJS,
+        &[
+            "zkc",
+            "verifier.zkey",
+            "verifier_next.zkey",
+            &format!("--name=rotation {}", next.epoch),
+            &format!("-e={}", random_entropy()),
+        ],
+        dir.to_str(),
+        logging_level,
+    )?;
+    fs::rename(dir.join("verifier_next.zkey"), dir.join("verifier.zkey")).map_err(|e| {
+        WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("replacing verifier.zkey")),
+        }
+    })?;
+    if role.needs("verification_key.json") {
+        command_execution(
+            Executable::SnarkJS,
+            &["zkev", "verifier.zkey", "verification_key.json"],
+            dir.to_str(),
+            logging_level,
+        )?;
+    }
+
+    // UPDATE HISTORY
+
+    history.epochs.last_mut().unwrap().valid_until = Some(now);
+    history.epochs.push(next);
+    history.write(circuit_name)?;
+
+    Ok(next)
+}
+
+fn finalize(
+    circuit_name: &str,
+    beacon: &dyn Beacon,
+    logging_level: &LoggingLevel,
+) -> Result<SetupAttestation, WinterCircomError> {
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    if !scheme.has_circuit_specific_setup() {
+        return Err(WinterCircomError::UnsupportedScheme {
+            scheme: String::from(scheme.name()),
+            comment: String::from("the keys have no circuit-specific contribution to finalize"),
+        });
+    }
+
+    let dir = circuit_dir(circuit_name);
+    check_file(
+        dir.join("verifier.zkey").to_string_lossy().into_owned(),
+        Some("did you generate the circuit-specific keys?"),
+    )?;
+
+    // FETCH BEACON
+
+    if logging_level.print_big_steps() {
+        println!("{}", "Fetching random beacon...".green());
+    }
+
+    let value = beacon.fetch()?;
+
+    // APPLY BEACON AND EXPORT KEYS
+
+    if logging_level.print_big_steps() {
+        println!("{}", "Finalizing circuit-specific keys...".green());
+    }
+
+    command_execution(
+        Executable::SnarkJS,
+        &[
+            "zkb",
+            "verifier.zkey",
+            "verifier_final.zkey",
+            &value.hex,
+            &BEACON_ITERATIONS_EXP.to_string(),
+            "--name=beacon",
+        ],
+        dir.to_str(),
+        logging_level,
+    )?;
+    fs::rename(dir.join("verifier_final.zkey"), dir.join("verifier.zkey")).map_err(|e| {
+        WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("replacing verifier.zkey")),
+        }
+    })?;
+    let exports_vk = HostRole::from_env()?.needs("verification_key.json");
+    if exports_vk {
+        command_execution(
+            Executable::SnarkJS,
+            &["zkev", "verifier.zkey", "verification_key.json"],
+            dir.to_str(),
+            logging_level,
+        )?;
+    }
+
+    // WRITE ATTESTATION
+
+    let hash = |file: &str| {
+        hash_file(&dir.join(file)).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("hashing {}", file)),
+        })
+    };
+    let attestation = SetupAttestation {
+        epoch: KeyHistory::read(circuit_name)?.current().epoch,
+        beacon: value,
+        iterations_exp: BEACON_ITERATIONS_EXP,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        zkey_hash: hash("verifier.zkey")?,
+        verification_key_hash: if exports_vk {
+            Some(hash("verification_key.json")?)
+        } else {
+            None
+        },
+    };
+    attestation.write(circuit_name)?;
+
+    Ok(attestation)
+}
+
+fn attestation_path(circuit_name: &str) -> PathBuf {
+    circuit_dir(circuit_name)
+        .join("keys")
+        .join("attestation.json")
+}
+
+fn archive_dir(circuit_name: &str, epoch: usize) -> PathBuf {
+    circuit_dir(circuit_name)
+        .join("keys")
+        .join(epoch.to_string())
+}
+
+fn history_path(circuit_name: &str) -> PathBuf {
+    circuit_dir(circuit_name).join("keys").join("history.json")
+}
+
+/// Returns random entropy for a key contribution, drawn from the randomly
+/// seeded hasher of the standard library.
+pub(crate) fn random_entropy() -> String {
+    (0..4)
+        .map(|_| format!("{:016x}", RandomState::new().build_hasher().finish()))
+        .collect()
+}
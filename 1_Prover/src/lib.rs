@@ -4,10 +4,14 @@
 //!
 //! The main API components of this library are the following functions:
 //!
-//! - `circom_compile` for generating a Circom circuit capable of verifying a
-//! Winterfell proof, compiling it and generating circuit-specific keys.
-//! - `circom_prove` for generating a SNARK Groth16 proof of the verification of
-//! the Winterfell proof.
+//! - `circom_create` for generating a Circom circuit capable of verifying a
+//! Winterfell proof.
+//! - `circom_prove` for generating the inputs of the circuit from a Winterfell
+//! proof.
+//! - `circom_compile`, `groth16_setup`, `compute_witness` and `groth16_prove`
+//! for compiling the circuit, generating circuit-specific keys and generating a
+//! SNARK Groth16 proof of the verification of the Winterfell proof. These
+//! stages can be chained with a `Pipeline`.
 //! - `circom_verify` for verifying the proof generated by the previous function.
 //!
 //! # Powers of tau phase 1 transcript
@@ -177,7 +181,7 @@
 //! impl Default for WorkAir {
 //!     fn default() -> Self {
 //!         WorkAir::new(
-//!             TraceInfo::new(0, 0),
+//!             TraceInfo::new(2, 8),
 //!             PublicInputs::default(),
 //!             ProofOptions::new(
 //!                 32,
@@ -374,10 +378,12 @@
 //! **Compile executable**
 //!
 //! ```rust
-//! use winter_circom_prover::{circom_compile, utils::{LoggingLevel, WinterCircomError}};
+//! use winter_circom_prover::{circom_create, utils::{LoggingLevel, WinterCircomError}, Pipeline};
 //!
 //! fn main() -> Result<(), WinterCircomError> {
-//!     circom_compile::<WorkProver, 2>(PROOF_OPTIONS, "sum", LoggingLevel::Default)
+//!     circom_create::<WorkProver, 2>(PROOF_OPTIONS, "sum", LoggingLevel::Default)?;
+//!     Pipeline::new("sum").compile().setup().run(LoggingLevel::Default)?;
+//!     Ok(())
 //! }
 //! ```
 //!
@@ -388,6 +394,7 @@
 //!     circom_prove,
 //!     utils::{LoggingLevel, WinterCircomError},
 //!     winterfell::math::{fields::f256::BaseElement, FieldElement},
+//!     Pipeline,
 //! };
 //!
 //! fn main() -> Result<(), WinterCircomError> {
@@ -399,7 +406,9 @@
 //!     let prover = WorkProver::new(options.clone());
 //!     let trace = prover.build_trace(start, PROOF_OPTIONS.trace_length);
 //!
-//!     circom_prove(prover, trace, "sum", LoggingLevel::Default)
+//!     circom_prove(prover, trace, "sum", LoggingLevel::Default)?;
+//!     Pipeline::new("sum").witness().prove().run(LoggingLevel::Default)?;
+//!     Ok(())
 //! }
 //! ```
 //!
@@ -432,6 +441,44 @@
 //! In a real life scenario, the last step is of course executed by another
 //! party.
 //!
+//! # Features
+//!
+//! Each capability of the crate is a feature, so that the applications only
+//! compile the dependencies of the ones they use:
+//!
+//! - `codegen`: Circom code generation ([circom_create], [scaffold]), without
+//! the pipeline. It depends on the `rug` crate, which requires GMP to be built
+//! for the target.
+//! - `prove`: Groth16 proving and verification ([circom_prove],
+//! [circom_verify], [Pipeline]), key rotation, key finalization with a
+//! random [Beacon] and preflight (requires `codegen`). These functions call
+//! the external Circom and snarkjs executables.
+//! - `verify` (default): out-of-domain consistency check of the Groth16
+//! public signals ([check_ood_frame], [exposed_public_inputs]), in pure Rust.
+//! - `service`: async variants of the pipeline stages, running the external
+//! commands with `tokio`, and the upload of the proofs to a remote verifier
+//! ([ArtifactUpload]) (requires `prove`).
+//! - `testkit`: the [MockExecutor] testing the orchestration of the pipeline
+//! without the external tools, and the security self-test (requires `prove`
+//! and `verify`).
+//! - `cli`: the `winter-circom` executable, see [cli] (requires `prove`).
+//! - `noir`: export of the proof inputs to Noir (requires `prove`).
+//! - `parquet`: loading execution traces from Parquet files.
+//! - `schema`: JSON schemas of the generated artifacts (see [schema]).
+//!
+//! The `prover`, `verifier` and `async` features are the previous names of
+//! `prove`, `verify` and `service`. The Winterfell fork is a single crate, so
+//! that every feature set compiles its prover. The default feature set does not
+//! depend on any C library, nor on `colored`: without `codegen`, messages are
+//! printed without colors. It builds on any target supported by the Rust
+//! standard library.
+//!
+//! # Stability
+//!
+//! The supported API is re-exported by the [prelude], and follows semantic
+//! versioning. The other public items may change with the internals of the
+//! pipeline, e.g. the names of the input signals of the generated circuits.
+//!
 //! # Disclaimer
 //!
 //! This library is a research project, has not been audited for safety and
@@ -440,22 +487,278 @@
 //! The circuit-specific keys, generated by the `compile` executable, do not
 //! contain contributions and are therefore unsafe to use in production.
 
+// The shared helpers (JSON export, command execution, logging) are mostly used
+// by the proving functions.
+#![cfg_attr(not(feature = "prove"), allow(dead_code, unused_imports))]
+
 use serde::Serialize;
 
 mod json;
+pub use json::{public_coin_seed, FriLayer};
+
+mod signals;
+// the signals follow circuits/verify.circom, and the code generator
+#[doc(hidden)]
+pub use signals::Signal;
 
+mod packing;
+pub use packing::{PublicInputLayout, MAX_PACKED_BITS};
+
+#[cfg(feature = "codegen")]
+mod codegen;
+#[cfg(feature = "codegen")]
+pub use codegen::{circom_create, MainArgument};
+
+#[cfg(feature = "prove")]
 mod circom;
-pub use circom::{circom_create, circom_prove, circom_verify};
+#[cfg(feature = "prove")]
+pub use circom::{
+    circom_prove, circom_prove_from_proof, circom_prove_with_data, circom_verify, ProveArtifacts,
+    ProveTimings,
+};
+
+#[cfg(feature = "codegen")]
+mod in_memory;
+#[cfg(feature = "codegen")]
+pub use in_memory::{circom_create_in_memory, InMemoryCircuit};
+#[cfg(feature = "prove")]
+pub use in_memory::{circom_prove_in_memory, circom_verify_in_memory, CircuitInputs};
+
+mod encoding;
+pub use encoding::{ByteOrder, ElementEncoding, Representation};
+
+mod manifest;
+use manifest::check_column_map;
+pub use manifest::{fri_tree_depths, CircuitManifest, CircuitParameters, IntermediateVerification};
+
+mod scheme;
+pub use scheme::SnarkScheme;
+
+mod optimizer;
+pub use optimizer::{
+    OptimizationGoal, OptimizationReport, OptimizedProofOptions, ProofOptionsCandidate,
+    ProofOptionsOptimizer,
+};
+
+mod lint;
+pub use lint::{lint_air_source, lint_air_template, AirLint, LintKind, LintSeverity};
+
+mod assertions;
+pub use assertions::canonical_assertions;
+
+mod constraints;
+pub use constraints::{
+    constraint_breakdown, transition_constraint_costs, transition_degrees, ConstraintBreakdown,
+    ConstraintCategory, TransitionConstraintCost,
+};
+
+mod workspace;
+pub use workspace::{with_workspace, CircomWorkspace};
+
+mod scratch;
+pub use scratch::ScratchSpace;
+
+mod watchdog;
+pub use watchdog::Watchdog;
+
+mod executor;
+#[cfg(any(test, feature = "testkit"))]
+pub use executor::MockExecutor;
+pub use executor::{with_executor, Executor, Invocation, ProcessExecutor};
+
+#[cfg(feature = "prove")]
+mod memory;
+#[cfg(feature = "prove")]
+pub use memory::MemoryLimit;
+
+#[cfg(feature = "prove")]
+mod concurrency;
+#[cfg(feature = "prove")]
+pub use concurrency::Concurrency;
+
+mod interrupt;
+#[cfg(feature = "prove")]
+pub use interrupt::install_interrupt_handler;
+
+mod cancellation;
+pub use cancellation::{with_cancellation, CancellationToken};
+
+mod audit;
+pub use audit::{history, AuditAction, AuditEntry};
+
+mod telemetry;
+pub use telemetry::{Telemetry, TelemetryReport};
+
+mod stats;
+pub use stats::ProofStats;
+
+mod tenant;
+pub use tenant::{validate_circuit_name, CleanupPolicy, Tenant};
+
+mod gc;
+pub use gc::{gc, GcPolicy, GcReport, StaleCircuit, StaleReason};
+
+mod roles;
+pub use roles::{distribute_artifacts, prune_artifacts, HostRole};
+
+mod backend;
+pub use backend::ProverBackend;
+
+#[cfg(feature = "prove")]
+mod snark_backend;
+#[cfg(feature = "prove")]
+pub use snark_backend::{with_snark_backend, SnarkBackend, SnarkJSBackend};
+
+#[cfg(feature = "prove")]
+mod solidity;
+#[cfg(feature = "prove")]
+pub use solidity::{export_solidity_verifier, Groth16Calldata, Groth16Proof};
+
+#[cfg(feature = "prove")]
+mod starknet;
+#[cfg(feature = "prove")]
+pub use starknet::{export_cairo_verifier, starknet_calldata};
+
+#[cfg(feature = "prove")]
+mod solana;
+#[cfg(feature = "prove")]
+pub use solana::{export_solana_verifier, solana_instruction_data};
+
+#[cfg(feature = "prove")]
+mod ood_check;
+#[cfg(feature = "prove")]
+pub use ood_check::{circom_check_ood, circom_create_ood_check, OodCheckInputs};
+
+#[cfg(feature = "service")]
+mod upload;
+#[cfg(feature = "service")]
+pub use upload::ArtifactUpload;
+
+#[cfg(feature = "prove")]
+mod checked;
+#[cfg(feature = "prove")]
+pub use checked::CheckedProver;
 
+#[cfg(feature = "codegen")]
+mod scaffold;
+#[cfg(feature = "codegen")]
+pub use scaffold::{scaffold, ScaffoldOptions};
+
+#[cfg(feature = "prove")]
+mod preflight;
+#[cfg(feature = "prove")]
+pub use preflight::{
+    preflight, CheckStatus, PreflightCheck, PreflightReport, PreflightRequirements,
+};
+
+#[cfg(feature = "prove")]
+mod pipeline;
+#[cfg(feature = "prove")]
+pub use pipeline::{
+    circom_compile, compute_witness, compute_witness_from_input, groth16_prove, groth16_setup,
+    groth16_verify, Pipeline, Stage,
+};
+
+#[cfg(feature = "prove")]
+mod smoke;
+#[cfg(feature = "prove")]
+pub use smoke::{circom_create_smoke_tested, smoke_test};
+
+#[cfg(feature = "service")]
+mod async_pipeline;
+#[cfg(feature = "service")]
+pub use async_pipeline::{
+    circom_compile_async, compute_witness_async, groth16_prove_async, groth16_setup_async,
+    groth16_verify_async,
+};
+
+#[cfg(feature = "prove")]
+mod plan;
+#[cfg(feature = "prove")]
+pub use plan::{plan, PipelinePlan, PlannedFile, PlannedStep};
+
+#[cfg(feature = "prove")]
+mod project;
+#[cfg(feature = "prove")]
+pub use project::{build_all, CircuitBuild, CircuitRegistry, ProjectCircuit, ProjectManifest};
+
+#[cfg(feature = "prove")]
+mod beacon;
+#[cfg(feature = "prove")]
+pub use beacon::{Beacon, BeaconSource, BeaconValue, BlockhashBeacon, DrandBeacon, ManualBeacon};
+
+#[cfg(feature = "prove")]
+mod keys;
+#[cfg(feature = "prove")]
+pub use keys::{
+    circom_verify_with_history, finalize_keys, rotate_keys, KeyEpoch, KeyHistory, SetupAttestation,
+};
+
+#[cfg(feature = "prove")]
+mod proofs;
+#[cfg(feature = "prove")]
+pub use proofs::{circom_verify_proof, list_proofs, proof_dir, save_proof};
+
+#[cfg(feature = "prove")]
+mod package;
+#[cfg(feature = "prove")]
+pub use package::export_verifier_package;
+
+#[cfg(feature = "verify")]
 mod verification;
-pub use verification::check_ood_frame;
+#[cfg(feature = "verify")]
+pub use verification::{
+    check_ood_frame, check_public_commitment, decode_public_signals, exposed_public_inputs, OodData,
+};
+
+#[cfg(feature = "testkit")]
+mod selftest;
+#[cfg(feature = "testkit")]
+pub use selftest::{security_selftest, Mutation};
+
+#[cfg(feature = "noir")]
+pub mod noir;
+
+#[cfg(feature = "native")]
+pub mod native;
+
+#[cfg(feature = "evm")]
+pub mod evm;
+
+#[cfg(feature = "schema")]
+pub mod schema;
+
+#[cfg(feature = "ipfs")]
+pub mod ipfs;
+
+#[cfg(feature = "cli")]
+pub mod cli;
+
+pub mod circom_poseidon;
+
+pub mod prelude;
+
+pub mod commitment;
+
+pub mod gadgets;
+
+pub mod trace;
 
 pub mod utils;
 
+#[cfg(test)]
+mod tests;
+
 /// Re-export of a modified version of Winterfell, that has been adapted to suit
 /// the needs of this crate.
 pub use winterfell;
-use winterfell::{HashFunction, ProofOptions, TransitionConstraintDegree};
+use winterfell::{math::log2, ProofOptions, TransitionConstraintDegree};
+
+/// Smallest LDE blowup factor supported by Winterfell.
+const MIN_LDE_BLOWUP_FACTOR: usize = 2;
+
+/// Default target soundness of the proofs, in bits.
+const DEFAULT_SECURITY_BITS: u32 = 128;
 
 /// Trait for compatibility between implementations of [winterfell::Air::PublicInputs]
 /// and this crate.
@@ -464,6 +767,13 @@ use winterfell::{HashFunction, ProofOptions, TransitionConstraintDegree};
 /// [NUM_PUB_INPUTS](WinterPublicInputs::NUM_PUB_INPUTS) constant).
 pub trait WinterPublicInputs: Serialize + Clone {
     const NUM_PUB_INPUTS: usize;
+
+    /// Bit widths of the public inputs, used to pack them into fewer Groth16
+    /// public signals (see [PublicInputLayout]). A width of 0 denotes a full
+    /// field element.
+    ///
+    /// Public inputs are not packed by default.
+    const PUBLIC_INPUT_BITS: &'static [usize] = &[];
 }
 
 /// Proof options for a input-independant circuit.
@@ -474,6 +784,7 @@ pub trait WinterPublicInputs: Serialize + Clone {
 ///
 /// Element `transition_constraint_degree` is a usize array that will be mapped to
 /// an array of [TransitionConstraintDegree] through its `new()` method.
+#[derive(Clone, Copy)]
 pub struct WinterCircomProofOptions<const N: usize> {
     pub trace_length: usize,
     pub trace_width: usize,
@@ -484,6 +795,22 @@ pub struct WinterCircomProofOptions<const N: usize> {
     grinding_factor: u32,
     fri_folding_factor: usize,
     fri_max_remainder_size: usize,
+    expose_public_inputs: bool,
+    commit_public_signals: bool,
+    data_commitment: Option<DataCommitment>,
+    intermediate_verification: IntermediateVerification,
+    scaffold_on_missing: bool,
+    target_security_bits: u32,
+    snark_scheme: SnarkScheme,
+    column_map: Option<&'static [usize]>,
+}
+
+/// External data bound to a public input (see
+/// [bind_data_commitment](WinterCircomProofOptions::bind_data_commitment)).
+#[derive(Clone, Copy)]
+struct DataCommitment {
+    public_input_index: usize,
+    max_bytes: usize,
 }
 
 impl<const N: usize> WinterCircomProofOptions<N> {
@@ -508,24 +835,293 @@ impl<const N: usize> WinterCircomProofOptions<N> {
             grinding_factor,
             fri_folding_factor,
             fri_max_remainder_size,
+            expose_public_inputs: false,
+            commit_public_signals: false,
+            data_commitment: None,
+            intermediate_verification: IntermediateVerification::DebugOnly,
+            scaffold_on_missing: false,
+            target_security_bits: DEFAULT_SECURITY_BITS,
+            snark_scheme: SnarkScheme::Groth16,
+            column_map: None,
+        }
+    }
+
+    /// Declare the public inputs of the AIR as public signals of the generated
+    /// Circom main component.
+    ///
+    /// By default, the public inputs are private signals of the Groth16 proof,
+    /// which only attests that *some* public inputs are valid. With this option,
+    /// the public inputs are appended to the Groth16 public signals, binding the
+    /// proof to the application-level statement (e.g. a message and a public key).
+    pub const fn expose_public_inputs(self) -> Self {
+        Self {
+            expose_public_inputs: true,
+            ..self
+        }
+    }
+
+    /// Replace the public signals of the generated Circom main component by
+    /// their Poseidon hash, a single public signal.
+    ///
+    /// The OOD frame and constraint evaluations, and the public inputs if they
+    /// are exposed (see
+    /// [expose_public_inputs](WinterCircomProofOptions::expose_public_inputs)),
+    /// are then private signals, whose hash is the only value of `public.json`.
+    /// This keeps the cost of on-chain verification constant, whatever the
+    /// size of the AIR: the verifier recomputes the commitment from the values
+    /// it expects (see [check_public_commitment](crate::check_public_commitment)).
+    pub const fn commit_public_signals(self) -> Self {
+        Self {
+            commit_public_signals: true,
+            ..self
         }
     }
 
+    /// Bind external data of at most `max_bytes` bytes to the public input of
+    /// index `public_input_index`.
+    ///
+    /// The generated Circom main component checks that this public input is the
+    /// [commitment](crate::commitment) to the data given to
+    /// [circom_prove_with_data](crate::circom_prove_with_data). The public input
+    /// is typically exposed as well (see
+    /// [expose_public_inputs](WinterCircomProofOptions::expose_public_inputs)),
+    /// so that verifiers can compare it with the commitment to the data they
+    /// expect.
+    pub const fn bind_data_commitment(self, public_input_index: usize, max_bytes: usize) -> Self {
+        Self {
+            data_commitment: Some(DataCommitment {
+                public_input_index,
+                max_bytes,
+            }),
+            ..self
+        }
+    }
+
+    /// Set when [circom_prove](crate::circom_prove) verifies the Winterfell
+    /// proof before converting it into circuit inputs.
+    ///
+    /// The mode is recorded in the [CircuitManifest], along with whether the
+    /// last proof was actually verified. It defaults to
+    /// [DebugOnly](IntermediateVerification::DebugOnly).
+    pub const fn intermediate_verification(self, mode: IntermediateVerification) -> Self {
+        Self {
+            intermediate_verification: mode,
+            ..self
+        }
+    }
+
+    /// Set the proving system of the circuit, Groth16 by default (see
+    /// [SnarkScheme]).
+    ///
+    /// The scheme is recorded in the [CircuitManifest], and used by all the
+    /// stages of the pipeline of the circuit.
+    pub const fn snark_scheme(self, snark_scheme: SnarkScheme) -> Self {
+        Self {
+            snark_scheme,
+            ..self
+        }
+    }
+
+    /// Declare the order in which the AIR template reads the trace columns:
+    /// column `i` of the template, e.g. the `column` of its assertions, is
+    /// column `template_columns[i]` of the Winterfell trace.
+    ///
+    /// This is meant for templates written for another column order than the
+    /// one of the Rust trace. The map is recorded in the [CircuitManifest] and
+    /// compiled into the circuit, which applies it to the columns of the
+    /// assertions. The signals exported by [circom_prove](crate::circom_prove)
+    /// keep the order of the trace, which its commitments and the public coin
+    /// depend on. [circom_create](crate::circom_create) refuses a map that is
+    /// not a permutation of the trace columns, and `circom_prove` a proof whose
+    /// trace width differs from the one of the map.
+    pub const fn remap_columns(self, template_columns: &'static [usize]) -> Self {
+        Self {
+            column_map: Some(template_columns),
+            ..self
+        }
+    }
+
+    /// Check the [column map](WinterCircomProofOptions::remap_columns) of the
+    /// options against the trace width.
+    pub fn check_column_map(&self) -> Result<(), utils::WinterCircomError> {
+        match self.column_map {
+            Some(column_map) => check_column_map(column_map, self.trace_width),
+            None => Ok(()),
+        }
+    }
+
+    /// Write a commented stub of the AIR template if it is missing.
+    ///
+    /// [circom_create](crate::circom_create) then still fails with
+    /// [MissingAirTemplate](utils::WinterCircomError::MissingAirTemplate), but
+    /// the stub at `circuits/air/<circuit_name>.circom` already declares the
+    /// transition constraint degrees and number of assertions of these options,
+    /// leaving only the assertions to be defined.
+    pub const fn scaffold_on_missing(self) -> Self {
+        Self {
+            scaffold_on_missing: true,
+            ..self
+        }
+    }
+
+    /// Set the target soundness of the proofs, in bits (128 by default).
+    ///
+    /// The Circom circuit draws query positions until `num_queries` distinct
+    /// ones are found except with probability `2^-bits`, so that a lower target
+    /// (e.g. 96 bits) results in fewer draws and a smaller circuit. The target
+    /// is recorded in the [CircuitManifest], and
+    /// [circom_create](crate::circom_create) warns if the
+    /// [conjectured security](WinterCircomProofOptions::conjectured_security_bits)
+    /// of the options is lower.
+    pub const fn target_security(self, bits: u32) -> Self {
+        Self {
+            target_security_bits: bits,
+            ..self
+        }
+    }
+
+    /// Returns the target soundness of the proofs, in bits.
+    pub fn target_security_bits(&self) -> u32 {
+        self.target_security_bits
+    }
+
+    /// Returns the conjectured security of proofs generated with these options,
+    /// in bits.
+    ///
+    /// Under the usual conjecture on the soundness of FRI, each query provides
+    /// `log2(lde_blowup_factor)` bits of security, and grinding adds
+    /// `grinding_factor` bits. The limits of the field size and of the
+    /// collision resistance of the hash function are not accounted for.
+    pub fn conjectured_security_bits(&self) -> u32 {
+        self.num_queries as u32 * log2(self.lde_blowup_factor) + self.grinding_factor
+    }
+
+    /// Check the target soundness of the proofs against the conjectured security
+    /// of the options.
+    ///
+    /// Returns a warning if the options do not reach the target.
+    pub fn check_target_security(&self) -> Option<String> {
+        let conjectured = self.conjectured_security_bits();
+        (conjectured < self.target_security_bits).then(|| {
+            format!(
+                "the proof options provide {} bits of conjectured security, below the \
+                target of {} bits",
+                conjectured, self.target_security_bits
+            )
+        })
+    }
+
     pub fn get_proof_options(&self) -> ProofOptions {
-        assert!(self.trace_length * self.lde_blowup_factor > self.fri_max_remainder_size,
-            "trace_length * lde_blowup_factor must be greater than fri_max_remainder_size for the Circom circuit to work");
-
-        ProofOptions::new(
-            self.num_queries,
-            self.lde_blowup_factor,
-            self.grinding_factor,
-            HashFunction::Poseidon,
-            winterfell::FieldExtension::None,
+        self.circuit_parameters().proof_options()
+    }
+
+    /// Returns the parameters of the circuit generated with these options.
+    ///
+    /// All the options but
+    /// [intermediate_verification](WinterCircomProofOptions::intermediate_verification)
+    /// and [scaffold_on_missing](WinterCircomProofOptions::scaffold_on_missing)
+    /// are fixed by the compiled circuit. The parameters of the Winterfell
+    /// proofs are among them, and are recorded in the [CircuitManifest] so that
+    /// provers can read them back with
+    /// [CircuitParameters::of_circuit] instead of these options.
+    pub fn circuit_parameters(&self) -> CircuitParameters {
+        CircuitParameters {
+            trace_length: self.trace_length,
+            trace_width: self.trace_width,
+            num_transition_constraints: N,
+            num_assertions: self.num_assertions,
+            num_queries: self.num_queries,
+            lde_blowup_factor: self.lde_blowup_factor,
+            grinding_factor: self.grinding_factor,
+            fri_folding_factor: self.fri_folding_factor,
+            fri_max_remainder_size: self.fri_max_remainder_size,
+        }
+    }
+
+    /// Returns the depths of the Merkle trees committing to the FRI layers of
+    /// proofs generated with these options (see [fri_tree_depths]).
+    pub fn fri_tree_depths(&self) -> Vec<usize> {
+        fri_tree_depths(
+            self.trace_length * self.lde_blowup_factor,
             self.fri_folding_factor,
             self.fri_max_remainder_size,
         )
     }
 
+    /// Returns the number of FRI layers of proofs generated with these options.
+    ///
+    /// This is 0 for tiny traces whose LDE domain is no larger than the maximum
+    /// remainder size: the FRI proof is then only the remainder, i.e. the DEEP
+    /// composition polynomial evaluated over the whole LDE domain, whose degree
+    /// is checked directly by the circuit.
+    pub fn num_fri_layers(&self) -> usize {
+        self.fri_tree_depths().len()
+    }
+
+    /// Returns the number of columns of the constraint composition polynomial
+    /// of proofs generated with these options.
+    ///
+    /// Winterfell splits the composition polynomial into as many columns as the
+    /// constraint evaluation blowup factor, i.e. the highest transition
+    /// constraint degree rounded up to a power of two. AIRs with high-degree
+    /// constraints therefore need more composition columns, each of which is
+    /// committed to and opened at every query.
+    ///
+    /// This is computed from the declared degrees exactly as Winterfell does in
+    /// [AirContext](winterfell::AirContext).
+    pub fn num_composition_columns(&self) -> usize {
+        self.transition_constraint_degrees()
+            .iter()
+            .map(|degree| degree.min_blowup_factor())
+            .max()
+            .unwrap_or(MIN_LDE_BLOWUP_FACTOR)
+    }
+
+    /// Returns the smallest sound LDE blowup factor for the declared transition
+    /// constraint degrees.
+    ///
+    /// The constraints are evaluated over the LDE domain, which must therefore be
+    /// at least as large as the constraint evaluation domain. A smaller blowup
+    /// factor is rejected by [circom_create](crate::circom_create), while a
+    /// larger one only increases the proving time and the size of the circuit
+    /// (it also increases the security of each query, which can be obtained
+    /// with more queries instead).
+    pub fn min_lde_blowup_factor(&self) -> usize {
+        self.num_composition_columns().max(MIN_LDE_BLOWUP_FACTOR)
+    }
+
+    /// Set the LDE blowup factor to the smallest sound one (see
+    /// [min_lde_blowup_factor](WinterCircomProofOptions::min_lde_blowup_factor)).
+    pub fn with_min_lde_blowup_factor(self) -> Self {
+        Self {
+            lde_blowup_factor: self.min_lde_blowup_factor(),
+            ..self
+        }
+    }
+
+    /// Check the LDE blowup factor against the declared transition constraint
+    /// degrees.
+    ///
+    /// Returns an error if the blowup factor is too small to be sound, and a
+    /// warning if it is larger than needed.
+    pub fn check_lde_blowup_factor(&self) -> Result<Option<String>, utils::WinterCircomError> {
+        let min_blowup_factor = self.min_lde_blowup_factor();
+        if self.lde_blowup_factor < min_blowup_factor {
+            return Err(utils::WinterCircomError::BlowupFactorTooSmall {
+                blowup_factor: self.lde_blowup_factor,
+                min_blowup_factor,
+            });
+        }
+
+        Ok((self.lde_blowup_factor > min_blowup_factor).then(|| {
+            format!(
+                "LDE blowup factor {} is larger than the {} needed by the transition \
+                constraints, which slows down proving",
+                self.lde_blowup_factor, min_blowup_factor
+            )
+        }))
+    }
+
     pub(crate) fn fri_folding_factor(&self) -> usize {
         self.fri_folding_factor
     }
@@ -542,6 +1138,61 @@ impl<const N: usize> WinterCircomProofOptions<N> {
         self.num_queries
     }
 
+    pub(crate) fn intermediate_verification_mode(&self) -> IntermediateVerification {
+        self.intermediate_verification
+    }
+
+    pub(crate) fn scaffold_on_missing_enabled(&self) -> bool {
+        self.scaffold_on_missing
+    }
+
+    pub(crate) fn snark_scheme_used(&self) -> SnarkScheme {
+        self.snark_scheme
+    }
+
+    /// Returns the trace column read as each column of the AIR template.
+    pub(crate) fn template_columns(&self) -> Vec<usize> {
+        self.column_map
+            .map_or_else(|| (0..self.trace_width).collect(), <[usize]>::to_vec)
+    }
+
+    pub(crate) fn column_map(&self) -> Option<Vec<usize>> {
+        self.column_map.map(<[usize]>::to_vec)
+    }
+
+    pub(crate) fn public_inputs_exposed(&self) -> bool {
+        self.expose_public_inputs
+    }
+
+    pub(crate) fn public_signals_committed(&self) -> bool {
+        self.commit_public_signals
+    }
+
+    /// Returns the number of values hashed into the public commitment, or 0 if
+    /// the public signals are not committed to.
+    pub(crate) fn num_committed_signals(&self, num_public_inputs: usize) -> usize {
+        match (self.commit_public_signals, self.expose_public_inputs) {
+            (false, _) => 0,
+            (true, exposed) => {
+                let num_exposed = if exposed { num_public_inputs } else { 0 };
+                N + 2 * self.trace_width + num_exposed
+            }
+        }
+    }
+
+    /// Returns the maximum size of the bound external data, if any.
+    pub(crate) fn data_commitment_max_bytes(&self) -> Option<usize> {
+        self.data_commitment
+            .map(|data_commitment| data_commitment.max_bytes)
+    }
+
+    /// Returns the index of the public input the external data is bound to, or
+    /// 0 if no data is bound.
+    pub(crate) fn data_commitment_index(&self) -> usize {
+        self.data_commitment
+            .map_or(0, |data_commitment| data_commitment.public_input_index)
+    }
+
     pub fn transition_constraint_degrees(&self) -> Vec<TransitionConstraintDegree> {
         self.transition_constraint_degrees
             .iter()
@@ -0,0 +1,445 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+};
+
+use crate::utils::{Colorize, LoggingLevel, WinterCircomError};
+
+/// Severity of an [AirLint].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    /// The pattern is suspicious, but may be intended.
+    Warning,
+
+    /// The template cannot soundly verify the declared AIR.
+    Error,
+}
+
+/// Mistake detected in an AIR template by [lint_air_template].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    /// A signal is assigned with `<--` but never constrained with `===`, so a
+    /// malicious prover can choose its value.
+    UnconstrainedAssignment,
+
+    /// An input of an assertion component is never assigned, leaving the
+    /// evaluation of the assertion unconstrained.
+    UnassignedInput,
+
+    /// A transition degree of `AIRTransitions` differs from the declared
+    /// degrees, or is missing.
+    DegreeMismatch,
+
+    /// The assertions of `AIRAssertions` differ from the declared number of
+    /// assertions.
+    AssertionMismatch,
+
+    /// Two assertions of `AIRAssertions` assert the same value at the same
+    /// steps of the same column, which Winterfell rejects (see
+    /// [canonical_assertions](crate::canonical_assertions)).
+    DuplicateAssertion,
+}
+
+impl LintKind {
+    /// Returns the severity of the lints of this kind.
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            LintKind::UnconstrainedAssignment => LintSeverity::Warning,
+            LintKind::UnassignedInput
+            | LintKind::DegreeMismatch
+            | LintKind::AssertionMismatch
+            | LintKind::DuplicateAssertion => LintSeverity::Error,
+        }
+    }
+}
+
+/// Result of the lint pass over an AIR template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AirLint {
+    pub kind: LintKind,
+
+    /// Line of the template the lint refers to, starting from 1. 0 if the lint
+    /// refers to the template as a whole, e.g. a missing assignment.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for AirLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// Lint the `AIRTransitions` and `AIRAssertions` templates of an AIR template
+/// file against the declared transition constraint degrees and number of
+/// assertions.
+///
+/// The lint pass is a textual analysis of the statements of the template, run
+/// by [circom_create](crate::circom_create) before compilation. It detects:
+///
+/// - signals assigned with `<--` without a matching `===` constraint;
+/// - inputs of the `SingleAssertion`, `PeriodicAssertion` and
+/// `SequenceAssertion` components that are never assigned;
+/// - `transition_degree` values that differ from the declared degrees;
+/// - a number of assertions (or of `assert(num_assertions == _)`) that differs
+/// from the declared one.
+/// - `SingleAssertion` and `PeriodicAssertion` components whose inputs are
+/// assigned the same expressions as a previous one.
+///
+/// Values and indices computed in loops or from variables are not checked.
+pub fn lint_air_template(
+    template: &str,
+    transition_degrees: &[usize],
+    num_assertions: usize,
+) -> Result<Vec<AirLint>, WinterCircomError> {
+    let source = fs::read_to_string(template).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", template)),
+    })?;
+    Ok(lint_air_source(&source, transition_degrees, num_assertions))
+}
+
+/// Lint the source of an AIR template, see [lint_air_template].
+pub fn lint_air_source(
+    source: &str,
+    transition_degrees: &[usize],
+    num_assertions: usize,
+) -> Vec<AirLint> {
+    let statements = statements(source);
+    let mut lints = Vec::new();
+
+    // UNCONSTRAINED ASSIGNMENTS
+
+    let constraints = statements
+        .iter()
+        .filter(|(_, statement)| statement.contains("==="))
+        .map(|(_, statement)| statement.as_str())
+        .collect::<Vec<_>>();
+    for (line, statement) in statements.iter() {
+        if let Some((signal, _)) = statement.split_once("<--") {
+            let signal = signal.trim().trim_start_matches("signal ").trim();
+            if !constraints.iter().any(|c| contains_signal(c, signal)) {
+                lints.push(AirLint {
+                    kind: LintKind::UnconstrainedAssignment,
+                    line: *line,
+                    message: format!(
+                        "{} is assigned with <-- but never constrained with ===",
+                        signal
+                    ),
+                });
+            }
+        }
+    }
+
+    // TRANSITION DEGREES
+
+    let mut degrees = BTreeMap::new();
+    let mut dynamic_degrees = false;
+    for (line, statement) in statements.iter() {
+        if let Some((index, value)) = indexed_assignment(statement, "transition_degree[") {
+            match (parse_literal(index), parse_literal(value)) {
+                (Some(index), Some(value)) => {
+                    degrees.insert(index, (*line, value));
+                }
+                _ => dynamic_degrees = true,
+            }
+        }
+    }
+    if !dynamic_degrees {
+        for (index, declared) in transition_degrees.iter().enumerate() {
+            match degrees.get(&index) {
+                Some((_, degree)) if degree == declared => {}
+                Some((line, degree)) => lints.push(AirLint {
+                    kind: LintKind::DegreeMismatch,
+                    line: *line,
+                    message: format!(
+                        "transition_degree[{}] is {}, but degree {} is declared",
+                        index, degree, declared
+                    ),
+                }),
+                None => lints.push(AirLint {
+                    kind: LintKind::DegreeMismatch,
+                    line: 0,
+                    message: format!(
+                        "transition_degree[{}] is not assigned, degree {} is declared",
+                        index, declared
+                    ),
+                }),
+            }
+        }
+        for (index, (line, _)) in degrees.range(transition_degrees.len()..) {
+            lints.push(AirLint {
+                kind: LintKind::DegreeMismatch,
+                line: *line,
+                message: format!(
+                    "transition_degree[{}] is assigned, but only {} transition constraints \
+                    are declared",
+                    index,
+                    transition_degrees.len()
+                ),
+            });
+        }
+    }
+
+    // ASSERTIONS
+
+    for (line, statement) in statements.iter() {
+        let compact = statement.replace(' ', "");
+        if let Some(count) = compact
+            .strip_prefix("assert(num_assertions==")
+            .and_then(|count| count.strip_suffix(')'))
+            .and_then(parse_literal)
+        {
+            if count != num_assertions {
+                lints.push(AirLint {
+                    kind: LintKind::AssertionMismatch,
+                    line: *line,
+                    message: format!(
+                        "the template asserts {} assertions, but {} are declared",
+                        count, num_assertions
+                    ),
+                });
+            }
+        }
+    }
+
+    let mut components = BTreeMap::new();
+    let mut assigned_inputs = BTreeMap::new();
+    let mut dynamic_assertions = false;
+    for (line, statement) in statements.iter() {
+        let (index, rest) = match statement
+            .strip_prefix("assertions[")
+            .and_then(|rest| rest.split_once(']'))
+        {
+            Some(assignment) => assignment,
+            None => continue,
+        };
+        let index = match parse_literal(index) {
+            Some(index) => index,
+            None => {
+                dynamic_assertions = true;
+                continue;
+            }
+        };
+        let rest = rest.trim_start();
+        if let Some(template) = rest.strip_prefix('=').filter(|t| !t.starts_with('=')) {
+            let template = template.split('(').next().unwrap_or_default().trim();
+            components.insert(index, (*line, String::from(template)));
+        } else if let Some(input) = rest.strip_prefix('.') {
+            let input = input
+                .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .next()
+                .unwrap_or_default();
+            let value = rest
+                .split_once("<==")
+                .map_or_else(String::new, |(_, value)| value.replace(' ', ""));
+            assigned_inputs.insert((index, String::from(input)), value);
+        }
+    }
+    if !dynamic_assertions {
+        for index in (0..num_assertions).filter(|index| !components.contains_key(index)) {
+            lints.push(AirLint {
+                kind: LintKind::AssertionMismatch,
+                line: 0,
+                message: format!("assertions[{}] is not instantiated", index),
+            });
+        }
+        for (index, (line, template)) in components.range(num_assertions..) {
+            lints.push(AirLint {
+                kind: LintKind::AssertionMismatch,
+                line: *line,
+                message: format!(
+                    "assertions[{}] = {}() is instantiated, but only {} assertions are declared",
+                    index, template, num_assertions
+                ),
+            });
+        }
+        for (index, (line, template)) in components.iter() {
+            for input in assertion_inputs(template) {
+                if !assigned_inputs.contains_key(&(*index, String::from(*input))) {
+                    lints.push(AirLint {
+                        kind: LintKind::UnassignedInput,
+                        line: *line,
+                        message: format!(
+                            "input {} of assertions[{}] is never assigned, the evaluation of \
+                            the assertion is unconstrained",
+                            input, index
+                        ),
+                    });
+                }
+            }
+        }
+
+        // values of sequence assertions are assigned element by element, and
+        // are not compared
+        let mut asserted = BTreeMap::new();
+        for (index, (line, template)) in components.iter() {
+            if template != "SingleAssertion" && template != "PeriodicAssertion" {
+                continue;
+            }
+            let inputs = assertion_inputs(template)
+                .iter()
+                .map(|input| {
+                    assigned_inputs
+                        .get(&(*index, String::from(*input)))
+                        .cloned()
+                })
+                .collect::<Option<Vec<_>>>();
+            let key = match inputs {
+                Some(inputs) => (template, inputs),
+                None => continue,
+            };
+            match asserted.get(&key) {
+                Some(first) => lints.push(AirLint {
+                    kind: LintKind::DuplicateAssertion,
+                    line: *line,
+                    message: format!(
+                        "assertions[{}] duplicates assertions[{}], declare one assertion less",
+                        index, first
+                    ),
+                }),
+                None => {
+                    asserted.insert(key, *index);
+                }
+            }
+        }
+    }
+
+    lints
+}
+
+/// Lint an AIR template before compilation, printing the warnings and
+/// returning an [AirTemplateLint](WinterCircomError::AirTemplateLint) error if
+/// any lint is an error.
+pub(crate) fn check_air_template(
+    template: &str,
+    transition_degrees: &[usize],
+    num_assertions: usize,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let (errors, warnings): (Vec<_>, Vec<_>) =
+        lint_air_template(template, transition_degrees, num_assertions)?
+            .into_iter()
+            .partition(|lint| lint.kind.severity() == LintSeverity::Error);
+
+    if logging_level.print_big_steps() {
+        for warning in warnings {
+            println!(
+                "{}",
+                format!("Warning: {}, {}.", template, warning).yellow()
+            );
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(WinterCircomError::AirTemplateLint {
+            template: String::from(template),
+            errors: errors.iter().map(|lint| lint.to_string()).collect(),
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the statements of a Circom source with their line, without
+/// comments. Statements are delimited by `;`, `{` and `}`.
+fn statements(source: &str) -> Vec<(usize, String)> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut start_line = 1;
+    let mut line = 1;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+                current.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                current.push(' ');
+            }
+            ';' | '{' | '}' => {
+                let statement = current.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !statement.is_empty() {
+                    statements.push((start_line, statement));
+                }
+                current.clear();
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                if current.trim().is_empty() && !c.is_whitespace() {
+                    start_line = line;
+                }
+                current.push(c);
+            }
+        }
+    }
+    statements
+}
+
+/// Returns the index and the value of an assignment `<prefix><index>] <== <value>`.
+fn indexed_assignment<'a>(statement: &'a str, prefix: &str) -> Option<(&'a str, &'a str)> {
+    let (index, rest) = statement.strip_prefix(prefix)?.split_once(']')?;
+    let value = rest.trim_start().strip_prefix("<==")?;
+    Some((index.trim(), value.trim()))
+}
+
+fn parse_literal(value: &str) -> Option<usize> {
+    value.trim().parse().ok()
+}
+
+/// Returns whether a statement refers to the given signal, e.g. `x[0]` but not
+/// `x_next[0]` or `prefix.x[0]`.
+fn contains_signal(statement: &str, signal: &str) -> bool {
+    let is_identifier = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+    statement.match_indices(signal).any(|(start, _)| {
+        let before = statement[..start].chars().next_back();
+        let after = statement[start + signal.len()..].chars().next();
+        !before.map_or(false, is_identifier) && !after.map_or(false, is_identifier)
+    })
+}
+
+/// Returns the inputs of an assertion template of `circuits/utils/assertions.circom`
+/// that are not provided by `AIRAssertions` itself.
+fn assertion_inputs(template: &str) -> &'static [&'static str] {
+    match template {
+        "SingleAssertion" => &["column", "step", "value"],
+        "PeriodicAssertion" => &["column", "first_step", "stride", "value"],
+        "SequenceAssertion" => &[
+            "addicity_root",
+            "column",
+            "first_step",
+            "g_trace",
+            "stride",
+            "values",
+            "z",
+        ],
+        _ => &[],
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use winterfell::{
+    math::{fields::f256::BaseElement, log2, FieldElement},
+    FieldExtension, HashFunction, ProofOptions,
+};
+
+use crate::{
+    audit::hash_artifact,
+    commitment::commitment_opening,
+    tenant::validate_circuit_name,
+    utils::WinterCircomError,
+    workspace::{circuit_dir, CircomWorkspace},
+    SnarkScheme,
+};
+
+/// Metadata of a generated circuit, written by
+/// [circom_create](crate::circom_create) to
+/// `target/circom/<circuit_name>/manifest.json`.
+///
+/// The manifest records the compile-time parameters of the circuit which are
+/// not otherwise recoverable from the compiled artifacts. At proving time,
+/// [circom_prove](crate::circom_prove) checks that the Winterfell proof
+/// matches them, so that a mismatch is reported before any witness is computed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CircuitManifest {
+    pub circuit_name: String,
+    pub trace_length: usize,
+    pub trace_width: usize,
+
+    /// Depths of the Merkle trees committing to the FRI layers (see
+    /// [fri_tree_depths](crate::fri_tree_depths)).
+    pub fri_tree_depths: Vec<usize>,
+
+    /// Number of columns of the constraint composition polynomial (see
+    /// [num_composition_columns](crate::WinterCircomProofOptions::num_composition_columns)).
+    /// Missing from the manifests of circuits generated by earlier versions of
+    /// this crate.
+    #[serde(default)]
+    pub num_composition_columns: Option<usize>,
+
+    /// Maximum size of the external data bound to a public input, if any (see
+    /// [commitment](crate::commitment)).
+    pub data_commitment_max_bytes: Option<usize>,
+
+    /// Fingerprint of the sources the circuit was compiled from (see
+    /// [source_fingerprint](CircuitManifest::source_fingerprint)). Missing from
+    /// the manifests of circuits generated by earlier versions of this crate.
+    #[serde(default)]
+    pub source_fingerprint: Option<String>,
+
+    /// When [circom_prove](crate::circom_prove) verifies the Winterfell proof.
+    #[serde(default)]
+    pub intermediate_verification: IntermediateVerification,
+
+    /// Whether the last Winterfell proof passed to
+    /// [circom_prove](crate::circom_prove) on this host was verified, or `None`
+    /// if no proof was passed yet.
+    #[serde(default)]
+    pub inner_proof_verified: Option<bool>,
+
+    /// Target soundness of the proofs, in bits (see
+    /// [target_security](crate::WinterCircomProofOptions::target_security)).
+    /// Missing from the manifests of circuits generated by earlier versions of
+    /// this crate, which targeted 128 bits.
+    #[serde(default)]
+    pub target_security_bits: Option<u32>,
+
+    /// Whether the public inputs are Groth16 public signals (see
+    /// [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs)).
+    /// Missing from the manifests of circuits generated by earlier versions of
+    /// this crate.
+    #[serde(default)]
+    pub public_inputs_exposed: Option<bool>,
+
+    /// Whether the public signals are replaced by their hash (see
+    /// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)).
+    /// Missing from the manifests of circuits generated by earlier versions of
+    /// this crate.
+    #[serde(default)]
+    pub public_signals_committed: Option<bool>,
+
+    /// Parameters the circuit was compiled for. Missing from the manifests of
+    /// circuits generated by earlier versions of this crate.
+    #[serde(default)]
+    pub circuit_parameters: Option<CircuitParameters>,
+
+    /// Declared degrees of the transition constraints, checked against the
+    /// compiled circuit by [circom_prove](crate::circom_prove) (see
+    /// [transition_degrees](crate::transition_degrees)). Missing from the
+    /// manifests of circuits generated by earlier versions of this crate.
+    #[serde(default)]
+    pub transition_constraint_degrees: Option<Vec<usize>>,
+
+    /// Proving system of the circuit (see [SnarkScheme]). Missing from the
+    /// manifests of circuits generated by earlier versions of this crate, which
+    /// used Groth16.
+    #[serde(default)]
+    pub snark_scheme: SnarkScheme,
+
+    /// Trace column read as each column of the AIR template (see
+    /// [remap_columns](crate::WinterCircomProofOptions::remap_columns)), or
+    /// `None` if the template reads the columns in the order of the trace.
+    #[serde(default)]
+    pub column_map: Option<Vec<usize>>,
+
+    /// Hash of the `verifier.circom` file the circuit was last compiled from
+    /// (see [is_up_to_date](crate::Stage::is_up_to_date)), or `None` if it was
+    /// not compiled by this version of the crate.
+    #[serde(default)]
+    pub compiled_from: Option<String>,
+
+    /// Hash of the `verifier.r1cs` file and of the powers of tau transcript the
+    /// circuit-specific keys were last generated from (see
+    /// [is_up_to_date](crate::Stage::is_up_to_date)), or `None` if they were
+    /// not generated by this version of the crate.
+    #[serde(default)]
+    pub keys_generated_from: Option<String>,
+}
+
+/// Parameters of a circuit, fixed when it is compiled by
+/// [circom_create](crate::circom_create).
+///
+/// The Groth16 circuit only verifies Winterfell proofs of these exact
+/// parameters, so that all the proofs of a circuit must be generated with the
+/// same trace dimensions, AIR and Winterfell [ProofOptions]. Only the trace
+/// itself (and the external data, see [commitment](crate::commitment)) varies
+/// between proofs.
+///
+/// The parameters are recorded in the [CircuitManifest]. Provers should derive
+/// their options from them with [proof_options](CircuitParameters::proof_options),
+/// rather than from a separate copy of the
+/// [WinterCircomProofOptions](crate::WinterCircomProofOptions) that may have
+/// changed since the circuit was compiled:
+///
+/// ```ignore
+/// let parameters = CircuitParameters::of_circuit("sum")?;
+/// let prover = WorkProver::new(parameters.proof_options());
+/// let trace = prover.build_trace(start, parameters.trace_length);
+/// ```
+///
+/// [circom_prove](crate::circom_prove) checks the parameters of the prover and
+/// trace against the recorded ones before proving, and fails with a
+/// [ManifestMismatch](WinterCircomError::ManifestMismatch) error otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CircuitParameters {
+    pub trace_length: usize,
+    pub trace_width: usize,
+    pub num_transition_constraints: usize,
+    pub num_assertions: usize,
+    pub num_queries: usize,
+    pub lde_blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub fri_folding_factor: usize,
+    pub fri_max_remainder_size: usize,
+}
+
+impl CircuitParameters {
+    /// Returns the parameters recorded in the manifest of the given circuit.
+    ///
+    /// Returns a [FileNotFound](WinterCircomError::FileNotFound) error if the
+    /// circuit has no manifest, or if it was generated by an earlier version of
+    /// this crate which did not record its parameters.
+    pub fn of_circuit(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        CircuitManifest::read(circuit_name)?
+            .and_then(|manifest| manifest.circuit_parameters)
+            .ok_or_else(|| WinterCircomError::FileNotFound {
+                file: CircuitManifest::path(circuit_name),
+                comment: Some(String::from(
+                    "the circuit parameters are recorded by circom_create",
+                )),
+            })
+    }
+
+    /// Returns the Winterfell proof options of the proofs verified by the
+    /// circuit.
+    pub fn proof_options(&self) -> ProofOptions {
+        ProofOptions::new(
+            self.num_queries,
+            self.lde_blowup_factor,
+            self.grinding_factor,
+            HashFunction::Poseidon,
+            FieldExtension::None,
+            self.fri_folding_factor,
+            self.fri_max_remainder_size,
+        )
+    }
+
+    /// Check that the parameters of a proof match the ones of the circuit.
+    ///
+    /// Returns a [ManifestMismatch](WinterCircomError::ManifestMismatch) error
+    /// for the first parameter that differs.
+    pub fn check(&self, proved: &CircuitParameters) -> Result<(), WinterCircomError> {
+        let fields = [
+            ("trace_length", self.trace_length, proved.trace_length),
+            ("trace_width", self.trace_width, proved.trace_width),
+            (
+                "num_transition_constraints",
+                self.num_transition_constraints,
+                proved.num_transition_constraints,
+            ),
+            ("num_assertions", self.num_assertions, proved.num_assertions),
+            ("num_queries", self.num_queries, proved.num_queries),
+            (
+                "lde_blowup_factor",
+                self.lde_blowup_factor,
+                proved.lde_blowup_factor,
+            ),
+            (
+                "grinding_factor",
+                self.grinding_factor as usize,
+                proved.grinding_factor as usize,
+            ),
+            (
+                "fri_folding_factor",
+                self.fri_folding_factor,
+                proved.fri_folding_factor,
+            ),
+            (
+                "fri_max_remainder_size",
+                self.fri_max_remainder_size,
+                proved.fri_max_remainder_size,
+            ),
+        ];
+        match fields
+            .iter()
+            .find(|(_, compiled, proved)| compiled != proved)
+        {
+            Some((field, compiled, proved)) => Err(WinterCircomError::ManifestMismatch {
+                field: String::from(*field),
+                compiled: compiled.to_string(),
+                proved: proved.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Verification of the Winterfell proof by [circom_prove](crate::circom_prove),
+/// before it is converted into circuit inputs.
+///
+/// An invalid Winterfell proof always results in an unsatisfiable circuit, so
+/// this verification only reports the error earlier and more precisely than
+/// the witness generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum IntermediateVerification {
+    Always,
+    Never,
+
+    /// Only verify in debug builds.
+    DebugOnly,
+}
+
+impl Default for IntermediateVerification {
+    fn default() -> Self {
+        IntermediateVerification::DebugOnly
+    }
+}
+
+impl IntermediateVerification {
+    /// Returns whether the Winterfell proof is verified in this build.
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            IntermediateVerification::Always => true,
+            IntermediateVerification::Never => false,
+            IntermediateVerification::DebugOnly => cfg!(debug_assertions),
+        }
+    }
+}
+
+impl CircuitManifest {
+    /// Returns the path of the manifest of the given circuit.
+    pub fn path(circuit_name: &str) -> String {
+        circuit_dir(circuit_name)
+            .join("manifest.json")
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Read the manifest of the given circuit, if it exists.
+    pub fn read(circuit_name: &str) -> Result<Option<Self>, WinterCircomError> {
+        validate_circuit_name(circuit_name)?;
+        let path = CircuitManifest::path(circuit_name);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(WinterCircomError::IoError {
+                    io_error: e,
+                    comment: Some(String::from("reading manifest.json")),
+                })
+            }
+        };
+
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| WinterCircomError::CorruptArtifact {
+                file: path,
+                comment: e.to_string(),
+            })
+    }
+
+    /// Write the manifest to the output directory of its circuit.
+    pub fn write(&self) -> Result<(), WinterCircomError> {
+        let json = serde_json::to_string_pretty(self).expect("manifest should be serializable");
+        fs::write(CircuitManifest::path(&self.circuit_name), json).map_err(|e| {
+            WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("writing manifest.json")),
+            }
+        })
+    }
+
+    /// Returns the fingerprint of the current sources of the given circuit, or
+    /// `None` if any of them is missing.
+    ///
+    /// The fingerprint is the BLAKE3 hash of the AIR template
+    /// (`circuits/air/<circuit>.circom`), of the templates shared by all
+    /// circuits (the other `.circom` files of `circuits/`, such as the `Verify`
+    /// template) and of the generated main file, which hardcodes the proof
+    /// options. A circuit whose recorded fingerprint differs from the current
+    /// one was compiled from outdated sources.
+    ///
+    /// Only the contents and relative paths of the sources are hashed, not their
+    /// modification times, so that copying a project or checking it out from git
+    /// does not change the fingerprint.
+    pub fn source_fingerprint(circuit_name: &str) -> Option<String> {
+        let workspace = CircomWorkspace::current();
+        let circuits_dir = workspace.circuits_dir();
+        let mut sources = vec![workspace.air_template(circuit_name)];
+        sources.extend(shared_templates(circuits_dir, circuits_dir));
+        sources.push(workspace.circuit_dir(circuit_name).join("verifier.circom"));
+
+        // paths are hashed relative to the default workspace, so that the
+        // fingerprint does not depend on the location of the workspace
+        let relative_path = |path: &Path| {
+            if let Ok(path) = path.strip_prefix(circuits_dir) {
+                Path::new("circuits").join(path)
+            } else if let Ok(path) = path.strip_prefix(workspace.root_dir()) {
+                Path::new("target/circom").join(path)
+            } else {
+                path.to_path_buf()
+            }
+        };
+
+        let mut bytes = Vec::new();
+        for source in sources {
+            bytes.extend(relative_path(&source).to_string_lossy().as_bytes());
+            bytes.extend(fs::read(source).ok()?);
+        }
+        Some(hash_artifact(&bytes))
+    }
+
+    /// Returns whether the sources of the circuit changed since it was
+    /// generated, i.e. whether the recorded fingerprint differs from the one of
+    /// the current sources.
+    ///
+    /// Returns `false` for circuits generated by earlier versions of this
+    /// crate, which record no fingerprint.
+    pub fn sources_changed(&self) -> bool {
+        match &self.source_fingerprint {
+            Some(recorded) => {
+                CircuitManifest::source_fingerprint(&self.circuit_name).as_ref() != Some(recorded)
+            }
+            None => false,
+        }
+    }
+
+    /// Check that the FRI tree depths of a proof match the compiled circuit.
+    pub fn check_fri_tree_depths(
+        &self,
+        fri_tree_depths: &[usize],
+    ) -> Result<(), WinterCircomError> {
+        if self.fri_tree_depths != fri_tree_depths {
+            return Err(WinterCircomError::ManifestMismatch {
+                field: String::from("fri_tree_depths"),
+                compiled: format!("{:?}", self.fri_tree_depths),
+                proved: format!("{:?}", fri_tree_depths),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that the column map of the compiled circuit, if any, is a
+    /// permutation of the columns of a trace of the given width.
+    pub fn check_column_map(&self, trace_width: usize) -> Result<(), WinterCircomError> {
+        match &self.column_map {
+            Some(column_map) => check_column_map(column_map, trace_width),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that the number of composition columns of a proof matches the
+    /// compiled circuit, if recorded.
+    pub fn check_num_composition_columns(
+        &self,
+        num_composition_columns: usize,
+    ) -> Result<(), WinterCircomError> {
+        match self.num_composition_columns {
+            Some(compiled) if compiled != num_composition_columns => {
+                Err(WinterCircomError::ManifestMismatch {
+                    field: String::from("num_composition_columns"),
+                    compiled: compiled.to_string(),
+                    proved: num_composition_columns.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the value of the `commitment_data` input signal: the opening of
+    /// the commitment to the given external data, or a single zero element if
+    /// the circuit binds no data.
+    ///
+    /// Returns an error if data is given but the circuit binds no data, or the
+    /// other way around.
+    pub fn commitment_data(
+        &self,
+        data: Option<&[u8]>,
+    ) -> Result<Vec<BaseElement>, WinterCircomError> {
+        match (data, self.data_commitment_max_bytes) {
+            (None, None) => Ok(vec![BaseElement::ZERO]),
+            (Some(data), Some(max_bytes)) => commitment_opening(data, max_bytes),
+            (data, max_bytes) => Err(WinterCircomError::ManifestMismatch {
+                field: String::from("data_commitment_max_bytes"),
+                compiled: format!("{:?}", max_bytes),
+                proved: format!("{:?}", data.map(|data| data.len())),
+            }),
+        }
+    }
+}
+
+// FRI TREE DEPTHS
+// ===========================================================================
+
+/// Returns the depths of the Merkle trees committing to the FRI layers of a
+/// proof, from the first layer to the last one.
+///
+/// Each folding divides the evaluation domain by `folding_factor`, and the
+/// tree of a layer has one leaf per coset of the folded domain. Layers are
+/// folded until the domain is no larger than `max_remainder_size`.
+///
+/// These values are hardcoded in the generated Circom main file by
+/// [circom_create](crate::circom_create) and recorded in the circuit
+/// [CircuitManifest]. They are checked against the ones of the actual proof by
+/// [circom_prove](crate::circom_prove).
+pub fn fri_tree_depths(
+    lde_domain_size: usize,
+    folding_factor: usize,
+    max_remainder_size: usize,
+) -> Vec<usize> {
+    let mut fri_tree_depths = vec![];
+    let mut domain_size = lde_domain_size;
+    while domain_size > max_remainder_size {
+        domain_size /= folding_factor;
+        fri_tree_depths.push(log2(domain_size) as usize);
+    }
+    fri_tree_depths
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Check that a column map is a permutation of the columns of a trace of the
+/// given width.
+pub(crate) fn check_column_map(
+    column_map: &[usize],
+    trace_width: usize,
+) -> Result<(), WinterCircomError> {
+    if column_map.len() != trace_width {
+        return Err(WinterCircomError::InvalidColumnMap {
+            comment: format!(
+                "the map has {} columns, but the trace has {}",
+                column_map.len(),
+                trace_width
+            ),
+        });
+    }
+    let mut mapped = vec![false; trace_width];
+    for column in column_map {
+        match mapped.get_mut(*column) {
+            Some(mapped) if !*mapped => *mapped = true,
+            Some(_) => {
+                return Err(WinterCircomError::InvalidColumnMap {
+                    comment: format!("trace column {} is mapped twice", column),
+                })
+            }
+            None => {
+                return Err(WinterCircomError::InvalidColumnMap {
+                    comment: format!(
+                        "trace column {} does not exist, the trace has {} columns",
+                        column, trace_width
+                    ),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the `.circom` files of a directory of the circuits directory and its
+/// subdirectories, except for the AIR templates of `air/`, sorted by path.
+fn shared_templates(circuits_dir: &Path, dir: &Path) -> Vec<PathBuf> {
+    let mut templates = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if path.is_dir() && path != circuits_dir.join("air") {
+                templates.extend(shared_templates(circuits_dir, &path));
+            } else if path.extension().map_or(false, |ext| ext == "circom") {
+                templates.push(path);
+            }
+        }
+    }
+    templates.sort();
+    templates
+}
@@ -0,0 +1,167 @@
+use std::{
+    env, fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::utils::{format_size, ErrorClass, WinterCircomError};
+
+/// Size of a serialized field element, in bytes.
+const ELEMENT_BYTES: u64 = 32;
+
+/// Memory limit of the STARK proving and JSON export of
+/// [circom_prove](crate::circom_prove).
+///
+/// Large traces are proven in-process, and a prover that exceeds the memory of
+/// its container is OOM-killed without any indication of the cause. With a
+/// memory limit, `circom_prove`:
+///
+/// - fails with [MemoryLimitExceeded](WinterCircomError::MemoryLimitExceeded)
+/// before proving if the [estimated](MemoryLimit::estimate) memory exceeds the
+/// limit;
+/// - monitors the resident memory of the process while proving and exporting
+/// the proof, and exits the process with the
+/// [ResourceExhausted](ErrorClass::ResourceExhausted) exit code and a clear
+/// message if it exceeds the limit.
+///
+/// The limit should be set somewhat below the memory of the container, so that
+/// the process exits before being killed. Resident memory is only monitored on
+/// Linux.
+///
+/// ## Configuration
+///
+/// There is no limit by default. It is set with the
+/// `WINTER_CIRCOM_MEMORY_LIMIT_MB` environment variable, in MiB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryLimit {
+    limit_bytes: u64,
+}
+
+impl MemoryLimit {
+    pub const LIMIT_VARIABLE: &'static str = "WINTER_CIRCOM_MEMORY_LIMIT_MB";
+
+    pub fn new(limit_bytes: u64) -> Self {
+        Self { limit_bytes }
+    }
+
+    /// Returns the memory limit configured by the environment variable, if any.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_MEMORY_LIMIT_MB` is not an integer.
+    pub fn from_env() -> Result<Option<Self>, WinterCircomError> {
+        match env::var(MemoryLimit::LIMIT_VARIABLE) {
+            Ok(value) => value
+                .parse::<u64>()
+                .map(|megabytes| Some(Self::new(megabytes << 20)))
+                .map_err(|_| WinterCircomError::InvalidEnvVariable {
+                    variable: String::from(MemoryLimit::LIMIT_VARIABLE),
+                    comment: format!("expected a number of MiB, got {}", value),
+                }),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns the memory limit, in bytes.
+    pub fn limit_bytes(&self) -> u64 {
+        self.limit_bytes
+    }
+
+    /// Returns the estimated peak memory of proving a trace of the given
+    /// dimensions, in bytes.
+    ///
+    /// The estimate accounts for the low-degree extension of the trace and of
+    /// the constraint composition polynomial, and for the Merkle trees
+    /// committing to them, which dominate the memory of the prover.
+    pub fn estimate(
+        trace_length: usize,
+        trace_width: usize,
+        lde_blowup_factor: usize,
+        num_composition_columns: usize,
+    ) -> u64 {
+        let lde_domain_size = (trace_length * lde_blowup_factor) as u64;
+        // trace polynomials and their extension, composition columns and the
+        // nodes of the two Merkle trees
+        let trace_elements = trace_width as u64 * (trace_length as u64 + lde_domain_size);
+        let composition_elements = num_composition_columns as u64 * lde_domain_size;
+        let merkle_nodes = 2 * 2 * lde_domain_size;
+        (trace_elements + composition_elements + merkle_nodes) * ELEMENT_BYTES
+    }
+
+    /// Check that the estimated memory of a proof is within the limit.
+    pub fn check(&self, estimated_bytes: u64) -> Result<(), WinterCircomError> {
+        if estimated_bytes > self.limit_bytes {
+            return Err(WinterCircomError::MemoryLimitExceeded {
+                required: estimated_bytes,
+                limit: self.limit_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Monitor the resident memory of the process until the returned guard is
+    /// dropped, exiting the process if it exceeds the limit.
+    pub(crate) fn monitor(&self) -> MemoryMonitor {
+        let stop = Arc::new(AtomicBool::new(false));
+        let limit_bytes = self.limit_bytes;
+        let handle = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    if let Some(rss) = resident_memory().filter(|rss| *rss > limit_bytes) {
+                        eprintln!(
+                            "Memory limit exceeded: {} resident but the limit is {}, increase the memory limit or reduce trace_length.",
+                            format_size(rss),
+                            format_size(limit_bytes)
+                        );
+                        std::process::exit(ErrorClass::ResourceExhausted.exit_code());
+                    }
+                    thread::sleep(Duration::from_millis(100));
+                }
+            })
+        };
+
+        MemoryMonitor {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Guard of the memory monitoring thread started by [MemoryLimit::monitor].
+pub(crate) struct MemoryMonitor {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for MemoryMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the resident memory of this process, in bytes.
+///
+/// This reads the `VmRSS` field of `/proc/self/status`, and therefore only
+/// works on Linux.
+fn resident_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kilobytes << 10)
+}
@@ -0,0 +1,560 @@
+//! Native Rust Groth16 backend, built on `ark-circom` and `ark-groth16`.
+//!
+//! The functions of this module replace the snarkjs commands of the setup,
+//! witness, prove and verify [Stage](crate::Stage)s: the circuit compiled by
+//! [circom_compile](crate::circom_compile) is loaded from `verifier.r1cs` and
+//! `verifier_js/verifier.wasm`, and the keys, the witness and the proofs are
+//! computed in the current process, without Node.js. The Circom compiler is
+//! still needed to compile the circuit.
+//!
+//! The proving key is serialized to `verifier.ark`, which snarkjs cannot read.
+//! The verification key, the proof and its public signals are written in the
+//! snarkjs JSON format, as `verification_key.json`, `proof.json` and
+//! `public.json`: proofs are generated with the reduction of snarkjs
+//! ([CircomReduction]), so that [circom_verify](crate::circom_verify), the
+//! [verifier package](crate::export_verifier_package) and
+//! [circom_verify_in_memory](crate::circom_verify_in_memory) accept them as
+//! well.
+//!
+//! ```ignore
+//! circom_create::<SumProver, 1>(OPTIONS, "sum", LoggingLevel::Default)?;
+//! circom_compile("sum", LoggingLevel::Default)?;
+//! native_setup("sum", LoggingLevel::Default)?;
+//! circom_prove(prover, trace, "sum", LoggingLevel::Default)?;
+//! native_prove("sum", LoggingLevel::Default)?;
+//! native_verify("sum", LoggingLevel::Default)?;
+//! ```
+//!
+//! The [NativeBackend] runs these steps as the setup, prove and verify stages
+//! of a [Pipeline](crate::Pipeline), see [SnarkBackend].
+//!
+//! ## Limitations
+//!
+//! Only [Groth16](SnarkScheme::Groth16) circuits over BN254 are supported. The
+//! keys are generated from local randomness, without the powers of tau
+//! transcript: they are as trusted as the host which generated them, and
+//! cannot be rotated by [rotate_keys](crate::rotate_keys). This module is only
+//! available with the `native` feature.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_circom::{CircomBuilder, CircomConfig, CircomReduction};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::rand::thread_rng;
+use num_bigint::BigInt;
+use serde_json::{json, Value};
+
+use crate::{
+    audit::{audited, AuditAction},
+    scratch::scratch_dir,
+    utils::{check_file, LoggingLevel, StageTimer, WinterCircomError},
+    workspace::circuit_dir,
+    HostRole, SnarkBackend, SnarkScheme,
+};
+
+/// Generate the keys of a circuit compiled by
+/// [circom_compile](crate::circom_compile), without snarkjs.
+///
+/// The proving key is written to `verifier.ark`, and the verification key to
+/// `verification_key.json`, in the snarkjs format. On a
+/// [Prover](HostRole::Prover) host, the verification key is not exported.
+pub fn native_setup(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    check_scheme(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    let artifacts = [
+        dir.join("verifier.r1cs"),
+        dir.join("verifier.ark"),
+        dir.join("verification_key.json"),
+    ];
+    audited(
+        circuit_name,
+        AuditAction::Setup,
+        json!({ "backend": "native" }),
+        &artifacts,
+        || {
+            let verifying_key = generate_keys(circuit_name, &logging_level)?;
+            if HostRole::from_env()?.needs("verification_key.json") {
+                write_json(
+                    &artifacts[2],
+                    &verification_key_to_json(&verifying_key),
+                    "writing verification_key.json",
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Compute the witness and the Groth16 proof of a circuit from the
+/// `input.json` file written by [circom_prove](crate::circom_prove) and the
+/// proving key generated by [native_setup], without snarkjs.
+///
+/// The proof and its public signals are written to `proof.json` and
+/// `public.json`, in the directory of the circuit and in the snarkjs format.
+pub fn native_prove(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    check_scheme(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    let artifacts = [
+        dir.join("verifier.ark"),
+        dir.join("proof.json"),
+        dir.join("public.json"),
+    ];
+    audited(
+        circuit_name,
+        AuditAction::Groth16Prove,
+        json!({ "backend": "native" }),
+        &artifacts,
+        || prove(circuit_name, &logging_level),
+    )
+}
+
+/// Verify the Groth16 proof of a circuit generated by [native_prove] (or by
+/// snarkjs), without snarkjs.
+///
+/// This is the native counterpart of [circom_verify](crate::circom_verify):
+/// the proof is checked against `verification_key.json` and `public.json`.
+pub fn native_verify(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    check_scheme(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    let artifacts = [
+        dir.join("verification_key.json"),
+        dir.join("public.json"),
+        dir.join("proof.json"),
+    ];
+    audited(
+        circuit_name,
+        AuditAction::Verify,
+        json!({ "backend": "native" }),
+        &artifacts,
+        || verify(circuit_name, &logging_level),
+    )
+}
+
+// SNARK BACKEND
+// ===========================================================================
+
+/// [SnarkBackend] running the steps of this module, so that the stages of a
+/// [Pipeline](crate::Pipeline) use the native prover:
+///
+/// ```ignore
+/// with_snark_backend(Rc::new(NativeBackend), || {
+///     Pipeline::full("sum").run(LoggingLevel::Default)
+/// })?;
+/// ```
+///
+/// The proof is computed from `input.json`, as by [native_prove]: the
+/// [Witness](crate::Stage::Witness) stage can be left out of the pipeline.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeBackend;
+
+impl SnarkBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn setup(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        check_scheme(circuit_name)?;
+        generate_keys(circuit_name, &logging_level).map(|_| ())
+    }
+
+    fn export_vk(
+        &self,
+        circuit_name: &str,
+        _logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        let dir = circuit_dir(circuit_name);
+        let proving_key = read_proving_key(&dir.join("verifier.ark"))?;
+        write_json(
+            &dir.join("verification_key.json"),
+            &verification_key_to_json(&proving_key.vk),
+            "writing verification_key.json",
+        )
+    }
+
+    fn prove(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        check_scheme(circuit_name)?;
+        prove(circuit_name, &logging_level)
+    }
+
+    fn verify(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        check_scheme(circuit_name)?;
+        verify(circuit_name, &logging_level)
+    }
+}
+
+/// Generate the keys of a circuit, writing the proving key to `verifier.ark`.
+fn generate_keys(
+    circuit_name: &str,
+    logging_level: &LoggingLevel,
+) -> Result<VerifyingKey<Bn254>, WinterCircomError> {
+    let path = circuit_dir(circuit_name).join("verifier.ark");
+    let builder = circom_builder(circuit_name)?;
+    let timer = StageTimer::start("Generating circuit-specific keys...", logging_level);
+    let (proving_key, verifying_key) = Groth16::<Bn254, CircomReduction>::circuit_specific_setup(
+        builder.setup(),
+        &mut thread_rng(),
+    )
+    .map_err(|e| native_error("generating the keys", e))?;
+
+    // the key is only read back by this host, uncompressed keys are much
+    // faster to deserialize. It is streamed to the file, as large keys do not
+    // fit in memory twice
+    let io_error = |e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("writing verifier.ark")),
+    };
+    let mut file = BufWriter::new(File::create(&path).map_err(io_error)?);
+    proving_key
+        .serialize_uncompressed(&mut file)
+        .map_err(|e| native_error("serializing the proving key", e))?;
+    file.flush().map_err(io_error)?;
+    timer.finish(&[path]);
+    Ok(verifying_key)
+}
+
+/// Compute the witness and the proof of a circuit, see [native_prove].
+fn prove(circuit_name: &str, logging_level: &LoggingLevel) -> Result<(), WinterCircomError> {
+    let dir = circuit_dir(circuit_name);
+    let input_path = scratch_dir(circuit_name).join("input.json");
+    let artifacts = [
+        dir.join("verifier.ark"),
+        dir.join("proof.json"),
+        dir.join("public.json"),
+    ];
+    check_file(
+        input_path.to_string_lossy().into_owned(),
+        Some("did you run circom_prove?"),
+    )?;
+    check_file(
+        artifacts[0].to_string_lossy().into_owned(),
+        Some("did you run native_setup?"),
+    )?;
+    let input = read_json(&input_path, "reading input.json")?;
+    let mut builder = circom_builder(circuit_name)?;
+
+    let timer = StageTimer::start("Computing execution witness...", logging_level);
+    let signals = input
+        .as_object()
+        .ok_or_else(|| invalid_artifact("input.json"))?;
+    for (name, value) in signals {
+        let mut values = vec![];
+        flatten_input(value, &mut values).ok_or_else(|| invalid_artifact("input.json"))?;
+        for value in values {
+            builder.push_input(name, value);
+        }
+    }
+    let circuit = builder
+        .build()
+        .map_err(|e| native_error("computing the witness", e))?;
+    let public_signals = circuit
+        .get_public_inputs()
+        .expect("the witness of the circuit should be computed");
+    timer.finish::<&str>(&[]);
+
+    let timer = StageTimer::start("Generating Groth16 proof...", logging_level);
+    let proving_key = read_proving_key(&artifacts[0])?;
+    let proof = Groth16::<Bn254, CircomReduction>::prove(&proving_key, circuit, &mut thread_rng())
+        .map_err(|e| native_error("generating the proof", e))?;
+
+    write_json(&artifacts[1], &proof_to_json(&proof), "writing proof.json")?;
+    write_json(
+        &artifacts[2],
+        &Value::from(public_signals.iter().map(decimal).collect::<Vec<_>>()),
+        "writing public.json",
+    )?;
+    timer.finish(&artifacts[1..]);
+    Ok(())
+}
+
+/// Verify the proof of a circuit, see [native_verify].
+fn verify(circuit_name: &str, logging_level: &LoggingLevel) -> Result<(), WinterCircomError> {
+    let dir = circuit_dir(circuit_name);
+    let artifacts = [
+        dir.join("verification_key.json"),
+        dir.join("public.json"),
+        dir.join("proof.json"),
+    ];
+    for path in artifacts.iter() {
+        check_file(
+            path.to_string_lossy().into_owned(),
+            Some("needed for verification"),
+        )?;
+    }
+    let verifying_key =
+        verification_key_from_json(&read_json(&artifacts[0], "reading verification_key.json")?)?;
+    let public_signals =
+        public_signals_from_json(&read_json(&artifacts[1], "reading public.json")?)?;
+    let proof = proof_from_json(&read_json(&artifacts[2], "reading proof.json")?)?;
+
+    let timer = StageTimer::start("Verifying Groth16 proof...", logging_level);
+    let valid = Groth16::<Bn254, CircomReduction>::verify(&verifying_key, &public_signals, &proof)
+        .map_err(|e| native_error("verifying the proof", e))?;
+    timer.finish::<&str>(&[]);
+
+    if !valid {
+        return Err(WinterCircomError::NativeProverError {
+            comment: String::from("the proof is invalid"),
+        });
+    }
+    Ok(())
+}
+
+// SNARKJS ARTIFACTS
+// ===========================================================================
+
+/// Returns a verification key in the snarkjs JSON format.
+///
+/// The `vk_alphabeta_12` pairing exported by snarkjs is left out, as snarkjs
+/// does not read it to verify proofs.
+pub(crate) fn verification_key_to_json(verifying_key: &VerifyingKey<Bn254>) -> Value {
+    json!({
+        "protocol": "groth16",
+        "curve": "bn128",
+        "nPublic": verifying_key.gamma_abc_g1.len() - 1,
+        "vk_alpha_1": g1_to_json(&verifying_key.alpha_g1),
+        "vk_beta_2": g2_to_json(&verifying_key.beta_g2),
+        "vk_gamma_2": g2_to_json(&verifying_key.gamma_g2),
+        "vk_delta_2": g2_to_json(&verifying_key.delta_g2),
+        "IC": verifying_key.gamma_abc_g1.iter().map(g1_to_json).collect::<Vec<_>>(),
+    })
+}
+
+/// Parse a verification key in the snarkjs JSON format.
+pub(crate) fn verification_key_from_json(
+    json: &Value,
+) -> Result<VerifyingKey<Bn254>, WinterCircomError> {
+    let parse = || {
+        Some(VerifyingKey {
+            alpha_g1: g1_from_json(&json["vk_alpha_1"])?,
+            beta_g2: g2_from_json(&json["vk_beta_2"])?,
+            gamma_g2: g2_from_json(&json["vk_gamma_2"])?,
+            delta_g2: g2_from_json(&json["vk_delta_2"])?,
+            gamma_abc_g1: json["IC"]
+                .as_array()?
+                .iter()
+                .map(g1_from_json)
+                .collect::<Option<Vec<_>>>()?,
+        })
+    };
+    match parse() {
+        Some(verifying_key) if json["protocol"] == "groth16" => Ok(verifying_key),
+        _ => Err(invalid_artifact("verification_key.json")),
+    }
+}
+
+/// Returns a Groth16 proof in the snarkjs JSON format.
+pub(crate) fn proof_to_json(proof: &Proof<Bn254>) -> Value {
+    json!({
+        "pi_a": g1_to_json(&proof.a),
+        "pi_b": g2_to_json(&proof.b),
+        "pi_c": g1_to_json(&proof.c),
+        "protocol": "groth16",
+        "curve": "bn128",
+    })
+}
+
+/// Parse a Groth16 proof in the snarkjs JSON format.
+pub(crate) fn proof_from_json(json: &Value) -> Result<Proof<Bn254>, WinterCircomError> {
+    let parse = || {
+        Some(Proof {
+            a: g1_from_json(&json["pi_a"])?,
+            b: g2_from_json(&json["pi_b"])?,
+            c: g1_from_json(&json["pi_c"])?,
+        })
+    };
+    parse().ok_or_else(|| invalid_artifact("proof.json"))
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn check_scheme(circuit_name: &str) -> Result<(), WinterCircomError> {
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    if scheme != SnarkScheme::Groth16 {
+        return Err(WinterCircomError::UnsupportedScheme {
+            scheme: String::from(scheme.name()),
+            comment: String::from("the native backend only supports Groth16 circuits"),
+        });
+    }
+    Ok(())
+}
+
+/// Load the circuit compiled by [circom_compile](crate::circom_compile).
+fn circom_builder(circuit_name: &str) -> Result<CircomBuilder<Fr>, WinterCircomError> {
+    let dir = circuit_dir(circuit_name);
+    let wasm = dir.join("verifier_js").join("verifier.wasm");
+    let r1cs = dir.join("verifier.r1cs");
+    for path in [&wasm, &r1cs] {
+        check_file(
+            path.to_string_lossy().into_owned(),
+            Some("did you run circom_compile?"),
+        )?;
+    }
+    let config = CircomConfig::<Fr>::new(&wasm, &r1cs)
+        .map_err(|e| native_error("loading the compiled circuit", e))?;
+    Ok(CircomBuilder::new(config))
+}
+
+/// Flatten the value of an input signal into the list of its elements, in the
+/// order of the witness calculator.
+fn flatten_input(value: &Value, values: &mut Vec<BigInt>) -> Option<()> {
+    match value {
+        Value::Array(elements) => elements
+            .iter()
+            .try_for_each(|element| flatten_input(element, values)),
+        Value::String(s) => {
+            values.push(BigInt::from_str(s).ok()?);
+            Some(())
+        }
+        Value::Number(n) => {
+            values.push(BigInt::from(n.as_u64()?));
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+fn public_signals_from_json(json: &Value) -> Result<Vec<Fr>, WinterCircomError> {
+    json.as_array()
+        .and_then(|signals| {
+            signals
+                .iter()
+                .map(|signal| Fr::from_str(signal.as_str()?).ok())
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| invalid_artifact("public.json"))
+}
+
+/// Returns a point of G1 as snarkjs serializes it, in projective coordinates.
+fn g1_to_json(point: &G1Affine) -> Value {
+    if point.infinity {
+        return json!(["0", "1", "0"]);
+    }
+    json!([decimal(&point.x), decimal(&point.y), "1"])
+}
+
+/// Returns a point of G2 as snarkjs serializes it, in projective coordinates.
+fn g2_to_json(point: &G2Affine) -> Value {
+    if point.infinity {
+        return json!([["0", "0"], ["1", "0"], ["0", "0"]]);
+    }
+    json!([
+        [decimal(&point.x.c0), decimal(&point.x.c1)],
+        [decimal(&point.y.c0), decimal(&point.y.c1)],
+        ["1", "0"]
+    ])
+}
+
+/// Parse a point of G1, checking that it is on the curve and in the subgroup.
+fn g1_from_json(json: &Value) -> Option<G1Affine> {
+    if json[2] == "0" {
+        return Some(G1Affine::zero());
+    }
+    if json[2] != "1" {
+        return None;
+    }
+    let point = G1Affine::new_unchecked(fq(&json[0])?, fq(&json[1])?);
+    (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then(|| point)
+}
+
+/// Parse a point of G2, checking that it is on the curve and in the subgroup.
+fn g2_from_json(json: &Value) -> Option<G2Affine> {
+    if json[2] == json!(["0", "0"]) {
+        return Some(G2Affine::zero());
+    }
+    if json[2] != json!(["1", "0"]) {
+        return None;
+    }
+    let point = G2Affine::new_unchecked(
+        Fq2::new(fq(&json[0][0])?, fq(&json[0][1])?),
+        Fq2::new(fq(&json[1][0])?, fq(&json[1][1])?),
+    );
+    (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then(|| point)
+}
+
+fn fq(json: &Value) -> Option<Fq> {
+    Fq::from_str(json.as_str()?).ok()
+}
+
+fn decimal<F: PrimeField>(element: &F) -> String {
+    element.into_bigint().to_string()
+}
+
+/// Read the proving key written to `verifier.ark` by [native_setup].
+fn read_proving_key(path: &Path) -> Result<ProvingKey<Bn254>, WinterCircomError> {
+    let file = File::open(path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("reading verifier.ark")),
+    })?;
+    ProvingKey::<Bn254>::deserialize_uncompressed_unchecked(BufReader::new(file))
+        .map_err(|_| invalid_artifact("verifier.ark"))
+}
+
+fn native_error<E: std::fmt::Display>(step: &str, error: E) -> WinterCircomError {
+    WinterCircomError::NativeProverError {
+        comment: format!("{} failed: {}", step, error),
+    }
+}
+
+fn invalid_artifact(file: &str) -> WinterCircomError {
+    WinterCircomError::NativeProverError {
+        comment: format!("{} is not a valid snarkjs Groth16 artifact", file),
+    }
+}
+
+fn read_json(path: &Path, comment: &str) -> Result<Value, WinterCircomError> {
+    let data = fs::read(path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from(comment)),
+    })?;
+    let file = path.file_name().unwrap().to_string_lossy();
+    serde_json::from_slice(&data).map_err(|_| invalid_artifact(&file))
+}
+
+fn write(path: &Path, data: &[u8], comment: &str) -> Result<(), WinterCircomError> {
+    fs::write(path, data).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from(comment)),
+    })
+}
+
+fn write_json(path: &Path, json: &Value, comment: &str) -> Result<(), WinterCircomError> {
+    write(
+        path,
+        serde_json::to_string_pretty(json)
+            .expect("JSON values should be serializable")
+            .as_bytes(),
+        comment,
+    )
+}
@@ -0,0 +1,120 @@
+//! Experimental export of the verification statement to the Noir toolchain.
+//!
+//! Teams using the Barretenberg backend (`bb`, `bb.js` with UltraHonk) rather
+//! than snarkjs can feed the same statement to a Noir program: the input
+//! signals generated by [circom_prove](crate::circom_prove) are rewritten as a
+//! `Prover.toml` file, with the names of the input signals of the `Verify`
+//! template as keys.
+//!
+//! Both Circom and Noir operate over the scalar field of BN254, so the values
+//! are exported unchanged, as decimal strings.
+//!
+//! ## Limitations
+//!
+//! This crate does not provide a Noir port of `circuits/verify.circom`. The
+//! exported file is meant for a Noir verifier program whose `main` function
+//! takes one parameter per signal, with the same names and dimensions as the
+//! inputs of the `Verify` template. This module is only available with the
+//! `noir` feature.
+
+use std::fs::{self, create_dir_all};
+
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::{
+    scratch::scratch_dir,
+    signals::Signal,
+    tenant::validate_circuit_name,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+/// Export the `input.json` file of a circuit to a Noir `Prover.toml` file.
+///
+/// The input is read from the `input.json` file of the circuit
+/// [ScratchSpace](crate::ScratchSpace) and the output is written to
+/// `target/noir/<circuit_name>/Prover.toml`.
+pub fn noir_export(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    if logging_level.print_big_steps() {
+        println!("{}", "Exporting inputs to Noir...".green());
+    }
+
+    let path = scratch_dir(circuit_name).join("input.json");
+    let data = fs::read_to_string(&path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("reading input.json, did you run prove?")),
+    })?;
+    let input: Value =
+        serde_json::from_str(&data).map_err(|e| WinterCircomError::CorruptArtifact {
+            file: path.to_string_lossy().into_owned(),
+            comment: e.to_string(),
+        })?;
+    let toml = to_prover_toml(&input)?;
+
+    create_dir_all(format!("target/noir/{}", circuit_name)).map_err(|e| {
+        WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("creating Noir output directory")),
+        }
+    })?;
+    fs::write(format!("target/noir/{}/Prover.toml", circuit_name), toml).map_err(|e| {
+        WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("writing Prover.toml")),
+        }
+    })
+}
+
+/// Format Circom input signals as a Noir `Prover.toml` file.
+///
+/// Keys are written in the order of the input signals of the `Verify`
+/// template. Returns a [CorruptArtifact](WinterCircomError::CorruptArtifact)
+/// error if any of the signals is missing or if a value is not a number, a
+/// field element or an array of those.
+pub fn to_prover_toml(input: &Value) -> Result<String, WinterCircomError> {
+    let corrupt = |comment: String| WinterCircomError::CorruptArtifact {
+        file: String::from("input.json"),
+        comment,
+    };
+
+    let mut toml = String::new();
+    for signal in Signal::ALL {
+        let value = input
+            .get(signal.name())
+            .ok_or_else(|| corrupt(format!("missing value for signal {}", signal.name())))?;
+        let value = toml_value(value).ok_or_else(|| {
+            corrupt(format!(
+                "cannot export non-numeric value of signal {}",
+                signal.name()
+            ))
+        })?;
+        toml.push_str(&format!("{} = {}\n", signal.name(), value));
+    }
+    Ok(toml)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Format a JSON value as a TOML value, writing all numbers as strings.
+///
+/// Returns `None` if the value is not a number, a string or an array of those.
+fn toml_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(format!("\"{}\"", s)),
+        Value::Number(n) => Some(format!("\"{}\"", n)),
+        Value::Array(values) => Some(format!(
+            "[{}]",
+            values
+                .iter()
+                .map(toml_value)
+                .collect::<Option<Vec<_>>>()?
+                .join(", ")
+        )),
+        _ => None,
+    }
+}
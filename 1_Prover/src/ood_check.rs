@@ -0,0 +1,289 @@
+use std::fs;
+
+use serde_json::{json, Value};
+use winterfell::{
+    crypto::{hashers::Poseidon, RandomCoin},
+    math::{fields::f256::BaseElement, FieldElement, StarkField},
+    Air, StarkProof,
+};
+
+use crate::{
+    codegen::main_arguments,
+    json::public_coin_seed_bytes,
+    packing::public_input_elements,
+    tenant::{split_circuit_name, validate_circuit_name},
+    utils::{command_execution, Executable, LoggingLevel, WinterCircomError},
+    workspace::CircomWorkspace,
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Arguments of the `OodCheck` template, in the order of the template.
+const OOD_CHECK_ARGUMENTS: [&str; 8] = [
+    "addicity",
+    "num_composition_columns",
+    "column_map",
+    "num_assertions",
+    "num_public_inputs",
+    "num_transition_constraints",
+    "trace_length",
+    "trace_width",
+];
+
+/// Inputs of the standalone out-of-domain (OOD) consistency check of
+/// `circuits/ood_check.circom`.
+///
+/// The check evaluates only the `OodConsistencyCheck` template of the
+/// verifier, with the Fiat-Shamir coefficients and the OOD point given as
+/// inputs instead of being drawn from the public coin. This allows
+/// differential tests of the Circom check against the Rust `check_ood_frame`
+/// on arbitrary, e.g. mutated, OOD data, without compiling and proving the
+/// whole verifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OodCheckInputs {
+    /// OOD point of evaluation.
+    pub z: BaseElement,
+
+    /// Fiat-Shamir coefficients of the transition and boundary constraints.
+    pub transition_coeffs: Vec<(BaseElement, BaseElement)>,
+    pub boundary_coeffs: Vec<(BaseElement, BaseElement)>,
+
+    /// Evaluations of the transition constraints at the OOD trace frame.
+    pub constraint_evaluations: Vec<BaseElement>,
+
+    /// OOD trace frame, i.e. the trace evaluations at the OOD point and at the
+    /// next step.
+    pub current: Vec<BaseElement>,
+    pub next: Vec<BaseElement>,
+
+    /// Evaluations of the columns of the composition polynomial at the OOD
+    /// point, sent by the prover.
+    pub composition_evaluations: Vec<BaseElement>,
+
+    /// Field elements of the public inputs.
+    pub public_inputs: Vec<BaseElement>,
+}
+
+impl OodCheckInputs {
+    /// Returns the OOD check inputs of a Winterfell proof, replaying the public
+    /// coin of the Winterfell verifier to draw the coefficients and the OOD
+    /// point.
+    pub fn from_proof<AIR>(
+        proof: &StarkProof,
+        pub_inputs: AIR::PublicInputs,
+    ) -> Result<Self, WinterCircomError>
+    where
+        AIR: Air<BaseField = BaseElement>,
+        AIR::PublicInputs: WinterPublicInputs,
+    {
+        let air = AIR::new(
+            proof.get_trace_info(),
+            pub_inputs.clone(),
+            proof.options().clone(),
+        );
+        let invalid = |comment: &str| WinterCircomError::InvalidStarkProof {
+            comment: String::from(comment),
+        };
+
+        // only one trace segment is supported by the Circom code
+        if air.trace_layout().num_segments() != 1 {
+            return Err(invalid("expected a single trace segment"));
+        }
+
+        let mut public_coin = RandomCoin::<BaseElement, Poseidon<BaseElement>>::new(
+            &public_coin_seed_bytes(&pub_inputs, &proof.context),
+        );
+        let (trace_commitments, constraint_commitment, _) = proof
+            .commitments
+            .clone()
+            .parse::<Poseidon<BaseElement>>(
+                1,
+                air.options()
+                    .to_fri_options()
+                    .num_fri_layers(air.lde_domain_size()),
+            )
+            .map_err(|_| invalid("could not parse the commitments"))?;
+
+        public_coin.reseed(trace_commitments[0]);
+        let coefficients = air
+            .get_constraint_composition_coefficients::<BaseElement, Poseidon<BaseElement>>(
+                &mut public_coin,
+            )
+            .map_err(|_| invalid("could not draw the composition coefficients"))?;
+        public_coin.reseed(constraint_commitment);
+        let z = public_coin
+            .draw::<BaseElement>()
+            .map_err(|_| invalid("could not draw the OOD point"))?;
+
+        let (ood_trace_frame, _, composition_evaluations) = proof
+            .ood_frame
+            .clone()
+            .parse::<BaseElement>(
+                air.trace_layout().main_trace_width(),
+                air.trace_layout().aux_trace_width(),
+                air.ce_blowup_factor(),
+            )
+            .map_err(|_| invalid("could not parse the OOD frame"))?;
+
+        // FIXME: fix periodic values, as in proof_to_json
+        let mut constraint_evaluations =
+            BaseElement::zeroed_vector(air.context().num_transition_constraints());
+        air.evaluate_transition::<BaseElement>(&ood_trace_frame, &[], &mut constraint_evaluations);
+
+        Ok(OodCheckInputs {
+            z,
+            transition_coeffs: coefficients.transition,
+            boundary_coeffs: coefficients.boundary,
+            constraint_evaluations,
+            current: ood_trace_frame.current().to_vec(),
+            next: ood_trace_frame.next().to_vec(),
+            composition_evaluations,
+            public_inputs: public_input_elements(&pub_inputs),
+        })
+    }
+
+    /// Returns the input signals of the `OodCheck` template, as written to
+    /// `ood_check_input.json` by [circom_check_ood].
+    pub fn to_json(&self) -> Value {
+        json!({
+            "addicity_root": BaseElement::TWO_ADIC_ROOT_OF_UNITY,
+            "boundary_coeffs": self.boundary_coeffs,
+            "ood_constraint_evaluations": self.composition_evaluations,
+            "ood_frame_constraint_evaluation": self.constraint_evaluations,
+            "ood_trace_frame": [self.current, self.next],
+            "public_inputs": self.public_inputs,
+            "transition_coeffs": self.transition_coeffs,
+            "z": self.z,
+        })
+    }
+}
+
+/// Generate and compile the standalone OOD consistency check of a circuit,
+/// `target/circom/<circuit_name>/ood_check.circom`, for the proofs generated
+/// with the given options (see [OodCheckInputs]).
+///
+/// The check includes the AIR template of the circuit, and is compiled to
+/// WebAssembly only, as no proof of it is ever generated.
+pub fn circom_create_ood_check<AIR, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    validate_circuit_name(circuit_name)?;
+
+    let workspace = CircomWorkspace::current();
+    let dir = workspace.circuit_dir(circuit_name);
+    fs::create_dir_all(&dir)
+        .and_then(|_| {
+            fs::write(
+                dir.join("ood_check.circom"),
+                ood_check_source::<AIR, N>(
+                    &proof_options,
+                    split_circuit_name(circuit_name).1,
+                    &workspace.include_dir(circuit_name),
+                ),
+            )
+        })
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("writing the OOD consistency check")),
+        })?;
+
+    command_execution(
+        Executable::Circom,
+        &["ood_check.circom", "--wasm"],
+        dir.to_str(),
+        &logging_level,
+    )
+}
+
+/// Evaluate the OOD consistency check of a circuit, generated by
+/// [circom_create_ood_check], on the given inputs.
+///
+/// Returns whether the witness of the check could be computed, i.e. whether
+/// the Circom verifier accepts the OOD data of the inputs.
+pub fn circom_check_ood(
+    circuit_name: &str,
+    inputs: &OodCheckInputs,
+    logging_level: LoggingLevel,
+) -> Result<bool, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+
+    let dir = CircomWorkspace::current().circuit_dir(circuit_name);
+    fs::write(
+        dir.join("ood_check_input.json"),
+        inputs.to_json().to_string(),
+    )
+    .map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from(
+            "writing the inputs of the OOD consistency check",
+        )),
+    })?;
+
+    match command_execution(
+        Executable::SnarkJS,
+        &[
+            "wc",
+            "ood_check_js/ood_check.wasm",
+            "ood_check_input.json",
+            "ood_check.wtns",
+        ],
+        dir.to_str(),
+        &logging_level,
+    ) {
+        Ok(()) => Ok(true),
+        Err(WinterCircomError::ExitCodeError { .. }) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the source of the Circom main file of the OOD consistency check,
+/// with the arguments of the `Verify` template the `OodCheck` template shares.
+fn ood_check_source<AIR, const N: usize>(
+    proof_options: &WinterCircomProofOptions<N>,
+    air_name: &str,
+    circuits_dir: &str,
+) -> String
+where
+    AIR: Air<BaseField = BaseElement>,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let main_arguments = main_arguments::<BaseElement, AIR, N>(proof_options);
+    let arguments = OOD_CHECK_ARGUMENTS
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let argument = main_arguments
+                .iter()
+                .find(|argument| argument.name == *name)
+                .expect("OodCheck arguments should be arguments of Verify");
+            let separator = if i + 1 < OOD_CHECK_ARGUMENTS.len() {
+                ","
+            } else {
+                ""
+            };
+            format!("{}{} // {}", argument.value, separator, name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    format!(
+        "pragma circom 2.0.0;\n\
+        \n\
+        include \"{}/ood_check.circom\";\n\
+        include \"{}/air/{}.circom\";\n\
+        \n\
+        component main = OodCheck(\n    \
+            {}\n\
+        );\n\
+",
+        circuits_dir, circuits_dir, air_name, arguments
+    )
+}
@@ -0,0 +1,275 @@
+use std::fmt::{self, Display};
+
+use serde::Serialize;
+use winterfell::math::log2;
+
+use crate::{fri_tree_depths, utils::WinterCircomError, WinterCircomProofOptions};
+
+/// Largest number of queries accepted by Winterfell.
+const MAX_NUM_QUERIES: usize = 128;
+
+/// Largest LDE blowup factor accepted by Winterfell.
+const MAX_LDE_BLOWUP_FACTOR: usize = 128;
+
+/// Largest grinding factor accepted by Winterfell.
+const MAX_GRINDING_FACTOR: u32 = 32;
+
+/// Cost minimized by a [ProofOptionsOptimizer].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizationGoal {
+    /// Minimize the size of the Circom circuit, i.e. the cost of the recursion:
+    /// few queries, at the cost of a larger LDE domain and more grinding.
+    CircuitSize,
+
+    /// Minimize the time of the Winterfell prover: a small LDE domain and
+    /// little grinding, at the cost of more queries.
+    ProverTime,
+}
+
+/// Search of the number of queries, LDE blowup factor and grinding factor
+/// reaching the [target security](WinterCircomProofOptions::target_security)
+/// of proof options at the lowest cost.
+///
+/// ```ignore
+/// let optimized = ProofOptionsOptimizer::new(OptimizationGoal::CircuitSize)
+///     .max_grinding_factor(16)
+///     .optimize(WinterCircomProofOptions::new(128, 2, 3, [1, 1], 42, 8, 0, 8, 256))?;
+/// println!("{}", optimized.report);
+/// circom_create::<WorkProver, 2>(optimized.options, "sum", LoggingLevel::Default)?;
+/// ```
+///
+/// Every combination whose [conjectured security](WinterCircomProofOptions::conjectured_security_bits)
+/// reaches the target is considered, with the fewest queries reaching it. The
+/// costs are estimated rather than measured:
+///
+/// - the circuit size is the number of hashes the circuit verifies per proof,
+/// i.e. the leaves and Merkle authentication paths of the trace, the
+/// constraint composition and the FRI layers, at each query;
+/// - the prover time is the number of elements of the low-degree extensions
+/// of the trace and of the composition polynomial, plus the `2^grinding_factor`
+/// hashes of the proof-of-work.
+///
+/// Ties are broken by the other cost. The trace dimensions, the transition
+/// constraint degrees and the FRI options are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofOptionsOptimizer {
+    goal: OptimizationGoal,
+    max_grinding_factor: u32,
+    max_lde_blowup_factor: usize,
+}
+
+/// Proof options chosen by a [ProofOptionsOptimizer], and the justification of
+/// the choice.
+pub struct OptimizedProofOptions<const N: usize> {
+    pub options: WinterCircomProofOptions<N>,
+    pub report: OptimizationReport,
+}
+
+/// Justification of the proof options chosen by a [ProofOptionsOptimizer].
+///
+/// It is displayed as a summary of the choice, followed by the best
+/// alternatives for each LDE blowup factor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OptimizationReport {
+    pub goal: OptimizationGoal,
+    pub target_security_bits: u32,
+    pub chosen: ProofOptionsCandidate,
+
+    /// Cheapest candidate of each LDE blowup factor, cheapest first.
+    pub alternatives: Vec<ProofOptionsCandidate>,
+
+    /// Number of combinations reaching the target security.
+    pub num_candidates: usize,
+}
+
+/// Combination of options considered by a [ProofOptionsOptimizer].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ProofOptionsCandidate {
+    pub num_queries: usize,
+    pub lde_blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub conjectured_security_bits: u32,
+
+    /// Estimated number of hashes verified by the circuit.
+    pub circuit_cost: u64,
+
+    /// Estimated work of the Winterfell prover.
+    pub prover_cost: u64,
+}
+
+impl ProofOptionsOptimizer {
+    /// Returns an optimizer of the given goal, with a grinding factor of at
+    /// most 20 bits and an LDE blowup factor of at most 64.
+    pub fn new(goal: OptimizationGoal) -> Self {
+        Self {
+            goal,
+            max_grinding_factor: 20,
+            max_lde_blowup_factor: 64,
+        }
+    }
+
+    /// Set the largest grinding factor considered, at most 32. Each bit of
+    /// grinding doubles the proof-of-work of the prover.
+    pub fn max_grinding_factor(self, max_grinding_factor: u32) -> Self {
+        Self {
+            max_grinding_factor: max_grinding_factor.min(MAX_GRINDING_FACTOR),
+            ..self
+        }
+    }
+
+    /// Set the largest LDE blowup factor considered, at most 128.
+    pub fn max_lde_blowup_factor(self, max_lde_blowup_factor: usize) -> Self {
+        Self {
+            max_lde_blowup_factor: max_lde_blowup_factor.min(MAX_LDE_BLOWUP_FACTOR),
+            ..self
+        }
+    }
+
+    /// Returns the options reaching the target security of the given options
+    /// at the lowest cost, and the report justifying them.
+    ///
+    /// The LDE blowup factors considered are the powers of two from the
+    /// [smallest sound one](WinterCircomProofOptions::min_lde_blowup_factor).
+    /// Returns an error if no combination reaches the target security.
+    pub fn optimize<const N: usize>(
+        &self,
+        options: WinterCircomProofOptions<N>,
+    ) -> Result<OptimizedProofOptions<N>, WinterCircomError> {
+        let target = options.target_security_bits();
+        let mut candidates = Vec::new();
+        let mut lde_blowup_factor = options.min_lde_blowup_factor().next_power_of_two();
+        while lde_blowup_factor <= self.max_lde_blowup_factor {
+            // the last FRI layer must be smaller than the LDE domain
+            if options.trace_length * lde_blowup_factor > options.fri_max_remainder_size {
+                for grinding_factor in 0..=self.max_grinding_factor.min(target) {
+                    let bits_per_query = log2(lde_blowup_factor);
+                    let num_queries =
+                        ((target - grinding_factor + bits_per_query - 1) / bits_per_query).max(1);
+                    if num_queries as usize <= MAX_NUM_QUERIES {
+                        candidates.push(candidate(
+                            &options,
+                            num_queries as usize,
+                            lde_blowup_factor,
+                            grinding_factor,
+                        ));
+                    }
+                }
+            }
+            lde_blowup_factor *= 2;
+        }
+
+        candidates.sort_by_key(|candidate| self.costs(candidate));
+        let chosen = *candidates
+            .first()
+            .ok_or(WinterCircomError::UnreachableSecurity {
+                target_bits: target,
+            })?;
+        let mut alternatives: Vec<ProofOptionsCandidate> = Vec::new();
+        for candidate in candidates.iter() {
+            if candidate.lde_blowup_factor != chosen.lde_blowup_factor
+                && alternatives
+                    .iter()
+                    .all(|alternative| alternative.lde_blowup_factor != candidate.lde_blowup_factor)
+            {
+                alternatives.push(*candidate);
+            }
+        }
+
+        Ok(OptimizedProofOptions {
+            options: WinterCircomProofOptions {
+                num_queries: chosen.num_queries,
+                lde_blowup_factor: chosen.lde_blowup_factor,
+                grinding_factor: chosen.grinding_factor,
+                ..options
+            },
+            report: OptimizationReport {
+                goal: self.goal,
+                target_security_bits: target,
+                chosen,
+                alternatives,
+                num_candidates: candidates.len(),
+            },
+        })
+    }
+
+    /// Returns the costs of a candidate, the minimized one first.
+    fn costs(&self, candidate: &ProofOptionsCandidate) -> (u64, u64) {
+        match self.goal {
+            OptimizationGoal::CircuitSize => (candidate.circuit_cost, candidate.prover_cost),
+            OptimizationGoal::ProverTime => (candidate.prover_cost, candidate.circuit_cost),
+        }
+    }
+}
+
+impl Display for ProofOptionsCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} queries, blowup {}, grinding {} ({} bits): circuit cost {}, prover cost {}",
+            self.num_queries,
+            self.lde_blowup_factor,
+            self.grinding_factor,
+            self.conjectured_security_bits,
+            self.circuit_cost,
+            self.prover_cost
+        )
+    }
+}
+
+impl Display for OptimizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let goal = match self.goal {
+            OptimizationGoal::CircuitSize => "circuit size",
+            OptimizationGoal::ProverTime => "prover time",
+        };
+        writeln!(
+            f,
+            "Chose {} out of {} combinations reaching {} bits, minimizing the {}.",
+            self.chosen, self.num_candidates, self.target_security_bits, goal
+        )?;
+        for alternative in self.alternatives.iter() {
+            write!(f, "\n  - {}", alternative)?;
+        }
+        Ok(())
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn candidate<const N: usize>(
+    options: &WinterCircomProofOptions<N>,
+    num_queries: usize,
+    lde_blowup_factor: usize,
+    grinding_factor: u32,
+) -> ProofOptionsCandidate {
+    let lde_domain_size = options.trace_length * lde_blowup_factor;
+    let num_composition_columns = options.num_composition_columns();
+
+    // leaf and authentication path of the trace, the composition and each FRI
+    // layer, at each query, and the proof-of-work
+    let depth = log2(lde_domain_size) as u64;
+    let fri_hashes = fri_tree_depths(
+        lde_domain_size,
+        options.fri_folding_factor,
+        options.fri_max_remainder_size,
+    )
+    .iter()
+    .map(|depth| 1 + *depth as u64)
+    .sum::<u64>();
+    let circuit_cost = num_queries as u64 * (2 * (1 + depth) + fri_hashes) + 1;
+
+    let prover_cost = (options.trace_width + num_composition_columns) as u64
+        * lde_domain_size as u64
+        + (1 << grinding_factor);
+
+    ProofOptionsCandidate {
+        num_queries,
+        lde_blowup_factor,
+        grinding_factor,
+        conjectured_security_bits: num_queries as u32 * log2(lde_blowup_factor) + grinding_factor,
+        circuit_cost,
+        prover_cost,
+    }
+}
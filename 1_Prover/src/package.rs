@@ -0,0 +1,381 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+use winterfell::{
+    math::{fields::f256::BaseElement, StarkField},
+    Air,
+};
+
+use crate::{
+    proofs::{list_proofs, proof_dir},
+    signals::Signal,
+    tenant::validate_circuit_name,
+    utils::{check_file, command_execution, Executable, LoggingLevel, WinterCircomError},
+    workspace::circuit_dir,
+    CircuitManifest, PublicInputLayout, SnarkScheme, WinterPublicInputs,
+};
+
+/// Export the verification artifacts of a circuit as a self-contained
+/// directory, `target/circom/<circuit_name>/verifier_package/`, to be handed
+/// over to the parties that verify its proofs.
+///
+/// The package contains:
+///
+/// - `verification_key.json`: the verification key of the [SnarkScheme] of
+/// the circuit, in the snarkjs format;
+/// - `verifier.sol`: the Solidity verifier contract, if the proving key
+/// (`verifier.zkey`) is available on this host;
+/// - `public_signals.json`: the encoding of the Groth16 public signals, i.e.
+/// the offset and length of each public signal in `public.json` and the bit
+/// widths of the packed public inputs;
+/// - `samples/`: the last proof and the [saved](crate::save_proof) proofs of
+/// the circuit, if any;
+/// - `snippets/`: verification snippets for Rust, JavaScript and Solidity.
+///
+/// As with [check_ood_frame](crate::check_ood_frame), the [Default]
+/// implementation of the [Air] must have the same trace width and transition
+/// constraints as the one used to generate the proofs. Returns the path of the
+/// package.
+pub fn export_verifier_package<AIR>(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<PathBuf, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    validate_circuit_name(circuit_name)?;
+    let circuit_dir = circuit_dir(circuit_name);
+    let package = circuit_dir.join("verifier_package");
+    check_file(
+        circuit_dir
+            .join("verification_key.json")
+            .to_string_lossy()
+            .into_owned(),
+        Some("needed for the verifier package"),
+    )?;
+    let manifest =
+        CircuitManifest::read(circuit_name)?.ok_or_else(|| WinterCircomError::FileNotFound {
+            file: CircuitManifest::path(circuit_name),
+            comment: Some(String::from("needed to describe the public signals")),
+        })?;
+
+    for dir in [
+        package.clone(),
+        package.join("samples"),
+        package.join("snippets"),
+    ] {
+        fs::create_dir_all(&dir).map_err(io_error(format!("creating {}", dir.display())))?;
+    }
+
+    // VERIFICATION KEYS
+
+    copy(
+        &circuit_dir.join("verification_key.json"),
+        &package.join("verification_key.json"),
+    )?;
+    if circuit_dir.join("verifier.zkey").exists() {
+        command_execution(
+            Executable::SnarkJS,
+            &["zkesv", "verifier.zkey", "verifier_package/verifier.sol"],
+            circuit_dir.to_str(),
+            &logging_level,
+        )?;
+    } else if logging_level.print_big_steps() {
+        println!(
+            "{}",
+            "Warning: verifier.zkey not found, the Solidity verifier is not exported.".yellow()
+        );
+    }
+
+    // PUBLIC SIGNALS ENCODING
+
+    let encoding = public_signals_encoding::<AIR>(
+        manifest.public_inputs_exposed.unwrap_or(false),
+        manifest.public_signals_committed.unwrap_or(false),
+    );
+    let num_public_signals = encoding["num_public_signals"].as_u64().unwrap_or(0) as usize;
+    write(
+        &package.join("public_signals.json"),
+        &serde_json::to_string_pretty(&encoding).unwrap(),
+    )?;
+
+    // SAMPLE PROOFS
+
+    let mut samples = vec![(String::from("latest"), circuit_dir.clone())];
+    samples.extend(
+        list_proofs(circuit_name)
+            .into_iter()
+            .filter_map(|proof_id| {
+                Some((proof_id.clone(), proof_dir(circuit_name, &proof_id).ok()?))
+            }),
+    );
+    for (name, dir) in samples {
+        if !dir.join("proof.json").exists() || !dir.join("public.json").exists() {
+            continue;
+        }
+        let sample = package.join("samples").join(name);
+        fs::create_dir_all(&sample).map_err(io_error(format!("creating {}", sample.display())))?;
+        for file in ["proof.json", "public.json"] {
+            copy(&dir.join(file), &sample.join(file))?;
+        }
+    }
+
+    // SNIPPETS
+
+    let snippets = package.join("snippets");
+    let scheme = manifest.snark_scheme;
+    write(
+        &snippets.join("verify.rs"),
+        &RUST_SNIPPET
+            .replace("g16v", scheme.verify_command())
+            .replace("Groth16", scheme.name()),
+    )?;
+    write(
+        &snippets.join("verify.js"),
+        &JS_SNIPPET.replace("groth16", scheme.snarkjs_module()),
+    )?;
+    let solidity_snippet = match scheme {
+        SnarkScheme::Groth16 => SOLIDITY_SNIPPET,
+        SnarkScheme::Plonk => PLONK_SOLIDITY_SNIPPET,
+        SnarkScheme::Fflonk => FFLONK_SOLIDITY_SNIPPET,
+    };
+    write(
+        &snippets.join("Verify.sol"),
+        &solidity_snippet.replace("NUM_PUBLIC_SIGNALS", &num_public_signals.to_string()),
+    )?;
+
+    Ok(package)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the encoding of the Groth16 public signals of `public.json`.
+fn public_signals_encoding<AIR>(
+    public_inputs_exposed: bool,
+    public_signals_committed: bool,
+) -> Value
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let air = AIR::default();
+    let trace_width = air.trace_info().width();
+    let num_transition_constraints = air.context().num_transition_constraints();
+    let layout = PublicInputLayout::of::<AIR::PublicInputs>();
+
+    let mut signals = vec![
+        json!({
+            "name": Signal::OodFrameConstraintEvaluation.name(),
+            "offset": 0,
+            "length": num_transition_constraints,
+            "description": "evaluations of the transition constraints at the out-of-domain point",
+        }),
+        json!({
+            "name": Signal::OodTraceFrame.name(),
+            "offset": num_transition_constraints,
+            "length": 2 * trace_width,
+            "description": "out-of-domain trace frame, the current row followed by the next row",
+        }),
+    ];
+    let mut num_public_signals = num_transition_constraints + 2 * trace_width;
+    if public_inputs_exposed {
+        let signal = if layout.is_packed() {
+            Signal::PackedPublicInputs
+        } else {
+            Signal::PublicInputs
+        };
+        signals.push(json!({
+            "name": signal.name(),
+            "offset": num_public_signals,
+            "length": layout.num_packed(),
+            "description": "public inputs of the AIR, packed in little-endian order into the \
+                signals of their slots (bit width 0 means a full field element)",
+            "bits": layout.bits(),
+            "slots": layout.slots(),
+        }));
+        num_public_signals += layout.num_packed();
+    }
+
+    // the committed values are the public signals, with unpacked public inputs
+    if public_signals_committed {
+        let mut committed = vec![
+            Signal::OodFrameConstraintEvaluation.name(),
+            Signal::OodTraceFrame.name(),
+        ];
+        if public_inputs_exposed {
+            committed.push(Signal::PublicInputs.name());
+        }
+        signals = vec![json!({
+            "name": Signal::PublicCommitment.name(),
+            "offset": 0,
+            "length": 1,
+            "description": "Poseidon hash of the committed signals, in order, which are private",
+            "committed_signals": committed,
+        })];
+        num_public_signals = 1;
+    }
+
+    json!({
+        "field_modulus": BaseElement::MODULUS.to_string(),
+        "encoding": "decimal strings",
+        "num_public_signals": num_public_signals,
+        "signals": signals,
+    })
+}
+
+fn io_error(comment: String) -> impl FnOnce(std::io::Error) -> WinterCircomError {
+    move |e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(comment),
+    }
+}
+
+fn copy(source: &Path, target: &Path) -> Result<(), WinterCircomError> {
+    fs::copy(source, target)
+        .map(|_| ())
+        .map_err(io_error(format!("copying {}", source.display())))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), WinterCircomError> {
+    fs::write(path, contents).map_err(io_error(format!("writing {}", path.display())))
+}
+
+const RUST_SNIPPET: &str = r#"// Verify a proof with snarkjs, then decode its public signals with
+// winter-circom-prover, using the AIR of the proved computation.
+use std::process::Command;
+
+use winter_circom_prover::decode_public_signals;
+
+fn main() {
+    let status = Command::new("snarkjs")
+        .args(["g16v", "verification_key.json", "samples/latest/public.json", "samples/latest/proof.json"])
+        .status()
+        .expect("snarkjs should be installed");
+    assert!(status.success(), "invalid Groth16 proof");
+
+    // replace MyAir with the AIR of the computation, the OOD data must then be
+    // checked against its transition constraints (see check_ood_frame)
+    let (public_inputs, ood_data) =
+        decode_public_signals::<MyAir>("samples/latest/public.json").unwrap();
+}
+"#;
+
+const JS_SNIPPET: &str = r#"// Verify a proof with snarkjs (npm install snarkjs).
+const snarkjs = require("snarkjs");
+const fs = require("fs");
+
+async function main() {
+    const vkey = JSON.parse(fs.readFileSync("verification_key.json"));
+    const publicSignals = JSON.parse(fs.readFileSync("samples/latest/public.json"));
+    const proof = JSON.parse(fs.readFileSync("samples/latest/proof.json"));
+
+    // see public_signals.json for the meaning of each public signal
+    const valid = await snarkjs.groth16.verify(vkey, publicSignals, proof);
+    console.log(valid ? "valid proof" : "invalid proof");
+    process.exit(valid ? 0 : 1);
+}
+
+main();
+"#;
+
+const SOLIDITY_SNIPPET: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// Interface of the contract of verifier.sol (its name depends on the version of
+// snarkjs). The calldata of a proof is printed by:
+//     snarkjs zkey export soliditycalldata public.json proof.json
+interface IGroth16Verifier {
+    function verifyProof(
+        uint256[2] calldata a,
+        uint256[2][2] calldata b,
+        uint256[2] calldata c,
+        uint256[NUM_PUBLIC_SIGNALS] calldata input
+    ) external view returns (bool);
+}
+
+contract Verify {
+    IGroth16Verifier public immutable verifier;
+
+    constructor(IGroth16Verifier _verifier) {
+        verifier = _verifier;
+    }
+
+    // see public_signals.json for the meaning of each public signal
+    function verify(
+        uint256[2] calldata a,
+        uint256[2][2] calldata b,
+        uint256[2] calldata c,
+        uint256[NUM_PUBLIC_SIGNALS] calldata input
+    ) external view returns (bool) {
+        return verifier.verifyProof(a, b, c, input);
+    }
+}
+"#;
+
+const PLONK_SOLIDITY_SNIPPET: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// Interface of the contract of verifier.sol (its name and the encoding of the
+// proof depend on the version of snarkjs). The calldata of a proof is printed
+// by:
+//     snarkjs zkey export soliditycalldata public.json proof.json
+interface IPlonkVerifier {
+    function verifyProof(
+        uint256[24] calldata proof,
+        uint256[NUM_PUBLIC_SIGNALS] calldata pubSignals
+    ) external view returns (bool);
+}
+
+contract Verify {
+    IPlonkVerifier public immutable verifier;
+
+    constructor(IPlonkVerifier _verifier) {
+        verifier = _verifier;
+    }
+
+    // see public_signals.json for the meaning of each public signal
+    function verify(
+        uint256[24] calldata proof,
+        uint256[NUM_PUBLIC_SIGNALS] calldata pubSignals
+    ) external view returns (bool) {
+        return verifier.verifyProof(proof, pubSignals);
+    }
+}
+"#;
+
+const FFLONK_SOLIDITY_SNIPPET: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// Interface of the contract of verifier.sol (its name depends on the version of
+// snarkjs). FFLONK proofs are encoded as 24 words. The calldata of a proof is
+// printed by:
+//     snarkjs zkey export soliditycalldata public.json proof.json
+interface IFflonkVerifier {
+    function verifyProof(
+        bytes32[24] calldata proof,
+        uint256[NUM_PUBLIC_SIGNALS] calldata pubSignals
+    ) external view returns (bool);
+}
+
+contract Verify {
+    IFflonkVerifier public immutable verifier;
+
+    constructor(IFflonkVerifier _verifier) {
+        verifier = _verifier;
+    }
+
+    // see public_signals.json for the meaning of each public signal
+    function verify(
+        bytes32[24] calldata proof,
+        uint256[NUM_PUBLIC_SIGNALS] calldata pubSignals
+    ) external view returns (bool) {
+        return verifier.verifyProof(proof, pubSignals);
+    }
+}
+"#;
@@ -0,0 +1,202 @@
+use serde::Serialize;
+use winterfell::math::{
+    fields::f256::{BaseElement, U256},
+    StarkField,
+};
+
+use crate::{utils::WinterCircomError, WinterPublicInputs};
+
+/// Maximum number of bits of a packed public input.
+///
+/// Packed values must be smaller than the modulus of the Circom field, so that
+/// the in-circuit bit decomposition is unique.
+pub const MAX_PACKED_BITS: usize = 253;
+
+/// Layout of the public inputs in the Groth16 public signals.
+///
+/// Each f256 public signal of the Groth16 proof costs gas when verified
+/// on-chain. Public inputs that are known to be small (e.g. balances or
+/// indices) can be packed into fewer public signals by declaring their bit
+/// width in [PUBLIC_INPUT_BITS](WinterPublicInputs::PUBLIC_INPUT_BITS).
+///
+/// Consecutive public inputs of non-zero width are concatenated (little-endian)
+/// into the same packed signal, as long as it does not exceed
+/// [MAX_PACKED_BITS]. Public inputs of width 0 are full field elements and
+/// occupy a packed signal of their own. The generated Circom main component
+/// unpacks the packed signals and checks that they match the public inputs of
+/// the Winterfell proof, which also range-checks the packed public inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputLayout {
+    bits: Vec<usize>,
+    slots: Vec<usize>,
+}
+
+impl PublicInputLayout {
+    /// Build the layout of public inputs of given bit widths.
+    ///
+    /// Panics if any width exceeds [MAX_PACKED_BITS].
+    pub fn new(bits: &[usize]) -> Self {
+        let mut slots = Vec::with_capacity(bits.len());
+        let mut num_slots = 0;
+        let mut slot_bits = MAX_PACKED_BITS;
+
+        for &width in bits {
+            assert!(
+                width <= MAX_PACKED_BITS,
+                "public inputs cannot be packed in more than {} bits",
+                MAX_PACKED_BITS
+            );
+
+            // open a new slot for full elements or if the current one is full
+            if width == 0 || slot_bits + width > MAX_PACKED_BITS {
+                num_slots += 1;
+                slot_bits = 0;
+            }
+            slots.push(num_slots - 1);
+            slot_bits += width;
+
+            // full elements do not share their slot
+            if width == 0 {
+                slot_bits = MAX_PACKED_BITS;
+            }
+        }
+
+        Self {
+            bits: bits.to_vec(),
+            slots,
+        }
+    }
+
+    /// Returns the layout of the given public inputs.
+    ///
+    /// Public inputs are not packed if
+    /// [PUBLIC_INPUT_BITS](WinterPublicInputs::PUBLIC_INPUT_BITS) is empty.
+    ///
+    /// Panics if it is neither empty nor of length
+    /// [NUM_PUB_INPUTS](WinterPublicInputs::NUM_PUB_INPUTS).
+    pub fn of<I: WinterPublicInputs>() -> Self {
+        if I::PUBLIC_INPUT_BITS.is_empty() {
+            return Self::new(&vec![0; I::NUM_PUB_INPUTS]);
+        }
+
+        assert_eq!(
+            I::PUBLIC_INPUT_BITS.len(),
+            I::NUM_PUB_INPUTS,
+            "PUBLIC_INPUT_BITS must give the width of every public input"
+        );
+        Self::new(I::PUBLIC_INPUT_BITS)
+    }
+
+    /// Returns whether any public inputs are packed together.
+    pub fn is_packed(&self) -> bool {
+        self.num_packed() < self.bits.len()
+    }
+
+    /// Returns the number of packed public signals.
+    pub fn num_packed(&self) -> usize {
+        self.slots.last().map_or(0, |slot| slot + 1)
+    }
+
+    /// Returns the bit width of each public input (0 for full elements).
+    pub fn bits(&self) -> &[usize] {
+        &self.bits
+    }
+
+    /// Returns the index of the packed signal of each public input.
+    pub fn slots(&self) -> &[usize] {
+        &self.slots
+    }
+
+    /// Pack public inputs into the packed public signals.
+    ///
+    /// Returns an error if a public input does not fit in its bit width.
+    pub fn pack(
+        &self,
+        public_inputs: &[BaseElement],
+    ) -> Result<Vec<BaseElement>, WinterCircomError> {
+        assert_eq!(public_inputs.len(), self.bits.len());
+
+        let zero = U256::from(0u8);
+        let mut packed = vec![zero; self.num_packed()];
+        let mut offsets = vec![0; self.num_packed()];
+
+        for (i, input) in public_inputs.iter().enumerate() {
+            let (width, slot) = (self.bits[i], self.slots[i]);
+            let value = input.as_int();
+
+            if width == 0 {
+                packed[slot] = value;
+                continue;
+            }
+            if value >> (width as u32) != zero {
+                return Err(WinterCircomError::PublicInputTooLarge {
+                    index: i,
+                    bits: width,
+                });
+            }
+            packed[slot] = packed[slot] | (value << (offsets[slot] as u32));
+            offsets[slot] += width;
+        }
+
+        Ok(packed.into_iter().map(BaseElement::new).collect())
+    }
+
+    /// Unpack the packed public signals into the public inputs.
+    pub fn unpack(&self, packed: &[BaseElement]) -> Vec<BaseElement> {
+        assert_eq!(packed.len(), self.num_packed());
+
+        let one = U256::from(1u8);
+        let mut offsets = vec![0; self.num_packed()];
+
+        self.bits
+            .iter()
+            .zip(self.slots.iter())
+            .map(|(&width, &slot)| {
+                let value = packed[slot].as_int();
+                if width == 0 {
+                    return BaseElement::new(value);
+                }
+
+                let mask = (one << (width as u32)) - one;
+                let input = (value >> (offsets[slot] as u32)) & mask;
+                offsets[slot] += width;
+                BaseElement::new(input)
+            })
+            .collect()
+    }
+
+    /// Returns the Circom array literals of the bit widths and packed signal
+    /// indices of the public inputs, for the generated main component.
+    pub(crate) fn circom_arguments(&self) -> (String, String) {
+        (circom_array(&self.bits), circom_array(&self.slots))
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the field elements of serialized public inputs.
+pub(crate) fn public_input_elements<I: Serialize>(pub_inputs: &I) -> Vec<BaseElement> {
+    let json = serde_json::to_value(pub_inputs).expect("public inputs should be serializable");
+    json.as_array()
+        .expect("public inputs should be serialized as a sequence")
+        .iter()
+        .map(|value| BaseElement::new(U256::from_str_radix(value.as_str().unwrap(), 10).unwrap()))
+        .collect()
+}
+
+/// Format an array as a Circom array literal, with a single zero element if
+/// empty, as Circom does not support empty arrays.
+fn circom_array(values: &[usize]) -> String {
+    if values.is_empty() {
+        return String::from("[0]");
+    }
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
@@ -0,0 +1,779 @@
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde_json::{json, Value};
+
+#[cfg(feature = "service")]
+use crate::ArtifactUpload;
+use crate::{
+    audit::{audited, hash_artifact, hash_file, AuditAction},
+    constraints::check_transition_degrees,
+    keys::random_entropy,
+    scratch::scratch_dir,
+    snark_backend::current_snark_backend,
+    utils::{
+        check_file, command_execution, command_execution_with_stdin, delete_file, Executable,
+        LoggingLevel, StageTimer, WinterCircomError, STDIN_PATH,
+    },
+    workspace::{circuit_dir, CircomWorkspace},
+    CircuitManifest, HostRole, ProverBackend, ScratchSpace, SnarkScheme,
+};
+
+/// Stage of the Groth16 pipeline of a circuit, run by a [Pipeline].
+///
+/// Stages are ordered: each one reads the artifacts written by the previous
+/// ones, and the witness stage reads the `input.json` file written by
+/// [circom_prove](crate::circom_prove).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Compile `verifier.circom`, see [circom_compile].
+    Compile,
+
+    /// Generate the circuit-specific keys, see [groth16_setup].
+    Setup,
+
+    /// Compute the execution witness, see [compute_witness].
+    Witness,
+
+    /// Generate the Groth16 proof, see [groth16_prove].
+    Prove,
+
+    /// Verify the Groth16 proof, see [groth16_verify].
+    Verify,
+}
+
+impl Stage {
+    /// All stages, in order.
+    pub const ALL: [Stage; 5] = [
+        Stage::Compile,
+        Stage::Setup,
+        Stage::Witness,
+        Stage::Prove,
+        Stage::Verify,
+    ];
+
+    /// Run the stage for the given circuit.
+    pub fn run(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        run_stage(*self, circuit_name, &logging_level)
+    }
+
+    /// Returns whether the outputs of the stage exist and were written from
+    /// its current inputs, so that running it again would not change them.
+    ///
+    /// The inputs are compared by content: the hash of `verifier.circom`, and
+    /// the one of `verifier.r1cs` and of the powers of tau transcript, are
+    /// recorded in the [CircuitManifest] when the compile and setup stages
+    /// complete. Copying a project or checking it out from git does not make
+    /// the stages outdated, while replacing the transcript does, whatever its
+    /// modification time. The stages of circuits without manifest, or compiled
+    /// by earlier versions of this crate, are never up to date.
+    ///
+    /// Only the compile and setup stages can be up to date: the other ones
+    /// depend on the Winterfell proof, which changes on every run.
+    pub fn is_up_to_date(&self, circuit_name: &str) -> bool {
+        let dir = circuit_dir(circuit_name);
+        let outputs = match self {
+            Stage::Compile => vec![
+                dir.join("verifier.r1cs"),
+                dir.join("verifier.sym"),
+                dir.join("verifier_js").join("verifier.wasm"),
+            ],
+            Stage::Setup => vec![dir.join("verifier.zkey"), dir.join("verification_key.json")],
+            Stage::Witness | Stage::Prove | Stage::Verify => return false,
+        };
+        if !outputs.iter().all(|path| path.exists()) {
+            return false;
+        }
+
+        let recorded = match CircuitManifest::read(circuit_name) {
+            Ok(Some(manifest)) if *self == Stage::Compile => manifest.compiled_from,
+            Ok(Some(manifest)) => manifest.keys_generated_from,
+            _ => None,
+        };
+        recorded.is_some() && recorded == self.inputs_hash(circuit_name)
+    }
+
+    /// Returns the hash of the current inputs of the compile and setup stages
+    /// (see [is_up_to_date](Stage::is_up_to_date)), or `None` for the other
+    /// stages or if an input is missing.
+    fn inputs_hash(&self, circuit_name: &str) -> Option<String> {
+        let dir = circuit_dir(circuit_name);
+        let inputs = match self {
+            Stage::Compile => vec![dir.join("verifier.circom")],
+            Stage::Setup => vec![
+                dir.join("verifier.r1cs"),
+                CircomWorkspace::current().ptau().to_path_buf(),
+            ],
+            Stage::Witness | Stage::Prove | Stage::Verify => return None,
+        };
+        let hashes = inputs
+            .iter()
+            .map(|path| hash_file(path).ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some(hash_artifact(hashes.join("\n").as_bytes()))
+    }
+
+    /// Record the hash of the current inputs of the stage in the manifest of
+    /// the circuit, once it completed.
+    fn record_inputs_hash(&self, mut manifest: CircuitManifest) -> Result<(), WinterCircomError> {
+        let hash = self.inputs_hash(&manifest.circuit_name);
+        match self {
+            Stage::Compile => manifest.compiled_from = hash,
+            Stage::Setup => manifest.keys_generated_from = hash,
+            Stage::Witness | Stage::Prove | Stage::Verify => return Ok(()),
+        }
+        manifest.write()
+    }
+}
+
+/// Chain of [Stage]s of the Groth16 pipeline of a circuit.
+///
+/// The stages are run in order, whatever the order they are added in. Stages
+/// can be reused independently, e.g. to only compute the witness and the
+/// Groth16 proof of a new Winterfell proof:
+///
+/// ```ignore
+/// circom_prove(prover, trace, "sum", LoggingLevel::Default)?;
+/// Pipeline::new("sum").witness().prove().run(LoggingLevel::Default)?;
+/// ```
+///
+/// [full](Pipeline::full) pipelines skip the stages which are
+/// [up to date](Stage::is_up_to_date), so that the circuit is only compiled and
+/// its keys only generated when `verifier.circom` or the powers of tau
+/// transcript have changed. A stage is never skipped once a previous stage has
+/// run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pipeline {
+    circuit_name: String,
+    stages: BTreeSet<Stage>,
+    skip_up_to_date: bool,
+}
+
+impl Pipeline {
+    /// Returns a pipeline of the given circuit without any stage.
+    pub fn new(circuit_name: &str) -> Self {
+        Self {
+            circuit_name: String::from(circuit_name),
+            stages: BTreeSet::new(),
+            skip_up_to_date: false,
+        }
+    }
+
+    /// Returns a pipeline of the given circuit with all the stages, skipping
+    /// the up to date ones.
+    pub fn full(circuit_name: &str) -> Self {
+        Stage::ALL
+            .into_iter()
+            .fold(Self::new(circuit_name), Self::with_stage)
+            .skip_up_to_date(true)
+    }
+
+    pub fn with_stage(mut self, stage: Stage) -> Self {
+        self.stages.insert(stage);
+        self
+    }
+
+    pub fn compile(self) -> Self {
+        self.with_stage(Stage::Compile)
+    }
+
+    pub fn setup(self) -> Self {
+        self.with_stage(Stage::Setup)
+    }
+
+    pub fn witness(self) -> Self {
+        self.with_stage(Stage::Witness)
+    }
+
+    pub fn prove(self) -> Self {
+        self.with_stage(Stage::Prove)
+    }
+
+    pub fn verify(self) -> Self {
+        self.with_stage(Stage::Verify)
+    }
+
+    /// Skip the stages which are [up to date](Stage::is_up_to_date).
+    pub fn skip_up_to_date(self, skip_up_to_date: bool) -> Self {
+        Self {
+            skip_up_to_date,
+            ..self
+        }
+    }
+
+    /// Returns the stages of the pipeline, in order.
+    pub fn stages(&self) -> Vec<Stage> {
+        self.stages.iter().copied().collect()
+    }
+
+    /// Run the stages of the pipeline, stopping at the first error.
+    ///
+    /// Returns the stages that were run, without the skipped ones.
+    pub fn run(&self, logging_level: LoggingLevel) -> Result<Vec<Stage>, WinterCircomError> {
+        let mut run = Vec::new();
+        for stage in self.stages.iter() {
+            if self.skips(stage, &run, &logging_level) {
+                continue;
+            }
+            stage.run(&self.circuit_name, logging_level)?;
+            run.push(*stage);
+        }
+        Ok(run)
+    }
+
+    pub(crate) fn circuit_name(&self) -> &str {
+        &self.circuit_name
+    }
+
+    /// Returns whether a stage is skipped, once the given stages have run.
+    pub(crate) fn skips(&self, stage: &Stage, run: &[Stage], logging_level: &LoggingLevel) -> bool {
+        let skipped =
+            self.skip_up_to_date && run.is_empty() && stage.is_up_to_date(&self.circuit_name);
+        if skipped && logging_level.print_big_steps() {
+            println!(
+                "{}",
+                format!("Skipping up to date {:?} stage.", stage).green()
+            );
+        }
+        skipped
+    }
+}
+
+/// Compile the Circom code of a circuit generated by
+/// [circom_create](crate::circom_create).
+///
+/// `verifier.circom` is compiled into `verifier.r1cs`, `verifier.sym` and the
+/// `verifier_js/` witness generator, in the directory of the circuit. The
+/// transition degrees compiled into the constraints are then checked against
+/// the manifest, see [transition_degrees](crate::transition_degrees).
+///
+/// The compilation of large circuits can last several minutes.
+pub fn circom_compile(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    Stage::Compile.run(circuit_name, logging_level)
+}
+
+/// Generate the circuit-specific keys of a circuit compiled by
+/// [circom_compile], from the powers of tau phase 1 transcript.
+///
+/// A contribution with random entropy is added to the keys generated from the
+/// transcript, written to `verifier.zkey`, and the matching
+/// `verification_key.json` file is exported. On a [Prover](HostRole::Prover)
+/// host, the verification key is not exported.
+///
+/// The generation of the keys can last several minutes.
+pub fn groth16_setup(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    Stage::Setup.run(circuit_name, logging_level)
+}
+
+/// Compute the execution witness of a circuit from the `input.json` file
+/// written by [circom_prove](crate::circom_prove).
+///
+/// The witness is written to `witness.wtns`, next to `input.json` in the
+/// [ScratchSpace].
+pub fn compute_witness(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    Stage::Witness.run(circuit_name, logging_level)
+}
+
+/// Compute the execution witness of a circuit from input signals held in
+/// memory, e.g. the [input](crate::CircuitInputs::input) returned by
+/// [circom_prove_in_memory](crate::circom_prove_in_memory).
+///
+/// The input is piped to the witness generator on Unix platforms, so that
+/// `input.json` is neither written nor read, and is written to the
+/// [ScratchSpace] for [compute_witness] on the other ones. The witness is
+/// written to `witness.wtns` in the scratch space, as by [compute_witness].
+pub fn compute_witness_from_input(
+    circuit_name: &str,
+    input: &Value,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let input = input.to_string().into_bytes();
+    let scratch = ScratchSpace::from_env()?;
+    if STDIN_PATH.is_none() {
+        scratch.write(circuit_name, "input.json", &input)?;
+        return compute_witness(circuit_name, logging_level);
+    }
+
+    let scratch_dir = scratch.circuit_dir(circuit_name);
+    fs::create_dir_all(&scratch_dir).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("creating {}", scratch_dir.display())),
+    })?;
+    // there is no input file to record, its hash is recorded instead
+    let parameters = json!({ "input": hash_artifact(&input) });
+    audited(circuit_name, AuditAction::Witness, parameters, &[], || {
+        let result = witness_plan(circuit_name, Some(input))?.run(&logging_level);
+        Stage::Witness.complete(circuit_name, result, &logging_level)
+    })
+}
+
+/// Generate the Groth16 proof of a circuit from the witness computed by
+/// [compute_witness] and the keys generated by [groth16_setup].
+///
+/// The proof and its public signals are written to `proof.json` and
+/// `public.json`, in the directory of the circuit.
+pub fn groth16_prove(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    Stage::Prove.run(circuit_name, logging_level)
+}
+
+/// Verify the Groth16 proof of a circuit generated by [groth16_prove].
+///
+/// This is [circom_verify](crate::circom_verify), as the last stage of a
+/// [Pipeline].
+pub fn groth16_verify(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    Stage::Verify.run(circuit_name, logging_level)
+}
+
+/// External commands of a [Stage], run in order in the directory of the
+/// circuit.
+pub(crate) struct StagePlan {
+    /// Message printed when the stage starts.
+    pub(crate) message: &'static str,
+    pub(crate) dir: PathBuf,
+    pub(crate) commands: Vec<(Executable, Vec<String>)>,
+
+    /// Data piped to the standard input of the commands, which read it at
+    /// [STDIN_PATH], if any.
+    pub(crate) stdin: Option<Vec<u8>>,
+
+    /// Artifacts written by the commands, printed with the timings.
+    pub(crate) outputs: Vec<PathBuf>,
+}
+
+impl Stage {
+    /// Returns the audit action of the stage and the artifacts it records.
+    pub(crate) fn audit(&self, circuit_name: &str) -> (AuditAction, Vec<PathBuf>) {
+        let dir = circuit_dir(circuit_name);
+        match self {
+            Stage::Compile => (
+                AuditAction::Compile,
+                vec![
+                    dir.join("verifier.circom"),
+                    dir.join("verifier.r1cs"),
+                    dir.join("verifier.sym"),
+                ],
+            ),
+            Stage::Setup => (
+                AuditAction::Setup,
+                vec![
+                    dir.join("verifier.r1cs"),
+                    dir.join("verifier.zkey"),
+                    dir.join("verification_key.json"),
+                ],
+            ),
+            Stage::Witness => (
+                AuditAction::Witness,
+                vec![scratch_dir(circuit_name).join("input.json")],
+            ),
+            Stage::Prove => (
+                AuditAction::Groth16Prove,
+                vec![
+                    dir.join("verifier.zkey"),
+                    dir.join("proof.json"),
+                    dir.join("public.json"),
+                ],
+            ),
+            Stage::Verify => (
+                AuditAction::Verify,
+                vec![
+                    dir.join("verification_key.json"),
+                    dir.join("public.json"),
+                    dir.join("proof.json"),
+                ],
+            ),
+        }
+    }
+
+    /// Returns the commands of the stage, once its inputs are checked.
+    pub(crate) fn plan(&self, circuit_name: &str) -> Result<StagePlan, WinterCircomError> {
+        self.check_inputs(circuit_name)?;
+        self.commands(
+            circuit_name,
+            SnarkScheme::of_circuit(circuit_name)?,
+            &random_entropy(),
+        )
+    }
+
+    /// Check that the [inputs](Stage::inputs) of the stage exist.
+    pub(crate) fn check_inputs(&self, circuit_name: &str) -> Result<(), WinterCircomError> {
+        for (path, comment) in self.inputs(circuit_name) {
+            check_file(path.to_string_lossy().into_owned(), Some(comment))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the input files of the stage which are not written by the
+    /// previous stages, with the hint printed when one is missing.
+    pub(crate) fn inputs(&self, circuit_name: &str) -> Vec<(PathBuf, &'static str)> {
+        let dir = circuit_dir(circuit_name);
+        let scratch_dir = scratch_dir(circuit_name);
+        match self {
+            Stage::Compile => vec![(dir.join("verifier.circom"), "did you run circom_create?")],
+            Stage::Setup => vec![(
+                CircomWorkspace::current().ptau().to_path_buf(),
+                "required for the generation of circuit-specific keys",
+            )],
+            Stage::Witness => vec![(scratch_dir.join("input.json"), "did you run circom_prove?")],
+            Stage::Prove => vec![
+                (
+                    scratch_dir.join("witness.wtns"),
+                    "did you compute the witness?",
+                ),
+                (
+                    dir.join("verifier.zkey"),
+                    "did you generate the circuit-specific keys?",
+                ),
+            ],
+            Stage::Verify => ["verification_key.json", "public.json", "proof.json"]
+                .iter()
+                .map(|file| (dir.join(file), "needed for verification"))
+                .collect(),
+        }
+    }
+
+    /// Returns the files written by the stage.
+    pub(crate) fn outputs(&self, circuit_name: &str) -> Result<Vec<PathBuf>, WinterCircomError> {
+        let dir = circuit_dir(circuit_name);
+        Ok(match self {
+            Stage::Compile => vec![
+                dir.join("verifier.r1cs"),
+                dir.join("verifier.sym"),
+                dir.join("verifier_js").join("verifier.wasm"),
+            ],
+            Stage::Setup => {
+                let mut outputs = vec![dir.join("verifier.zkey")];
+                if HostRole::from_env()?.needs("verification_key.json") {
+                    outputs.push(dir.join("verification_key.json"));
+                }
+                outputs
+            }
+            Stage::Witness => vec![scratch_dir(circuit_name).join("witness.wtns")],
+            Stage::Prove => vec![dir.join("proof.json"), dir.join("public.json")],
+            Stage::Verify => vec![],
+        })
+    }
+
+    /// Returns the commands of the stage for the given scheme, without checking
+    /// its inputs. The contribution to the circuit-specific keys is made with
+    /// the given entropy.
+    pub(crate) fn commands(
+        &self,
+        circuit_name: &str,
+        scheme: SnarkScheme,
+        entropy: &str,
+    ) -> Result<StagePlan, WinterCircomError> {
+        let dir = circuit_dir(circuit_name);
+
+        let (message, commands, outputs) = match self {
+            Stage::Compile => (
+                "Compiling Circom code...",
+                vec![(
+                    Executable::Circom,
+                    ["verifier.circom", "--r1cs", "--wasm", "--sym"]
+                        .iter()
+                        .map(|arg| String::from(*arg))
+                        .collect(),
+                )],
+                vec![dir.join("verifier.r1cs")],
+            ),
+            Stage::Setup => {
+                let mut plan = keys_plan(circuit_name, scheme, entropy)?;
+                if HostRole::from_env()?.needs("verification_key.json") {
+                    plan.commands.extend(export_vk_plan(circuit_name).commands);
+                }
+                (plan.message, plan.commands, plan.outputs)
+            }
+            Stage::Witness => {
+                let plan = witness_plan(circuit_name, None)?;
+                (plan.message, plan.commands, plan.outputs)
+            }
+            // the witness and the keys are read at random offsets, and cannot
+            // be piped
+            Stage::Prove => {
+                let witness = absolute(&scratch_dir(circuit_name).join("witness.wtns"))?;
+                let (message, command) =
+                    ProverBackend::from_env()?.prove_command(scheme, &witness.to_string_lossy())?;
+                (
+                    message,
+                    vec![command],
+                    vec![dir.join("proof.json"), dir.join("public.json")],
+                )
+            }
+            Stage::Verify => (
+                match scheme {
+                    SnarkScheme::Groth16 => "Verifying Groth16 proof...",
+                    SnarkScheme::Plonk => "Verifying PLONK proof...",
+                    SnarkScheme::Fflonk => "Verifying FFLONK proof...",
+                },
+                vec![snarkjs(&[
+                    scheme.verify_command(),
+                    "verification_key.json",
+                    "public.json",
+                    "proof.json",
+                ])],
+                vec![],
+            ),
+        };
+
+        Ok(StagePlan {
+            message,
+            dir,
+            commands,
+            stdin: None,
+            outputs,
+        })
+    }
+
+    /// Complete the stage once its commands ran, or one of them failed.
+    ///
+    /// The artifacts of a proof are uploaded by the
+    /// [ArtifactUpload](crate::ArtifactUpload) of the environment, if any, once
+    /// the prove stage succeeds.
+    pub(crate) fn complete(
+        &self,
+        circuit_name: &str,
+        result: Result<(), WinterCircomError>,
+        logging_level: &LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        match self {
+            Stage::Compile => {
+                result?;
+                match CircuitManifest::read(circuit_name)? {
+                    Some(manifest) => {
+                        check_transition_degrees(&manifest, logging_level)?;
+                        self.record_inputs_hash(manifest)
+                    }
+                    None => Ok(()),
+                }
+            }
+            Stage::Setup => {
+                let keys = circuit_dir(circuit_name).join("verifier_0.zkey");
+                delete_file(keys.to_string_lossy().into_owned());
+                result?;
+                match CircuitManifest::read(circuit_name)? {
+                    Some(manifest) => self.record_inputs_hash(manifest),
+                    None => Ok(()),
+                }
+            }
+            Stage::Prove => {
+                result?;
+                upload_proof(circuit_name, logging_level)
+            }
+            Stage::Witness | Stage::Verify => result,
+        }
+    }
+}
+
+impl StagePlan {
+    /// Run the commands of the plan in order, stopping at the first error.
+    pub(crate) fn run(self, logging_level: &LoggingLevel) -> Result<(), WinterCircomError> {
+        let timer = StageTimer::start(self.message, logging_level);
+        for (executable, args) in self.commands {
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            match &self.stdin {
+                Some(stdin) => command_execution_with_stdin(
+                    executable,
+                    &args,
+                    stdin,
+                    self.dir.to_str(),
+                    logging_level,
+                )?,
+                None => command_execution(executable, &args, self.dir.to_str(), logging_level)?,
+            }
+        }
+        timer.finish(&self.outputs);
+        Ok(())
+    }
+}
+
+/// Returns the snarkjs command of the [Witness](Stage::Witness) stage, reading
+/// the given input from its standard input (see [STDIN_PATH]), or the
+/// `input.json` file of the [ScratchSpace] if there is none.
+///
+/// Only the input can be piped: the witness is written at random offsets,
+/// which a pipe does not support.
+fn witness_plan(
+    circuit_name: &str,
+    input: Option<Vec<u8>>,
+) -> Result<StagePlan, WinterCircomError> {
+    let scratch_dir = absolute(&scratch_dir(circuit_name))?;
+    let input_path = match (&input, STDIN_PATH) {
+        (Some(_), Some(stdin_path)) => String::from(stdin_path),
+        _ => scratch_dir
+            .join("input.json")
+            .to_string_lossy()
+            .into_owned(),
+    };
+    Ok(StagePlan {
+        message: "Computing execution witness...",
+        dir: circuit_dir(circuit_name),
+        commands: vec![snarkjs(&[
+            "wc",
+            "verifier_js/verifier.wasm",
+            &input_path,
+            &scratch_dir.join("witness.wtns").to_string_lossy(),
+        ])],
+        stdin: input.filter(|_| STDIN_PATH.is_some()),
+        outputs: vec![scratch_dir.join("witness.wtns")],
+    })
+}
+
+/// Returns the snarkjs commands generating the circuit-specific keys of the
+/// [Setup](Stage::Setup) stage, without the export of the verification key.
+pub(crate) fn keys_plan(
+    circuit_name: &str,
+    scheme: SnarkScheme,
+    entropy: &str,
+) -> Result<StagePlan, WinterCircomError> {
+    let dir = circuit_dir(circuit_name);
+    let ptau = absolute(CircomWorkspace::current().ptau())?;
+
+    // PLONK and FFLONK keys do not receive any circuit-specific contribution
+    let commands = if scheme.has_circuit_specific_setup() {
+        vec![
+            snarkjs(&[
+                scheme.setup_command(),
+                "verifier.r1cs",
+                &ptau.to_string_lossy(),
+                "verifier_0.zkey",
+            ]),
+            snarkjs(&[
+                "zkc",
+                "verifier_0.zkey",
+                "verifier.zkey",
+                "--name=setup",
+                &format!("-e={}", entropy),
+            ]),
+        ]
+    } else {
+        vec![snarkjs(&[
+            scheme.setup_command(),
+            "verifier.r1cs",
+            &ptau.to_string_lossy(),
+            "verifier.zkey",
+        ])]
+    };
+    Ok(StagePlan {
+        message: "Generating circuit-specific keys...",
+        outputs: vec![dir.join("verifier.zkey")],
+        dir,
+        commands,
+        stdin: None,
+    })
+}
+
+/// Returns the snarkjs command exporting `verification_key.json` from the
+/// `verifier.zkey` generated by [keys_plan].
+pub(crate) fn export_vk_plan(circuit_name: &str) -> StagePlan {
+    let dir = circuit_dir(circuit_name);
+    StagePlan {
+        message: "Exporting verification key...",
+        outputs: vec![dir.join("verification_key.json")],
+        dir,
+        commands: vec![snarkjs(&["zkev", "verifier.zkey", "verification_key.json"])],
+        stdin: None,
+    }
+}
+
+/// Run a stage, see [Stage::run].
+///
+/// The setup, prove and verify stages are run by the
+/// [SnarkBackend](crate::SnarkBackend) of the current thread.
+pub(crate) fn run_stage(
+    stage: Stage,
+    circuit_name: &str,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let (action, artifacts) = stage.audit(circuit_name);
+    let backend = current_snark_backend();
+    let parameters = match stage {
+        Stage::Compile | Stage::Witness => json!({}),
+        Stage::Setup | Stage::Prove | Stage::Verify => json!({ "backend": backend.name() }),
+    };
+    audited(circuit_name, action, parameters, &artifacts, || {
+        let result = match stage {
+            Stage::Compile | Stage::Witness => stage.plan(circuit_name)?.run(logging_level),
+            Stage::Setup => backend.setup(circuit_name, *logging_level).and_then(|_| {
+                if HostRole::from_env()?.needs("verification_key.json") {
+                    backend.export_vk(circuit_name, *logging_level)
+                } else {
+                    Ok(())
+                }
+            }),
+            Stage::Prove => backend.prove(circuit_name, *logging_level),
+            Stage::Verify => backend.verify(circuit_name, *logging_level),
+        };
+        stage.complete(circuit_name, result, logging_level)
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn snarkjs(args: &[&str]) -> (Executable, Vec<String>) {
+    (
+        Executable::SnarkJS,
+        args.iter().map(|arg| String::from(*arg)).collect(),
+    )
+}
+
+/// Upload `public.json` and `proof.json` with the [ArtifactUpload] of the
+/// environment, if any.
+#[cfg(feature = "service")]
+fn upload_proof(circuit_name: &str, logging_level: &LoggingLevel) -> Result<(), WinterCircomError> {
+    match ArtifactUpload::from_env()? {
+        Some(upload) => ["public.json", "proof.json"].iter().try_for_each(|file| {
+            upload.upload(
+                circuit_name,
+                &circuit_dir(circuit_name).join(file),
+                logging_level,
+            )
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "service"))]
+fn upload_proof(
+    _circuit_name: &str,
+    _logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    Ok(())
+}
+
+/// Returns the absolute path of a path relative to the current directory, as
+/// the external commands run in the directory of the circuit.
+fn absolute(path: &Path) -> Result<PathBuf, WinterCircomError> {
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    env::current_dir()
+        .map(|dir| dir.join(path))
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("reading the current directory")),
+        })
+}
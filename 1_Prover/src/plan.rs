@@ -0,0 +1,260 @@
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::{
+    audit::{history, AuditAction, AuditEntry},
+    codegen::circuit_manifest,
+    pipeline::Pipeline,
+    tenant::validate_circuit_name,
+    utils::WinterCircomError,
+    workspace::circuit_dir,
+    CircuitManifest, WinterCircomProofOptions,
+};
+
+/// Steps that would be run to generate, compile, set up and prove a circuit,
+/// returned by [plan].
+///
+/// A plan is a dry run: nothing is written, and no external command is
+/// spawned. It is displayed as a list of steps, each with its commands and the
+/// files it writes:
+///
+/// ```text
+/// Plan of circuit sum: 4 steps, about 3h 12m.
+///
+/// + create (about 2s)
+///     + target/circom/sum/verifier.circom
+///     ~ target/circom/sum/manifest.json
+/// + compile (about 25m 3s)
+///     $ circom verifier.circom --r1cs --wasm --sym
+///     ...
+/// ```
+///
+/// where `+` files are created and `~` files overwritten.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PipelinePlan {
+    pub circuit_name: String,
+
+    /// Steps that would be run, in order.
+    pub steps: Vec<PlannedStep>,
+
+    /// Steps that would be skipped, as their outputs are up to date.
+    pub up_to_date: Vec<AuditAction>,
+}
+
+/// Step of a [PipelinePlan].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlannedStep {
+    pub action: AuditAction,
+
+    /// Command lines of the external commands, run in `dir`. The step of
+    /// [circom_create](crate::circom_create) does not run any.
+    pub commands: Vec<String>,
+    pub dir: PathBuf,
+    pub files: Vec<PlannedFile>,
+
+    /// Duration of the last successful run of the step for this circuit,
+    /// recorded in its audit log (see [history]), or `None` if it never ran.
+    pub estimated_duration_ms: Option<u64>,
+}
+
+/// File written by a [PlannedStep].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+
+    /// Whether the file already exists.
+    pub overwritten: bool,
+}
+
+impl PipelinePlan {
+    /// Returns the estimated duration of the plan, or `None` if a step never
+    /// ran for this circuit.
+    pub fn estimated_duration(&self) -> Option<Duration> {
+        self.steps
+            .iter()
+            .map(|step| step.estimated_duration_ms)
+            .sum::<Option<u64>>()
+            .map(Duration::from_millis)
+    }
+
+    /// Returns whether every step is up to date.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl Display for PipelinePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Plan of circuit {}: {} steps",
+            self.circuit_name,
+            self.steps.len()
+        )?;
+        match self.estimated_duration() {
+            Some(duration) if !self.is_empty() => {
+                writeln!(f, ", about {}.", format_duration(duration))?
+            }
+            _ => writeln!(f, ".")?,
+        }
+        for action in self.up_to_date.iter() {
+            write!(f, "\n= {} (up to date)", action_name(action))?;
+        }
+        if !self.up_to_date.is_empty() {
+            writeln!(f)?;
+        }
+
+        for step in self.steps.iter() {
+            write!(f, "\n+ {}", action_name(&step.action))?;
+            if let Some(ms) = step.estimated_duration_ms {
+                write!(f, " (about {})", format_duration(Duration::from_millis(ms)))?;
+            }
+            for command in step.commands.iter() {
+                write!(f, "\n    $ {}", command)?;
+            }
+            for file in step.files.iter() {
+                let sign = if file.overwritten { '~' } else { '+' };
+                write!(f, "\n    {} {}", sign, file.path.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the steps that would be run to generate a circuit with the given
+/// options, compile it, set it up, and prove and verify the output of
+/// [circom_prove](crate::circom_prove), without running them.
+///
+/// The Circom code is generated again unless the manifest of the circuit
+/// records the same options and the sources are unchanged. The compilation and
+/// the key generation are then skipped when they are up to date, i.e. when
+/// their inputs have the same contents as when they last ran (see
+/// [is_up_to_date](crate::Stage::is_up_to_date)), as in a
+/// [full](Pipeline::full) pipeline. The contribution entropy of the key
+/// generation is random, and redacted from the planned commands.
+///
+/// This is meant to be reviewed before running the steps, which can last
+/// hours for large circuits.
+pub fn plan<const N: usize>(
+    circuit_name: &str,
+    options: &WinterCircomProofOptions<N>,
+) -> Result<PipelinePlan, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let history = history(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+
+    let mut steps = Vec::new();
+    let mut up_to_date = Vec::new();
+    if is_generated(circuit_name, options)? {
+        up_to_date.push(AuditAction::Create);
+    } else {
+        steps.push(PlannedStep {
+            action: AuditAction::Create,
+            commands: Vec::new(),
+            dir: dir.clone(),
+            files: planned_files(vec![
+                dir.join("verifier.circom"),
+                dir.join("manifest.json"),
+                dir.join("parameters.md"),
+                dir.join("parameters.json"),
+            ]),
+            estimated_duration_ms: estimated_duration_ms(&history, AuditAction::Create),
+        });
+    }
+
+    // a stage is never skipped once a previous step has run
+    let mut skipping = steps.is_empty();
+    for stage in Pipeline::full(circuit_name).stages() {
+        let (action, _) = stage.audit(circuit_name);
+        if skipping && stage.is_up_to_date(circuit_name) {
+            up_to_date.push(action);
+            continue;
+        }
+        skipping = false;
+        let stage_plan = stage.commands(circuit_name, options.snark_scheme_used(), "<random>")?;
+        steps.push(PlannedStep {
+            action,
+            commands: stage_plan
+                .commands
+                .iter()
+                .map(|(executable, args)| {
+                    let mut command = vec![executable.executable_name()];
+                    command.extend(args.iter().cloned());
+                    command.join(" ")
+                })
+                .collect(),
+            dir: stage_plan.dir,
+            files: planned_files(stage.outputs(circuit_name)?),
+            estimated_duration_ms: estimated_duration_ms(&history, action),
+        });
+    }
+
+    Ok(PipelinePlan {
+        circuit_name: String::from(circuit_name),
+        steps,
+        up_to_date,
+    })
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns whether the Circom code of a circuit was generated with the given
+/// options, from the current sources.
+fn is_generated<const N: usize>(
+    circuit_name: &str,
+    options: &WinterCircomProofOptions<N>,
+) -> Result<bool, WinterCircomError> {
+    let manifest = match CircuitManifest::read(circuit_name)? {
+        Some(manifest) if manifest.source_fingerprint.is_some() => manifest,
+        _ => return Ok(false),
+    };
+    let expected = CircuitManifest {
+        source_fingerprint: manifest.source_fingerprint.clone(),
+        inner_proof_verified: manifest.inner_proof_verified,
+        compiled_from: manifest.compiled_from.clone(),
+        keys_generated_from: manifest.keys_generated_from.clone(),
+        ..circuit_manifest(options, circuit_name)
+    };
+    Ok(manifest == expected && !manifest.sources_changed())
+}
+
+fn planned_files(paths: Vec<PathBuf>) -> Vec<PlannedFile> {
+    paths
+        .into_iter()
+        .map(|path| PlannedFile {
+            overwritten: path.exists(),
+            path,
+        })
+        .collect()
+}
+
+fn estimated_duration_ms(history: &[AuditEntry], action: AuditAction) -> Option<u64> {
+    history
+        .iter()
+        .rev()
+        .find(|entry| entry.action == action && entry.is_success())
+        .map(|entry| entry.duration_ms)
+}
+
+fn action_name(action: &AuditAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_else(|| format!("{:?}", action))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, 0) => format!("{}ms", duration.as_millis()),
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
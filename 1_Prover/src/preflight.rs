@@ -0,0 +1,266 @@
+use std::{fs, path::Path};
+
+use colored::Colorize;
+
+use crate::{
+    scratch::{scratch_root, ScratchSpace},
+    utils::{command_output, strip_colors, Executable},
+    workspace::CircomWorkspace,
+    ProverBackend,
+};
+
+/// Outcome of a single [preflight] check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+
+    /// The check could not be performed on this host (e.g. the free disk space
+    /// on a platform without `df`). Warnings do not prevent readiness.
+    Warn,
+
+    Fail,
+}
+
+/// Result of a single [preflight] check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Readiness report of a proving host, returned by [preflight].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// Returns whether no check failed.
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+
+    /// Returns the failed checks.
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks.iter().filter(|c| c.status == CheckStatus::Fail)
+    }
+
+    /// Print the report to stdout, one line per check.
+    pub fn print(&self) {
+        for check in self.checks.iter() {
+            let line = format!("{}: {}", check.name, check.detail);
+            match check.status {
+                CheckStatus::Pass => println!("{} {}", "[ok]  ".green(), line),
+                CheckStatus::Warn => println!("{} {}", "[warn]".yellow(), line),
+                CheckStatus::Fail => println!("{} {}", "[fail]".red(), line),
+            }
+        }
+    }
+
+    fn push(&mut self, name: &'static str, status: CheckStatus, detail: String) {
+        self.checks.push(PreflightCheck {
+            name,
+            status,
+            detail,
+        });
+    }
+}
+
+/// Minimum resources required by the proving jobs of a host.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreflightRequirements {
+    /// Estimated disk usage of a proving job, in bytes.
+    pub disk_bytes: Option<u64>,
+
+    /// Estimated memory usage of a proving job, in bytes.
+    pub memory_bytes: Option<u64>,
+}
+
+/// Check that the host is ready to accept proving jobs.
+///
+/// This function is intended to be run at service startup. It checks:
+///
+/// - the availability and versions of `circom`, `snarkjs` and `node`, and the
+/// presence of the rapidsnark prover if it is the [ProverBackend],
+/// - the presence of the `final.ptau` powers of tau transcript,
+/// - the free disk space of the `target/circom` directory (see
+/// [CircomWorkspace](crate::CircomWorkspace)) and of the
+/// [ScratchSpace] against [disk_bytes](PreflightRequirements::disk_bytes),
+/// - the available memory against
+/// [memory_bytes](PreflightRequirements::memory_bytes),
+/// - the write permissions of the output directories.
+///
+/// No check returns an error: failures are reported in the returned
+/// [PreflightReport].
+pub fn preflight(requirements: PreflightRequirements) -> PreflightReport {
+    let mut report = PreflightReport::default();
+
+    // TOOLS
+    // ===========================================================================
+
+    let tools: [(&'static str, Executable, &[&str]); 3] = [
+        ("circom", Executable::Circom, &["--version"]),
+        ("snarkjs", Executable::SnarkJS, &["--version"]),
+        ("node", Executable::Node, &["--version"]),
+    ];
+    for (name, executable, args) in tools {
+        match command_output(executable, args) {
+            Ok(version) => report.push(name, CheckStatus::Pass, first_line(&version)),
+            // snarkjs prints its version in the usage banner and exits with a
+            // non-zero code on unknown options
+            Err(_) if name == "snarkjs" && Path::new("iden3/snarkjs/build/cli.cjs").exists() => {
+                report.push(name, CheckStatus::Pass, String::from("installed"))
+            }
+            Err(e) => report.push(name, CheckStatus::Fail, strip_colors(&e.to_string())),
+        }
+    }
+    let backend = ProverBackend::from_env();
+    if let Err(e) = &backend {
+        report.push("backend", CheckStatus::Fail, strip_colors(&e.to_string()));
+    }
+    if matches!(backend, Ok(ProverBackend::Rapidsnark)) {
+        // the rapidsnark prover has no version option
+        let prover = Path::new("iden3/rapidsnark/package/bin/prover");
+        if prover.exists() {
+            report.push("rapidsnark", CheckStatus::Pass, String::from("installed"));
+        } else {
+            report.push(
+                "rapidsnark",
+                CheckStatus::Fail,
+                format!("{} not found", prover.display()),
+            );
+        }
+    }
+
+    // POWERS OF TAU
+    // ===========================================================================
+
+    let workspace = CircomWorkspace::current();
+    match fs::metadata(workspace.ptau()) {
+        Ok(metadata) => report.push(
+            "ptau",
+            CheckStatus::Pass,
+            format!("{} ({} bytes)", workspace.ptau().display(), metadata.len()),
+        ),
+        Err(_) => report.push(
+            "ptau",
+            CheckStatus::Fail,
+            format!("{} not found", workspace.ptau().display()),
+        ),
+    }
+
+    // DISK SPACE AND PERMISSIONS
+    // ===========================================================================
+
+    if let Err(e) = ScratchSpace::from_env() {
+        report.push("scratch", CheckStatus::Fail, strip_colors(&e.to_string()));
+    }
+    let mut dirs = vec![workspace.root_dir().to_path_buf()];
+    if scratch_root() != dirs[0] {
+        dirs.push(scratch_root());
+    }
+
+    for dir in dirs.iter() {
+        let probe = dir.join(".preflight");
+        let writable = fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&probe, b""))
+            .and_then(|_| fs::remove_file(&probe));
+        match writable {
+            Ok(_) => report.push(
+                "permissions",
+                CheckStatus::Pass,
+                format!("{} is writable", dir.to_string_lossy()),
+            ),
+            Err(e) => report.push(
+                "permissions",
+                CheckStatus::Fail,
+                format!("{} is not writable ({})", dir.to_string_lossy(), e),
+            ),
+        }
+
+        match (free_disk_bytes(dir), requirements.disk_bytes) {
+            (Some(free), Some(required)) if free < required => report.push(
+                "disk",
+                CheckStatus::Fail,
+                format!(
+                    "{} bytes free in {}, {} bytes required",
+                    free,
+                    dir.to_string_lossy(),
+                    required
+                ),
+            ),
+            (Some(free), _) => report.push(
+                "disk",
+                CheckStatus::Pass,
+                format!("{} bytes free in {}", free, dir.to_string_lossy()),
+            ),
+            (None, _) => report.push(
+                "disk",
+                CheckStatus::Warn,
+                format!("free space of {} unknown", dir.to_string_lossy()),
+            ),
+        }
+    }
+
+    // MEMORY
+    // ===========================================================================
+
+    match (available_memory_bytes(), requirements.memory_bytes) {
+        (Some(available), Some(required)) if available < required => report.push(
+            "memory",
+            CheckStatus::Fail,
+            format!("{} bytes available, {} bytes required", available, required),
+        ),
+        (Some(available), _) => report.push(
+            "memory",
+            CheckStatus::Pass,
+            format!("{} bytes available", available),
+        ),
+        (None, _) => report.push(
+            "memory",
+            CheckStatus::Warn,
+            String::from("available memory unknown"),
+        ),
+    }
+
+    report
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the free disk space of the file system of a directory, using the
+/// POSIX output format of `df`.
+fn free_disk_bytes(dir: &Path) -> Option<u64> {
+    let output = command_output(
+        Executable::Custom {
+            path: String::from("/bin/df"),
+            verbose_argument: None,
+        },
+        &["-Pk", dir.to_str()?],
+    )
+    .ok()?;
+
+    // the second line is "<fs> <blocks> <used> <available> <capacity> <mount>"
+    let available_kb: u64 = output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse()
+        .ok()?;
+    Some(available_kb * 1024)
+}
+
+/// Returns the available memory, read from `/proc/meminfo` on Linux.
+fn available_memory_bytes() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let available_kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(available_kb * 1024)
+}
+
+fn first_line(output: &str) -> String {
+    output.lines().next().unwrap_or("").trim().to_owned()
+}
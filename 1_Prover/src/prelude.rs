@@ -0,0 +1,56 @@
+//! Supported API of this crate.
+//!
+//! ```ignore
+//! use winter_circom_prover::prelude::*;
+//! ```
+//!
+//! The items of the prelude follow semantic versioning: they are only removed
+//! or changed in a breaking way by a new major version. The other public items
+//! are either documented in their own module (e.g. [gadgets](crate::gadgets),
+//! [trace](crate::trace)) or diagnostics whose shape follows the generated
+//! circuits, and may change with the code generator or the backend of the
+//! pipeline.
+
+// ERRORS AND LOGGING
+// ===========================================================================
+
+pub use crate::utils::{
+    print_json_result, ColorChoice, ErrorClass, LoggingLevel, WinterCircomError,
+};
+
+// OPTIONS AND TRAITS
+// ===========================================================================
+
+pub use crate::{
+    canonical_assertions, with_workspace, CircomWorkspace, CircuitManifest, CircuitParameters,
+    IntermediateVerification, OptimizationGoal, OptimizationReport, OptimizedProofOptions,
+    ProofOptionsOptimizer, SnarkScheme, WinterCircomProofOptions, WinterPublicInputs,
+};
+
+pub use crate::trace::TraceTableExt;
+
+// PIPELINE
+// ===========================================================================
+
+#[cfg(feature = "codegen")]
+pub use crate::{circom_create, scaffold, ScaffoldOptions};
+
+#[cfg(feature = "prove")]
+pub use crate::{
+    build_all, circom_compile, circom_prove, circom_prove_from_proof, circom_prove_with_data,
+    circom_verify, compute_witness, groth16_prove, groth16_setup, groth16_verify, plan,
+    CheckedProver, CircuitBuild, CircuitRegistry, Pipeline, PipelinePlan, PlannedFile, PlannedStep,
+    ProjectCircuit, ProjectManifest, ProveArtifacts, ProveTimings, Stage,
+};
+
+#[cfg(feature = "service")]
+pub use crate::{
+    circom_compile_async, compute_witness_async, groth16_prove_async, groth16_setup_async,
+    groth16_verify_async,
+};
+
+// VERIFICATION
+// ===========================================================================
+
+#[cfg(feature = "verify")]
+pub use crate::{check_ood_frame, check_public_commitment, exposed_public_inputs};
@@ -0,0 +1,428 @@
+use std::{
+    any::Any,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+use crate::{
+    circom_create,
+    pipeline::{Pipeline, Stage},
+    utils::{LoggingLevel, WinterCircomError},
+    with_workspace, CircomWorkspace, CircuitManifest, CircuitParameters, SnarkScheme,
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Circuits of a project, listed in a `winter-circom.toml` file and built
+/// together by [build_all].
+///
+/// ```toml
+/// # transcript of the circuits which do not set their own
+/// ptau = "final.ptau"
+///
+/// [[circuits]]
+/// name = "sum"
+/// trace_length = 128
+/// trace_width = 2
+/// num_assertions = 3
+/// transition_constraint_degrees = [1, 1]
+/// num_queries = 42
+/// lde_blowup_factor = 8
+/// grinding_factor = 0
+/// fri_folding_factor = 8
+/// fri_max_remainder_size = 256
+///
+/// [[circuits]]
+/// name = "merkle"
+/// ptau = "ptau/large.ptau"
+/// # ...
+/// ```
+///
+/// The paths are relative to the current directory, as the ones of a
+/// [CircomWorkspace]. The transcript defaults to the one of the workspace.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectManifest {
+    pub ptau: Option<PathBuf>,
+
+    #[serde(default)]
+    pub circuits: Vec<ProjectCircuit>,
+}
+
+/// Circuit of a [ProjectManifest], and the proof options it is generated for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectCircuit {
+    pub name: String,
+    pub trace_length: usize,
+    pub trace_width: usize,
+    pub num_assertions: usize,
+    pub transition_constraint_degrees: Vec<usize>,
+    pub num_queries: usize,
+    pub lde_blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub fri_folding_factor: usize,
+    pub fri_max_remainder_size: usize,
+
+    /// See [expose_public_inputs](WinterCircomProofOptions::expose_public_inputs).
+    #[serde(default)]
+    pub expose_public_inputs: bool,
+
+    /// See [commit_public_signals](WinterCircomProofOptions::commit_public_signals).
+    #[serde(default)]
+    pub commit_public_signals: bool,
+
+    /// See [snark_scheme](WinterCircomProofOptions::snark_scheme).
+    #[serde(default)]
+    pub snark_scheme: SnarkScheme,
+
+    /// Transcript of the circuit, instead of the one of the project.
+    pub ptau: Option<PathBuf>,
+}
+
+impl ProjectManifest {
+    /// Name of the manifest file, at the root of the project.
+    pub const FILE_NAME: &'static str = "winter-circom.toml";
+
+    /// Read a manifest file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let file = path.to_string_lossy().into_owned();
+        let data = fs::read_to_string(path).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("reading the project manifest {}", file)),
+        })?;
+        Self::parse(&data, &file)
+    }
+
+    /// Parse the contents of a manifest file.
+    ///
+    /// Returns an error if two circuits have the same name.
+    pub fn parse(data: &str, file: &str) -> Result<Self, WinterCircomError> {
+        let manifest: Self = toml::from_str(data).map_err(|e| invalid(file, e.to_string()))?;
+
+        let mut names = BTreeSet::new();
+        if let Some(circuit) = manifest
+            .circuits
+            .iter()
+            .find(|circuit| !names.insert(&circuit.name))
+        {
+            return Err(invalid(
+                file,
+                format!("circuit {} is listed twice", circuit.name),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    /// Returns the circuit of the given name.
+    pub fn circuit(&self, name: &str) -> Option<&ProjectCircuit> {
+        self.circuits.iter().find(|circuit| circuit.name == name)
+    }
+}
+
+impl ProjectCircuit {
+    /// Returns the parameters of the proofs of the circuit.
+    pub fn parameters(&self) -> CircuitParameters {
+        CircuitParameters {
+            trace_length: self.trace_length,
+            trace_width: self.trace_width,
+            num_transition_constraints: self.transition_constraint_degrees.len(),
+            num_assertions: self.num_assertions,
+            num_queries: self.num_queries,
+            lde_blowup_factor: self.lde_blowup_factor,
+            grinding_factor: self.grinding_factor,
+            fri_folding_factor: self.fri_folding_factor,
+            fri_max_remainder_size: self.fri_max_remainder_size,
+        }
+    }
+
+    /// Returns the proof options of the circuit, with `N` transition
+    /// constraints.
+    pub fn proof_options<const N: usize>(
+        &self,
+    ) -> Result<WinterCircomProofOptions<N>, WinterCircomError> {
+        let degrees: [usize; N] = self
+            .transition_constraint_degrees
+            .clone()
+            .try_into()
+            .map_err(|degrees: Vec<usize>| {
+                invalid(
+                    ProjectManifest::FILE_NAME,
+                    format!(
+                        "circuit {} has {} transition constraints, but its prover has {}",
+                        self.name,
+                        degrees.len(),
+                        N
+                    ),
+                )
+            })?;
+        let options = WinterCircomProofOptions::new(
+            self.trace_length,
+            self.trace_width,
+            self.num_assertions,
+            degrees,
+            self.num_queries,
+            self.lde_blowup_factor,
+            self.grinding_factor,
+            self.fri_folding_factor,
+            self.fri_max_remainder_size,
+        )
+        .snark_scheme(self.snark_scheme);
+        let options = if self.expose_public_inputs {
+            options.expose_public_inputs()
+        } else {
+            options
+        };
+        if self.commit_public_signals {
+            return Ok(options.commit_public_signals());
+        }
+        Ok(options)
+    }
+
+    /// Returns whether the Circom code of the circuit was generated with the
+    /// proof options of the manifest, from the current sources.
+    ///
+    /// Changes to the Rust AIR are not tracked: delete the directory of the
+    /// circuit to generate it again.
+    pub fn is_generated(&self) -> bool {
+        match CircuitManifest::read(&self.name) {
+            Ok(Some(manifest)) => {
+                manifest.circuit_parameters == Some(self.parameters())
+                    && manifest.transition_constraint_degrees.as_ref()
+                        == Some(&self.transition_constraint_degrees)
+                    && manifest.public_inputs_exposed.unwrap_or(false) == self.expose_public_inputs
+                    && manifest.public_signals_committed.unwrap_or(false)
+                        == self.commit_public_signals
+                    && manifest.snark_scheme == self.snark_scheme
+                    && manifest.source_fingerprint.is_some()
+                    && !manifest.sources_changed()
+            }
+            _ => false,
+        }
+    }
+}
+
+type CreateFn = Box<dyn Fn(&ProjectCircuit, LoggingLevel) -> Result<(), WinterCircomError>>;
+type PresetFn = Box<dyn Fn(LoggingLevel) -> Result<(), WinterCircomError>>;
+
+/// Provers of the circuits of a [ProjectManifest].
+///
+/// [circom_create] needs the Winterfell prover of a circuit, which cannot be
+/// named in the manifest:
+///
+/// ```ignore
+/// let registry = CircuitRegistry::new()
+///     .register::<WorkProver, 2>("sum")
+///     .register::<MerkleProver, 3>("merkle");
+/// build_all(&ProjectManifest::read("winter-circom.toml")?, &registry, LoggingLevel::Default)?;
+/// ```
+///
+/// ## Presets
+///
+/// Circuits can also be registered with their proof options, as named
+/// presets, so that they are created and proven without a manifest:
+///
+/// ```ignore
+/// let registry =
+///     CircuitRegistry::new().register_preset::<MerkleProver, 3>("merkle", presets::merkle());
+/// registry.create("merkle", LoggingLevel::Default)?;
+/// let prover = MerkleProver::new(registry.preset::<3>("merkle").unwrap().get_proof_options());
+/// ```
+#[derive(Default)]
+pub struct CircuitRegistry {
+    circuits: BTreeMap<String, (usize, CreateFn)>,
+    presets: BTreeMap<String, (Box<dyn Any>, PresetFn)>,
+}
+
+impl CircuitRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the prover of a circuit, with `N` transition constraints.
+    pub fn register<P, const N: usize>(mut self, circuit_name: &str) -> Self
+    where
+        P: Prover<BaseField = BaseElement> + 'static,
+        <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+    {
+        let create: CreateFn = Box::new(|circuit, logging_level| {
+            circom_create::<P, N>(circuit.proof_options::<N>()?, &circuit.name, logging_level)
+        });
+        self.circuits
+            .insert(String::from(circuit_name), (N, create));
+        self
+    }
+
+    /// Register the prover of a circuit with its proof options, created by
+    /// [create](CircuitRegistry::create).
+    ///
+    /// The circuit can still be built from a [ProjectManifest], with the proof
+    /// options of the manifest.
+    pub fn register_preset<P, const N: usize>(
+        self,
+        circuit_name: &str,
+        proof_options: WinterCircomProofOptions<N>,
+    ) -> Self
+    where
+        P: Prover<BaseField = BaseElement> + 'static,
+        <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+    {
+        let name = String::from(circuit_name);
+        let create: PresetFn = Box::new(move |logging_level| {
+            circom_create::<P, N>(proof_options, &name, logging_level)
+        });
+        let mut registry = self.register::<P, N>(circuit_name);
+        registry.presets.insert(
+            String::from(circuit_name),
+            (Box::new(proof_options), create),
+        );
+        registry
+    }
+
+    /// Returns whether a circuit is registered.
+    pub fn contains(&self, circuit_name: &str) -> bool {
+        self.circuits.contains_key(circuit_name)
+    }
+
+    /// Returns the proof options a circuit was registered with, if it was
+    /// registered as a preset with `N` transition constraints.
+    pub fn preset<const N: usize>(
+        &self,
+        circuit_name: &str,
+    ) -> Option<WinterCircomProofOptions<N>> {
+        self.presets
+            .get(circuit_name)
+            .and_then(|(proof_options, _)| proof_options.downcast_ref().copied())
+    }
+
+    /// Generate the Circom code of a circuit registered as a preset, with
+    /// [circom_create].
+    pub fn create(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        match self.presets.get(circuit_name) {
+            Some((_, create)) => create(logging_level),
+            None => Err(WinterCircomError::InvalidCircuitName {
+                circuit_name: String::from(circuit_name),
+                reason: String::from("no preset is registered with this name"),
+            }),
+        }
+    }
+}
+
+/// Outcome of [build_all] for a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBuild {
+    pub circuit_name: String,
+
+    /// Whether the Circom code was generated again.
+    pub generated: bool,
+
+    /// Stages that were run, without the up to date ones.
+    pub stages: Vec<Stage>,
+}
+
+impl CircuitBuild {
+    /// Returns whether the circuit was already up to date.
+    pub fn is_up_to_date(&self) -> bool {
+        !self.generated && self.stages.is_empty()
+    }
+}
+
+/// Generate, compile and set up every circuit of a project, in order.
+///
+/// Each step is skipped while its inputs are unchanged, so that only the
+/// circuits affected by a change are built again:
+///
+/// - the Circom code is generated again when the proof options of the manifest
+/// or the Circom templates changed (see [is_generated](ProjectCircuit::is_generated)),
+/// - the circuit is compiled again when its Circom code changed,
+/// - the circuit-specific keys are generated again when the compiled circuit
+/// or the transcript changed.
+///
+/// Changes are detected from the contents of the files, not from their
+/// modification times (see [is_up_to_date](Stage::is_up_to_date)), so that
+/// copying a project or checking it out from git does not build it again.
+///
+/// Every circuit must be registered with a prover of the right number of
+/// transition constraints, which is checked before anything is built. The
+/// build stops at the first error.
+pub fn build_all(
+    manifest: &ProjectManifest,
+    registry: &CircuitRegistry,
+    logging_level: LoggingLevel,
+) -> Result<Vec<CircuitBuild>, WinterCircomError> {
+    for circuit in manifest.circuits.iter() {
+        let num_constraints = circuit.transition_constraint_degrees.len();
+        match registry.circuits.get(&circuit.name) {
+            None => {
+                return Err(invalid(
+                    ProjectManifest::FILE_NAME,
+                    format!("circuit {} has no registered prover", circuit.name),
+                ))
+            }
+            Some((n, _)) if *n != num_constraints => {
+                return Err(invalid(
+                    ProjectManifest::FILE_NAME,
+                    format!(
+                        "circuit {} has {} transition constraints, but its prover has {}",
+                        circuit.name, num_constraints, n
+                    ),
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    let workspace = CircomWorkspace::current();
+    let mut builds = Vec::new();
+    for circuit in manifest.circuits.iter() {
+        let ptau = circuit.ptau.as_ref().or(manifest.ptau.as_ref());
+        let workspace = match ptau {
+            Some(ptau) => workspace.clone().with_ptau(ptau),
+            None => workspace.clone(),
+        };
+        let (_, create) = &registry.circuits[&circuit.name];
+
+        let build = with_workspace(workspace, || {
+            let generated = !circuit.is_generated();
+            if generated {
+                create(circuit, logging_level)?;
+            } else if logging_level.print_big_steps() {
+                println!(
+                    "{}",
+                    format!("Circuit {} is up to date.", circuit.name).green()
+                );
+            }
+            let stages = Pipeline::new(&circuit.name)
+                .compile()
+                .setup()
+                .skip_up_to_date(true)
+                .run(logging_level)?;
+            Ok::<_, WinterCircomError>(CircuitBuild {
+                circuit_name: circuit.name.clone(),
+                generated,
+                stages,
+            })
+        })?;
+        builds.push(build);
+    }
+    Ok(builds)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn invalid(file: &str, comment: String) -> WinterCircomError {
+    WinterCircomError::InvalidConfig {
+        file: String::from(file),
+        comment,
+    }
+}
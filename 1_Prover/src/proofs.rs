@@ -0,0 +1,177 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::json;
+
+use crate::{
+    audit::{audited, AuditAction},
+    tenant::validate_circuit_name,
+    utils::{check_file, command_execution, Executable, LoggingLevel, WinterCircomError},
+    workspace::circuit_dir,
+    SnarkScheme,
+};
+
+/// Outputs of a proof, moved to its directory by [save_proof]. The FRI layers
+/// are only written by [circom_prove](crate::circom_prove) and are optional.
+const PROOF_FILES: [(&str, bool); 3] = [
+    ("proof.json", true),
+    ("public.json", true),
+    ("fri_layers.json", false),
+];
+
+/// Returns the directory of a saved proof of a circuit,
+/// `target/circom/<circuit_name>/proofs/<proof_id>/`.
+///
+/// Returns an error if the proof ID is not one [save_proof] accepts.
+pub fn proof_dir(circuit_name: &str, proof_id: &str) -> Result<PathBuf, WinterCircomError> {
+    validate_proof_id(proof_id)?;
+    Ok(proofs_dir(circuit_name).join(proof_id))
+}
+
+/// Move the outputs of the last proof of a circuit (`proof.json`,
+/// `public.json` and `fri_layers.json`) to their own directory, so that they
+/// are not overwritten by the next proof.
+///
+/// The proof is identified by the given ID, or by the current time in
+/// milliseconds since the Unix epoch if `None`. Returns the ID, which can be
+/// passed to [proof_dir] and [circom_verify_proof]. An error is returned if a
+/// proof with the same ID was already saved, or if the proof ID is empty,
+/// starts with `.` or contains characters other than ASCII alphanumerics, `-`,
+/// `_` and `.`.
+pub fn save_proof(circuit_name: &str, proof_id: Option<&str>) -> Result<String, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let proof_id = match proof_id {
+        Some(proof_id) => String::from(proof_id),
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0)
+            .to_string(),
+    };
+    validate_proof_id(&proof_id)?;
+
+    let circuit_dir = circuit_dir(circuit_name);
+    for (file, required) in PROOF_FILES {
+        if required {
+            check_file(
+                circuit_dir.join(file).to_string_lossy().into_owned(),
+                Some("did you generate the proof?"),
+            )?;
+        }
+    }
+
+    let dir = proof_dir(circuit_name, &proof_id)?;
+    fs::create_dir_all(proofs_dir(circuit_name))
+        .and_then(|_| fs::create_dir(&dir))
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("creating {}", dir.display())),
+        })?;
+    for (file, _) in PROOF_FILES {
+        if circuit_dir.join(file).exists() {
+            fs::rename(circuit_dir.join(file), dir.join(file)).map_err(|e| {
+                WinterCircomError::IoError {
+                    io_error: e,
+                    comment: Some(format!("moving {}", file)),
+                }
+            })?;
+        }
+    }
+
+    Ok(proof_id)
+}
+
+/// Returns the IDs of the saved proofs of a circuit, sorted.
+pub fn list_proofs(circuit_name: &str) -> Vec<String> {
+    let mut proof_ids = fs::read_dir(proofs_dir(circuit_name))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().join("proof.json").exists())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    proof_ids.sort();
+    proof_ids
+}
+
+/// Verify a saved proof of a circuit.
+///
+/// This is the same as [circom_verify](crate::circom_verify), for the proof
+/// saved by [save_proof] with the given ID. As with `circom_verify`, this
+/// function must be used alongside [check_ood_frame](crate::check_ood_frame)
+/// (or [decode_public_signals](crate::decode_public_signals) on the
+/// `public.json` file of the [proof_dir]).
+pub fn circom_verify_proof(
+    circuit_name: &str,
+    proof_id: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let dir = proof_dir(circuit_name, proof_id)?;
+    audited(
+        circuit_name,
+        AuditAction::Verify,
+        json!({ "proof_id": proof_id }),
+        &[
+            circuit_dir(circuit_name).join("verification_key.json"),
+            dir.join("public.json"),
+            dir.join("proof.json"),
+        ],
+        || verify_proof(circuit_name, proof_id, &logging_level),
+    )
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn proofs_dir(circuit_name: &str) -> PathBuf {
+    circuit_dir(circuit_name).join("proofs")
+}
+
+fn validate_proof_id(proof_id: &str) -> Result<(), WinterCircomError> {
+    let valid = !proof_id.is_empty()
+        && !proof_id.starts_with('.')
+        && proof_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(WinterCircomError::InvalidProofId {
+            proof_id: String::from(proof_id),
+        })
+    }
+}
+
+fn verify_proof(
+    circuit_name: &str,
+    proof_id: &str,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let circuit_dir = circuit_dir(circuit_name);
+    let public = format!("proofs/{}/public.json", proof_id);
+    let proof = format!("proofs/{}/proof.json", proof_id);
+    for file in ["verification_key.json", &public, &proof] {
+        check_file(
+            circuit_dir.join(file).to_string_lossy().into_owned(),
+            Some("needed for verification"),
+        )?;
+    }
+
+    command_execution(
+        Executable::SnarkJS,
+        &[
+            SnarkScheme::of_circuit(circuit_name)?.verify_command(),
+            "verification_key.json",
+            &public,
+            &proof,
+        ],
+        circuit_dir.to_str(),
+        logging_level,
+    )
+}
@@ -0,0 +1,194 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    tenant::validate_circuit_name,
+    utils::{directory_size, WinterCircomError},
+    workspace::circuit_dir,
+};
+
+/// Artifacts only needed to generate Groth16 proofs.
+const PROVER_ONLY: [&str; 2] = ["verifier.zkey", "verifier_js"];
+
+/// Artifacts only needed to verify Groth16 proofs.
+const VERIFIER_ONLY: [&str; 1] = ["verification_key.json"];
+
+/// Role of a host, which determines the artifacts of a circuit it stores.
+///
+/// Proving keys (`verifier.zkey`) are large and only needed by the hosts that
+/// generate proofs, while verification keys are only needed by the hosts that
+/// verify them. Artifacts can be copied to a host with [distribute_artifacts]
+/// and removed from it with [prune_artifacts] according to its role.
+///
+/// ## Configuration
+///
+/// The role of the current host is read from the `WINTER_CIRCOM_HOST_ROLE`
+/// environment variable (`prover`, `verifier` or `full`), and defaults to
+/// [Full](HostRole::Full). On a prover host, [rotate_keys](crate::rotate_keys)
+/// does not export the verification key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostRole {
+    /// Host that only generates proofs.
+    Prover,
+
+    /// Host that only verifies proofs.
+    Verifier,
+
+    /// Host that generates circuits, proves and verifies.
+    Full,
+}
+
+impl HostRole {
+    pub const ROLE_VARIABLE: &'static str = "WINTER_CIRCOM_HOST_ROLE";
+
+    /// Returns the role configured by the environment variable.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_HOST_ROLE` is not `prover`, `verifier` or `full`.
+    pub fn from_env() -> Result<Self, WinterCircomError> {
+        match env::var(HostRole::ROLE_VARIABLE).as_deref() {
+            Ok("prover") => Ok(HostRole::Prover),
+            Ok("verifier") => Ok(HostRole::Verifier),
+            Ok("full") | Err(_) => Ok(HostRole::Full),
+            Ok(value) => Err(WinterCircomError::InvalidEnvVariable {
+                variable: String::from(HostRole::ROLE_VARIABLE),
+                comment: format!("expected prover, verifier or full, got {}", value),
+            }),
+        }
+    }
+
+    /// Returns the entries of the directory of a circuit needed by the role, or
+    /// `None` if it needs all of them.
+    pub fn artifacts(&self) -> Option<&'static [&'static str]> {
+        match self {
+            HostRole::Prover => Some(&["manifest.json", "verifier.zkey", "verifier_js"]),
+            HostRole::Verifier => Some(&["manifest.json", "verification_key.json", "keys"]),
+            HostRole::Full => None,
+        }
+    }
+
+    /// Returns whether files of the given name are needed by the role.
+    ///
+    /// This also applies to the keys of previous epochs archived in the `keys/`
+    /// directory (see [KeyHistory](crate::KeyHistory)).
+    pub fn needs(&self, file_name: &str) -> bool {
+        match self {
+            HostRole::Prover => !VERIFIER_ONLY.contains(&file_name),
+            HostRole::Verifier => !PROVER_ONLY.contains(&file_name),
+            HostRole::Full => true,
+        }
+    }
+}
+
+/// Copy the artifacts of a circuit needed by a role to another artifact
+/// directory, as `<destination>/<circuit_name>/`.
+///
+/// The destination is typically the `target/circom` directory of the host,
+/// e.g. a mounted volume. Returns the number of bytes copied.
+pub fn distribute_artifacts<P: AsRef<Path>>(
+    circuit_name: &str,
+    role: HostRole,
+    destination: P,
+) -> Result<u64, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let source = circuit_dir(circuit_name);
+    let target = destination.as_ref().join(circuit_name);
+
+    let entries = match role.artifacts() {
+        Some(artifacts) => artifacts.iter().map(PathBuf::from).collect(),
+        None => fs::read_dir(&source)
+            .map_err(io_error(format!("reading {}", source.display())))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| PathBuf::from(entry.file_name()))
+            .collect::<Vec<_>>(),
+    };
+
+    let mut copied = 0;
+    for entry in entries {
+        if source.join(&entry).exists() {
+            copied += copy_entry(&source.join(&entry), &target.join(&entry), role)?;
+        }
+    }
+    Ok(copied)
+}
+
+/// Remove the artifacts of a circuit that are not needed by a role from this
+/// host.
+///
+/// Returns the number of bytes freed.
+pub fn prune_artifacts(circuit_name: &str, role: HostRole) -> Result<u64, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    let before = directory_size(&dir);
+    prune_dir(&dir, role)?;
+    Ok(before.saturating_sub(directory_size(&dir)))
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn io_error(comment: String) -> impl FnOnce(std::io::Error) -> WinterCircomError {
+    move |e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(comment),
+    }
+}
+
+fn is_needed(path: &Path, role: HostRole) -> bool {
+    path.file_name()
+        .map_or(true, |name| role.needs(&name.to_string_lossy()))
+}
+
+/// Recursively copy a file or directory, skipping the entries not needed by
+/// the role. Returns the number of bytes copied.
+fn copy_entry(source: &Path, target: &Path, role: HostRole) -> Result<u64, WinterCircomError> {
+    if !is_needed(source, role) {
+        return Ok(0);
+    }
+
+    if source.is_dir() {
+        fs::create_dir_all(target).map_err(io_error(format!("creating {}", target.display())))?;
+        let mut copied = 0;
+        for entry in fs::read_dir(source)
+            .map_err(io_error(format!("reading {}", source.display())))?
+            .filter_map(|entry| entry.ok())
+        {
+            copied += copy_entry(&entry.path(), &target.join(entry.file_name()), role)?;
+        }
+        Ok(copied)
+    } else {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(io_error(format!("creating {}", parent.display())))?;
+        }
+        fs::copy(source, target).map_err(io_error(format!("copying {}", source.display())))
+    }
+}
+
+/// Recursively remove the entries of a directory not needed by the role.
+fn prune_dir(dir: &Path, role: HostRole) -> Result<(), WinterCircomError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
+    };
+
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        let result = match (is_needed(&path, role), path.is_dir()) {
+            (true, true) => prune_dir(&path, role),
+            (true, false) => Ok(()),
+            (false, true) => {
+                fs::remove_dir_all(&path).map_err(io_error(format!("removing {}", path.display())))
+            }
+            (false, false) => {
+                fs::remove_file(&path).map_err(io_error(format!("removing {}", path.display())))
+            }
+        };
+        result?;
+    }
+    Ok(())
+}
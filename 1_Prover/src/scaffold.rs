@@ -0,0 +1,593 @@
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    tenant::{split_circuit_name, validate_circuit_name},
+    utils::WinterCircomError,
+    CircomWorkspace, WinterCircomProofOptions,
+};
+
+/// Dimensions and proof options of a circuit generated by [scaffold].
+///
+/// They are written to the `PROOF_OPTIONS` constant of the generated AIR,
+/// which is where they are edited afterwards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScaffoldOptions {
+    pub trace_length: usize,
+    pub trace_width: usize,
+    pub num_assertions: usize,
+    pub transition_constraint_degrees: Vec<usize>,
+    pub num_queries: usize,
+    pub lde_blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub fri_folding_factor: usize,
+    pub fri_max_remainder_size: usize,
+
+    /// Directory of the generated crate, `examples/example-<air name>` by
+    /// default.
+    pub crate_dir: Option<PathBuf>,
+}
+
+impl ScaffoldOptions {
+    /// Returns the options of a circuit of the given dimensions, with the proof
+    /// options of `examples/example-sum`, and at least the
+    /// [smallest sound](WinterCircomProofOptions::min_lde_blowup_factor) LDE
+    /// blowup factor.
+    pub fn new(
+        trace_length: usize,
+        trace_width: usize,
+        num_assertions: usize,
+        transition_constraint_degrees: Vec<usize>,
+    ) -> Self {
+        let lde_blowup_factor = transition_constraint_degrees
+            .iter()
+            .map(|degree| winterfell::TransitionConstraintDegree::new(*degree).min_blowup_factor())
+            .max()
+            .unwrap_or(1)
+            .max(8);
+        Self {
+            trace_length,
+            trace_width,
+            num_assertions,
+            transition_constraint_degrees,
+            num_queries: 32,
+            lde_blowup_factor,
+            grinding_factor: 0,
+            fri_folding_factor: 8,
+            fri_max_remainder_size: 128,
+            crate_dir: None,
+        }
+    }
+
+    /// Set the directory of the generated crate.
+    pub fn crate_dir<D: Into<PathBuf>>(self, crate_dir: D) -> Self {
+        Self {
+            crate_dir: Some(crate_dir.into()),
+            ..self
+        }
+    }
+}
+
+impl<const N: usize> From<&WinterCircomProofOptions<N>> for ScaffoldOptions {
+    fn from(options: &WinterCircomProofOptions<N>) -> Self {
+        Self {
+            trace_length: options.trace_length,
+            trace_width: options.trace_width,
+            num_assertions: options.num_assertions,
+            transition_constraint_degrees: options.transition_constraint_degrees.to_vec(),
+            num_queries: options.num_queries,
+            lde_blowup_factor: options.lde_blowup_factor,
+            grinding_factor: options.grinding_factor,
+            fri_folding_factor: options.fri_folding_factor,
+            fri_max_remainder_size: options.fri_max_remainder_size,
+            crate_dir: None,
+        }
+    }
+}
+
+/// Generate the skeleton of a new circuit, mirroring the layout of
+/// `examples/example-sum`:
+///
+/// - the AIR template `circuits/air/<air name>.circom` of the
+/// [CircomWorkspace], with stub `AIRTransitions` and `AIRAssertions` templates;
+/// - a crate whose `air.rs` and `prover.rs` modules are stubs of the Winterfell
+/// [Air](winterfell::Air) and [Prover](winterfell::Prover), and whose `create`,
+/// `prove` and `verify` binaries run [circom_create](crate::circom_create),
+/// [circom_prove](crate::circom_prove) and
+/// [circom_verify](crate::circom_verify).
+///
+/// The Rust stubs compile, but the transition constraints, the assertions and
+/// the trace are left to be written, where marked with `TODO`. The AIR template
+/// does not compile until its assertions are defined (see
+/// [scaffold_on_missing](WinterCircomProofOptions::scaffold_on_missing)). The Rust types are
+/// named after the AIR name, e.g. `MyCircuitAir` for `my_circuit`.
+///
+/// Returns the written files. Nothing is written if one of them already exists.
+pub fn scaffold(
+    circuit_name: &str,
+    options: &ScaffoldOptions,
+) -> Result<Vec<PathBuf>, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let air_name = split_circuit_name(circuit_name).1;
+    let crate_dir = options
+        .crate_dir
+        .clone()
+        .unwrap_or_else(|| Path::new("examples").join(format!("example-{}", crate_name(air_name))));
+
+    let files = vec![
+        (
+            CircomWorkspace::current().air_template(circuit_name),
+            air_template_stub(
+                &options.transition_constraint_degrees,
+                options.num_assertions,
+            ),
+        ),
+        (
+            crate_dir.join("Cargo.toml"),
+            cargo_manifest(air_name, &crate_dir),
+        ),
+        (
+            crate_dir.join("src").join("air.rs"),
+            rust_stub(AIR_RS, circuit_name, options),
+        ),
+        (
+            crate_dir.join("src").join("prover.rs"),
+            rust_stub(PROVER_RS, circuit_name, options),
+        ),
+        (
+            crate_dir.join("src").join("create.rs"),
+            rust_stub(CREATE_RS, circuit_name, options),
+        ),
+        (
+            crate_dir.join("src").join("prove.rs"),
+            rust_stub(PROVE_RS, circuit_name, options),
+        ),
+        (
+            crate_dir.join("src").join("verify.rs"),
+            rust_stub(VERIFY_RS, circuit_name, options),
+        ),
+    ];
+
+    if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(WinterCircomError::IoError {
+            io_error: io::Error::new(io::ErrorKind::AlreadyExists, "file exists"),
+            comment: Some(format!("scaffolding {}", path.display())),
+        });
+    }
+    for (path, contents) in files.iter() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(format!("creating {}", parent.display())),
+            })?;
+        }
+        fs::write(path, contents).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("writing {}", path.display())),
+        })?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Returns the [MissingAirTemplate](WinterCircomError::MissingAirTemplate)
+/// error of a missing AIR template, writing a stub to its path if `scaffold` is
+/// set (see
+/// [scaffold_on_missing](crate::WinterCircomProofOptions::scaffold_on_missing)).
+pub(crate) fn missing_air_template(
+    template: &str,
+    transition_degrees: &[usize],
+    num_assertions: usize,
+    scaffold: bool,
+) -> WinterCircomError {
+    let mut scaffolded = false;
+    if scaffold {
+        let path = Path::new(template);
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        scaffolded = fs::write(path, air_template_stub(transition_degrees, num_assertions)).is_ok();
+    }
+
+    WinterCircomError::MissingAirTemplate {
+        template: String::from(template),
+        transition_degrees: transition_degrees.to_vec(),
+        num_assertions,
+        scaffolded,
+    }
+}
+
+/// Returns a commented AIR template with the given transition constraint
+/// degrees and number of assertions, in the format of the templates of the
+/// `circuits/air/` directory.
+///
+/// The assertions are left to be defined: the components of the
+/// `AIRAssertions` template are not instantiated, so that the stub does not
+/// compile until they are.
+pub(crate) fn air_template_stub(transition_degrees: &[usize], num_assertions: usize) -> String {
+    let mut stub = String::from(
+        "pragma circom 2.0.0;
+
+include \"../utils/comparators.circom\";
+
+
+template AIRTransitions(num_transition_constraints) {
+    signal output transition_degree[num_transition_constraints];
+
+    /* === EDIT FROM HERE === */
+
+    // Transition degrees declared in WinterCircomProofOptions.
+",
+    );
+    for (i, degree) in transition_degrees.iter().enumerate() {
+        stub.push_str(&format!("    transition_degree[{}] <== {};\n", i, degree));
+    }
+
+    stub.push_str(
+        "
+    /* ====== TO HERE ====== */
+}
+
+
+template AIRAssertions(addicity, num_assertions, num_public_inputs, trace_length, trace_width) {
+    signal input addicity_root;
+    signal input public_inputs[num_public_inputs];
+    signal input g_trace;
+
+    signal output evaluations[num_assertions];
+    signal output number_of_steps[num_assertions];
+    signal output registers[num_assertions];
+    signal output step_offsets[num_assertions];
+    signal output strides[num_assertions];
+
+    component assertions[num_assertions];
+
+    /* === EDIT FROM HERE === */
+
+    // Number of assertions declared in WinterCircomProofOptions.
+
+",
+    );
+    stub.push_str(&format!(
+        "    assert(num_assertions == {});\n",
+        num_assertions
+    ));
+    stub.push_str(
+        "
+    // Define your assertions here, once each as in canonical_assertions, using
+    // the SingleAssertion, PeriodicAssertion and SequenceAssertion templates:
+    //
+    // assertions[0] = SingleAssertion();
+    // assertions[0].column <== 0;
+    // assertions[0].step <== 0;
+    // assertions[0].value <== public_inputs[0];
+
+    /* ====== TO HERE ====== */
+
+    for (var i = 0; i < num_assertions; i++) {
+        evaluations[i] <== assertions[i].evaluation;
+        number_of_steps[i] <== assertions[i].number_of_steps;
+        registers[i] <== assertions[i].register;
+        step_offsets[i] <== assertions[i].step_offset;
+        strides[i] <== assertions[i].stride_out;
+    }
+}
+",
+    );
+    stub
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the name of an AIR in the name of its crate, e.g. `my-circuit` in
+/// `example-my-circuit`.
+fn crate_name(air_name: &str) -> String {
+    air_name
+        .to_ascii_lowercase()
+        .replace(|c| c == '_' || c == '.', "-")
+}
+
+/// Returns the prefix of the Rust types of an AIR, e.g. `MyCircuit`.
+fn type_prefix(air_name: &str) -> String {
+    let prefix = air_name
+        .split(|c| c == '_' || c == '-' || c == '.')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect::<String>();
+    if prefix.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("Circuit{}", prefix);
+    }
+    prefix
+}
+
+fn cargo_manifest(air_name: &str, crate_dir: &Path) -> String {
+    // path dependency when scaffolding in this repository, as the examples
+    let path = Path::new("1_Prover");
+    let relative = crate_dir
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    let dependency = match (path.join("Cargo.toml").exists(), relative) {
+        (false, _) => String::new(),
+        (true, true) => {
+            let depth = crate_dir.components().count();
+            let path = Path::new(&"../".repeat(depth)).join(path);
+            format!(", path = \"{}\"", path.display())
+        }
+        (true, false) => match fs::canonicalize(path) {
+            Ok(path) => format!(", path = \"{}\"", path.display()),
+            Err(_) => String::new(),
+        },
+    };
+    CARGO_TOML
+        .replace("__CRATE__", &format!("example-{}", crate_name(air_name)))
+        .replace("__PATH__", &dependency)
+}
+
+fn rust_stub(template: &str, circuit_name: &str, options: &ScaffoldOptions) -> String {
+    let degrees = &options.transition_constraint_degrees;
+    template
+        .replace("__CIRCUIT__", circuit_name)
+        .replace("__TYPE__", &type_prefix(split_circuit_name(circuit_name).1))
+        .replace("__N__", &degrees.len().to_string())
+        .replace(
+            "__PROOF_OPTIONS__",
+            &format!(
+                "{}, {}, {}, [{}], {}, {}, {}, {}, {}",
+                options.trace_length,
+                options.trace_width,
+                options.num_assertions,
+                degrees
+                    .iter()
+                    .map(|degree| degree.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                options.num_queries,
+                options.lde_blowup_factor,
+                options.grinding_factor,
+                options.fri_folding_factor,
+                options.fri_max_remainder_size
+            ),
+        )
+}
+
+const CARGO_TOML: &str = r#"[package]
+name = "__CRATE__"
+version = "0.1.0"
+edition = "2021"
+rust-version = "1.60"
+default-run = "prove"
+
+[features]
+std = ["serde/std", "winter-circom-prover/std"]
+default = ["std"]
+concurrent = ["std", "winter-circom-prover/concurrent"]
+
+[dependencies]
+winter-circom-prover = { version = "0.1.0", default-features = false, features = ["prove", "verify"]__PATH__ }
+serde = { version = "1.0", default-features = false }
+
+[[bin]]
+name = "create"
+path = "src/create.rs"
+
+[[bin]]
+name = "prove"
+path = "src/prove.rs"
+
+[[bin]]
+name = "verify"
+path = "src/verify.rs"
+"#;
+
+const AIR_RS: &str = r#"use serde::{ser::SerializeTuple, Serialize};
+use winter_circom_prover::{
+    winterfell::{
+        math::{fields::f256::BaseElement, FieldElement},
+        Air, AirContext, Assertion, ByteWriter, EvaluationFrame, ProofOptions, Serializable,
+        TraceInfo,
+    },
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+pub(crate) const PROOF_OPTIONS: WinterCircomProofOptions<__N__> =
+    WinterCircomProofOptions::new(__PROOF_OPTIONS__);
+
+// TODO: the public inputs of the AIR, in the order of the `public_inputs`
+// input of the `AIRAssertions` template.
+#[derive(Clone, Default)]
+pub struct PublicInputs {
+    pub start: BaseElement,
+}
+
+impl WinterPublicInputs for PublicInputs {
+    const NUM_PUB_INPUTS: usize = 1;
+}
+
+impl Serialize for PublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(1)?;
+        state.serialize_element(&self.start)?;
+        state.end()
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.start);
+    }
+}
+
+pub struct __TYPE__Air {
+    context: AirContext<BaseElement>,
+}
+
+impl Air for __TYPE__Air {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, _pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        let degrees = PROOF_OPTIONS.transition_constraint_degrees();
+
+        let num_assertions = PROOF_OPTIONS.num_assertions();
+
+        __TYPE__Air {
+            context: AirContext::new(trace_info, degrees, num_assertions, options),
+        }
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        _frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        _result: &mut [E],
+    ) {
+        // TODO: the transition constraints, of the degrees returned by the
+        // `AIRTransitions` template.
+        unimplemented!("transition constraints of __CIRCUIT__")
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        // TODO: the assertions of the `AIRAssertions` template, deduplicated
+        // and ordered by `canonical_assertions(vec![...])`.
+        unimplemented!("assertions of __CIRCUIT__")
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for __TYPE__Air {
+    fn default() -> Self {
+        __TYPE__Air::new(
+            TraceInfo::new(PROOF_OPTIONS.trace_width, PROOF_OPTIONS.trace_length),
+            PublicInputs::default(),
+            PROOF_OPTIONS.get_proof_options(),
+        )
+    }
+}
+"#;
+
+const PROVER_RS: &str = r#"use super::air::{PublicInputs, __TYPE__Air, PROOF_OPTIONS};
+use winter_circom_prover::winterfell::{
+    math::{fields::f256::BaseElement, FieldElement},
+    ProofOptions, Prover, TraceTable,
+};
+
+pub struct __TYPE__Prover {
+    options: ProofOptions,
+}
+
+impl __TYPE__Prover {
+    pub fn new(options: ProofOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn build_trace(&self, start: BaseElement, n: usize) -> TraceTable<BaseElement> {
+        let trace_width = PROOF_OPTIONS.trace_width;
+        let mut trace = TraceTable::new(trace_width, n);
+
+        // TODO: the initial state and the transition function of the trace.
+        trace.fill(
+            |state| {
+                state[0] = start;
+            },
+            |_, state| {
+                state[0] += BaseElement::ONE;
+            },
+        );
+
+        trace
+    }
+}
+
+impl Prover for __TYPE__Prover {
+    type BaseField = BaseElement;
+    type Air = __TYPE__Air;
+    type Trace = TraceTable<Self::BaseField>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        PublicInputs {
+            start: trace.get(0, 0),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+"#;
+
+const CREATE_RS: &str = r#"use winter_circom_prover::{
+    circom_create,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+#[allow(dead_code)]
+mod prover;
+use prover::__TYPE__Prover;
+
+mod air;
+use air::PROOF_OPTIONS;
+
+fn main() -> Result<(), WinterCircomError> {
+    circom_create::<__TYPE__Prover, __N__>(PROOF_OPTIONS, "__CIRCUIT__", LoggingLevel::Default)
+}
+"#;
+
+const PROVE_RS: &str = r#"use winter_circom_prover::{
+    circom_prove,
+    utils::{LoggingLevel, WinterCircomError},
+    winterfell::math::{fields::f256::BaseElement, FieldElement},
+    CheckedProver, CircuitParameters,
+};
+
+mod air;
+
+mod prover;
+pub use prover::__TYPE__Prover;
+
+fn main() -> Result<(), WinterCircomError> {
+    // parameters
+    let start = BaseElement::ONE;
+
+    // build proof with the parameters the circuit was compiled for
+    let parameters = CircuitParameters::of_circuit("__CIRCUIT__")?;
+    let prover = __TYPE__Prover::new(parameters.proof_options());
+    let trace = prover.build_trace(start, parameters.trace_length);
+
+    // check the public inputs against the trace before proving
+    circom_prove(
+        CheckedProver::new(prover),
+        trace,
+        "__CIRCUIT__",
+        LoggingLevel::Default,
+    )?;
+    Ok(())
+}
+"#;
+
+const VERIFY_RS: &str = r#"use winter_circom_prover::{
+    check_ood_frame, circom_verify,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+mod air;
+use air::__TYPE__Air;
+
+fn main() -> Result<(), WinterCircomError> {
+    check_ood_frame::<__TYPE__Air>("__CIRCUIT__");
+    circom_verify("__CIRCUIT__", LoggingLevel::Verbose)?;
+
+    Ok(())
+}
+"#;
@@ -0,0 +1,207 @@
+//! Versioned JSON schemas of the artifacts written by this crate.
+//!
+//! External tools (explorers, verifiers in other languages, ...) parse the
+//! JSON files of the `target/circom/<circuit_name>/` directory. The schemas of
+//! this module are generated from the Rust types that produce these files, and
+//! can be published with [write_schemas] so that consumers detect layout
+//! changes instead of silently misreading artifacts.
+//!
+//! Each schema is identified by `urn:winter-circom:<file>:v<SCHEMA_VERSION>`.
+
+use std::{fs, path::Path};
+
+use jsonschema::JSONSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    scratch::scratch_dir, signals::Signal, tenant::validate_circuit_name, utils::WinterCircomError,
+    workspace::circuit_dir, CircuitManifest, FriLayer,
+};
+
+/// Version of the JSON schemas of the artifacts.
+///
+/// It is incremented on every breaking change of the layout of an artifact, so
+/// that external tools can reject artifacts they do not understand.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON artifacts written to the `target/circom/<circuit_name>/` directory (or
+/// the [ScratchSpace]) that external tools may consume.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Artifact {
+    /// Input signals of the Circom circuit, written by
+    /// [circom_prove](crate::circom_prove).
+    Input,
+
+    /// Circuit metadata, written by [circom_create](crate::circom_create).
+    Manifest,
+
+    /// Per-layer FRI query data, written by [circom_prove](crate::circom_prove).
+    FriLayers,
+
+    /// Groth16 proof generated by snarkjs.
+    Proof,
+
+    /// Groth16 public signals generated by snarkjs.
+    PublicSignals,
+}
+
+impl Artifact {
+    /// All JSON artifacts.
+    pub const ALL: [Artifact; 5] = [
+        Artifact::Input,
+        Artifact::Manifest,
+        Artifact::FriLayers,
+        Artifact::Proof,
+        Artifact::PublicSignals,
+    ];
+
+    /// Returns the file name of the artifact.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Artifact::Input => "input.json",
+            Artifact::Manifest => "manifest.json",
+            Artifact::FriLayers => "fri_layers.json",
+            Artifact::Proof => "proof.json",
+            Artifact::PublicSignals => "public.json",
+        }
+    }
+
+    /// Returns the path of the artifact of the given circuit.
+    pub fn path(&self, circuit_name: &str) -> String {
+        match self {
+            Artifact::Input => scratch_dir(circuit_name)
+                .join(self.file_name())
+                .to_string_lossy()
+                .into_owned(),
+            _ => circuit_dir(circuit_name)
+                .join(self.file_name())
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+
+    /// Returns the JSON schema of the artifact.
+    pub fn schema(&self) -> Value {
+        let mut schema = match self {
+            Artifact::Input => input_schema(),
+            Artifact::Manifest => to_value(schema_for!(CircuitManifest)),
+            Artifact::FriLayers => to_value(schema_for!(Vec<FriLayer>)),
+            Artifact::Proof => to_value(schema_for!(Groth16Proof)),
+            Artifact::PublicSignals => to_value(schema_for!(Vec<FieldElementString>)),
+        };
+
+        schema["$id"] = Value::from(format!(
+            "urn:winter-circom:{}:v{}",
+            self.file_name(),
+            SCHEMA_VERSION
+        ));
+        schema
+    }
+}
+
+/// Groth16 proof, as written to `proof.json` by snarkjs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Groth16Proof {
+    pub pi_a: Vec<FieldElementString>,
+    pub pi_b: Vec<Vec<FieldElementString>>,
+    pub pi_c: Vec<FieldElementString>,
+    pub protocol: String,
+    pub curve: String,
+}
+
+/// Field element, serialized as a decimal string.
+pub type FieldElementString = String;
+
+/// Validate a JSON value against the schema of an artifact.
+pub fn validate_against_schema(artifact: Artifact, value: &Value) -> Result<(), WinterCircomError> {
+    let schema = JSONSchema::compile(&artifact.schema()).expect("artifact schemas should be valid");
+
+    schema
+        .validate(value)
+        .map_err(|errors| WinterCircomError::SchemaValidation {
+            file: String::from(artifact.file_name()),
+            errors: errors
+                .map(|e| format!("{} at {}", e, e.instance_path))
+                .collect(),
+        })
+}
+
+/// Validate the artifact of a circuit against its schema.
+pub fn validate_file(artifact: Artifact, circuit_name: &str) -> Result<(), WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let path = artifact.path(circuit_name);
+    let data = fs::read_to_string(&path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", path)),
+    })?;
+    let value = serde_json::from_str(&data).map_err(|e| WinterCircomError::SchemaValidation {
+        file: String::from(artifact.file_name()),
+        errors: vec![e.to_string()],
+    })?;
+
+    validate_against_schema(artifact, &value)
+}
+
+/// Write the schemas of all artifacts to the given directory, as
+/// `<artifact>.schema.json` files.
+pub fn write_schemas<P: AsRef<Path>>(dir: P) -> Result<(), WinterCircomError> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("creating schema directory")),
+    })?;
+
+    for artifact in Artifact::ALL {
+        let name = artifact.file_name().replace(".json", ".schema.json");
+        let schema = serde_json::to_string_pretty(&artifact.schema()).unwrap();
+        fs::write(dir.join(name), schema).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("writing the schema of {}", artifact.file_name())),
+        })?;
+    }
+
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the schema of `input.json`, with one required property per [Signal].
+///
+/// The dimensions of the signals depend on the circuit parameters and are
+/// therefore not checked.
+fn input_schema() -> Value {
+    let properties = Signal::ALL
+        .iter()
+        .map(|signal| {
+            (
+                String::from(signal.name()),
+                json!({ "$ref": "#/definitions/signal_value" }),
+            )
+        })
+        .collect::<Map<_, _>>();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Input signals of the Verify Circom template",
+        "type": "object",
+        "properties": properties,
+        "required": Signal::ALL.iter().map(|signal| signal.name()).collect::<Vec<_>>(),
+        "additionalProperties": false,
+        "definitions": {
+            "signal_value": {
+                "oneOf": [
+                    { "type": "string", "pattern": "^[0-9]+$" },
+                    { "type": "integer", "minimum": 0 },
+                    { "type": "array", "items": { "$ref": "#/definitions/signal_value" } }
+                ]
+            }
+        }
+    })
+}
+
+fn to_value<T: Serialize>(schema: T) -> Value {
+    serde_json::to_value(schema).expect("schemas should be serializable")
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{utils::WinterCircomError, CircuitManifest};
+
+/// Proving system of the circuit verifying the Winterfell proofs, run by
+/// snarkjs.
+///
+/// Groth16 proofs are the smallest and the cheapest to verify, but the keys of
+/// each circuit need a circuit-specific phase 2 ceremony: the contribution made
+/// by [groth16_setup](crate::groth16_setup), and the ones of
+/// [rotate_keys](crate::rotate_keys). PLONK keys are derived from the powers of
+/// tau transcript alone, so that no circuit-specific ceremony is needed, at the
+/// cost of larger proofs, a slower prover and a larger transcript (PLONK counts
+/// the additions of the circuit as constraints). FFLONK keys need no ceremony
+/// either, and its proofs are the cheapest to verify on Ethereum, but its
+/// prover is the slowest and needs an even larger transcript. Its verification
+/// keys, proofs and Solidity verifier have formats of their own.
+///
+/// The scheme is chosen with
+/// [snark_scheme](crate::WinterCircomProofOptions::snark_scheme) and recorded
+/// in the [CircuitManifest], from which the stages of the pipeline read it. The
+/// stage functions keep their Groth16 names, e.g.
+/// [groth16_prove](crate::groth16_prove) generates a PLONK proof for a PLONK
+/// circuit. The [schema](crate::schema) of `proof.json` is the one of Groth16
+/// proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SnarkScheme {
+    Groth16,
+    Plonk,
+    Fflonk,
+}
+
+impl Default for SnarkScheme {
+    fn default() -> Self {
+        SnarkScheme::Groth16
+    }
+}
+
+impl SnarkScheme {
+    /// Returns the scheme of a circuit, recorded in its manifest. Circuits
+    /// without manifest, or generated by earlier versions of this crate, use
+    /// Groth16.
+    pub fn of_circuit(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        Ok(CircuitManifest::read(circuit_name)?
+            .map_or_else(SnarkScheme::default, |manifest| manifest.snark_scheme))
+    }
+
+    /// Returns the scheme of a snarkjs verification key, from its `protocol`
+    /// field. Keys which cannot be parsed are assumed to be Groth16 keys, and
+    /// rejected by snarkjs.
+    pub(crate) fn of_verification_key(verification_key: &[u8]) -> Self {
+        match serde_json::from_slice::<Value>(verification_key) {
+            Ok(key) if key["protocol"] == "plonk" => SnarkScheme::Plonk,
+            Ok(key) if key["protocol"] == "fflonk" => SnarkScheme::Fflonk,
+            _ => SnarkScheme::Groth16,
+        }
+    }
+
+    /// Returns whether the keys of a circuit receive contributions of a
+    /// circuit-specific ceremony.
+    pub fn has_circuit_specific_setup(&self) -> bool {
+        match self {
+            SnarkScheme::Groth16 => true,
+            SnarkScheme::Plonk | SnarkScheme::Fflonk => false,
+        }
+    }
+
+    /// Returns the name of the scheme, as printed in the messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SnarkScheme::Groth16 => "Groth16",
+            SnarkScheme::Plonk => "PLONK",
+            SnarkScheme::Fflonk => "FFLONK",
+        }
+    }
+
+    /// Returns the name of the module of the snarkjs JavaScript API of the
+    /// scheme.
+    pub(crate) fn snarkjs_module(&self) -> &'static str {
+        match self {
+            SnarkScheme::Groth16 => "groth16",
+            SnarkScheme::Plonk => "plonk",
+            SnarkScheme::Fflonk => "fflonk",
+        }
+    }
+
+    /// Returns the snarkjs command generating the keys of a circuit.
+    pub(crate) fn setup_command(&self) -> &'static str {
+        match self {
+            SnarkScheme::Groth16 => "g16s",
+            SnarkScheme::Plonk => "pks",
+            SnarkScheme::Fflonk => "ffs",
+        }
+    }
+
+    /// Returns the snarkjs command generating a proof.
+    pub(crate) fn prove_command(&self) -> &'static str {
+        match self {
+            SnarkScheme::Groth16 => "g16p",
+            SnarkScheme::Plonk => "pkp",
+            SnarkScheme::Fflonk => "ffp",
+        }
+    }
+
+    /// Returns the snarkjs command verifying a proof.
+    pub(crate) fn verify_command(&self) -> &'static str {
+        match self {
+            SnarkScheme::Groth16 => "g16v",
+            SnarkScheme::Plonk => "pkv",
+            SnarkScheme::Fflonk => "ffv",
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    env,
+    fs::{self, create_dir_all},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    utils::{directory_size, WinterCircomError},
+    workspace::CircomWorkspace,
+};
+
+/// Location of the scratch artifacts of the circuits.
+///
+/// Scratch artifacts (the `input.json` file and the execution witnesses) are
+/// rewritten on every proof and are not needed once the Groth16 proof has been
+/// generated. They can be placed on a fast, possibly volatile, storage (e.g. a
+/// tmpfs mount) separate from the persistent artifacts (circuits, keys and
+/// proofs), which always remain in the `target/circom/<circuit_name>/`
+/// directory.
+///
+/// ## Configuration
+///
+/// The scratch space is configured through environment variables, so that the
+/// JavaScript helpers of the `tools` directory agree with this crate:
+///
+/// - `WINTER_CIRCOM_SCRATCH_DIR`: root of the scratch space. Scratch artifacts
+/// of a circuit are placed in its `<circuit_name>/` subdirectory. Defaults to
+/// the root of the artifacts of the [CircomWorkspace], `target/circom`.
+/// - `WINTER_CIRCOM_SCRATCH_MAX_BYTES`: maximum total size of the files under
+/// the root of the scratch space. Writing an artifact that would exceed it fails with
+/// [ScratchSpaceExceeded](WinterCircomError::ScratchSpaceExceeded). Unlimited
+/// by default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScratchSpace {
+    root: Option<PathBuf>,
+    max_bytes: Option<u64>,
+}
+
+impl ScratchSpace {
+    pub const DIR_VARIABLE: &'static str = "WINTER_CIRCOM_SCRATCH_DIR";
+    pub const MAX_BYTES_VARIABLE: &'static str = "WINTER_CIRCOM_SCRATCH_MAX_BYTES";
+
+    pub fn new(root: Option<PathBuf>, max_bytes: Option<u64>) -> Self {
+        Self { root, max_bytes }
+    }
+
+    /// Returns the scratch space configured by the environment variables.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_SCRATCH_MAX_BYTES` is not an integer.
+    pub fn from_env() -> Result<Self, WinterCircomError> {
+        let max_bytes = match env::var(ScratchSpace::MAX_BYTES_VARIABLE) {
+            Ok(value) => {
+                Some(
+                    value
+                        .parse()
+                        .map_err(|_| WinterCircomError::InvalidEnvVariable {
+                            variable: String::from(ScratchSpace::MAX_BYTES_VARIABLE),
+                            comment: format!("expected a number of bytes, got {}", value),
+                        })?,
+                )
+            }
+            Err(_) => None,
+        };
+        Ok(Self::new(root_from_env(), max_bytes))
+    }
+
+    /// Returns the root of the scratch space.
+    pub fn root(&self) -> PathBuf {
+        match &self.root {
+            Some(root) => root.clone(),
+            None => CircomWorkspace::current().root_dir().to_path_buf(),
+        }
+    }
+
+    /// Returns the directory of the scratch artifacts of a circuit.
+    pub fn circuit_dir(&self, circuit_name: &str) -> PathBuf {
+        self.root().join(circuit_name)
+    }
+
+    /// Write a scratch artifact of a circuit, checking the size of the scratch
+    /// space first.
+    ///
+    /// Returns the path of the written file.
+    pub(crate) fn write(
+        &self,
+        circuit_name: &str,
+        file_name: &str,
+        contents: &[u8],
+    ) -> Result<PathBuf, WinterCircomError> {
+        let dir = self.circuit_dir(circuit_name);
+        create_dir_all(&dir).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("creating scratch directory")),
+        })?;
+
+        let path = dir.join(file_name);
+        self.check_capacity(&path, contents.len() as u64)?;
+
+        fs::write(&path, contents).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("writing {}", file_name)),
+        })?;
+        Ok(path)
+    }
+
+    /// Check that replacing the file at `path` with `len` bytes does not exceed
+    /// the maximum size of the scratch space.
+    fn check_capacity(&self, path: &Path, len: u64) -> Result<(), WinterCircomError> {
+        let max_bytes = match self.max_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return Ok(()),
+        };
+
+        let replaced = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let required = directory_size(&self.root()) - replaced + len;
+        if required > max_bytes {
+            return Err(WinterCircomError::ScratchSpaceExceeded {
+                required,
+                available: max_bytes,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Returns the root of the scratch space configured by the environment
+/// variables, see [ScratchSpace::root].
+///
+/// Unlike [ScratchSpace::from_env], the maximum size of the scratch space is not
+/// read, so that locating the scratch artifacts never fails.
+pub(crate) fn scratch_root() -> PathBuf {
+    ScratchSpace::new(root_from_env(), None).root()
+}
+
+/// Returns the directory of the scratch artifacts of a circuit, in the scratch
+/// space configured by the environment variables (see [scratch_root]).
+pub(crate) fn scratch_dir(circuit_name: &str) -> PathBuf {
+    scratch_root().join(circuit_name)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn root_from_env() -> Option<PathBuf> {
+    env::var_os(ScratchSpace::DIR_VARIABLE).map(PathBuf::from)
+}
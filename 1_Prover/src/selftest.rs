@@ -0,0 +1,372 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::PathBuf,
+};
+
+use colored::Colorize;
+use rug::Integer;
+use serde_json::Value;
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+use crate::{
+    circom_prove,
+    scratch::ScratchSpace,
+    signals::Signal,
+    tenant::validate_circuit_name,
+    utils::{
+        canonicalize, check_file, command_execution, command_execution_with_stdin,
+        workspace::circuit_dir, Executable, LoggingLevel, WinterCircomError, STDIN_PATH,
+    },
+    OodData, SnarkScheme, WinterPublicInputs,
+};
+
+/// Mutations applied to a valid `input.json` file by [security_selftest].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Flip a node of the first trace Merkle authentication path.
+    FlipMerkleNode,
+
+    /// Alter the first element of the out-of-domain trace frame.
+    AlterOodValue,
+
+    /// Change the first public input.
+    ChangePublicInput,
+
+    /// Replace the proof-of-work nonce, as if it had been reused from another
+    /// proof.
+    ReuseNonce,
+}
+
+impl Mutation {
+    /// All mutations applied by [security_selftest].
+    pub const ALL: [Mutation; 4] = [
+        Mutation::FlipMerkleNode,
+        Mutation::AlterOodValue,
+        Mutation::ChangePublicInput,
+        Mutation::ReuseNonce,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutation::FlipMerkleNode => "flip_merkle_node",
+            Mutation::AlterOodValue => "alter_ood_value",
+            Mutation::ChangePublicInput => "change_public_input",
+            Mutation::ReuseNonce => "reuse_nonce",
+        }
+    }
+
+    /// Returns the JSON pointer of the value modified by this mutation.
+    fn pointer(&self) -> String {
+        match self {
+            Mutation::FlipMerkleNode => format!("/{}/0/0", Signal::TraceQueryProofs.name()),
+            Mutation::AlterOodValue => format!("/{}/0/0", Signal::OodTraceFrame.name()),
+            Mutation::ChangePublicInput => format!("/{}/0", Signal::PublicInputs.name()),
+            Mutation::ReuseNonce => format!("/{}", Signal::PowNonce.name()),
+        }
+    }
+
+    /// Apply the mutation to the given Circom input JSON object.
+    ///
+    /// Returns `false`, leaving the input unchanged, if the mutation does not
+    /// apply to the input, e.g. [ChangePublicInput](Mutation::ChangePublicInput)
+    /// for an AIR without public inputs.
+    pub fn apply(&self, input: &mut Value) -> bool {
+        input
+            .pointer_mut(&self.pointer())
+            .map_or(false, flip_lowest_bit)
+    }
+}
+
+/// Run a security self-test of the whole verification chain of a circuit.
+///
+/// A valid proof is first generated with [circom_prove]. A catalog of
+/// [Mutation]s is then applied to the generated `input.json` file, and each
+/// input goes through the verification path of the circuit: its execution
+/// witness is computed, a Groth16 proof of the witness is generated and
+/// verified, the OOD data of the input are checked against the [Default]
+/// implementation of the [Air], as by [check_ood_frame](crate::check_ood_frame),
+/// and the exposed public inputs of the proof are compared with those of the
+/// valid proof. This function asserts that the valid input passes every step,
+/// and that each mutated input fails one of them. Mutations that do not apply
+/// to the circuit (see [Mutation::apply]) are skipped.
+///
+/// Witnesses and proofs are written in the `selftest/` directory of the
+/// circuit [ScratchSpace]. Mutated inputs are piped to the witness generator on
+/// Unix platforms, and written to the same directory otherwise.
+///
+/// The public signals of circuits generated with
+/// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)
+/// are not compared, as their commitment also covers the OOD data.
+///
+/// ## Requirements
+///
+/// The circuit must have been compiled to WebAssembly, i.e. the directory
+/// `target/circom/<circuit_name>/verifier_js/` must exist, and its
+/// `verifier.zkey` and `verification_key.json` keys must have been generated.
+pub fn security_selftest<P>(
+    prover: P,
+    trace: <P as Prover>::Trace,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <P as Prover>::Air: Default,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    validate_circuit_name(circuit_name)?;
+    let circuit_dir = circuit_dir(circuit_name).to_string_lossy().into_owned();
+    check_file(
+        format!("{}/verifier_js/verifier.wasm", circuit_dir),
+        Some("did you compile the circuit?"),
+    )?;
+    for file in ["verifier.zkey", "verification_key.json"] {
+        check_file(
+            format!("{}/{}", circuit_dir, file),
+            Some("did you generate the circuit-specific keys?"),
+        )?;
+    }
+
+    // GENERATE VALID PROOF
+    // ===========================================================================
+
+    if logging_level.print_big_steps() {
+        println!("{}", "Generating valid proof...".green());
+    }
+
+    // the mutations are applied to the input in memory, not read back
+    let input = circom_prove(prover, trace, circuit_name, LoggingLevel::Quiet)?.input;
+
+    let scratch = ScratchSpace::from_env()?;
+    let scratch_dir = scratch.circuit_dir(circuit_name);
+
+    create_dir_all(scratch_dir.join("selftest")).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(String::from("creating self-test output directory")),
+    })?;
+    let context = SelftestContext {
+        scratch: &scratch,
+        circuit_name,
+        circuit_dir: &circuit_dir,
+        selftest_dir: canonicalize(scratch_dir.join("selftest"))?,
+        scheme: SnarkScheme::of_circuit(circuit_name)?,
+    };
+
+    // the unmodified input must be accepted, otherwise the test is meaningless
+    if logging_level.print_big_steps() {
+        println!("{}", "Checking valid proof...".green());
+    }
+    let statement =
+        verify_selftest_input::<P::Air>(&input, "valid", None, &context, &logging_level)?;
+
+    // APPLY MUTATIONS
+    // ===========================================================================
+
+    for mutation in Mutation::ALL {
+        let mut mutated = input.clone();
+        if !mutation.apply(&mut mutated) {
+            if logging_level.print_big_steps() {
+                println!(
+                    "{}",
+                    format!("Skipping mutation {}...", mutation.name()).green()
+                );
+            }
+            continue;
+        }
+
+        if logging_level.print_big_steps() {
+            println!(
+                "{}",
+                format!("Checking mutation {}...", mutation.name()).green()
+            );
+        }
+
+        let verified = verify_selftest_input::<P::Air>(
+            &mutated,
+            mutation.name(),
+            Some(&statement),
+            &context,
+            &logging_level,
+        );
+        if verified.is_ok() {
+            return Err(WinterCircomError::SecurityTestFailed {
+                mutation: String::from(mutation.name()),
+            });
+        }
+    }
+
+    if logging_level.print_big_steps() {
+        println!("{}", "All mutated proofs were rejected.".green());
+    }
+
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Locations of the artifacts of a security self-test.
+struct SelftestContext<'a> {
+    scratch: &'a ScratchSpace,
+    circuit_name: &'a str,
+    /// Directory of the compiled circuit.
+    circuit_dir: &'a str,
+    /// Absolute path of the `selftest/` directory of the scratch space.
+    selftest_dir: PathBuf,
+    scheme: SnarkScheme,
+}
+
+/// Run the verification path of [security_selftest] on the given input, whose
+/// Groth16 proof is written to `selftest/<name>.proof.json` and
+/// `selftest/<name>.public.json` in the scratch space.
+///
+/// Returns the exposed public inputs of the proof, which must be equal to the
+/// `expected` ones if given.
+fn verify_selftest_input<AIR>(
+    input: &Value,
+    name: &str,
+    expected: Option<&[String]>,
+    context: &SelftestContext,
+    logging_level: &LoggingLevel,
+) -> Result<Vec<String>, WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+{
+    let path = |extension: &str| {
+        let path = context.selftest_dir.join(format!("{}.{}", name, extension));
+        path.to_str().expect("non UTF-8 scratch path").to_owned()
+    };
+    let (witness, proof, public) = (path("wtns"), path("proof.json"), path("public.json"));
+
+    // GROTH16 PROOF
+
+    compute_selftest_witness(input, name, context, logging_level)?;
+    command_execution(
+        Executable::SnarkJS,
+        &[
+            context.scheme.prove_command(),
+            "verifier.zkey",
+            &witness,
+            &proof,
+            &public,
+        ],
+        Some(context.circuit_dir),
+        logging_level,
+    )?;
+    command_execution(
+        Executable::SnarkJS,
+        &[
+            context.scheme.verify_command(),
+            "verification_key.json",
+            &public,
+            &proof,
+        ],
+        Some(context.circuit_dir),
+        logging_level,
+    )?;
+
+    // OOD CONSISTENCY
+
+    let ood_data = OodData::from_input(input)?;
+    if !ood_data.is_consistent_with::<AIR>() {
+        return Err(WinterCircomError::InvalidProof(None));
+    }
+
+    // EXPOSED PUBLIC INPUTS
+
+    let data = fs::read_to_string(&public).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", public)),
+    })?;
+    let signals: Vec<String> =
+        serde_json::from_str(&data).map_err(|_| WinterCircomError::InvalidPublicSignals {
+            comment: String::from("expected an array of signals"),
+        })?;
+    let num_ood_signals = ood_data.constraint_evaluations.len() + 2 * ood_data.current.len();
+    let statement = signals.get(num_ood_signals..).unwrap_or_default().to_vec();
+    match expected {
+        Some(expected) if expected != statement.as_slice() => {
+            Err(WinterCircomError::InvalidPublicSignals {
+                comment: String::from("the exposed public inputs differ from the valid proof"),
+            })
+        }
+        _ => Ok(statement),
+    }
+}
+
+/// Compute the execution witness of the given input, written to
+/// `selftest/<name>.wtns` in the scratch space.
+///
+/// The input is piped to the witness generator where supported (see
+/// [STDIN_PATH]), and written to `selftest/<name>.json` otherwise.
+fn compute_selftest_witness(
+    input: &Value,
+    name: &str,
+    context: &SelftestContext,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let witness_file = context.selftest_dir.join(format!("{}.wtns", name));
+    let witness_file = witness_file.to_str().expect("non UTF-8 scratch path");
+
+    if let Some(stdin_path) = STDIN_PATH {
+        return command_execution_with_stdin(
+            Executable::Node,
+            &[
+                "verifier_js/generate_witness.js",
+                "verifier_js/verifier.wasm",
+                stdin_path,
+                witness_file,
+            ],
+            input.to_string().as_bytes(),
+            Some(context.circuit_dir),
+            logging_level,
+        );
+    }
+
+    let input_file = context.scratch.write(
+        context.circuit_name,
+        &format!("selftest/{}.json", name),
+        input.to_string().as_bytes(),
+    )?;
+    let input_file = canonicalize(input_file)?;
+
+    command_execution(
+        Executable::Node,
+        &[
+            "verifier_js/generate_witness.js",
+            "verifier_js/verifier.wasm",
+            input_file.to_str().expect("non UTF-8 scratch path"),
+            witness_file,
+        ],
+        Some(context.circuit_dir),
+        logging_level,
+    )
+}
+
+/// Flip the lowest bit of a field element or integer JSON value.
+///
+/// Flipping the lowest bit always yields a different element of the field, as
+/// the field modulus is odd. Returns `false`, leaving the value unchanged, if
+/// it is not numeric.
+fn flip_lowest_bit(value: &mut Value) -> bool {
+    let flipped = match &*value {
+        Value::String(s) => match Integer::from_str_radix(s, 10) {
+            Ok(mut n) => {
+                if n.is_odd() {
+                    n -= 1;
+                } else {
+                    n += 1;
+                }
+                Value::from(n.to_string())
+            }
+            Err(_) => return false,
+        },
+        Value::Number(n) => match n.as_u64() {
+            Some(n) => Value::from(n ^ 1),
+            None => return false,
+        },
+        _ => return false,
+    };
+    *value = flipped;
+    true
+}
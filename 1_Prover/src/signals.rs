@@ -0,0 +1,177 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Input signals of the `Verify` template defined in `circuits/verify.circom`.
+///
+/// This enumeration is the single source of truth for the names of the Circom
+/// input signals. It is used both by the JSON exporter, which writes the
+/// `input.json` file consumed by the witness generator, and by the code
+/// generator, which declares the public signals of the main component.
+///
+/// ## Stability
+///
+/// The names returned by [name](Signal::name) are part of the interface
+/// between this crate and the Circom circuits. They must never be changed
+/// without updating `circuits/verify.circom` accordingly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Signal {
+    AddicityRoot,
+    CommitmentData,
+    ConstraintCommitment,
+    ConstraintEvaluations,
+    ConstraintQueryProofs,
+    FriCommitments,
+    FriLayerProofs,
+    FriLayerQueries,
+    FriRemainder,
+    OodConstraintEvaluations,
+    OodFrameConstraintEvaluation,
+    OodTraceFrame,
+    PackedPublicInputs,
+    PowNonce,
+    PubCoinSeed,
+    PublicCommitment,
+    PublicInputs,
+    TraceCommitment,
+    TraceEvaluations,
+    TraceQueryProofs,
+}
+
+impl Signal {
+    /// All input signals of the `Verify` template, in alphabetical order.
+    pub const ALL: [Signal; 20] = [
+        Signal::AddicityRoot,
+        Signal::CommitmentData,
+        Signal::ConstraintCommitment,
+        Signal::ConstraintEvaluations,
+        Signal::ConstraintQueryProofs,
+        Signal::FriCommitments,
+        Signal::FriLayerProofs,
+        Signal::FriLayerQueries,
+        Signal::FriRemainder,
+        Signal::OodConstraintEvaluations,
+        Signal::OodFrameConstraintEvaluation,
+        Signal::OodTraceFrame,
+        Signal::PackedPublicInputs,
+        Signal::PowNonce,
+        Signal::PubCoinSeed,
+        Signal::PublicCommitment,
+        Signal::PublicInputs,
+        Signal::TraceCommitment,
+        Signal::TraceEvaluations,
+        Signal::TraceQueryProofs,
+    ];
+
+    /// Input signals always declared public in the generated main component.
+    ///
+    /// Their order defines the order of the Groth16 public signals in
+    /// `public.json`. Circuits generated with
+    /// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)
+    /// declare the [PublicCommitment](Signal::PublicCommitment) alone instead.
+    pub const PUBLIC: [Signal; 2] = [Signal::OodFrameConstraintEvaluation, Signal::OodTraceFrame];
+
+    /// Returns the name of the signal, as declared in `circuits/verify.circom`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Signal::AddicityRoot => "addicity_root",
+            Signal::CommitmentData => "commitment_data",
+            Signal::ConstraintCommitment => "constraint_commitment",
+            Signal::ConstraintEvaluations => "constraint_evaluations",
+            Signal::ConstraintQueryProofs => "constraint_query_proofs",
+            Signal::FriCommitments => "fri_commitments",
+            Signal::FriLayerProofs => "fri_layer_proofs",
+            Signal::FriLayerQueries => "fri_layer_queries",
+            Signal::FriRemainder => "fri_remainder",
+            Signal::OodConstraintEvaluations => "ood_constraint_evaluations",
+            Signal::OodFrameConstraintEvaluation => "ood_frame_constraint_evaluation",
+            Signal::OodTraceFrame => "ood_trace_frame",
+            Signal::PackedPublicInputs => "packed_public_inputs",
+            Signal::PowNonce => "pow_nonce",
+            Signal::PubCoinSeed => "pub_coin_seed",
+            Signal::PublicCommitment => "public_commitment",
+            Signal::PublicInputs => "public_inputs",
+            Signal::TraceCommitment => "trace_commitment",
+            Signal::TraceEvaluations => "trace_evaluations",
+            Signal::TraceQueryProofs => "trace_query_proofs",
+        }
+    }
+
+    /// Returns whether the signal is always a public signal of the main component.
+    pub fn is_public(&self) -> bool {
+        Signal::PUBLIC.contains(self)
+    }
+
+    /// Returns the public signals of the main component, in `public.json` order.
+    ///
+    /// [PublicInputs](Signal::PublicInputs) are appended to [Signal::PUBLIC] if
+    /// `expose_public_inputs` is set (see
+    /// [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs)),
+    /// or [PackedPublicInputs](Signal::PackedPublicInputs) if they are packed as
+    /// well (see [PublicInputLayout](crate::PublicInputLayout)).
+    ///
+    /// These are the values hashed into the single
+    /// [PublicCommitment](Signal::PublicCommitment) of circuits generated with
+    /// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals),
+    /// except that the public inputs are committed to unpacked.
+    pub fn public_signals(expose_public_inputs: bool, packed: bool) -> Vec<Signal> {
+        let mut signals = Signal::PUBLIC.to_vec();
+        match (expose_public_inputs, packed) {
+            (true, false) => signals.push(Signal::PublicInputs),
+            (true, true) => signals.push(Signal::PackedPublicInputs),
+            (false, _) => {}
+        }
+        signals
+    }
+
+    /// Returns the list of public signals, formatted for the declaration of the
+    /// Circom main component (e.g. `[ood_frame_constraint_evaluation, ood_trace_frame]`).
+    pub(crate) fn public_list(expose_public_inputs: bool, packed: bool) -> String {
+        format!(
+            "[{}]",
+            Signal::public_signals(expose_public_inputs, packed)
+                .iter()
+                .map(|s| s.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+// SIGNAL VALUES
+// ===========================================================================
+
+/// Builder for the JSON object of input signals, keyed by [Signal].
+///
+/// Using this builder instead of a raw JSON object guarantees that the keys
+/// written by the exporter are the ones expected by the circuit.
+pub(crate) struct SignalValues {
+    map: Map<String, Value>,
+}
+
+impl SignalValues {
+    pub fn new() -> Self {
+        Self { map: Map::new() }
+    }
+
+    /// Set the value of a signal, overwriting any previous value.
+    pub fn insert<T: Serialize>(&mut self, signal: Signal, value: T) {
+        self.map.insert(
+            String::from(signal.name()),
+            serde_json::to_value(value).expect("signal value should be serializable"),
+        );
+    }
+
+    /// Returns the JSON object of input signals.
+    ///
+    /// Panics if the value of any of the signals of [Signal::ALL] is missing.
+    pub fn into_json(self) -> Value {
+        for signal in Signal::ALL {
+            assert!(
+                self.map.contains_key(signal.name()),
+                "missing value for signal {}",
+                signal.name()
+            );
+        }
+        Value::Object(self.map)
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs;
+
+use colored::Colorize;
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+use crate::{
+    circom_create, circom_prove,
+    pipeline::Pipeline,
+    tenant::validate_circuit_name,
+    utils::{delete_file, LoggingLevel, WinterCircomError},
+    workspace::circuit_dir,
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Generate a circuit with [circom_create], compile it and generate its keys
+/// with a [Pipeline], then check that it is functional with a [smoke_test] on
+/// the given known-good trace.
+///
+/// This is meant to be run once, on the host generating the keys, before the
+/// circuit and its keys are distributed to the provers (see
+/// [distribute_artifacts](crate::distribute_artifacts)).
+pub fn circom_create_smoke_tested<P, const N: usize>(
+    proof_options: WinterCircomProofOptions<N>,
+    circuit_name: &str,
+    prover: P,
+    trace: <P as Prover>::Trace,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    circom_create::<P, N>(proof_options, circuit_name, logging_level)?;
+    Pipeline::new(circuit_name)
+        .compile()
+        .setup()
+        .run(logging_level)?;
+    smoke_test(prover, trace, circuit_name, logging_level)
+}
+
+/// Prove a known-good trace with a circuit and verify the proof, to check that
+/// the circuit and its keys are functional.
+///
+/// The Winterfell proof of the trace is converted with [circom_prove], and the
+/// witness, Groth16 proof and verification stages of the [Pipeline] are then
+/// run. The error of the first failing step is returned, and its artifacts are
+/// kept for inspection. Otherwise, `proof.json` and `public.json` are removed
+/// once verified, so that the smoke proof is never mistaken for a real one.
+///
+/// The `proof.json` and `public.json` files of a real proof, if any, are
+/// restored once the smoke test completes, whether it passes or not.
+///
+/// The trace should be as small as the circuit allows, e.g. the trace of a
+/// trivial computation, as the smoke test lasts as long as a real proof.
+pub fn smoke_test<P>(
+    prover: P,
+    trace: <P as Prover>::Trace,
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError>
+where
+    P: Prover<BaseField = BaseElement>,
+    <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+{
+    validate_circuit_name(circuit_name)?;
+    if logging_level.print_big_steps() {
+        println!("{}", "Running smoke test...".green());
+    }
+
+    let dir = circuit_dir(circuit_name);
+    let proofs = ["proof.json", "public.json"]
+        .iter()
+        .map(|file| (dir.join(file), fs::read(dir.join(file)).ok()))
+        .collect::<Vec<_>>();
+
+    let result = circom_prove(prover, trace, circuit_name, LoggingLevel::Quiet).and_then(|_| {
+        Pipeline::new(circuit_name)
+            .witness()
+            .prove()
+            .verify()
+            .run(LoggingLevel::Quiet)
+    });
+
+    for (path, proof) in proofs {
+        match proof {
+            Some(proof) => fs::write(&path, proof).map_err(|e| WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(format!("restoring {}", path.display())),
+            })?,
+            None if result.is_ok() => delete_file(path.to_string_lossy().into_owned()),
+            None => {}
+        }
+    }
+    result?;
+
+    if logging_level.print_big_steps() {
+        println!("{}", "Smoke test passed.".green());
+    }
+
+    Ok(())
+}
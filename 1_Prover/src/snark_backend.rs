@@ -0,0 +1,174 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    keys::random_entropy,
+    pipeline::{export_vk_plan, keys_plan, Stage},
+    utils::{LoggingLevel, WinterCircomError},
+    SnarkScheme,
+};
+
+thread_local! {
+    static SNARK_BACKEND: RefCell<Option<Rc<dyn SnarkBackend>>> = RefCell::new(None);
+}
+
+/// Backend of the SNARK steps of the pipeline: the generation of the
+/// circuit-specific keys, and the generation and verification of the proofs.
+///
+/// The [Setup](Stage::Setup), [Prove](Stage::Prove) and [Verify](Stage::Verify)
+/// stages of the [Pipeline](crate::Pipeline) run the steps of the backend of
+/// the current thread, set with [with_snark_backend], and the ones of the
+/// [SnarkJSBackend] otherwise. Circuits are still compiled by Circom, and
+/// their witnesses computed by snarkjs.
+///
+/// The steps read and write the files of the directory of the circuit, so that
+/// the other functions of this crate read the outputs of any backend:
+///
+/// - [setup](SnarkBackend::setup) generates the proving key of the compiled
+/// `verifier.r1cs`, in a file of the backend's choosing,
+/// - [export_vk](SnarkBackend::export_vk) writes `verification_key.json`, on
+/// the hosts which need it (see [HostRole](crate::HostRole)),
+/// - [prove](SnarkBackend::prove) reads the `witness.wtns` (or the
+/// `input.json`) of the [ScratchSpace](crate::ScratchSpace), and writes
+/// `proof.json` and `public.json`,
+/// - [verify](SnarkBackend::verify) checks them against
+/// `verification_key.json`.
+///
+/// The JSON files are in the snarkjs format. A remote proving service, for
+/// instance, only replaces the prove step:
+///
+/// ```ignore
+/// impl SnarkBackend for RemoteProver {
+///     fn name(&self) -> &'static str {
+///         "remote"
+///     }
+///
+///     fn prove(&self, circuit_name: &str, _: LoggingLevel) -> Result<(), WinterCircomError> {
+///         // upload the witness, download proof.json and public.json
+///     }
+///
+///     // setup, export_vk and verify delegate to SnarkJSBackend
+/// }
+///
+/// with_snark_backend(Rc::new(RemoteProver::new(url)), || {
+///     Pipeline::new("sum").witness().prove().run(LoggingLevel::Default)
+/// })?;
+/// ```
+///
+/// The steps are audited as the stages they run in, recording the
+/// [name](SnarkBackend::name) of the backend. [Plans](crate::plan) and the
+/// asynchronous stages only run the commands of the [SnarkJSBackend].
+pub trait SnarkBackend {
+    /// Name of the backend, recorded in the audit log.
+    fn name(&self) -> &'static str;
+
+    /// Generate the proving key of a circuit compiled by
+    /// [circom_compile](crate::circom_compile).
+    fn setup(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError>;
+
+    /// Write the `verification_key.json` file of the proving key generated by
+    /// [setup](SnarkBackend::setup).
+    fn export_vk(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError>;
+
+    /// Generate the proof of the witness computed by
+    /// [compute_witness](crate::compute_witness), written to `proof.json` and
+    /// `public.json`.
+    fn prove(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError>;
+
+    /// Verify the proof generated by [prove](SnarkBackend::prove), returning an
+    /// error if it is invalid.
+    fn verify(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError>;
+}
+
+/// [SnarkBackend] running snarkjs, and the rapidsnark prover on hosts
+/// configured with the [Rapidsnark](crate::ProverBackend::Rapidsnark) prover
+/// backend.
+///
+/// The proving key is written to `verifier.zkey`, with a contribution of random
+/// entropy for the schemes with a circuit-specific setup.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SnarkJSBackend;
+
+impl SnarkBackend for SnarkJSBackend {
+    fn name(&self) -> &'static str {
+        "snarkjs"
+    }
+
+    fn setup(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        Stage::Setup.check_inputs(circuit_name)?;
+        keys_plan(
+            circuit_name,
+            SnarkScheme::of_circuit(circuit_name)?,
+            &random_entropy(),
+        )?
+        .run(&logging_level)
+    }
+
+    fn export_vk(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        export_vk_plan(circuit_name).run(&logging_level)
+    }
+
+    fn prove(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        Stage::Prove.plan(circuit_name)?.run(&logging_level)
+    }
+
+    fn verify(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        Stage::Verify.plan(circuit_name)?.run(&logging_level)
+    }
+}
+
+/// Run a closure with the given [SnarkBackend] running the SNARK steps of the
+/// pipeline on the current thread.
+///
+/// The previous backend is restored when the closure returns or panics.
+pub fn with_snark_backend<T, F: FnOnce() -> T>(backend: Rc<dyn SnarkBackend>, f: F) -> T {
+    struct Restore(Option<Rc<dyn SnarkBackend>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SNARK_BACKEND.with(|backend| *backend.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(SNARK_BACKEND.with(|current| current.borrow_mut().replace(backend)));
+    f()
+}
+
+/// Returns the [SnarkBackend] of the current thread.
+pub(crate) fn current_snark_backend() -> Rc<dyn SnarkBackend> {
+    SNARK_BACKEND
+        .with(|backend| backend.borrow().clone())
+        .unwrap_or_else(|| Rc::new(SnarkJSBackend))
+}
@@ -0,0 +1,262 @@
+use std::{fs, path::PathBuf};
+
+use serde_json::Value;
+
+use crate::{
+    solidity::{point, read_json, twist_point},
+    tenant::validate_circuit_name,
+    utils::{check_file, WinterCircomError},
+    workspace::circuit_dir,
+    Groth16Calldata, SnarkScheme,
+};
+
+/// Name of the crate of the Solana program, in the directory of the circuit.
+const SOLANA_PROGRAM: &str = "solana_verifier";
+
+/// Big-endian modulus of the base field of BN254, negating the point `A` of
+/// the proofs.
+const BN254_BASE_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Export the Solana program verifying the Groth16 proofs of a circuit, and
+/// return the path of its crate, `target/circom/<circuit_name>/solana_verifier/`.
+///
+/// The verification key of `verification_key.json` is compiled into the
+/// program, which checks the proofs with the `alt_bn128` syscalls through the
+/// [groth16-solana](https://github.com/Lightprotocol/groth16-solana) crate, and
+/// fails the instruction if the proof is invalid. The program is built with
+/// `cargo build-sbf` and deployed with `solana program deploy`, and the proofs
+/// are then submitted with their [solana_instruction_data].
+pub fn export_solana_verifier(circuit_name: &str) -> Result<PathBuf, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    if scheme != SnarkScheme::Groth16 {
+        return Err(WinterCircomError::UnsupportedScheme {
+            scheme: String::from(scheme.name()),
+            comment: String::from("only Groth16 proofs are verified on Solana"),
+        });
+    }
+    let path = circuit_dir(circuit_name).join("verification_key.json");
+    check_file(
+        path.to_string_lossy().into_owned(),
+        Some("did you generate the circuit-specific keys?"),
+    )?;
+    let verification_key = read_json(path)?;
+
+    let dir = circuit_dir(circuit_name).join(SOLANA_PROGRAM);
+    let files = [
+        (dir.join("Cargo.toml"), program_manifest(circuit_name)),
+        (
+            dir.join("src").join("lib.rs"),
+            program_source(circuit_name, &verification_key)?,
+        ),
+    ];
+    fs::create_dir_all(dir.join("src"))
+        .and_then(|_| {
+            files
+                .iter()
+                .try_for_each(|(path, contents)| fs::write(path, contents))
+        })
+        .map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(String::from("writing the Solana verifier program")),
+        })?;
+
+    Ok(dir)
+}
+
+/// Returns the instruction data of the Solana program of a Groth16 circuit
+/// (see [export_solana_verifier]) for the proof of the last
+/// [circom_prove](crate::circom_prove) of the circuit.
+pub fn solana_instruction_data(circuit_name: &str) -> Result<Vec<u8>, WinterCircomError> {
+    instruction_data(&Groth16Calldata::of_circuit(circuit_name)?)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the instruction data of the calldata of a Groth16 proof: the point
+/// `-A` (64 bytes), `B` (128 bytes) and `C` (64 bytes) of the proof, followed
+/// by the public signals (32 bytes each).
+///
+/// All values are big-endian, and the coefficients of the coordinates of `B`
+/// are in the order of the calldata, as expected by the `alt_bn128` syscalls.
+/// `A` is negated for the pairing check of groth16-solana.
+pub(crate) fn instruction_data(calldata: &Groth16Calldata) -> Result<Vec<u8>, WinterCircomError> {
+    let mut data = Vec::with_capacity(256 + 32 * calldata.input.len());
+    data.extend_from_slice(&big_endian(&calldata.a[0])?);
+    data.extend_from_slice(&negate(big_endian(&calldata.a[1])?));
+    for value in calldata.b.iter().flatten().chain(&calldata.c) {
+        data.extend_from_slice(&big_endian(value)?);
+    }
+    for signal in &calldata.input {
+        data.extend_from_slice(&big_endian(signal)?);
+    }
+    Ok(data)
+}
+
+fn program_manifest(circuit_name: &str) -> String {
+    let name: String = circuit_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!(
+        "[package]\n\
+        name = \"{}-verifier\"\n\
+        version = \"0.1.0\"\n\
+        edition = \"2021\"\n\
+        \n\
+        [lib]\n\
+        crate-type = [\"cdylib\", \"lib\"]\n\
+        \n\
+        [dependencies]\n\
+        groth16-solana = \"0.0.3\"\n\
+        solana-program = \"1.18\"\n\
+        \n\
+        [workspace]\n",
+        name.to_lowercase()
+    )
+}
+
+fn program_source(
+    circuit_name: &str,
+    verification_key: &Value,
+) -> Result<String, WinterCircomError> {
+    let g1 = |value: &Value| -> Result<String, WinterCircomError> {
+        let [x, y] = point(value)?;
+        byte_array(&[x, y])
+    };
+    let g2 = |value: &Value| -> Result<String, WinterCircomError> {
+        let [x, y] = twist_point(value)?;
+        byte_array(&[x[0].clone(), x[1].clone(), y[0].clone(), y[1].clone()])
+    };
+    let ic = verification_key["IC"]
+        .as_array()
+        .ok_or_else(|| invalid_data("expected the IC points"))?
+        .iter()
+        .map(|value| g1(value).map(|point| format!("        {},\n", point)))
+        .collect::<Result<String, _>>()?;
+    let num_public = verification_key["nPublic"]
+        .as_u64()
+        .ok_or_else(|| invalid_data("expected the number of public signals"))?;
+
+    Ok(format!(
+        r#"//! Solana program verifying the Groth16 proofs of the `{circuit_name}` circuit,
+//! generated by winter-circom-prover.
+
+use groth16_solana::groth16::{{Groth16Verifier, Groth16Verifyingkey}};
+use solana_program::{{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey,
+}};
+
+const NR_PUBLIC_INPUTS: usize = {num_public};
+
+const VERIFYING_KEY: Groth16Verifyingkey = Groth16Verifyingkey {{
+    nr_pubinputs: NR_PUBLIC_INPUTS,
+    vk_alpha_g1: {alpha},
+    vk_beta_g2: {beta},
+    vk_gamme_g2: {gamma},
+    vk_delta_g2: {delta},
+    vk_ic: &[
+{ic}    ],
+}};
+
+entrypoint!(process_instruction);
+
+/// Verify the proof of the instruction data: the point -A (64 bytes), B (128
+/// bytes) and C (64 bytes) of the proof, followed by the public signals (32
+/// bytes each), big-endian.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {{
+    if data.len() != 256 + 32 * NR_PUBLIC_INPUTS {{
+        return Err(ProgramError::InvalidInstructionData);
+    }}
+    let proof_a: [u8; 64] = data[..64].try_into().unwrap();
+    let proof_b: [u8; 128] = data[64..192].try_into().unwrap();
+    let proof_c: [u8; 64] = data[192..256].try_into().unwrap();
+    let mut public_inputs = [[0u8; 32]; NR_PUBLIC_INPUTS];
+    for (input, chunk) in public_inputs.iter_mut().zip(data[256..].chunks_exact(32)) {{
+        input.copy_from_slice(chunk);
+    }}
+
+    let mut verifier =
+        Groth16Verifier::new(&proof_a, &proof_b, &proof_c, &public_inputs, &VERIFYING_KEY)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+    match verifier.verify() {{
+        Ok(true) => Ok(()),
+        _ => Err(ProgramError::InvalidArgument),
+    }}
+}}
+"#,
+        circuit_name = circuit_name,
+        num_public = num_public,
+        alpha = g1(&verification_key["vk_alpha_1"])?,
+        beta = g2(&verification_key["vk_beta_2"])?,
+        gamma = g2(&verification_key["vk_gamma_2"])?,
+        delta = g2(&verification_key["vk_delta_2"])?,
+        ic = ic,
+    ))
+}
+
+/// Returns the Rust array of the big-endian bytes of decimal field elements.
+fn byte_array(values: &[String]) -> Result<String, WinterCircomError> {
+    let bytes = values
+        .iter()
+        .map(|value| big_endian(value))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!(
+        "[{}]",
+        bytes
+            .iter()
+            .flatten()
+            .map(|byte| format!("{:#04x}", byte))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// Returns the 32-byte big-endian encoding of a decimal field element.
+fn big_endian(value: &str) -> Result<[u8; 32], WinterCircomError> {
+    let mut bytes = [0u8; 32];
+    for digit in value.chars() {
+        let mut carry = digit
+            .to_digit(10)
+            .ok_or_else(|| invalid_data("expected decimal field elements"))?;
+        for byte in bytes.iter_mut().rev() {
+            let product = *byte as u32 * 10 + carry;
+            *byte = product as u8;
+            carry = product >> 8;
+        }
+        if carry != 0 {
+            return Err(invalid_data("field element larger than 256 bits"));
+        }
+    }
+    Ok(bytes)
+}
+
+/// Returns the opposite of an element of the base field of BN254.
+fn negate(value: [u8; 32]) -> [u8; 32] {
+    if value == [0; 32] {
+        return value;
+    }
+    let mut result = [0u8; 32];
+    let mut borrow = 0;
+    for i in (0..32).rev() {
+        let difference = BN254_BASE_MODULUS[i] as i16 - value[i] as i16 - borrow;
+        result[i] = difference.rem_euclid(256) as u8;
+        borrow = (difference < 0) as i16;
+    }
+    result
+}
+
+fn invalid_data(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidSnarkProof {
+        comment: String::from(comment),
+    }
+}
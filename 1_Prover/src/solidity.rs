@@ -0,0 +1,318 @@
+use std::{fmt::Display, fs, path::PathBuf};
+
+use serde_json::{json, Value};
+use winterfell::math::fields::f256::U256;
+
+use crate::{
+    tenant::validate_circuit_name,
+    utils::{check_file, command_execution, Executable, LoggingLevel, WinterCircomError},
+    workspace::circuit_dir,
+    SnarkScheme,
+};
+
+/// Modulus of the base field of BN254, the field of the coordinates of the
+/// points of the proofs.
+const BN254_BASE_MODULUS: &str =
+    "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+
+/// Export the Solidity verifier contract of a circuit to
+/// `target/circom/<circuit_name>/verifier.sol`, and return its path.
+///
+/// The contract is generated by `snarkjs zkey export solidityverifier` from the
+/// proving key, `verifier.zkey`, and verifies the proofs of the
+/// [SnarkScheme] of the circuit. Its `verifyProof` function takes the
+/// [Groth16Calldata] of a proof for Groth16 circuits.
+pub fn export_solidity_verifier(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<PathBuf, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let dir = circuit_dir(circuit_name);
+    check_file(
+        dir.join("verifier.zkey").to_string_lossy().into_owned(),
+        Some("did you generate the circuit-specific keys?"),
+    )?;
+
+    command_execution(
+        Executable::SnarkJS,
+        &["zkesv", "verifier.zkey", "verifier.sol"],
+        dir.to_str(),
+        &logging_level,
+    )?;
+
+    Ok(dir.join("verifier.sol"))
+}
+
+/// Arguments of the `verifyProof` function of the Solidity verifier of a
+/// Groth16 circuit (see [export_solidity_verifier]), as decimal strings.
+///
+/// This is the calldata printed by `snarkjs zkey export soliditycalldata`: the
+/// points `a` and `c` of the proof are given by their affine coordinates, the
+/// coordinates of `b` in the extension field are swapped, as expected by the
+/// pairing precompile, and `input` are the public signals of `public.json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Groth16Calldata {
+    pub a: [String; 2],
+    pub b: [[String; 2]; 2],
+    pub c: [String; 2],
+    pub input: Vec<String>,
+}
+
+impl Groth16Calldata {
+    /// Returns the calldata of the proof of the last
+    /// [circom_prove](crate::circom_prove) of a Groth16 circuit, read from its
+    /// `proof.json` and `public.json` files.
+    pub fn of_circuit(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        let dir = groth16_circuit_dir(
+            circuit_name,
+            "only Groth16 proofs are formatted, run snarkjs zkey export soliditycalldata",
+        )?;
+        let proof = read_json(dir.join("proof.json"))?;
+        let public_signals = read_json(dir.join("public.json"))?;
+        Groth16Calldata::from_json(&proof, &public_signals)
+    }
+
+    /// Returns the calldata of a Groth16 proof and its public signals, in the
+    /// snarkjs JSON format.
+    pub fn from_json(proof: &Value, public_signals: &Value) -> Result<Self, WinterCircomError> {
+        if proof["protocol"] != "groth16" {
+            return Err(invalid_proof("expected a Groth16 proof"));
+        }
+
+        let a = point(&proof["pi_a"])?;
+        let b = twist_point(&proof["pi_b"])?;
+        let c = point(&proof["pi_c"])?;
+        let input = public_signals
+            .as_array()
+            .ok_or_else(|| WinterCircomError::InvalidPublicSignals {
+                comment: String::from("expected an array of signals"),
+            })?
+            .iter()
+            .map(|signal| {
+                decimal(signal).ok_or_else(|| WinterCircomError::InvalidPublicSignals {
+                    comment: String::from("expected decimal field elements"),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Groth16Calldata { a, b, c, input })
+    }
+}
+
+impl Display for Groth16Calldata {
+    /// Formats the calldata as the arguments of `verifyProof`, in the format of
+    /// `snarkjs zkey export soliditycalldata` with decimal values, e.g. for
+    /// `cast call` or Remix.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let array = |values: &[String]| {
+            format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(|value| format!("\"{}\"", value))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        };
+        write!(
+            f,
+            "{},[{},{}],{},{}",
+            array(&self.a),
+            array(&self.b[0]),
+            array(&self.b[1]),
+            array(&self.c),
+            array(&self.input)
+        )
+    }
+}
+
+/// Groth16 proof of a `proof.json` file, by the affine coordinates of its
+/// points, with its encoding of
+/// [EIP-197](https://eips.ethereum.org/EIPS/eip-197).
+///
+/// The EIP-197 encoding is the input format of the BN254 pairing precompile of
+/// Ethereum, and of the pairing-check contracts built on it, so that the proof
+/// can be passed to any of them as is:
+///
+/// ```ignore
+/// let proof = Groth16Proof::of_circuit("sum")?;
+/// let bytes = proof.to_eip197_bytes();
+/// assert_eq!(Groth16Proof::from_eip197_bytes(&bytes)?, proof);
+/// ```
+///
+/// Each coordinate is a 32-byte big-endian word, and the coordinates of `b` in
+/// the extension field have their imaginary coefficient first, as in the
+/// [Groth16Calldata] of the proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub a: [U256; 2],
+    pub b: [[U256; 2]; 2],
+    pub c: [U256; 2],
+}
+
+impl Groth16Proof {
+    /// Number of bytes of the EIP-197 encoding of a proof: the points `a` (64
+    /// bytes), `b` (128 bytes) and `c` (64 bytes).
+    pub const EIP197_BYTES: usize = 256;
+
+    /// Returns the proof of the last [circom_prove](crate::circom_prove) of a
+    /// Groth16 circuit, read from its `proof.json` file.
+    pub fn of_circuit(circuit_name: &str) -> Result<Self, WinterCircomError> {
+        let dir = groth16_circuit_dir(circuit_name, "only Groth16 proofs are encoded")?;
+        Groth16Proof::from_json(&read_json(dir.join("proof.json"))?)
+    }
+
+    /// Returns a Groth16 proof in the snarkjs JSON format.
+    pub fn from_json(proof: &Value) -> Result<Self, WinterCircomError> {
+        if proof["protocol"] != "groth16" {
+            return Err(invalid_proof("expected a Groth16 proof"));
+        }
+        let [a_x, a_y] = point(&proof["pi_a"])?;
+        let [[b_x1, b_x0], [b_y1, b_y0]] = twist_point(&proof["pi_b"])?;
+        let [c_x, c_y] = point(&proof["pi_c"])?;
+        let value = |decimal: String| {
+            U256::from_str_radix(&decimal, 10)
+                .map_err(|_| invalid_proof("expected decimal coordinates"))
+        };
+        Ok(Groth16Proof {
+            a: [value(a_x)?, value(a_y)?],
+            b: [[value(b_x1)?, value(b_x0)?], [value(b_y1)?, value(b_y0)?]],
+            c: [value(c_x)?, value(c_y)?],
+        })
+    }
+
+    /// Returns the proof in the snarkjs JSON format of `proof.json`.
+    pub fn to_json(&self) -> Value {
+        let g1 = |[x, y]: &[U256; 2]| json!([x.to_string(), y.to_string(), "1"]);
+        let g2 = |[x1, x0]: &[U256; 2]| json!([x0.to_string(), x1.to_string()]);
+        json!({
+            "pi_a": g1(&self.a),
+            "pi_b": [g2(&self.b[0]), g2(&self.b[1]), ["1", "0"]],
+            "pi_c": g1(&self.c),
+            "protocol": "groth16",
+            "curve": "bn128",
+        })
+    }
+
+    /// Encode the proof as in EIP-197, into
+    /// [EIP197_BYTES](Self::EIP197_BYTES) bytes.
+    pub fn to_eip197_bytes(&self) -> Vec<u8> {
+        self.a
+            .iter()
+            .chain(self.b.iter().flatten())
+            .chain(&self.c)
+            .flat_map(|value| {
+                let mut word = [0u8; 32];
+                for (i, byte) in word.iter_mut().rev().enumerate() {
+                    *byte = ((*value >> (8 * i as u32)).low_u64() & 0xff) as u8;
+                }
+                word
+            })
+            .collect()
+    }
+
+    /// Decode a proof encoded as in EIP-197, see
+    /// [to_eip197_bytes](Self::to_eip197_bytes).
+    ///
+    /// Returns an error if there are not exactly
+    /// [EIP197_BYTES](Self::EIP197_BYTES) bytes, or if a coordinate is not an
+    /// element of the base field of BN254. Whether the points are on the curve
+    /// is left to the pairing check.
+    pub fn from_eip197_bytes(bytes: &[u8]) -> Result<Self, WinterCircomError> {
+        if bytes.len() != Self::EIP197_BYTES {
+            return Err(invalid_proof(&format!(
+                "expected {} bytes, {} given",
+                Self::EIP197_BYTES,
+                bytes.len()
+            )));
+        }
+        let modulus = U256::from_str_radix(BN254_BASE_MODULUS, 10)
+            .expect("the modulus should be a decimal integer");
+        let words = bytes
+            .chunks_exact(32)
+            .map(|word| {
+                let value = word.iter().fold(U256::from(0u8), |value, byte| {
+                    (value << 8u32) | U256::from(*byte)
+                });
+                if value < modulus {
+                    Ok(value)
+                } else {
+                    Err(invalid_proof("coordinate not lower than the modulus"))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Groth16Proof {
+            a: [words[0], words[1]],
+            b: [[words[2], words[3]], [words[4], words[5]]],
+            c: [words[6], words[7]],
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the directory of a circuit, checking that it is a Groth16 circuit.
+fn groth16_circuit_dir(circuit_name: &str, comment: &str) -> Result<PathBuf, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    if scheme != SnarkScheme::Groth16 {
+        return Err(WinterCircomError::UnsupportedScheme {
+            scheme: String::from(scheme.name()),
+            comment: String::from(comment),
+        });
+    }
+    Ok(circuit_dir(circuit_name))
+}
+
+pub(crate) fn read_json(path: PathBuf) -> Result<Value, WinterCircomError> {
+    let data = fs::read_to_string(&path).map_err(|_| WinterCircomError::FileNotFound {
+        file: path.to_string_lossy().into_owned(),
+        comment: Some(String::from("did you run circom_prove?")),
+    })?;
+    serde_json::from_str(&data).map_err(|e| WinterCircomError::InvalidSnarkProof {
+        comment: format!("reading {}: {}", path.display(), e),
+    })
+}
+
+/// Returns the affine coordinates of a point of G1 given in projective
+/// coordinates, with a third coordinate of 1.
+pub(crate) fn point(value: &Value) -> Result<[String; 2], WinterCircomError> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([x, y, z]) if *z == "1" => Ok([coordinate(x)?, coordinate(y)?]),
+        _ => Err(invalid_proof("expected a point of G1 of three coordinates")),
+    }
+}
+
+/// Returns the affine coordinates of a point of G2 given in projective
+/// coordinates, with a third coordinate of 1, with the coefficients of each
+/// coordinate swapped.
+pub(crate) fn twist_point(value: &Value) -> Result<[[String; 2]; 2], WinterCircomError> {
+    let swapped = |value: &Value| match value.as_array().map(Vec::as_slice) {
+        Some([c0, c1]) => Ok([coordinate(c1)?, coordinate(c0)?]),
+        _ => Err(invalid_proof("expected coordinates of two coefficients")),
+    };
+    match value.as_array().map(Vec::as_slice) {
+        Some([x, y, z]) if *z == json!(["1", "0"]) => Ok([swapped(x)?, swapped(y)?]),
+        _ => Err(invalid_proof("expected a point of G2 of three coordinates")),
+    }
+}
+
+fn coordinate(value: &Value) -> Result<String, WinterCircomError> {
+    decimal(value).ok_or_else(|| invalid_proof("expected decimal coordinates"))
+}
+
+/// Returns the decimal string of a field element.
+fn decimal(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .filter(|value| U256::from_str_radix(value, 10).is_ok())
+        .map(String::from)
+}
+
+fn invalid_proof(comment: &str) -> WinterCircomError {
+    WinterCircomError::InvalidSnarkProof {
+        comment: String::from(comment),
+    }
+}
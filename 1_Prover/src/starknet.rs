@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+use crate::{
+    tenant::validate_circuit_name,
+    utils::{
+        check_file, command_execution, command_output, Executable, LoggingLevel, WinterCircomError,
+    },
+    workspace::circuit_dir,
+    SnarkScheme,
+};
+
+/// Name of the Cairo project of the verifier, in the directory of the circuit.
+const CAIRO_PROJECT: &str = "cairo_verifier";
+
+/// Export the Cairo verifier of a Groth16 circuit, to verify its proofs on
+/// StarkNet, and return the path of the Cairo project,
+/// `target/circom/<circuit_name>/cairo_verifier/`.
+///
+/// The project is generated by `garaga gen` from `verification_key.json`: its
+/// contract verifies the Groth16 proofs of the circuit over BN254 and returns
+/// their public signals, and is declared and deployed with the StarkNet
+/// tooling (e.g. `scarb` and `starkli`). The proofs are then submitted with
+/// their [starknet_calldata]. [Garaga](https://github.com/keep-starknet-strange/garaga)
+/// must be installed, e.g. with `pip install garaga`.
+pub fn export_cairo_verifier(
+    circuit_name: &str,
+    logging_level: LoggingLevel,
+) -> Result<PathBuf, WinterCircomError> {
+    let dir = groth16_circuit_dir(circuit_name)?;
+    check_file(
+        dir.join("verification_key.json")
+            .to_string_lossy()
+            .into_owned(),
+        Some("did you generate the circuit-specific keys?"),
+    )?;
+
+    command_execution(
+        Executable::Garaga,
+        &[
+            "gen",
+            "--system",
+            "groth16",
+            "--vk",
+            "verification_key.json",
+            "--project-name",
+            CAIRO_PROJECT,
+        ],
+        dir.to_str(),
+        &logging_level,
+    )?;
+
+    Ok(dir.join(CAIRO_PROJECT))
+}
+
+/// Returns the calldata of the Cairo verifier of a Groth16 circuit (see
+/// [export_cairo_verifier]) for the proof of the last
+/// [circom_prove](crate::circom_prove) of the circuit, as an array of felts in
+/// decimal or hexadecimal strings.
+///
+/// The felts encode the proof, its public signals and the hints of the pairing
+/// check of the verifier, which are computed by `garaga calldata` from
+/// `proof.json`, `public.json` and `verification_key.json`.
+pub fn starknet_calldata(circuit_name: &str) -> Result<Vec<String>, WinterCircomError> {
+    let dir = groth16_circuit_dir(circuit_name)?;
+    let paths = ["verification_key.json", "proof.json", "public.json"]
+        .iter()
+        .map(|file| {
+            let path = dir.join(file).to_string_lossy().into_owned();
+            check_file(path.clone(), Some("did you prove the circuit?")).map(|_| path)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let output = command_output(
+        Executable::Garaga,
+        &[
+            "calldata",
+            "--system",
+            "groth16",
+            "--vk",
+            &paths[0],
+            "--proof",
+            &paths[1],
+            "--public-inputs",
+            &paths[2],
+            "--format",
+            "starkli",
+        ],
+    )?;
+    parse_felts(&output)
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Returns the directory of a circuit, checking that it is a Groth16 circuit.
+fn groth16_circuit_dir(circuit_name: &str) -> Result<PathBuf, WinterCircomError> {
+    validate_circuit_name(circuit_name)?;
+    let scheme = SnarkScheme::of_circuit(circuit_name)?;
+    if scheme != SnarkScheme::Groth16 {
+        return Err(WinterCircomError::UnsupportedScheme {
+            scheme: String::from(scheme.name()),
+            comment: String::from("only Groth16 proofs are verified on StarkNet"),
+        });
+    }
+    Ok(circuit_dir(circuit_name))
+}
+
+/// Parse the felts of the calldata printed by `garaga calldata`, separated by
+/// whitespace.
+pub(crate) fn parse_felts(output: &str) -> Result<Vec<String>, WinterCircomError> {
+    output
+        .split_whitespace()
+        .map(|felt| {
+            let is_felt = match felt.strip_prefix("0x") {
+                Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+                None => felt.chars().all(|c| c.is_ascii_digit()),
+            };
+            if is_felt {
+                Ok(String::from(felt))
+            } else {
+                Err(WinterCircomError::InvalidSnarkProof {
+                    comment: format!("garaga printed an invalid felt: {}", felt),
+                })
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+use std::{fmt, fs, path::Path};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{signals::Signal, utils::WinterCircomError};
+
+/// Counts and sizes of the input signals of a proof, read from its
+/// `input.json`.
+///
+/// These statistics are printed after the artifact sizes of
+/// [circom_prove](crate::circom_prove) with the
+/// [Timings](crate::LoggingLevel::Timings) logging level, and are serializable
+/// so that they can be exported to monitoring dashboards.
+///
+/// ## Padding
+///
+/// The FRI layers of `input.json` are padded with zeroes to the depth and the
+/// number of queries of the first layer (see [FriLayer](crate::FriLayer)). The
+/// path lengths of [fri_path_lengths](ProofStats::fri_path_lengths) are those
+/// of the unpadded layers, a Merkle node being zero with negligible probability, while
+/// [total_elements](ProofStats::total_elements) counts the padding, which is
+/// part of the inputs of the circuit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProofStats {
+    /// Number of queries of the proof.
+    pub num_queries: usize,
+
+    /// Length of the Merkle paths of the trace queries.
+    pub trace_path_length: usize,
+
+    /// Length of the Merkle paths of the constraint queries.
+    pub constraint_path_length: usize,
+
+    /// Length of the Merkle paths of each FRI layer, empty for remainder-only
+    /// proofs.
+    pub fri_path_lengths: Vec<usize>,
+
+    /// Number of elements of the OOD trace frame, twice the trace width.
+    pub ood_frame_size: usize,
+
+    /// Number of field elements of all the input signals.
+    pub total_elements: usize,
+}
+
+impl ProofStats {
+    /// Returns the statistics of the `input.json` file at `path`, e.g. the
+    /// [input_path](crate::ProveArtifacts::input_path) of a proof.
+    pub fn from_input_json<P: AsRef<Path>>(path: P) -> Result<Self, WinterCircomError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("reading {}", path.display())),
+        })?;
+        let input = serde_json::from_str(&data).map_err(|e| invalid_input(&e.to_string()))?;
+        Self::from_input(&input)
+    }
+
+    /// Returns the statistics of the input signals of a proof, e.g. the
+    /// [input](crate::ProveArtifacts::input) of a proof.
+    pub fn from_input(input: &Value) -> Result<Self, WinterCircomError> {
+        let trace_query_proofs = array(input, Signal::TraceQueryProofs)?;
+        let constraint_query_proofs = array(input, Signal::ConstraintQueryProofs)?;
+        let num_fri_layers = array(input, Signal::FriCommitments)?
+            .len()
+            .saturating_sub(1);
+        let fri_path_lengths = array(input, Signal::FriLayerProofs)?
+            .iter()
+            .take(num_fri_layers)
+            .map(|layer| {
+                layer
+                    .as_array()
+                    .map(|paths| paths.iter().map(unpadded_length).max().unwrap_or(0))
+                    .ok_or_else(|| invalid_input("expected the paths of each FRI layer"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ProofStats {
+            num_queries: trace_query_proofs.len(),
+            trace_path_length: trace_query_proofs.first().map_or(0, count_elements),
+            constraint_path_length: constraint_query_proofs.first().map_or(0, count_elements),
+            fri_path_lengths,
+            ood_frame_size: count_elements(&input[Signal::OodTraceFrame.name()]),
+            total_elements: count_elements(input),
+        })
+    }
+}
+
+impl fmt::Display for ProofStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "    {} queries, Merkle paths of {} (trace), {} (constraints) and {:?} (FRI) nodes",
+            self.num_queries,
+            self.trace_path_length,
+            self.constraint_path_length,
+            self.fri_path_lengths
+        )?;
+        write!(
+            f,
+            "    OOD frame of {} elements, {} field elements in total",
+            self.ood_frame_size, self.total_elements
+        )
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn array(input: &Value, signal: Signal) -> Result<&Vec<Value>, WinterCircomError> {
+    input[signal.name()]
+        .as_array()
+        .ok_or_else(|| invalid_input(&format!("expected the {} array", signal.name())))
+}
+
+/// Returns the number of leaves of a JSON value, i.e. of field elements of an
+/// input signal.
+fn count_elements(value: &Value) -> usize {
+    match value {
+        Value::Array(values) => values.iter().map(count_elements).sum(),
+        Value::Object(values) => values.values().map(count_elements).sum(),
+        Value::Null => 0,
+        _ => 1,
+    }
+}
+
+/// Returns the length of a padded Merkle path, without its trailing zeroes.
+fn unpadded_length(path: &Value) -> usize {
+    path.as_array().map_or(0, |nodes| {
+        nodes.len() - nodes.iter().rev().take_while(|node| *node == "0").count()
+    })
+}
+
+fn invalid_input(comment: &str) -> WinterCircomError {
+    WinterCircomError::SchemaValidation {
+        file: String::from("input.json"),
+        errors: vec![String::from(comment)],
+    }
+}
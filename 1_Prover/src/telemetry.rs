@@ -0,0 +1,121 @@
+use std::env;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    audit::{AuditAction, AuditEntry},
+    utils::{command_execution_with_stdin, Executable, LoggingLevel, WinterCircomError},
+};
+
+/// Opt-in reporter of anonymous performance statistics of the pipeline steps.
+///
+/// When configured, every invocation of [circom_create](crate::circom_create),
+/// [circom_prove](crate::circom_prove), [circom_verify](crate::circom_verify),
+/// [rotate_keys](crate::rotate_keys) and of the [Stage](crate::Stage)s of the
+/// Groth16 pipeline POSTs a [TelemetryReport] to the endpoint, as a JSON
+/// object. The reports are sent with `curl`, which must be installed. A failed
+/// report does not fail the step.
+///
+/// ## Configuration
+///
+/// Telemetry is disabled by default, and there is no default endpoint. It is
+/// enabled by setting the `WINTER_CIRCOM_TELEMETRY_ENDPOINT` environment
+/// variable to the URL of the collector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Telemetry {
+    endpoint: String,
+}
+
+impl Telemetry {
+    pub const ENDPOINT_VARIABLE: &'static str = "WINTER_CIRCOM_TELEMETRY_ENDPOINT";
+
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: String::from(endpoint),
+        }
+    }
+
+    /// Returns the telemetry configured by the environment variable, if any.
+    pub fn from_env() -> Option<Self> {
+        env::var(Telemetry::ENDPOINT_VARIABLE)
+            .ok()
+            .filter(|endpoint| !endpoint.trim().is_empty())
+            .map(|endpoint| Self::new(endpoint.trim()))
+    }
+
+    /// Returns the URL the reports are sent to.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Send a report to the endpoint, giving up after 5 seconds.
+    pub fn send(&self, report: &TelemetryReport) -> Result<(), WinterCircomError> {
+        let body = serde_json::to_vec(report).expect("report should be serializable");
+        command_execution_with_stdin(
+            Executable::Curl,
+            &[
+                "-sfS",
+                "-m",
+                "5",
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+                &self.endpoint,
+            ],
+            &body,
+            None,
+            &LoggingLevel::Quiet,
+        )
+    }
+}
+
+/// Anonymous statistics of a pipeline step, sent by [Telemetry].
+///
+/// Reports do not identify the circuit or the host: they contain neither the
+/// circuit name, nor paths, artifact hashes, timestamps or error messages.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TelemetryReport {
+    pub action: AuditAction,
+
+    /// Numeric and boolean parameters of the step (e.g. trace length, number
+    /// of queries). The other parameters are dropped.
+    pub parameters: Map<String, Value>,
+
+    pub duration_ms: u64,
+
+    /// [Name](crate::utils::ErrorClass::name) of the class of the error if the
+    /// step failed.
+    pub error_class: Option<&'static str>,
+
+    /// Version of this crate.
+    pub version: &'static str,
+}
+
+impl TelemetryReport {
+    /// Returns the report of the step recorded by an audit entry.
+    pub fn of<T>(entry: &AuditEntry, result: &Result<T, WinterCircomError>) -> Self {
+        let parameters = entry
+            .parameters
+            .as_object()
+            .map(|parameters| {
+                parameters
+                    .iter()
+                    .filter(|(_, value)| value.is_number() || value.is_boolean())
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            action: entry.action,
+            parameters,
+            duration_ms: entry.duration_ms,
+            error_class: result.as_ref().err().map(|e| e.class().name()),
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
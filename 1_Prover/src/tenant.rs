@@ -0,0 +1,267 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::{directory_size, WinterCircomError},
+    workspace::CircomWorkspace,
+};
+
+/// Namespace of the circuits proven on behalf of a tenant.
+///
+/// The artifacts of the circuits of a tenant are placed in the
+/// `target/circom/<tenant>/<circuit>/` directory. The functions of this crate
+/// accept namespaced circuit names of the form `<tenant>/<circuit>` (see
+/// [circuit_name](Tenant::circuit_name)), while the AIR Circom file is still
+/// looked up in `circuits/air/<circuit>.circom`.
+///
+/// ## Storage quota
+///
+/// A tenant saved with [write](Tenant::write) may define a storage quota.
+/// [circom_create](crate::circom_create) and [circom_prove](crate::circom_prove)
+/// fail with [TenantQuotaExceeded](WinterCircomError::TenantQuotaExceeded)
+/// when the artifacts of the tenant exceed it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tenant {
+    name: String,
+    quota_bytes: Option<u64>,
+}
+
+/// Cleanup policy for the artifacts of a [Tenant].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CleanupPolicy {
+    /// Delete the artifacts that are regenerated on every proof (inputs,
+    /// witnesses, self-test files), keeping circuits, keys and proofs.
+    ScratchArtifacts,
+
+    /// Delete the circuits whose directory has not been modified for the given
+    /// duration.
+    OlderThan(Duration),
+
+    /// Delete all the circuits of the tenant.
+    All,
+}
+
+impl Tenant {
+    /// Returns a tenant without storage quota.
+    ///
+    /// Panics if the name is empty or contains a path separator.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        let name = name.into();
+        assert!(
+            !name.is_empty() && !name.contains(['/', '\\']) && name != "." && name != "..",
+            "invalid tenant name: {}",
+            name
+        );
+        Self {
+            name,
+            quota_bytes: None,
+        }
+    }
+
+    /// Set the maximum total size of the artifacts of the tenant.
+    pub fn with_quota(self, quota_bytes: u64) -> Self {
+        Self {
+            quota_bytes: Some(quota_bytes),
+            ..self
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn quota_bytes(&self) -> Option<u64> {
+        self.quota_bytes
+    }
+
+    /// Returns the namespaced name of a circuit of the tenant, to be passed to
+    /// the functions of this crate.
+    pub fn circuit_name(&self, circuit_name: &str) -> String {
+        format!("{}/{}", self.name, circuit_name)
+    }
+
+    /// Returns the directory of the artifacts of the tenant.
+    pub fn dir(&self) -> PathBuf {
+        CircomWorkspace::current().root_dir().join(&self.name)
+    }
+
+    /// Returns the tenant of a namespaced circuit name, if it has been saved.
+    ///
+    /// Returns a [CorruptArtifact](WinterCircomError::CorruptArtifact) error if
+    /// its `tenant.json` cannot be parsed.
+    pub fn of_circuit(circuit_name: &str) -> Result<Option<Self>, WinterCircomError> {
+        let tenant = match split_circuit_name(circuit_name) {
+            (Some(tenant), _) => tenant,
+            (None, _) => return Ok(None),
+        };
+
+        let path = Tenant::new(tenant).config_path();
+        match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).map(Some).map_err(|e| {
+                WinterCircomError::CorruptArtifact {
+                    file: path.to_string_lossy().into_owned(),
+                    comment: e.to_string(),
+                }
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(WinterCircomError::IoError {
+                io_error: e,
+                comment: Some(String::from("reading tenant.json")),
+            }),
+        }
+    }
+
+    /// Save the tenant configuration to `target/circom/<tenant>/tenant.json`.
+    pub fn write(&self) -> Result<(), WinterCircomError> {
+        let io_error = |comment: &str| {
+            let comment = Some(String::from(comment));
+            move |e| WinterCircomError::IoError {
+                io_error: e,
+                comment,
+            }
+        };
+
+        fs::create_dir_all(self.dir()).map_err(io_error("creating tenant directory"))?;
+        let json = serde_json::to_string_pretty(self).expect("tenant should be serializable");
+        fs::write(self.config_path(), json).map_err(io_error("writing tenant.json"))
+    }
+
+    /// Returns the names of the circuits of the tenant.
+    pub fn circuits(&self) -> Vec<String> {
+        let mut circuits = fs::read_dir(self.dir())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        circuits.sort();
+        circuits
+    }
+
+    /// Returns the total size of the artifacts of the tenant, in bytes.
+    pub fn usage(&self) -> u64 {
+        directory_size(&self.dir())
+    }
+
+    /// Check that the artifacts of the tenant do not exceed its quota.
+    pub fn check_quota(&self) -> Result<(), WinterCircomError> {
+        if let Some(quota) = self.quota_bytes {
+            let used = self.usage();
+            if used > quota {
+                return Err(WinterCircomError::TenantQuotaExceeded {
+                    tenant: self.name.clone(),
+                    used,
+                    quota,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete artifacts of the tenant according to the given policy.
+    ///
+    /// Returns the number of bytes freed.
+    pub fn cleanup(&self, policy: CleanupPolicy) -> Result<u64, WinterCircomError> {
+        let before = self.usage();
+
+        for circuit in self.circuits() {
+            let dir = self.dir().join(&circuit);
+            match policy {
+                CleanupPolicy::ScratchArtifacts => {
+                    for file in ["input.json", "witness.wtns", "fri_layers.json"] {
+                        remove(&dir.join(file))?;
+                    }
+                    remove(&dir.join("selftest"))?;
+                }
+                CleanupPolicy::OlderThan(age) => {
+                    let modified = fs::metadata(&dir).and_then(|m| m.modified()).ok();
+                    let expired = modified
+                        .and_then(|m| SystemTime::now().duration_since(m).ok())
+                        .map_or(false, |elapsed| elapsed > age);
+                    if expired {
+                        remove(&dir)?;
+                    }
+                }
+                CleanupPolicy::All => remove(&dir)?,
+            }
+        }
+
+        Ok(before.saturating_sub(self.usage()))
+    }
+
+    fn config_path(&self) -> PathBuf {
+        self.dir().join("tenant.json")
+    }
+}
+
+/// Check that a circuit name can safely be used in paths and in the arguments
+/// of the external commands.
+///
+/// A circuit name is an AIR name, optionally prefixed by a tenant name and a
+/// `/` (see [Tenant]). Both must be non-empty, must not start with a `.` and
+/// may only contain ASCII alphanumerics, `-`, `_` and `.`, so that a name
+/// cannot escape the `target/circom` directory (`..`), nor be split or
+/// reinterpreted by the commands it is passed to (spaces, quotes, ...).
+///
+/// This is checked by all the functions of this crate that take a circuit name
+/// before they touch the file system.
+pub fn validate_circuit_name(circuit_name: &str) -> Result<(), WinterCircomError> {
+    let invalid = |reason: &str| WinterCircomError::InvalidCircuitName {
+        circuit_name: String::from(circuit_name),
+        reason: String::from(reason),
+    };
+
+    let segments = circuit_name.split('/').collect::<Vec<_>>();
+    if segments.len() > 2 {
+        return Err(invalid("at most one '/' may separate the tenant name"));
+    }
+    for segment in segments {
+        if segment.is_empty() {
+            return Err(invalid("names must not be empty"));
+        }
+        if segment.starts_with('.') {
+            return Err(invalid("names must not start with '.'"));
+        }
+        if !segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        {
+            return Err(invalid(
+                "names may only contain ASCII alphanumerics, '-', '_' and '.'",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Split a circuit name into its tenant, if any, and its AIR name.
+pub(crate) fn split_circuit_name(circuit_name: &str) -> (Option<&str>, &str) {
+    match circuit_name.split_once('/') {
+        Some((tenant, air)) => (Some(tenant), air),
+        None => (None, circuit_name),
+    }
+}
+
+/// Remove a file or directory, ignoring missing ones.
+fn remove(path: &Path) -> Result<(), WinterCircomError> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match result {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("removing {}", path.to_string_lossy())),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,4684 @@
+use std::collections::HashSet;
+
+use serde_json::json;
+use winterfell::math::{fields::f256::BaseElement, FieldElement};
+
+use crate::{
+    audit::{audited, history, AuditAction},
+    fri_tree_depths,
+    json::{fri_layer_inputs, FriLayer},
+    signals::{Signal, SignalValues},
+    utils::{format_size, print_json_result, ErrorClass, WinterCircomError},
+    validate_circuit_name, CircuitManifest, CircuitParameters, CleanupPolicy, GcPolicy,
+    IntermediateVerification, ScratchSpace, SnarkScheme, StaleReason, Tenant, Watchdog,
+    WinterCircomProofOptions,
+};
+
+// SIGNAL NAMES TESTS
+// ===========================================================================
+
+const VERIFY_CIRCOM: &str = include_str!("../../circuits/verify.circom");
+
+/// Returns the names of the input signals declared by the `Verify` template.
+fn verify_input_signals() -> HashSet<String> {
+    VERIFY_CIRCOM
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("signal input "))
+        .map(|declaration| {
+            declaration
+                .split(|c| c == '[' || c == ';')
+                .next()
+                .unwrap()
+                .trim()
+                .to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn signal_names_match_verify_template() {
+    let declared = verify_input_signals();
+    let exported = Signal::ALL
+        .iter()
+        .map(|s| s.name().to_owned())
+        .collect::<HashSet<_>>();
+
+    assert_eq!(Signal::ALL.len(), exported.len(), "duplicate signal names");
+    assert_eq!(declared, exported);
+}
+
+#[test]
+fn public_signals_are_inputs() {
+    let declared = verify_input_signals();
+    for signal in Signal::PUBLIC {
+        assert!(signal.is_public());
+        assert!(declared.contains(signal.name()));
+    }
+
+    let list = Signal::public_list(false, false);
+    assert_eq!(list, "[ood_frame_constraint_evaluation, ood_trace_frame]");
+    assert_eq!(Signal::public_list(false, true), list);
+
+    let list = Signal::public_list(true, false);
+    assert_eq!(
+        list,
+        "[ood_frame_constraint_evaluation, ood_trace_frame, public_inputs]"
+    );
+
+    let list = Signal::public_list(true, true);
+    assert_eq!(
+        list,
+        "[ood_frame_constraint_evaluation, ood_trace_frame, packed_public_inputs]"
+    );
+}
+
+#[test]
+fn signal_values_keys_match_signal_names() {
+    let mut values = SignalValues::new();
+    for signal in Signal::ALL {
+        values.insert(signal, 0u64);
+    }
+    let json = values.into_json();
+    let keys = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    assert_eq!(keys, verify_input_signals());
+}
+
+#[test]
+#[should_panic]
+fn signal_values_reject_missing_signal() {
+    let mut values = SignalValues::new();
+    for signal in &Signal::ALL[1..] {
+        values.insert(*signal, 0u64);
+    }
+    values.into_json();
+}
+
+// OOD FRAME CHECK TESTS
+// ===========================================================================
+
+/// The Merkle AIR has fewer transition constraints than trace columns, so that
+/// `public.json` is only read correctly if its OOD constraint evaluations are
+/// counted by transition constraint.
+#[cfg(feature = "verify")]
+#[test]
+fn ood_frame_check_reads_one_evaluation_per_transition_constraint() {
+    use winterfell::{
+        math::{fields::f256::BaseElement, FieldElement, StarkField},
+        Air, EvaluationFrame,
+    };
+
+    use crate::{
+        check_ood_frame,
+        gadgets::merkle::{MerkleAir, TRACE_WIDTH, TRANSITION_CONSTRAINT_DEGREES},
+        OodData,
+    };
+
+    let num_transition_constraints = TRANSITION_CONSTRAINT_DEGREES.len();
+    assert_ne!(num_transition_constraints, TRACE_WIDTH);
+
+    let mut frame = EvaluationFrame::new(TRACE_WIDTH);
+    for i in 0..TRACE_WIDTH {
+        frame.current_mut()[i] = BaseElement::from(i as u64 + 1);
+        frame.next_mut()[i] = BaseElement::from(i as u64 + 7);
+    }
+    let mut evaluations = BaseElement::zeroed_vector(num_transition_constraints);
+    MerkleAir::default().evaluate_transition::<BaseElement>(&frame, &[], &mut evaluations);
+    let public_signals = [&evaluations[..], frame.current(), frame.next()]
+        .concat()
+        .iter()
+        .map(|e| e.as_int().to_string())
+        .collect::<Vec<_>>();
+
+    let circuit_name = format!("ood-frame-test-{}", std::process::id());
+    let dir = format!("target/circom/{}", circuit_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        format!("{}/public.json", dir),
+        serde_json::to_string(&public_signals).unwrap(),
+    )
+    .unwrap();
+
+    // panics if the evaluations or the frame are misread
+    check_ood_frame::<MerkleAir>(&circuit_name);
+    let ood_data = OodData::of_circuit::<MerkleAir>(&circuit_name).unwrap();
+    assert!(ood_data.is_consistent_with::<MerkleAir>());
+    assert_eq!(ood_data.next, frame.next());
+
+    // wrong evaluations and dimensions are reported, without panicking
+    let write = |signals: &[String]| {
+        std::fs::write(
+            format!("{}/public.json", dir),
+            serde_json::to_string(signals).unwrap(),
+        )
+        .unwrap()
+    };
+    let mut wrong_signals = public_signals.clone();
+    wrong_signals[0] = String::from("0");
+    write(&wrong_signals);
+    let ood_data = OodData::of_circuit::<MerkleAir>(&circuit_name).unwrap();
+    assert!(!ood_data.is_consistent_with::<MerkleAir>());
+    write(&public_signals[1..]);
+    assert!(matches!(
+        OodData::of_circuit::<MerkleAir>(&circuit_name),
+        Err(WinterCircomError::InvalidPublicSignals { .. })
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// FRI LAYERS TESTS
+// ===========================================================================
+
+fn sample_fri_layer() -> FriLayer {
+    let e = |x: u64| BaseElement::from(x);
+    FriLayer {
+        index: 1,
+        num_queries: 2,
+        tree_depth: 2,
+        proofs: vec![vec![e(1), e(2)], vec![e(3), e(4)]],
+        queries: vec![e(5), e(6), e(7), e(8)],
+    }
+}
+
+#[test]
+fn fri_layer_padding_preserves_layer_data() {
+    let layer = sample_fri_layer();
+
+    let proofs = layer.padded_proofs(3, 4);
+    assert_eq!(proofs.len(), 3);
+    for (padded, path) in proofs.iter().zip(layer.proofs.iter()) {
+        assert_eq!(padded.len(), 4);
+        assert_eq!(&padded[..layer.tree_depth], path.as_slice());
+        assert!(padded[layer.tree_depth..]
+            .iter()
+            .all(|x| *x == BaseElement::ZERO));
+    }
+    assert_eq!(proofs[2], vec![BaseElement::ZERO; 4]);
+
+    let queries = layer.padded_queries(6);
+    assert_eq!(&queries[..4], layer.queries.as_slice());
+    assert_eq!(&queries[4..], &[BaseElement::ZERO; 2]);
+}
+
+#[test]
+fn fri_layer_serializes_layer_index() {
+    let json = serde_json::to_value(sample_fri_layer()).unwrap();
+    assert_eq!(json["index"], 1);
+    assert_eq!(json["num_queries"], 2);
+    assert_eq!(json["tree_depth"], 2);
+    assert_eq!(json["proofs"].as_array().unwrap().len(), 2);
+    assert_eq!(json["queries"].as_array().unwrap().len(), 4);
+}
+
+// FRI TREE DEPTHS TESTS
+// ===========================================================================
+
+#[test]
+fn fri_tree_depths_fold_until_remainder() {
+    assert_eq!(fri_tree_depths(128, 8, 32), vec![4]);
+    assert_eq!(fri_tree_depths(1 << 12, 4, 16), vec![10, 8, 6, 4]);
+    assert_eq!(fri_tree_depths(32, 8, 32), Vec::<usize>::new());
+
+    let options = WinterCircomProofOptions::new(128, 2, 3, [1, 1], 32, 8, 0, 8, 128);
+    assert_eq!(options.fri_tree_depths(), fri_tree_depths(1024, 8, 128));
+}
+
+#[test]
+fn lde_blowup_factor_follows_constraint_degrees() {
+    let options = WinterCircomProofOptions::new(128, 2, 3, [1, 5], 32, 2, 0, 8, 16);
+    let min_blowup_factor = options.min_lde_blowup_factor();
+    assert!(min_blowup_factor > 2);
+    assert!(min_blowup_factor >= options.num_composition_columns());
+    assert!(matches!(
+        options.check_lde_blowup_factor(),
+        Err(WinterCircomError::BlowupFactorTooSmall {
+            blowup_factor: 2,
+            ..
+        })
+    ));
+
+    let options = options.with_min_lde_blowup_factor();
+    assert_eq!(options.check_lde_blowup_factor().unwrap(), None);
+
+    let options = WinterCircomProofOptions::new(128, 2, 3, [1, 5], 32, 64, 0, 8, 16);
+    assert!(options.check_lde_blowup_factor().unwrap().is_some());
+}
+
+#[test]
+fn target_security_is_checked_against_conjectured_security() {
+    // 32 queries with a blowup factor of 8 and 4 bits of grinding
+    let options = WinterCircomProofOptions::new(128, 2, 3, [1, 1], 32, 8, 4, 8, 128);
+    assert_eq!(options.target_security_bits(), 128);
+    assert_eq!(options.conjectured_security_bits(), 100);
+    assert!(options.check_target_security().is_some());
+
+    let options = options.target_security(96);
+    assert_eq!(options.target_security_bits(), 96);
+    assert_eq!(options.check_target_security(), None);
+}
+
+/// Returns the manifest of a circuit of 128 rows and 2 columns, recording none
+/// of the optional fields, for the tests to override by field.
+fn sample_manifest(circuit_name: &str) -> CircuitManifest {
+    CircuitManifest {
+        circuit_name: String::from(circuit_name),
+        trace_length: 128,
+        trace_width: 2,
+        fri_tree_depths: vec![],
+        num_composition_columns: None,
+        data_commitment_max_bytes: None,
+        source_fingerprint: None,
+        intermediate_verification: IntermediateVerification::Always,
+        inner_proof_verified: None,
+        target_security_bits: None,
+        public_inputs_exposed: None,
+        public_signals_committed: None,
+        circuit_parameters: None,
+        transition_constraint_degrees: None,
+        snark_scheme: SnarkScheme::Groth16,
+        column_map: None,
+        compiled_from: None,
+        keys_generated_from: None,
+    }
+}
+
+#[test]
+fn manifest_rejects_mismatched_fri_tree_depths() {
+    let manifest = CircuitManifest {
+        fri_tree_depths: vec![7, 4],
+        num_composition_columns: Some(2),
+        ..sample_manifest("test")
+    };
+
+    assert!(manifest.check_fri_tree_depths(&[7, 4]).is_ok());
+    assert!(matches!(
+        manifest.check_fri_tree_depths(&[7]),
+        Err(WinterCircomError::ManifestMismatch { .. })
+    ));
+
+    assert!(manifest.check_num_composition_columns(2).is_ok());
+    assert!(matches!(
+        manifest.check_num_composition_columns(4),
+        Err(WinterCircomError::ManifestMismatch { .. })
+    ));
+
+    // manifests of earlier circuits do not record the composition columns
+    let legacy = CircuitManifest {
+        num_composition_columns: None,
+        ..manifest
+    };
+    assert!(legacy.check_num_composition_columns(4).is_ok());
+}
+
+#[test]
+fn manifest_detects_changed_sources_by_content() {
+    let circuit_name = format!("fingerprint-test-{}", std::process::id());
+    let circuit_dir = std::path::Path::new("target/circom").join(&circuit_name);
+    std::fs::create_dir_all("circuits/air").unwrap();
+    std::fs::create_dir_all(&circuit_dir).unwrap();
+    let template = format!("circuits/air/{}.circom", circuit_name);
+    std::fs::write(&template, "template AIRTransitions() {}").unwrap();
+    std::fs::write(circuit_dir.join("verifier.circom"), "component main;").unwrap();
+
+    let manifest = CircuitManifest {
+        source_fingerprint: CircuitManifest::source_fingerprint(&circuit_name),
+        ..sample_manifest(&circuit_name)
+    };
+    assert!(manifest.source_fingerprint.is_some());
+    assert!(!manifest.sources_changed());
+
+    // rewriting the same contents only updates the modification time
+    std::fs::write(&template, "template AIRTransitions() {}").unwrap();
+    assert!(!manifest.sources_changed());
+
+    std::fs::write(&template, "template AIRTransitions() { }").unwrap();
+    assert!(manifest.sources_changed());
+
+    std::fs::remove_file(template).unwrap();
+    std::fs::remove_dir_all(circuit_dir).unwrap();
+    // only removed if no other test created templates
+    let _ = std::fs::remove_dir("circuits/air");
+    let _ = std::fs::remove_dir("circuits");
+}
+
+#[test]
+fn manifest_defaults_intermediate_verification_to_debug_builds() {
+    let manifest: CircuitManifest = serde_json::from_value(json!({
+        "circuit_name": "test",
+        "trace_length": 128,
+        "trace_width": 2,
+        "fri_tree_depths": [7, 4],
+        "data_commitment_max_bytes": null,
+    }))
+    .unwrap();
+    assert_eq!(
+        manifest.intermediate_verification,
+        IntermediateVerification::DebugOnly
+    );
+    assert_eq!(manifest.inner_proof_verified, None);
+
+    assert!(IntermediateVerification::Always.is_enabled());
+    assert!(!IntermediateVerification::Never.is_enabled());
+    assert_eq!(
+        IntermediateVerification::DebugOnly.is_enabled(),
+        cfg!(debug_assertions)
+    );
+}
+
+// NOIR EXPORT TESTS
+// ===========================================================================
+
+#[cfg(feature = "noir")]
+#[test]
+fn noir_prover_toml_lists_all_signals() {
+    let mut values = SignalValues::new();
+    for signal in Signal::ALL {
+        values.insert(signal, vec![vec!["1", "2"], vec!["3", "4"]]);
+    }
+    values.insert(Signal::PowNonce, 42u64);
+    let toml = crate::noir::to_prover_toml(&values.into_json()).unwrap();
+
+    let lines = toml.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), Signal::ALL.len());
+    assert!(lines.contains(&"pow_nonce = \"42\""));
+    assert!(lines.contains(&"trace_commitment = [[\"1\", \"2\"], [\"3\", \"4\"]]"));
+}
+
+#[cfg(feature = "noir")]
+#[test]
+fn noir_prover_toml_rejects_missing_and_non_numeric_signals() {
+    let mut values = SignalValues::new();
+    for signal in Signal::ALL {
+        values.insert(signal, vec!["1"]);
+    }
+    let input = values.into_json();
+
+    let mut missing = input.clone();
+    missing
+        .as_object_mut()
+        .unwrap()
+        .remove(Signal::PowNonce.name());
+    assert!(matches!(
+        crate::noir::to_prover_toml(&missing),
+        Err(WinterCircomError::CorruptArtifact { .. })
+    ));
+
+    let mut non_numeric = input;
+    non_numeric[Signal::PowNonce.name()] = json!(true);
+    assert!(matches!(
+        crate::noir::to_prover_toml(&non_numeric),
+        Err(WinterCircomError::CorruptArtifact { .. })
+    ));
+}
+
+// NATIVE BACKEND TESTS
+// ===========================================================================
+
+#[cfg(feature = "native")]
+#[test]
+fn native_artifacts_use_the_snarkjs_format() {
+    use ark_bn254::{G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+
+    use crate::native::{
+        proof_from_json, proof_to_json, verification_key_from_json, verification_key_to_json,
+    };
+
+    let g1 = G1Affine::generator();
+    let g2 = G2Affine::generator();
+    let verifying_key = ark_groth16::VerifyingKey {
+        alpha_g1: g1,
+        beta_g2: g2,
+        gamma_g2: g2,
+        delta_g2: G2Affine::zero(),
+        gamma_abc_g1: vec![g1, G1Affine::zero()],
+    };
+    let json = verification_key_to_json(&verifying_key);
+    assert_eq!(json["protocol"], "groth16");
+    assert_eq!(json["nPublic"], 1);
+    assert_eq!(json["vk_alpha_1"], json!(["1", "2", "1"]));
+    assert_eq!(json["IC"][1], json!(["0", "1", "0"]));
+    assert_eq!(verification_key_from_json(&json).unwrap(), verifying_key);
+
+    let proof = ark_groth16::Proof {
+        a: g1,
+        b: g2,
+        c: g1,
+    };
+    let json = proof_to_json(&proof);
+    assert_eq!(json["pi_b"][2], json!(["1", "0"]));
+    assert_eq!(proof_from_json(&json).unwrap(), proof);
+
+    // points which are not on the curve are rejected
+    let mut invalid = json;
+    invalid["pi_a"][1] = json!("3");
+    assert!(matches!(
+        proof_from_json(&invalid),
+        Err(WinterCircomError::NativeProverError { .. })
+    ));
+}
+
+// SCRATCH SPACE TESTS
+// ===========================================================================
+
+#[test]
+fn scratch_space_enforces_max_size() {
+    let root = std::env::temp_dir().join(format!("winter-circom-scratch-{}", std::process::id()));
+    let scratch = ScratchSpace::new(Some(root.clone()), Some(16));
+
+    let path = scratch.write("test", "input.json", &[0; 10]).unwrap();
+    assert_eq!(path, root.join("test").join("input.json"));
+
+    // replacing a file only accounts for the size difference
+    assert!(scratch.write("test", "input.json", &[0; 16]).is_ok());
+    assert!(matches!(
+        scratch.write("test", "witness.wtns", &[0; 1]),
+        Err(WinterCircomError::ScratchSpaceExceeded {
+            required: 17,
+            available: 16
+        })
+    ));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// WORKSPACE TESTS
+// ===========================================================================
+
+#[test]
+fn workspace_moves_circuit_artifacts() {
+    use crate::{with_workspace, CircomWorkspace};
+
+    // the default workspace includes the templates relatively to the circuit
+    let workspace = CircomWorkspace::default();
+    assert_eq!(workspace.include_dir("sum"), "../../../circuits");
+    assert_eq!(workspace.include_dir("acme/sum"), "../../../../circuits");
+    assert_eq!(
+        workspace.air_template("acme/sum"),
+        std::path::Path::new("circuits/air/sum.circom")
+    );
+
+    let root = std::env::temp_dir().join(format!("winter-circom-workspace-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(root.join("out"))
+        .with_circuits_dir(root.join("circuits"))
+        .with_ptau(root.join("final.ptau"));
+    assert_eq!(
+        workspace.include_dir("sum"),
+        root.join("circuits").to_string_lossy()
+    );
+
+    let manifest = sample_manifest("sum");
+    with_workspace(workspace.clone(), || {
+        assert_eq!(CircomWorkspace::current(), workspace);
+        std::fs::create_dir_all(workspace.circuit_dir("sum")).unwrap();
+        manifest.write().unwrap();
+        assert_eq!(
+            CircuitManifest::read("sum").unwrap(),
+            Some(manifest.clone())
+        );
+        assert_eq!(ScratchSpace::new(None, None).root(), root.join("out"));
+
+        // a truncated manifest is reported instead of panicking
+        std::fs::write(CircuitManifest::path("sum"), "{").unwrap();
+        assert!(matches!(
+            CircuitManifest::read("sum"),
+            Err(WinterCircomError::CorruptArtifact { .. })
+        ));
+    });
+    assert!(root.join("out/sum/manifest.json").exists());
+    assert_ne!(CircomWorkspace::current(), workspace);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// AUDIT LOG TESTS
+// ===========================================================================
+
+#[test]
+fn audit_log_records_invocations() {
+    let circuit_name = format!("audit-test-{}", std::process::id());
+
+    audited(
+        &circuit_name,
+        AuditAction::Create,
+        json!({ "trace_length": 8 }),
+        &[],
+        || Ok(()),
+    )
+    .unwrap();
+    let failed = audited(&circuit_name, AuditAction::Verify, json!({}), &[], || {
+        Err::<(), _>(WinterCircomError::InvalidProof(None))
+    });
+    assert!(failed.is_err());
+
+    // a torn line, e.g. of a killed process, is skipped
+    let log = format!("target/circom/{}/audit.log", circuit_name);
+    let mut data = std::fs::read(&log).unwrap();
+    data.extend_from_slice(b"{\"timestamp\":17");
+    std::fs::write(&log, data).unwrap();
+    audited(&circuit_name, AuditAction::Prove, json!({}), &[], || Ok(())).unwrap();
+
+    let entries = history(&circuit_name).unwrap();
+    std::fs::remove_dir_all(format!("target/circom/{}", circuit_name)).unwrap();
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[2].action, AuditAction::Prove);
+    assert_eq!(entries[0].action, AuditAction::Create);
+    assert_eq!(entries[0].parameters["trace_length"], 8);
+    assert!(entries[0].is_success());
+    assert_eq!(entries[1].action, AuditAction::Verify);
+    assert_eq!(entries[1].error.as_deref(), Some("Invalid proof."));
+}
+
+#[test]
+fn audited_steps_return_their_output() {
+    let circuit_name = format!("audit-output-test-{}", std::process::id());
+
+    let output = audited(&circuit_name, AuditAction::Prove, json!({}), &[], || {
+        Ok(vec![7, 4])
+    });
+    std::fs::remove_dir_all(format!("target/circom/{}", circuit_name)).unwrap();
+    assert_eq!(output.unwrap(), vec![7, 4]);
+}
+
+#[test]
+fn interrupted_steps_mark_artifacts_incomplete() {
+    let circuit_name = format!("interrupt-test-{}", std::process::id());
+    let circuit_dir = std::path::PathBuf::from(format!("target/circom/{}", circuit_name));
+    let interrupted = || {
+        Err::<(), _>(WinterCircomError::Interrupted {
+            executable: String::from("snarkjs"),
+        })
+    };
+
+    // failed steps that were not interrupted leave no state
+    let failed = audited(&circuit_name, AuditAction::Create, json!({}), &[], || {
+        Err::<(), _>(WinterCircomError::InvalidProof(None))
+    });
+    assert!(failed.is_err());
+    assert!(!circuit_dir.join("state.json").exists());
+
+    let result = audited(
+        &circuit_name,
+        AuditAction::RotateKeys,
+        json!({}),
+        &[circuit_dir.join("verifier.zkey")],
+        interrupted,
+    );
+    assert_eq!(result.unwrap_err().class(), ErrorClass::Interrupted);
+    assert!(circuit_dir.join("state.json").exists());
+
+    // other steps reject the partial artifacts, until the step is run again
+    let rejected = audited(
+        &circuit_name,
+        AuditAction::Verify,
+        json!({}),
+        &[],
+        || Ok(()),
+    );
+    match rejected {
+        Err(WinterCircomError::IncompleteArtifacts {
+            step, artifacts, ..
+        }) => {
+            assert_eq!(step, "rotate_keys");
+            assert_eq!(artifacts, vec![String::from("verifier.zkey")]);
+        }
+        other => panic!("unexpected result: {:?}", other.err()),
+    }
+    audited(
+        &circuit_name,
+        AuditAction::RotateKeys,
+        json!({}),
+        &[],
+        || Ok(()),
+    )
+    .unwrap();
+    assert!(!circuit_dir.join("state.json").exists());
+    audited(
+        &circuit_name,
+        AuditAction::Verify,
+        json!({}),
+        &[],
+        || Ok(()),
+    )
+    .unwrap();
+
+    // a torn state file is reported, and replaced by the generation of the
+    // circuit
+    std::fs::write(circuit_dir.join("state.json"), "{ \"incomplete_st").unwrap();
+    let verified = audited(
+        &circuit_name,
+        AuditAction::Verify,
+        json!({}),
+        &[],
+        || Ok(()),
+    );
+    assert!(matches!(
+        verified,
+        Err(WinterCircomError::CorruptArtifact { .. })
+    ));
+    audited(
+        &circuit_name,
+        AuditAction::Create,
+        json!({}),
+        &[],
+        || Ok(()),
+    )
+    .unwrap();
+    assert!(!circuit_dir.join("state.json").exists());
+
+    std::fs::remove_dir_all(circuit_dir).unwrap();
+}
+
+// TELEMETRY TESTS
+// ===========================================================================
+
+#[test]
+fn telemetry_reports_are_anonymous() {
+    use std::rc::Rc;
+
+    use crate::{with_executor, AuditEntry, MockExecutor, Telemetry, TelemetryReport};
+
+    let entry = AuditEntry {
+        timestamp: 1_700_000_000,
+        action: AuditAction::Prove,
+        parameters: json!({
+            "trace_length": 1024,
+            "intermediate_verification": "always",
+            "expose_public_inputs": true,
+        }),
+        artifacts: [(String::from("input.json"), String::from("00"))]
+            .into_iter()
+            .collect(),
+        duration_ms: 42,
+        error: Some(String::from("circuit tenant-a/secret not found")),
+    };
+    let error = WinterCircomError::OutdatedCircuit {
+        circuit_name: String::from("tenant-a/secret"),
+    };
+    let report = TelemetryReport::of(&entry, &Err(error));
+    let body = serde_json::to_value(&report).unwrap();
+    assert_eq!(body["action"], "prove");
+    assert_eq!(
+        body["parameters"],
+        json!({ "trace_length": 1024, "expose_public_inputs": true })
+    );
+    assert_eq!(body["duration_ms"], 42);
+    assert_eq!(body["error_class"], "circuit_mismatch");
+    assert!(!body.to_string().contains("secret"));
+    assert!(!body.to_string().contains("input.json"));
+
+    // reports are posted to the configured endpoint only
+    let executor = Rc::new(MockExecutor::new());
+    with_executor(executor.clone(), || {
+        Telemetry::new("https://telemetry.example.com/v1").send(&report)
+    })
+    .unwrap();
+    let invocations = executor.invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0].program, "curl");
+    assert_eq!(
+        invocations[0].args.last().map(String::as_str),
+        Some("https://telemetry.example.com/v1")
+    );
+    let sent: serde_json::Value =
+        serde_json::from_slice(invocations[0].stdin.as_ref().unwrap()).unwrap();
+    assert_eq!(sent, body);
+}
+
+// TENANT TESTS
+// ===========================================================================
+
+#[test]
+fn tenant_cleanup_and_quota() {
+    let tenant = Tenant::new(format!("tenant-test-{}", std::process::id())).with_quota(8);
+    tenant.write().unwrap();
+    assert_eq!(
+        Tenant::of_circuit(&tenant.circuit_name("sum")).unwrap(),
+        Some(tenant.clone())
+    );
+    assert_eq!(Tenant::of_circuit("sum").unwrap(), None);
+
+    let circuit_dir = tenant.dir().join("sum");
+    std::fs::create_dir_all(&circuit_dir).unwrap();
+    std::fs::write(circuit_dir.join("input.json"), [0; 16]).unwrap();
+    std::fs::write(circuit_dir.join("proof.json"), [0; 4]).unwrap();
+    assert_eq!(tenant.circuits(), vec![String::from("sum")]);
+    assert!(matches!(
+        tenant.check_quota(),
+        Err(WinterCircomError::TenantQuotaExceeded { .. })
+    ));
+
+    // only the scratch artifacts are removed
+    assert_eq!(tenant.cleanup(CleanupPolicy::ScratchArtifacts).unwrap(), 16);
+    assert!(circuit_dir.join("proof.json").exists());
+
+    tenant.cleanup(CleanupPolicy::All).unwrap();
+    assert!(tenant.circuits().is_empty());
+
+    // a corrupt configuration is reported
+    std::fs::write(tenant.dir().join("tenant.json"), "{ \"name\"").unwrap();
+    assert!(matches!(
+        Tenant::of_circuit(&tenant.circuit_name("sum")),
+        Err(WinterCircomError::CorruptArtifact { .. })
+    ));
+    std::fs::remove_dir_all(tenant.dir()).unwrap();
+}
+
+#[test]
+fn circuit_names_are_validated() {
+    for name in ["sum", "merkle-v2", "acme/rollup_1.0"] {
+        assert!(validate_circuit_name(name).is_ok(), "{}", name);
+    }
+    for name in [
+        "",
+        "..",
+        "../sum",
+        "acme/../../sum",
+        "a/b/c",
+        "/sum",
+        "sum/",
+        ".hidden",
+        "my circuit",
+        "sum;rm",
+        "ŝum",
+    ] {
+        assert!(
+            matches!(
+                validate_circuit_name(name),
+                Err(WinterCircomError::InvalidCircuitName { .. })
+            ),
+            "{}",
+            name
+        );
+    }
+
+    // entry points fail before touching the file system
+    assert!(matches!(
+        history("../escaped"),
+        Err(WinterCircomError::InvalidCircuitName { .. })
+    ));
+    assert!(audited("../escaped", AuditAction::Verify, json!({}), &[], || Ok(())).is_err());
+    assert!(!std::path::Path::new("target/escaped").exists());
+}
+
+// AIR TEMPLATE SCAFFOLD TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn missing_air_template_writes_stub() {
+    use crate::scaffold::missing_air_template;
+
+    let template = format!(
+        "target/circom/scaffold-test-{}/air.circom",
+        std::process::id()
+    );
+    let error = missing_air_template(&template, &[1, 2], 3, true);
+    assert!(matches!(
+        &error,
+        WinterCircomError::MissingAirTemplate {
+            num_assertions: 3,
+            scaffolded: true,
+            ..
+        }
+    ));
+    assert!(error.to_string().contains("[1, 2]"));
+
+    let stub = std::fs::read_to_string(&template).unwrap();
+    assert!(stub.contains("transition_degree[1] <== 2;"));
+    assert!(stub.contains("assert(num_assertions == 3);"));
+
+    std::fs::remove_dir_all(std::path::Path::new(&template).parent().unwrap()).unwrap();
+}
+
+// AIR TEMPLATE LINT TESTS
+// ===========================================================================
+
+#[test]
+fn shipped_air_templates_pass_lints() {
+    use crate::{
+        gadgets::{merkle, rollup, signature},
+        lint_air_source,
+    };
+
+    let templates: [(&str, &[usize], usize); 5] = [
+        (include_str!("../../circuits/air/sum.circom"), &[1, 1], 3),
+        (
+            include_str!("../../circuits/air/fixture.circom"),
+            &[1, 1],
+            3,
+        ),
+        (
+            include_str!("../../circuits/air/merkle.circom"),
+            &merkle::TRANSITION_CONSTRAINT_DEGREES,
+            merkle::NUM_ASSERTIONS,
+        ),
+        (
+            include_str!("../../circuits/air/rollup.circom"),
+            &rollup::TRANSITION_CONSTRAINT_DEGREES,
+            rollup::NUM_ASSERTIONS,
+        ),
+        (
+            include_str!("../../circuits/air/signature.circom"),
+            &signature::TRANSITION_CONSTRAINT_DEGREES,
+            signature::NUM_ASSERTIONS,
+        ),
+    ];
+    for (source, degrees, num_assertions) in templates {
+        let lints = lint_air_source(source, degrees, num_assertions);
+        assert!(lints.is_empty(), "{:?}", lints);
+    }
+}
+
+#[test]
+fn air_template_lints_find_common_mistakes() {
+    use crate::{lint_air_source, LintKind, LintSeverity};
+
+    let source = "template AIRTransitions(num_transition_constraints) {
+    signal output transition_degree[num_transition_constraints];
+    transition_degree[0] <== 2;
+    transition_degree[1] <== 3; // declared as 2
+}
+
+template AIRAssertions(addicity, num_assertions, num_public_inputs, trace_length, trace_width) {
+    signal input public_inputs[num_public_inputs];
+    signal boundary;
+    component assertions[num_assertions];
+
+    assert(num_assertions == 3);
+
+    boundary <-- public_inputs[0] * 2;
+
+    assertions[0] = SingleAssertion();
+    assertions[0].column <== 0;
+    assertions[0].step <== 0;
+    assertions[0].value <== boundary;
+
+    assertions[1] = SingleAssertion();
+    assertions[1].column <== 1;
+    assertions[1].step <== 0;
+}
+";
+    let lints = lint_air_source(source, &[2, 2], 2);
+    let kinds = lints
+        .iter()
+        .map(|lint| (lint.kind, lint.line))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            (LintKind::UnconstrainedAssignment, 14),
+            (LintKind::DegreeMismatch, 4),
+            (LintKind::AssertionMismatch, 12),
+            (LintKind::UnassignedInput, 21),
+        ]
+    );
+    assert!(lints[3]
+        .to_string()
+        .contains("input value of assertions[1]"));
+    assert_eq!(lints[0].kind.severity(), LintSeverity::Warning);
+    assert_eq!(lints[1].kind.severity(), LintSeverity::Error);
+
+    // constrained assignments are allowed
+    let constrained = source.replace(
+        "boundary <-- public_inputs[0] * 2;",
+        "boundary <-- public_inputs[0] * 2;\n    boundary === public_inputs[0] * 2;",
+    );
+    assert!(lint_air_source(&constrained, &[2, 2], 2)
+        .iter()
+        .all(|lint| lint.kind != LintKind::UnconstrainedAssignment));
+}
+
+// GARBAGE COLLECTION TESTS
+// ===========================================================================
+
+#[test]
+fn gc_reports_circuits_without_template() {
+    let circuit_name = format!("gc-test-{}", std::process::id());
+    let circuit_dir = std::path::Path::new("target/circom").join(&circuit_name);
+    std::fs::create_dir_all(&circuit_dir).unwrap();
+    std::fs::write(circuit_dir.join("verifier.circom"), "").unwrap();
+    std::fs::write(circuit_dir.join("verifier.zkey"), [0; 32]).unwrap();
+
+    let report = crate::gc(GcPolicy::DryRun).unwrap();
+    let stale = report
+        .stale
+        .iter()
+        .find(|stale| stale.circuit_name == circuit_name)
+        .unwrap();
+    assert_eq!(stale.reason, StaleReason::MissingTemplate);
+    assert_eq!(stale.size_bytes, 32);
+    assert!(report.reclaimed_bytes >= 32);
+    assert!(circuit_dir.exists());
+
+    std::fs::remove_dir_all(circuit_dir).unwrap();
+}
+
+#[test]
+fn gc_removes_stale_circuits_of_unsaved_tenants() {
+    use crate::{with_workspace, CircomWorkspace};
+
+    let root = std::env::temp_dir().join(format!("winter-circom-gc-{}", std::process::id()));
+    let circuits_dir = root.join("circuits");
+    std::fs::create_dir_all(circuits_dir.join("air")).unwrap();
+    std::fs::write(circuits_dir.join("air").join("live.circom"), "").unwrap();
+    let workspace = CircomWorkspace::new(root.join("circom")).with_circuits_dir(&circuits_dir);
+
+    // the tenant has no tenant.json, its circuits are still namespaced
+    let tenant_dir = workspace.root_dir().join("acme");
+    for circuit in ["live", "stale"] {
+        std::fs::create_dir_all(tenant_dir.join(circuit)).unwrap();
+        std::fs::write(tenant_dir.join(circuit).join("verifier.circom"), "").unwrap();
+    }
+
+    let report = with_workspace(workspace, || crate::gc(GcPolicy::Remove)).unwrap();
+    let stale = report
+        .stale
+        .iter()
+        .map(|stale| stale.circuit_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(stale, ["acme/stale"]);
+    assert!(!tenant_dir.join("stale").exists());
+    assert!(tenant_dir.join("live").join("verifier.circom").exists());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// CONSTRAINT BREAKDOWN TESTS
+// ===========================================================================
+
+/// Returns an r1cs file of one-term constraints on the given wires.
+fn sample_r1cs(constraints: &[[u32; 3]]) -> Vec<u8> {
+    let field_size = 32u32;
+    let mut header = field_size.to_le_bytes().to_vec();
+    header.extend([0; 32]);
+    header.extend([0; 4 * 4 + 8]);
+    header.extend((constraints.len() as u32).to_le_bytes());
+
+    let mut section = Vec::new();
+    for wires in constraints {
+        for wire in wires {
+            section.extend(1u32.to_le_bytes());
+            section.extend(wire.to_le_bytes());
+            section.extend([0; 32]);
+        }
+    }
+
+    let mut r1cs = b"r1cs".to_vec();
+    r1cs.extend(1u32.to_le_bytes());
+    r1cs.extend(2u32.to_le_bytes());
+    for (section_type, bytes) in [(2u32, section), (1u32, header)] {
+        r1cs.extend(section_type.to_le_bytes());
+        r1cs.extend((bytes.len() as u64).to_le_bytes());
+        r1cs.extend(bytes);
+    }
+    r1cs
+}
+
+#[test]
+fn constraint_breakdown_attributes_constraints_to_components() {
+    use crate::{constraint_breakdown, ConstraintCategory};
+
+    let circuit_name = format!("breakdown-test-{}", std::process::id());
+    let dir = std::path::Path::new("target/circom").join(&circuit_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("verifier.r1cs"),
+        sample_r1cs(&[[1, 2, 3], [3, 4, 4], [1, 1, 1], [2, 2, 2]]),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("verifier.sym"),
+        "1,1,0,main.deep_composition[0]\n\
+        2,2,1,main.fri.out\n\
+        3,3,2,main.traceCommitmentVerifier[1].hash.out\n\
+        4,4,3,main.traceCommitmentVerifier[1].hash.ark[0].out\n\
+        5,-1,3,main.ood.removed\n\
+        6,5,3,main.ood.out\n",
+    )
+    .unwrap();
+
+    let breakdown = constraint_breakdown(&circuit_name).unwrap();
+    assert_eq!(breakdown.total, 4);
+    assert_eq!(breakdown.components["traceCommitmentVerifier"], 2);
+    assert_eq!(breakdown.components["main"], 1);
+    assert_eq!(breakdown.components["fri"], 1);
+    assert_eq!(breakdown.categories()[&ConstraintCategory::Merkle], 2);
+    assert_eq!(breakdown.categories()[&ConstraintCategory::Composition], 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(matches!(
+        constraint_breakdown(&circuit_name),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+}
+
+// TRANSITION DEGREE TESTS
+// ===========================================================================
+
+/// Scalar field of BN254, in little-endian.
+const BN254_PRIME: [u8; 32] = [
+    0x01, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28,
+    0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30,
+];
+
+/// Returns an r1cs file of linear constraints, given as pairs of wires and
+/// coefficients.
+fn linear_r1cs(constraints: &[Vec<(u32, i64)>]) -> Vec<u8> {
+    let field_element = |value: i64| {
+        let magnitude = value.unsigned_abs().to_le_bytes();
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&magnitude);
+        if value < 0 {
+            let mut borrow = 0;
+            for (i, p) in BN254_PRIME.iter().enumerate() {
+                let digit = *p as i16 - *magnitude.get(i).unwrap_or(&0) as i16 - borrow;
+                borrow = (digit < 0) as i16;
+                bytes[i] = (digit + 256 * borrow) as u8;
+            }
+        }
+        bytes
+    };
+
+    let mut header = 32u32.to_le_bytes().to_vec();
+    header.extend(BN254_PRIME);
+    header.extend([0; 4 * 4 + 8]);
+    header.extend((constraints.len() as u32).to_le_bytes());
+
+    let mut section = Vec::new();
+    for terms in constraints {
+        // A and B are empty
+        section.extend([0; 8]);
+        section.extend((terms.len() as u32).to_le_bytes());
+        for (wire, coefficient) in terms {
+            section.extend(wire.to_le_bytes());
+            section.extend(field_element(*coefficient));
+        }
+    }
+
+    let mut r1cs = b"r1cs".to_vec();
+    r1cs.extend(1u32.to_le_bytes());
+    r1cs.extend(2u32.to_le_bytes());
+    for (section_type, bytes) in [(1u32, header), (2u32, section)] {
+        r1cs.extend(section_type.to_le_bytes());
+        r1cs.extend((bytes.len() as u64).to_le_bytes());
+        r1cs.extend(bytes);
+    }
+    r1cs
+}
+
+#[test]
+fn transition_degrees_are_recovered_from_compiled_circuits() {
+    use crate::{
+        constraints::check_transition_degrees, transition_degrees, utils::LoggingLevel,
+        with_workspace, CircomWorkspace,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-degrees-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // the output of the first constraint is fixed to 1, the output of the
+    // second one is removed but its adjustment exponent 3 * 128 - 2 - 2 * 127
+    // is decomposed into bits, nothing is left of the third one
+    let mut sym = String::from("1,1,0,main.ood.AIR.transition_degree[0]\n");
+    sym.push_str("2,-1,0,main.ood.AIR.transition_degree[1]\n");
+    let mut bits = vec![(0, 128)];
+    for k in 0..9 {
+        sym.push_str(&format!(
+            "{},{},1,main.ood.transition_deg_adjustment[1].n2b.out[{}]\n",
+            k + 3,
+            k + 2,
+            k
+        ));
+        bits.push((k + 2, -(1 << k)));
+    }
+    sym.push_str("12,-1,0,main.ood.AIR.transition_degree[2]\n");
+    std::fs::write(dir.join("verifier.sym"), sym).unwrap();
+    std::fs::write(
+        dir.join("verifier.r1cs"),
+        linear_r1cs(&[vec![(0, -1), (1, 1)], bits]),
+    )
+    .unwrap();
+
+    let mut manifest = CircuitManifest {
+        num_composition_columns: Some(2),
+        circuit_parameters: Some(CircuitParameters {
+            trace_length: 128,
+            trace_width: 2,
+            num_transition_constraints: 3,
+            num_assertions: 1,
+            num_queries: 42,
+            lde_blowup_factor: 8,
+            grinding_factor: 0,
+            fri_folding_factor: 4,
+            fri_max_remainder_size: 7,
+        }),
+        transition_constraint_degrees: Some(vec![1, 2, 1]),
+        ..sample_manifest("sum")
+    };
+    with_workspace(workspace, || {
+        manifest.write().unwrap();
+        assert_eq!(
+            transition_degrees("sum").unwrap(),
+            vec![Some(1), Some(2), None]
+        );
+        assert!(check_transition_degrees(&manifest, &LoggingLevel::Quiet).is_ok());
+
+        manifest.transition_constraint_degrees = Some(vec![1, 3, 1]);
+        assert!(matches!(
+            check_transition_degrees(&manifest, &LoggingLevel::Quiet),
+            Err(WinterCircomError::TransitionDegreeMismatch {
+                constraint: 1,
+                declared: 3,
+                compiled: 2,
+                ..
+            })
+        ));
+
+        // circuits of earlier versions do not record the degrees
+        manifest.transition_constraint_degrees = None;
+        assert!(check_transition_degrees(&manifest, &LoggingLevel::Quiet).is_ok());
+    });
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// TRANSITION CONSTRAINT COST TESTS
+// ===========================================================================
+
+#[test]
+fn transition_constraint_costs_are_attributed_to_constraints() {
+    use crate::{
+        transition_constraint_costs, with_workspace, CircomWorkspace, TransitionConstraintCost,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-costs-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("verifier.sym"),
+        "1,1,1,main.ood.transition_deg_adjustment[1].mul[0].out\n\
+        2,2,0,main.ood.transition_temp[1]\n\
+        3,3,0,main.ood.evaluation_result[0]\n\
+        4,4,0,main.ood.evaluation_result[1]\n\
+        5,5,0,main.ood.transition_temp[0]\n\
+        6,6,0,main.constraint_div[0][0]\n\
+        7,7,0,main.constraint_evalxcoeff[0][1]\n\
+        8,8,2,main.constraintCommitmentVerifier.V[0].P_leaf.mix[0].out\n\
+        9,9,0,main.ood.channel_ood_pow[1]\n\
+        10,10,3,main.fri.out\n\
+        11,11,0,main.ood.evaluation_result[2]\n",
+    )
+    .unwrap();
+    // two constraints of the second transition constraint, one of the first
+    // one, four of the composition columns and two of neither
+    std::fs::write(
+        dir.join("verifier.r1cs"),
+        sample_r1cs(&[
+            [1, 2, 2],
+            [2, 4, 3],
+            [5, 3, 3],
+            [6, 6, 6],
+            [7, 7, 7],
+            [8, 8, 8],
+            [9, 9, 9],
+            [10, 10, 10],
+            [11, 4, 4],
+        ]),
+    )
+    .unwrap();
+
+    let manifest = CircuitManifest {
+        num_composition_columns: Some(4),
+        transition_constraint_degrees: Some(vec![1, 5]),
+        ..sample_manifest("sum")
+    };
+    with_workspace(workspace, || {
+        assert!(matches!(
+            transition_constraint_costs("sum"),
+            Err(WinterCircomError::FileNotFound { .. })
+        ));
+
+        manifest.write().unwrap();
+        // the degree 5 of the second constraint requires 4 columns instead of 1
+        assert_eq!(
+            transition_constraint_costs("sum").unwrap(),
+            vec![
+                TransitionConstraintCost {
+                    constraint: 0,
+                    degree: 1,
+                    evaluation: 1,
+                    composition: 0,
+                },
+                TransitionConstraintCost {
+                    constraint: 1,
+                    degree: 5,
+                    evaluation: 2,
+                    composition: 3,
+                },
+            ]
+        );
+    });
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// HOST ROLE TESTS
+// ===========================================================================
+
+#[test]
+fn host_roles_select_artifacts() {
+    use crate::{distribute_artifacts, prune_artifacts, HostRole};
+
+    let circuit_name = format!("roles-test-{}", std::process::id());
+    let dir = std::path::Path::new("target/circom").join(&circuit_name);
+    std::fs::create_dir_all(dir.join("verifier_js")).unwrap();
+    std::fs::create_dir_all(dir.join("keys/0")).unwrap();
+    std::fs::write(dir.join("manifest.json"), [0; 2]).unwrap();
+    std::fs::write(dir.join("verifier.zkey"), [0; 64]).unwrap();
+    std::fs::write(dir.join("verifier_js/verifier.wasm"), [0; 16]).unwrap();
+    std::fs::write(dir.join("verification_key.json"), [0; 4]).unwrap();
+    std::fs::write(dir.join("keys/0/verifier.zkey"), [0; 64]).unwrap();
+    std::fs::write(dir.join("keys/0/verification_key.json"), [0; 4]).unwrap();
+
+    // a prover receives the proving key and the witness generator only
+    let destination = std::env::temp_dir().join(&circuit_name);
+    let copied = distribute_artifacts(&circuit_name, HostRole::Prover, &destination).unwrap();
+    assert_eq!(copied, 2 + 64 + 16);
+    let received = destination.join(&circuit_name);
+    assert!(received.join("verifier.zkey").exists());
+    assert!(!received.join("verification_key.json").exists());
+    std::fs::remove_dir_all(destination).unwrap();
+
+    // a verifier keeps the verification keys of all epochs
+    assert_eq!(
+        prune_artifacts(&circuit_name, HostRole::Verifier).unwrap(),
+        64 + 16 + 64
+    );
+    assert!(dir.join("keys/0/verification_key.json").exists());
+    assert!(!dir.join("keys/0/verifier.zkey").exists());
+    assert!(!dir.join("verifier_js").exists());
+    assert_eq!(prune_artifacts(&circuit_name, HostRole::Full).unwrap(), 0);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// LOGGING TESTS
+// ===========================================================================
+
+#[test]
+fn format_size_uses_binary_units() {
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1536), "1.5 KiB");
+    assert_eq!(format_size(5 << 20), "5.0 MiB");
+    assert_eq!(format_size(3 << 40), "3.0 TiB");
+}
+
+#[test]
+fn color_choices_are_parsed() {
+    use crate::utils::ColorChoice;
+
+    assert_eq!("auto".parse(), Ok(ColorChoice::Auto));
+    assert_eq!("Always".parse(), Ok(ColorChoice::Always));
+    assert_eq!(" never\n".parse(), Ok(ColorChoice::Never));
+    assert!("sometimes".parse::<ColorChoice>().is_err());
+}
+
+// ERROR OUTPUT TESTS
+// ===========================================================================
+
+#[test]
+fn errors_report_stable_classes_as_json() {
+    let error = WinterCircomError::FileNotFound {
+        file: String::from("verifier.zkey"),
+        comment: None,
+    };
+    assert_eq!(error.class(), ErrorClass::MissingArtifact);
+
+    let json = error.to_json();
+    assert_eq!(json["status"], "error");
+    assert_eq!(json["error_class"], "missing_artifact");
+    assert_eq!(json["exit_code"], 11);
+    assert!(!json["message"].as_str().unwrap().contains('\x1b'));
+
+    assert_eq!(print_json_result(Ok(json!({ "epoch": 1 }))), 0);
+    assert_eq!(
+        print_json_result::<()>(Err(WinterCircomError::OutdatedCircuit {
+            circuit_name: String::from("sum")
+        })),
+        15
+    );
+}
+
+// EXECUTOR TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn mock_executor_records_verification() {
+    use std::rc::Rc;
+
+    use crate::{circom_verify, utils::LoggingLevel, with_executor, MockExecutor};
+
+    let circuit_name = format!("executor-test-{}", std::process::id());
+    let circuit_dir = std::path::PathBuf::from(format!("target/circom/{}", circuit_name));
+    std::fs::create_dir_all(&circuit_dir).unwrap();
+    for file in ["verification_key.json", "public.json", "proof.json"] {
+        std::fs::write(circuit_dir.join(file), "{}").unwrap();
+    }
+
+    let executor = Rc::new(MockExecutor::new());
+    with_executor(executor.clone(), || {
+        circom_verify(&circuit_name, LoggingLevel::Quiet)
+    })
+    .unwrap();
+    let invocations = executor.invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0].program, "snarkjs");
+    assert_eq!(
+        invocations[0].args,
+        ["g16v", "verification_key.json", "public.json", "proof.json"]
+    );
+    assert_eq!(invocations[0].current_dir.as_ref(), Some(&circuit_dir));
+
+    // canned failures are reported as failures of the executable
+    let executor = Rc::new(MockExecutor::new().with_failure("snarkjs", &["g16v"], 1));
+    let result = with_executor(executor, || {
+        circom_verify(&circuit_name, LoggingLevel::Quiet)
+    });
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::ExitCodeError { code: 1, .. })
+    ));
+
+    std::fs::remove_dir_all(circuit_dir).unwrap();
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn in_memory_verification_uses_a_temporary_directory() {
+    use std::rc::Rc;
+
+    use crate::{circom_verify_in_memory, utils::LoggingLevel, with_executor, MockExecutor};
+
+    let executor = Rc::new(MockExecutor::new());
+    with_executor(executor.clone(), || {
+        circom_verify_in_memory(b"{}", &json!(["1"]), &json!({}), LoggingLevel::Quiet)
+    })
+    .unwrap();
+    let invocations = executor.invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(
+        invocations[0].args,
+        ["g16v", "verification_key.json", "public.json", "proof.json"]
+    );
+    let dir = invocations[0].current_dir.clone().unwrap();
+    assert!(dir.starts_with(std::env::temp_dir()));
+    assert!(!dir.exists());
+
+    // the directory is removed on failure as well
+    let executor = Rc::new(MockExecutor::new().with_failure("snarkjs", &["g16v"], 1));
+    let result = with_executor(executor.clone(), || {
+        circom_verify_in_memory(b"{}", &json!(["1"]), &json!({}), LoggingLevel::Quiet)
+    });
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::ExitCodeError { code: 1, .. })
+    ));
+    assert!(!executor.invocations()[0]
+        .current_dir
+        .as_ref()
+        .unwrap()
+        .exists());
+}
+
+// PIPELINE TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn pipeline_skips_up_to_date_stages() {
+    use std::{fs, rc::Rc};
+
+    use crate::{
+        utils::LoggingLevel, with_executor, with_workspace, CircomWorkspace, MockExecutor,
+        Pipeline, Stage,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-pipeline-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root).with_ptau(root.join("final.ptau"));
+    let dir = workspace.circuit_dir("sum");
+    fs::create_dir_all(dir.join("verifier_js")).unwrap();
+    fs::write(root.join("final.ptau"), "").unwrap();
+    fs::write(dir.join("verifier.circom"), "").unwrap();
+    with_workspace(workspace.clone(), || sample_manifest("sum").write()).unwrap();
+    // the artifacts of the external commands
+    for file in [
+        "verifier.r1cs",
+        "verifier.sym",
+        "verifier_js/verifier.wasm",
+        "verifier.zkey",
+        "verification_key.json",
+        "input.json",
+        "witness.wtns",
+        "proof.json",
+        "public.json",
+    ] {
+        fs::write(dir.join(file), "{}").unwrap();
+    }
+
+    let run = |pipeline: Pipeline| {
+        let executor = Rc::new(MockExecutor::new());
+        let stages = with_workspace(workspace.clone(), || {
+            with_executor(executor.clone(), || pipeline.run(LoggingLevel::Quiet))
+        })
+        .unwrap();
+        (stages, executor.invocations())
+    };
+    let commands = |invocations: &[crate::Invocation]| {
+        invocations
+            .iter()
+            .map(|invocation| format!("{} {}", invocation.program, invocation.args[0]))
+            .collect::<Vec<_>>()
+    };
+
+    // the compile and setup stages run in order, whatever the order they are
+    // added in
+    let (stages, invocations) = run(Pipeline::new("sum").setup().compile());
+    assert_eq!(stages, [Stage::Compile, Stage::Setup]);
+    assert_eq!(
+        commands(&invocations),
+        [
+            "circom verifier.circom",
+            "snarkjs g16s",
+            "snarkjs zkc",
+            "snarkjs zkev"
+        ]
+    );
+    assert_eq!(
+        invocations[1].args[2],
+        root.join("final.ptau").to_string_lossy()
+    );
+    assert!(invocations
+        .iter()
+        .all(|i| i.current_dir.as_ref() == Some(&dir)));
+
+    // once compiled, only the witness and the proof are computed again
+    let (stages, invocations) = run(Pipeline::full("sum"));
+    assert_eq!(stages, [Stage::Witness, Stage::Prove, Stage::Verify]);
+    assert_eq!(
+        commands(&invocations),
+        ["snarkjs wc", "snarkjs g16p", "snarkjs g16v"]
+    );
+    assert_eq!(
+        invocations[0].args[2],
+        dir.join("input.json").to_string_lossy()
+    );
+
+    // the inputs are compared by content, not by modification time
+    fs::write(dir.join("verifier.circom"), "").unwrap();
+    let (stages, _) = run(Pipeline::full("sum"));
+    assert_eq!(stages, [Stage::Witness, Stage::Prove, Stage::Verify]);
+    fs::write(root.join("final.ptau"), "replaced").unwrap();
+    let (stages, _) = run(Pipeline::full("sum"));
+    assert_eq!(
+        stages,
+        [Stage::Setup, Stage::Witness, Stage::Prove, Stage::Verify]
+    );
+
+    // a missing output of the compilation reruns every stage
+    fs::remove_file(dir.join("verifier.sym")).unwrap();
+    let (stages, _) = run(Pipeline::full("sum"));
+    assert_eq!(stages, Stage::ALL);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn witness_inputs_are_piped_from_memory() {
+    use std::{fs, rc::Rc};
+
+    use crate::{
+        compute_witness_from_input,
+        utils::{LoggingLevel, STDIN_PATH},
+        with_executor, with_workspace, CircomWorkspace, MockExecutor,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-stdin-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    let input = json!({ "ood_trace_frame": ["1", "2"] });
+
+    let executor = Rc::new(MockExecutor::new());
+    with_workspace(workspace, || {
+        with_executor(executor.clone(), || {
+            compute_witness_from_input("sum", &input, LoggingLevel::Quiet)
+        })
+    })
+    .unwrap();
+    let invocations = executor.invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0].args[0], "wc");
+    assert_eq!(
+        invocations[0].args[3],
+        dir.join("witness.wtns").to_string_lossy()
+    );
+    match STDIN_PATH {
+        // the input is neither written nor read back
+        Some(stdin_path) => {
+            assert_eq!(invocations[0].args[2], stdin_path);
+            assert_eq!(
+                invocations[0].stdin.as_deref(),
+                Some(input.to_string().as_bytes())
+            );
+            assert!(!dir.join("input.json").exists());
+        }
+        None => {
+            assert_eq!(invocations[0].stdin, None);
+            assert_eq!(
+                fs::read(dir.join("input.json")).unwrap(),
+                input.to_string().into_bytes()
+            );
+        }
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "service")]
+#[test]
+fn async_stages_check_their_inputs() {
+    use crate::{
+        circom_compile_async, history,
+        utils::{LoggingLevel, WinterCircomError},
+        with_workspace, AuditAction, CircomWorkspace, Pipeline,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-async-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    // the futures can be spawned on multi-threaded runtimes
+    fn assert_send<T: Send + 'static>(_: &T) {}
+    let compile = with_workspace(workspace.clone(), || {
+        circom_compile_async("sum", LoggingLevel::Quiet)
+    });
+    assert_send(&compile);
+
+    // the workspace is captured when the future is created, not when polled
+    assert!(matches!(
+        runtime.block_on(compile),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    let pipeline = with_workspace(workspace.clone(), || {
+        Pipeline::full("sum").run_async(LoggingLevel::Quiet)
+    });
+    assert!(matches!(
+        runtime.block_on(pipeline),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+    let history = with_workspace(workspace, || history("sum")).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history
+        .iter()
+        .all(|entry| entry.action == AuditAction::Compile && entry.error.is_some()));
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+// CLI TESTS
+// ===========================================================================
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_configs_are_validated() {
+    use clap::Parser;
+
+    use crate::{
+        cli::{Cli, CliCommand, CliConfig, GadgetCircuit, TransferConfig},
+        utils::{ColorChoice, LoggingLevel},
+    };
+
+    let cli =
+        Cli::try_parse_from(["winter-circom", "--json", "-c", "rollup.json", "prove"]).unwrap();
+    assert_eq!(cli.command, CliCommand::Prove);
+    assert_eq!(cli.config, std::path::PathBuf::from("rollup.json"));
+    assert_eq!(cli.logging_level(), LoggingLevel::Quiet);
+    assert!(cli.json_output());
+    let cli = Cli::try_parse_from(["winter-circom", "--output", "json", "verify"]).unwrap();
+    assert!(cli.json_output());
+    assert_eq!(cli.logging_level(), LoggingLevel::Quiet);
+    let cli = Cli::try_parse_from(["winter-circom", "--output", "text", "verify"]).unwrap();
+    assert!(!cli.json_output());
+    assert!(Cli::try_parse_from(["winter-circom", "--output", "yaml", "verify"]).is_err());
+    assert!(Cli::try_parse_from(["winter-circom", "-q", "-v", "verify"]).is_err());
+    assert_eq!(cli.color, None);
+    let cli = Cli::try_parse_from(["winter-circom", "--color", "never", "verify"]).unwrap();
+    assert_eq!(cli.color, Some(ColorChoice::Never));
+    assert!(Cli::try_parse_from(["winter-circom", "--color", "sometimes", "verify"]).is_err());
+    let cli = Cli::try_parse_from([
+        "winter-circom",
+        "scaffold",
+        "my_circuit",
+        "--trace-width",
+        "2",
+        "--assertions",
+        "3",
+        "--degrees",
+        "1,2",
+    ])
+    .unwrap();
+    assert!(matches!(
+        cli.command,
+        CliCommand::Scaffold { trace_length: 128, ref degrees, .. } if degrees == &[1, 2]
+    ));
+
+    let config = CliConfig::parse(
+        r#"{
+            "circuit_name": "rollup",
+            "circuit": {
+                "gadget": "rollup",
+                "balances": [1000, 500],
+                "transfers": [{ "a_to_b": 20 }, { "b_to_a": 10 }, { "a_to_b": 5 }, { "a_to_b": 1 },
+                    { "a_to_b": 2 }, { "a_to_b": 3 }, { "a_to_b": 4 }]
+            },
+            "workspace": { "output_dir": "build/circom" }
+        }"#,
+        "rollup.json",
+    )
+    .unwrap();
+    assert_eq!(config.circuit.trace_length(), 8);
+    assert!(matches!(
+        &config.circuit,
+        GadgetCircuit::Rollup { transfers, .. } if transfers[1] == TransferConfig::BToA(10)
+    ));
+    assert_eq!(
+        config.workspace().root_dir(),
+        std::path::Path::new("build/circom")
+    );
+
+    // the inputs must be provable by the gadget
+    let merkle = |siblings: &str| {
+        CliConfig::parse(
+            &format!(
+                r#"{{ "circuit_name": "merkle", "circuit": {{ "gadget": "merkle", "leaf": 42, "index": 5, "siblings": {} }} }}"#,
+                siblings
+            ),
+            "merkle.json",
+        )
+    };
+    assert!(merkle("[1, 2, 3, 4, 5, 6, 7]").is_ok());
+    let error = merkle("[1, 2, 3]").unwrap_err();
+    assert!(matches!(error, WinterCircomError::InvalidConfig { .. }));
+    assert_eq!(error.exit_code(), 14);
+    assert!(matches!(
+        CliConfig::parse(
+            r#"{ "circuit_name": "sum", "circuit": { "gadget": "sum" } }"#,
+            "sum.json"
+        ),
+        Err(WinterCircomError::InvalidConfig { .. })
+    ));
+}
+
+// PIPELINE PLAN TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn plans_list_the_steps_to_run() {
+    use std::fs;
+
+    use crate::{gadgets::merkle, plan, with_workspace, AuditAction, CircomWorkspace};
+
+    let root = std::env::temp_dir().join(format!("winter-circom-plan-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root).with_ptau(root.join("final.ptau"));
+    let dir = workspace.circuit_dir("merkle");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("verifier.circom"), "").unwrap();
+
+    let plan = with_workspace(workspace, || plan("merkle", &merkle::proof_options(3))).unwrap();
+    let actions = plan
+        .steps
+        .iter()
+        .map(|step| step.action)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actions,
+        [
+            AuditAction::Create,
+            AuditAction::Compile,
+            AuditAction::Setup,
+            AuditAction::Witness,
+            AuditAction::Groth16Prove,
+            AuditAction::Verify,
+        ]
+    );
+    assert!(plan.up_to_date.is_empty());
+    assert_eq!(plan.estimated_duration(), None);
+
+    // nothing is written, and the random entropy is not disclosed
+    assert!(plan.steps[0].commands.is_empty());
+    assert!(plan.steps[0].files[0].overwritten);
+    assert!(!plan.steps[0].files[1].overwritten);
+    assert!(!dir.join("manifest.json").exists());
+    assert_eq!(
+        plan.steps[1].commands,
+        ["circom verifier.circom --r1cs --wasm --sym"]
+    );
+    assert!(plan.steps[2].commands[1].ends_with("-e=<random>"));
+    assert!(plan
+        .to_string()
+        .starts_with("Plan of circuit merkle: 6 steps.\n\n+ create"));
+
+    let _ = fs::remove_dir_all(root);
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn plans_skip_the_stages_whose_inputs_are_unchanged() {
+    use std::{fs, rc::Rc};
+
+    use crate::{
+        codegen::circuit_manifest, gadgets::merkle, plan, utils::LoggingLevel, with_executor,
+        with_workspace, AuditAction, CircomWorkspace, MockExecutor, Pipeline,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-replan-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(root.join("circom"))
+        .with_circuits_dir(root.join("circuits"))
+        .with_ptau(root.join("final.ptau"));
+    let dir = workspace.circuit_dir("merkle");
+    fs::create_dir_all(root.join("circuits/air")).unwrap();
+    fs::create_dir_all(dir.join("verifier_js")).unwrap();
+    fs::write(root.join("circuits/air/merkle.circom"), "").unwrap();
+    fs::write(root.join("final.ptau"), "").unwrap();
+    fs::write(dir.join("verifier.circom"), "").unwrap();
+    let options = merkle::proof_options(3);
+
+    let executor = Rc::new(MockExecutor::new());
+    with_workspace(workspace.clone(), || {
+        let mut manifest = circuit_manifest(&options, "merkle");
+        manifest.source_fingerprint = CircuitManifest::source_fingerprint("merkle");
+        manifest.write().unwrap();
+
+        // the compilation is run before its outputs exist, so that its
+        // constraint degrees are not checked against the fake artifacts
+        with_executor(executor.clone(), || {
+            Pipeline::new("merkle").compile().run(LoggingLevel::Quiet)?;
+            for file in [
+                "verifier.r1cs",
+                "verifier.sym",
+                "verifier_js/verifier.wasm",
+                "verifier.zkey",
+                "verification_key.json",
+            ] {
+                fs::write(dir.join(file), "{}").unwrap();
+            }
+            Pipeline::new("merkle").setup().run(LoggingLevel::Quiet)
+        })
+    })
+    .unwrap();
+
+    // rewriting the sources with the same contents makes them newer than the
+    // compiled artifacts, which are still up to date
+    fs::write(dir.join("verifier.circom"), "").unwrap();
+    fs::write(root.join("final.ptau"), "").unwrap();
+    let planned = with_workspace(workspace.clone(), || plan("merkle", &options)).unwrap();
+    assert_eq!(
+        planned.up_to_date,
+        [
+            AuditAction::Create,
+            AuditAction::Compile,
+            AuditAction::Setup
+        ]
+    );
+    assert_eq!(planned.steps[0].action, AuditAction::Witness);
+
+    // a transcript with other contents requires new keys
+    fs::write(root.join("final.ptau"), "replaced").unwrap();
+    let planned = with_workspace(workspace, || plan("merkle", &options)).unwrap();
+    assert_eq!(planned.steps[0].action, AuditAction::Setup);
+
+    let _ = fs::remove_dir_all(root);
+}
+
+// PROJECT MANIFEST TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn project_manifests_are_validated() {
+    use crate::{
+        build_all, gadgets::merkle::MerkleProver, utils::LoggingLevel, CircuitRegistry,
+        ProjectManifest,
+    };
+
+    let circuit = |name: &str, degrees: &str| {
+        format!(
+            r#"
+            [[circuits]]
+            name = "{}"
+            trace_length = 8
+            trace_width = 4
+            num_assertions = 2
+            transition_constraint_degrees = {}
+            num_queries = 12
+            lde_blowup_factor = 8
+            grinding_factor = 0
+            fri_folding_factor = 4
+            fri_max_remainder_size = 8
+            "#,
+            name, degrees
+        )
+    };
+    let data = format!(
+        "ptau = \"final.ptau\"\n{}{}ptau = \"large.ptau\"\n",
+        circuit("merkle", "[5, 5, 1]"),
+        circuit("sum", "[1, 1]"),
+    );
+    let manifest = ProjectManifest::parse(&data, ProjectManifest::FILE_NAME).unwrap();
+    assert_eq!(manifest.circuits.len(), 2);
+    assert_eq!(manifest.ptau, Some(std::path::PathBuf::from("final.ptau")));
+    let sum = manifest.circuit("sum").unwrap();
+    assert_eq!(sum.ptau, Some(std::path::PathBuf::from("large.ptau")));
+    assert_eq!(sum.parameters().num_transition_constraints, 2);
+    assert!(sum.proof_options::<2>().is_ok());
+    assert!(matches!(
+        sum.proof_options::<3>(),
+        Err(WinterCircomError::InvalidConfig { .. })
+    ));
+
+    // circuit names are unique
+    let duplicate = format!("{}{}", circuit("sum", "[1, 1]"), circuit("sum", "[1, 1]"));
+    assert!(matches!(
+        ProjectManifest::parse(&duplicate, ProjectManifest::FILE_NAME),
+        Err(WinterCircomError::InvalidConfig { .. })
+    ));
+
+    // every circuit needs a prover before anything is built
+    let registry = CircuitRegistry::new().register::<MerkleProver, 3>("merkle");
+    assert!(registry.contains("merkle"));
+    let error = build_all(&manifest, &registry, LoggingLevel::Quiet).unwrap_err();
+    assert!(matches!(error, WinterCircomError::InvalidConfig { .. }));
+    assert_eq!(error.exit_code(), 14);
+    let registry = CircuitRegistry::new()
+        .register::<MerkleProver, 3>("merkle")
+        .register::<MerkleProver, 3>("sum");
+    assert!(build_all(&manifest, &registry, LoggingLevel::Quiet).is_err());
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn presets_are_looked_up_by_name() {
+    use crate::{
+        gadgets::merkle::{self, MerkleProver},
+        utils::LoggingLevel,
+        CircuitRegistry,
+    };
+
+    let registry = CircuitRegistry::new()
+        .register_preset::<MerkleProver, 3>("merkle", merkle::proof_options(5));
+    assert!(registry.contains("merkle"));
+    let preset = registry.preset::<3>("merkle").unwrap();
+    assert_eq!(preset.trace_length, merkle::proof_options(5).trace_length);
+
+    // presets are typed by their number of transition constraints
+    assert!(registry.preset::<2>("merkle").is_none());
+    assert!(registry.preset::<3>("sum").is_none());
+    assert!(matches!(
+        registry.create("sum", LoggingLevel::Quiet),
+        Err(WinterCircomError::InvalidCircuitName { .. })
+    ));
+}
+
+// SCAFFOLD TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn scaffolds_mirror_the_examples() {
+    use std::fs;
+
+    use crate::{scaffold, with_workspace, CircomWorkspace, ScaffoldOptions};
+
+    let root = std::env::temp_dir().join(format!("winter-circom-scaffold-{}", std::process::id()));
+    let workspace =
+        CircomWorkspace::new(root.join("target")).with_circuits_dir(root.join("circuits"));
+    let options = ScaffoldOptions::new(64, 2, 3, vec![1, 2]).crate_dir(root.join("example"));
+    assert_eq!(options.lde_blowup_factor, 8);
+
+    let files = with_workspace(workspace.clone(), || scaffold("my_circuit", &options)).unwrap();
+    assert_eq!(files.len(), 7);
+    assert_eq!(
+        files[0],
+        root.join("circuits").join("air").join("my_circuit.circom")
+    );
+    let read = |path: &[&str]| {
+        fs::read_to_string(path.iter().fold(root.clone(), |dir, file| dir.join(file))).unwrap()
+    };
+    assert!(read(&["circuits", "air", "my_circuit.circom"]).contains("transition_degree[1] <== 2;"));
+    assert!(read(&["example", "Cargo.toml"]).contains("name = \"example-my-circuit\""));
+    let air = read(&["example", "src", "air.rs"]);
+    assert!(air.contains("WinterCircomProofOptions<2> =\n    WinterCircomProofOptions::new(64, 2, 3, [1, 2], 32, 8, 0, 8, 128);"));
+    assert!(air.contains("impl Air for MyCircuitAir {"));
+    assert!(read(&["example", "src", "create.rs"])
+        .contains("circom_create::<MyCircuitProver, 2>(PROOF_OPTIONS, \"my_circuit\""));
+
+    // existing files are never overwritten
+    fs::write(root.join("example").join("src").join("verify.rs"), "").unwrap();
+    fs::remove_file(root.join("circuits").join("air").join("my_circuit.circom")).unwrap();
+    let error = with_workspace(workspace, || scaffold("my_circuit", &options)).unwrap_err();
+    assert!(matches!(error, WinterCircomError::IoError { .. }));
+    assert!(!root
+        .join("circuits")
+        .join("air")
+        .join("my_circuit.circom")
+        .exists());
+
+    let _ = fs::remove_dir_all(root);
+}
+
+// PRELUDE TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn prelude_reexports_the_pipeline() {
+    use crate::prelude::*;
+
+    let verify: fn(&str, LoggingLevel) -> Result<(), WinterCircomError> = circom_verify;
+    let stages = Pipeline::new("sum").compile().setup().stages();
+    assert_eq!(stages, [Stage::Compile, Stage::Setup]);
+
+    let root = std::env::temp_dir().join(format!("winter-circom-prelude-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let error = with_workspace(workspace, || verify("sum", LoggingLevel::Quiet)).unwrap_err();
+    assert_eq!(error.class(), ErrorClass::MissingArtifact);
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+// PRE-GENERATED PROOF TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn pre_generated_proofs_are_deserialized_first() {
+    use crate::{
+        circom_prove_from_proof,
+        gadgets::merkle::{MerkleAir, MerklePublicInputs},
+        utils::LoggingLevel,
+    };
+
+    let circuit_name = format!("from-proof-test-{}", std::process::id());
+    let result = circom_prove_from_proof::<MerkleAir>(
+        &[1, 2, 3],
+        MerklePublicInputs::default(),
+        None,
+        &circuit_name,
+        LoggingLevel::Quiet,
+    );
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::InvalidStarkProof { .. })
+    ));
+
+    // nothing is recorded for proofs that cannot be read
+    assert!(!std::path::Path::new("target/circom")
+        .join(&circuit_name)
+        .exists());
+}
+
+// WATCHDOG TESTS
+// ===========================================================================
+
+#[cfg(unix)]
+#[test]
+fn watchdog_kills_inactive_process() {
+    use std::{process::Command, time::Duration};
+
+    let watchdog = Watchdog::new(Duration::from_millis(200));
+
+    let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    assert_eq!(watchdog.wait(&mut child, false).unwrap(), None);
+
+    let mut child = Command::new("true").spawn().unwrap();
+    assert!(watchdog.wait(&mut child, false).unwrap().unwrap().success());
+}
+
+#[cfg(unix)]
+#[test]
+fn piped_inputs_are_written_while_the_child_is_supervised() {
+    use crate::{
+        utils::{run_process, Executable},
+        Invocation,
+    };
+
+    // larger than the buffer of a pipe
+    let input = vec![b'0'; 1 << 22];
+    let run = |script: &str| {
+        run_process(&Invocation {
+            program: String::from("sh"),
+            args: vec![String::from("-c"), String::from(script)],
+            current_dir: None,
+            stdin: Some(input.clone()),
+            executable: Executable::Custom {
+                path: String::from("/bin/sh"),
+                verbose_argument: None,
+            },
+            verbose: false,
+            print_output: false,
+            capture_output: false,
+        })
+    };
+
+    assert!(run("cat > /dev/null").is_ok());
+    // the failure of a child which stops reading is reported, not the broken
+    // pipe
+    assert!(matches!(
+        run("exit 3"),
+        Err(WinterCircomError::ExitCodeError { code: 3, .. })
+    ));
+}
+
+// MEMORY LIMIT TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn memory_limit_rejects_large_traces() {
+    use crate::MemoryLimit;
+
+    // 2^20 rows of 4 columns with a blowup factor of 8
+    let estimate = MemoryLimit::estimate(1 << 20, 4, 8, 2);
+    assert_eq!(estimate, (4 * 9 + 2 * 8 + 4 * 8) << 25);
+
+    assert!(MemoryLimit::new(4 << 30).check(estimate).is_ok());
+    assert!(matches!(
+        MemoryLimit::new(1 << 30).check(estimate),
+        Err(WinterCircomError::MemoryLimitExceeded { .. })
+    ));
+}
+
+// CONCURRENCY TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn concurrency_defaults_to_the_rayon_thread_pool() {
+    use crate::Concurrency;
+
+    assert_eq!(Concurrency::default().num_threads(), None);
+    assert_eq!(Concurrency::new(4).num_threads(), Some(4));
+    assert!(std::panic::catch_unwind(|| Concurrency::new(0)).is_err());
+
+    // values of the environment variable
+    assert_eq!("8".parse::<Concurrency>(), Ok(Concurrency::new(8)));
+    for value in ["0", "-1", "four", ""] {
+        assert!(value.parse::<Concurrency>().is_err());
+    }
+}
+
+// POSEIDON TESTS
+// ===========================================================================
+
+#[test]
+fn merkle_root_hashes_nodes_in_index_order() {
+    use crate::circom_poseidon::{hash_elements, hash_merkle_node, merkle_root};
+
+    let e = |x: u64| BaseElement::from(x);
+    let leaf = [e(1), e(2), e(3)];
+    let opening = [e(4), e(5)];
+
+    // index 2 = 0b10: the leaf is a left child, its parent a right child
+    let parent = hash_merkle_node(hash_elements(&leaf), opening[0]);
+    let root = hash_merkle_node(opening[1], parent);
+
+    assert_eq!(merkle_root(&leaf, 2, &opening), root);
+    assert_ne!(merkle_root(&leaf, 1, &opening), root);
+}
+
+#[test]
+fn hash_elements_matches_test_vectors() {
+    use crate::circom_poseidon::{
+        hash_elements, hash_merkle_node, verify_test_vectors, MDS, TEST_VECTORS,
+    };
+
+    assert_eq!(verify_test_vectors(hash_elements), Ok(()));
+
+    // another hash function fails on the first vector
+    let other = |elements: &[BaseElement]| hash_elements(&[elements, &[BaseElement::ONE]].concat());
+    assert_eq!(verify_test_vectors(other), Err(TEST_VECTORS[0]));
+
+    // the vector of two elements is a Merkle node
+    let e = |x: u64| BaseElement::from(x);
+    assert_eq!(
+        hash_merkle_node(e(1), e(2)),
+        TEST_VECTORS[3].digest_element()
+    );
+
+    // the MDS matrix is the one of the circuits
+    let params = std::fs::read_to_string("../circuits/poseidon/param.circom").unwrap();
+    let matrix = params.split("POSEIDON_M(t)").nth(1).unwrap();
+    let matrix = &matrix[..matrix.find(';').unwrap()];
+    let entries: Vec<&str> = matrix
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    assert_eq!(entries, MDS.concat());
+}
+
+#[test]
+fn query_positions_are_the_first_distinct_draws() {
+    use winterfell::math::StarkField;
+
+    use crate::circom_poseidon::{derive_query_positions, hash_elements, QueryDrawParameters};
+
+    let parameters = QueryDrawParameters {
+        num_draws: 64,
+        num_queries: 8,
+        lde_domain_size: 16,
+    };
+    let seed = BaseElement::from(42u64);
+    let positions = derive_query_positions(seed, parameters).unwrap();
+    assert_eq!(positions.len(), 8);
+    assert!(positions.iter().all(|position| *position < 16));
+    assert_eq!(positions.iter().collect::<HashSet<_>>().len(), 8);
+
+    // the first draw is always a query position
+    let first = hash_elements(&[seed, BaseElement::ONE]).as_int().low_u64() & 15;
+    assert_eq!(positions[0], first as usize);
+
+    // too few draws to find enough distinct positions
+    let parameters = QueryDrawParameters {
+        num_draws: 4,
+        ..parameters
+    };
+    assert_eq!(derive_query_positions(seed, parameters), None);
+}
+
+/// Compares [derive_query_positions] with the witness of the
+/// `DrawQueryPositions` Circom template. Requires `circom` and `node`.
+#[test]
+#[ignore]
+fn query_positions_match_circuit() {
+    use std::process::Command;
+
+    use winterfell::math::StarkField;
+
+    use crate::circom_poseidon::{derive_query_positions, QueryDrawParameters};
+
+    let parameters = QueryDrawParameters {
+        num_draws: 32,
+        num_queries: 8,
+        lde_domain_size: 64,
+    };
+    let dir = std::path::Path::new("target/circom")
+        .join(format!("query-parity-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("main.circom"),
+        format!(
+            "pragma circom 2.0.0;\n\
+            include \"../../../../circuits/public_coin.circom\";\n\
+            component main = DrawQueryPositions({}, {}, {});\n",
+            parameters.num_draws, parameters.num_queries, parameters.lde_domain_size
+        ),
+    )
+    .unwrap();
+    let run = |program: &str, args: &[&str]| {
+        let status = Command::new(program)
+            .args(args)
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "{} failed", program);
+    };
+    run("circom", &["main.circom", "--wasm"]);
+
+    for seed in [1u64, 7, 1 << 40] {
+        let seed = BaseElement::from(seed);
+        let input = json!({ "seed": seed.as_int().to_string() });
+        std::fs::write(dir.join("input.json"), input.to_string()).unwrap();
+        run(
+            "node",
+            &[
+                "main_js/generate_witness.js",
+                "main_js/main.wasm",
+                "input.json",
+                "witness.wtns",
+            ],
+        );
+
+        // the outputs are the first signals of the witness, after the constant 1
+        let witness = read_witness(&std::fs::read(dir.join("witness.wtns")).unwrap());
+        let circuit_positions = witness[1..=parameters.num_queries]
+            .iter()
+            .map(|value| u64::from_le_bytes(value[..8].try_into().unwrap()) as usize)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            derive_query_positions(seed, parameters).unwrap(),
+            circuit_positions
+        );
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Returns the little-endian values of a `.wtns` file.
+fn read_witness(wtns: &[u8]) -> Vec<Vec<u8>> {
+    let u32_at = |pos: usize| u32::from_le_bytes(wtns[pos..pos + 4].try_into().unwrap()) as usize;
+    let u64_at = |pos: usize| u64::from_le_bytes(wtns[pos..pos + 8].try_into().unwrap()) as usize;
+    assert_eq!(&wtns[..4], b"wtns");
+
+    // magic, version and number of sections, then the header and witness
+    // sections, each preceded by its type and size
+    let mut pos = 12;
+    let mut field_size = 0;
+    let mut values = Vec::new();
+    while pos < wtns.len() {
+        let (section_type, size) = (u32_at(pos), u64_at(pos + 4));
+        let section = &wtns[pos + 12..pos + 12 + size];
+        match section_type {
+            1 => field_size = u32::from_le_bytes(section[..4].try_into().unwrap()) as usize,
+            2 => values = section.chunks(field_size).map(|v| v.to_vec()).collect(),
+            _ => {}
+        }
+        pos += 12 + size;
+    }
+    values
+}
+
+// DATA COMMITMENT TESTS
+// ===========================================================================
+
+#[test]
+fn commitment_opening_is_length_prefixed_and_padded() {
+    use crate::commitment::{commit, commitment_opening, CHUNK_BYTES};
+
+    let data = [1u8; CHUNK_BYTES + 1];
+    let opening = commitment_opening(&data, 3 * CHUNK_BYTES).unwrap();
+    assert_eq!(opening.len(), 4);
+    assert_eq!(opening[0], BaseElement::from(32u8));
+    assert_eq!(opening[2], BaseElement::from(1u8));
+    assert_eq!(opening[3], BaseElement::ZERO);
+
+    // the length prefix distinguishes data from its zero-padded extension
+    assert_ne!(
+        commit(&data, 3 * CHUNK_BYTES).unwrap(),
+        commit(&[&data[..], &[0]].concat(), 3 * CHUNK_BYTES).unwrap()
+    );
+    assert!(matches!(
+        commitment_opening(&data, CHUNK_BYTES),
+        Err(WinterCircomError::CommitmentDataTooLarge { size: 32, .. })
+    ));
+}
+
+// PUBLIC INPUT PACKING TESTS
+// ===========================================================================
+
+#[test]
+fn public_input_layout_packs_small_inputs() {
+    use crate::{PublicInputLayout, MAX_PACKED_BITS};
+
+    let layout = PublicInputLayout::new(&[64, 64, 0, 128, 128, 64]);
+    assert_eq!(layout.slots(), &[0, 0, 1, 2, 3, 3]);
+    assert_eq!(layout.num_packed(), 4);
+    assert!(layout.is_packed());
+    assert!(!PublicInputLayout::new(&[0, 0]).is_packed());
+    assert_eq!(
+        PublicInputLayout::new(&[MAX_PACKED_BITS, 1]).num_packed(),
+        2
+    );
+
+    let e = |x: u64| BaseElement::from(x);
+    let inputs = [e(1), e(u64::MAX), -e(1), e(3), e(4), e(5)];
+    let packed = layout.pack(&inputs).unwrap();
+    assert_eq!(packed.len(), 4);
+    assert_eq!(packed[1], -e(1));
+    assert_eq!(layout.unpack(&packed), inputs.to_vec());
+
+    assert!(matches!(
+        PublicInputLayout::new(&[8]).pack(&[e(256)]),
+        Err(WinterCircomError::PublicInputTooLarge { index: 0, bits: 8 })
+    ));
+}
+
+// CSV TRACE TESTS
+// ===========================================================================
+
+#[test]
+fn column_parsers_reject_invalid_values() {
+    use winterfell::math::StarkField;
+
+    use crate::trace::ColumnParser;
+
+    let e = |x: u64| Some(BaseElement::from(x));
+    assert_eq!(ColumnParser::U64.parse(" 42 "), e(42));
+    assert_eq!(ColumnParser::U64.parse("-1"), None);
+    assert_eq!(ColumnParser::U64.parse("18446744073709551616"), None);
+
+    assert_eq!(ColumnParser::Hex.parse("0x2a"), e(42));
+    assert_eq!(ColumnParser::Hex.parse("0X2A"), e(42));
+    assert_eq!(ColumnParser::Hex.parse("2a"), e(42));
+    assert_eq!(ColumnParser::Hex.parse("0xg"), None);
+
+    assert_eq!(ColumnParser::Decimal.parse("42"), e(42));
+    assert_eq!(
+        ColumnParser::Decimal.parse(&(-BaseElement::ONE).as_int().to_string()),
+        Some(-BaseElement::ONE)
+    );
+    // values must be lower than the field modulus
+    assert_eq!(
+        ColumnParser::Decimal.parse(&BaseElement::MODULUS.to_string()),
+        None
+    );
+    assert_eq!(ColumnParser::Decimal.parse("4.2"), None);
+}
+
+#[test]
+fn csv_errors_point_at_the_offending_row() {
+    use winterfell::{Trace, TraceTable};
+
+    use crate::trace::{ColumnMapping, ColumnParser, TraceTableExt};
+
+    let dir = std::env::temp_dir().join(format!("winter-circom-csv-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("trace.csv");
+    let mapping = ColumnMapping::new(vec!["b", "a"]);
+    let parsers = [ColumnParser::Hex, ColumnParser::U64];
+    let header = "a,b,unused\n";
+    let rows = (0..8)
+        .map(|i| format!("{},0x{:x},x\n", i, 16 + i))
+        .collect::<String>();
+    let read = |data: &str, parsers: &[ColumnParser]| {
+        std::fs::write(&path, data).unwrap();
+        TraceTable::<BaseElement>::from_csv(&path, &mapping, parsers)
+    };
+    let row = |result: Result<TraceTable<BaseElement>, WinterCircomError>| match result {
+        Err(WinterCircomError::InvalidTrace { row, .. }) => row,
+        other => panic!("unexpected result: {:?}", other.map(|trace| trace.length())),
+    };
+
+    // the columns are mapped by name, unmapped columns are not parsed
+    let trace = read(&format!("{}{}", header, rows), &parsers).unwrap();
+    assert_eq!((trace.width(), trace.length()), (2, 8));
+    assert_eq!(trace.get(0, 3), BaseElement::from(19u64));
+    assert_eq!(trace.get(1, 3), BaseElement::from(3u64));
+
+    let replace_row = |step: usize, line: &str| {
+        let mut lines = rows.lines().map(String::from).collect::<Vec<_>>();
+        lines[step] = String::from(line);
+        format!("{}{}\n", header, lines.join("\n"))
+    };
+    assert_eq!(row(read(&replace_row(2, "2,0x12"), &parsers)), Some(2));
+    assert_eq!(row(read(&replace_row(5, "-5,0x15,x"), &parsers)), Some(5));
+    assert_eq!(row(read(&replace_row(6, "6,0xzz,x"), &parsers)), Some(6));
+    // commas in quoted fields would be misread
+    assert_eq!(
+        row(read(&replace_row(1, "1,\"0x11\",x"), &parsers)),
+        Some(1)
+    );
+    assert_eq!(
+        row(read(&format!("\"a\",b,unused\n{}", rows), &parsers)),
+        None
+    );
+
+    // errors of the whole file
+    assert_eq!(row(read("", &parsers)), None);
+    assert_eq!(row(read(&format!("a,c\n{}", rows), &parsers)), None);
+    assert_eq!(
+        row(read(&format!("{}{}", header, rows), &parsers[..1])),
+        None
+    );
+    let odd_rows = rows.lines().take(7).collect::<Vec<_>>().join("\n");
+    assert_eq!(
+        row(read(&format!("{}{}", header, odd_rows), &parsers)),
+        None
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// PARQUET TRACE TESTS
+// ===========================================================================
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_traces_map_columns_by_name() {
+    use std::sync::Arc;
+
+    use parquet::{
+        data_type::{BoolType, ByteArray, ByteArrayType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    use winterfell::{Trace, TraceTable};
+
+    use crate::trace::{ColumnMapping, TraceTableExt};
+
+    let dir = std::env::temp_dir().join(format!("winter-circom-parquet-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("trace.parquet");
+    let write = |a: &[i64], b: &[&str], c: &[bool]| {
+        let schema = parse_message_type(
+            "message trace { REQUIRED INT64 a; REQUIRED BYTE_ARRAY b (UTF8); REQUIRED BOOLEAN c; }",
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema), properties).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(a, None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        let b = b.iter().map(|&s| ByteArray::from(s)).collect::<Vec<_>>();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&b, None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<BoolType>()
+            .write_batch(c, None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        row_group.close().unwrap();
+        writer.close().unwrap();
+    };
+    let row = |result: Result<TraceTable<BaseElement>, WinterCircomError>| match result {
+        Err(WinterCircomError::InvalidTrace { row, .. }) => row,
+        other => panic!("unexpected result: {:?}", other.map(|trace| trace.length())),
+    };
+
+    let a = (0..8).collect::<Vec<i64>>();
+    let b = ["16", "17", "18", "19", "20", "21", "22", "23"];
+    let c = (0..8).map(|i| i % 2 == 1).collect::<Vec<_>>();
+    let mapping = ColumnMapping::new(vec!["b", "c", "a"]);
+
+    // integer, string and boolean columns are all read as field elements
+    write(&a, &b, &c);
+    let trace = TraceTable::<BaseElement>::from_parquet(&path, &mapping).unwrap();
+    assert_eq!((trace.width(), trace.length()), (3, 8));
+    assert_eq!(trace.get(0, 3), BaseElement::from(19u64));
+    assert_eq!(trace.get(1, 3), BaseElement::ONE);
+    assert_eq!(trace.get(2, 3), BaseElement::from(3u64));
+
+    // unmapped columns are not parsed
+    let partial = ColumnMapping::new(vec!["a"]);
+    let mut negative = a.clone();
+    negative[5] = -5;
+    write(&negative, &b, &c);
+    assert_eq!(
+        row(TraceTable::<BaseElement>::from_parquet(&path, &partial)),
+        Some(5)
+    );
+    let mut not_decimal = b;
+    not_decimal[6] = "0x16";
+    write(&a, &not_decimal, &c);
+    assert!(TraceTable::<BaseElement>::from_parquet(&path, &partial).is_ok());
+    assert_eq!(
+        row(TraceTable::<BaseElement>::from_parquet(&path, &mapping)),
+        Some(6)
+    );
+
+    // errors of the whole file
+    let missing = ColumnMapping::new(vec!["a", "d"]);
+    write(&a, &b, &c);
+    assert_eq!(
+        row(TraceTable::<BaseElement>::from_parquet(&path, &missing)),
+        None
+    );
+    write(&a[..7], &b[..7], &c[..7]);
+    assert_eq!(
+        row(TraceTable::<BaseElement>::from_parquet(&path, &mapping)),
+        None
+    );
+    std::fs::write(&path, "a,b,c\n").unwrap();
+    assert_eq!(
+        row(TraceTable::<BaseElement>::from_parquet(&path, &mapping)),
+        None
+    );
+    assert!(matches!(
+        TraceTable::<BaseElement>::from_parquet(dir.join("missing.parquet"), &mapping),
+        Err(WinterCircomError::IoError { .. })
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// TRACE LAYOUT TESTS
+// ===========================================================================
+
+#[test]
+fn row_major_traces_are_transposed() {
+    use winterfell::{Trace, TraceTable};
+
+    use crate::trace::TraceTableExt;
+
+    // more than a block of steps, so that the last block is partial
+    let length = 2048;
+    let data = (0..3 * length as u64)
+        .map(BaseElement::from)
+        .collect::<Vec<_>>();
+
+    let trace = TraceTable::<BaseElement>::from_row_major(&data, 3).unwrap();
+    assert_eq!((trace.width(), trace.length()), (3, length));
+    assert_eq!(trace.get(1, 0), BaseElement::from(1u64));
+    assert_eq!(trace.get(2, 1500), BaseElement::from(4502u64));
+    assert_eq!(trace.to_row_major(), data);
+
+    let streamed = TraceTable::<BaseElement>::from_rows(data.chunks(3), 3).unwrap();
+    assert_eq!(streamed.to_row_major(), data);
+
+    // rows of the wrong width are reported
+    let mut rows = data.chunks(3).map(<[_]>::to_vec).collect::<Vec<_>>();
+    rows[5].pop();
+    assert!(matches!(
+        TraceTable::<BaseElement>::from_rows(rows, 3),
+        Err(WinterCircomError::InvalidTrace { row: Some(5), .. })
+    ));
+    assert!(matches!(
+        TraceTable::<BaseElement>::from_row_major(&data[1..], 3),
+        Err(WinterCircomError::InvalidTrace { row: None, .. })
+    ));
+}
+
+// SPARSE TRACE TESTS
+// ===========================================================================
+
+#[test]
+fn sparse_traces_expand_with_their_padding() {
+    use winterfell::Trace;
+
+    use crate::trace::{PaddingStrategy, SparseTrace};
+
+    let values = (0..5u64).map(BaseElement::from).collect::<Vec<_>>();
+    let sparse = |padding| {
+        SparseTrace::new(5, padding)
+            .with_dense_column(values.clone())
+            .with_constant_column(BaseElement::ONE)
+            .with_sparse_column(BaseElement::ZERO, [(2, BaseElement::from(7u64))])
+    };
+    assert_eq!(sparse(PaddingStrategy::RepeatLast).stored_elements(), 8);
+
+    let trace = sparse(PaddingStrategy::RepeatLast).into_trace().unwrap();
+    assert_eq!(trace.length(), 8);
+    assert_eq!(trace.get(0, 4), BaseElement::from(4u64));
+    assert_eq!(trace.get(0, 7), BaseElement::from(4u64));
+    assert_eq!(trace.get(1, 7), BaseElement::ONE);
+    assert_eq!(trace.get(2, 2), BaseElement::from(7u64));
+    assert_eq!(trace.get(2, 3), BaseElement::ZERO);
+
+    let trace = sparse(PaddingStrategy::ColumnDefault).into_trace().unwrap();
+    assert_eq!(trace.get(0, 7), BaseElement::ZERO);
+    assert_eq!(trace.get(1, 7), BaseElement::ONE);
+
+    // the padding strategy is recorded by name
+    assert_eq!(
+        serde_json::to_value(PaddingStrategy::ColumnDefault).unwrap(),
+        json!("column_default")
+    );
+
+    // unpadded traces must already have a valid length
+    assert!(matches!(
+        sparse(PaddingStrategy::None).into_trace(),
+        Err(WinterCircomError::InvalidTrace { row: None, .. })
+    ));
+    assert!(matches!(
+        SparseTrace::new(8, PaddingStrategy::None)
+            .with_sparse_column(BaseElement::ZERO, [(8, BaseElement::ONE)])
+            .into_trace(),
+        Err(WinterCircomError::InvalidTrace { row: Some(8), .. })
+    ));
+}
+
+// ELEMENT ENCODING TESTS
+// ===========================================================================
+
+#[test]
+fn element_encodings_round_trip() {
+    use crate::{ByteOrder, ElementEncoding, Representation};
+
+    let elements = [
+        BaseElement::ZERO,
+        BaseElement::ONE,
+        BaseElement::from(0x0102_0304_0506_0708u64),
+        BaseElement::ZERO - BaseElement::ONE,
+    ];
+    for byte_order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+        for representation in [Representation::Canonical, Representation::Montgomery] {
+            let encoding = ElementEncoding::new(byte_order, representation);
+            for element in elements {
+                assert_eq!(encoding.decode(&encoding.encode(element)).unwrap(), element);
+                assert_eq!(
+                    encoding.decode_hex(&encoding.encode_hex(element)).unwrap(),
+                    element
+                );
+            }
+        }
+    }
+
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    assert_eq!(ElementEncoding::default().encode(BaseElement::ONE), one);
+    one.reverse();
+    let little_endian = ElementEncoding::new(ByteOrder::LittleEndian, Representation::Canonical);
+    assert_eq!(little_endian.encode(BaseElement::ONE), one);
+
+    // the Montgomery form of one is 2^256 mod p
+    let montgomery = ElementEncoding::new(ByteOrder::BigEndian, Representation::Montgomery);
+    assert_eq!(
+        montgomery.encode_hex(BaseElement::ONE),
+        "0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb"
+    );
+
+    // the modulus itself is not an element
+    let modulus = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+    assert_eq!(
+        ElementEncoding::default()
+            .encode_hex(BaseElement::ZERO - BaseElement::ONE)
+            .replace("f0000000", "f0000001"),
+        modulus
+    );
+    for invalid in [modulus, "0x01", "zz"] {
+        assert!(matches!(
+            ElementEncoding::default().decode_hex(invalid),
+            Err(WinterCircomError::InvalidFieldElement { .. })
+        ));
+    }
+}
+
+#[test]
+fn input_signals_are_reencoded() {
+    use crate::{ByteOrder, ElementEncoding, Representation};
+
+    let encoding = ElementEncoding::new(ByteOrder::LittleEndian, Representation::Montgomery);
+    let input = json!({ "ood_trace_frame": [["1", "2"], ["3", "4"]], "pow_nonce": 5 });
+    let encoded = encoding.encode_json(&input).unwrap();
+    assert_eq!(
+        encoded["ood_trace_frame"][0][0],
+        json!(encoding.encode_hex(BaseElement::ONE))
+    );
+    assert_eq!(
+        encoding.decode_json(&encoded).unwrap(),
+        json!({ "ood_trace_frame": [["1", "2"], ["3", "4"]], "pow_nonce": "5" })
+    );
+
+    assert!(encoding.encode_json(&json!(["-1"])).is_err());
+    assert!(encoding.decode_json(&json!([true])).is_err());
+}
+
+// SCHEMA TESTS
+// ===========================================================================
+
+#[cfg(feature = "schema")]
+#[test]
+fn artifact_schemas_validate_artifacts() {
+    use crate::schema::{validate_against_schema, Artifact};
+
+    let input = Artifact::Input.schema();
+    assert_eq!(
+        input["required"].as_array().unwrap().len(),
+        Signal::ALL.len()
+    );
+
+    let manifest = CircuitManifest {
+        fri_tree_depths: vec![7, 4],
+        num_composition_columns: Some(2),
+        data_commitment_max_bytes: Some(64),
+        source_fingerprint: Some(String::from("00")),
+        inner_proof_verified: Some(true),
+        target_security_bits: Some(128),
+        public_inputs_exposed: Some(false),
+        public_signals_committed: Some(false),
+        ..sample_manifest("test")
+    };
+    let value = serde_json::to_value(&manifest).unwrap();
+    assert!(validate_against_schema(Artifact::Manifest, &value).is_ok());
+
+    let result = validate_against_schema(Artifact::Manifest, &json!({ "trace_length": "128" }));
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::SchemaValidation { .. })
+    ));
+}
+
+// PROOF BUNDLE TESTS
+// ===========================================================================
+
+#[cfg(feature = "ipfs")]
+#[test]
+fn proof_bundles_are_canonically_encoded() {
+    use crate::ipfs::{cid, decode_cbor, encode_cbor, ProofBundle};
+
+    // keys are sorted by length first, integers use their shortest encoding
+    let value = json!({ "bb": [true, null, -2, "x"], "a": 1 });
+    let encoded = encode_cbor(&value);
+    assert_eq!(
+        encoded,
+        [0xa2, 0x61, 0x61, 0x01, 0x62, 0x62, 0x62, 0x84, 0xf5, 0xf6, 0x21, 0x61, 0x78]
+    );
+    assert_eq!(decode_cbor(&encoded).unwrap(), value);
+    assert_eq!(encode_cbor(&json!(1000)), [0x19, 0x03, 0xe8]);
+    // 1 encoded on two bytes is not canonical
+    assert!(matches!(
+        decode_cbor(&[0x18, 0x01]),
+        Err(WinterCircomError::InvalidBundle { .. })
+    ));
+
+    let bundle = ProofBundle {
+        circuit_name: String::from("sum"),
+        proof: json!({ "pi_a": ["1", "2", "1"], "protocol": "groth16" }),
+        public_signals: json!(["3", "4"]),
+        verification_key: json!({ "nPublic": 2, "curve": "bn128" }),
+    };
+    assert_eq!(ProofBundle::from_cbor(&bundle.to_cbor()).unwrap(), bundle);
+
+    // CIDv1, dag-cbor codec and BLAKE3 multihash, in base32
+    let bundle_cid = bundle.cid();
+    assert_eq!(bundle_cid, cid(&bundle.to_cbor()));
+    assert!(bundle_cid.starts_with("bafyr4i"));
+    assert_eq!(bundle_cid.len(), 59);
+}
+
+// PROOF STORAGE TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn saved_proofs_coexist() {
+    let circuit_name = format!("proofs-test-{}", std::process::id());
+    let circuit_dir = std::path::Path::new("target/circom").join(&circuit_name);
+    std::fs::create_dir_all(&circuit_dir).unwrap();
+
+    for proof_id in ["b", "a"] {
+        std::fs::write(circuit_dir.join("proof.json"), proof_id).unwrap();
+        std::fs::write(circuit_dir.join("public.json"), "[]").unwrap();
+        assert_eq!(
+            crate::save_proof(&circuit_name, Some(proof_id)).unwrap(),
+            proof_id
+        );
+        assert!(!circuit_dir.join("proof.json").exists());
+    }
+    assert_eq!(crate::list_proofs(&circuit_name), vec!["a", "b"]);
+    assert_eq!(
+        std::fs::read_to_string(
+            crate::proof_dir(&circuit_name, "b")
+                .unwrap()
+                .join("proof.json")
+        )
+        .unwrap(),
+        "b"
+    );
+
+    // proofs are never overwritten
+    std::fs::write(circuit_dir.join("proof.json"), "c").unwrap();
+    std::fs::write(circuit_dir.join("public.json"), "[]").unwrap();
+    assert!(crate::save_proof(&circuit_name, Some("a")).is_err());
+
+    // proof IDs cannot escape the directory of the proofs
+    for proof_id in ["", "..", "../a", "a b"] {
+        assert!(matches!(
+            crate::save_proof(&circuit_name, Some(proof_id)),
+            Err(WinterCircomError::InvalidProofId { .. })
+        ));
+        assert!(crate::proof_dir(&circuit_name, proof_id).is_err());
+        assert!(matches!(
+            crate::circom_verify_proof(&circuit_name, proof_id, crate::utils::LoggingLevel::Quiet),
+            Err(WinterCircomError::InvalidProofId { .. })
+        ));
+    }
+    assert!(circuit_dir.join("proof.json").exists());
+
+    std::fs::remove_dir_all(circuit_dir).unwrap();
+}
+
+// CIRCUIT PARAMETERS TESTS
+// ===========================================================================
+
+#[test]
+fn circuit_parameters_match_proof_options() {
+    let options = WinterCircomProofOptions::new(128, 2, 3, [1, 1], 32, 8, 0, 8, 128);
+    let parameters = options.circuit_parameters();
+    assert_eq!(parameters.num_transition_constraints, 2);
+    assert_eq!(parameters.proof_options(), options.get_proof_options());
+
+    let serialized = serde_json::to_string(&parameters).unwrap();
+    assert_eq!(
+        serde_json::from_str::<CircuitParameters>(&serialized).unwrap(),
+        parameters
+    );
+
+    assert!(parameters.check(&parameters).is_ok());
+    let proved = CircuitParameters {
+        num_queries: 16,
+        ..parameters
+    };
+    match parameters.check(&proved) {
+        Err(WinterCircomError::ManifestMismatch {
+            field,
+            compiled,
+            proved,
+        }) => {
+            assert_eq!(field, "num_queries");
+            assert_eq!((compiled.as_str(), proved.as_str()), ("32", "16"));
+        }
+        other => panic!("unexpected result: {:?}", other.err()),
+    }
+}
+
+// MAIN ARGUMENT TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn main_arguments_are_documented_next_to_the_main_file() {
+    use crate::{
+        circom_create_in_memory,
+        codegen::generate_circom_main,
+        gadgets::merkle::{self, MerkleAir, MerkleProver},
+        with_workspace, CircomWorkspace, MainArgument,
+    };
+
+    let options = merkle::proof_options(3);
+    let circuit = circom_create_in_memory::<MerkleProver, 3>(options, "merkle", "..").unwrap();
+    let names = circuit
+        .arguments
+        .iter()
+        .map(|argument| argument.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names.len(), 24);
+    assert_eq!(names[0], "addicity");
+    assert_eq!(names[23], "tree_depth");
+    // every argument is written with its documented value
+    for argument in circuit.arguments.iter() {
+        let written = [
+            format!("{}, // {}", argument.value, argument.name),
+            format!("{} // {}", argument.value, argument.name),
+        ];
+        assert!(circuit
+            .source
+            .lines()
+            .any(|line| written.contains(&String::from(line.trim()))));
+    }
+
+    let root = std::env::temp_dir().join(format!("winter-circom-arguments-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("merkle");
+    std::fs::create_dir_all(&dir).unwrap();
+    with_workspace(workspace, || {
+        generate_circom_main::<BaseElement, MerkleAir, 3>(options, "merkle").unwrap();
+    });
+
+    let json = std::fs::read_to_string(dir.join("parameters.json")).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+        serde_json::to_value(&circuit.arguments).unwrap()
+    );
+    let markdown = std::fs::read_to_string(dir.join("parameters.md")).unwrap();
+    assert!(markdown.starts_with("# Parameters of `merkle`"));
+    let MainArgument { name, value, .. } = &circuit.arguments[11];
+    assert_eq!(*name, "num_composition_columns");
+    assert!(markdown.contains(&format!("| `{}` | `{}` |", name, value)));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// PUBLIC COMMITMENT TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn committed_public_signals_are_a_single_public_signal() {
+    use crate::{
+        circom_create_in_memory,
+        gadgets::merkle::{self, TRACE_WIDTH},
+    };
+
+    let argument = |options| {
+        let circuit =
+            circom_create_in_memory::<merkle::MerkleProver, 3>(options, "merkle", "..").unwrap();
+        assert_eq!(
+            circuit
+                .source
+                .contains("component main {public [public_commitment]}"),
+            circuit.manifest.public_signals_committed == Some(true)
+        );
+        circuit
+            .arguments
+            .iter()
+            .find(|argument| argument.name == "num_committed_signals")
+            .map(|argument| argument.value.clone())
+            .unwrap()
+    };
+
+    let options = merkle::proof_options(3);
+    assert_eq!(argument(options), "0");
+    // the OOD data, then the public inputs if they are exposed
+    let committed = options.commit_public_signals();
+    assert_eq!(argument(committed), (3 + 2 * TRACE_WIDTH).to_string());
+    let exposed = committed.expose_public_inputs();
+    assert_eq!(argument(exposed), (3 + 2 * TRACE_WIDTH + 2).to_string());
+}
+
+#[cfg(feature = "verify")]
+#[test]
+fn public_commitments_are_checked_against_the_ood_data() {
+    use winterfell::math::StarkField;
+
+    use crate::{
+        check_public_commitment, circom_poseidon::hash_elements, with_workspace, CircomWorkspace,
+        OodData,
+    };
+
+    let e = |x: u64| BaseElement::from(x);
+    let input = json!({
+        "ood_frame_constraint_evaluation": ["1"],
+        "ood_trace_frame": [["2", "3"], ["4", "5"]],
+    });
+    let ood_data = OodData::from_input(&input).unwrap();
+    assert_eq!(ood_data.current, [e(2), e(3)]);
+    let commitment = ood_data.public_commitment(&[e(6)]);
+    assert_eq!(
+        commitment,
+        hash_elements(&[e(1), e(2), e(3), e(4), e(5), e(6)])
+    );
+    assert!(OodData::from_input(&json!({ "ood_frame_constraint_evaluation": [] })).is_err());
+
+    let root =
+        std::env::temp_dir().join(format!("winter-circom-commitment-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    std::fs::create_dir_all(&dir).unwrap();
+    let public_signals = json!([commitment.as_int().to_string()]);
+    std::fs::write(dir.join("public.json"), public_signals.to_string()).unwrap();
+    with_workspace(workspace, || {
+        assert!(check_public_commitment("sum", &ood_data, &[e(6)]).is_ok());
+        assert!(matches!(
+            check_public_commitment("sum", &ood_data, &[e(7)]),
+            Err(WinterCircomError::InvalidPublicSignals { .. })
+        ));
+    });
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// PROOF STATISTICS TESTS
+// ===========================================================================
+
+#[test]
+fn proof_stats_count_the_input_signals() {
+    use crate::ProofStats;
+
+    // two queries, a trace of width 2 and two FRI layers, the second one padded
+    let input = json!({
+        "addicity_root": "5",
+        "constraint_query_proofs": [["1", "2", "3"], ["4", "5", "6"]],
+        "fri_commitments": ["1", "2", "3"],
+        "fri_layer_proofs": [[["1", "2"], ["3", "4"]], [["5", "0"], ["0", "0"]]],
+        "ood_trace_frame": [["1", "2"], ["3", "4"]],
+        "trace_query_proofs": [["1", "2", "3"], ["4", "5", "6"]],
+    });
+    let stats = ProofStats::from_input(&input).unwrap();
+    assert_eq!(stats.num_queries, 2);
+    assert_eq!(
+        (stats.trace_path_length, stats.constraint_path_length),
+        (3, 3)
+    );
+    assert_eq!(stats.fri_path_lengths, vec![2, 1]);
+    assert_eq!(stats.ood_frame_size, 4);
+    assert_eq!(stats.total_elements, 28);
+
+    let root = std::env::temp_dir().join(format!("winter-circom-stats-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let path = root.join("input.json");
+    std::fs::write(&path, input.to_string()).unwrap();
+    assert_eq!(ProofStats::from_input_json(&path).unwrap(), stats);
+    assert!(matches!(
+        ProofStats::from_input(&json!({})),
+        Err(WinterCircomError::SchemaValidation { .. })
+    ));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// CANCELLATION TESTS
+// ===========================================================================
+
+#[test]
+fn cancellation_tokens_are_scoped_to_the_thread() {
+    use crate::{cancellation::checkpoint, with_cancellation, CancellationToken};
+
+    let token = CancellationToken::new();
+    assert!(with_cancellation(&token, || checkpoint("testing")).is_ok());
+
+    // clones are cancelled together
+    token.clone().cancel();
+    assert!(token.is_cancelled());
+    match with_cancellation(&token, || checkpoint("testing")) {
+        Err(WinterCircomError::Cancelled { step }) => assert_eq!(step, "testing"),
+        other => panic!("unexpected result: {:?}", other.err()),
+    }
+    assert_eq!(
+        WinterCircomError::Cancelled {
+            step: String::from("testing")
+        }
+        .class(),
+        ErrorClass::Interrupted
+    );
+
+    // the previous token is restored
+    assert!(checkpoint("testing").is_ok());
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn cancelled_jobs_stop_converting_the_proof() {
+    use crate::{
+        circom_create_in_memory, circom_prove_in_memory,
+        gadgets::merkle::{self, MerklePath, MerkleProver},
+        utils::LoggingLevel,
+        with_cancellation, CancellationToken,
+    };
+
+    let options = merkle::proof_options(7);
+    let circuit = circom_create_in_memory::<MerkleProver, 3>(options, "merkle", "..").unwrap();
+    let path = MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 5,
+        siblings: (1000..1007u64).map(BaseElement::from).collect(),
+    };
+    let prove = || {
+        let prover = MerkleProver::new(options.get_proof_options());
+        let trace = prover.build_trace(&path);
+        circom_prove_in_memory(prover, trace, None, &circuit, LoggingLevel::Quiet)
+    };
+
+    let token = CancellationToken::new();
+    token.cancel();
+    assert!(matches!(
+        with_cancellation(&token, prove),
+        Err(WinterCircomError::Cancelled { .. })
+    ));
+    assert!(prove().is_ok());
+}
+
+// OOD CHECK TESTS
+// ===========================================================================
+
+#[cfg(all(feature = "prove", feature = "verify"))]
+#[test]
+fn ood_check_agrees_with_the_rust_check() {
+    use std::rc::Rc;
+
+    use winterfell::Prover;
+
+    use crate::{
+        circom_check_ood, circom_create_ood_check,
+        gadgets::merkle::{self, MerkleAir, MerklePath, MerkleProver},
+        utils::LoggingLevel,
+        with_executor, with_workspace, CircomWorkspace, MockExecutor, OodCheckInputs, OodData,
+    };
+
+    let options = merkle::proof_options(7);
+    let path = MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 5,
+        siblings: (1000..1007u64).map(BaseElement::from).collect(),
+    };
+    let prover = MerkleProver::new(options.get_proof_options());
+    let trace = prover.build_trace(&path);
+    let pub_inputs = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).unwrap();
+    let inputs = OodCheckInputs::from_proof::<MerkleAir>(&proof, pub_inputs).unwrap();
+    let ood_data = |inputs: &OodCheckInputs| OodData {
+        constraint_evaluations: inputs.constraint_evaluations.clone(),
+        current: inputs.current.clone(),
+        next: inputs.next.clone(),
+    };
+    let mut mutated = inputs.clone();
+    mutated.constraint_evaluations[0] += BaseElement::ONE;
+
+    // the Rust check accepts the proof only
+    assert!(ood_data(&inputs).is_consistent_with::<MerkleAir>());
+    assert!(!ood_data(&mutated).is_consistent_with::<MerkleAir>());
+    let input = inputs.to_json();
+    assert_eq!(
+        input["ood_trace_frame"][0].as_array().unwrap().len(),
+        inputs.current.len()
+    );
+    assert_eq!(
+        input["transition_coeffs"][0],
+        json!([inputs.transition_coeffs[0].0, inputs.transition_coeffs[0].1])
+    );
+
+    // the Circom check is compiled next to the circuit, and rejects the OOD
+    // data if its witness cannot be computed
+    let root = std::env::temp_dir().join(format!("winter-circom-ood-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("merkle");
+    let executor = Rc::new(MockExecutor::new());
+    let accepted = with_workspace(workspace.clone(), || {
+        with_executor(executor.clone(), || {
+            circom_create_ood_check::<MerkleAir, 3>(options, "merkle", LoggingLevel::Quiet)?;
+            circom_check_ood("merkle", &inputs, LoggingLevel::Quiet)
+        })
+    });
+    assert!(accepted.unwrap());
+    let invocations = executor.invocations();
+    assert_eq!(invocations[0].args, ["ood_check.circom", "--wasm"]);
+    assert_eq!(
+        invocations[1].args[..2],
+        ["wc", "ood_check_js/ood_check.wasm"]
+    );
+    let source = std::fs::read_to_string(dir.join("ood_check.circom")).unwrap();
+    assert!(source.contains("component main = OodCheck("));
+    assert!(source.contains("/air/merkle.circom"));
+
+    let executor = Rc::new(MockExecutor::new().with_failure("snarkjs", &["wc"], 1));
+    let accepted = with_workspace(workspace, || {
+        with_executor(executor, || {
+            circom_check_ood("merkle", &mutated, LoggingLevel::Quiet)
+        })
+    });
+    assert!(!accepted.unwrap());
+    let input: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dir.join("ood_check_input.json")).unwrap()).unwrap();
+    assert_eq!(input, mutated.to_json());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// SMOKE TEST TESTS
+// ===========================================================================
+
+/// [MockExecutor](crate::MockExecutor) writing the files output by the
+/// snarkjs commands it records, so that the stages reading them can run.
+#[cfg(feature = "prove")]
+struct ArtifactWritingExecutor(crate::MockExecutor);
+
+#[cfg(feature = "prove")]
+impl crate::Executor for ArtifactWritingExecutor {
+    fn execute(&self, invocation: &crate::Invocation) -> Result<String, WinterCircomError> {
+        let outputs: &[usize] = match invocation.args[0].as_str() {
+            "zkc" | "zkev" => &[2],
+            "g16s" | "wc" => &[3],
+            "g16p" => &[3, 4],
+            _ => &[],
+        };
+        for output in outputs {
+            let dir = invocation.current_dir.clone().unwrap_or_default();
+            std::fs::write(dir.join(&invocation.args[*output]), "{}").unwrap();
+        }
+        self.0.execute(invocation)
+    }
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn smoke_tests_prove_and_verify_a_known_good_trace() {
+    use std::rc::Rc;
+
+    use crate::{
+        gadgets::merkle::{self, MerklePath, MerkleProver},
+        smoke_test,
+        utils::LoggingLevel,
+        with_executor, with_workspace, CircomWorkspace, MockExecutor,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-smoke-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("merkle");
+    std::fs::create_dir_all(&dir).unwrap();
+    // the keys of the circuit
+    for file in ["verifier.zkey", "verification_key.json"] {
+        std::fs::write(dir.join(file), "{}").unwrap();
+    }
+    let options = merkle::proof_options(3);
+    let path = MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 5,
+        siblings: (1000..1003u64).map(BaseElement::from).collect(),
+    };
+    let run = |executor: MockExecutor| {
+        let executor = Rc::new(ArtifactWritingExecutor(executor));
+        let result = with_workspace(workspace.clone(), || {
+            let prover = MerkleProver::new(options.get_proof_options());
+            let trace = prover.build_trace(&path);
+            with_executor(executor.clone(), || {
+                smoke_test(prover, trace, "merkle", LoggingLevel::Quiet)
+            })
+        });
+        (result, executor.0.invocations())
+    };
+
+    let (result, invocations) = run(MockExecutor::new());
+    result.unwrap();
+    let commands = invocations
+        .iter()
+        .map(|invocation| invocation.args[0].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(commands, ["wc", "g16p", "g16v"]);
+    // the smoke proof is removed once verified
+    assert!(!dir.join("proof.json").exists());
+    assert!(!dir.join("public.json").exists());
+
+    // failures are reported, and the artifacts kept
+    let (result, _) = run(MockExecutor::new().with_failure("snarkjs", &["g16v"], 1));
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::ExitCodeError { code: 1, .. })
+    ));
+    assert!(dir.join("proof.json").exists());
+
+    // a real proof is restored, whether the smoke test passes or not
+    for executor in [
+        MockExecutor::new(),
+        MockExecutor::new().with_failure("snarkjs", &["g16v"], 1),
+    ] {
+        std::fs::write(dir.join("proof.json"), "real").unwrap();
+        std::fs::write(dir.join("public.json"), "real").unwrap();
+        let _ = run(executor);
+        assert_eq!(std::fs::read(dir.join("proof.json")).unwrap(), b"real");
+        assert_eq!(std::fs::read(dir.join("public.json")).unwrap(), b"real");
+    }
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn created_circuits_are_compiled_and_set_up_before_the_smoke_test() {
+    use std::rc::Rc;
+
+    use crate::{
+        circom_create_smoke_tested,
+        gadgets::merkle::{self, MerklePath, MerkleProver},
+        utils::LoggingLevel,
+        with_executor, with_workspace, CircomWorkspace, MockExecutor,
+    };
+
+    let root =
+        std::env::temp_dir().join(format!("winter-circom-smoke-create-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("final.ptau"), "").unwrap();
+    let workspace = CircomWorkspace::new(&root)
+        .with_circuits_dir("../circuits")
+        .with_ptau(root.join("final.ptau"));
+    let options = merkle::proof_options(3);
+    let path = MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 5,
+        siblings: (1000..1003u64).map(BaseElement::from).collect(),
+    };
+
+    let executor = Rc::new(ArtifactWritingExecutor(MockExecutor::new()));
+    with_workspace(workspace.clone(), || {
+        let prover = MerkleProver::new(options.get_proof_options());
+        let trace = prover.build_trace(&path);
+        with_executor(executor.clone(), || {
+            circom_create_smoke_tested(options, "merkle", prover, trace, LoggingLevel::Quiet)
+        })
+    })
+    .unwrap();
+    let commands = executor
+        .0
+        .invocations()
+        .iter()
+        .map(|invocation| format!("{} {}", invocation.program, invocation.args[0]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        commands,
+        [
+            "circom verifier.circom",
+            "snarkjs g16s",
+            "snarkjs zkc",
+            "snarkjs zkev",
+            "snarkjs wc",
+            "snarkjs g16p",
+            "snarkjs g16v",
+        ]
+    );
+    assert!(workspace
+        .circuit_dir("merkle")
+        .join("verifier.zkey")
+        .exists());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// SECURITY SELF-TEST TESTS
+// ===========================================================================
+
+#[cfg(feature = "testkit")]
+#[test]
+fn mutations_that_do_not_apply_leave_the_input_unchanged() {
+    use crate::Mutation;
+
+    let mut input = json!({
+        "trace_query_proofs": [["4"]],
+        "ood_trace_frame": [["7"], ["9"]],
+        "public_inputs": [],
+        "pow_nonce": 3,
+    });
+    assert!(Mutation::FlipMerkleNode.apply(&mut input));
+    assert_eq!(input["trace_query_proofs"][0][0], "5");
+    assert!(Mutation::ReuseNonce.apply(&mut input));
+    assert_eq!(input["pow_nonce"], 2);
+
+    // AIRs without public inputs have no public input to change
+    let unchanged = input.clone();
+    assert!(!Mutation::ChangePublicInput.apply(&mut input));
+    assert_eq!(input, unchanged);
+}
+
+#[cfg(feature = "testkit")]
+#[test]
+fn security_selftests_run_the_whole_verification_path() {
+    use std::rc::Rc;
+
+    use crate::{
+        gadgets::merkle::{self, MerklePath, MerkleProver},
+        security_selftest,
+        utils::LoggingLevel,
+        with_executor, with_workspace, CircomWorkspace, MockExecutor, Mutation, ScratchSpace,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-selftest-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("merkle");
+    std::fs::create_dir_all(dir.join("verifier_js")).unwrap();
+    for file in [
+        "verifier_js/verifier.wasm",
+        "verifier.zkey",
+        "verification_key.json",
+    ] {
+        std::fs::write(dir.join(file), "").unwrap();
+    }
+    let selftest_dir = with_workspace(workspace.clone(), || {
+        ScratchSpace::from_env()
+            .unwrap()
+            .circuit_dir("merkle")
+            .join("selftest")
+    });
+    std::fs::create_dir_all(&selftest_dir).unwrap();
+    let selftest_dir = std::fs::canonicalize(selftest_dir).unwrap();
+    // the public signals of the mock proofs, without exposed public inputs
+    let names = Mutation::ALL.iter().map(|mutation| mutation.name());
+    for name in names.chain(["valid"]) {
+        std::fs::write(selftest_dir.join(format!("{}.public.json", name)), "[]").unwrap();
+    }
+
+    let options = merkle::proof_options(3);
+    let path = MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 5,
+        siblings: (1000..1003u64).map(BaseElement::from).collect(),
+    };
+    let run = |executor: Rc<MockExecutor>| {
+        with_workspace(workspace.clone(), || {
+            let prover = MerkleProver::new(options.get_proof_options());
+            let trace = prover.build_trace(&path);
+            with_executor(executor, || {
+                security_selftest(prover, trace, "merkle", LoggingLevel::Quiet)
+            })
+        })
+    };
+
+    // a mutated input whose Groth16 proof verifies is reported
+    let executor = Rc::new(MockExecutor::new());
+    assert!(matches!(
+        run(executor.clone()),
+        Err(WinterCircomError::SecurityTestFailed { mutation }) if mutation == "flip_merkle_node"
+    ));
+    let steps = executor
+        .invocations()
+        .iter()
+        .map(|invocation| invocation.args[0].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        ["verifier_js/generate_witness.js", "g16p", "g16v"].repeat(2)
+    );
+
+    // the altered OOD value is rejected by the OOD check, the other mutations by
+    // the Groth16 verifier
+    let mut executor = MockExecutor::new();
+    for mutation in ["flip_merkle_node", "change_public_input", "reuse_nonce"] {
+        let public = selftest_dir.join(format!("{}.public.json", mutation));
+        executor = executor.with_failure(
+            "snarkjs",
+            &["g16v", "verification_key.json", public.to_str().unwrap()],
+            1,
+        );
+    }
+    let executor = Rc::new(executor);
+    run(executor.clone()).unwrap();
+    assert_eq!(executor.invocations().len(), 3 * (1 + Mutation::ALL.len()));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// PROOF OPTIONS OPTIMIZER TESTS
+// ===========================================================================
+
+#[test]
+fn optimizer_trades_queries_for_grinding_and_blowup() {
+    use crate::{OptimizationGoal, ProofOptionsOptimizer};
+
+    let options = || WinterCircomProofOptions::new(128, 2, 3, [1, 1], 42, 8, 0, 8, 256);
+
+    // fewer queries make a smaller circuit
+    let optimized = ProofOptionsOptimizer::new(OptimizationGoal::CircuitSize)
+        .optimize(options())
+        .unwrap();
+    let chosen = optimized.report.chosen;
+    assert_eq!(
+        (
+            chosen.lde_blowup_factor,
+            chosen.grinding_factor,
+            chosen.num_queries
+        ),
+        (64, 20, 18)
+    );
+    assert_eq!(optimized.options.conjectured_security_bits(), 128);
+    assert!(optimized.options.check_lde_blowup_factor().is_ok());
+    assert!(optimized
+        .report
+        .alternatives
+        .iter()
+        .all(|alternative| alternative.circuit_cost >= chosen.circuit_cost));
+    assert!(optimized
+        .report
+        .to_string()
+        .contains("minimizing the circuit size"));
+
+    // a small LDE domain makes a faster prover, the last FRI layer must be
+    // smaller than the LDE domain
+    let optimized = ProofOptionsOptimizer::new(OptimizationGoal::ProverTime)
+        .optimize(options())
+        .unwrap();
+    let chosen = optimized.report.chosen;
+    assert_eq!(
+        (
+            chosen.lde_blowup_factor,
+            chosen.grinding_factor,
+            chosen.num_queries
+        ),
+        (4, 0, 64)
+    );
+
+    let error = ProofOptionsOptimizer::new(OptimizationGoal::ProverTime)
+        .max_grinding_factor(0)
+        .max_lde_blowup_factor(4)
+        .optimize(options().target_security(300))
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        WinterCircomError::UnreachableSecurity { target_bits: 300 }
+    ));
+}
+
+// SNARK SCHEME TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn plonk_circuits_run_the_plonk_commands() {
+    use std::{fs, rc::Rc};
+
+    use crate::{
+        circom_verify_in_memory, codegen::circuit_manifest, gadgets::merkle, plan, rotate_keys,
+        utils::LoggingLevel, with_executor, with_workspace, CircomWorkspace, MockExecutor,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-plonk-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root).with_ptau(root.join("final.ptau"));
+    let dir = workspace.circuit_dir("merkle");
+    fs::create_dir_all(&dir).unwrap();
+    let options = merkle::proof_options(3).snark_scheme(SnarkScheme::Plonk);
+
+    // the keys are derived from the transcript alone
+    let plan = with_workspace(workspace.clone(), || plan("merkle", &options)).unwrap();
+    let commands = |action: AuditAction| {
+        plan.steps
+            .iter()
+            .find(|step| step.action == action)
+            .unwrap()
+            .commands
+            .clone()
+    };
+    let setup = commands(AuditAction::Setup);
+    assert!(setup[0].starts_with("snarkjs pks verifier.r1cs "));
+    assert!(setup[0].ends_with(" verifier.zkey"));
+    assert!(setup.iter().all(|command| !command.contains("zkc")));
+    assert!(commands(AuditAction::Groth16Prove)[0].starts_with("snarkjs pkp verifier.zkey "));
+    assert_eq!(
+        commands(AuditAction::Verify),
+        ["snarkjs pkv verification_key.json public.json proof.json"]
+    );
+
+    // the scheme is recorded in the manifest, Groth16 for earlier manifests
+    let manifest = circuit_manifest(&options, "merkle");
+    let mut legacy = serde_json::to_value(&manifest).unwrap();
+    assert_eq!(legacy["snark_scheme"], "plonk");
+    legacy.as_object_mut().unwrap().remove("snark_scheme");
+    let legacy: CircuitManifest = serde_json::from_value(legacy).unwrap();
+    assert_eq!(legacy.snark_scheme, SnarkScheme::Groth16);
+
+    with_workspace(workspace, || {
+        manifest.write().unwrap();
+        assert_eq!(
+            SnarkScheme::of_circuit("merkle").unwrap(),
+            SnarkScheme::Plonk
+        );
+        for file in ["verifier.zkey", "verification_key.json"] {
+            fs::write(dir.join(file), "{}").unwrap();
+        }
+        assert!(matches!(
+            rotate_keys("merkle", LoggingLevel::Quiet),
+            Err(WinterCircomError::UnsupportedScheme { .. })
+        ));
+    });
+
+    // in-memory verification reads the scheme of the verification key
+    let executor = Rc::new(MockExecutor::new());
+    with_executor(executor.clone(), || {
+        circom_verify_in_memory(
+            br#"{"protocol": "plonk"}"#,
+            &json!(["1"]),
+            &json!({}),
+            LoggingLevel::Quiet,
+        )
+    })
+    .unwrap();
+    assert_eq!(executor.invocations()[0].args[0], "pkv");
+
+    let _ = fs::remove_dir_all(root);
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn fflonk_circuits_run_the_fflonk_commands() {
+    use crate::{pipeline::Stage, utils::Executable};
+
+    let commands = |stage: Stage| {
+        stage
+            .commands("fflonk-test", SnarkScheme::Fflonk, "entropy")
+            .unwrap()
+            .commands
+            .into_iter()
+            .filter(|(executable, _)| matches!(executable, Executable::SnarkJS))
+            .map(|(_, args)| args)
+            .collect::<Vec<_>>()
+    };
+    let setup = commands(Stage::Setup);
+    assert_eq!(setup[0][0], "ffs");
+    assert_eq!(setup[0][3], "verifier.zkey");
+    assert!(setup.iter().all(|args| args[0] != "zkc"));
+    assert_eq!(commands(Stage::Prove)[0][0], "ffp");
+    assert_eq!(commands(Stage::Verify)[0][0], "ffv");
+
+    // FFLONK verification keys have a protocol of their own
+    assert_eq!(
+        SnarkScheme::of_verification_key(br#"{"protocol": "fflonk", "X_2": []}"#),
+        SnarkScheme::Fflonk
+    );
+    assert_eq!(
+        serde_json::to_value(SnarkScheme::Fflonk).unwrap(),
+        json!("fflonk")
+    );
+}
+
+// COLUMN MAP TESTS
+// ===========================================================================
+
+#[test]
+fn column_maps_are_permutations_of_the_trace() {
+    use crate::codegen::circuit_manifest;
+
+    let options = || WinterCircomProofOptions::new(128, 3, 3, [1, 1], 42, 8, 0, 8, 256);
+    assert!(options().check_column_map().is_ok());
+    assert_eq!(options().template_columns(), [0, 1, 2]);
+
+    let remapped = options().remap_columns(&[2, 0, 1]);
+    assert!(remapped.check_column_map().is_ok());
+    assert_eq!(remapped.template_columns(), [2, 0, 1]);
+    let invalid: [&'static [usize]; 3] = [&[0, 1], &[0, 1, 1], &[0, 1, 3]];
+    for column_map in invalid {
+        assert!(matches!(
+            options().remap_columns(column_map).check_column_map(),
+            Err(WinterCircomError::InvalidColumnMap { .. })
+        ));
+    }
+
+    // the map is recorded, and checked against the width of the proved trace
+    let manifest = circuit_manifest(&remapped, "test");
+    assert_eq!(manifest.column_map, Some(vec![2, 0, 1]));
+    assert!(manifest.check_column_map(3).is_ok());
+    assert!(matches!(
+        manifest.check_column_map(4),
+        Err(WinterCircomError::InvalidColumnMap { .. })
+    ));
+    assert_eq!(circuit_manifest(&options(), "test").column_map, None);
+}
+
+// ASSERTION TESTS
+// ===========================================================================
+
+#[test]
+fn assertions_are_deduplicated_in_canonical_order() {
+    use winterfell::Assertion;
+
+    use crate::{assertions::check_assertions, canonical_assertions, lint_air_source, LintKind};
+
+    let one = BaseElement::ONE;
+    let assertions = vec![
+        Assertion::single(1, 127, one + one),
+        Assertion::periodic(2, 0, 4, one),
+        Assertion::single(1, 0, one),
+        Assertion::single(0, 0, one),
+        Assertion::single(1, 0, one),
+    ];
+    assert!(matches!(
+        check_assertions(&assertions),
+        Err(WinterCircomError::InvalidAssertions { .. })
+    ));
+
+    let canonical = canonical_assertions(assertions);
+    let steps = canonical
+        .iter()
+        .map(|assertion| (assertion.column(), assertion.first_step()))
+        .collect::<Vec<_>>();
+    assert_eq!(steps, [(0, 0), (1, 0), (1, 127), (2, 0)]);
+    assert!(check_assertions(&canonical).is_ok());
+
+    // different values of the same steps are kept, and refused before proving,
+    // while non-adjacent duplicates are removed
+    let conflicting = canonical_assertions(vec![
+        Assertion::single(0, 0, one),
+        Assertion::single(0, 0, one + one),
+        Assertion::single(0, 0, one),
+    ]);
+    assert_eq!(conflicting.len(), 2);
+    assert!(check_assertions(&conflicting)
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("assertions differ"));
+
+    // assertions of different strides can still assert a common step
+    let overlapping = vec![
+        Assertion::periodic(0, 0, 4, one),
+        Assertion::single(0, 8, one),
+    ];
+    assert_eq!(canonical_assertions(overlapping.clone()).len(), 2);
+    assert!(check_assertions(&overlapping)
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("assertions overlap"));
+    assert!(check_assertions(&[
+        Assertion::periodic(0, 1, 4, one),
+        Assertion::single(0, 8, one),
+    ])
+    .is_ok());
+
+    // duplicated assertions of an AIR template are found by the lints
+    let source = "template AIRAssertions(addicity, num_assertions, num_public_inputs, trace_length, trace_width) {
+    signal input public_inputs[num_public_inputs];
+    component assertions[num_assertions];
+
+    assertions[0] = SingleAssertion();
+    assertions[0].column <== 0;
+    assertions[0].step <== 0;
+    assertions[0].value <== public_inputs[0];
+
+    assertions[1] = SingleAssertion();
+    assertions[1].column <== 0;
+    assertions[1].step <== 0;
+    assertions[1].value <== public_inputs [0];
+
+    assertions[2] = SingleAssertion();
+    assertions[2].column <== 0;
+    assertions[2].step <== trace_length - 1;
+    assertions[2].value <== public_inputs[0];
+}
+";
+    let lints = lint_air_source(source, &[], 3);
+    assert_eq!(lints.len(), 1, "{:?}", lints);
+    assert_eq!(lints[0].kind, LintKind::DuplicateAssertion);
+    assert_eq!(lints[0].line, 10);
+}
+
+// REMAINDER-ONLY PROOF TESTS
+// ===========================================================================
+
+#[test]
+fn tiny_traces_have_remainder_only_proofs() {
+    // the 64 elements of the LDE domain fit the remainder
+    let options = WinterCircomProofOptions::new(8, 2, 3, [1, 1], 32, 8, 0, 8, 64);
+    assert_eq!(options.num_fri_layers(), 0);
+    assert!(options.fri_tree_depths().is_empty());
+    assert_eq!(options.get_proof_options().num_queries(), 32);
+    let options = WinterCircomProofOptions::new(16, 2, 3, [1, 1], 32, 8, 0, 8, 64);
+    assert_eq!(options.num_fri_layers(), 1);
+
+    // a single layer of zeroes stands for the missing layers
+    let (proofs, queries) = fri_layer_inputs(&[], 4, 8, 6);
+    assert_eq!(proofs, vec![vec![vec![BaseElement::ZERO; 6]; 4]]);
+    assert_eq!(queries, vec![vec![BaseElement::ZERO; 32]]);
+
+    // actual layers are only padded
+    let layer = sample_fri_layer();
+    let (proofs, queries) = fri_layer_inputs(&[layer.clone()], 3, 2, 4);
+    assert_eq!(proofs, vec![layer.padded_proofs(3, 4)]);
+    assert_eq!(queries, vec![layer.padded_queries(6)]);
+}
+
+// PROVER BACKEND TESTS
+// ===========================================================================
+
+#[test]
+fn rapidsnark_proves_groth16_circuits_only() {
+    use crate::{utils::Executable, ProverBackend};
+
+    let (_, (executable, args)) = ProverBackend::Rapidsnark
+        .prove_command(SnarkScheme::Groth16, "/tmp/witness.wtns")
+        .unwrap();
+    assert!(matches!(executable, Executable::Rapidsnark));
+    assert_eq!(
+        args,
+        [
+            "verifier.zkey",
+            "/tmp/witness.wtns",
+            "proof.json",
+            "public.json"
+        ]
+    );
+
+    // snarkjs reads the same files
+    let (_, (executable, args)) = ProverBackend::SnarkJS
+        .prove_command(SnarkScheme::Groth16, "/tmp/witness.wtns")
+        .unwrap();
+    assert!(matches!(executable, Executable::SnarkJS));
+    assert_eq!(args[0], "g16p");
+    assert_eq!(
+        args[1..],
+        [
+            "verifier.zkey",
+            "/tmp/witness.wtns",
+            "proof.json",
+            "public.json"
+        ]
+    );
+
+    assert!(matches!(
+        ProverBackend::Rapidsnark.prove_command(SnarkScheme::Plonk, "witness.wtns"),
+        Err(WinterCircomError::UnsupportedScheme { .. })
+    ));
+    assert_eq!(ProverBackend::default(), ProverBackend::SnarkJS);
+}
+
+// SOLIDITY VERIFIER TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn solidity_verifiers_are_exported_with_proof_calldata() {
+    use std::rc::Rc;
+
+    use crate::{
+        export_solidity_verifier, utils::LoggingLevel, with_executor, with_workspace,
+        CircomWorkspace, Groth16Calldata, MockExecutor,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-solidity-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("verifier.zkey"), "").unwrap();
+
+    let executor = Rc::new(MockExecutor::new());
+    let path = with_workspace(workspace, || {
+        with_executor(executor.clone(), || {
+            export_solidity_verifier("sum", LoggingLevel::Quiet)
+        })
+    })
+    .unwrap();
+    assert_eq!(path, dir.join("verifier.sol"));
+    let invocations = executor.invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(
+        invocations[0].args,
+        ["zkesv", "verifier.zkey", "verifier.sol"]
+    );
+    std::fs::remove_dir_all(root).unwrap();
+
+    // the coefficients of the coordinates of b are swapped
+    let proof = json!({
+        "pi_a": ["1", "2", "1"],
+        "pi_b": [["3", "4"], ["5", "6"], ["1", "0"]],
+        "pi_c": ["7", "8", "1"],
+        "protocol": "groth16",
+        "curve": "bn128",
+    });
+    let calldata = Groth16Calldata::from_json(&proof, &json!(["9", "10"])).unwrap();
+    assert_eq!(
+        calldata.b,
+        [["4", "3"], ["6", "5"]].map(|c| c.map(String::from))
+    );
+    assert_eq!(
+        calldata.to_string(),
+        r#"["1","2"],[["4","3"],["6","5"]],["7","8"],["9","10"]"#
+    );
+
+    assert!(matches!(
+        Groth16Calldata::from_json(&json!({ "protocol": "plonk" }), &json!([])),
+        Err(WinterCircomError::InvalidSnarkProof { .. })
+    ));
+    assert!(matches!(
+        Groth16Calldata::from_json(&proof, &json!([9])),
+        Err(WinterCircomError::InvalidPublicSignals { .. })
+    ));
+}
+
+#[cfg(feature = "prove")]
+#[test]
+fn groth16_proofs_round_trip_through_eip197_bytes() {
+    use crate::Groth16Proof;
+
+    let json = json!({
+        "pi_a": ["1", "2", "1"],
+        "pi_b": [["3", "4"], ["5", "6"], ["1", "0"]],
+        "pi_c": ["7", "256", "1"],
+        "protocol": "groth16",
+        "curve": "bn128",
+    });
+    let proof = Groth16Proof::from_json(&json).unwrap();
+    assert_eq!(proof.to_json(), json);
+
+    // a, then b with the imaginary coefficients first, then c
+    let bytes = proof.to_eip197_bytes();
+    assert_eq!(bytes.len(), Groth16Proof::EIP197_BYTES);
+    let words: Vec<u8> = bytes.chunks(32).map(|word| word[31]).collect();
+    assert_eq!(words, [1, 2, 4, 3, 6, 5, 7, 0]);
+    assert_eq!(bytes[224 + 30..], [1, 0]);
+    assert_eq!(Groth16Proof::from_eip197_bytes(&bytes).unwrap(), proof);
+
+    assert!(matches!(
+        Groth16Proof::from_eip197_bytes(&bytes[..255]),
+        Err(WinterCircomError::InvalidSnarkProof { .. })
+    ));
+    // coordinates must be lower than the modulus of the base field
+    let mut bytes = bytes;
+    bytes[..32].copy_from_slice(&[0xff; 32]);
+    assert!(matches!(
+        Groth16Proof::from_eip197_bytes(&bytes),
+        Err(WinterCircomError::InvalidSnarkProof { .. })
+    ));
+}
+
+#[cfg(feature = "evm")]
+#[test]
+fn groth16_calldata_is_encoded_as_fixed_arrays() {
+    use crate::{evm::verify_proof_call, Groth16Calldata};
+
+    let calldata = Groth16Calldata {
+        a: [1, 2].map(|x| x.to_string()),
+        b: [[3, 4], [5, 6]].map(|c| c.map(|x| x.to_string())),
+        c: [7, 8].map(|x| x.to_string()),
+        input: vec![String::from("9"), String::from("10")],
+    };
+    let (function, data) = verify_proof_call(&calldata).unwrap();
+    assert_eq!(data[..4], function.short_signature());
+    // one word per element, without offset or length
+    assert_eq!(data.len(), 4 + 32 * 10);
+    assert_eq!(data[4 + 32 * 9..], {
+        let mut word = [0; 32];
+        word[31] = 10;
+        word
+    });
+}
+
+// STARKNET VERIFIER TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn starknet_calldata_is_computed_by_garaga() {
+    use std::rc::Rc;
+
+    use crate::{
+        export_cairo_verifier, starknet::parse_felts, starknet_calldata, utils::LoggingLevel,
+        with_executor, with_workspace, CircomWorkspace, MockExecutor,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-starknet-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    std::fs::create_dir_all(&dir).unwrap();
+    for file in ["verification_key.json", "proof.json", "public.json"] {
+        std::fs::write(dir.join(file), "{}").unwrap();
+    }
+
+    let executor =
+        Rc::new(MockExecutor::new().with_output("garaga", &["calldata"], "3 0x1f 2\n42\n"));
+    let (project, calldata) = with_workspace(workspace, || {
+        with_executor(executor.clone(), || {
+            (
+                export_cairo_verifier("sum", LoggingLevel::Quiet).unwrap(),
+                starknet_calldata("sum").unwrap(),
+            )
+        })
+    });
+    assert_eq!(project, dir.join("cairo_verifier"));
+    assert_eq!(calldata, ["3", "0x1f", "2", "42"]);
+    let invocations = executor.invocations();
+    assert_eq!(invocations[0].args[..3], ["gen", "--system", "groth16"]);
+    assert_eq!(invocations[0].current_dir.as_ref(), Some(&dir));
+    assert_eq!(invocations[1].args[0], "calldata");
+    std::fs::remove_dir_all(root).unwrap();
+
+    assert!(matches!(
+        parse_felts("1 0x 2"),
+        Err(WinterCircomError::InvalidSnarkProof { .. })
+    ));
+}
+
+// SOLANA VERIFIER TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn solana_instruction_data_negates_a() {
+    use crate::{
+        export_solana_verifier, solana::instruction_data, with_workspace, CircomWorkspace,
+        Groth16Calldata,
+    };
+
+    let calldata = Groth16Calldata {
+        a: [String::from("1"), String::from("2")],
+        b: [
+            [String::from("3"), String::from("4")],
+            [String::from("5"), String::from("6")],
+        ],
+        c: [String::from("7"), String::from("256")],
+        input: vec![String::from("42")],
+    };
+    let data = instruction_data(&calldata).unwrap();
+    assert_eq!(data.len(), 256 + 32);
+    assert_eq!(data[31], 1);
+    // -2 modulo the base field of BN254
+    assert_eq!(data[32..34], [0x30, 0x64]);
+    assert_eq!(data[62..64], [0xfd, 0x45]);
+    assert_eq!(data[64 + 31], 3);
+    assert_eq!(data[224 + 30..256], [1, 0]);
+    assert_eq!(data[256 + 31], 42);
+
+    let root = std::env::temp_dir().join(format!("winter-circom-solana-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("sum");
+    std::fs::create_dir_all(&dir).unwrap();
+    let g2 = json!([["1", "2"], ["3", "4"], ["1", "0"]]);
+    let key = json!({
+        "protocol": "groth16",
+        "nPublic": 1,
+        "vk_alpha_1": ["1", "2", "1"],
+        "vk_beta_2": g2,
+        "vk_gamma_2": g2,
+        "vk_delta_2": g2,
+        "IC": [["1", "2", "1"], ["3", "4", "1"]],
+    });
+    std::fs::write(dir.join("verification_key.json"), key.to_string()).unwrap();
+    let program = with_workspace(workspace, || export_solana_verifier("sum").unwrap());
+    assert_eq!(program, dir.join("solana_verifier"));
+    let source = std::fs::read_to_string(program.join("src").join("lib.rs")).unwrap();
+    assert!(source.contains("const NR_PUBLIC_INPUTS: usize = 1;"));
+    assert!(std::fs::read_to_string(program.join("Cargo.toml"))
+        .unwrap()
+        .contains("name = \"sum-verifier\""));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// ARTIFACT UPLOAD TESTS
+// ===========================================================================
+
+#[cfg(feature = "service")]
+#[test]
+fn artifacts_are_uploaded_with_their_hash() {
+    use std::rc::Rc;
+
+    use crate::{
+        audit::hash_artifact, utils::LoggingLevel, with_executor, ArtifactUpload, MockExecutor,
+    };
+
+    let dir = std::env::temp_dir().join(format!("winter-circom-upload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("proof.json");
+    std::fs::write(&path, "{}").unwrap();
+
+    let upload = ArtifactUpload::new("https://verifier.example.com/artifacts/").with_retries(5);
+    let executor = Rc::new(MockExecutor::new());
+    with_executor(executor.clone(), || {
+        upload.upload("tenant-a/sum", &path, &LoggingLevel::Quiet)
+    })
+    .unwrap();
+    let invocations = executor.invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0].program, "curl");
+    let args = &invocations[0].args;
+    assert_eq!(
+        args.last().map(String::as_str),
+        Some("https://verifier.example.com/artifacts/tenant-a/sum/proof.json")
+    );
+    assert!(args.windows(2).any(|w| w == ["--retry", "5"]));
+    assert!(args.contains(&format!("X-Winter-Circom-Blake3: {}", hash_artifact(b"{}"))));
+    std::fs::remove_dir_all(dir).unwrap();
+
+    // failed uploads are reported
+    let executor = Rc::new(MockExecutor::new().with_failure("curl", &[], 22));
+    assert!(with_executor(executor, || {
+        upload.upload("sum", &path, &LoggingLevel::Quiet)
+    })
+    .is_err());
+}
+
+// LARGE ARTIFACT TESTS
+// ===========================================================================
+
+/// Sparse files of more than 4 GiB, which take no disk space on the file
+/// systems supporting them, stand for large witnesses and zkeys.
+#[test]
+fn artifacts_over_4gb_are_not_truncated() {
+    use std::{
+        fs::{self, File},
+        path::PathBuf,
+    };
+
+    use crate::{
+        audit::{hash_artifact, hash_file},
+        utils::directory_size,
+    };
+
+    // the 4 GiB file is removed even if an assertion fails
+    struct RemoveOnDrop(PathBuf);
+
+    impl Drop for RemoveOnDrop {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("winter-circom-large-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let _cleanup = RemoveOnDrop(dir.clone());
+    let size = (1u64 << 32) + 1;
+    let large = dir.join("verifier.zkey");
+    File::create(&large).unwrap().set_len(size).unwrap();
+    // the file a 32-bit size would read
+    let truncated = dir.join("truncated.zkey");
+    fs::write(&truncated, [0u8]).unwrap();
+
+    assert_eq!(fs::metadata(&large).unwrap().len(), size);
+    assert_eq!(directory_size(&dir), size + 1);
+    assert_eq!(format_size(size), "4.0 GiB");
+    assert_eq!(hash_file(&truncated).unwrap(), hash_artifact(&[0]));
+    assert_ne!(hash_file(&large).unwrap(), hash_file(&truncated).unwrap());
+}
+
+// SNARK BACKEND TESTS
+// ===========================================================================
+
+#[test]
+fn pipeline_stages_run_the_snark_backend_of_the_thread() {
+    use std::{cell::RefCell, fs, rc::Rc};
+
+    use crate::{
+        history, utils::LoggingLevel, with_executor, with_snark_backend, with_workspace,
+        AuditAction, CircomWorkspace, MockExecutor, Pipeline, SnarkBackend,
+    };
+
+    #[derive(Default)]
+    struct RecordingBackend {
+        steps: RefCell<Vec<&'static str>>,
+    }
+
+    impl SnarkBackend for RecordingBackend {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn setup(&self, _: &str, _: LoggingLevel) -> Result<(), WinterCircomError> {
+            self.steps.borrow_mut().push("setup");
+            Ok(())
+        }
+
+        fn export_vk(&self, _: &str, _: LoggingLevel) -> Result<(), WinterCircomError> {
+            self.steps.borrow_mut().push("export_vk");
+            Ok(())
+        }
+
+        fn prove(&self, _: &str, _: LoggingLevel) -> Result<(), WinterCircomError> {
+            self.steps.borrow_mut().push("prove");
+            Ok(())
+        }
+
+        fn verify(&self, _: &str, _: LoggingLevel) -> Result<(), WinterCircomError> {
+            self.steps.borrow_mut().push("verify");
+            Err(WinterCircomError::NativeProverError {
+                comment: String::from("the proof is invalid"),
+            })
+        }
+    }
+
+    let root = std::env::temp_dir().join(format!(
+        "winter-circom-snark-backend-{}",
+        std::process::id()
+    ));
+    let workspace = CircomWorkspace::new(&root);
+    fs::create_dir_all(workspace.circuit_dir("sum")).unwrap();
+
+    let backend = Rc::new(RecordingBackend::default());
+    let executor = Rc::new(MockExecutor::new());
+    let result = with_workspace(workspace.clone(), || {
+        with_executor(executor.clone(), || {
+            with_snark_backend(backend.clone(), || {
+                Pipeline::new("sum")
+                    .setup()
+                    .prove()
+                    .verify()
+                    .run(LoggingLevel::Quiet)
+            })
+        })
+    });
+
+    // the backend replaces the snarkjs commands, and fails the stages
+    assert!(matches!(
+        result,
+        Err(WinterCircomError::NativeProverError { .. })
+    ));
+    assert_eq!(
+        *backend.steps.borrow(),
+        ["setup", "export_vk", "prove", "verify"]
+    );
+    assert!(executor.invocations().is_empty());
+
+    let entries = with_workspace(workspace, || history("sum")).unwrap();
+    assert_eq!(entries[0].action, AuditAction::Setup);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.parameters["backend"] == "recording"));
+    assert!(!entries[2].is_success());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+// KEY FINALIZATION TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn keys_are_finalized_with_a_recorded_beacon() {
+    use std::{fs, rc::Rc};
+
+    use crate::{
+        codegen::circuit_manifest, finalize_keys, gadgets::merkle, utils::LoggingLevel,
+        with_executor, with_workspace, Beacon, BeaconSource, BlockhashBeacon, CircomWorkspace,
+        DrandBeacon, ManualBeacon, MockExecutor, SetupAttestation,
+    };
+
+    // beacon values are checked and normalized
+    let value = ManualBeacon::new("0xABCD", "test vector").fetch().unwrap();
+    assert_eq!(value.hex, "abcd");
+    for hex in ["", "abc", "0xzz"] {
+        assert!(matches!(
+            ManualBeacon::new(hex, "test vector").fetch(),
+            Err(WinterCircomError::InvalidBeacon { .. })
+        ));
+    }
+
+    let drand = r#"{"round": 42, "randomness": "00ff", "signature": "beef"}"#;
+    let executor = Rc::new(MockExecutor::new().with_output("curl", &["-sfS"], drand));
+    let value = with_executor(executor.clone(), || DrandBeacon::new(42).fetch()).unwrap();
+    assert_eq!(value.hex, "00ff");
+    assert_eq!(
+        value.source,
+        BeaconSource::Drand {
+            url: String::from("https://api.drand.sh"),
+            round: 42,
+            signature: String::from("beef"),
+        }
+    );
+    assert_eq!(
+        executor.invocations()[0].args.last().map(String::as_str),
+        Some("https://api.drand.sh/public/42")
+    );
+    assert!(with_executor(executor, || DrandBeacon::new(43).fetch()).is_err());
+
+    // blocks are not available before they are proposed
+    let beacon = BlockhashBeacon {
+        rpc_url: String::from("http://localhost:8545"),
+        number: 255,
+    };
+    let executor = Rc::new(MockExecutor::new().with_output(
+        "curl",
+        &["-sfS"],
+        r#"{"jsonrpc": "2.0", "id": 1, "result": {"hash": "0x1234"}}"#,
+    ));
+    let value = with_executor(executor.clone(), || beacon.fetch()).unwrap();
+    assert_eq!(value.hex, "1234");
+    assert!(executor.invocations()[0]
+        .args
+        .iter()
+        .any(|arg| arg.contains("\"0xff\"")));
+    let executor = Rc::new(MockExecutor::new().with_output(
+        "curl",
+        &["-sfS"],
+        r#"{"jsonrpc": "2.0", "id": 1, "result": null}"#,
+    ));
+    assert!(matches!(
+        with_executor(executor, || beacon.fetch()),
+        Err(WinterCircomError::InvalidBeacon { .. })
+    ));
+
+    // the beacon is applied to the proving key, and recorded
+    let root = std::env::temp_dir().join(format!("winter-circom-beacon-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("merkle");
+    fs::create_dir_all(&dir).unwrap();
+    let executor = Rc::new(MockExecutor::new());
+    let attestation = with_workspace(workspace.clone(), || {
+        circuit_manifest(&merkle::proof_options(3), "merkle")
+            .write()
+            .unwrap();
+        for file in [
+            "verifier.zkey",
+            "verifier_final.zkey",
+            "verification_key.json",
+        ] {
+            fs::write(dir.join(file), file).unwrap();
+        }
+        with_executor(executor.clone(), || {
+            finalize_keys(
+                "merkle",
+                &ManualBeacon::new("0102", "test vector"),
+                LoggingLevel::Quiet,
+            )
+        })
+    })
+    .unwrap();
+
+    let invocations = executor.invocations();
+    assert_eq!(
+        invocations[0].args,
+        [
+            "zkb",
+            "verifier.zkey",
+            "verifier_final.zkey",
+            "0102",
+            "10",
+            "--name=beacon"
+        ]
+    );
+    assert_eq!(invocations[1].args[0], "zkev");
+    assert_eq!(
+        fs::read_to_string(dir.join("verifier.zkey")).unwrap(),
+        "verifier_final.zkey"
+    );
+    assert_eq!(attestation.epoch, 0);
+    assert_eq!(
+        attestation.beacon.source,
+        BeaconSource::Manual {
+            description: String::from("test vector")
+        }
+    );
+    assert!(attestation.verification_key_hash.is_some());
+    assert_eq!(
+        with_workspace(workspace.clone(), || SetupAttestation::read("merkle")).unwrap(),
+        Some(attestation)
+    );
+
+    // a truncated attestation is reported
+    fs::write(dir.join("keys").join("attestation.json"), "{ \"epoch\": 0,").unwrap();
+    assert!(matches!(
+        with_workspace(workspace, || SetupAttestation::read("merkle")),
+        Err(WinterCircomError::CorruptArtifact { .. })
+    ));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+// KEY ROTATION TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn rotated_keys_are_archived_and_still_accepted() {
+    use std::{fs, rc::Rc};
+
+    use crate::{
+        circom_verify_with_history, codegen::circuit_manifest, gadgets::merkle, rotate_keys,
+        utils::LoggingLevel, with_executor, with_workspace, CircomWorkspace, KeyHistory,
+        MockExecutor,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-keys-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root);
+    let dir = workspace.circuit_dir("merkle");
+    fs::create_dir_all(&dir).unwrap();
+    let executor = Rc::new(MockExecutor::new());
+    let epoch = with_workspace(workspace.clone(), || {
+        circuit_manifest(&merkle::proof_options(3), "merkle")
+            .write()
+            .unwrap();
+        for (file, content) in [
+            ("verifier.zkey", "zkey 0"),
+            ("verifier_next.zkey", "zkey 1"),
+            ("verification_key.json", "vk 0"),
+            ("public.json", "[]"),
+            ("proof.json", "{}"),
+        ] {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        with_executor(executor.clone(), || {
+            rotate_keys("merkle", LoggingLevel::Quiet)
+        })
+    })
+    .unwrap();
+
+    // a new contribution is added, and the previous keys archived
+    assert_eq!((epoch.epoch, epoch.valid_until), (1, None));
+    let invocations = executor.invocations();
+    assert_eq!(
+        invocations[0].args[..3],
+        ["zkc", "verifier.zkey", "verifier_next.zkey"]
+    );
+    assert_eq!(invocations[0].args[3], "--name=rotation 1");
+    assert_eq!(
+        invocations[1].args,
+        ["zkev", "verifier.zkey", "verification_key.json"]
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("verifier.zkey")).unwrap(),
+        "zkey 1"
+    );
+    let archive = dir.join("keys").join("0");
+    assert_eq!(
+        fs::read_to_string(archive.join("verifier.zkey")).unwrap(),
+        "zkey 0"
+    );
+    assert_eq!(
+        fs::read_to_string(archive.join("verification_key.json")).unwrap(),
+        "vk 0"
+    );
+
+    let history = with_workspace(workspace.clone(), || KeyHistory::read("merkle")).unwrap();
+    assert_eq!(history.epochs.len(), 2);
+    assert_eq!(history.epochs[0].valid_until, Some(epoch.valid_from));
+    assert_eq!(history.current(), epoch);
+    assert_eq!(history.verification_key("merkle", 2), None);
+
+    // proofs are checked against the accepted epochs in order, unknown epochs
+    // being skipped
+    let current_vkey = with_workspace(workspace.clone(), || {
+        history.verification_key("merkle", 1).unwrap()
+    });
+    let verify = |executor: MockExecutor, epochs: &[usize]| {
+        with_workspace(workspace.clone(), || {
+            with_executor(Rc::new(executor), || {
+                circom_verify_with_history("merkle", epochs, LoggingLevel::Quiet)
+            })
+        })
+    };
+    let reject_current = || {
+        MockExecutor::new().with_failure("snarkjs", &["g16v", current_vkey.to_str().unwrap()], 1)
+    };
+    assert_eq!(verify(MockExecutor::new(), &[1, 0]).unwrap(), 1);
+    assert_eq!(verify(reject_current(), &[7, 1, 0]).unwrap(), 0);
+    assert!(matches!(
+        verify(reject_current(), &[1]),
+        Err(WinterCircomError::ExitCodeError { code: 1, .. })
+    ));
+    assert!(matches!(
+        verify(MockExecutor::new(), &[7]),
+        Err(WinterCircomError::FileNotFound { .. })
+    ));
+
+    // corrupt histories are reported
+    for history in ["{ \"epochs\": [", "{ \"epochs\": [] }"] {
+        fs::write(dir.join("keys").join("history.json"), history).unwrap();
+        assert!(matches!(
+            with_workspace(workspace.clone(), || KeyHistory::read("merkle")),
+            Err(WinterCircomError::CorruptArtifact { .. })
+        ));
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+// PREFLIGHT TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn preflight_reports_each_failed_check() {
+    use std::rc::Rc;
+
+    use crate::{
+        preflight, with_executor, with_workspace, CheckStatus, CircomWorkspace, MockExecutor,
+        PreflightReport, PreflightRequirements,
+    };
+
+    let root = std::env::temp_dir().join(format!("winter-circom-preflight-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root).with_ptau(root.join("final.ptau"));
+    let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+              /dev/sda1 100 60 40 60% /\n";
+    let tools = || {
+        MockExecutor::new()
+            .with_output("circom", &["--version"], "circom compiler 2.1.6\n")
+            .with_output("snarkjs", &["--version"], "snarkjs@0.7.0\n")
+            .with_output("node", &["--version"], "v18.0.0\n")
+            .with_output("df", &["-Pk"], df)
+    };
+    let run = |executor: MockExecutor, requirements: PreflightRequirements| {
+        with_workspace(workspace.clone(), || {
+            with_executor(Rc::new(executor), || preflight(requirements))
+        })
+    };
+    let statuses = |report: &PreflightReport, name: &str| {
+        report
+            .checks
+            .iter()
+            .filter(|check| check.name == name)
+            .map(|check| check.status)
+            .collect::<Vec<_>>()
+    };
+
+    // a missing transcript and too little disk space are failures
+    let report = run(
+        tools(),
+        PreflightRequirements {
+            disk_bytes: Some(1 << 20),
+            memory_bytes: None,
+        },
+    );
+    assert!(!report.is_ready());
+    assert_eq!(
+        report
+            .failures()
+            .map(|check| check.name)
+            .collect::<Vec<_>>(),
+        ["ptau", "disk"]
+    );
+    assert_eq!(report.checks[0].detail, "circom compiler 2.1.6");
+    assert_eq!(statuses(&report, "permissions"), [CheckStatus::Pass]);
+    assert!(root.exists() && !root.join(".preflight").exists());
+
+    std::fs::write(root.join("final.ptau"), "ptau").unwrap();
+    let report = run(tools(), PreflightRequirements::default());
+    assert!(report.is_ready());
+    assert_eq!(statuses(&report, "disk"), [CheckStatus::Pass]);
+    assert_eq!(
+        report.checks[3].detail,
+        format!("{} (4 bytes)", root.join("final.ptau").display())
+    );
+
+    // missing tools fail, while unknown free space only warns
+    let executor = MockExecutor::new()
+        .with_failure("circom", &["--version"], 127)
+        .with_failure("df", &["-Pk"], 1);
+    let report = run(executor, PreflightRequirements::default());
+    assert_eq!(statuses(&report, "circom"), [CheckStatus::Fail]);
+    assert_eq!(statuses(&report, "disk"), [CheckStatus::Warn]);
+    assert!(!report.is_ready());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+// PUBLIC SIGNAL DECODING TESTS
+// ===========================================================================
+
+#[cfg(feature = "verify")]
+#[test]
+fn public_signals_decode_into_public_inputs_and_ood_data() {
+    use winterfell::math::StarkField;
+
+    use crate::{
+        decode_public_signals,
+        gadgets::merkle::{MerkleAir, MerklePublicInputs, TRACE_WIDTH},
+    };
+
+    let dir = std::env::temp_dir().join(format!("winter-circom-decode-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("public.json");
+    let decode = |signals: &[u64]| {
+        let signals = signals
+            .iter()
+            .map(|&x| BaseElement::from(x).as_int().to_string())
+            .collect::<Vec<_>>();
+        std::fs::write(&path, serde_json::to_string(&signals).unwrap()).unwrap();
+        decode_public_signals::<MerkleAir>(&path)
+    };
+
+    // the constraint evaluations and the OOD frame come before the public
+    // inputs
+    let num_ood_signals = 3 + 2 * TRACE_WIDTH;
+    let signals = (0..num_ood_signals as u64 + 2).collect::<Vec<_>>();
+    let (public_inputs, ood_data): (MerklePublicInputs, _) = decode(&signals).unwrap();
+    assert_eq!(public_inputs.leaf, BaseElement::from(11u64));
+    assert_eq!(public_inputs.root, BaseElement::from(12u64));
+    let e = |range: std::ops::Range<u64>| range.map(BaseElement::from).collect::<Vec<_>>();
+    assert_eq!(ood_data.constraint_evaluations, e(0..3));
+    assert_eq!(ood_data.current, e(3..7));
+    assert_eq!(ood_data.next, e(7..11));
+
+    // circuits without exposed public inputs have fewer signals
+    assert!(matches!(
+        decode(&signals[..num_ood_signals]),
+        Err(WinterCircomError::InvalidPublicSignals { .. })
+    ));
+    std::fs::write(&path, r#"["1", "0x2"]"#).unwrap();
+    assert!(matches!(
+        decode_public_signals::<MerkleAir>(&path),
+        Err(WinterCircomError::InvalidPublicSignals { .. })
+    ));
+    assert!(matches!(
+        decode_public_signals::<MerkleAir>(dir.join("missing.json")),
+        Err(WinterCircomError::IoError { .. })
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// CHECKED PROVER TESTS
+// ===========================================================================
+
+#[cfg(feature = "prove")]
+#[test]
+fn public_inputs_inconsistent_with_the_trace_are_rejected() {
+    use winterfell::{ProofOptions, Prover, Trace, TraceTable};
+
+    use crate::{
+        circom_create_in_memory, circom_prove_in_memory,
+        gadgets::merkle::{self, MerkleAir, MerklePath, MerkleProver, MerklePublicInputs},
+        utils::LoggingLevel,
+        CheckedProver,
+    };
+
+    // a prover reading the root from the wrong step of the trace
+    struct WrongRootProver(MerkleProver);
+
+    impl Prover for WrongRootProver {
+        type BaseField = BaseElement;
+        type Air = MerkleAir;
+        type Trace = TraceTable<BaseElement>;
+
+        fn get_pub_inputs(&self, trace: &Self::Trace) -> MerklePublicInputs {
+            MerklePublicInputs {
+                root: trace.get(0, trace.length() - 2),
+                ..self.0.get_pub_inputs(trace)
+            }
+        }
+
+        fn options(&self) -> &ProofOptions {
+            self.0.options()
+        }
+    }
+
+    let options = merkle::proof_options(7);
+    let circuit = circom_create_in_memory::<MerkleProver, 3>(options, "merkle", "..").unwrap();
+    let path = MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 5,
+        siblings: (1000..1007u64).map(BaseElement::from).collect(),
+    };
+    let prover = || MerkleProver::new(options.get_proof_options());
+    let trace = || prover().build_trace(&path);
+
+    let checked = CheckedProver::new(prover());
+    assert!(checked.check(&trace()).is_ok());
+    assert!(circom_prove_in_memory(checked, trace(), None, &circuit, LoggingLevel::Quiet).is_ok());
+
+    // the assertion of the root fails on the last step, with or without the
+    // wrapper
+    let checked = CheckedProver::new(WrongRootProver(prover()));
+    assert!(matches!(
+        checked.check(&trace()),
+        Err(WinterCircomError::InvalidTrace { row: Some(7), .. })
+    ));
+    for result in [
+        circom_prove_in_memory(checked, trace(), None, &circuit, LoggingLevel::Quiet),
+        circom_prove_in_memory(
+            WrongRootProver(prover()),
+            trace(),
+            None,
+            &circuit,
+            LoggingLevel::Quiet,
+        ),
+    ] {
+        assert!(matches!(
+            result,
+            Err(WinterCircomError::InvalidTrace { row: Some(7), .. })
+        ));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{fs, path::Path};
+
+use winterfell::math::fields::f256::BaseElement;
+
+use super::{ColumnMapping, ColumnParser};
+use crate::utils::WinterCircomError;
+
+/// Read the mapped columns of a CSV file into columns of field elements.
+///
+/// Fields are split on every comma, without quoting support: lines containing
+/// a double quote are rejected rather than misread.
+pub(super) fn read_columns(
+    path: &Path,
+    column_mapping: &ColumnMapping,
+    parsers: &[ColumnParser],
+) -> Result<Vec<Vec<BaseElement>>, WinterCircomError> {
+    if parsers.len() != column_mapping.width() {
+        return Err(WinterCircomError::InvalidTrace {
+            row: None,
+            comment: format!(
+                "{} column parsers given for {} mapped columns",
+                parsers.len(),
+                column_mapping.width()
+            ),
+        });
+    }
+
+    let data = fs::read_to_string(path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", path.to_string_lossy())),
+    })?;
+    let mut lines = data.lines().filter(|line| !line.trim().is_empty());
+
+    // resolve the index of each mapped column in the header
+    let header = lines
+        .next()
+        .ok_or_else(|| WinterCircomError::InvalidTrace {
+            row: None,
+            comment: String::from("CSV file is empty"),
+        })?;
+    if header.contains('"') {
+        return Err(unsupported_quotes(None));
+    }
+    let header = header
+        .split(',')
+        .map(|name| name.trim())
+        .collect::<Vec<_>>();
+    let indexes = column_mapping
+        .columns()
+        .iter()
+        .map(|name| {
+            header
+                .iter()
+                .position(|h| *h == name.as_str())
+                .ok_or_else(|| WinterCircomError::InvalidTrace {
+                    row: None,
+                    comment: format!("column {} not found in CSV header", name),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut columns = vec![Vec::new(); column_mapping.width()];
+    for (step, line) in lines.enumerate() {
+        if line.contains('"') {
+            return Err(unsupported_quotes(Some(step)));
+        }
+        let values = line.split(',').collect::<Vec<_>>();
+        if values.len() != header.len() {
+            return Err(WinterCircomError::InvalidTrace {
+                row: Some(step),
+                comment: format!("expected {} values, found {}", header.len(), values.len()),
+            });
+        }
+
+        for (column, (&index, parser)) in indexes.iter().zip(parsers).enumerate() {
+            let element =
+                parser
+                    .parse(values[index])
+                    .ok_or_else(|| WinterCircomError::InvalidTrace {
+                        row: Some(step),
+                        comment: format!(
+                            "invalid {} value {:?} in column {}",
+                            parser.name(),
+                            values[index].trim(),
+                            header[index]
+                        ),
+                    })?;
+            columns[column].push(element);
+        }
+    }
+
+    Ok(columns)
+}
+
+fn unsupported_quotes(row: Option<usize>) -> WinterCircomError {
+    WinterCircomError::InvalidTrace {
+        row,
+        comment: String::from("quoted CSV fields are not supported"),
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(feature = "concurrent")]
+use rayon::prelude::*;
+use winterfell::math::{fields::f256::BaseElement, FieldElement};
+
+use crate::utils::WinterCircomError;
+
+/// Number of elements above which transpositions are parallelized. Below it,
+/// spawning the tasks costs more than the copy.
+#[cfg(feature = "concurrent")]
+const PARALLEL_TRANSPOSE_THRESHOLD: usize = 1 << 16;
+
+/// Number of steps transposed at once, in a single task when parallelized.
+const STEP_BLOCK: usize = 1 << 10;
+
+/// Transpose a stream of rows into the columns of a trace.
+///
+/// Each row is copied into the columns as soon as it is read, so that the rows
+/// themselves are never buffered: this is the layout of event streams and of
+/// the result sets of database queries.
+pub(crate) fn columns_from_rows<I, R>(
+    rows: I,
+    width: usize,
+) -> Result<Vec<Vec<BaseElement>>, WinterCircomError>
+where
+    I: IntoIterator<Item = R>,
+    R: AsRef<[BaseElement]>,
+{
+    let rows = rows.into_iter();
+    let capacity = rows.size_hint().0;
+    let mut columns = (0..width)
+        .map(|_| Vec::with_capacity(capacity))
+        .collect::<Vec<_>>();
+    for (step, row) in rows.enumerate() {
+        let row = row.as_ref();
+        check_row_width(row.len(), width, step)?;
+        for (column, value) in columns.iter_mut().zip(row) {
+            column.push(*value);
+        }
+    }
+    Ok(columns)
+}
+
+/// Transpose a row-major buffer, whose `width` first elements are the first
+/// step of the trace, into the columns of a trace.
+pub(crate) fn columns_from_row_major(
+    data: &[BaseElement],
+    width: usize,
+) -> Result<Vec<Vec<BaseElement>>, WinterCircomError> {
+    if width == 0 || data.len() % width != 0 {
+        return Err(WinterCircomError::InvalidTrace {
+            row: None,
+            comment: format!(
+                "{} elements cannot be split into rows of {} columns",
+                data.len(),
+                width
+            ),
+        });
+    }
+
+    let length = data.len() / width;
+    let mut columns = vec![vec![BaseElement::ZERO; length]; width];
+    let transpose_block = |column: usize, values: &mut [BaseElement], first_step: usize| {
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = data[(first_step + i) * width + column];
+        }
+    };
+
+    #[cfg(feature = "concurrent")]
+    if data.len() >= PARALLEL_TRANSPOSE_THRESHOLD {
+        columns
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(column, values)| {
+                values
+                    .par_chunks_mut(STEP_BLOCK)
+                    .enumerate()
+                    .for_each(|(block, values)| {
+                        transpose_block(column, values, block * STEP_BLOCK)
+                    });
+            });
+        return Ok(columns);
+    }
+
+    for (column, values) in columns.iter_mut().enumerate() {
+        transpose_block(column, values, 0);
+    }
+    Ok(columns)
+}
+
+/// Transpose columns of equal length into a row-major buffer, see
+/// [columns_from_row_major].
+pub(crate) fn row_major_from_columns(columns: &[&[BaseElement]]) -> Vec<BaseElement> {
+    let width = columns.len();
+    let length = columns.first().map_or(0, |column| column.len());
+    let mut data = vec![BaseElement::ZERO; width * length];
+    if width == 0 {
+        return data;
+    }
+    let transpose_block = |rows: &mut [BaseElement], first_step: usize| {
+        for (i, row) in rows.chunks_mut(width).enumerate() {
+            for (value, column) in row.iter_mut().zip(columns) {
+                *value = column[first_step + i];
+            }
+        }
+    };
+
+    #[cfg(feature = "concurrent")]
+    if data.len() >= PARALLEL_TRANSPOSE_THRESHOLD {
+        data.par_chunks_mut(STEP_BLOCK * width)
+            .enumerate()
+            .for_each(|(block, rows)| transpose_block(rows, block * STEP_BLOCK));
+        return data;
+    }
+
+    for (block, rows) in data.chunks_mut(STEP_BLOCK * width).enumerate() {
+        transpose_block(rows, block * STEP_BLOCK);
+    }
+    data
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+fn check_row_width(row_width: usize, width: usize, step: usize) -> Result<(), WinterCircomError> {
+    if row_width != width {
+        return Err(WinterCircomError::InvalidTrace {
+            row: Some(step),
+            comment: format!("row has {} values, expected {}", row_width, width),
+        });
+    }
+    Ok(())
+}
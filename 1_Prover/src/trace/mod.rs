@@ -0,0 +1,234 @@
+//! Builders of Winterfell execution traces from external data sources.
+
+use std::path::Path;
+
+use winterfell::{
+    math::{
+        fields::f256::{BaseElement, U256},
+        StarkField,
+    },
+    TraceInfo, TraceTable,
+};
+
+use crate::utils::WinterCircomError;
+
+mod csv_reader;
+
+mod layout;
+
+mod sparse;
+pub use sparse::{PaddingStrategy, SparseColumn, SparseTrace};
+
+#[cfg(feature = "parquet")]
+mod parquet_reader;
+
+/// Mapping between the columns of an external data source and the columns of
+/// an execution trace.
+///
+/// The `i`-th column of the trace is read from the source column named
+/// `columns[i]`. The width of the trace is therefore the number of mapped
+/// columns.
+#[derive(Clone, Debug)]
+pub struct ColumnMapping {
+    columns: Vec<String>,
+}
+
+impl ColumnMapping {
+    pub fn new<S: Into<String>>(columns: Vec<S>) -> Self {
+        Self {
+            columns: columns.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+
+    /// Returns the number of trace columns defined by this mapping.
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the source column names, ordered by trace column index.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+/// Parser converting a textual value into a field element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnParser {
+    /// Unsigned 64-bit decimal integer.
+    U64,
+
+    /// Hexadecimal integer, with or without a `0x` prefix.
+    Hex,
+
+    /// Decimal integer of arbitrary size, lower than the field modulus.
+    Decimal,
+}
+
+impl ColumnParser {
+    /// Parse a value into a field element, returning `None` if the value is
+    /// invalid.
+    pub fn parse(&self, value: &str) -> Option<BaseElement> {
+        let value = value.trim();
+        let n = match self {
+            ColumnParser::U64 => return value.parse::<u64>().ok().map(BaseElement::from),
+            ColumnParser::Hex => {
+                let digits = value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("0X"))
+                    .unwrap_or(value);
+                U256::from_str_radix(digits, 16).ok()?
+            }
+            ColumnParser::Decimal => U256::from_str_radix(value, 10).ok()?,
+        };
+
+        if n >= BaseElement::MODULUS {
+            return None;
+        }
+        Some(BaseElement::new(n))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ColumnParser::U64 => "u64",
+            ColumnParser::Hex => "hex",
+            ColumnParser::Decimal => "decimal",
+        }
+    }
+}
+
+/// Extension methods for building a [TraceTable] from external data sources.
+///
+/// Winterfell stores traces column-major, while most sources produce them row
+/// by row. The conversions between the two layouts are parallelized for large
+/// traces when the `concurrent` feature is enabled.
+pub trait TraceTableExt: Sized {
+    /// Load an execution trace from a Parquet file.
+    ///
+    /// Each row of the file is a step of the trace. Integer columns are mapped
+    /// to field elements directly, string columns are parsed as decimal
+    /// integers and 32-byte binary columns are read as little-endian field
+    /// elements.
+    ///
+    /// The number of rows must be a power of two greater than or equal to
+    /// [TraceInfo::MIN_TRACE_LENGTH].
+    #[cfg(feature = "parquet")]
+    fn from_parquet<P: AsRef<Path>>(
+        path: P,
+        column_mapping: &ColumnMapping,
+    ) -> Result<Self, WinterCircomError>;
+
+    /// Load an execution trace from a CSV file.
+    ///
+    /// The first line of the file must be a header naming the columns. Each
+    /// following line is a step of the trace, whose mapped values are parsed
+    /// with the parser of the corresponding trace column (`parsers[i]` for
+    /// trace column `i`).
+    ///
+    /// Fields are separated by commas, and quoted fields are not supported:
+    /// files containing double quotes are rejected. Errors point at the
+    /// offending trace step (the first line after the header being step 0).
+    fn from_csv<P: AsRef<Path>>(
+        path: P,
+        column_mapping: &ColumnMapping,
+        parsers: &[ColumnParser],
+    ) -> Result<Self, WinterCircomError>;
+
+    /// Build an execution trace from a stream of `width` wide rows, e.g. the
+    /// events of a log or the rows of a database query.
+    ///
+    /// The rows are consumed one at a time, without being buffered. The number
+    /// of rows must be a power of two greater than or equal to
+    /// [TraceInfo::MIN_TRACE_LENGTH], and errors point at the first row of the
+    /// wrong width.
+    fn from_rows<I, R>(rows: I, width: usize) -> Result<Self, WinterCircomError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[BaseElement]>;
+
+    /// Build an execution trace from a row-major buffer, whose `width` first
+    /// elements are the first step of the trace.
+    fn from_row_major(data: &[BaseElement], width: usize) -> Result<Self, WinterCircomError>;
+
+    /// Returns the main segment of the trace as a row-major buffer, see
+    /// [from_row_major](TraceTableExt::from_row_major).
+    fn to_row_major(&self) -> Vec<BaseElement>;
+}
+
+impl TraceTableExt for TraceTable<BaseElement> {
+    #[cfg(feature = "parquet")]
+    fn from_parquet<P: AsRef<Path>>(
+        path: P,
+        column_mapping: &ColumnMapping,
+    ) -> Result<Self, WinterCircomError> {
+        let columns = parquet_reader::read_columns(path.as_ref(), column_mapping)?;
+        build_trace(columns)
+    }
+
+    fn from_csv<P: AsRef<Path>>(
+        path: P,
+        column_mapping: &ColumnMapping,
+        parsers: &[ColumnParser],
+    ) -> Result<Self, WinterCircomError> {
+        let columns = csv_reader::read_columns(path.as_ref(), column_mapping, parsers)?;
+        build_trace(columns)
+    }
+
+    fn from_rows<I, R>(rows: I, width: usize) -> Result<Self, WinterCircomError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[BaseElement]>,
+    {
+        build_trace(layout::columns_from_rows(rows, width)?)
+    }
+
+    fn from_row_major(data: &[BaseElement], width: usize) -> Result<Self, WinterCircomError> {
+        build_trace(layout::columns_from_row_major(data, width)?)
+    }
+
+    fn to_row_major(&self) -> Vec<BaseElement> {
+        let columns = (0..self.width())
+            .map(|column| self.get_column(column))
+            .collect::<Vec<_>>();
+        layout::row_major_from_columns(&columns)
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Build a trace table from its columns, validating its dimensions.
+pub(crate) fn build_trace(
+    columns: Vec<Vec<BaseElement>>,
+) -> Result<TraceTable<BaseElement>, WinterCircomError> {
+    if columns.is_empty() {
+        return Err(WinterCircomError::InvalidTrace {
+            row: None,
+            comment: String::from("trace must have at least one column"),
+        });
+    }
+
+    let length = columns[0].len();
+    if let Some(i) = columns.iter().position(|c| c.len() != length) {
+        return Err(WinterCircomError::InvalidTrace {
+            row: None,
+            comment: format!(
+                "column {} has {} rows, expected {}",
+                i,
+                columns[i].len(),
+                length
+            ),
+        });
+    }
+    if !length.is_power_of_two() || length < TraceInfo::MIN_TRACE_LENGTH {
+        return Err(WinterCircomError::InvalidTrace {
+            row: None,
+            comment: format!(
+                "trace length must be a power of two greater than or equal to {}, got {}",
+                TraceInfo::MIN_TRACE_LENGTH,
+                length
+            ),
+        });
+    }
+
+    Ok(TraceTable::init(columns))
+}
@@ -0,0 +1,95 @@
+use std::{fs::File, path::Path};
+
+use parquet::{
+    file::reader::{FileReader, SerializedFileReader},
+    record::Field,
+};
+use winterfell::math::{
+    fields::f256::{BaseElement, U256},
+    FieldElement,
+};
+
+use super::ColumnMapping;
+use crate::utils::WinterCircomError;
+
+/// Read the mapped columns of a Parquet file into columns of field elements.
+pub(super) fn read_columns(
+    path: &Path,
+    column_mapping: &ColumnMapping,
+) -> Result<Vec<Vec<BaseElement>>, WinterCircomError> {
+    let file = File::open(path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("opening {}", path.to_string_lossy())),
+    })?;
+    let reader = SerializedFileReader::new(file).map_err(|e| WinterCircomError::InvalidTrace {
+        row: None,
+        comment: format!("could not read Parquet file: {}", e),
+    })?;
+
+    // resolve the index of each mapped column in the file schema
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let indexes = column_mapping
+        .columns()
+        .iter()
+        .map(|name| {
+            (0..schema.num_columns())
+                .find(|&i| schema.column(i).name() == name)
+                .ok_or_else(|| WinterCircomError::InvalidTrace {
+                    row: None,
+                    comment: format!("column {} not found in Parquet file", name),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rows = reader
+        .get_row_iter(None)
+        .map_err(|e| WinterCircomError::InvalidTrace {
+            row: None,
+            comment: format!("could not read Parquet rows: {}", e),
+        })?;
+
+    let mut columns = vec![Vec::new(); column_mapping.width()];
+    for (step, row) in rows.enumerate() {
+        let row = row.map_err(|e| WinterCircomError::InvalidTrace {
+            row: Some(step),
+            comment: format!("could not read Parquet row: {}", e),
+        })?;
+        let fields = row.get_column_iter().collect::<Vec<_>>();
+
+        for (column, &index) in indexes.iter().enumerate() {
+            let (name, field) = fields[index];
+            let element =
+                field_to_element(field).ok_or_else(|| WinterCircomError::InvalidTrace {
+                    row: Some(step),
+                    comment: format!("unsupported value {} in column {}", field, name),
+                })?;
+            columns[column].push(element);
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Convert a Parquet value into a field element, if its type is supported.
+fn field_to_element(field: &Field) -> Option<BaseElement> {
+    match field {
+        Field::Bool(b) => Some(if *b {
+            BaseElement::ONE
+        } else {
+            BaseElement::ZERO
+        }),
+        Field::UByte(v) => Some(BaseElement::from(*v)),
+        Field::UShort(v) => Some(BaseElement::from(*v)),
+        Field::UInt(v) => Some(BaseElement::from(*v)),
+        Field::ULong(v) => Some(BaseElement::from(*v)),
+        Field::Byte(v) if *v >= 0 => Some(BaseElement::from(*v as u8)),
+        Field::Short(v) if *v >= 0 => Some(BaseElement::from(*v as u16)),
+        Field::Int(v) if *v >= 0 => Some(BaseElement::from(*v as u32)),
+        Field::Long(v) if *v >= 0 => Some(BaseElement::from(*v as u64)),
+        Field::Str(s) => U256::from_str_radix(s, 10).ok().map(BaseElement::new),
+        Field::Bytes(bytes) if bytes.len() == BaseElement::ELEMENT_BYTES => {
+            Some(BaseElement::from_le_bytes(bytes.data()))
+        }
+        _ => None,
+    }
+}
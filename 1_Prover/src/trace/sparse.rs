@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use winterfell::{
+    math::{fields::f256::BaseElement, FieldElement},
+    TraceInfo, TraceTable,
+};
+
+use super::build_trace;
+use crate::utils::WinterCircomError;
+
+/// Column of a [SparseTrace].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SparseColumn {
+    /// The same value at every step, including the padding steps.
+    Constant(BaseElement),
+
+    /// `default` at every step but the ones of `values`.
+    Sparse {
+        default: BaseElement,
+        values: BTreeMap<usize, BaseElement>,
+    },
+
+    /// A value for every step before padding.
+    Dense(Vec<BaseElement>),
+}
+
+impl SparseColumn {
+    /// Returns the value of the column at a step lower than the length of the
+    /// trace.
+    fn get(&self, step: usize) -> BaseElement {
+        match self {
+            SparseColumn::Constant(value) => *value,
+            SparseColumn::Sparse { default, values } => *values.get(&step).unwrap_or(default),
+            SparseColumn::Dense(values) => values[step],
+        }
+    }
+
+    /// Returns the value the column is padded with by
+    /// [ColumnDefault](PaddingStrategy::ColumnDefault).
+    fn default_value(&self) -> BaseElement {
+        match self {
+            SparseColumn::Constant(value) => *value,
+            SparseColumn::Sparse { default, .. } => *default,
+            SparseColumn::Dense(_) => BaseElement::ZERO,
+        }
+    }
+
+    /// Returns the number of field elements stored for the column.
+    fn stored_elements(&self) -> usize {
+        match self {
+            SparseColumn::Constant(_) => 1,
+            SparseColumn::Sparse { values, .. } => 1 + values.len(),
+            SparseColumn::Dense(values) => values.len(),
+        }
+    }
+}
+
+/// Extension of the steps of a [SparseTrace] to a power of two.
+///
+/// The strategy is part of the trace: two traces with the same steps but
+/// different strategies have different padding steps, and thus different
+/// proofs. It is serializable so that it can be recorded alongside the proofs
+/// of the trace (e.g. in the directory of a proof saved with `save_proof`), and
+/// the trace rebuilt identically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaddingStrategy {
+    /// No padding: the number of steps must already be a power of two greater
+    /// than or equal to [TraceInfo::MIN_TRACE_LENGTH].
+    None,
+
+    /// Repeat the value of the last step of each column.
+    RepeatLast,
+
+    /// Pad each column with its constant or default value, and dense columns
+    /// with zeros.
+    ColumnDefault,
+}
+
+/// Execution trace whose mostly-constant columns are stored sparsely.
+///
+/// Winterfell expects the main segment of a trace as a dense matrix, which is
+/// extended to the LDE domain by the prover. Constant and sparse columns are
+/// only expanded when the trace is converted into a [TraceTable] by
+/// [into_trace](SparseTrace::into_trace), right before proving, so that they
+/// take a single element (plus the non-default values) while the trace is
+/// built and held:
+///
+/// ```ignore
+/// let trace = SparseTrace::new(1000, PaddingStrategy::RepeatLast)
+///     .with_dense_column(values)
+///     .with_constant_column(BaseElement::ONE)
+///     .with_sparse_column(BaseElement::ZERO, [(0, BaseElement::ONE)])
+///     .into_trace()?;
+/// assert_eq!(trace.length(), 1024);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseTrace {
+    length: usize,
+    columns: Vec<SparseColumn>,
+    padding: PaddingStrategy,
+}
+
+impl SparseTrace {
+    /// Returns a trace of `length` steps without columns, padded with the given
+    /// strategy.
+    pub fn new(length: usize, padding: PaddingStrategy) -> Self {
+        Self {
+            length,
+            columns: Vec::new(),
+            padding,
+        }
+    }
+
+    pub fn with_column(mut self, column: SparseColumn) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn with_constant_column(self, value: BaseElement) -> Self {
+        self.with_column(SparseColumn::Constant(value))
+    }
+
+    pub fn with_sparse_column<I>(self, default: BaseElement, values: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, BaseElement)>,
+    {
+        self.with_column(SparseColumn::Sparse {
+            default,
+            values: values.into_iter().collect(),
+        })
+    }
+
+    pub fn with_dense_column(self, values: Vec<BaseElement>) -> Self {
+        self.with_column(SparseColumn::Dense(values))
+    }
+
+    /// Returns the number of columns of the trace.
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the number of steps of the trace, before padding.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns the number of steps of the trace, after padding.
+    pub fn padded_length(&self) -> usize {
+        match self.padding {
+            PaddingStrategy::None => self.length,
+            _ => self
+                .length
+                .next_power_of_two()
+                .max(TraceInfo::MIN_TRACE_LENGTH),
+        }
+    }
+
+    pub fn padding(&self) -> PaddingStrategy {
+        self.padding
+    }
+
+    /// Returns the value of a column at a step, padding steps included.
+    ///
+    /// Panics if the column or the step is out of bounds.
+    pub fn get(&self, column: usize, step: usize) -> BaseElement {
+        assert!(step < self.padded_length(), "step {} out of bounds", step);
+        let column = &self.columns[column];
+        if step < self.length {
+            return column.get(step);
+        }
+        match self.padding {
+            PaddingStrategy::RepeatLast => column.get(self.length - 1),
+            _ => column.default_value(),
+        }
+    }
+
+    /// Returns the number of field elements stored by the trace, to be
+    /// compared with the `width * padded_length` elements of its expansion.
+    pub fn stored_elements(&self) -> usize {
+        self.columns.iter().map(SparseColumn::stored_elements).sum()
+    }
+
+    /// Expand the columns of the trace into a [TraceTable].
+    ///
+    /// Returns an error if a dense column does not have a value for every step,
+    /// if a sparse column has a value after the last step, or if the padded
+    /// trace is not a valid Winterfell trace.
+    pub fn into_trace(self) -> Result<TraceTable<BaseElement>, WinterCircomError> {
+        for (i, column) in self.columns.iter().enumerate() {
+            match column {
+                SparseColumn::Dense(values) if values.len() != self.length => {
+                    return Err(WinterCircomError::InvalidTrace {
+                        row: None,
+                        comment: format!(
+                            "column {} has {} rows, expected {}",
+                            i,
+                            values.len(),
+                            self.length
+                        ),
+                    })
+                }
+                SparseColumn::Sparse { values, .. } => {
+                    if let Some((step, _)) = values.range(self.length..).next() {
+                        return Err(WinterCircomError::InvalidTrace {
+                            row: Some(*step),
+                            comment: format!("value of sparse column {} after the last step", i),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        if self.length == 0 {
+            return Err(WinterCircomError::InvalidTrace {
+                row: None,
+                comment: String::from("trace must have at least one step"),
+            });
+        }
+
+        let padded_length = self.padded_length();
+        let columns = (0..self.width())
+            .map(|column| {
+                (0..padded_length)
+                    .map(|step| self.get(column, step))
+                    .collect()
+            })
+            .collect();
+        build_trace(columns)
+    }
+}
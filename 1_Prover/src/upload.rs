@@ -0,0 +1,132 @@
+use std::{env, path::Path};
+
+use colored::Colorize;
+
+use crate::{
+    audit::hash_file,
+    utils::{command_execution, Executable, LoggingLevel, WinterCircomError},
+};
+
+/// Header of the uploads carrying the hexadecimal BLAKE3 hash of the artifact.
+const HASH_HEADER: &str = "X-Winter-Circom-Blake3";
+
+/// Uploader pushing the artifacts of a proof to a remote verifier as soon as
+/// they are written, instead of after the whole pipeline.
+///
+/// When configured, the [Prove](crate::Stage::Prove) stage PUTs `public.json`
+/// and `proof.json` to `<endpoint>/<circuit_name>/<file>` once the proof is
+/// generated, before the following stages run. Each upload carries the
+/// BLAKE3 hash of the file in the `X-Winter-Circom-Blake3` header (the hash
+/// recorded in the [audit log](crate::history)), so that the verifier can check
+/// the integrity of what it received, and is retried on transient errors
+/// (timeouts, 408, 429 and 5xx responses). A failed upload fails the stage,
+/// the artifacts being kept on disk. The uploads are sent with `curl`, which
+/// streams the files and must be installed.
+///
+/// ## Configuration
+///
+/// Uploads are disabled by default. They are enabled by setting the
+/// `WINTER_CIRCOM_ARTIFACT_ENDPOINT` environment variable to the URL of the
+/// verifier. `WINTER_CIRCOM_ARTIFACT_RETRIES` sets the number of retries, 3 by
+/// default.
+///
+/// This uploader is only available with the `service` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactUpload {
+    endpoint: String,
+    retries: u32,
+}
+
+impl ArtifactUpload {
+    pub const ENDPOINT_VARIABLE: &'static str = "WINTER_CIRCOM_ARTIFACT_ENDPOINT";
+    pub const RETRIES_VARIABLE: &'static str = "WINTER_CIRCOM_ARTIFACT_RETRIES";
+
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: String::from(endpoint.trim_end_matches('/')),
+            retries: 3,
+        }
+    }
+
+    /// Set the number of retries of a failed upload.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Returns the uploader configured by the environment variables, if any.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_ARTIFACT_RETRIES` is not a non-negative integer.
+    pub fn from_env() -> Result<Option<Self>, WinterCircomError> {
+        let upload = match env::var(ArtifactUpload::ENDPOINT_VARIABLE) {
+            Ok(endpoint) if !endpoint.trim().is_empty() => Self::new(endpoint.trim()),
+            _ => return Ok(None),
+        };
+        match env::var(ArtifactUpload::RETRIES_VARIABLE) {
+            Ok(value) => match value.trim().parse() {
+                Ok(retries) => Ok(Some(upload.with_retries(retries))),
+                Err(_) => Err(WinterCircomError::InvalidEnvVariable {
+                    variable: String::from(ArtifactUpload::RETRIES_VARIABLE),
+                    comment: format!("expected a number of retries, got {:?}", value),
+                }),
+            },
+            Err(_) => Ok(Some(upload)),
+        }
+    }
+
+    /// Returns the URL an artifact of a circuit is uploaded to.
+    pub fn url(&self, circuit_name: &str, file_name: &str) -> String {
+        format!("{}/{}/{}", self.endpoint, circuit_name, file_name)
+    }
+
+    /// Upload an artifact of a circuit, giving up on each attempt after 30
+    /// seconds.
+    pub fn upload(
+        &self,
+        circuit_name: &str,
+        path: &Path,
+        logging_level: &LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        let hash = hash_file(path).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("hashing {}", path.display())),
+        })?;
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let url = self.url(circuit_name, &file_name);
+        if logging_level.print_big_steps() {
+            println!(
+                "{}",
+                format!("Uploading {} to {}...", file_name, url).yellow()
+            );
+        }
+
+        let hash_header = format!("{}: {}", HASH_HEADER, hash);
+        let file = path.to_string_lossy();
+        let retries = self.retries.to_string();
+        command_execution(
+            Executable::Curl,
+            &[
+                "-sfS",
+                "-m",
+                "30",
+                "--retry",
+                &retries,
+                "-X",
+                "PUT",
+                "-H",
+                "Content-Type: application/json",
+                "-H",
+                &hash_header,
+                "-T",
+                &file,
+                &url,
+            ],
+            None,
+            logging_level,
+        )
+    }
+}
@@ -1,13 +1,25 @@
 use std::{
     fmt::{Debug, Display},
-    io,
+    io::{self, Write},
     path::{Path, PathBuf},
-    process::{Command, Stdio},
+    process::{Child, Command, ExitStatus, Stdio},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+    thread,
+    time::{Duration, Instant},
 };
 
-use colored::Colorize;
+#[cfg(feature = "codegen")]
+pub(crate) use colored::Colorize;
+use serde::Serialize;
+use serde_json::{json, Value};
 use winterfell::{ProverError, VerifierError};
 
+use crate::{
+    executor::{execute, Invocation},
+    interrupt, Watchdog,
+};
+
 // ERRORS
 // ===========================================================================
 
@@ -27,6 +39,36 @@ pub enum WinterCircomError {
         comment: Option<String>,
     },
 
+    /// This error is triggered when a circuit name cannot safely be used in
+    /// paths and commands (see
+    /// [validate_circuit_name](crate::validate_circuit_name)).
+    InvalidCircuitName {
+        circuit_name: String,
+        reason: String,
+    },
+
+    /// This error is triggered when the ID of a saved proof cannot safely be
+    /// used in paths (see [save_proof](crate::save_proof)).
+    InvalidProofId { proof_id: String },
+
+    /// This error is triggered when the AIR template of a circuit is missing.
+    /// It lists the declarations the template must match, and whether a stub
+    /// was written (see
+    /// [scaffold_on_missing](crate::WinterCircomProofOptions::scaffold_on_missing)).
+    MissingAirTemplate {
+        template: String,
+        transition_degrees: Vec<usize>,
+        num_assertions: usize,
+        scaffolded: bool,
+    },
+
+    /// This error is triggered when the lint pass over an AIR template found
+    /// errors (see [lint_air_template](crate::lint_air_template)).
+    AirTemplateLint {
+        template: String,
+        errors: Vec<String>,
+    },
+
     /// This error type is triggered when an underlying command called by a
     /// function of this crate failed (returned a non-zero exit code).
     ExitCodeError {
@@ -40,6 +82,174 @@ pub enum WinterCircomError {
 
     /// This error is triggered when the Winterfell proof generation failed.
     ProverError(ProverError),
+
+    /// This error is triggered when a mutated proof was accepted during a
+    /// security self-test.
+    SecurityTestFailed { mutation: String },
+
+    /// This error is triggered when an execution trace loaded from an external
+    /// source is invalid.
+    InvalidTrace {
+        row: Option<usize>,
+        comment: String,
+    },
+
+    /// This error is triggered when a proof does not match the parameters
+    /// recorded in the manifest of the compiled circuit.
+    ManifestMismatch {
+        field: String,
+        compiled: String,
+        proved: String,
+    },
+
+    /// This error is triggered when the sources of a circuit changed since it
+    /// was generated (see
+    /// [sources_changed](crate::CircuitManifest::sources_changed)).
+    OutdatedCircuit { circuit_name: String },
+
+    /// This error is triggered when writing a scratch artifact would exceed
+    /// the maximum size of the scratch space (see [ScratchSpace](crate::ScratchSpace)).
+    ScratchSpaceExceeded { required: u64, available: u64 },
+
+    /// This error is triggered when the degree of a transition constraint
+    /// compiled in a circuit differs from the declared degree (see
+    /// [transition_degrees](crate::transition_degrees)).
+    TransitionDegreeMismatch {
+        circuit_name: String,
+        constraint: usize,
+        declared: usize,
+        compiled: usize,
+    },
+
+    /// This error is triggered when the artifacts of a tenant exceed its
+    /// storage quota (see [Tenant](crate::Tenant)).
+    TenantQuotaExceeded {
+        tenant: String,
+        used: u64,
+        quota: u64,
+    },
+
+    /// This error is triggered when the estimated memory of a proof exceeds the
+    /// [MemoryLimit](crate::MemoryLimit).
+    MemoryLimitExceeded { required: u64, limit: u64 },
+
+    /// This error is triggered when an underlying command showed no activity
+    /// for the inactivity period of the [Watchdog] and was killed.
+    ProcessWedged {
+        executable: String,
+        inactivity: Duration,
+    },
+
+    /// This error is triggered when SIGINT or SIGTERM was received while an
+    /// underlying command was running, the command being killed.
+    Interrupted { executable: String },
+
+    /// This error is triggered when the [CancellationToken](crate::CancellationToken)
+    /// of the current thread was cancelled while the given step was running.
+    Cancelled { step: String },
+
+    /// This error is triggered when the artifacts of a circuit may be
+    /// partially written, because the step producing them was interrupted.
+    IncompleteArtifacts {
+        circuit_name: String,
+        step: String,
+        artifacts: Vec<String>,
+    },
+
+    /// This error is triggered when a file written by this crate, such as the
+    /// manifest of a circuit, cannot be parsed, e.g. because it was truncated
+    /// by a crash or edited by hand.
+    CorruptArtifact { file: String, comment: String },
+
+    /// This error is triggered when the external data bound to a proof is
+    /// larger than the maximum size supported by the circuit (see
+    /// [commitment](crate::commitment)).
+    CommitmentDataTooLarge { size: usize, max_bytes: usize },
+
+    /// This error is triggered when a public input does not fit in its bit
+    /// width (see [PublicInputLayout](crate::PublicInputLayout)).
+    PublicInputTooLarge { index: usize, bits: usize },
+
+    /// This error is triggered when the LDE blowup factor of the proof options
+    /// is too small for the degrees of the transition constraints (see
+    /// [min_lde_blowup_factor](crate::WinterCircomProofOptions::min_lde_blowup_factor)).
+    BlowupFactorTooSmall {
+        blowup_factor: usize,
+        min_blowup_factor: usize,
+    },
+
+    /// This error is triggered when the Groth16 public signals of a proof
+    /// cannot be decoded (see
+    /// [decode_public_signals](crate::decode_public_signals)).
+    InvalidPublicSignals { comment: String },
+
+    /// This error is triggered when an encoded field element cannot be
+    /// decoded (see [ElementEncoding](crate::ElementEncoding)).
+    InvalidFieldElement { comment: String },
+
+    /// This error is triggered when an artifact does not match its JSON schema
+    /// (see [schema](crate::schema)).
+    SchemaValidation { file: String, errors: Vec<String> },
+
+    /// This error is triggered when a proof bundle is not canonically encoded
+    /// or does not match its content identifier (see [ipfs](crate::ipfs)).
+    InvalidBundle { comment: String },
+
+    /// This error is triggered when a Winterfell proof cannot be converted into
+    /// the inputs of its Circom verifier: a serialized proof given to
+    /// [circom_prove_from_proof](crate::circom_prove_from_proof) cannot be
+    /// deserialized, the proof was not generated with the Poseidon hash
+    /// function, or it does not match the parameters of its AIR.
+    InvalidStarkProof { comment: String },
+
+    /// This error is triggered when the `proof.json` file of a SNARK proof
+    /// cannot be formatted as the calldata of its Solidity or Cairo verifier
+    /// (see [Groth16Calldata](crate::Groth16Calldata) and
+    /// [starknet_calldata](crate::starknet_calldata)).
+    InvalidSnarkProof { comment: String },
+
+    /// This error is triggered when a configuration file, such as the
+    /// [ProjectManifest](crate::ProjectManifest) of a project, is invalid.
+    InvalidConfig { file: String, comment: String },
+
+    /// This error is triggered when an environment variable configuring this
+    /// crate, such as the number of threads of the prover (see
+    /// [Concurrency](crate::Concurrency)), has an invalid value.
+    InvalidEnvVariable { variable: String, comment: String },
+
+    /// This error is triggered when no proof options explored by a
+    /// [ProofOptionsOptimizer](crate::ProofOptionsOptimizer) reach the target
+    /// security.
+    UnreachableSecurity { target_bits: u32 },
+
+    /// This error is triggered when an operation is not supported by the
+    /// [SnarkScheme](crate::SnarkScheme) of a circuit, such as the rotation of
+    /// the keys of a PLONK circuit.
+    UnsupportedScheme { scheme: String, comment: String },
+
+    /// This error is triggered when the column map of a circuit is not a
+    /// permutation of the columns of the trace (see
+    /// [remap_columns](crate::WinterCircomProofOptions::remap_columns)).
+    InvalidColumnMap { comment: String },
+
+    /// This error is triggered when the assertions of an AIR assert the same
+    /// steps of a column twice (see
+    /// [canonical_assertions](crate::canonical_assertions)).
+    InvalidAssertions { comment: String },
+
+    /// This error is triggered when the native Groth16 backend (see
+    /// [native](crate::native)) fails to load a circuit, to generate or read
+    /// its keys, or to generate or verify a proof.
+    NativeProverError { comment: String },
+
+    /// This error is triggered when a Solidity verifier cannot be compiled or
+    /// deployed, or when a proof cannot be submitted to it (see
+    /// [evm](crate::evm)).
+    EvmError { comment: String },
+
+    /// This error is triggered when a [Beacon](crate::Beacon) cannot be
+    /// fetched, or its value is not a hexadecimal string.
+    InvalidBeacon { comment: String },
 }
 
 impl Display for WinterCircomError {
@@ -59,6 +269,40 @@ impl Display for WinterCircomError {
                     format!("File not found: {}.", file)
                 }
             }
+            WinterCircomError::InvalidCircuitName {
+                circuit_name,
+                reason,
+            } => {
+                format!("Invalid circuit name {:?}: {}.", circuit_name, reason)
+            }
+            WinterCircomError::InvalidProofId { proof_id } => {
+                format!(
+                    "Invalid proof ID {:?}: IDs must not be empty nor start with '.', and may only contain ASCII alphanumerics, '-', '_' and '.'.",
+                    proof_id
+                )
+            }
+            WinterCircomError::MissingAirTemplate {
+                template,
+                transition_degrees,
+                num_assertions,
+                scaffolded,
+            } => {
+                let mut message = format!(
+                    "AIR template not found: {} must define AIRTransitions({}) with \
+                    transition degrees {:?}, and AIRAssertions with {} assertions.",
+                    template,
+                    transition_degrees.len(),
+                    transition_degrees,
+                    num_assertions
+                );
+                if *scaffolded {
+                    message.push_str(" A stub was written, define the assertions in it.");
+                }
+                message
+            }
+            WinterCircomError::AirTemplateLint { template, errors } => {
+                format!("Invalid AIR template {}: {}.", template, errors.join(", "))
+            }
             WinterCircomError::ExitCodeError { executable, code } => {
                 format!("Executable {} exited with code {}.", executable, code)
             }
@@ -72,6 +316,179 @@ impl Display for WinterCircomError {
             WinterCircomError::ProverError(prover_error) => {
                 format!("Prover error: {}.", prover_error)
             }
+            WinterCircomError::SecurityTestFailed { mutation } => {
+                format!(
+                    "Security self-test failed: mutated proof accepted ({}).",
+                    mutation
+                )
+            }
+            WinterCircomError::InvalidTrace { row, comment } => {
+                if let Some(row) = row {
+                    format!("Invalid trace: {} (row {}).", comment, row)
+                } else {
+                    format!("Invalid trace: {}.", comment)
+                }
+            }
+            WinterCircomError::OutdatedCircuit { circuit_name } => {
+                format!(
+                    "Circuit {} was generated from outdated sources, regenerate it.",
+                    circuit_name
+                )
+            }
+            WinterCircomError::TransitionDegreeMismatch {
+                circuit_name,
+                constraint,
+                declared,
+                compiled,
+            } => {
+                format!(
+                    "Transition constraint {} of circuit {} has degree {} in the compiled circuit, \
+                    but degree {} is declared.",
+                    constraint, circuit_name, compiled, declared
+                )
+            }
+            WinterCircomError::ManifestMismatch {
+                field,
+                compiled,
+                proved,
+            } => {
+                format!(
+                    "Manifest mismatch: {} is {} in the compiled circuit but {} in the proof.",
+                    field, compiled, proved
+                )
+            }
+            WinterCircomError::ScratchSpaceExceeded {
+                required,
+                available,
+            } => {
+                format!(
+                    "Scratch space exceeded: {} bytes required, {} bytes available.",
+                    required, available
+                )
+            }
+            WinterCircomError::TenantQuotaExceeded {
+                tenant,
+                used,
+                quota,
+            } => {
+                format!(
+                    "Tenant quota exceeded: {} uses {} bytes, quota is {} bytes.",
+                    tenant, used, quota
+                )
+            }
+            WinterCircomError::MemoryLimitExceeded { required, limit } => {
+                format!(
+                    "Memory limit exceeded: {} required but the limit is {}, increase the memory limit or reduce trace_length.",
+                    format_size(*required),
+                    format_size(*limit)
+                )
+            }
+            WinterCircomError::ProcessWedged {
+                executable,
+                inactivity,
+            } => {
+                format!(
+                    "Process wedged: {} showed no activity for {:?} and was killed.",
+                    executable, inactivity
+                )
+            }
+            WinterCircomError::Interrupted { executable } => {
+                format!(
+                    "Interrupted: {} was killed, the artifacts of the current step are marked incomplete.",
+                    executable
+                )
+            }
+            WinterCircomError::IncompleteArtifacts {
+                circuit_name,
+                step,
+                artifacts,
+            } => {
+                format!(
+                    "Incomplete artifacts: the {} step of circuit {} was interrupted, so {} may be partially written. Run it again, or regenerate the circuit with circom_create.",
+                    step,
+                    circuit_name,
+                    artifacts.join(", ")
+                )
+            }
+            WinterCircomError::CorruptArtifact { file, comment } => {
+                format!("Corrupt artifact {}: {}.", file, comment)
+            }
+            WinterCircomError::CommitmentDataTooLarge { size, max_bytes } => {
+                format!(
+                    "Commitment data too large: {} bytes, at most {} bytes can be bound.",
+                    size, max_bytes
+                )
+            }
+            WinterCircomError::PublicInputTooLarge { index, bits } => {
+                format!(
+                    "Public input too large: public input {} does not fit in {} bits.",
+                    index, bits
+                )
+            }
+            WinterCircomError::BlowupFactorTooSmall {
+                blowup_factor,
+                min_blowup_factor,
+            } => {
+                format!(
+                    "LDE blowup factor too small: {} given, {} required.",
+                    blowup_factor, min_blowup_factor
+                )
+            }
+            WinterCircomError::InvalidPublicSignals { comment } => {
+                format!("Invalid public signals: {}.", comment)
+            }
+            WinterCircomError::InvalidFieldElement { comment } => {
+                format!("Invalid field element: {}.", comment)
+            }
+            WinterCircomError::SchemaValidation { file, errors } => {
+                format!(
+                    "Schema validation failed: {} ({}).",
+                    file,
+                    errors.join(", ")
+                )
+            }
+            WinterCircomError::InvalidBundle { comment } => {
+                format!("Invalid proof bundle: {}.", comment)
+            }
+            WinterCircomError::InvalidStarkProof { comment } => {
+                format!("Invalid STARK proof: {}.", comment)
+            }
+            WinterCircomError::InvalidSnarkProof { comment } => {
+                format!("Invalid SNARK proof: {}.", comment)
+            }
+            WinterCircomError::InvalidConfig { file, comment } => {
+                format!("Invalid configuration file {}: {}.", file, comment)
+            }
+            WinterCircomError::InvalidEnvVariable { variable, comment } => {
+                format!("Invalid environment variable {}: {}.", variable, comment)
+            }
+            WinterCircomError::UnreachableSecurity { target_bits } => {
+                format!(
+                    "Unreachable security: no proof options provide {} bits of conjectured security, increase the maximum blowup or grinding factor.",
+                    target_bits
+                )
+            }
+            WinterCircomError::UnsupportedScheme { scheme, comment } => {
+                format!("Unsupported by {}: {}.", scheme, comment)
+            }
+            WinterCircomError::InvalidColumnMap { comment } => {
+                format!("Invalid column map: {}.", comment)
+            }
+            WinterCircomError::InvalidAssertions { comment } => {
+                format!("Invalid assertions: {}.", comment)
+            }
+            WinterCircomError::NativeProverError { comment } => {
+                format!("Native Groth16 backend: {}.", comment)
+            }
+            WinterCircomError::EvmError { comment } => {
+                format!("EVM: {}.", comment)
+            }
+            WinterCircomError::InvalidBeacon { comment } => {
+                format!("Invalid beacon: {}.", comment)
+            }
+            WinterCircomError::Cancelled { step } => {
+                format!("Cancelled: the job was cancelled while {}.", step)
+            }
         };
 
         write!(f, "{}", error_string.yellow())
@@ -84,13 +501,177 @@ impl Debug for WinterCircomError {
     }
 }
 
+/// Class of a [WinterCircomError], for programs that report errors to
+/// orchestration systems.
+///
+/// Each class has a stable [exit code](ErrorClass::exit_code) and
+/// [name](ErrorClass::name), which are not changed when variants are added to
+/// [WinterCircomError].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Reading or writing a file failed.
+    Io,
+
+    /// A required artifact is missing (e.g. the circuit was not compiled).
+    MissingArtifact,
+
+    /// An underlying command (Circom, snarkjs, ...) or EVM node failed, or a
+    /// command was wedged.
+    Subprocess,
+
+    /// The Winterfell proof, or a native Groth16 proof, could not be generated
+    /// or verified.
+    Proving,
+
+    /// The inputs of the invocation (trace, public inputs, data, options) are
+    /// invalid.
+    InvalidInput,
+
+    /// The inputs do not match the compiled circuit, which must be
+    /// regenerated.
+    CircuitMismatch,
+
+    /// A resource limit (memory, scratch space or tenant quota) would be
+    /// exceeded.
+    ResourceExhausted,
+
+    /// The invocation was interrupted by SIGINT or SIGTERM, or cancelled.
+    Interrupted,
+}
+
+impl ErrorClass {
+    /// Returns the exit code of the class, between 10 and 17. 0 is left for
+    /// success and 1 and 2 for panics and usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorClass::Io => 10,
+            ErrorClass::MissingArtifact => 11,
+            ErrorClass::Subprocess => 12,
+            ErrorClass::Proving => 13,
+            ErrorClass::InvalidInput => 14,
+            ErrorClass::CircuitMismatch => 15,
+            ErrorClass::ResourceExhausted => 16,
+            ErrorClass::Interrupted => 17,
+        }
+    }
+
+    /// Returns the snake case name of the class.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorClass::Io => "io",
+            ErrorClass::MissingArtifact => "missing_artifact",
+            ErrorClass::Subprocess => "subprocess",
+            ErrorClass::Proving => "proving",
+            ErrorClass::InvalidInput => "invalid_input",
+            ErrorClass::CircuitMismatch => "circuit_mismatch",
+            ErrorClass::ResourceExhausted => "resource_exhausted",
+            ErrorClass::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl WinterCircomError {
+    /// Returns the class of the error.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            WinterCircomError::IoError { .. } => ErrorClass::Io,
+            WinterCircomError::FileNotFound { .. }
+            | WinterCircomError::MissingAirTemplate { .. }
+            | WinterCircomError::IncompleteArtifacts { .. }
+            | WinterCircomError::CorruptArtifact { .. } => ErrorClass::MissingArtifact,
+            WinterCircomError::ExitCodeError { .. }
+            | WinterCircomError::ProcessWedged { .. }
+            | WinterCircomError::EvmError { .. } => ErrorClass::Subprocess,
+            WinterCircomError::InvalidProof(_)
+            | WinterCircomError::ProverError(_)
+            | WinterCircomError::SecurityTestFailed { .. }
+            | WinterCircomError::NativeProverError { .. } => ErrorClass::Proving,
+            WinterCircomError::InvalidTrace { .. }
+            | WinterCircomError::InvalidCircuitName { .. }
+            | WinterCircomError::InvalidProofId { .. }
+            | WinterCircomError::AirTemplateLint { .. }
+            | WinterCircomError::CommitmentDataTooLarge { .. }
+            | WinterCircomError::PublicInputTooLarge { .. }
+            | WinterCircomError::BlowupFactorTooSmall { .. }
+            | WinterCircomError::InvalidPublicSignals { .. }
+            | WinterCircomError::InvalidFieldElement { .. }
+            | WinterCircomError::SchemaValidation { .. }
+            | WinterCircomError::InvalidBundle { .. }
+            | WinterCircomError::InvalidStarkProof { .. }
+            | WinterCircomError::InvalidSnarkProof { .. }
+            | WinterCircomError::InvalidConfig { .. }
+            | WinterCircomError::InvalidEnvVariable { .. }
+            | WinterCircomError::UnreachableSecurity { .. }
+            | WinterCircomError::UnsupportedScheme { .. }
+            | WinterCircomError::InvalidColumnMap { .. }
+            | WinterCircomError::InvalidAssertions { .. }
+            | WinterCircomError::InvalidBeacon { .. } => ErrorClass::InvalidInput,
+            WinterCircomError::ManifestMismatch { .. }
+            | WinterCircomError::OutdatedCircuit { .. }
+            | WinterCircomError::TransitionDegreeMismatch { .. } => ErrorClass::CircuitMismatch,
+            WinterCircomError::ScratchSpaceExceeded { .. }
+            | WinterCircomError::TenantQuotaExceeded { .. }
+            | WinterCircomError::MemoryLimitExceeded { .. } => ErrorClass::ResourceExhausted,
+            WinterCircomError::Interrupted { .. } | WinterCircomError::Cancelled { .. } => {
+                ErrorClass::Interrupted
+            }
+        }
+    }
+
+    /// Returns the exit code of the class of the error.
+    pub fn exit_code(&self) -> i32 {
+        self.class().exit_code()
+    }
+
+    /// Returns the machine-parseable JSON result of a failed invocation:
+    ///
+    /// ```json
+    /// { "status": "error", "error_class": "missing_artifact", "exit_code": 11, "message": "..." }
+    /// ```
+    ///
+    /// The message is the [Display] of the error, without terminal colors.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "status": "error",
+            "error_class": self.class().name(),
+            "exit_code": self.exit_code(),
+            "message": strip_colors(&self.to_string()),
+        })
+    }
+}
+
+/// Print the JSON result of an invocation to stdout, and returns its exit code.
+///
+/// A successful result is printed as `{ "status": "ok", "result": ... }` with
+/// exit code 0, and an error as its [to_json](WinterCircomError::to_json)
+/// representation. The output is a single line, so that it can be captured by
+/// orchestration systems alongside the logs of the invocation, which are
+/// printed before it:
+///
+/// ```ignore
+/// std::process::exit(print_json_result(circom_verify("sum", LoggingLevel::Quiet)));
+/// ```
+pub fn print_json_result<T: Serialize>(result: Result<T, WinterCircomError>) -> i32 {
+    let (json, exit_code) = match result {
+        Ok(result) => (json!({ "status": "ok", "result": result }), 0),
+        Err(e) => (e.to_json(), e.exit_code()),
+    };
+    println!("{}", json);
+    exit_code
+}
+
 // COMMAND EXECUTION HELPERS
 // ===========================================================================
 
+#[derive(Clone, Debug)]
 pub(crate) enum Executable {
     Circom,
     SnarkJS,
+    Rapidsnark,
     Make,
+    Node,
+    Curl,
+    Garaga,
     Custom {
         path: String,
         verbose_argument: Option<String>,
@@ -102,16 +683,24 @@ impl Executable {
         Ok(match self {
             Self::Circom => canonicalize("iden3/circom/target/release/circom")?,
             Self::SnarkJS => canonicalize("iden3/snarkjs/build/cli.cjs")?,
+            Self::Rapidsnark => canonicalize("iden3/rapidsnark/package/bin/prover")?,
             Self::Make => "make".into(),
+            Self::Node => "node".into(),
+            Self::Curl => "curl".into(),
+            Self::Garaga => "garaga".into(),
             Self::Custom { path, .. } => canonicalize(path)?,
         })
     }
 
-    fn executable_name(&self) -> String {
+    pub(crate) fn executable_name(&self) -> String {
         match self {
             Self::Circom => String::from("circom"),
             Self::SnarkJS => String::from("snarkjs"),
+            Self::Rapidsnark => String::from("rapidsnark"),
             Self::Make => String::from("make"),
+            Self::Node => String::from("node"),
+            Self::Curl => String::from("curl"),
+            Self::Garaga => String::from("garaga"),
             Self::Custom { path, .. } => Path::new(path)
                 .file_name()
                 .unwrap()
@@ -134,24 +723,191 @@ pub(crate) fn canonicalize<P: AsRef<Path>>(path: P) -> Result<PathBuf, WinterCir
 }
 
 /// Execute a system command, returning an error on failure.
+///
+/// The command is run by the [Executor](crate::Executor) of the current
+/// thread, by default a [ProcessExecutor](crate::ProcessExecutor) which kills it
+/// if it becomes inactive while a [Watchdog] is configured.
 pub(crate) fn command_execution(
     executable: Executable,
     args: &[&str],
     current_dir: Option<&str>,
     logging_level: &LoggingLevel,
 ) -> Result<(), WinterCircomError> {
+    execute(invocation(executable, args, current_dir, logging_level)).map(|_| ())
+}
+
+/// Path under which a command can read its standard input as a file, on the
+/// platforms that support it.
+///
+/// Passing this path instead of a file path to subcommands that read their
+/// input with a single read (e.g. the witness generator of a compiled circuit)
+/// avoids writing temporary files.
+#[cfg(unix)]
+pub(crate) const STDIN_PATH: Option<&str> = Some("/dev/stdin");
+#[cfg(not(unix))]
+pub(crate) const STDIN_PATH: Option<&str> = None;
+
+/// Execute a system command with the given data piped to its standard input,
+/// returning an error on failure.
+pub(crate) fn command_execution_with_stdin(
+    executable: Executable,
+    args: &[&str],
+    stdin: &[u8],
+    current_dir: Option<&str>,
+    logging_level: &LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    let invocation = Invocation {
+        stdin: Some(stdin.to_vec()),
+        ..invocation(executable, args, current_dir, logging_level)
+    };
+    execute(invocation).map(|_| ())
+}
+
+/// Execute a system command and return its standard output, returning an error
+/// on failure.
+pub(crate) fn command_output(
+    executable: Executable,
+    args: &[&str],
+) -> Result<String, WinterCircomError> {
+    let invocation = Invocation {
+        capture_output: true,
+        ..invocation(executable, args, None, &LoggingLevel::Quiet)
+    };
+    execute(invocation)
+}
+
+/// Execute a system command asynchronously, with the given data piped to its
+/// standard input if any, returning an error on failure.
+///
+/// The command is spawned with `tokio::process`, and killed if the returned
+/// future is dropped before completion, e.g. when the task running it is
+/// aborted. Unlike [command_execution], it is neither run by the
+/// [Executor](crate::Executor) of the current thread nor supervised by the
+/// [Watchdog].
+#[cfg(feature = "service")]
+pub(crate) async fn command_execution_async(
+    executable: Executable,
+    args: Vec<String>,
+    stdin: Option<Vec<u8>>,
+    current_dir: PathBuf,
+    logging_level: LoggingLevel,
+) -> Result<(), WinterCircomError> {
+    use tokio::io::AsyncWriteExt;
+
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let invocation = invocation(
+        executable.clone(),
+        &args,
+        current_dir.to_str(),
+        &logging_level,
+    );
+    let io_error = |e: io::Error| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!(
+            "during execution of: {}",
+            executable.executable_name()
+        )),
+    };
+
+    let mut command = tokio::process::Command::from(build_command(&invocation)?);
+    if stdin.is_some() {
+        command.stdin(Stdio::piped());
+    }
+    let mut child = command.kill_on_drop(true).spawn().map_err(io_error)?;
+    if let Some(stdin) = stdin {
+        // the pipe is closed when dropped, signaling the end of the input
+        let mut pipe = child.stdin.take().expect("stdin should be piped");
+        if let Err(e) = pipe.write_all(&stdin).await {
+            let _ = child.kill().await;
+            return Err(io_error(e));
+        }
+    }
+
+    check_status(&executable, child.wait().await)
+}
+
+/// Run a command as a child process (see
+/// [ProcessExecutor](crate::ProcessExecutor)).
+pub(crate) fn run_process(invocation: &Invocation) -> Result<String, WinterCircomError> {
+    let executable = &invocation.executable;
+    let io_error = |e: io::Error| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!(
+            "during execution of: {}",
+            executable.executable_name()
+        )),
+    };
+
+    if invocation.capture_output {
+        let mut command = Command::new(executable.executable_path()?);
+        command.args(&invocation.args).stderr(Stdio::null());
+        ColorChoice::Never.configure_command(&mut command);
+
+        let output = command.output().map_err(io_error)?;
+        check_status(executable, Ok(output.status))?;
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+
+    let mut command = build_command(invocation)?;
+    if invocation.stdin.is_some() {
+        command.stdin(Stdio::piped());
+    }
+    // read once, so that the child is supervised the way it was spawned for
+    let watchdog = Watchdog::from_env()?;
+    let mut child = spawn_command(executable, &mut command, watchdog)?;
+    // the input is written from another thread, so that the watchdog and the
+    // interrupt handler supervise the child while it reads it, and the output
+    // of the child is drained while the input is written
+    let writer = invocation.stdin.clone().map(|stdin| {
+        let mut pipe = child.stdin.take().expect("stdin should be piped");
+        // the pipe is closed when dropped, signaling the end of the input
+        thread::spawn(move || pipe.write_all(&stdin))
+    });
+
+    let result = wait_command(executable, child, watchdog, invocation.print_output);
+    // the writer returns once the child exited, at the latest, as the pipe
+    // is then broken; a failed or killed child is reported first
+    let written = match writer {
+        Some(writer) => writer.join().unwrap_or(Ok(())),
+        None => Ok(()),
+    };
+    result?;
+    written.map_err(io_error)?;
+    Ok(String::new())
+}
+
+fn invocation(
+    executable: Executable,
+    args: &[&str],
+    current_dir: Option<&str>,
+    logging_level: &LoggingLevel,
+) -> Invocation {
+    Invocation {
+        program: executable.executable_name(),
+        args: args.iter().map(|arg| String::from(*arg)).collect(),
+        current_dir: current_dir.map(PathBuf::from),
+        stdin: None,
+        executable,
+        verbose: logging_level.verbose_commands(),
+        print_output: logging_level.print_command_output(),
+        capture_output: false,
+    }
+}
+
+fn build_command(invocation: &Invocation) -> Result<Command, WinterCircomError> {
+    let executable = &invocation.executable;
     let mut command = Command::new(executable.executable_path()?);
 
     // set arguments and current directory
-    for arg in args {
+    for arg in &invocation.args {
         command.arg(arg);
     }
-    if let Some(dir) = current_dir {
+    if let Some(dir) = &invocation.current_dir {
         command.current_dir(dir);
     }
 
     // set verbose flag if logging level is very verbose
-    if logging_level.verbose_commands() {
+    if invocation.verbose {
         match executable {
             Executable::Circom => {
                 command.arg("--verbose");
@@ -160,8 +916,7 @@ pub(crate) fn command_execution(
                 command.arg("--verbose");
             }
             Executable::Custom {
-                ref verbose_argument,
-                ..
+                verbose_argument, ..
             } => {
                 if let Some(verbose_argument) = verbose_argument {
                     command.arg(verbose_argument);
@@ -172,11 +927,75 @@ pub(crate) fn command_execution(
     };
 
     // do not print command stdout if logging level is below verbose
-    if !logging_level.print_command_output() {
+    if !invocation.print_output {
         command.stdout(Stdio::null());
     }
 
-    match command.status() {
+    // pass the color choice to the command
+    ColorChoice::current().configure_command(&mut command);
+
+    Ok(command)
+}
+
+/// Spawn a command, piping its standard output to the [Watchdog] if there is
+/// one.
+fn spawn_command(
+    executable: &Executable,
+    command: &mut Command,
+    watchdog: Option<Watchdog>,
+) -> Result<Child, WinterCircomError> {
+    if watchdog.is_some() {
+        command.stdout(Stdio::piped());
+    }
+
+    command.spawn().map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!(
+            "during execution of: {}",
+            executable.executable_name()
+        )),
+    })
+}
+
+/// Wait for a spawned command to exit, under the supervision of the
+/// [Watchdog] if there is one.
+fn wait_command(
+    executable: &Executable,
+    mut child: Child,
+    watchdog: Option<Watchdog>,
+    print_output: bool,
+) -> Result<(), WinterCircomError> {
+    let watchdog = match watchdog {
+        Some(watchdog) => watchdog,
+        None => {
+            return match interrupt::wait(&mut child) {
+                Ok(Some(status)) => check_status(executable, Ok(status)),
+                Ok(None) => Err(WinterCircomError::Interrupted {
+                    executable: executable.executable_name(),
+                }),
+                Err(e) => check_status(executable, Err(e)),
+            }
+        }
+    };
+
+    match watchdog.wait(&mut child, print_output) {
+        Ok(Some(status)) => check_status(executable, Ok(status)),
+        Ok(None) if interrupt::is_interrupted() => Err(WinterCircomError::Interrupted {
+            executable: executable.executable_name(),
+        }),
+        Ok(None) => Err(WinterCircomError::ProcessWedged {
+            executable: executable.executable_name(),
+            inactivity: watchdog.inactivity_timeout(),
+        }),
+        Err(e) => check_status(executable, Err(e)),
+    }
+}
+
+fn check_status(
+    executable: &Executable,
+    status: io::Result<ExitStatus>,
+) -> Result<(), WinterCircomError> {
+    match status {
         Ok(status) => {
             if !status.success() {
                 return Err(WinterCircomError::ExitCodeError {
@@ -214,6 +1033,37 @@ pub(crate) fn check_file(path: String, comment: Option<&str>) -> Result<(), Wint
     Ok(())
 }
 
+/// Returns the total size of the files of a directory, recursively.
+pub(crate) fn directory_size(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| match entry.metadata() {
+                    Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Remove the terminal color codes of an error message.
+pub(crate) fn strip_colors(message: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip the escape sequence, up to its final 'm'
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
 pub(crate) fn delete_file(path: String) {
     let _ = std::fs::remove_file(&path);
 }
@@ -226,6 +1076,7 @@ pub(crate) fn delete_directory(path: String) {
 // ===========================================================================
 
 /// Logging level selector for functions of this crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LoggingLevel {
     /// Nothing is printed to stdout (errors are still printed to stderr)
     Quiet,
@@ -233,6 +1084,10 @@ pub enum LoggingLevel {
     /// Minimal logging (only major steps are logged to stdout)
     Default,
 
+    /// Major steps are logged along with their elapsed time and the size of the
+    /// artifacts they generated
+    Timings,
+
     /// Output of underlying executables is printed as well
     Verbose,
 
@@ -247,6 +1102,7 @@ impl LoggingLevel {
     /// This is used to trigger the printing of big step announcements in the functions
     /// of this crate.
     pub(crate) fn print_big_steps(&self) -> bool {
+        configure_colors();
         match self {
             Self::Quiet => false,
             _ => true,
@@ -259,6 +1115,22 @@ impl LoggingLevel {
     /// This is used to trigger the printing of underlying commands stdout in the
     /// functions of this crate.
     pub(crate) fn print_command_output(&self) -> bool {
+        configure_colors();
+        match self {
+            Self::Quiet => false,
+            Self::Default => false,
+            Self::Timings => false,
+            _ => true,
+        }
+    }
+
+    /// Returns whether the logging level is set to [Timings](LoggingLevel::Timings)
+    /// or above.
+    ///
+    /// This is used to trigger the printing of the elapsed time and artifact sizes
+    /// of big steps in the functions of this crate.
+    pub(crate) fn print_timings(&self) -> bool {
+        configure_colors();
         match self {
             Self::Quiet => false,
             Self::Default => false,
@@ -278,3 +1150,224 @@ impl LoggingLevel {
         }
     }
 }
+
+/// Color choice currently applied, 0 until one is applied.
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Colors of the messages printed by the functions of this crate.
+///
+/// With [Auto](ColorChoice::Auto), messages are colored only if the standard
+/// output is a terminal and `NO_COLOR` is not set (as detected by `colored`),
+/// so that the logs written to files or captured by CI runners are plain
+/// text. The choice also applies to the output of the underlying executables
+/// (Circom, snarkjs, ...), which are passed `NO_COLOR` and `FORCE_COLOR`
+/// accordingly. Outputs captured by this crate are never colored.
+///
+/// ## Configuration
+///
+/// The choice is read from the `WINTER_CIRCOM_COLOR` environment variable
+/// (`auto`, `always` or `never`) when the first message is printed, and
+/// defaults to `auto`. [apply](ColorChoice::apply) overrides it for the rest of
+/// the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colors are enabled when printing to a terminal
+    Auto,
+
+    /// Colors are always enabled
+    Always,
+
+    /// Colors are never enabled
+    Never,
+}
+
+impl ColorChoice {
+    pub const COLOR_VARIABLE: &'static str = "WINTER_CIRCOM_COLOR";
+
+    /// Returns the choice of the environment variable, [Auto](ColorChoice::Auto)
+    /// if it is not set.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_COLOR` is not `auto`, `always` or `never`.
+    pub fn from_env() -> Result<Self, WinterCircomError> {
+        match std::env::var(ColorChoice::COLOR_VARIABLE) {
+            Ok(value) => value
+                .parse()
+                .map_err(|comment| WinterCircomError::InvalidEnvVariable {
+                    variable: String::from(ColorChoice::COLOR_VARIABLE),
+                    comment,
+                }),
+            Err(_) => Ok(ColorChoice::Auto),
+        }
+    }
+
+    /// Returns the choice applied to the messages of this crate.
+    pub fn current() -> Self {
+        configure_colors();
+        match COLOR_CHOICE.load(Ordering::Relaxed) {
+            2 => ColorChoice::Always,
+            3 => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        }
+    }
+
+    /// Apply this choice to the messages printed by this crate and to the
+    /// underlying executables, for the rest of the process.
+    ///
+    /// With the `codegen` feature, this also applies to the other messages
+    /// colored with the `colored` crate.
+    pub fn apply(self) {
+        let value = match self {
+            ColorChoice::Auto => 1,
+            ColorChoice::Always => 2,
+            ColorChoice::Never => 3,
+        };
+        COLOR_CHOICE.store(value, Ordering::Relaxed);
+        override_colors(self);
+    }
+
+    /// Set the environment variables controlling the colors of the output of
+    /// an underlying executable.
+    fn configure_command(&self, command: &mut Command) {
+        match self {
+            ColorChoice::Auto => {}
+            ColorChoice::Always => {
+                command.env("FORCE_COLOR", "1").env("CLICOLOR_FORCE", "1");
+            }
+            ColorChoice::Never => {
+                command.env("NO_COLOR", "1").env("FORCE_COLOR", "0");
+            }
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("expected auto, always or never, got {}", s)),
+        }
+    }
+}
+
+/// Apply the color choice of the environment variable, unless a choice was
+/// already applied.
+///
+/// Every message of this crate is gated by a [LoggingLevel] predicate, which
+/// calls this function, so that the choice is applied before the first one. An
+/// invalid choice must not fail the functions printing the messages: colors are
+/// then chosen automatically, with a warning.
+fn configure_colors() {
+    if COLOR_CHOICE.load(Ordering::Relaxed) == 0 {
+        let choice = ColorChoice::from_env().unwrap_or_else(|e| {
+            eprintln!(
+                "Warning: {} Colors are chosen automatically.",
+                strip_colors(&e.to_string())
+            );
+            ColorChoice::Auto
+        });
+        choice.apply();
+    }
+}
+
+/// Timer of a big step of the functions of this crate.
+///
+/// The step is announced when the timer is started, and its elapsed time and
+/// the size of the artifacts it generated are printed when it is finished, if
+/// the logging level is set to [Timings](LoggingLevel::Timings) or above.
+pub(crate) struct StageTimer<'a> {
+    logging_level: &'a LoggingLevel,
+    start: Instant,
+}
+
+impl<'a> StageTimer<'a> {
+    /// Announce a big step and start timing it.
+    pub fn start(step: &str, logging_level: &'a LoggingLevel) -> Self {
+        if logging_level.print_big_steps() {
+            println!("{}", step.green());
+        }
+        Self {
+            logging_level,
+            start: Instant::now(),
+        }
+    }
+
+    /// Print the elapsed time of the step and the size of the given artifacts,
+    /// and return the elapsed time.
+    ///
+    /// Missing artifacts are silently ignored.
+    pub fn finish<P: AsRef<Path>>(self, artifacts: &[P]) -> Duration {
+        let elapsed = self.start.elapsed();
+        if !self.logging_level.print_timings() {
+            return elapsed;
+        }
+
+        println!("    done in {:.2?}", elapsed);
+        for artifact in artifacts {
+            let artifact = artifact.as_ref();
+            if let Ok(metadata) = std::fs::metadata(artifact) {
+                println!(
+                    "    {} ({})",
+                    artifact.display(),
+                    format_size(metadata.len())
+                );
+            }
+        }
+        elapsed
+    }
+}
+
+/// Format a size in bytes with a binary unit prefix (e.g. `1.5 MiB`).
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Override the terminal detection of the `colored` crate, which is only a
+/// dependency of the builds generating circuits.
+#[cfg(feature = "codegen")]
+fn override_colors(choice: ColorChoice) {
+    match choice {
+        ColorChoice::Auto => colored::control::unset_override(),
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+    }
+}
+
+#[cfg(not(feature = "codegen"))]
+fn override_colors(_choice: ColorChoice) {}
+
+/// Plain replacement of the colors of the `colored` crate, which is only a
+/// dependency of the builds generating circuits. The messages of the other
+/// builds, e.g. the errors of the verifier, are printed without colors.
+#[cfg(not(feature = "codegen"))]
+pub(crate) trait Colorize {
+    fn green(self) -> String;
+    fn yellow(self) -> String;
+}
+
+#[cfg(not(feature = "codegen"))]
+impl<T: Display> Colorize for T {
+    fn green(self) -> String {
+        self.to_string()
+    }
+
+    fn yellow(self) -> String {
+        self.to_string()
+    }
+}
@@ -1,13 +1,145 @@
-use std::fs;
+use std::{fs, path::Path};
 
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use winterfell::{
     math::{
         fields::f256::{BaseElement, U256},
-        FieldElement,
+        FieldElement, StarkField,
     },
     Air, EvaluationFrame,
 };
 
+use crate::{
+    circom_poseidon::public_commitment, signals::Signal, utils::WinterCircomError,
+    workspace::circuit_dir, PublicInputLayout, WinterPublicInputs,
+};
+
+/// Out-of-domain (OOD) data of a Winterfell proof, decoded from the Groth16
+/// public signals by [decode_public_signals].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OodData {
+    /// Evaluations of the transition constraints at the OOD point, as
+    /// committed to by the prover.
+    pub constraint_evaluations: Vec<BaseElement>,
+
+    /// OOD trace frame, i.e. the trace evaluations at the OOD point and at the
+    /// next step.
+    pub current: Vec<BaseElement>,
+    pub next: Vec<BaseElement>,
+}
+
+impl OodData {
+    /// Returns the OOD data of the `input.json` file of a proof, written by
+    /// [circom_prove](crate::circom_prove).
+    ///
+    /// The OOD data of circuits generated with
+    /// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)
+    /// are not in `public.json`, and are sent to the verifiers this way instead.
+    pub fn from_input(input: &Value) -> Result<Self, WinterCircomError> {
+        let invalid = |signal: Signal| WinterCircomError::InvalidPublicSignals {
+            comment: format!("expected decimal field elements in {}", signal.name()),
+        };
+
+        let constraint_evaluations =
+            decimal_elements(&input[Signal::OodFrameConstraintEvaluation.name()])
+                .ok_or_else(|| invalid(Signal::OodFrameConstraintEvaluation))?;
+        let frame = &input[Signal::OodTraceFrame.name()];
+        match (decimal_elements(&frame[0]), decimal_elements(&frame[1])) {
+            (Some(current), Some(next)) if current.len() == next.len() => Ok(OodData {
+                constraint_evaluations,
+                current,
+                next,
+            }),
+            _ => Err(invalid(Signal::OodTraceFrame)),
+        }
+    }
+
+    /// Returns the OOD data of the `public.json` file in the
+    /// `target/circom/<circuit_name>/` directory, as read by [check_ood_frame].
+    ///
+    /// Unlike [decode_public_signals], the public inputs need not be exposed,
+    /// and are ignored if they are. Returns an error if the file cannot be read
+    /// or if the dimensions of the signals do not match the [Default]
+    /// implementation of the [Air].
+    pub fn of_circuit<AIR>(circuit_name: &str) -> Result<Self, WinterCircomError>
+    where
+        AIR: Air<BaseField = BaseElement> + Default,
+        AIR::PublicInputs: WinterPublicInputs,
+    {
+        let path = circuit_dir(circuit_name).join("public.json");
+        let data = fs::read_to_string(&path).map_err(|e| WinterCircomError::IoError {
+            io_error: e,
+            comment: Some(format!("reading {}", path.display())),
+        })?;
+        let signals = parse_public_signals(&data)?;
+
+        let air = AIR::default();
+        let trace_width = air.trace_info().width();
+        let num_transition_constraints = air.context().num_transition_constraints();
+        let num_ood_signals = num_transition_constraints + 2 * trace_width;
+        let num_exposed = PublicInputLayout::of::<AIR::PublicInputs>().num_packed();
+        if signals.len() != num_ood_signals && signals.len() != num_ood_signals + num_exposed {
+            return Err(WinterCircomError::InvalidPublicSignals {
+                comment: format!(
+                    "expected {} signals, or {} with exposed public inputs, found {}",
+                    num_ood_signals,
+                    num_ood_signals + num_exposed,
+                    signals.len()
+                ),
+            });
+        }
+
+        let frame_offset = num_transition_constraints;
+        Ok(OodData {
+            constraint_evaluations: signals[..num_transition_constraints].to_vec(),
+            current: signals[frame_offset..frame_offset + trace_width].to_vec(),
+            next: signals[frame_offset + trace_width..num_ood_signals].to_vec(),
+        })
+    }
+
+    /// Returns the OOD trace frame as an [EvaluationFrame].
+    pub fn frame(&self) -> EvaluationFrame<BaseElement> {
+        let mut frame = EvaluationFrame::new(self.current.len());
+        frame.current_mut().copy_from_slice(&self.current);
+        frame.next_mut().copy_from_slice(&self.next);
+        frame
+    }
+
+    /// Returns whether the constraint evaluations are the evaluations of the
+    /// transition constraints of the [Default] implementation of the [Air] at
+    /// the OOD trace frame, as checked by [check_ood_frame].
+    pub fn is_consistent_with<AIR>(&self) -> bool
+    where
+        AIR: Air<BaseField = BaseElement> + Default,
+    {
+        let air = AIR::default();
+        let mut evaluations =
+            BaseElement::zeroed_vector(air.context().num_transition_constraints());
+        if self.current.len() != air.trace_info().width()
+            || self.constraint_evaluations.len() != evaluations.len()
+        {
+            return false;
+        }
+        air.evaluate_transition::<BaseElement>(&self.frame(), &[], &mut evaluations);
+        evaluations == self.constraint_evaluations
+    }
+
+    /// Returns the public commitment to the OOD data and to the given public
+    /// inputs, see [public_commitment].
+    pub fn public_commitment(&self, public_inputs: &[BaseElement]) -> BaseElement {
+        public_commitment(
+            &[
+                &self.constraint_evaluations[..],
+                &self.current[..],
+                &self.next[..],
+                public_inputs,
+            ]
+            .concat(),
+        )
+    }
+}
+
 /// Check that the out-of-domain (OOD) trace frame corresponds to the given [Air]
 /// and the OOD constraint evaluations.
 ///
@@ -17,53 +149,58 @@ use winterfell::{
 /// function therefore guarantees that the OOD constraint evaluations are correct.
 ///
 /// This function requires the `public.json` file in the
-/// `target/circom/<circuit_name>/` directory to contain `t` ood constraint
-/// evaluations and 2`t` ood trace frame elements, in that order, where `t` is
-/// the trace width. This should be correct if the Circom proof was generated
-/// with the [circom_prove](crate::circom_prove) function.
+/// `target/circom/<circuit_name>/` directory to contain `c` ood constraint
+/// evaluations and 2`t` ood trace frame elements, in that order, where `c` is
+/// the number of transition constraints and `t` is the trace width, optionally
+/// followed by the (possibly packed) public inputs (see
+/// [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs)).
+/// This should be correct if the Circom proof was generated with the
+/// [circom_prove](crate::circom_prove) function.
+///
+/// The [Default] implementation of the [Air] must therefore have the same trace
+/// width and transition constraints as the one used to generate the proof.
+/// Circuits generated with
+/// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)
+/// are checked with [check_public_commitment] instead.
 pub fn check_ood_frame<AIR>(circuit_name: &str)
 where
     AIR: Air<BaseField = BaseElement> + Default,
+    AIR::PublicInputs: WinterPublicInputs,
 {
-    // public.json parsing
-    let data = fs::read_to_string(format!("target/circom/{}/public.json", circuit_name))
-        .expect("Unable to read file");
-    let json: serde_json::Value =
-        serde_json::from_str(&data).expect("public.json format incorrect!");
+    let pub_inputs = read_public_signals(circuit_name);
 
-    let pub_inputs = json.as_array().unwrap();
+    // We only need to access the 'evaluate_constraints' method which doesn't depend on the air.
+    // A default implementation of a Workair is sufficient here.
+    let air = AIR::default();
+    let trace_width = air.trace_info().width();
+    let num_transition_constraints = air.context().num_transition_constraints();
 
-    // public.json contains 3 * trace_width elements :
-    //  - trace_width ood_constraint_evaluation
+    // public.json contains num_transition_constraints + 2 * trace_width elements :
+    //  - num_transition_constraints ood_constraint_evaluation
     //  - 2 * trace_width elements for the OOD trace frame
-    let trace_width = pub_inputs.len() / 3;
-
-    let mut channel_ood_constraint_evaluation = Vec::<BaseElement>::with_capacity(trace_width);
+    // followed by the public inputs if they are exposed
+    let num_ood_signals = num_transition_constraints + 2 * trace_width;
+    let num_exposed = PublicInputLayout::of::<AIR::PublicInputs>().num_packed();
+    assert!(
+        pub_inputs.len() == num_ood_signals || pub_inputs.len() == num_ood_signals + num_exposed,
+        "public.json does not match the dimensions of the AIR"
+    );
 
-    for i in 0..trace_width {
-        channel_ood_constraint_evaluation.push(BaseElement::new(
-            U256::from_str_radix(pub_inputs[i].as_str().unwrap(), 10).unwrap(),
-        ));
-    }
+    let channel_ood_constraint_evaluation = &pub_inputs[..num_transition_constraints];
 
+    let offset = num_transition_constraints;
     let mut frame = EvaluationFrame::new(trace_width);
 
     for i in 0..trace_width {
-        frame.current_mut()[i] = BaseElement::new(
-            U256::from_str_radix(pub_inputs[trace_width + i].as_str().unwrap(), 10).unwrap(),
-        );
-        frame.next_mut()[i] = BaseElement::new(
-            U256::from_str_radix(pub_inputs[2 * trace_width + i].as_str().unwrap(), 10).unwrap(),
-        );
+        frame.current_mut()[i] = pub_inputs[offset + i];
+        frame.next_mut()[i] = pub_inputs[offset + trace_width + i];
     }
 
-    // We only need to access the 'evaluate_constraints' method which doesn't depend on the air.
-    // A default implementation of a Workair is sufficient here.
-    let air = AIR::default();
-    let mut ood_frame_constraint_evaluation = BaseElement::zeroed_vector(trace_width);
+    let mut ood_frame_constraint_evaluation =
+        BaseElement::zeroed_vector(num_transition_constraints);
     air.evaluate_transition::<BaseElement>(&frame, &[], &mut ood_frame_constraint_evaluation);
 
-    for i in 0..trace_width {
+    for i in 0..num_transition_constraints {
         assert!(
             ood_frame_constraint_evaluation[i] == channel_ood_constraint_evaluation[i],
             "\x1b[33m{}\x1b[0m",
@@ -76,3 +213,187 @@ where
         "OOD constraint evaluations are correct!"
     );
 }
+
+/// Check that the `public.json` file in the `target/circom/<circuit_name>/`
+/// directory of a circuit generated with
+/// [commit_public_signals](crate::WinterCircomProofOptions::commit_public_signals)
+/// contains the public commitment to the given OOD data and public inputs,
+/// and nothing else.
+///
+/// The public inputs are the (unpacked) public inputs the verifier expects the
+/// proof to attest of, and must be empty if the circuit was generated without
+/// [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs).
+/// Once the commitment is checked, the OOD data are guaranteed by the Groth16
+/// proof as they are for other circuits, and their consistency with the AIR is
+/// checked with [is_consistent_with](OodData::is_consistent_with). On chain,
+/// the commitment is the only public signal of the Groth16 verifier.
+pub fn check_public_commitment(
+    circuit_name: &str,
+    ood_data: &OodData,
+    public_inputs: &[BaseElement],
+) -> Result<(), WinterCircomError> {
+    let path = circuit_dir(circuit_name).join("public.json");
+    let data = fs::read_to_string(&path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", path.display())),
+    })?;
+    let signals = parse_public_signals(&data)?;
+
+    match signals.as_slice() {
+        [commitment] if *commitment == ood_data.public_commitment(public_inputs) => Ok(()),
+        [_] => Err(WinterCircomError::InvalidPublicSignals {
+            comment: String::from("the public commitment does not match"),
+        }),
+        _ => Err(WinterCircomError::InvalidPublicSignals {
+            comment: format!(
+                "expected a public commitment, found {} signals",
+                signals.len()
+            ),
+        }),
+    }
+}
+
+/// Returns the public inputs exposed as Groth16 public signals in the
+/// `public.json` file in the `target/circom/<circuit_name>/` directory.
+///
+/// The returned vector is empty if the circuit was generated without
+/// [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs).
+/// Packed public inputs are unpacked according to their [PublicInputLayout].
+/// Verifiers should compare the returned values with the statement they expect
+/// the proof to attest of.
+pub fn exposed_public_inputs<AIR>(circuit_name: &str) -> Vec<BaseElement>
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+    AIR::PublicInputs: WinterPublicInputs,
+{
+    let pub_inputs = read_public_signals(circuit_name);
+
+    let air = AIR::default();
+    let num_ood_signals = air.context().num_transition_constraints() + 2 * air.trace_info().width();
+
+    let layout = PublicInputLayout::of::<AIR::PublicInputs>();
+    if pub_inputs.len() == num_ood_signals + layout.num_packed() {
+        layout.unpack(&pub_inputs[num_ood_signals..])
+    } else {
+        Vec::new()
+    }
+}
+
+/// Decode the Groth16 public signals of a `public.json` file into the public
+/// inputs of the AIR and the OOD data of the Winterfell proof.
+///
+/// This is the inverse of the serialization of the public signals by
+/// [circom_prove](crate::circom_prove), so that verifying services can inspect
+/// the application-level statement a proof attests of. The public inputs are
+/// deserialized from the sequence of their (unpacked) field elements, i.e. the
+/// format of their [Serialize](serde::Serialize) implementation.
+///
+/// Returns an error if the dimensions of the signals do not match the AIR or if
+/// the circuit was generated without
+/// [expose_public_inputs](crate::WinterCircomProofOptions::expose_public_inputs).
+/// As with [check_ood_frame], the [Default] implementation of the [Air] must
+/// have the same trace width and transition constraints as the one used to
+/// generate the proof.
+pub fn decode_public_signals<AIR>(
+    public_signals: impl AsRef<Path>,
+) -> Result<(AIR::PublicInputs, OodData), WinterCircomError>
+where
+    AIR: Air<BaseField = BaseElement> + Default,
+    AIR::PublicInputs: WinterPublicInputs + DeserializeOwned,
+{
+    let path = public_signals.as_ref();
+    let data = fs::read_to_string(path).map_err(|e| WinterCircomError::IoError {
+        io_error: e,
+        comment: Some(format!("reading {}", path.display())),
+    })?;
+    let signals = parse_public_signals(&data)?;
+
+    let air = AIR::default();
+    let trace_width = air.trace_info().width();
+    let num_transition_constraints = air.context().num_transition_constraints();
+    let num_ood_signals = num_transition_constraints + 2 * trace_width;
+
+    let layout = PublicInputLayout::of::<AIR::PublicInputs>();
+    if signals.len() != num_ood_signals + layout.num_packed() {
+        return Err(WinterCircomError::InvalidPublicSignals {
+            comment: format!(
+                "expected {} signals with exposed public inputs, found {}",
+                num_ood_signals + layout.num_packed(),
+                signals.len()
+            ),
+        });
+    }
+
+    let public_inputs = layout
+        .unpack(&signals[num_ood_signals..])
+        .iter()
+        .map(|element| serde_json::to_value(element).expect("elements should be serializable"))
+        .collect::<Vec<_>>();
+    let public_inputs = serde_json::from_value(Value::Array(public_inputs)).map_err(|e| {
+        WinterCircomError::InvalidPublicSignals {
+            comment: format!("decoding public inputs: {}", e),
+        }
+    })?;
+
+    let frame_offset = num_transition_constraints;
+    let ood_data = OodData {
+        constraint_evaluations: signals[..num_transition_constraints].to_vec(),
+        current: signals[frame_offset..frame_offset + trace_width].to_vec(),
+        next: signals[frame_offset + trace_width..num_ood_signals].to_vec(),
+    };
+
+    Ok((public_inputs, ood_data))
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Parse the contents of a `public.json` file into field elements.
+fn parse_public_signals(data: &str) -> Result<Vec<BaseElement>, WinterCircomError> {
+    let invalid = |comment: &str| WinterCircomError::InvalidPublicSignals {
+        comment: String::from(comment),
+    };
+
+    let json: Value = serde_json::from_str(data).map_err(|_| invalid("invalid JSON"))?;
+    json.as_array()
+        .ok_or_else(|| invalid("expected an array of signals"))?
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .and_then(|value| U256::from_str_radix(value, 10).ok())
+                .map(BaseElement::new)
+                .ok_or_else(|| invalid("expected decimal field elements"))
+        })
+        .collect()
+}
+
+/// Parse an array of decimal field elements.
+fn decimal_elements(value: &Value) -> Option<Vec<BaseElement>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .and_then(|value| U256::from_str_radix(value, 10).ok())
+                .filter(|value| *value < BaseElement::MODULUS)
+                .map(BaseElement::new)
+        })
+        .collect()
+}
+
+/// Parse the Groth16 public signals of the `public.json` file into field
+/// elements.
+fn read_public_signals(circuit_name: &str) -> Vec<BaseElement> {
+    let data = fs::read_to_string(circuit_dir(circuit_name).join("public.json"))
+        .expect("Unable to read file");
+    let json: serde_json::Value =
+        serde_json::from_str(&data).expect("public.json format incorrect!");
+
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|value| BaseElement::new(U256::from_str_radix(value.as_str().unwrap(), 10).unwrap()))
+        .collect()
+}
@@ -0,0 +1,155 @@
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process::{Child, ExitStatus},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    interrupt::{is_interrupted, RunningChild},
+    utils::WinterCircomError,
+};
+
+/// Watchdog of the external processes (Circom, snarkjs, ...) called by the
+/// functions of this crate.
+///
+/// A process is considered active as long as it consumes CPU time or writes to
+/// its standard output. A process that shows no activity for the configured
+/// inactivity period is killed, and the function that spawned it fails with
+/// [ProcessWedged](crate::utils::WinterCircomError::ProcessWedged) instead of
+/// blocking forever.
+///
+/// CPU time is only monitored on Linux. On other platforms, only the output of
+/// the process is monitored.
+///
+/// ## Configuration
+///
+/// The watchdog is disabled by default. It is enabled by setting the
+/// `WINTER_CIRCOM_WATCHDOG_SECS` environment variable to the inactivity period,
+/// in seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchdog {
+    inactivity_timeout: Duration,
+}
+
+impl Watchdog {
+    pub const TIMEOUT_VARIABLE: &'static str = "WINTER_CIRCOM_WATCHDOG_SECS";
+
+    pub fn new(inactivity_timeout: Duration) -> Self {
+        Self { inactivity_timeout }
+    }
+
+    /// Returns the watchdog configured by the environment variable, if any.
+    ///
+    /// Returns an [InvalidEnvVariable](WinterCircomError::InvalidEnvVariable)
+    /// error if `WINTER_CIRCOM_WATCHDOG_SECS` is not an integer.
+    pub fn from_env() -> Result<Option<Self>, WinterCircomError> {
+        match env::var(Watchdog::TIMEOUT_VARIABLE) {
+            Ok(value) => value
+                .parse()
+                .map(|seconds| Some(Self::new(Duration::from_secs(seconds))))
+                .map_err(|_| WinterCircomError::InvalidEnvVariable {
+                    variable: String::from(Watchdog::TIMEOUT_VARIABLE),
+                    comment: format!("expected a number of seconds, got {}", value),
+                }),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns the period of inactivity after which a process is killed.
+    pub fn inactivity_timeout(&self) -> Duration {
+        self.inactivity_timeout
+    }
+
+    /// Wait for a child process to exit, killing it if it becomes inactive.
+    ///
+    /// The standard output of the child, if piped, is forwarded to the standard
+    /// output of this process when `print_output` is set, and discarded
+    /// otherwise.
+    ///
+    /// Returns `None` if the process was killed by the watchdog, or because
+    /// SIGINT or SIGTERM was received.
+    pub(crate) fn wait(
+        &self,
+        child: &mut Child,
+        print_output: bool,
+    ) -> io::Result<Option<ExitStatus>> {
+        let output_bytes = Arc::new(AtomicU64::new(0));
+        let forwarder = child.stdout.take().map(|stdout| {
+            let output_bytes = Arc::clone(&output_bytes);
+            thread::spawn(move || forward_output(stdout, &output_bytes, print_output))
+        });
+
+        let _running = RunningChild::new();
+        let mut activity = (0, cpu_time(child.id()));
+        let mut last_activity = Instant::now();
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+
+            let current = (output_bytes.load(Ordering::Relaxed), cpu_time(child.id()));
+            if current != activity {
+                activity = current;
+                last_activity = Instant::now();
+            } else if last_activity.elapsed() >= self.inactivity_timeout || is_interrupted() {
+                child.kill()?;
+                child.wait()?;
+                break None;
+            }
+
+            thread::sleep(self.poll_interval());
+        };
+
+        // the output pipe is closed once the process has exited, unless it was
+        // inherited by a process spawned by the killed one
+        if let (Some(forwarder), Some(_)) = (forwarder, status) {
+            let _ = forwarder.join();
+        }
+
+        Ok(status)
+    }
+
+    fn poll_interval(&self) -> Duration {
+        (self.inactivity_timeout / 10).clamp(Duration::from_millis(10), Duration::from_secs(1))
+    }
+}
+
+// HELPER FUNCTIONS
+// ===========================================================================
+
+/// Copy the output of a child process, counting the bytes read.
+fn forward_output<R: Read>(mut output: R, output_bytes: &AtomicU64, print_output: bool) {
+    let mut buffer = [0; 4096];
+    while let Ok(n) = output.read(&mut buffer) {
+        if n == 0 {
+            break;
+        }
+        output_bytes.fetch_add(n as u64, Ordering::Relaxed);
+        if print_output {
+            let _ = io::stdout().write_all(&buffer[..n]);
+        }
+    }
+}
+
+/// Returns the CPU time consumed by a process, in clock ticks.
+///
+/// This reads the `utime` and `stime` fields of `/proc/<pid>/stat`, and
+/// therefore only works on Linux.
+fn cpu_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // the process name may contain spaces, the fields are read after it
+    let fields = stat[stat.rfind(')')? + 1..]
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some(utime + stime)
+}
@@ -0,0 +1,234 @@
+use std::{
+    cell::RefCell,
+    env,
+    path::{Component, Path, PathBuf},
+};
+
+#[cfg(feature = "codegen")]
+use winterfell::{math::fields::f256::BaseElement, Air, Prover};
+
+use crate::tenant::split_circuit_name;
+#[cfg(feature = "codegen")]
+use crate::{
+    circom_create,
+    utils::{LoggingLevel, WinterCircomError},
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+#[cfg(feature = "prove")]
+use crate::{circom_prove, circom_verify, ProveArtifacts};
+
+thread_local! {
+    static WORKSPACE: RefCell<Option<CircomWorkspace>> = RefCell::new(None);
+}
+
+/// Directories and files used by the pipeline stages.
+///
+/// By default, the artifacts of a circuit are placed in
+/// `target/circom/<circuit_name>/`, the Circom templates are read from
+/// `circuits/` and the powers of tau transcript from `final.ptau`, all
+/// relative to the current directory. A workspace moves them elsewhere, so
+/// that multiple projects or CI runners sharing a host use isolated
+/// directories.
+///
+/// The functions of this crate use the workspace of the current thread, set
+/// with [with_workspace] or by the [create](CircomWorkspace::create),
+/// [prove](CircomWorkspace::prove) and [verify](CircomWorkspace::verify)
+/// methods, and the one configured by the environment variables otherwise.
+///
+/// ## Configuration
+///
+/// The default workspace is configured through environment variables, so that
+/// the JavaScript helpers of the `tools` directory agree with this crate:
+///
+/// - `WINTER_CIRCOM_OUTPUT_DIR`: root of the artifacts of the circuits.
+/// Defaults to `target/circom`.
+/// - `WINTER_CIRCOM_CIRCUITS_DIR`: directory of the Circom templates, including
+/// the AIR templates of its `air/` subdirectory. Defaults to `circuits`.
+/// - `WINTER_CIRCOM_PTAU`: path of the powers of tau transcript. Defaults to
+/// `final.ptau`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircomWorkspace {
+    root_dir: PathBuf,
+    circuits_dir: PathBuf,
+    ptau: PathBuf,
+}
+
+impl Default for CircomWorkspace {
+    fn default() -> Self {
+        Self {
+            root_dir: PathBuf::from("target/circom"),
+            circuits_dir: PathBuf::from("circuits"),
+            ptau: PathBuf::from("final.ptau"),
+        }
+    }
+}
+
+impl CircomWorkspace {
+    pub const OUTPUT_DIR_VARIABLE: &'static str = "WINTER_CIRCOM_OUTPUT_DIR";
+    pub const CIRCUITS_DIR_VARIABLE: &'static str = "WINTER_CIRCOM_CIRCUITS_DIR";
+    pub const PTAU_VARIABLE: &'static str = "WINTER_CIRCOM_PTAU";
+
+    /// Returns a workspace placing the artifacts of the circuits under
+    /// `root_dir`, with the default circuits directory and transcript.
+    pub fn new<D: Into<PathBuf>>(root_dir: D) -> Self {
+        Self {
+            root_dir: root_dir.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_circuits_dir<D: Into<PathBuf>>(self, circuits_dir: D) -> Self {
+        Self {
+            circuits_dir: circuits_dir.into(),
+            ..self
+        }
+    }
+
+    pub fn with_ptau<P: Into<PathBuf>>(self, ptau: P) -> Self {
+        Self {
+            ptau: ptau.into(),
+            ..self
+        }
+    }
+
+    /// Returns the workspace configured by the environment variables.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let var = |name| env::var_os(name).map(PathBuf::from);
+        Self {
+            root_dir: var(CircomWorkspace::OUTPUT_DIR_VARIABLE).unwrap_or(default.root_dir),
+            circuits_dir: var(CircomWorkspace::CIRCUITS_DIR_VARIABLE)
+                .unwrap_or(default.circuits_dir),
+            ptau: var(CircomWorkspace::PTAU_VARIABLE).unwrap_or(default.ptau),
+        }
+    }
+
+    /// Returns the workspace of the current thread (see [with_workspace]).
+    pub fn current() -> Self {
+        WORKSPACE
+            .with(|workspace| workspace.borrow().clone())
+            .unwrap_or_else(Self::from_env)
+    }
+
+    /// Returns the root of the artifacts of the circuits.
+    pub fn root_dir(&self) -> &Path {
+        &self.root_dir
+    }
+
+    /// Returns the directory of the Circom templates.
+    pub fn circuits_dir(&self) -> &Path {
+        &self.circuits_dir
+    }
+
+    /// Returns the path of the powers of tau transcript.
+    pub fn ptau(&self) -> &Path {
+        &self.ptau
+    }
+
+    /// Returns the directory of the artifacts of a circuit.
+    pub fn circuit_dir(&self, circuit_name: &str) -> PathBuf {
+        self.root_dir.join(circuit_name)
+    }
+
+    /// Returns the path of the AIR template of a circuit, without the tenant
+    /// prefix of namespaced circuit names (see [Tenant](crate::Tenant)).
+    pub fn air_template(&self, circuit_name: &str) -> PathBuf {
+        self.circuits_dir
+            .join("air")
+            .join(format!("{}.circom", split_circuit_name(circuit_name).1))
+    }
+
+    /// Returns the path of the circuits directory to include the templates from
+    /// in the generated main file of a circuit.
+    ///
+    /// The path is relative to the directory of the circuit when both
+    /// directories are relative and the circuit directory does not go up the
+    /// tree, so that the generated sources do not depend on the location of the
+    /// project, and absolute otherwise.
+    pub(crate) fn include_dir(&self, circuit_name: &str) -> String {
+        let circuit_dir = self.circuit_dir(circuit_name);
+        let components = circuit_dir.components();
+        if self.circuits_dir.is_relative()
+            && components
+                .clone()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            let up = "../".repeat(components.count());
+            format!("{}{}", up, self.circuits_dir.to_string_lossy())
+        } else {
+            let circuits_dir = env::current_dir()
+                .map(|dir| dir.join(&self.circuits_dir))
+                .unwrap_or_else(|_| self.circuits_dir.clone());
+            circuits_dir.to_string_lossy().into_owned()
+        }
+    }
+
+    /// Run [circom_create] in this workspace.
+    #[cfg(feature = "codegen")]
+    pub fn create<P, const N: usize>(
+        &self,
+        proof_options: WinterCircomProofOptions<N>,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError>
+    where
+        P: Prover<BaseField = BaseElement>,
+        <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+    {
+        with_workspace(self.clone(), || {
+            circom_create::<P, N>(proof_options, circuit_name, logging_level)
+        })
+    }
+
+    /// Run [circom_prove] in this workspace.
+    #[cfg(feature = "prove")]
+    pub fn prove<P>(
+        &self,
+        prover: P,
+        trace: <P as Prover>::Trace,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<ProveArtifacts, WinterCircomError>
+    where
+        P: Prover<BaseField = BaseElement>,
+        <<P as Prover>::Air as Air>::PublicInputs: WinterPublicInputs,
+    {
+        with_workspace(self.clone(), || {
+            circom_prove(prover, trace, circuit_name, logging_level)
+        })
+    }
+
+    /// Run [circom_verify] in this workspace.
+    #[cfg(feature = "prove")]
+    pub fn verify(
+        &self,
+        circuit_name: &str,
+        logging_level: LoggingLevel,
+    ) -> Result<(), WinterCircomError> {
+        with_workspace(self.clone(), || circom_verify(circuit_name, logging_level))
+    }
+}
+
+/// Run a closure with the given [CircomWorkspace] used by the functions of this
+/// crate on the current thread.
+///
+/// The previous workspace is restored when the closure returns or panics.
+pub fn with_workspace<T, F: FnOnce() -> T>(workspace: CircomWorkspace, f: F) -> T {
+    struct Restore(Option<CircomWorkspace>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            WORKSPACE.with(|workspace| *workspace.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(WORKSPACE.with(|current| current.borrow_mut().replace(workspace)));
+    f()
+}
+
+/// Returns the directory of the artifacts of a circuit in the current
+/// workspace.
+pub(crate) fn circuit_dir(circuit_name: &str) -> PathBuf {
+    CircomWorkspace::current().circuit_dir(circuit_name)
+}
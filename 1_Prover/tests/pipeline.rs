@@ -0,0 +1,220 @@
+//! Whole-pipeline integration test, on a fixture circuit small enough to be
+//! compiled, set up, proven and verified in under a minute.
+//!
+//! The test requires the Circom and snarkjs installations of the `iden3`
+//! directory, `node`, and the `final.ptau` transcript at the root of the
+//! workspace, and is therefore ignored by default:
+//!
+//! ```bash
+//! cargo test -p winter-circom-prover --features prove --test pipeline -- --ignored
+//! ```
+//!
+//! It runs in its own test binary, since the pipeline resolves the external
+//! tools from the current directory, which the test moves to the root of the
+//! workspace.
+#![cfg(feature = "prove")]
+
+use std::{env, fs, path::Path};
+
+use serde::{ser::SerializeTuple, Serialize};
+use winter_circom_prover::{
+    check_ood_frame, circom_create, circom_prove, preflight,
+    utils::LoggingLevel,
+    winterfell::{
+        math::{fields::f256::BaseElement, FieldElement},
+        Air, AirContext, Assertion, ByteWriter, EvaluationFrame, ProofOptions, Prover,
+        Serializable, Trace, TraceInfo, TraceTable,
+    },
+    with_workspace, CircomWorkspace, CircuitParameters, Pipeline, PreflightRequirements,
+    WinterCircomProofOptions, WinterPublicInputs,
+};
+
+/// Name of the fixture circuit, and of its `circuits/air/fixture.circom` AIR
+/// template.
+const CIRCUIT_NAME: &str = "fixture";
+
+/// Proof options of the fixture circuit.
+///
+/// The LDE domain of 64 elements fits in the remainder, so that the proofs have
+/// no FRI layer and the circuit stays small. The options are far below the
+/// usual security level, which `circom_create` only warns about.
+const FIXTURE_OPTIONS: WinterCircomProofOptions<2> =
+    WinterCircomProofOptions::new(8, 2, 3, [1, 1], 8, 8, 0, 8, 64);
+
+// FIXTURE AIR
+// ===========================================================================
+
+#[derive(Clone, Default)]
+struct PublicInputs {
+    start: BaseElement,
+    result: BaseElement,
+}
+
+impl WinterPublicInputs for PublicInputs {
+    const NUM_PUB_INPUTS: usize = 2;
+}
+
+impl Serialize for PublicInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_tuple(2)?;
+        state.serialize_element(&self.start)?;
+        state.serialize_element(&self.result)?;
+        state.end()
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write(self.start);
+        target.write(self.result);
+    }
+}
+
+/// Sums the integers from `start`, as the AIR of the `sum` example.
+struct FixtureAir {
+    context: AirContext<BaseElement>,
+    start: BaseElement,
+    result: BaseElement,
+}
+
+impl Air for FixtureAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        FixtureAir {
+            context: AirContext::new(
+                trace_info,
+                FIXTURE_OPTIONS.transition_constraint_degrees(),
+                FIXTURE_OPTIONS.num_assertions(),
+                options,
+            ),
+            start: pub_inputs.start,
+            result: pub_inputs.result,
+        }
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = &frame.current();
+        let next = &frame.next();
+
+        result[0] = next[0] - (current[0] + E::ONE);
+        result[1] = next[1] - (current[1] + current[0] + E::ONE);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, self.start),
+            Assertion::single(1, 0, self.start),
+            Assertion::single(1, last_step, self.result),
+        ]
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+}
+
+impl Default for FixtureAir {
+    fn default() -> Self {
+        FixtureAir::new(
+            TraceInfo::new(FIXTURE_OPTIONS.trace_width, FIXTURE_OPTIONS.trace_length),
+            PublicInputs::default(),
+            FIXTURE_OPTIONS.get_proof_options(),
+        )
+    }
+}
+
+struct FixtureProver {
+    options: ProofOptions,
+}
+
+impl FixtureProver {
+    fn build_trace(&self, start: BaseElement) -> TraceTable<BaseElement> {
+        let mut trace = TraceTable::new(FIXTURE_OPTIONS.trace_width, FIXTURE_OPTIONS.trace_length);
+        trace.fill(
+            |state| {
+                state[0] = start;
+                state[1] = start;
+            },
+            |_, state| {
+                state[0] += BaseElement::ONE;
+                state[1] += state[0];
+            },
+        );
+        trace
+    }
+}
+
+impl Prover for FixtureProver {
+    type BaseField = BaseElement;
+    type Air = FixtureAir;
+    type Trace = TraceTable<Self::BaseField>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        PublicInputs {
+            start: trace.get(0, 0),
+            result: trace.get(1, trace.length() - 1),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// PIPELINE TESTS
+// ===========================================================================
+
+/// Creates, compiles, sets up, proves and verifies the fixture circuit, in a
+/// workspace of its own under `target/circom`.
+#[test]
+#[ignore]
+fn fixture_circuit_passes_the_whole_pipeline() {
+    env::set_current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .expect("the root of the workspace exists");
+    let root_dir = Path::new("target/circom").join(format!("pipeline-test-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root_dir);
+    let logging_level = LoggingLevel::Timings;
+
+    with_workspace(workspace, || {
+        let report = preflight(PreflightRequirements::default());
+        if !report.is_ready() {
+            report.print();
+            panic!("the host is missing tools or files required by the pipeline");
+        }
+
+        circom_create::<FixtureProver, 2>(FIXTURE_OPTIONS, CIRCUIT_NAME, logging_level).unwrap();
+        Pipeline::new(CIRCUIT_NAME)
+            .compile()
+            .setup()
+            .run(logging_level)
+            .unwrap();
+
+        let parameters = CircuitParameters::of_circuit(CIRCUIT_NAME).unwrap();
+        let prover = FixtureProver {
+            options: parameters.proof_options(),
+        };
+        let trace = prover.build_trace(BaseElement::ONE);
+        circom_prove(prover, trace, CIRCUIT_NAME, logging_level).unwrap();
+        Pipeline::new(CIRCUIT_NAME)
+            .witness()
+            .prove()
+            .verify()
+            .run(logging_level)
+            .unwrap();
+
+        check_ood_frame::<FixtureAir>(CIRCUIT_NAME);
+    });
+
+    fs::remove_dir_all(&root_dir).unwrap();
+}
@@ -0,0 +1,134 @@
+//! Reference rollup integration test: batches of account transfers of the
+//! `gadgets::rollup` AIR are proven one after the other, each batch starting
+//! from the final state of the previous one, and the proofs are exported with
+//! the Solidity verifier of the circuit and their calldata.
+//!
+//! As the whole-pipeline test, it requires the Circom and snarkjs
+//! installations of the `iden3` directory, `node`, and the `final.ptau`
+//! transcript at the root of the workspace, and is therefore ignored by
+//! default:
+//!
+//! ```bash
+//! cargo test -p winter-circom-prover --features prove --test rollup -- --ignored
+//! ```
+//!
+//! The batches are proven and verified individually: there is no aggregation
+//! circuit folding them into a single Groth16 proof yet.
+#![cfg(feature = "prove")]
+
+use std::{env, fs, path::Path};
+
+use winter_circom_prover::{
+    check_ood_frame, circom_create, circom_prove, export_solidity_verifier, exposed_public_inputs,
+    gadgets::rollup::{proof_options, RollupAir, RollupProver, RollupState, Transfer},
+    preflight,
+    utils::LoggingLevel,
+    winterfell::math::fields::f256::BaseElement,
+    with_workspace, CircomWorkspace, CircuitParameters, Groth16Calldata, Pipeline,
+    PreflightRequirements, WinterCircomProofOptions,
+};
+
+/// Name of the circuit, and of its `circuits/air/rollup.circom` AIR template.
+const CIRCUIT_NAME: &str = "rollup";
+
+/// Number of batches proven against the same circuit.
+const NUM_BATCHES: usize = 3;
+
+/// Number of transfers of a batch, matching the trace length of the [Default]
+/// implementation of [RollupAir].
+const NUM_TRANSFERS: usize = 15;
+
+const ROLLUP_OPTIONS: WinterCircomProofOptions<3> = proof_options(NUM_TRANSFERS + 1);
+
+/// Returns an arbitrary batch of transfers, different for every batch.
+fn batch(index: usize) -> Vec<Transfer> {
+    (0..NUM_TRANSFERS as u64)
+        .map(|i| {
+            let amount = 10 * index as u64 + i;
+            if i % 3 == index as u64 % 3 {
+                Transfer::BToA(amount)
+            } else {
+                Transfer::AToB(amount)
+            }
+        })
+        .collect()
+}
+
+// ROLLUP TESTS
+// ===========================================================================
+
+/// Creates, compiles and sets up the rollup circuit and exports its Solidity
+/// verifier, then proves, verifies and formats the calldata of consecutive
+/// batches, in a workspace of its own under `target/circom`.
+#[test]
+#[ignore]
+fn rollup_batches_are_proven_and_exported_to_solidity() {
+    env::set_current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .expect("the root of the workspace exists");
+    let root_dir = Path::new("target/circom").join(format!("rollup-test-{}", std::process::id()));
+    let workspace = CircomWorkspace::new(&root_dir);
+    let logging_level = LoggingLevel::Timings;
+
+    with_workspace(workspace, || {
+        let report = preflight(PreflightRequirements::default());
+        if !report.is_ready() {
+            report.print();
+            panic!("the host is missing tools or files required by the pipeline");
+        }
+
+        circom_create::<RollupProver, 3>(ROLLUP_OPTIONS, CIRCUIT_NAME, logging_level).unwrap();
+        Pipeline::new(CIRCUIT_NAME)
+            .compile()
+            .setup()
+            .run(logging_level)
+            .unwrap();
+
+        let verifier = export_solidity_verifier(CIRCUIT_NAME, logging_level).unwrap();
+        let contract = fs::read_to_string(verifier).unwrap();
+        assert!(contract.contains("function verifyProof"));
+
+        let parameters = CircuitParameters::of_circuit(CIRCUIT_NAME).unwrap();
+        let mut state = RollupState {
+            balance_a: BaseElement::from(1000u64),
+            balance_b: BaseElement::from(500u64),
+        };
+        let mut calldata = Vec::new();
+        for index in 0..NUM_BATCHES {
+            let transfers = batch(index);
+            let prover = RollupProver::new(parameters.proof_options());
+            let trace = prover.build_trace(state, &transfers);
+            circom_prove(prover, trace, CIRCUIT_NAME, logging_level).unwrap();
+            Pipeline::new(CIRCUIT_NAME)
+                .witness()
+                .prove()
+                .verify()
+                .run(logging_level)
+                .unwrap();
+            check_ood_frame::<RollupAir>(CIRCUIT_NAME);
+
+            // the proof attests of the transition from the previous final state
+            let final_state = state.apply_batch(&transfers);
+            assert_eq!(
+                exposed_public_inputs::<RollupAir>(CIRCUIT_NAME),
+                vec![
+                    state.balance_a,
+                    state.balance_b,
+                    final_state.balance_a,
+                    final_state.balance_b,
+                ]
+            );
+            calldata.push(Groth16Calldata::of_circuit(CIRCUIT_NAME).unwrap());
+            state = final_state;
+        }
+
+        // the batches are distinct statements of the same verifier contract
+        assert!(calldata
+            .iter()
+            .all(|data| data.input.len() == calldata[0].input.len()));
+        assert!(calldata
+            .windows(2)
+            .all(|pair| pair[0].input != pair[1].input));
+    });
+
+    fs::remove_dir_all(&root_dir).unwrap();
+}
@@ -22,34 +22,37 @@ template FriVerifier(
 ) {
     var remainder_size = (trace_length * lde_blowup_factor) \ (folding_factor ** num_fri_layers);
     var lde_domain_size = trace_length * lde_blowup_factor;
+    // remainder-only proofs have no FRI layer, but Circom does not support
+    // empty arrays: their layer inputs hold a single padding layer of zeroes
+    var num_layer_inputs = num_fri_layers == 0 ? 1 : num_fri_layers;
 
     signal input addicity_root;
     signal input deep_evaluations[num_queries];
     signal input fri_commitments[num_fri_layers + 1];
-    signal input fri_layer_proofs[num_fri_layers][num_queries][tree_depth];
-    signal input fri_layer_queries[num_fri_layers][num_queries * folding_factor];
+    signal input fri_layer_proofs[num_layer_inputs][num_queries][tree_depth];
+    signal input fri_layer_queries[num_layer_inputs][num_queries * folding_factor];
     signal input fri_remainder[remainder_size];
     signal input g_lde;
-    signal input layer_alphas[num_fri_layers];
+    signal input layer_alphas[num_layer_inputs];
     signal input query_positions[num_queries];
 
-    signal coordinates_xe[num_fri_layers][num_queries];
+    signal coordinates_xe[num_layer_inputs][num_queries];
     signal max_degree_plus_1[num_fri_layers + 1];
-    signal query_values[num_fri_layers][num_queries];
+    signal query_values[num_layer_inputs][num_queries];
     signal t1[folding_factor];
     signal t2[folding_factor];
     signal x_pow[lde_domain_size];
 
-    component coordinate_pow_selectors[num_fri_layers];
-    component coordinate_interpolators[num_fri_layers];
-    component evaluations[num_fri_layers][num_queries];
-    component folded_positions[num_fri_layers];
-    component folded_position_modulos[num_fri_layers][num_queries];
+    component coordinate_pow_selectors[num_layer_inputs];
+    component coordinate_interpolators[num_layer_inputs];
+    component evaluations[num_layer_inputs][num_queries];
+    component folded_positions[num_layer_inputs];
+    component folded_position_modulos[num_layer_inputs][num_queries];
     component folding_roots;
-    component layer_commitment_verifiers[num_fri_layers];
-    component layer_queries_divisions[num_fri_layers][num_queries];
-    component layer_queries_lookups[num_fri_layers];
-    component layer_query_selectors[num_fri_layers];
+    component layer_commitment_verifiers[num_layer_inputs];
+    component layer_queries_divisions[num_layer_inputs][num_queries];
+    component layer_queries_lookups[num_layer_inputs];
+    component layer_query_selectors[num_layer_inputs];
     component remainder_degree;
     component remainder_length_lt;
     component remainder_degree_lt;
@@ -238,16 +241,38 @@ template FriVerifier(
     // 2 - VERIFY THE REMAINDER OF THE FRI PROOF
     // ==========================================================================
 
-    // check remainder values against last level evaluations
     remainder_selectors = MultiSelector(remainder_size, num_queries);
     for (var i = 0; i < remainder_size; i++) {
         remainder_selectors.in[i] <== fri_remainder[i];
     }
-    for (var i = 0; i < num_queries; i++) {
-        remainder_selectors.indexes[i] <== folded_positions[num_fri_layers - 1].out[i];
-    }
-    for (var i = 0; i < num_queries; i++) {
-        (remainder_selectors.out[i] - evaluations[num_fri_layers - 1][i].out) * folded_positions[num_fri_layers - 1].out_mask[i] === 0;
+    if (num_fri_layers == 0) {
+        // without FRI layer, the remainder is the evaluation of the DEEP
+        // composition polynomial over the whole LDE domain: check it against
+        // deep_evaluations at the query positions
+        for (var i = 0; i < num_queries; i++) {
+            remainder_selectors.indexes[i] <== query_positions[i];
+        }
+        for (var i = 0; i < num_queries; i++) {
+            remainder_selectors.out[i] === deep_evaluations[i];
+        }
+
+        // the padding layer must not carry any data
+        for (var i = 0; i < num_queries * folding_factor; i++) {
+            fri_layer_queries[0][i] === 0;
+        }
+        for (var i = 0; i < num_queries; i++) {
+            for (var j = 0; j < tree_depth; j++) {
+                fri_layer_proofs[0][i][j] === 0;
+            }
+        }
+    } else {
+        // check remainder values against last level evaluations
+        for (var i = 0; i < num_queries; i++) {
+            remainder_selectors.indexes[i] <== folded_positions[num_fri_layers - 1].out[i];
+        }
+        for (var i = 0; i < num_queries; i++) {
+            (remainder_selectors.out[i] - evaluations[num_fri_layers - 1][i].out) * folded_positions[num_fri_layers - 1].out_mask[i] === 0;
+        }
     }
 
     // transpose remainder into a matrix of width folding_factor and hash each line
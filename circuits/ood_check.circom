@@ -0,0 +1,108 @@
+pragma circom 2.0.0;
+
+include "ood_consistency_check.circom";
+include "utils/powers.circom";
+
+
+/**
+ * Standalone out-of-domain consistency check, evaluating only the
+ * OodConsistencyCheck template of the verifier. The Fiat-Shamir coefficients
+ * and the OOD point are inputs instead of being drawn from the public coin, so
+ * that the check can be compared with the Rust verifier on any OOD data, and
+ * AIR templates can be tested without compiling the whole verifier.
+ *
+ * The witness can only be computed if the evaluations of the composition
+ * polynomial sent by the prover are consistent with the OOD frame, the OOD
+ * transition constraint evaluations and the assertions of the AIR template.
+ *
+ * ARGUMENTS:
+ * - See verify.circom
+ *
+ * INPUTS:
+ * - addicity_root: primitive root of unity of order 2**addicity.
+ * - boundary_coeffs: Fiat-Shamir coefficients for the boundary constraints.
+ * - ood_constraint_evaluations: evaluations of the composition polynomial
+     columns at the OOD point, sent by the prover.
+ * - ood_frame_constraint_evaluation: evaluations of the transition constraints
+     over the OOD frame.
+ * - ood_trace_frame: the OOD frame, i.e. the trace evaluations at the OOD point
+     and at the next step.
+ * - public_inputs: inputs used for the calculation.
+ * - transition_coeffs: Fiat-Shamir coefficients for the transition constraints.
+ * - z: OOD point of evaluation.
+ */
+template OodCheck(
+    addicity,
+    num_composition_columns,
+    column_map,
+    num_assertions,
+    num_public_inputs,
+    num_transition_constraints,
+    trace_length,
+    trace_width
+) {
+    signal input addicity_root;
+    signal input boundary_coeffs[num_assertions][2];
+    signal input ood_constraint_evaluations[num_composition_columns];
+    signal input ood_frame_constraint_evaluation[num_transition_constraints];
+    signal input ood_trace_frame[2][trace_width];
+    signal input public_inputs[num_public_inputs];
+    signal input transition_coeffs[num_transition_constraints][2];
+    signal input z;
+
+    signal g_trace;
+
+    component addicity_pow[2];
+    component ood;
+
+
+    // CALCULATE TRACE DOMAIN GENERATOR
+    addicity_pow[0] = Pow(2 ** addicity);
+    addicity_pow[0].in <== addicity_root;
+    addicity_pow[0].out === 1;
+
+    var log2_trace_length = numbits(trace_length) - 1;
+    assert(log2_trace_length <= addicity);
+    addicity_pow[1] = Pow(2 ** (addicity - log2_trace_length));
+    addicity_pow[1].in <== addicity_root;
+    g_trace <== addicity_pow[1].out;
+
+
+    // OOD CONSISTENCY CHECK
+    ood = OodConsistencyCheck(
+        addicity,
+        num_composition_columns,
+        column_map,
+        num_assertions,
+        num_public_inputs,
+        num_transition_constraints,
+        trace_length,
+        trace_width
+    );
+
+    ood.addicity_root <== addicity_root;
+    ood.g_trace <== g_trace;
+    ood.z <== z;
+
+    for (var i = 0; i < num_assertions; i++) {
+        for (var j = 0; j < 2; j++) {
+            ood.boundary_coeffs[i][j] <== boundary_coeffs[i][j];
+        }
+    }
+    for (var i = 0; i < num_composition_columns; i++) {
+        ood.channel_ood_evaluations[i] <== ood_constraint_evaluations[i];
+    }
+    for (var i = 0; i < num_transition_constraints; i++) {
+        ood.ood_frame_constraint_evaluation[i] <== ood_frame_constraint_evaluation[i];
+        for (var j = 0; j < 2; j++) {
+            ood.transition_coeffs[i][j] <== transition_coeffs[i][j];
+        }
+    }
+    for (var i = 0; i < num_public_inputs; i++) {
+        ood.public_inputs[i] <== public_inputs[i];
+    }
+    for (var i = 0; i < trace_width; i++) {
+        ood.frame[0][i] <== ood_trace_frame[0][i];
+        ood.frame[1][i] <== ood_trace_frame[1][i];
+    }
+}
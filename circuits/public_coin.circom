@@ -72,13 +72,10 @@ template PublicCoin(
     signal query_draws[num_draws];
 
     component constraint_coin;
-    component bits2num[num_draws];
     component deep_coin[3 * trace_width + ce_blowup_factor + 2];
+    component draw_queries;
     component fri_coin[num_fri_layers + 1];
     component init = Poseidon(num_pub_coin_seed);
-    component num2bits[num_draws];
-    component query_coin[num_draws];
-    component remove_duplicates;
     component reseed[num_seeds];
     component trace_coin[num_transition_constraints + num_assertions][2];
 
@@ -213,12 +210,49 @@ template PublicCoin(
 
     // DRAW QUERY POSITIONS
 
+    draw_queries = DrawQueryPositions(num_draws, num_queries, trace_length * lde_blowup_factor);
+    draw_queries.seed <== reseed[k].out;
+
+    for (var i = 0; i < num_queries; i++){
+        query_positions[i] <== draw_queries.out[i];
+    }
+}
+
+
+/**
+ * Draw the query positions from the seed of the public coin, by rejection
+ * sampling: the i-th draw is the low log2(lde_domain_size) bits of
+ * hash(seed, i + 1), and the query positions are the first num_queries
+ * distinct draws, in order.
+ *
+ * This must match derive_query_positions in winter-circom-prover.
+ *
+ * ARGUMENTS:
+ * - num_draws: see verify.circom
+ * - num_queries: see verify.circom
+ * - lde_domain_size: size of the LDE domain, a power of two
+ *
+ * INPUTS:
+ * - seed: seed of the public coin after the proof of work.
+ *
+ * OUTPUTS:
+ * - out: query positions.
+ */
+template DrawQueryPositions(num_draws, num_queries, lde_domain_size) {
+    signal input seed;
+    signal output out[num_queries];
+
+    component bits2num[num_draws];
+    component num2bits[num_draws];
+    component query_coin[num_draws];
+    component remove_duplicates;
+
     // TODO: divide number of hashes by 4
     // winterfell protocol has to be modified to match
     remove_duplicates = RemoveDuplicates(num_draws,num_queries);
 
     // compute the size of the query elements in bits
-    var bit_mask = trace_length * lde_blowup_factor;
+    var bit_mask = lde_domain_size;
     var mask_size = 0;
     while(bit_mask != 1) {
         bit_mask \= 2;
@@ -227,7 +261,7 @@ template PublicCoin(
 
     for (var i = 0; i < num_draws; i++) {
         query_coin[i] = Poseidon(2);
-        query_coin[i].in[0] <== reseed[k].out;
+        query_coin[i].in[0] <== seed;
         query_coin[i].in[1] <== i + 1;
         num2bits[i] = Num2Bits(255);
         num2bits[i].in <== query_coin[i].out;
@@ -239,7 +273,7 @@ template PublicCoin(
     }
 
     for (var i = 0; i < num_queries; i++){
-        query_positions[i] <== remove_duplicates.out[i];
+        out[i] <== remove_duplicates.out[i];
     }
 }
 
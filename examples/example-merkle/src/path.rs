@@ -0,0 +1,20 @@
+use winter_circom_prover::{
+    gadgets::merkle::{proof_options, MerklePath},
+    winterfell::math::fields::f256::BaseElement,
+    WinterCircomProofOptions,
+};
+
+pub(crate) const DEPTH: usize = 15;
+
+pub(crate) const PROOF_OPTIONS: WinterCircomProofOptions<3> = proof_options(DEPTH);
+
+/// Returns the authentication path of an arbitrary leaf.
+pub(crate) fn sample_path() -> MerklePath {
+    MerklePath {
+        leaf: BaseElement::from(42u64),
+        index: 0b0110_1001_0110_1001,
+        siblings: (0..DEPTH as u64)
+            .map(|i| BaseElement::from(1000 + i))
+            .collect(),
+    }
+}
@@ -0,0 +1,15 @@
+use winter_circom_prover::{
+    gadgets::merkle::MerkleProver, CircuitRegistry, WinterCircomProofOptions,
+};
+
+use crate::path::PROOF_OPTIONS;
+
+/// Proof options of the `merkle` circuit.
+pub(crate) fn merkle() -> WinterCircomProofOptions<3> {
+    PROOF_OPTIONS
+}
+
+/// Returns the registry of the circuits of this example, with their presets.
+pub(crate) fn registry() -> CircuitRegistry {
+    CircuitRegistry::new().register_preset::<MerkleProver, 3>("merkle", merkle())
+}
@@ -0,0 +1,25 @@
+use winter_circom_prover::{
+    circom_prove,
+    gadgets::merkle::MerkleProver,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+mod path;
+use path::sample_path;
+
+mod presets;
+
+fn main() -> Result<(), WinterCircomError> {
+    let path = sample_path();
+
+    // build proof with the preset of the circuit
+    let options = presets::registry()
+        .preset::<3>("merkle")
+        .expect("the merkle preset should be registered")
+        .get_proof_options();
+    let prover = MerkleProver::new(options);
+    let trace = prover.build_trace(&path);
+
+    circom_prove(prover, trace, "merkle", LoggingLevel::Default)?;
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use winter_circom_prover::{
+    check_ood_frame, circom_verify,
+    gadgets::merkle::MerkleAir,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+fn main() -> Result<(), WinterCircomError> {
+    check_ood_frame::<MerkleAir>("merkle");
+    circom_verify("merkle", LoggingLevel::Verbose)?;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use winter_circom_prover::{
+    gadgets::rollup::{proof_options, RollupState, Transfer},
+    winterfell::math::fields::f256::BaseElement,
+    WinterCircomProofOptions,
+};
+
+pub(crate) const NUM_TRANSFERS: usize = 15;
+
+pub(crate) const PROOF_OPTIONS: WinterCircomProofOptions<3> = proof_options(NUM_TRANSFERS + 1);
+
+/// Returns the state of the rollup before the batch.
+pub(crate) fn initial_state() -> RollupState {
+    RollupState {
+        balance_a: BaseElement::from(1000u64),
+        balance_b: BaseElement::from(500u64),
+    }
+}
+
+/// Returns an arbitrary batch of transfers.
+pub(crate) fn sample_batch() -> Vec<Transfer> {
+    (0..NUM_TRANSFERS as u64)
+        .map(|i| {
+            if i % 3 == 0 {
+                Transfer::BToA(10 + i)
+            } else {
+                Transfer::AToB(20 + i)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,15 @@
+use winter_circom_prover::{
+    gadgets::rollup::RollupProver, CircuitRegistry, WinterCircomProofOptions,
+};
+
+use crate::batch::PROOF_OPTIONS;
+
+/// Proof options of the `rollup` circuit.
+pub(crate) fn rollup() -> WinterCircomProofOptions<3> {
+    PROOF_OPTIONS
+}
+
+/// Returns the registry of the circuits of this example, with their presets.
+pub(crate) fn registry() -> CircuitRegistry {
+    CircuitRegistry::new().register_preset::<RollupProver, 3>("rollup", rollup())
+}
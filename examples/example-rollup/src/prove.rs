@@ -0,0 +1,25 @@
+use winter_circom_prover::{
+    circom_prove,
+    gadgets::rollup::RollupProver,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+mod batch;
+use batch::{initial_state, sample_batch};
+
+mod presets;
+
+fn main() -> Result<(), WinterCircomError> {
+    let transfers = sample_batch();
+
+    // build proof with the preset of the circuit
+    let options = presets::registry()
+        .preset::<3>("rollup")
+        .expect("the rollup preset should be registered")
+        .get_proof_options();
+    let prover = RollupProver::new(options);
+    let trace = prover.build_trace(initial_state(), &transfers);
+
+    circom_prove(prover, trace, "rollup", LoggingLevel::Default)?;
+    Ok(())
+}
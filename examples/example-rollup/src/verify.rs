@@ -0,0 +1,32 @@
+use winter_circom_prover::{
+    check_ood_frame, circom_verify, exposed_public_inputs,
+    gadgets::rollup::RollupAir,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+#[allow(dead_code)]
+mod batch;
+use batch::{initial_state, sample_batch};
+
+fn main() -> Result<(), WinterCircomError> {
+    check_ood_frame::<RollupAir>("rollup");
+
+    // the proof must attest of the expected state transition
+    let initial_state = initial_state();
+    let final_state = initial_state.apply_batch(&sample_batch());
+    let expected = vec![
+        initial_state.balance_a,
+        initial_state.balance_b,
+        final_state.balance_a,
+        final_state.balance_b,
+    ];
+    assert!(
+        exposed_public_inputs::<RollupAir>("rollup") == expected,
+        "\x1b[33m{}\x1b[0m",
+        "Proof invalid: unexpected rollup state transition!"
+    );
+
+    circom_verify("rollup", LoggingLevel::Verbose)?;
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use winter_circom_prover::utils::{LoggingLevel, WinterCircomError};
+
+#[allow(dead_code)]
+mod message;
+
+mod presets;
+
+fn main() -> Result<(), WinterCircomError> {
+    presets::registry().create("signature", LoggingLevel::Default)
+}
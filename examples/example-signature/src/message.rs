@@ -0,0 +1,29 @@
+use winter_circom_prover::{
+    gadgets::signature::{proof_options, PublicKey, SecretKey, Signature},
+    winterfell::math::fields::f256::BaseElement,
+    WinterCircomProofOptions,
+};
+
+pub(crate) const TRACE_LENGTH: usize = 16;
+
+pub(crate) const PROOF_OPTIONS: WinterCircomProofOptions<4> = proof_options(TRACE_LENGTH);
+
+pub(crate) const MESSAGE: usize = 11;
+
+/// Returns an arbitrary secret key.
+fn sample_secret_key() -> SecretKey {
+    SecretKey {
+        s1: BaseElement::from(1234u64),
+        s2: BaseElement::from(5678u64),
+    }
+}
+
+/// Returns the public key of the sample secret key.
+pub(crate) fn sample_public_key() -> PublicKey {
+    sample_secret_key().public_key(TRACE_LENGTH - 1)
+}
+
+/// Returns the signature of [MESSAGE] with the sample secret key.
+pub(crate) fn sample_signature() -> Signature {
+    sample_secret_key().sign(MESSAGE, TRACE_LENGTH - 1)
+}
@@ -0,0 +1,15 @@
+use winter_circom_prover::{
+    gadgets::signature::SignatureProver, CircuitRegistry, WinterCircomProofOptions,
+};
+
+use crate::message::PROOF_OPTIONS;
+
+/// Proof options of the `signature` circuit.
+pub(crate) fn signature() -> WinterCircomProofOptions<4> {
+    PROOF_OPTIONS
+}
+
+/// Returns the registry of the circuits of this example, with their presets.
+pub(crate) fn registry() -> CircuitRegistry {
+    CircuitRegistry::new().register_preset::<SignatureProver, 4>("signature", signature())
+}
@@ -0,0 +1,26 @@
+use winter_circom_prover::{
+    circom_prove,
+    gadgets::signature::SignatureProver,
+    utils::{LoggingLevel, WinterCircomError},
+};
+
+mod message;
+use message::{sample_public_key, sample_signature, MESSAGE, TRACE_LENGTH};
+
+mod presets;
+
+fn main() -> Result<(), WinterCircomError> {
+    let signature = sample_signature();
+    assert!(sample_public_key().verify(MESSAGE, &signature, TRACE_LENGTH - 1));
+
+    // build proof with the preset of the circuit
+    let options = presets::registry()
+        .preset::<4>("signature")
+        .expect("the signature preset should be registered")
+        .get_proof_options();
+    let prover = SignatureProver::new(options);
+    let trace = prover.build_trace(MESSAGE, &signature, TRACE_LENGTH);
+
+    circom_prove(prover, trace, "signature", LoggingLevel::Default)?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use winter_circom_prover::{
+    check_ood_frame, circom_verify, exposed_public_inputs,
+    gadgets::signature::SignatureAir,
+    utils::{LoggingLevel, WinterCircomError},
+    winterfell::math::fields::f256::BaseElement,
+};
+
+#[allow(dead_code)]
+mod message;
+use message::{sample_public_key, MESSAGE};
+
+fn main() -> Result<(), WinterCircomError> {
+    check_ood_frame::<SignatureAir>("signature");
+
+    // the proof must attest of the expected message and public key
+    let public_key = sample_public_key();
+    let expected = vec![
+        BaseElement::from(MESSAGE as u64),
+        public_key.pk1,
+        public_key.pk2,
+    ];
+    assert!(
+        exposed_public_inputs::<SignatureAir>("signature") == expected,
+        "\x1b[33m{}\x1b[0m",
+        "Proof invalid: unexpected message or public key!"
+    );
+
+    circom_verify("signature", LoggingLevel::Verbose)?;
+
+    Ok(())
+}
@@ -1,13 +1,13 @@
-use winter_circom_prover::{circom_create, utils::{LoggingLevel, WinterCircomError}};
+use winter_circom_prover::utils::{LoggingLevel, WinterCircomError};
 
 #[allow(dead_code)]
 mod prover;
-use prover::WorkProver;
 
 mod air;
-use air::PROOF_OPTIONS;
+
+mod presets;
 
 fn main() -> Result<(), WinterCircomError> {
     println!("Make here");
-    circom_create::<WorkProver, 2>(PROOF_OPTIONS, "sum", LoggingLevel::Default)
+    presets::registry().create("sum", LoggingLevel::Default)
 }
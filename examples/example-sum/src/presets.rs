@@ -0,0 +1,13 @@
+use winter_circom_prover::{CircuitRegistry, WinterCircomProofOptions};
+
+use crate::{air::PROOF_OPTIONS, prover::WorkProver};
+
+/// Proof options of the `sum` circuit.
+pub(crate) fn sum() -> WinterCircomProofOptions<2> {
+    PROOF_OPTIONS
+}
+
+/// Returns the registry of the circuits of this example, with their presets.
+pub(crate) fn registry() -> CircuitRegistry {
+    CircuitRegistry::new().register_preset::<WorkProver, 2>("sum", sum())
+}
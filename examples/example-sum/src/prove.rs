@@ -2,10 +2,10 @@ use winter_circom_prover::{
     circom_prove,
     utils::{LoggingLevel, WinterCircomError},
     winterfell::math::{fields::f256::BaseElement, FieldElement},
+    CheckedProver, CircuitParameters,
 };
 
 mod air;
-pub(crate) use air::PROOF_OPTIONS;
 
 mod prover;
 pub use prover::WorkProver;
@@ -14,10 +14,17 @@ fn main() -> Result<(), WinterCircomError> {
     // parameters
     let start = BaseElement::ONE;
 
-    // build proof
-    let options = PROOF_OPTIONS.get_proof_options();
-    let prover = WorkProver::new(options.clone());
-    let trace = prover.build_trace(start, PROOF_OPTIONS.trace_length);
+    // build proof with the parameters the circuit was compiled for
+    let parameters = CircuitParameters::of_circuit("sum")?;
+    let prover = WorkProver::new(parameters.proof_options());
+    let trace = prover.build_trace(start, parameters.trace_length);
 
-    circom_prove(prover, trace, "sum", LoggingLevel::Default)
+    // check the public inputs against the trace before proving
+    circom_prove(
+        CheckedProver::new(prover),
+        trace,
+        "sum",
+        LoggingLevel::Default,
+    )?;
+    Ok(())
 }
@@ -17,10 +17,24 @@ const RollupDb = require("@hermeznetwork/commonjs").RollupDB;
 // Define name-files
 const circuitName = "circuit";
 
+// Directory of the artifacts of a circuit, see CircomWorkspace in
+// winter-circom-prover.
+function circuitPath(CircuitName) {
+    const root = global.process.env.WINTER_CIRCOM_OUTPUT_DIR || path.join(__dirname, `../../target/circom`);
+    return path.resolve(root, CircuitName);
+}
+
+// Directory of the scratch artifacts (input.json, witness.wtns) of a circuit,
+// see ScratchSpace in winter-circom-prover.
+function scratchPath(CircuitName) {
+    const root = global.process.env.WINTER_CIRCOM_SCRATCH_DIR;
+    return root ? path.join(root, CircuitName) : circuitPath(CircuitName);
+}
+
 
 async function compileCircuit(CircuitName) {
     const startTime = performance.now();
-    const pathName = path.join(__dirname, `../../target/circom/${CircuitName}`);
+    const pathName = circuitPath(CircuitName);
     const cirName = path.join(pathName, `verifier.circom`);
 
     let flagsCircom;
@@ -36,10 +50,10 @@ async function compileCircuit(CircuitName) {
 
 
 async function computeWitness(CircuitName){
-    const pathName = path.join(__dirname, `../../target/circom/${CircuitName}`);
+    const pathName = circuitPath(CircuitName);
     // generate empty witness as an example
-    const witnessName = path.join(pathName, `witness.wtns`);
-    const inputName = path.join(pathName, `input.json`);
+    const witnessName = path.join(scratchPath(CircuitName), `witness.wtns`);
+    const inputName = path.join(scratchPath(CircuitName), `input.json`);
     const wasmName = path.join(pathName, `verifier_js/verifier.wasm`);
     
 
@@ -55,12 +69,12 @@ async function computeWitness(CircuitName){
 }
 
 async function computeZkey(CircuitName){
-    const pathName = path.join(__dirname, `../../target/circom/${CircuitName}`);
+    const pathName = circuitPath(CircuitName);
     console.log(pathName);
     const r1csName = `${pathName}/verifier.r1cs`;
     const zkey0Name = `${pathName}/verifier_0.zkey`;
     const zkey1Name = `${pathName}/verifier_1.zkey`;
-    const ptauName = path.join(__dirname, `../../final_21.ptau`);
+    const ptauName = global.process.env.WINTER_CIRCOM_PTAU || path.join(__dirname, `../../final_21.ptau`);
 
     if (!fs.existsSync(r1csName)) {
         console.error(`Constraint file ${r1csName} doesnt exist`);
@@ -83,7 +97,7 @@ async function computeZkey(CircuitName){
 }
 
 async function generateVerifier(CircuitName){
-    const pathName = path.join(__dirname, `../../target/circom/${CircuitName}`);
+    const pathName = circuitPath(CircuitName);
     const zkeyName = `${pathName}/verifier_1.zkey`;
     const vkeyName = `${pathName}/circuitVerifier.vkey`;
     const solName = `${pathName}/circuitVerifier.sol`;
@@ -106,11 +120,11 @@ async function generateVerifier(CircuitName){
 }
 
 async function generateProof(CircuitName){
-    const pathName = path.join(__dirname, `../../target/circom/${CircuitName}`);
+    const pathName = circuitPath(CircuitName);
     const zkeyName = `${pathName}/verifier_1.zkey`;
     const proofName = `${pathName}/proof.json`;
     const publicName = `${pathName}/public.json`;
-    const witnessName = `${pathName}/witness.wtns`;
+    const witnessName = path.join(scratchPath(CircuitName), `witness.wtns`);
     if (!fs.existsSync(zkeyName)) {
         console.log(`ZKey file ${zkeyName} doesnt exist`);
         return;
@@ -128,7 +142,7 @@ async function generateProof(CircuitName){
 
 
 async function verifyProof(CircuitName){
-    const pathName = path.join(__dirname, `../../target/circom/${CircuitName}`);
+    const pathName = circuitPath(CircuitName);
     const vkeyName = `${pathName}/circuitVerifier.vkey`;
     const proofName = `${pathName}/proof.json`;
     const publicName = `${pathName}/public.json`;